@@ -0,0 +1,193 @@
+//! Reading and writing the asciicast v2 format used by asciinema.
+//!
+//! An asciicast v2 file is newline-delimited JSON: a header object on the
+//! first line, then one `[time, code, data]` array per event. Because every
+//! line stands on its own, a recording can be appended to as it happens and a
+//! crash loses at most the line that was being written.
+//!
+//! See <https://docs.asciinema.org/manual/asciicast/v2/>.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::{Event, EventType, Recording};
+
+/// The only asciicast version this module reads or writes.
+pub const VERSION: u32 = 2;
+
+/// The header line of an asciicast v2 file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl From<&Recording> for Header {
+    /// The header for saving a finished recording, duration included.
+    fn from(recording: &Recording) -> Self {
+        Self {
+            version: VERSION,
+            width: recording.width,
+            height: recording.height,
+            timestamp: Duration::try_from_secs_f64(recording.timestamp)
+                .ok()
+                .map(|timestamp| timestamp.as_secs())
+                .filter(|&secs| secs > 0),
+            duration: Some(recording.duration),
+            command: Some(recording.command.clone()).filter(|command| !command.is_empty()),
+            title: Some(recording.title.clone()).filter(|title| !title.is_empty()),
+            env: recording.env.clone(),
+        }
+    }
+}
+
+/// Appends a recording to a writer one event at a time.
+///
+/// The header is written and flushed on creation. Events are only flushed
+/// when [`AsciicastWriter::flush`] is called, so the file on disk is a valid
+/// recording up to the last flush.
+pub struct AsciicastWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> AsciicastWriter<W> {
+    /// Write the header line and return a writer ready for events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header cannot be serialized, written, or flushed.
+    pub fn new(mut inner: W, header: &Header) -> Result<Self> {
+        serde_json::to_writer(&mut inner, header).context("Failed to write asciicast header")?;
+        inner.write_all(b"\n")?;
+        inner.flush()?;
+        Ok(Self { inner })
+    }
+
+    /// Append one event as a `[time, code, data]` line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be serialized or written.
+    pub fn write_event(&mut self, event: &Event) -> Result<()> {
+        serde_json::to_writer(
+            &mut self.inner,
+            &(event.time, &event.event_type, &event.data),
+        )
+        .context("Failed to write asciicast event")?;
+        self.inner.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flush the events written so far to the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer cannot be flushed.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Give back the underlying writer, e.g. to finish a gzip stream.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Returns true when `text` looks like asciicast v2 rather than beta's JSON.
+///
+/// Beta's own format is a single object that carries an `events` array, while
+/// an asciicast file starts with a header object on a line of its own.
+pub fn is_asciicast(text: &str) -> bool {
+    let Some(first_line) = text.lines().find(|line| !line.trim().is_empty()) else {
+        return false;
+    };
+    matches!(
+        serde_json::from_str::<serde_json::Value>(first_line),
+        Ok(serde_json::Value::Object(header)) if !header.contains_key("events")
+    )
+}
+
+/// Parse asciicast v2 text into a recording.
+///
+/// Output (`o`), input (`i`), resize (`r`) and marker (`m`) events are kept.
+/// Any other event code is skipped, as the format asks readers to do. A
+/// final line that does not parse is dropped, since that is what a recording
+/// cut off by a crash looks like; a bad line anywhere else is an error.
+///
+/// # Errors
+///
+/// Returns an error if the header is missing or malformed, if its version is
+/// not 2, or if any event line other than the last does not parse.
+pub fn parse(text: &str) -> Result<Recording> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+
+    let (_, header_line) = lines.next().context("Recording file is empty")?;
+    let header: Header =
+        serde_json::from_str(header_line).context("Failed to parse asciicast header")?;
+    if header.version != VERSION {
+        anyhow::bail!(
+            "Unsupported asciicast version {} (expected {VERSION})",
+            header.version
+        );
+    }
+
+    let mut events = Vec::new();
+    while let Some((index, line)) = lines.next() {
+        let (time, code, data): (f64, String, String) = match serde_json::from_str(line) {
+            Ok(event) => event,
+            Err(_) if lines.peek().is_none() => break,
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to parse asciicast event on line {}", index + 1)
+                })
+            }
+        };
+        let event_type = match code.as_str() {
+            "o" => EventType::Output,
+            "i" => EventType::Input,
+            "r" => EventType::Resize,
+            "m" => EventType::Marker,
+            _ => continue,
+        };
+        events.push(Event {
+            time,
+            event_type,
+            data,
+        });
+    }
+
+    let duration = header
+        .duration
+        .unwrap_or_else(|| events.last().map_or(0.0, |event| event.time));
+
+    Ok(Recording {
+        version: header.version,
+        width: header.width,
+        height: header.height,
+        timestamp: header.timestamp.unwrap_or_default() as f64,
+        duration,
+        command: header.command.unwrap_or_default(),
+        title: header.title.unwrap_or_default(),
+        env: header.env,
+        events,
+    })
+}
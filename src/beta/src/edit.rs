@@ -0,0 +1,353 @@
+//! Editing recordings: trimming, cutting spans, capping idle time, splicing
+//! and changing speed.
+//!
+//! Every edit maps the old timeline onto a new one and recomputes `duration`.
+//! Output inside a span that is removed is not thrown away: it is replayed
+//! instantly where the span was, so the screen after an edit looks exactly as
+//! it did at that point in the original.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{EditOperation, Event, EventType, Recording};
+
+/// A span of a recording played at a different speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedRange {
+    pub start: f64,
+    pub end: f64,
+    /// 2.0 plays the span twice as fast, 0.5 at half speed.
+    pub factor: f64,
+}
+
+impl FromStr for SpeedRange {
+    type Err = String;
+
+    /// Parse `START:END:FACTOR`, e.g. `10:25:4` plays seconds 10 to 25 four
+    /// times as fast.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let [start, end, factor] = parts[..] else {
+            return Err(format!("expected START:END:FACTOR, got {s:?}"));
+        };
+        let number = |text: &str| {
+            text.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("not a number: {text:?}"))
+        };
+        let range = Self {
+            start: number(start)?,
+            end: number(end)?,
+            factor: number(factor)?,
+        };
+        if !(range.start >= 0.0 && range.end > range.start && range.end.is_finite()) {
+            return Err(format!("range {s:?} needs 0 <= START < END"));
+        }
+        if !(range.factor > 0.0 && range.factor.is_finite()) {
+            return Err(format!("speed factor in {s:?} must be positive"));
+        }
+        Ok(range)
+    }
+}
+
+pub fn handle_edit(operation: EditOperation, force: bool) -> Result<()> {
+    let (edited, output) = match operation {
+        EditOperation::Trim {
+            input,
+            output,
+            start,
+            end,
+            start_marker,
+            end_marker,
+        } => {
+            let recording = Recording::load(&input)?;
+            let start = match (start, start_marker) {
+                (Some(time), _) => time,
+                (None, Some(name)) => marker_time(&recording, &name, 0.0)?,
+                (None, None) => 0.0,
+            };
+            let end = match (end, end_marker) {
+                (Some(time), _) => time,
+                (None, Some(name)) => marker_time(&recording, &name, start)?,
+                (None, None) => f64::INFINITY,
+            };
+            (trim(&recording, start, end)?, output_path(output, &input))
+        }
+        EditOperation::Cut {
+            input,
+            output,
+            start,
+            end,
+        } => (
+            cut(&Recording::load(&input)?, start, end)?,
+            output_path(output, &input),
+        ),
+        EditOperation::CutIdle { input, output, max } => (
+            cap_idle(&Recording::load(&input)?, max)?,
+            output_path(output, &input),
+        ),
+        EditOperation::Splice { inputs, output } => {
+            let recordings = inputs
+                .iter()
+                .map(|input| Recording::load(input))
+                .collect::<Result<Vec<_>>>()?;
+            (splice(&recordings)?, output_path(output, &inputs[0]))
+        }
+        EditOperation::Speed {
+            input,
+            output,
+            ranges,
+        } => (
+            speed(&Recording::load(&input)?, &ranges)?,
+            output_path(output, &input),
+        ),
+    };
+
+    edited.save(&output, force)?;
+    println!("Edited recording saved to: {}", output.display());
+    println!("Duration: {:.1}s", edited.duration);
+    Ok(())
+}
+
+/// Where an edit is saved when no output is given: next to the input, e.g.
+/// `demo.cast` becomes `demo.edited.cast`.
+fn output_path(output: Option<PathBuf>, input: &Path) -> PathBuf {
+    output.unwrap_or_else(|| input.with_extension("edited.cast"))
+}
+
+/// The time of the first marker called `name` at or after `after`.
+///
+/// # Errors
+///
+/// Returns an error if there is no such marker.
+pub fn marker_time(recording: &Recording, name: &str, after: f64) -> Result<f64> {
+    recording
+        .events
+        .iter()
+        .find(|event| {
+            matches!(event.event_type, EventType::Marker)
+                && event.data == name
+                && event.time >= after
+        })
+        .map(|event| event.time)
+        .with_context(|| format!("No marker named {name:?} at or after {after:.1}s"))
+}
+
+/// Keep only `start..=end`, moved to begin at zero.
+///
+/// An `end` past the end of the recording keeps everything after `start`.
+///
+/// # Errors
+///
+/// Returns an error if the span is empty or starts after the recording ends.
+pub fn trim(recording: &Recording, start: f64, end: f64) -> Result<Recording> {
+    check_span(recording, start, end)?;
+    let mut events = collapse(
+        recording.events.iter().filter(|event| event.time < start),
+        0.0,
+    );
+    events.extend(
+        recording
+            .events
+            .iter()
+            .filter(|event| event.time >= start && event.time <= end)
+            .map(|event| shifted(event, -start)),
+    );
+    Ok(Recording {
+        events,
+        duration: end.min(recording.duration) - start,
+        ..recording.clone()
+    })
+}
+
+/// Remove `start..end` and close the gap.
+///
+/// # Errors
+///
+/// Returns an error if the span is empty or starts after the recording ends.
+pub fn cut(recording: &Recording, start: f64, end: f64) -> Result<Recording> {
+    check_span(recording, start, end)?;
+    let end = end.min(recording.duration);
+    let removed = end - start;
+    let mut events: Vec<Event> = recording
+        .events
+        .iter()
+        .filter(|event| event.time < start)
+        .cloned()
+        .collect();
+    events.extend(collapse(
+        recording
+            .events
+            .iter()
+            .filter(|event| event.time >= start && event.time < end),
+        start,
+    ));
+    events.extend(
+        recording
+            .events
+            .iter()
+            .filter(|event| event.time >= end)
+            .map(|event| shifted(event, -removed)),
+    );
+    Ok(Recording {
+        events,
+        duration: recording.duration - removed,
+        ..recording.clone()
+    })
+}
+
+/// Shorten every pause longer than `max` seconds to `max`, including the
+/// pause before the first event and after the last.
+///
+/// # Errors
+///
+/// Returns an error if `max` is not a positive number of seconds.
+pub fn cap_idle(recording: &Recording, max: f64) -> Result<Recording> {
+    if !(max > 0.0 && max.is_finite()) {
+        anyhow::bail!("Idle limit must be a positive number of seconds, got {max}");
+    }
+    let mut previous = 0.0;
+    let mut now = 0.0;
+    let mut events = Vec::with_capacity(recording.events.len());
+    for event in &recording.events {
+        now += (event.time - previous).clamp(0.0, max);
+        previous = event.time;
+        events.push(Event {
+            time: now,
+            ..event.clone()
+        });
+    }
+    Ok(Recording {
+        events,
+        duration: now + (recording.duration - previous).clamp(0.0, max),
+        ..recording.clone()
+    })
+}
+
+/// Play recordings one after another.
+///
+/// The result keeps the header of the first recording. Where a recording
+/// starts at a different size than the one before it ended at, a resize is
+/// inserted at the seam.
+///
+/// # Errors
+///
+/// Returns an error if there are no recordings.
+pub fn splice(recordings: &[Recording]) -> Result<Recording> {
+    let (first, rest) = recordings.split_first().context("Nothing to splice")?;
+    let mut spliced = first.clone();
+    for next in rest {
+        let offset = spliced
+            .events
+            .last()
+            .map_or(spliced.duration, |event| spliced.duration.max(event.time));
+        let size = spliced
+            .events
+            .iter()
+            .rev()
+            .find_map(Event::size)
+            .unwrap_or((spliced.width, spliced.height));
+        if size != (next.width, next.height) {
+            spliced.events.push(Event {
+                time: offset,
+                event_type: EventType::Resize,
+                data: format!("{}x{}", next.width, next.height),
+            });
+        }
+        spliced
+            .events
+            .extend(next.events.iter().map(|event| shifted(event, offset)));
+        spliced.duration = offset + next.duration;
+    }
+    Ok(spliced)
+}
+
+/// Play each range at its own speed.
+///
+/// Ranges are in the original recording's time and may not overlap.
+///
+/// # Errors
+///
+/// Returns an error if two ranges overlap.
+pub fn speed(recording: &Recording, ranges: &[SpeedRange]) -> Result<Recording> {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by(|a, b| a.start.total_cmp(&b.start));
+    if let Some(pair) = ranges.windows(2).find(|pair| pair[1].start < pair[0].end) {
+        anyhow::bail!(
+            "Speed ranges {}-{}s and {}-{}s overlap",
+            pair[0].start,
+            pair[0].end,
+            pair[1].start,
+            pair[1].end
+        );
+    }
+    // Time saved (or added, when slowing down) by every range before `time`
+    let map = |time: f64| {
+        let mut saved = 0.0;
+        for range in ranges.iter().take_while(|range| range.start < time) {
+            let covered = time.min(range.end) - range.start;
+            saved += covered - covered / range.factor;
+        }
+        time - saved
+    };
+    Ok(Recording {
+        events: recording
+            .events
+            .iter()
+            .map(|event| Event {
+                time: map(event.time),
+                ..event.clone()
+            })
+            .collect(),
+        duration: map(recording.duration),
+        ..recording.clone()
+    })
+}
+
+fn check_span(recording: &Recording, start: f64, end: f64) -> Result<()> {
+    if !(start >= 0.0 && end > start) {
+        anyhow::bail!("Invalid span {start}s to {end}s: the end must come after the start");
+    }
+    if start >= recording.duration {
+        anyhow::bail!(
+            "Start {start}s is past the end of the recording ({:.1}s)",
+            recording.duration
+        );
+    }
+    Ok(())
+}
+
+/// Replay the output and resizes of a removed span instantly at `time`.
+///
+/// Input and markers in the span are dropped, and adjacent output is merged
+/// into one event.
+fn collapse<'a>(events: impl Iterator<Item = &'a Event>, time: f64) -> Vec<Event> {
+    let mut kept: Vec<Event> = Vec::new();
+    for event in events {
+        match event.event_type {
+            EventType::Output => match kept.last_mut() {
+                Some(last) if matches!(last.event_type, EventType::Output) => {
+                    last.data.push_str(&event.data);
+                }
+                _ => kept.push(Event {
+                    time,
+                    ..event.clone()
+                }),
+            },
+            EventType::Resize => kept.push(Event {
+                time,
+                ..event.clone()
+            }),
+            EventType::Input | EventType::Marker => {}
+        }
+    }
+    kept
+}
+
+fn shifted(event: &Event, by: f64) -> Event {
+    Event {
+        time: event.time + by,
+        ..event.clone()
+    }
+}
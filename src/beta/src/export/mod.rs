@@ -1,6 +1,8 @@
 use crate::ExportFormat;
 use anyhow::Result;
 
+pub mod png;
+pub mod svg;
 pub mod terminal_renderer;
 pub mod video;
 pub mod web;
@@ -21,5 +23,17 @@ pub async fn handle_export(format: ExportFormat) -> Result<()> {
             theme,
             optimize_web,
         } => video::export_video(input, output, fps, resolution, theme, optimize_web).await,
+        ExportFormat::Svg {
+            input,
+            output,
+            theme,
+        } => svg::export_svg(input, output, theme).await,
+        ExportFormat::Png {
+            input,
+            output,
+            at,
+            marker,
+            theme,
+        } => png::export_png(input, output, at, marker, theme).await,
     }
 }
@@ -0,0 +1,54 @@
+use super::terminal_renderer::{TerminalState, TerminalTheme};
+use super::video::{frame_size, render_terminal_to_image, FontManager};
+use crate::{edit, Recording};
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Render the screen at one moment of a recording to a PNG image.
+///
+/// The moment is `at` seconds, or the first marker called `marker`, or the
+/// end of the recording when neither is given.
+pub async fn export_png(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    at: Option<f64>,
+    marker: Option<String>,
+    theme: String,
+) -> Result<()> {
+    let recording = Recording::load(&input)?;
+
+    let output_path = output.unwrap_or_else(|| {
+        let mut path = input.clone();
+        path.set_extension("png");
+        path
+    });
+
+    let time = match (at, marker) {
+        (Some(time), _) => time,
+        (None, Some(name)) => edit::marker_time(&recording, &name, 0.0)?,
+        (None, None) => recording.duration,
+    };
+
+    let mut terminal_state = TerminalState::at(&recording, time, TerminalTheme::from_name(&theme))?;
+    terminal_state.protect_status_bar_area();
+
+    let (cols, rows) = (terminal_state.get_width(), terminal_state.get_height());
+    let (width, height) = frame_size(
+        u16::try_from(cols).unwrap_or(u16::MAX),
+        u16::try_from(rows).unwrap_or(u16::MAX),
+    );
+    let mut font_manager = FontManager::new()?;
+    let image = render_terminal_to_image(&terminal_state, width, height, &mut font_manager)?;
+    image
+        .save(&output_path)
+        .with_context(|| format!("Failed to save PNG: {}", output_path.display()))?;
+
+    println!(
+        "Snapshot at {:.1}s saved to: {}",
+        time,
+        output_path.display()
+    );
+    println!("Resolution: {}x{}", width, height);
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use super::terminal_renderer::{Cell, TerminalState, TerminalTheme};
+use crate::Recording;
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// Width of one character cell, in SVG pixels.
+const CELL_WIDTH: usize = 9;
+/// Height of one character cell, in SVG pixels.
+const CELL_HEIGHT: usize = 18;
+/// Distance from the top of a cell to the text baseline.
+const BASELINE: usize = 14;
+const FONT_SIZE: usize = 15;
+const FONT_FAMILY: &str =
+    "'JetBrains Mono','SF Mono',Menlo,Monaco,Consolas,'DejaVu Sans Mono',monospace";
+/// Space between the terminal and the edge of the image.
+const PADDING: usize = 16;
+/// Events closer together than this share a frame, so fast output does not
+/// turn into thousands of near-identical screens.
+const FRAME_INTERVAL: f64 = 1.0 / 30.0;
+
+pub async fn export_svg(input: PathBuf, output: Option<PathBuf>, theme: String) -> Result<()> {
+    let recording = Recording::load(&input)?;
+
+    let output_path = output.unwrap_or_else(|| {
+        let mut path = input.clone();
+        path.set_extension("svg");
+        path
+    });
+
+    let svg = render_svg(&recording, TerminalTheme::from_name(&theme))?;
+    std::fs::write(&output_path, &svg)
+        .with_context(|| format!("Failed to write SVG: {}", output_path.display()))?;
+
+    println!("SVG export saved to: {}", output_path.display());
+    println!("Duration: {:.1}s", recording.duration);
+    println!("Size: {} KB", svg.len() / 1024);
+
+    Ok(())
+}
+
+/// Render a recording as a self-contained animated SVG.
+///
+/// Every distinct screen is drawn once, side by side on a strip, and a single
+/// CSS animation slides the strip so each screen shows from the time it
+/// appeared in the recording. Nothing outside the file is needed, so the
+/// result can be embedded in a README as an image.
+pub fn render_svg(recording: &Recording, theme: TerminalTheme) -> Result<String> {
+    let background = theme.background;
+    let foreground = theme.foreground;
+    let frames = frames(recording, theme)?;
+
+    let (cols, rows) = recording.max_size();
+    let screen_width = usize::from(cols) * CELL_WIDTH;
+    let screen_height = usize::from(rows) * CELL_HEIGHT;
+    let width = screen_width + 2 * PADDING;
+    let height = screen_height + 2 * PADDING;
+    let duration = frames
+        .last()
+        .map_or(0.0, |(time, _)| recording.duration.max(*time));
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    let _ = write!(
+        svg,
+        "<style>.t{{font-family:{FONT_FAMILY};font-size:{FONT_SIZE}px;white-space:pre}}.b{{font-weight:bold}}.u{{text-decoration:underline}}"
+    );
+    if frames.len() > 1 && duration > 0.0 {
+        let _ = write!(
+            svg,
+            ".film{{animation:play {duration:.3}s steps(1,end) infinite}}@keyframes play{{"
+        );
+        for (index, (time, _)) in frames.iter().enumerate() {
+            let _ = write!(
+                svg,
+                "{:.3}%{{transform:translateX(-{}px)}}",
+                time / duration * 100.0,
+                index * screen_width
+            );
+        }
+        let _ = write!(
+            svg,
+            "100%{{transform:translateX(-{}px)}}}}",
+            (frames.len() - 1) * screen_width
+        );
+    }
+    svg.push_str("</style>");
+    let _ = write!(
+        svg,
+        r#"<rect width="{width}" height="{height}" rx="6" fill="{}"/>"#,
+        hex(background)
+    );
+    // The nested <svg> clips the strip to one screen
+    let _ = write!(
+        svg,
+        r#"<svg x="{PADDING}" y="{PADDING}" width="{screen_width}" height="{screen_height}"><g class="t film" fill="{}">"#,
+        hex(foreground)
+    );
+    for (index, (_, screen)) in frames.iter().enumerate() {
+        let _ = write!(
+            svg,
+            r#"<g transform="translate({},0)">{screen}</g>"#,
+            index * screen_width
+        );
+    }
+    svg.push_str("</g></svg></svg>\n");
+    Ok(svg)
+}
+
+/// Each distinct screen of the recording, with the time it first appears.
+fn frames(recording: &Recording, theme: TerminalTheme) -> Result<Vec<(f64, String)>> {
+    let mut state = TerminalState::new(
+        usize::from(recording.width),
+        usize::from(recording.height),
+        theme,
+    );
+    let events = &recording.events;
+    let mut frames = Vec::new();
+    if events.first().is_none_or(|event| event.time > 0.0) {
+        frames.push((0.0, render_screen(&state)));
+    }
+
+    let mut index = 0;
+    while index < events.len() {
+        let time = events[index].time.max(0.0);
+        while index < events.len() && events[index].time < time + FRAME_INTERVAL {
+            state.apply_event(&events[index])?;
+            index += 1;
+        }
+        let screen = render_screen(&state);
+        if frames.last().is_none_or(|(_, last)| *last != screen) {
+            frames.push((time, screen));
+        }
+    }
+    Ok(frames)
+}
+
+/// The SVG elements for one screen: cell backgrounds, then text, then the cursor.
+fn render_screen(state: &TerminalState) -> String {
+    let theme = state.get_theme();
+    let mut backgrounds = String::new();
+    let mut text = String::new();
+
+    for (y, row) in state.get_grid().iter().enumerate() {
+        let top = y * CELL_HEIGHT;
+        let colors: Vec<_> = row
+            .iter()
+            .map(|cell| state.resolve_cell_colors(cell))
+            .collect();
+
+        // Runs of cells sharing a background colour become one rectangle
+        let mut x = 0;
+        while x < row.len() {
+            let start = x;
+            let bg = colors[x].1;
+            while x < row.len() && colors[x].1 == bg {
+                x += 1;
+            }
+            if bg != theme.background {
+                let _ = write!(
+                    backgrounds,
+                    r#"<rect x="{}" y="{top}" width="{}" height="{CELL_HEIGHT}" fill="{}"/>"#,
+                    start * CELL_WIDTH,
+                    (x - start) * CELL_WIDTH,
+                    hex(bg)
+                );
+            }
+        }
+
+        // Runs of cells sharing a style become one <text>
+        let style = |x: usize| (colors[x].0, row[x].bold, row[x].underline);
+        let mut x = 0;
+        while x < row.len() {
+            let start = x;
+            let run_style = style(x);
+            while x < row.len() && style(x) == run_style {
+                x += 1;
+            }
+            write_text_run(&mut text, &row[start..x], start, top, run_style, theme);
+        }
+    }
+
+    if state.is_cursor_visible() {
+        let (cursor_x, cursor_y) = state.get_cursor_position();
+        if cursor_y < state.get_height() && cursor_x < state.get_width() {
+            let _ = write!(
+                text,
+                r#"<rect x="{}" y="{}" width="{CELL_WIDTH}" height="{CELL_HEIGHT}" fill-opacity="0.6"/>"#,
+                cursor_x * CELL_WIDTH,
+                cursor_y * CELL_HEIGHT
+            );
+        }
+    }
+
+    backgrounds + &text
+}
+
+/// Write one run of same-styled cells, leaving out blank cells at either end.
+fn write_text_run(
+    out: &mut String,
+    cells: &[Cell],
+    column: usize,
+    top: usize,
+    (fg, bold, underline): ((u8, u8, u8), bool, bool),
+    theme: &TerminalTheme,
+) {
+    let is_blank = |cell: &Cell| cell.ch == ' ' || cell.ch.is_control();
+    let Some(first) = cells.iter().position(|cell| !is_blank(cell)) else {
+        return;
+    };
+    let last = cells
+        .iter()
+        .rposition(|cell| !is_blank(cell))
+        .unwrap_or(first);
+    let cells = &cells[first..=last];
+
+    let _ = write!(
+        out,
+        r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs""#,
+        (column + first) * CELL_WIDTH,
+        top + BASELINE,
+        cells.len() * CELL_WIDTH
+    );
+    match (bold, underline) {
+        (true, true) => out.push_str(r#" class="b u""#),
+        (true, false) => out.push_str(r#" class="b""#),
+        (false, true) => out.push_str(r#" class="u""#),
+        (false, false) => {}
+    }
+    if fg != theme.foreground {
+        let _ = write!(out, r#" fill="{}""#, hex(fg));
+    }
+    out.push('>');
+    for cell in cells {
+        match cell.ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            ch if ch.is_control() => out.push(' '),
+            ch => out.push(ch),
+        }
+    }
+    out.push_str("</text>");
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
@@ -1,4 +1,5 @@
-use anyhow::Result;
+use crate::{Event, EventType, Recording};
+use anyhow::{Context, Result};
 use vte::{Parser, Perform};
 
 /// Narrows an SGR colour offset to the palette index it names.
@@ -297,6 +298,8 @@ pub struct TerminalState {
     // Scrolling region
     scroll_top: usize,
     scroll_bottom: usize,
+    // How many times the output has set the scrolling region
+    scroll_region_sets: usize,
     // Character set state
     use_acs: bool,
     // Cursor visibility
@@ -351,6 +354,7 @@ impl TerminalState {
             saved_cursor_y: 0,
             scroll_top: 0,
             scroll_bottom: height - 1,
+            scroll_region_sets: 0,
             use_acs: false,
             cursor_visible: true,
             dynamic_palette,
@@ -374,6 +378,71 @@ impl TerminalState {
         Ok(())
     }
 
+    /// Replay a recording up to and including `time`, giving the screen as it
+    /// looked at that moment.
+    pub fn at(recording: &Recording, time: f64, theme: TerminalTheme) -> Result<Self> {
+        let mut state = Self::new(
+            usize::from(recording.width),
+            usize::from(recording.height),
+            theme,
+        );
+        for event in recording
+            .events
+            .iter()
+            .take_while(|event| event.time <= time)
+        {
+            state.apply_event(event)?;
+        }
+        Ok(state)
+    }
+
+    /// Apply one recorded event: output is interpreted and a resize changes
+    /// the grid. Input and markers do not touch the screen.
+    pub fn apply_event(&mut self, event: &Event) -> Result<()> {
+        match event.event_type {
+            EventType::Output => self
+                .process_output(&event.data)
+                .context("Failed to process terminal output")?,
+            EventType::Resize => {
+                if let Some((cols, rows)) = event.size() {
+                    self.resize(usize::from(cols), usize::from(rows));
+                }
+            }
+            EventType::Input | EventType::Marker => {}
+        }
+        Ok(())
+    }
+
+    /// Change the terminal size, as a `SIGWINCH` in the recorded session did.
+    ///
+    /// Existing cells keep their position; new cells are blank and cells past
+    /// the new edges are dropped. The cursor is clamped onto the grid and the
+    /// scrolling region is reset to the full height, as xterm does.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.max(1);
+        let height = height.max(1);
+        let blank = Cell {
+            ch: ' ',
+            fg_color: self.theme.foreground,
+            bg_color: self.theme.background,
+            ..Default::default()
+        };
+
+        self.grid.resize_with(height, Vec::new);
+        for row in &mut self.grid {
+            row.resize(width, blank.clone());
+        }
+
+        self.width = width;
+        self.height = height;
+        self.cursor_x = self.cursor_x.min(width - 1);
+        self.cursor_y = self.cursor_y.min(height - 1);
+        self.saved_cursor_x = self.saved_cursor_x.min(width - 1);
+        self.saved_cursor_y = self.saved_cursor_y.min(height - 1);
+        self.scroll_top = 0;
+        self.scroll_bottom = height - 1;
+    }
+
     pub fn get_grid(&self) -> &Vec<Vec<Cell>> {
         &self.grid
     }
@@ -382,16 +451,25 @@ impl TerminalState {
         &self.theme
     }
 
-    #[cfg(test)]
     pub fn get_width(&self) -> usize {
         self.width
     }
 
-    #[cfg(test)]
     pub fn get_height(&self) -> usize {
         self.height
     }
 
+    /// The first and last rows of the scrolling region, zero-based.
+    pub fn get_scroll_region(&self) -> (usize, usize) {
+        (self.scroll_top, self.scroll_bottom)
+    }
+
+    /// How many times the output has set the scrolling region (DECSTBM),
+    /// including to the region it already had.
+    pub fn get_scroll_region_sets(&self) -> usize {
+        self.scroll_region_sets
+    }
+
     pub fn get_cursor_position(&self) -> (usize, usize) {
         (self.cursor_x, self.cursor_y)
     }
@@ -1222,17 +1300,22 @@ impl Perform for TerminalState {
                     .iter()
                     .nth(0)
                     .and_then(|p| p.first().copied())
+                    .filter(|&p| p != 0)
                     .unwrap_or(1) as usize;
+                // A missing or zero parameter means the default, so a bare
+                // `CSI r` resets the region to the whole screen
                 let bottom = params
                     .iter()
                     .nth(1)
                     .and_then(|p| p.first().copied())
+                    .filter(|&p| p != 0)
                     .map_or(self.height, usize::from);
 
                 // Validate and set scrolling region
                 if top > 0 && bottom <= self.height && top < bottom {
                     self.scroll_top = top - 1; // Convert to 0-based
                     self.scroll_bottom = bottom - 1;
+                    self.scroll_region_sets += 1;
 
                     // DECSTBM also moves cursor to home position
                     self.cursor_x = 0;
@@ -1,5 +1,5 @@
 use super::terminal_renderer::{TerminalState, TerminalTheme};
-use crate::{EventType, Recording};
+use crate::Recording;
 use ab_glyph::{Font, FontVec, PxScale};
 use anyhow::{Context, Result};
 use image::{ImageBuffer, Rgb, RgbImage};
@@ -24,13 +24,13 @@ fn is_light_color(color: (u8, u8, u8)) -> bool {
     luminance > 127.0
 }
 
-struct FontManager {
+pub(super) struct FontManager {
     fonts: Vec<FontVec>,
     glyph_cache: HashMap<char, usize>, // Character -> font index
 }
 
 impl FontManager {
-    fn new() -> Result<Self> {
+    pub(super) fn new() -> Result<Self> {
         let mut fonts = Vec::new();
         let font_paths = get_font_paths();
 
@@ -188,16 +188,22 @@ fn parse_resolution(resolution: &Option<String>, recording: &Recording) -> Resul
 
         Ok((width, height))
     } else {
-        const SCALE: u32 = 4; // 4x resolution for fine control
-        let char_width = 6 * SCALE - 1; // 23px - 0.25px tighter
-        let char_height = 13 * SCALE + 1; // 53px - 0.25px looser
-        let padding = 40 * SCALE; // 160px at 4x
+        // Size the frame for the largest terminal so no resize gets clipped
+        let (cols, rows) = recording.max_size();
+        Ok(frame_size(cols, rows))
+    }
+}
 
-        let width = (recording.width as u32 * char_width) + (padding * 2);
-        let height = (recording.height as u32 * char_height) + (padding * 2);
+/// Image size that fits a `cols` x `rows` terminal with room to spare.
+pub(super) fn frame_size(cols: u16, rows: u16) -> (u32, u32) {
+    const SCALE: u32 = 4; // 4x resolution for fine control
+    let char_width = 6 * SCALE - 1; // 23px - 0.25px tighter
+    let char_height = 13 * SCALE + 1; // 53px - 0.25px looser
+    let padding = 40 * SCALE; // 160px at 4x
 
-        Ok((width, height))
-    }
+    let width = (u32::from(cols) * char_width) + (padding * 2);
+    let height = (u32::from(rows) * char_height) + (padding * 2);
+    (width, height)
 }
 
 fn generate_and_encode_video(
@@ -244,12 +250,7 @@ fn generate_and_encode_video(
         while event_index < recording.events.len()
             && recording.events[event_index].time <= current_time
         {
-            let event = &recording.events[event_index];
-            if matches!(event.event_type, EventType::Output) {
-                terminal_state
-                    .process_output(&event.data)
-                    .context("Failed to process terminal output")?;
-            }
+            terminal_state.apply_event(&recording.events[event_index])?;
             event_index += 1;
         }
 
@@ -344,7 +345,7 @@ fn baseline_y(cell_top: u32, baseline_offset: f32) -> i32 {
     (cell_top as f32 + baseline_offset).round() as i32
 }
 
-fn render_terminal_to_image(
+pub(super) fn render_terminal_to_image(
     terminal_state: &TerminalState,
     width: u32,
     height: u32,
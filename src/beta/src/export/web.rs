@@ -280,13 +280,12 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
                 this.currentEventIndex = 0;
                 this.terminal.reset();
                 this.terminal.clear();
+                this.terminal.resize(this.recording.width, this.recording.height);
                 
                 // Replay all events up to the target time
                 for (let i = 0; i < this.events.length; i++) {
                     if (this.events[i].time <= time) {
-                        if (this.events[i].type === 'o') {
-                            this.terminal.write(this.events[i].data);
-                        }
+                        this.applyEvent(this.events[i]);
                         this.currentEventIndex = i + 1;
                     } else {
                         break;
@@ -301,6 +300,17 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
                 }
             }
             
+            applyEvent(event) {
+                if (event.type === 'o') {
+                    this.terminal.write(event.data);
+                } else if (event.type === 'r') {
+                    const [cols, rows] = event.data.split('x').map(Number);
+                    if (cols > 0 && rows > 0) {
+                        this.terminal.resize(cols, rows);
+                    }
+                }
+            }
+            
             getCurrentTime() {
                 if (this.isPlaying && this.startTime) {
                     return (Date.now() - this.startTime) / 1000 * this.playbackSpeed;
@@ -315,10 +325,7 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
                 
                 while (this.currentEventIndex < this.events.length && 
                        this.events[this.currentEventIndex].time <= currentTime) {
-                    const event = this.events[this.currentEventIndex];
-                    if (event.type === 'o') {
-                        this.terminal.write(event.data);
-                    }
+                    this.applyEvent(this.events[this.currentEventIndex]);
                     this.currentEventIndex++;
                 }
                 
@@ -1,9 +1,8 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Parser)]
 #[command(name = "beta")]
@@ -16,7 +15,11 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Record {
-        #[arg(short, long, help = "Output file for the recording")]
+        #[arg(
+            short,
+            long,
+            help = "Output file for the recording (asciicast v2, written as it happens)"
+        )]
         output: Option<PathBuf>,
 
         #[arg(short, long, help = "Command to record (default: shell)")]
@@ -24,6 +27,12 @@ enum Commands {
 
         #[arg(long, help = "Compress the recording with gzip")]
         compress: bool,
+
+        #[arg(
+            long,
+            help = "Mask input typed while terminal echo is off (e.g. passwords)"
+        )]
+        redact: bool,
     },
     Play {
         #[arg(help = "Recording file to play")]
@@ -39,6 +48,91 @@ enum Commands {
         #[command(subcommand)]
         format: ExportFormat,
     },
+    Edit {
+        #[arg(
+            long,
+            global = true,
+            help = "Replace the output file if it exists, even when it is the input"
+        )]
+        force: bool,
+
+        #[command(subcommand)]
+        operation: EditOperation,
+    },
+}
+
+/// Edits write a new asciicast v2 file. An existing file, the input included,
+/// is only replaced with `--force`.
+#[derive(Subcommand)]
+pub enum EditOperation {
+    /// Keep only part of a recording, by time or between markers
+    Trim {
+        #[arg(help = "Recording file to edit")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output file (default: INPUT.edited.cast)")]
+        output: Option<PathBuf>,
+
+        #[arg(long, conflicts_with = "start_marker", help = "Start time in seconds")]
+        start: Option<f64>,
+
+        #[arg(long, conflicts_with = "end_marker", help = "End time in seconds")]
+        end: Option<f64>,
+
+        #[arg(long, help = "Start at the first marker with this name")]
+        start_marker: Option<String>,
+
+        #[arg(long, help = "End at the first marker with this name after the start")]
+        end_marker: Option<String>,
+    },
+    /// Remove a span of time from a recording
+    Cut {
+        #[arg(help = "Recording file to edit")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output file (default: INPUT.edited.cast)")]
+        output: Option<PathBuf>,
+
+        #[arg(long, help = "Start of the span to remove, in seconds")]
+        start: f64,
+
+        #[arg(long, help = "End of the span to remove, in seconds")]
+        end: f64,
+    },
+    /// Shorten long pauses
+    CutIdle {
+        #[arg(help = "Recording file to edit")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output file (default: INPUT.edited.cast)")]
+        output: Option<PathBuf>,
+
+        #[arg(long, help = "Longest pause to keep, in seconds")]
+        max: f64,
+    },
+    /// Join recordings end to end
+    Splice {
+        #[arg(required = true, num_args = 2.., help = "Recording files, in order")]
+        inputs: Vec<PathBuf>,
+
+        #[arg(short, long, help = "Output file (default: FIRST_INPUT.edited.cast)")]
+        output: Option<PathBuf>,
+    },
+    /// Speed up or slow down parts of a recording
+    Speed {
+        #[arg(help = "Recording file to edit")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output file (default: INPUT.edited.cast)")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long = "range",
+            required = true,
+            help = "START:END:FACTOR in seconds, e.g. 10:25:4 plays 10s-25s four times as fast (repeatable)"
+        )]
+        ranges: Vec<edit::SpeedRange>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -79,6 +173,36 @@ pub enum ExportFormat {
         #[arg(long, help = "Optimize for web delivery")]
         optimize_web: bool,
     },
+    Svg {
+        #[arg(help = "Recording file to export")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output animated SVG file")]
+        output: Option<PathBuf>,
+
+        #[arg(long, default_value = "auto", help = "Theme for terminal rendering")]
+        theme: String,
+    },
+    Png {
+        #[arg(help = "Recording file to export")]
+        input: PathBuf,
+
+        #[arg(short, long, help = "Output PNG file")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            conflicts_with = "marker",
+            help = "Time in seconds to snapshot (default: end of recording)"
+        )]
+        at: Option<f64>,
+
+        #[arg(long, help = "Snapshot at the first marker with this name")]
+        marker: Option<String>,
+
+        #[arg(long, default_value = "auto", help = "Theme for terminal rendering")]
+        theme: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,24 +233,47 @@ pub enum EventType {
     Output,
     #[serde(rename = "i")]
     Input,
+    /// The terminal was resized; `data` is `COLSxROWS`.
+    #[serde(rename = "r")]
+    Resize,
+    /// A named point in the recording; `data` is the name.
+    #[serde(rename = "m")]
+    Marker,
 }
 
-pub fn get_timestamp() -> f64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs_f64()
+impl Event {
+    /// The new terminal size carried by a resize event, as `(cols, rows)`.
+    pub fn size(&self) -> Option<(u16, u16)> {
+        if !matches!(self.event_type, EventType::Resize) {
+            return None;
+        }
+        let (cols, rows) = self.data.split_once('x')?;
+        let size = (cols.trim().parse().ok()?, rows.trim().parse().ok()?);
+        (size.0 > 0 && size.1 > 0).then_some(size)
+    }
 }
 
 impl Recording {
+    /// The largest width and height the terminal reached over the recording.
+    pub fn max_size(&self) -> (u16, u16) {
+        self.events.iter().filter_map(Event::size).fold(
+            (self.width, self.height),
+            |(width, height), (cols, rows)| (width.max(cols), height.max(rows)),
+        )
+    }
+
     /// Load a recording from a file, auto-detecting gzip compression via magic bytes.
     ///
+    /// Both beta's own JSON format and asciicast v2 (as written by `beta record`
+    /// and asciinema) are accepted. A gzip stream that ends early, as it does
+    /// when the recorder was killed, still loads every complete asciicast line.
+    ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be opened, if its metadata cannot be
-    /// read, if it is empty, if seeking back to the start of the file fails, or
-    /// if its contents do not parse as a recording — either as gzip-compressed
-    /// JSON when the gzip magic bytes are present, or as plain JSON otherwise.
+    /// read, if it is empty, if seeking back to the start of the file fails, if
+    /// it is not valid UTF-8, or if its contents do not parse as a recording in
+    /// either format.
     pub fn load(path: &Path) -> Result<Self> {
         let mut file = std::fs::File::open(path)
             .with_context(|| format!("Failed to open recording file: {}", path.display()))?;
@@ -141,17 +288,91 @@ impl Recording {
 
         // Seek back to the beginning for the actual read
         file.seek(SeekFrom::Start(0))?;
-        let reader = std::io::BufReader::new(file);
+        let mut reader = std::io::BufReader::new(file);
+
+        let mut bytes = Vec::new();
+        let truncated = if is_gzip {
+            let mut decoder = flate2::read::GzDecoder::new(reader);
+            match decoder.read_to_end(&mut bytes) {
+                Ok(_) => false,
+                // read_to_end keeps whatever was decoded before the stream ended
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && !bytes.is_empty() => {
+                    true
+                }
+                Err(e) => return Err(e).context("Failed to decompress recording"),
+            }
+        } else {
+            reader.read_to_end(&mut bytes)?;
+            false
+        };
+        let text = String::from_utf8(bytes).context("Recording file is not valid UTF-8")?;
 
-        if is_gzip {
-            let decoder = flate2::read::GzDecoder::new(reader);
-            serde_json::from_reader(decoder).context("Failed to parse compressed recording")
+        if asciicast::is_asciicast(&text) {
+            asciicast::parse(&text)
+        } else if truncated {
+            anyhow::bail!("Compressed recording is truncated")
+        } else if is_gzip {
+            serde_json::from_str(&text).context("Failed to parse compressed recording")
         } else {
-            serde_json::from_reader(reader).context("Failed to parse recording")
+            serde_json::from_str(&text).context("Failed to parse recording")
+        }
+    }
+
+    /// Save the recording as asciicast v2.
+    ///
+    /// An existing file is only replaced when `replace` is set. The new
+    /// contents are then written beside it and renamed over it, so a failed
+    /// save never leaves it half written, and saving over the file the
+    /// recording was loaded from is safe.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists and `replace` is not set, or if it
+    /// cannot be created or written.
+    pub fn save(&self, path: &Path, replace: bool) -> Result<()> {
+        if !replace {
+            let file = match std::fs::File::create_new(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => anyhow::bail!(
+                    "{} already exists (use --force to replace it)",
+                    path.display()
+                ),
+                result => result.with_context(|| {
+                    format!("Failed to create recording file: {}", path.display())
+                })?,
+            };
+            return self.write_to(file);
+        }
+
+        let mut name = std::ffi::OsString::from(".");
+        name.push(path.file_name().unwrap_or_default());
+        name.push(".partial");
+        let partial = path.with_file_name(name);
+        let file = std::fs::File::create(&partial)
+            .with_context(|| format!("Failed to create recording file: {}", partial.display()))?;
+        let saved = self
+            .write_to(file)
+            .and_then(|()| std::fs::rename(&partial, path).map_err(Into::into));
+        if saved.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        saved.with_context(|| format!("Failed to replace recording file: {}", path.display()))
+    }
+
+    fn write_to(&self, file: std::fs::File) -> Result<()> {
+        let mut writer = asciicast::AsciicastWriter::new(
+            std::io::BufWriter::new(file),
+            &asciicast::Header::from(self),
+        )?;
+        for event in &self.events {
+            writer.write_event(event)?;
         }
+        writer.into_inner().flush()?;
+        Ok(())
     }
 }
 
+mod asciicast;
+mod edit;
 mod export;
 mod player;
 mod recorder;
@@ -168,12 +389,14 @@ async fn main() -> Result<()> {
             output,
             command,
             compress,
-        } => recorder::record(output, command, compress).await,
+            redact,
+        } => recorder::record(output, command, compress, redact).await,
         Commands::Play {
             file,
             speed,
             paused,
         } => player::play(file, speed, paused).await,
         Commands::Export { format } => export::handle_export(format).await,
+        Commands::Edit { force, operation } => edit::handle_edit(operation, force),
     }
 }
@@ -5,24 +5,139 @@ use crossterm::{
     execute,
     terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use std::fmt::Write as _;
 use std::io::{stdout, Write};
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::time::{interval, Instant};
 use tokio_stream::StreamExt;
 
-use crate::{EventType, Recording};
+use crate::export::terminal_renderer::{TerminalState, TerminalTheme};
+use crate::{Event, EventType, Recording};
+
+/// How far the arrow keys seek, in seconds.
+const SEEK_STEP: f64 = 5.0;
+
+/// How long a search message replaces the timeline.
+const NOTICE_TIME: Duration = Duration::from_secs(2);
+
+/// Narrowest timeline bar worth drawing; below this only the times are shown.
+const MIN_BAR_WIDTH: usize = 10;
+
+/// A cell's foreground, background, bold and underline.
+type CellStyle = ((u8, u8, u8), (u8, u8, u8), bool, bool);
 
 /// RAII guard to restore terminal state on drop (including on error/panic).
 struct PlayerTerminalGuard;
 
 impl Drop for PlayerTerminalGuard {
     fn drop(&mut self) {
-        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let mut out = stdout();
+        let _ = write!(out, "\x1b[r");
+        let _ = execute!(out, Show, LeaveAlternateScreen);
         let _ = terminal::disable_raw_mode();
     }
 }
 
+/// The playback position, with a virtual screen kept in step with what has
+/// been played so a seek can repaint a consistent frame.
+struct Playback<'a> {
+    recording: &'a Recording,
+    screen: TerminalState,
+    next_event: usize,
+    time: f64,
+}
+
+impl<'a> Playback<'a> {
+    fn new(recording: &'a Recording) -> Self {
+        Self {
+            recording,
+            screen: blank_screen(recording),
+            next_event: 0,
+            time: 0.0,
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.next_event >= self.recording.events.len()
+    }
+
+    /// Play every event up to `time` on the terminal. Returns true if any
+    /// event was played.
+    fn advance(&mut self, time: f64) -> Result<bool> {
+        let start = self.next_event;
+        while let Some(event) = self.recording.events.get(self.next_event) {
+            if event.time > time {
+                break;
+            }
+            let region_sets = self.screen.get_scroll_region_sets();
+            apply_event(event);
+            self.screen.apply_event(event)?;
+            self.next_event += 1;
+            // The output set the terminal's region as it would a terminal
+            // the recording's size, so set it again within the recording
+            if self.screen.get_scroll_region_sets() != region_sets {
+                let (x, y) = self.screen.get_cursor_position();
+                print!(
+                    "{}\x1b[{};{}H",
+                    scroll_region(&self.screen, terminal::size()?.1),
+                    y + 1,
+                    x + 1
+                );
+            }
+            // The terminal keeps its own size, so a resize is shown by
+            // redrawing the recording at its new size
+            if event.size().is_some() {
+                self.repaint()?;
+            }
+        }
+        self.time = time;
+        if self.next_event > start {
+            stdout().flush()?;
+        }
+        Ok(self.next_event > start)
+    }
+
+    /// Jump to `time` without playing the events in between: they are
+    /// replayed into the virtual screen, which is then painted in one go.
+    fn seek(&mut self, time: f64) -> Result<()> {
+        let time = time.clamp(0.0, self.recording.duration);
+        if time < self.time {
+            self.screen = blank_screen(self.recording);
+            self.next_event = 0;
+        }
+        while let Some(event) = self.recording.events.get(self.next_event) {
+            if event.time > time {
+                break;
+            }
+            self.screen.apply_event(event)?;
+            self.next_event += 1;
+        }
+        self.time = time;
+        self.repaint()
+    }
+
+    /// Redraw the virtual screen over whatever the terminal shows.
+    fn repaint(&self) -> Result<()> {
+        print!("{}", paint(&self.screen, terminal::size()?));
+        stdout().flush()?;
+        Ok(())
+    }
+
+    /// Seek to the next output containing `query`, returning a message to
+    /// show if the search wrapped or found nothing.
+    fn search(&mut self, query: &str) -> Result<Option<String>> {
+        match find_output(self.recording, query, self.time) {
+            Some(time) => {
+                let wrapped = time <= self.time;
+                self.seek(time)?;
+                Ok(wrapped.then(|| format!("Search wrapped to the start: {query}")))
+            }
+            None => Ok(Some(format!("Not found: {query}"))),
+        }
+    }
+}
+
 pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
     let recording = Recording::load(&file_path)?;
 
@@ -30,11 +145,25 @@ pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
     println!("Duration: {:.1}s", recording.duration);
     println!("Dimensions: {}x{}", recording.width, recording.height);
     println!("Events: {}", recording.events.len());
+    let markers: Vec<&Event> = recording
+        .events
+        .iter()
+        .filter(|event| matches!(event.event_type, EventType::Marker))
+        .collect();
+    for marker in &markers {
+        println!("Marker: {:>7.1}s  {}", marker.time, marker.data);
+    }
+    let marker_times: Vec<f64> = markers.iter().map(|marker| marker.time).collect();
     println!();
     println!("Controls:");
     println!("  Space: Pause/Resume");
     println!("  \u{2190}/\u{2192}: Rewind/Fast-forward 5s");
+    println!("  0-9: Jump to 0%-90%");
     println!("  \u{2191}/\u{2193}: Speed up/down");
+    if !markers.is_empty() {
+        println!("  m/M: Jump to next/previous marker");
+    }
+    println!("  /: Search output, n: Next match");
     println!("  q: Quit");
     println!();
     println!("Press any key to start...");
@@ -53,12 +182,21 @@ pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
     // Guard ensures terminal is restored even if we return early via `?`
     let _guard = PlayerTerminalGuard;
 
-    let mut current_event_idx = 0;
+    let mut playback = Playback::new(&recording);
+    playback.repaint()?;
     let mut is_paused = paused;
     let mut playback_speed = speed;
 
+    // Search text being typed after `/`, the last search, and a message that
+    // briefly replaces the timeline
+    let mut query: Option<String> = None;
+    let mut last_query: Option<String> = None;
+    let mut notice: Option<(String, Instant)> = None;
+    let mut status_line = String::new();
+    // The recording's and the terminal's sizes when they were last compared
+    let mut sizes = None;
+
     // Unified timing: track virtual playback position and advance by real delta * speed
-    let mut virtual_time = 0.0;
     let mut last_tick = Instant::now();
 
     let mut event_stream = crossterm::event::EventStream::new().fuse();
@@ -68,36 +206,71 @@ pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
     loop {
         tokio::select! {
             _ = ticker.tick() => {
-                if !is_paused && current_event_idx < recording.events.len() {
+                let mut played = false;
+                if !is_paused && query.is_none() && !playback.finished() {
                     let now = Instant::now();
                     let real_delta = now.duration_since(last_tick).as_secs_f64();
                     last_tick = now;
-                    virtual_time += real_delta * playback_speed;
-
-                    while current_event_idx < recording.events.len() {
-                        let event = &recording.events[current_event_idx];
-                        if event.time <= virtual_time {
-                            if matches!(event.event_type, EventType::Output) {
-                                print!("{}", event.data);
-                                stdout().flush()?;
-                            }
-                            current_event_idx += 1;
-                        } else {
-                            break;
-                        }
-                    }
+                    played = playback.advance(playback.time + real_delta * playback_speed)?;
 
-                    if current_event_idx >= recording.events.len() {
+                    if playback.finished() {
                         break;
                     }
                 } else {
                     // Keep last_tick current while paused so we don't get a huge delta on resume
                     last_tick = Instant::now();
                 }
+
+                let (cols, rows) = terminal::size()?;
+                let recording_size = (playback.screen.get_width(), playback.screen.get_height());
+                if sizes != Some((recording_size, (cols, rows))) {
+                    sizes = Some((recording_size, (cols, rows)));
+                    if let Some(text) = size_mismatch(recording_size, (cols, rows)) {
+                        notice = Some((text, Instant::now()));
+                    }
+                }
+                let line = match (&query, &notice) {
+                    (Some(query), _) => format!("/{query}"),
+                    (None, Some((text, since))) if since.elapsed() < NOTICE_TIME => text.clone(),
+                    _ => timeline(
+                        usize::from(cols),
+                        playback.time,
+                        recording.duration,
+                        playback_speed,
+                        is_paused,
+                        &marker_times,
+                    ),
+                };
+                // Output may have been drawn over the status line, so redraw it after any
+                if played || line != status_line {
+                    draw_status(&line, &playback.screen)?;
+                    status_line = line;
+                }
             }
 
             Some(Ok(event)) = event_stream.next() => {
                 if let TermEvent::Key(key) = event {
+                    // A seek repaints the whole screen, status line included
+                    status_line.clear();
+                    if let Some(text) = query.as_mut() {
+                        match key.code {
+                            KeyCode::Enter => {
+                                let text = query.take().unwrap_or_default();
+                                if !text.is_empty() {
+                                    notice = playback.search(&text)?.map(|text| (text, Instant::now()));
+                                    last_query = Some(text);
+                                }
+                                last_tick = Instant::now();
+                            }
+                            KeyCode::Esc => query = None,
+                            KeyCode::Backspace => {
+                                text.pop();
+                            }
+                            KeyCode::Char(ch) => text.push(ch),
+                            _ => {}
+                        }
+                        continue;
+                    }
                     match key.code {
                         KeyCode::Char('q') | KeyCode::Esc => break,
                         KeyCode::Char(' ') => {
@@ -105,19 +278,40 @@ pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
                             last_tick = Instant::now();
                         }
                         KeyCode::Left => {
-                            // Rewind 5 seconds
-                            let target_time = (virtual_time - 5.0).max(0.0);
-                            rewind_to_time(&recording, target_time, &mut current_event_idx)?;
-                            virtual_time = target_time;
+                            playback.seek(playback.time - SEEK_STEP)?;
                             last_tick = Instant::now();
                         }
                         KeyCode::Right => {
-                            // Fast-forward 5 seconds
-                            let target_time = (virtual_time + 5.0).min(recording.duration);
-                            fast_forward_to_time(&recording, target_time, &mut current_event_idx)?;
-                            virtual_time = target_time;
+                            playback.seek(playback.time + SEEK_STEP)?;
                             last_tick = Instant::now();
                         }
+                        KeyCode::Char(digit @ '0'..='9') => {
+                            let tenths = f64::from(digit.to_digit(10).unwrap_or(0));
+                            playback.seek(recording.duration * tenths / 10.0)?;
+                            last_tick = Instant::now();
+                        }
+                        KeyCode::Char('m') => {
+                            if let Some(&time) = marker_times.iter().find(|&&time| time > playback.time) {
+                                playback.seek(time)?;
+                                last_tick = Instant::now();
+                            }
+                        }
+                        KeyCode::Char('M') => {
+                            if let Some(&time) = marker_times.iter().rev().find(|&&time| time < playback.time) {
+                                playback.seek(time)?;
+                                last_tick = Instant::now();
+                            }
+                        }
+                        KeyCode::Char('/') => {
+                            query = Some(String::new());
+                            notice = None;
+                        }
+                        KeyCode::Char('n') => {
+                            if let Some(text) = &last_query {
+                                notice = playback.search(text)?.map(|text| (text, Instant::now()));
+                                last_tick = Instant::now();
+                            }
+                        }
                         KeyCode::Up => {
                             playback_speed = (playback_speed * 1.5).min(10.0);
                         }
@@ -134,47 +328,255 @@ pub async fn play(file_path: PathBuf, speed: f64, paused: bool) -> Result<()> {
     // Guard handles cleanup on drop, but we print status after it
     drop(_guard);
 
-    if current_event_idx >= recording.events.len() {
+    if playback.finished() {
         println!("\nPlayback complete!");
     } else {
-        println!("\nPlayback stopped at {:.1}s", virtual_time);
+        println!("\nPlayback stopped at {:.1}s", playback.time);
     }
 
     Ok(())
 }
 
-fn rewind_to_time(recording: &Recording, target_time: f64, current_idx: &mut usize) -> Result<()> {
-    execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+/// Play back one event on the real terminal.
+///
+/// Resizes only change the virtual screen: the terminal is never asked to
+/// change its size.
+fn apply_event(event: &Event) {
+    match event.event_type {
+        EventType::Output => print!("{}", event.data),
+        EventType::Resize | EventType::Input | EventType::Marker => {}
+    }
+}
 
-    *current_idx = 0;
-    for (idx, event) in recording.events.iter().enumerate() {
-        if event.time > target_time {
-            break;
-        }
-        if matches!(event.event_type, EventType::Output) {
-            print!("{}", event.data);
+/// A message saying a recording `width` by `height` does not fit in a
+/// terminal `cols` by `rows`, if it doesn't.
+fn size_mismatch((width, height): (usize, usize), (cols, rows): (u16, u16)) -> Option<String> {
+    (width > usize::from(cols) || height > usize::from(rows))
+        .then(|| format!("Recording is {width}x{height}, terminal is {cols}x{rows}"))
+}
+
+fn blank_screen(recording: &Recording) -> TerminalState {
+    TerminalState::new(
+        usize::from(recording.width),
+        usize::from(recording.height),
+        TerminalTheme::auto(),
+    )
+}
+
+/// Escape sequences that redraw `screen` on the real terminal and leave the
+/// cursor, colours and scrolling region where the recording had them, so
+/// output played after a seek lands where it would have. The region is always
+/// set, so scrolling stays within the recording's rows. A recording larger
+/// than the terminal's `(cols, rows)` is clipped to it.
+///
+/// Colours equal to the theme's defaults are drawn as the terminal's own
+/// defaults rather than as fixed RGB values.
+pub fn paint(screen: &TerminalState, (cols, rows): (u16, u16)) -> String {
+    let mut out = String::from("\x1b[0m\x1b[r\x1b[2J");
+    let theme = screen.get_theme();
+    for (y, row) in screen.get_grid().iter().take(usize::from(rows)).enumerate() {
+        let _ = write!(out, "\x1b[{};1H", y + 1);
+        let mut style = None;
+        for cell in row.iter().take(usize::from(cols)) {
+            let cell_style = (cell.fg_color, cell.bg_color, cell.bold, cell.underline);
+            if style != Some(cell_style) {
+                out.push_str(&sgr(theme, cell_style));
+                style = Some(cell_style);
+            }
+            out.push(if cell.ch.is_control() { ' ' } else { cell.ch });
         }
-        *current_idx = idx + 1;
     }
+    out.push_str(&scroll_region(screen, rows));
+    out.push_str(&restore_cursor(screen));
+    out
+}
+
+/// The escape sequence setting the recording's scrolling region on a
+/// terminal `rows` tall. Scrolling is kept within the recording's
+/// rows, so the rows below it stay free for the status line.
+fn scroll_region(screen: &TerminalState, rows: u16) -> String {
+    let (top, bottom) = screen.get_scroll_region();
+    let bottom = bottom.min(usize::from(rows).saturating_sub(1));
+    format!("\x1b[{};{}r", top.min(bottom) + 1, bottom + 1)
+}
+
+/// Move the cursor back to where the recording has it and restore the
+/// recording's current colours.
+fn restore_cursor(screen: &TerminalState) -> String {
+    let (x, y) = screen.get_cursor_position();
+    format!(
+        "\x1b[{};{}H{}",
+        y + 1,
+        x + 1,
+        sgr(
+            screen.get_theme(),
+            (
+                screen.current_fg,
+                screen.current_bg,
+                screen.bold,
+                screen.underline
+            )
+        )
+    )
+}
+
+/// A full SGR sequence that sets `style` from a reset.
+fn sgr(theme: &TerminalTheme, (fg, bg, bold, underline): CellStyle) -> String {
+    let mut out = String::from("\x1b[0");
+    if bold {
+        out.push_str(";1");
+    }
+    if underline {
+        out.push_str(";4");
+    }
+    if fg != theme.foreground {
+        let _ = write!(out, ";38;2;{};{};{}", fg.0, fg.1, fg.2);
+    }
+    if bg != theme.background {
+        let _ = write!(out, ";48;2;{};{};{}", bg.0, bg.1, bg.2);
+    }
+    out.push('m');
+    out
+}
+
+/// Draw `line` on the terminal's bottom row, then put the cursor back.
+///
+/// That row is outside the recording when the terminal is taller than it;
+/// otherwise the line covers the recording's last row.
+fn draw_status(line: &str, screen: &TerminalState) -> Result<()> {
+    let (_, rows) = terminal::size()?;
+    print!(
+        "\x1b[{rows};1H\x1b[0m\x1b[2K{line}{}",
+        restore_cursor(screen)
+    );
     stdout().flush()?;
     Ok(())
 }
 
-fn fast_forward_to_time(
-    recording: &Recording,
-    target_time: f64,
-    current_idx: &mut usize,
-) -> Result<()> {
-    while *current_idx < recording.events.len() {
-        let event = &recording.events[*current_idx];
-        if event.time > target_time {
-            break;
+/// The status line: play state, position, a progress bar with a tick for
+/// each marker, the duration and the speed, fitted to `width` columns.
+pub fn timeline(
+    width: usize,
+    time: f64,
+    duration: f64,
+    speed: f64,
+    paused: bool,
+    markers: &[f64],
+) -> String {
+    let icon = if paused { '\u{2016}' } else { '\u{25b6}' };
+    let left = format!("{icon} {time:.1}s ");
+    let right = format!(" {duration:.1}s  {speed:.1}x");
+    let room = width.saturating_sub(left.chars().count() + right.chars().count() + 2);
+    if room < MIN_BAR_WIDTH {
+        return format!("{left}/{right}").chars().take(width).collect();
+    }
+
+    let mut bar = String::with_capacity(room);
+    for cell in 0..room {
+        let start = duration * cell as f64 / room as f64;
+        let end = duration * (cell + 1) as f64 / room as f64;
+        bar.push(
+            if markers
+                .iter()
+                .any(|&marker| marker >= start && marker < end)
+            {
+                '|'
+            } else if (start + end) / 2.0 <= time {
+                '\u{2588}'
+            } else {
+                '\u{2591}'
+            },
+        );
+    }
+    format!("{left}[{bar}]{right}")
+}
+
+/// The time of the output event where the first match of `query` after
+/// `after` starts, wrapping round to the start if nothing later matches.
+///
+/// The output is searched as one stream, so a match may run across events,
+/// and escape sequences are ignored, so colouring inside a word does not hide
+/// it. A query with no capital letters matches case-insensitively.
+pub fn find_output(recording: &Recording, query: &str, after: f64) -> Option<f64> {
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let query = if ignore_case {
+        query.to_lowercase()
+    } else {
+        query.to_string()
+    };
+    // The output with escapes stripped, and where in it each event starts
+    let mut text = String::new();
+    let mut starts = Vec::new();
+    for event in &recording.events {
+        if !matches!(event.event_type, EventType::Output) {
+            continue;
         }
-        if matches!(event.event_type, EventType::Output) {
-            print!("{}", event.data);
-            stdout().flush()?;
+        starts.push((text.len(), event.time));
+        let stripped = strip_escapes(&event.data);
+        if ignore_case {
+            text.push_str(&stripped.to_lowercase());
+        } else {
+            text.push_str(&stripped);
         }
-        *current_idx += 1;
     }
-    Ok(())
+    let event_time = |offset: usize| {
+        let index = starts.partition_point(|&(start, _)| start <= offset);
+        starts.get(index.checked_sub(1)?).map(|&(_, time)| time)
+    };
+    let mut times = text
+        .match_indices(&query)
+        .filter_map(|(offset, _)| event_time(offset));
+    let first = times.next()?;
+    if first > after {
+        return Some(first);
+    }
+    Some(times.find(|&time| time > after).unwrap_or(first))
+}
+
+/// Output text with escape sequences and control characters other than
+/// newlines removed.
+pub fn strip_escapes(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut chars = data.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            if ch == '\n' || !ch.is_control() {
+                out.push(ch);
+            }
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in '@'..='~'
+            Some('[') => {
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+            // OSC: up to BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(ch) = chars.next() {
+                    if ch == '\x07' {
+                        break;
+                    }
+                    if ch == '\x1b' {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // nF escapes such as a charset choice: intermediates, then a final
+            Some(' '..='/') => {
+                for ch in chars.by_ref() {
+                    if !(' '..='/').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+            // Any other escape is two characters long
+            _ => {}
+        }
+    }
+    out
 }
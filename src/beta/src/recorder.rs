@@ -1,18 +1,132 @@
 use anyhow::{Context, Result};
 use crossterm::{terminal, tty::IsTty};
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use signal_hook::{consts::SIGINT, iterator::Signals};
+use nix::sys::termios::{tcgetattr, LocalFlags};
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use signal_hook::{
+    consts::{SIGINT, SIGWINCH},
+    iterator::Signals,
+};
 use std::fs::File;
 use std::io::{self, BufWriter, Read, Write};
-use std::path::PathBuf;
+use std::os::fd::BorrowedFd;
+use std::path::{Path, PathBuf};
 use std::sync::{
-    atomic::{AtomicBool, Ordering::Acquire, Ordering::Release},
+    atomic::{AtomicBool, Ordering::AcqRel, Ordering::Acquire, Ordering::Release},
     Arc, Mutex,
 };
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::asciicast::{self, AsciicastWriter, Header};
+use crate::{Event, EventType};
+
+/// Ctrl-] starts a marker. The name typed after it, up to Enter, goes into the
+/// recording and never reaches the shell.
+pub const MARKER_KEY: u8 = 0x1d;
+
+/// How often recorded events are flushed to disk. A crash loses at most this
+/// much of the recording.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Stand-in for each character of input typed while echo is off.
+const REDACTED: char = '*';
 
-use crate::{get_timestamp, Event, EventType, Recording};
+/// What one chunk of keyboard input turns into.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Input {
+    /// Bytes for the shell.
+    Forward(Vec<u8>),
+    /// A marker the user named.
+    Marker(String),
+}
+
+/// Splits keyboard input into bytes for the shell and markers for the recording.
+///
+/// Pressing [`MARKER_KEY`] starts a name. Enter ends it, Backspace edits it,
+/// and Esc abandons the marker. An empty name gets a numbered default. The
+/// state survives across chunks, since a name is usually typed one key per read.
+#[derive(Debug, Default)]
+pub struct InputFilter {
+    naming: Option<Vec<u8>>,
+    markers: usize,
+}
+
+impl InputFilter {
+    pub fn feed(&mut self, data: &[u8]) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        let mut forward = Vec::new();
+        for &byte in data {
+            let Some(name) = self.naming.as_mut() else {
+                if byte == MARKER_KEY {
+                    self.naming = Some(Vec::new());
+                } else {
+                    forward.push(byte);
+                }
+                continue;
+            };
+            match byte {
+                b'\r' | b'\n' => {
+                    let name = String::from_utf8_lossy(name).trim().to_string();
+                    self.naming = None;
+                    self.markers += 1;
+                    if !forward.is_empty() {
+                        inputs.push(Input::Forward(std::mem::take(&mut forward)));
+                    }
+                    inputs.push(Input::Marker(if name.is_empty() {
+                        format!("Marker {}", self.markers)
+                    } else {
+                        name
+                    }));
+                }
+                0x1b => self.naming = None,
+                0x7f | 0x08 => {
+                    name.pop();
+                }
+                _ => name.push(byte),
+            }
+        }
+        if !forward.is_empty() {
+            inputs.push(Input::Forward(forward));
+        }
+        inputs
+    }
+}
+
+/// Mask input so a recording shows that something was typed but not what.
+///
+/// Line endings are kept so the recording still shows the prompt being
+/// answered.
+pub fn redact(data: &str) -> String {
+    data.chars()
+        .map(|ch| {
+            if matches!(ch, '\r' | '\n') {
+                ch
+            } else {
+                REDACTED
+            }
+        })
+        .collect()
+}
+
+/// True when the shell's terminal is at a hidden-input prompt such as a
+/// password prompt.
+///
+/// Programs reading a password turn echo off but keep line editing
+/// (`ICANON`) on. Interactive shells and full-screen programs turn both off
+/// and echo for themselves, so checking echo alone would mask everything
+/// typed at a shell prompt.
+fn input_hidden(master: &dyn MasterPty) -> bool {
+    let Some(fd) = master.as_raw_fd() else {
+        return false;
+    };
+    // SAFETY: the descriptor belongs to `master`, which is borrowed for the
+    // whole call, so it stays open while `tcgetattr` uses it.
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    tcgetattr(fd).is_ok_and(|termios| {
+        !termios.local_flags.contains(LocalFlags::ECHO)
+            && termios.local_flags.contains(LocalFlags::ICANON)
+    })
+}
 
 // Guard to ensure raw mode is disabled on drop
 struct RawModeGuard;
@@ -23,63 +137,132 @@ impl Drop for RawModeGuard {
     }
 }
 
+/// Where the asciicast stream goes, optionally gzip-compressed.
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(flate2::write::GzEncoder<File>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    // For gzip this is a sync flush, so everything written so far can be
+    // decompressed even if the process dies before the stream is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl Sink {
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut writer) => writer.flush(),
+            Self::Gzip(encoder) => encoder.finish().map(|_| ()),
+        }
+    }
+}
+
 struct RecordingSession {
-    output_path: PathBuf,
-    compress: bool,
-    recording: Arc<Mutex<Recording>>,
+    // None once the recording has been finished
+    writer: Mutex<Option<AsciicastWriter<Sink>>>,
+    // Whether events have been written since the last flush
+    unflushed: AtomicBool,
     start_time: Instant,
     should_stop: Arc<AtomicBool>,
 }
 
 impl RecordingSession {
-    fn new(output_path: PathBuf, compress: bool, width: u16, height: u16, command: String) -> Self {
-        let recording = Recording {
-            version: 2,
+    fn new(
+        output_path: &Path,
+        compress: bool,
+        width: u16,
+        height: u16,
+        command: String,
+    ) -> Result<Self> {
+        let file = File::create_new(output_path)
+            .with_context(|| format!("Failed to create {}", output_path.display()))?;
+        let sink = if compress {
+            Sink::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            ))
+        } else {
+            Sink::Plain(BufWriter::new(file))
+        };
+
+        let env = ["SHELL", "TERM"]
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+            .collect();
+        let header = Header {
+            version: asciicast::VERSION,
             width,
             height,
-            timestamp: get_timestamp(),
-            duration: 0.0,
-            command,
-            title: format!("Terminal recording at {}", chrono::Local::now()),
-            env: std::collections::HashMap::new(),
-            events: Vec::new(),
+            timestamp: Some(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            ),
+            duration: None,
+            command: Some(command),
+            title: Some(format!("Terminal recording at {}", chrono::Local::now())),
+            env,
         };
 
-        Self {
-            output_path,
-            compress,
-            recording: Arc::new(Mutex::new(recording)),
+        Ok(Self {
+            writer: Mutex::new(Some(AsciicastWriter::new(sink, &header)?)),
+            unflushed: AtomicBool::new(false),
             start_time: Instant::now(),
             should_stop: Arc::new(AtomicBool::new(false)),
-        }
+        })
     }
 
-    fn add_event(&self, event_type: EventType, data: String) {
-        let elapsed = self.start_time.elapsed().as_secs_f64();
+    fn add_event(&self, event_type: EventType, data: String) -> Result<()> {
         let event = Event {
-            time: elapsed,
+            time: self.start_time.elapsed().as_secs_f64(),
             event_type,
             data,
         };
 
-        let mut recording = self.recording.lock().unwrap_or_else(|e| e.into_inner());
-        recording.events.push(event);
-        recording.duration = elapsed;
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        match writer.as_mut() {
+            Some(writer) => {
+                writer.write_event(&event)?;
+                self.unflushed.store(true, Release);
+                Ok(())
+            }
+            None => Ok(()),
+        }
     }
 
-    fn save(&self) -> Result<()> {
-        let recording = self.recording.lock().unwrap_or_else(|e| e.into_inner());
-
-        if self.compress {
-            let file = File::create(&self.output_path)?;
-            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
-            serde_json::to_writer(encoder, &*recording)?;
-        } else {
-            let file = File::create(&self.output_path)?;
-            let writer = BufWriter::new(file);
-            serde_json::to_writer_pretty(writer, &*recording)?;
+    /// Flush the events written since the last flush, if there are any. For
+    /// gzip every flush adds a little to the file, so nothing is flushed when
+    /// nothing has been written.
+    fn flush(&self) -> Result<()> {
+        if !self.unflushed.swap(false, AcqRel) {
+            return Ok(());
         }
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        match writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
 
+    fn finish(&self) -> Result<()> {
+        let writer = self.writer.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(writer) = writer {
+            writer.into_inner().finish()?;
+        }
         Ok(())
     }
 
@@ -96,6 +279,7 @@ pub async fn record(
     output: Option<PathBuf>,
     command: Option<String>,
     compress: bool,
+    redact_hidden: bool,
 ) -> Result<()> {
     if !io::stdout().is_tty() {
         anyhow::bail!("beta record must be run in a terminal");
@@ -103,7 +287,7 @@ pub async fn record(
 
     let output_path = output.unwrap_or_else(|| {
         let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        PathBuf::from(format!("beta_{}.json", timestamp))
+        PathBuf::from(format!("beta_{}.cast", timestamp))
     });
 
     if output_path.exists() {
@@ -117,6 +301,10 @@ pub async fn record(
 
     println!("Recording session to: {}", output_path.display());
     println!("Press Ctrl-C to stop recording, or 'exit' to end the shell session");
+    println!("Press Ctrl-] then type a name and Enter to drop a marker");
+    if redact_hidden {
+        println!("Input typed while echo is off will be masked");
+    }
     println!();
 
     // Enable raw mode for immediate character input
@@ -125,14 +313,14 @@ pub async fn record(
     // Ensure raw mode is disabled on exit
     let _raw_mode_guard = RawModeGuard;
 
-    // Create recording session
+    // Create recording session; the header is on disk before the shell starts
     let session = Arc::new(RecordingSession::new(
-        output_path.clone(),
+        &output_path,
         compress,
         term_width,
         term_height,
         shell.clone(),
-    ));
+    )?);
 
     // Set up PTY
     let pty_system = native_pty_system();
@@ -170,15 +358,26 @@ pub async fn record(
         .take_writer()
         .context("Failed to take PTY writer")?;
 
-    // Set up signal handling for graceful shutdown
+    // The signal thread resizes the PTY and the writer thread reads its modes
+    let master = Arc::new(Mutex::new(pair.master));
+
+    // Set up signal handling for graceful shutdown and terminal resizes
     let session_for_signal = session.clone();
+    let master_for_signal = master.clone();
     let _signal_handle = thread::spawn(move || {
-        if let Ok(mut signals) = Signals::new([SIGINT]) {
+        if let Ok(mut signals) = Signals::new([SIGINT, SIGWINCH]) {
             for sig in signals.forever() {
-                if sig == SIGINT {
+                if sig == SIGWINCH {
+                    if let Err(e) = resize(&session_for_signal, &master_for_signal) {
+                        eprintln!("Failed to record resize: {:#}", e);
+                    }
+                } else if sig == SIGINT {
                     // Disable raw mode before printing to fix terminal output
                     let _ = terminal::disable_raw_mode();
                     eprintln!("\nReceived interrupt signal, stopping recording...");
+                    if let Err(e) = session_for_signal.flush() {
+                        eprintln!("Failed to write recording: {:#}", e);
+                    }
                     session_for_signal.stop();
                     break;
                 }
@@ -186,6 +385,19 @@ pub async fn record(
         }
     });
 
+    // Thread to flush the recording to disk now and then rather than after
+    // every event
+    let session_flusher = session.clone();
+    let flusher_handle = thread::spawn(move || {
+        while session_flusher.should_continue() {
+            thread::sleep(FLUSH_INTERVAL);
+            if let Err(e) = session_flusher.flush() {
+                eprintln!("Failed to write recording: {:#}", e);
+                session_flusher.stop();
+            }
+        }
+    });
+
     // Thread to read from PTY and write to stdout
     let session_reader = session.clone();
     let reader_handle = thread::spawn(move || {
@@ -209,7 +421,11 @@ pub async fn record(
                     }
 
                     // Record the output
-                    session_reader.add_event(EventType::Output, data);
+                    if let Err(e) = session_reader.add_event(EventType::Output, data) {
+                        eprintln!("Failed to write recording: {:#}", e);
+                        session_reader.stop();
+                        break;
+                    }
                 }
                 Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
                     thread::sleep(std::time::Duration::from_millis(10));
@@ -231,24 +447,20 @@ pub async fn record(
     let writer_handle = thread::spawn(move || {
         let mut writer = writer; // Make writer mutable in this scope
         let mut buffer = vec![0; 4096];
+        let mut filter = InputFilter::default();
 
         while session_writer.should_continue() {
             match io::stdin().read(&mut buffer) {
                 Ok(0) => break, // EOF
                 Ok(n) => {
-                    let data = &buffer[..n];
-
-                    // Record the input
-                    let input_str = String::from_utf8_lossy(data).to_string();
-                    session_writer.add_event(EventType::Input, input_str);
-
-                    // Forward to PTY
-                    if let Err(e) = writer.write_all(data) {
-                        eprintln!("Failed to write to PTY: {}", e);
-                        break;
-                    }
-                    if let Err(e) = writer.flush() {
-                        eprintln!("Failed to flush PTY: {}", e);
+                    let inputs = filter.feed(&buffer[..n]);
+                    let hidden = redact_hidden
+                        && master
+                            .lock()
+                            .map(|master| input_hidden(master.as_ref()))
+                            .unwrap_or(false);
+                    if let Err(e) = forward(&session_writer, &mut writer, inputs, hidden) {
+                        eprintln!("{:#}", e);
                         break;
                     }
                 }
@@ -276,12 +488,61 @@ pub async fn record(
     // Wait for threads to finish
     let _ = reader_handle.join();
     let _ = writer_handle.join();
+    let _ = flusher_handle.join();
 
-    // Save the recording
-    session.save().context("Failed to save recording")?;
+    // Writes out whatever the flusher has not, and ends the gzip stream
+    session.finish().context("Failed to finish recording")?;
 
     println!("\nRecording saved to: {}", output_path.display());
     println!("Exit status: {}", exit_status);
 
     Ok(())
 }
+
+/// Match the PTY to the terminal's new size and record the change.
+fn resize(session: &RecordingSession, master: &Mutex<Box<dyn MasterPty + Send>>) -> Result<()> {
+    let (cols, rows) = terminal::size().context("Failed to get terminal size")?;
+    master
+        .lock()
+        .map_err(|_| anyhow::anyhow!("PTY lock poisoned"))?
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .context("Failed to resize PTY")?;
+    session.add_event(EventType::Resize, format!("{}x{}", cols, rows))
+}
+
+/// Record filtered input and send the keystrokes on to the PTY.
+fn forward(
+    session: &RecordingSession,
+    writer: &mut dyn Write,
+    inputs: Vec<Input>,
+    hidden: bool,
+) -> Result<()> {
+    for input in inputs {
+        match input {
+            Input::Marker(name) => {
+                session
+                    .add_event(EventType::Marker, name)
+                    .context("Failed to write recording")?;
+            }
+            Input::Forward(data) => {
+                let text = String::from_utf8_lossy(&data);
+                let text = if hidden {
+                    redact(&text)
+                } else {
+                    text.into_owned()
+                };
+                session
+                    .add_event(EventType::Input, text)
+                    .context("Failed to write recording")?;
+                writer.write_all(&data).context("Failed to write to PTY")?;
+                writer.flush().context("Failed to flush PTY")?;
+            }
+        }
+    }
+    Ok(())
+}
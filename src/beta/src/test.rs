@@ -106,6 +106,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scroll_region_sets_counted() {
+        let mut state = TerminalState::new(10, 5, TerminalTheme::auto());
+
+        state.process_output("\x1b[2;4r").unwrap();
+        assert_eq!(state.get_scroll_region(), (1, 3));
+        // A reset to the full screen counts even when it changes nothing
+        state.process_output("\x1b[r\x1b[r").unwrap();
+        assert_eq!(state.get_scroll_region(), (0, 4));
+        assert_eq!(state.get_scroll_region_sets(), 3);
+        // An invalid region is ignored, as a terminal would
+        state.process_output("\x1b[4;2r").unwrap();
+        assert_eq!(state.get_scroll_region_sets(), 3);
+    }
+
     #[test]
     fn test_scroll_down_via_reverse_index() {
         // Tests scroll_down logic via ESC M (reverse index) at top of scroll region.
@@ -323,4 +338,493 @@ mod tests {
         assert_eq!(fg, (255, 255, 255));
         assert_eq!(bg, (0, 0, 0));
     }
+
+    fn asciicast_header() -> crate::asciicast::Header {
+        crate::asciicast::Header {
+            version: 2,
+            width: 100,
+            height: 30,
+            timestamp: Some(1_700_000_000),
+            duration: None,
+            command: Some("zsh".to_string()),
+            title: Some("Streamed".to_string()),
+            env: std::collections::HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_asciicast_writer_round_trip() {
+        use crate::asciicast::AsciicastWriter;
+
+        let mut writer = AsciicastWriter::new(Vec::new(), &asciicast_header()).unwrap();
+        writer
+            .write_event(&Event {
+                time: 0.25,
+                event_type: EventType::Output,
+                data: "hi \"there\"\r\n".to_string(),
+            })
+            .unwrap();
+        writer
+            .write_event(&Event {
+                time: 1.5,
+                event_type: EventType::Input,
+                data: "q".to_string(),
+            })
+            .unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("\"version\":2"));
+        assert_eq!(lines[1], r#"[0.25,"o","hi \"there\"\r\n"]"#);
+        assert_eq!(lines[2], r#"[1.5,"i","q"]"#);
+
+        let recording = crate::asciicast::parse(&text).unwrap();
+        assert_eq!(recording.width, 100);
+        assert_eq!(recording.command, "zsh");
+        assert_eq!(recording.events.len(), 2);
+        assert_eq!(recording.events[0].data, "hi \"there\"\r\n");
+        assert!(matches!(recording.events[1].event_type, EventType::Input));
+        // No duration in a streamed header, so it comes from the last event
+        assert!((recording.duration - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_recording_load_asciinema_file() {
+        let path = std::env::temp_dir().join("test_beta_load_asciinema.cast");
+        // As written by asciinema: extra header fields and non-i/o event codes
+        let text = concat!(
+            r##"{"version": 2, "width": 80, "height": 24, "timestamp": 1504467315, "idle_time_limit": 2.0, "env": {"SHELL": "/bin/zsh", "TERM": "xterm-256color"}, "theme": {"fg": "#d0d0d0", "bg": "#212121", "palette": "#151515"}}"##,
+            "\n",
+            r#"[0.248848, "o", "\u001b[1;31mHello \u001b[32mWorld!\u001b[0m\n"]"#,
+            "\n",
+            r#"[1.001376, "r", "90x30"]"#,
+            "\n",
+            r#"[2.5, "o", "bye\r\n"]"#,
+            "\n",
+        );
+        std::fs::write(&path, text).unwrap();
+
+        let loaded = Recording::load(&path).unwrap();
+        assert_eq!(loaded.width, 80);
+        assert_eq!(loaded.env["SHELL"], "/bin/zsh");
+        assert_eq!(loaded.events.len(), 3);
+        assert!(loaded.events[0].data.starts_with("\u{1b}[1;31mHello"));
+        assert_eq!(loaded.events[1].size(), Some((90, 30)));
+        assert_eq!(loaded.max_size(), (90, 30));
+        assert!((loaded.duration - 2.5).abs() < f64::EPSILON);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recording_load_asciicast_cut_short() {
+        let path = std::env::temp_dir().join("test_beta_load_cut.cast");
+        let text = format!(
+            "{}\n[0.5, \"o\", \"kept\"]\n[0.9, \"o\", \"lo",
+            serde_json::to_string(&asciicast_header()).unwrap()
+        );
+        std::fs::write(&path, text).unwrap();
+
+        let loaded = Recording::load(&path).unwrap();
+        assert_eq!(loaded.events.len(), 1);
+        assert_eq!(loaded.events[0].data, "kept");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recording_load_asciicast_bad_middle_line() {
+        let header = serde_json::to_string(&asciicast_header()).unwrap();
+        let text = format!("{header}\nnot json\n[0.5, \"o\", \"x\"]\n");
+        let err = crate::asciicast::parse(&text).unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"));
+    }
+
+    #[test]
+    fn test_recording_load_unfinished_gzip_asciicast() {
+        use crate::asciicast::AsciicastWriter;
+        use flate2::write::GzEncoder;
+
+        let path = std::env::temp_dir().join("test_beta_load_unfinished.cast");
+        let encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut writer = AsciicastWriter::new(encoder, &asciicast_header()).unwrap();
+        writer
+            .write_event(&Event {
+                time: 0.1,
+                event_type: EventType::Output,
+                data: "survives".to_string(),
+            })
+            .unwrap();
+        writer.flush().unwrap();
+        // Simulate a killed recorder: take the flushed bytes without finishing
+        let bytes = writer.into_inner().get_ref().clone();
+        std::fs::write(&path, bytes).unwrap();
+
+        let loaded = Recording::load(&path).unwrap();
+        assert_eq!(loaded.events.len(), 1);
+        assert_eq!(loaded.events[0].data, "survives");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_event_size() {
+        let event = |data: &str| Event {
+            time: 0.0,
+            event_type: EventType::Resize,
+            data: data.to_string(),
+        };
+        assert_eq!(event("120x40").size(), Some((120, 40)));
+        assert_eq!(event("0x40").size(), None);
+        assert_eq!(event("wide").size(), None);
+
+        let output = Event {
+            time: 0.0,
+            event_type: EventType::Output,
+            data: "120x40".to_string(),
+        };
+        assert_eq!(output.size(), None);
+    }
+
+    #[test]
+    fn test_asciicast_resize_and_marker_round_trip() {
+        use crate::asciicast::AsciicastWriter;
+
+        let mut writer = AsciicastWriter::new(Vec::new(), &asciicast_header()).unwrap();
+        for (time, event_type, data) in [
+            (0.5, EventType::Resize, "132x43"),
+            (1.0, EventType::Marker, "deploy"),
+        ] {
+            writer
+                .write_event(&Event {
+                    time,
+                    event_type,
+                    data: data.to_string(),
+                })
+                .unwrap();
+        }
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(text.contains(r#"[0.5,"r","132x43"]"#));
+        assert!(text.contains(r#"[1.0,"m","deploy"]"#));
+
+        let recording = crate::asciicast::parse(&text).unwrap();
+        assert_eq!(recording.max_size(), (132, 43));
+        assert!(matches!(recording.events[1].event_type, EventType::Marker));
+        assert_eq!(recording.events[1].data, "deploy");
+    }
+
+    #[test]
+    fn test_terminal_state_resize() {
+        let mut state = TerminalState::new(10, 5, TerminalTheme::auto());
+        state.process_output("hello\x1b[5;10H").unwrap();
+
+        state.resize(4, 3);
+        assert_eq!((state.get_width(), state.get_height()), (4, 3));
+        assert_eq!((state.cursor_x, state.cursor_y), (3, 2));
+        let first: String = state.grid[0].iter().map(|cell| cell.ch).collect();
+        assert_eq!(first, "hell");
+
+        state.resize(8, 6);
+        assert_eq!(state.grid.len(), 6);
+        assert!(state.grid.iter().all(|row| row.len() == 8));
+        let first: String = state.grid[0].iter().map(|cell| cell.ch).collect();
+        assert_eq!(first, "hell    ");
+    }
+
+    #[test]
+    fn test_input_filter_forwards_plain_input() {
+        use crate::recorder::{Input, InputFilter};
+
+        let mut filter = InputFilter::default();
+        assert_eq!(filter.feed(b"ls\r"), vec![Input::Forward(b"ls\r".to_vec())]);
+        assert!(filter.feed(b"").is_empty());
+    }
+
+    #[test]
+    fn test_input_filter_markers() {
+        use crate::recorder::{Input, InputFilter, MARKER_KEY};
+
+        let mut filter = InputFilter::default();
+        // Input around a marker is kept in order and the name never reaches the shell
+        assert_eq!(
+            filter.feed(&[b'a', MARKER_KEY, b's', b't', b'x', 0x7f, b'e', b'p', b'\r', b'b']),
+            vec![
+                Input::Forward(b"a".to_vec()),
+                Input::Marker("step".to_string()),
+                Input::Forward(b"b".to_vec()),
+            ]
+        );
+
+        // A name typed across several reads, then an empty name
+        assert!(filter.feed(&[MARKER_KEY, b' ', b'x']).is_empty());
+        assert_eq!(filter.feed(b" \r"), vec![Input::Marker("x".to_string())]);
+        assert_eq!(
+            filter.feed(&[MARKER_KEY, b'\r']),
+            vec![Input::Marker("Marker 3".to_string())]
+        );
+
+        // Esc abandons the marker
+        assert_eq!(
+            filter.feed(&[MARKER_KEY, b'n', 0x1b, b'q']),
+            vec![Input::Forward(b"q".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        use crate::recorder::redact;
+
+        assert_eq!(redact("hunter2\r"), "*******\r");
+        assert_eq!(redact("pässwörd\n"), "********\n");
+        assert_eq!(redact(""), "");
+    }
+
+    /// Output, input, a marker, a resize and a long pause over twelve seconds.
+    fn edit_recording() -> Recording {
+        let event = |time, event_type, data: &str| Event {
+            time,
+            event_type,
+            data: data.to_string(),
+        };
+        Recording {
+            version: 2,
+            width: 80,
+            height: 24,
+            timestamp: 1_700_000_000.0,
+            duration: 12.0,
+            command: "bash".to_string(),
+            title: "Edit me".to_string(),
+            env: std::collections::HashMap::new(),
+            events: vec![
+                event(0.5, EventType::Output, "a"),
+                event(1.0, EventType::Input, "x"),
+                event(2.0, EventType::Marker, "mid"),
+                event(3.0, EventType::Output, "b"),
+                event(4.0, EventType::Resize, "100x30"),
+                event(10.0, EventType::Output, "c"),
+            ],
+        }
+    }
+
+    fn times(recording: &Recording) -> Vec<f64> {
+        recording.events.iter().map(|event| event.time).collect()
+    }
+
+    #[test]
+    fn test_edit_trim_replays_earlier_output_at_start() {
+        use crate::edit::{marker_time, trim};
+
+        let recording = edit_recording();
+        let start = marker_time(&recording, "mid", 0.0).unwrap();
+        let trimmed = trim(&recording, start, 5.0).unwrap();
+
+        assert!((trimmed.duration - 3.0).abs() < f64::EPSILON);
+        // "a" is kept at time zero so the screen matches the original at 2s
+        assert_eq!(times(&trimmed), vec![0.0, 0.0, 1.0, 2.0]);
+        assert_eq!(trimmed.events[0].data, "a");
+        assert!(matches!(trimmed.events[1].event_type, EventType::Marker));
+
+        assert!(marker_time(&recording, "mid", 2.5).is_err());
+        assert!(trim(&recording, 12.5, f64::INFINITY).is_err());
+        assert!(trim(&recording, 5.0, 4.0).is_err());
+    }
+
+    #[test]
+    fn test_edit_cut_closes_the_gap() {
+        use crate::edit::cut;
+
+        let cut = cut(&edit_recording(), 1.0, 8.0).unwrap();
+        assert!((cut.duration - 5.0).abs() < f64::EPSILON);
+        let kept: Vec<(f64, &str)> = cut
+            .events
+            .iter()
+            .map(|event| (event.time, event.data.as_str()))
+            .collect();
+        // Input and the marker go; output and the resize replay at the cut
+        assert_eq!(
+            kept,
+            vec![(0.5, "a"), (1.0, "b"), (1.0, "100x30"), (3.0, "c")]
+        );
+    }
+
+    #[test]
+    fn test_edit_cap_idle() {
+        use crate::edit::cap_idle;
+
+        let capped = cap_idle(&edit_recording(), 1.5).unwrap();
+        assert_eq!(times(&capped), vec![0.5, 1.0, 2.0, 3.0, 4.0, 5.5]);
+        assert!((capped.duration - 7.0).abs() < f64::EPSILON);
+        assert!(cap_idle(&edit_recording(), 0.0).is_err());
+    }
+
+    #[test]
+    fn test_edit_splice_inserts_resize_at_seam() {
+        use crate::edit::splice;
+
+        let first = edit_recording();
+        let spliced = splice(&[first.clone(), first]).unwrap();
+        assert!((spliced.duration - 24.0).abs() < f64::EPSILON);
+        assert_eq!(spliced.events.len(), 13);
+        // The first recording ends at 100x30 and the second starts at 80x24
+        assert_eq!(spliced.events[6].time, 12.0);
+        assert_eq!(spliced.events[6].size(), Some((80, 24)));
+        assert_eq!(spliced.events[7].time, 12.5);
+        assert!(splice(&[]).is_err());
+    }
+
+    #[test]
+    fn test_edit_speed_ranges() {
+        use crate::edit::{speed, SpeedRange};
+
+        let ranges = [
+            "4:10:2".parse::<SpeedRange>().unwrap(),
+            "0:1:0.5".parse::<SpeedRange>().unwrap(),
+        ];
+        let sped = speed(&edit_recording(), &ranges).unwrap();
+        assert_eq!(times(&sped), vec![1.0, 2.0, 3.0, 4.0, 5.0, 8.0]);
+        assert!((sped.duration - 10.0).abs() < f64::EPSILON);
+
+        let overlapping = ["0:5:2".parse().unwrap(), "4:6:2".parse().unwrap()];
+        assert!(speed(&edit_recording(), &overlapping).is_err());
+        assert!("5:4:2".parse::<SpeedRange>().is_err());
+        assert!("0:4:0".parse::<SpeedRange>().is_err());
+        assert!("0:4".parse::<SpeedRange>().is_err());
+    }
+
+    #[test]
+    fn test_recording_save_round_trip() {
+        let path = std::env::temp_dir().join("test_beta_save.cast");
+        let recording = edit_recording();
+        recording.save(&path, true).unwrap();
+
+        let loaded = Recording::load(&path).unwrap();
+        assert_eq!(loaded.title, "Edit me");
+        assert!((loaded.duration - 12.0).abs() < f64::EPSILON);
+        assert_eq!(times(&loaded), times(&recording));
+        assert_eq!(loaded.max_size(), (100, 30));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recording_save_keeps_existing_file() {
+        let path = std::env::temp_dir().join("test_beta_save_existing.cast");
+        std::fs::write(&path, "original").unwrap();
+        let recording = edit_recording();
+
+        let err = recording.save(&path, false).unwrap_err().to_string();
+        assert!(err.contains("--force"), "{err}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "original");
+
+        // Replacing in place, as `--force` does when the output is the input
+        recording.save(&path, true).unwrap();
+        assert_eq!(Recording::load(&path).unwrap().title, "Edit me");
+        assert!(!path
+            .with_file_name(".test_beta_save_existing.cast.partial")
+            .exists());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_state_at() {
+        let recording = edit_recording();
+
+        let state = TerminalState::at(&recording, 3.5, TerminalTheme::auto()).unwrap();
+        let first: String = state.grid[0].iter().take(3).map(|cell| cell.ch).collect();
+        assert_eq!(first, "ab ");
+        assert_eq!(state.get_width(), 80);
+
+        let state = TerminalState::at(&recording, 12.0, TerminalTheme::auto()).unwrap();
+        assert_eq!((state.get_width(), state.get_height()), (100, 30));
+    }
+
+    #[test]
+    fn test_svg_export() {
+        use crate::export::svg::render_svg;
+
+        let svg = render_svg(&edit_recording(), TerminalTheme::auto()).unwrap();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg></svg>\n"));
+        // One frame for the blank screen and one per output event
+        assert_eq!(svg.matches("<g transform=").count(), 4);
+        assert!(svg.contains("@keyframes play{0.000%"));
+        assert!(svg.contains("25.000%{transform:translateX(-1800px)}"));
+
+        let mut recording = edit_recording();
+        recording.events.truncate(1);
+        recording.events[0].data = "\x1b[31m<&>\x1b[0m".to_string();
+        let svg = render_svg(&recording, TerminalTheme::auto()).unwrap();
+        assert!(svg.contains(r##"fill="#ff5555">&lt;&amp;&gt;</text>"##));
+    }
+
+    #[test]
+    fn test_player_timeline() {
+        use crate::player::timeline;
+
+        let line = timeline(40, 5.0, 10.0, 1.0, false, &[7.5]);
+        assert_eq!(line.chars().count(), 40);
+        assert!(line.starts_with("\u{25b6} 5.0s ["));
+        assert!(line.ends_with("] 10.0s  1.0x"));
+        let bar: String = line.chars().skip(8).take(19).collect();
+        assert_eq!(bar.matches('\u{2588}').count(), 10);
+        assert_eq!(bar.matches('|').count(), 1);
+
+        // Too narrow for a bar
+        let line = timeline(20, 5.0, 10.0, 2.0, true, &[]);
+        assert_eq!(line, "\u{2016} 5.0s / 10.0s  2.0x");
+    }
+
+    #[test]
+    fn test_player_find_output() {
+        use crate::player::{find_output, strip_escapes};
+
+        assert_eq!(
+            strip_escapes("\x1b[1;31mer\x1b[0mror\x1b]0;title\x07\r\n\x1b(B!"),
+            "error\n!"
+        );
+
+        let mut recording = edit_recording();
+        recording.events[0].data = "Building \x1b[32mOK\x1b[0m".to_string();
+        recording.events[3].data = "Error: ok".to_string();
+        // Coloured text matches, and a lowercase query ignores case
+        assert_eq!(find_output(&recording, "ok", 0.0), Some(0.5));
+        assert_eq!(find_output(&recording, "ok", 0.5), Some(3.0));
+        // Nothing later matches, so the search wraps round
+        assert_eq!(find_output(&recording, "ok", 5.0), Some(0.5));
+        // A capital letter makes the search case-sensitive
+        assert_eq!(find_output(&recording, "OK", 0.5), Some(0.5));
+        // Input and markers are not searched
+        assert_eq!(find_output(&recording, "mid", 0.0), None);
+        // A match can run across events, and is found at the one it starts in
+        recording.events[5].data = "\x1b[1mor\x1b[0m found".to_string();
+        assert_eq!(find_output(&recording, "error found", 0.0), None);
+        recording.events[3].data = "Error: ok, err".to_string();
+        assert_eq!(find_output(&recording, "error found", 0.0), Some(3.0));
+        assert_eq!(find_output(&recording, "error found", 3.0), Some(3.0));
+    }
+
+    #[test]
+    fn test_player_paint() {
+        use crate::player::paint;
+
+        let mut state = TerminalState::new(10, 3, TerminalTheme::auto());
+        state
+            .process_output("hi\r\n\x1b[31mred\x1b[1;4H\x1b[1m")
+            .unwrap();
+        let painted = paint(&state, (80, 24));
+
+        assert!(painted.starts_with("\x1b[0m\x1b[r\x1b[2J\x1b[1;1H"));
+        assert!(painted.contains("hi "));
+        assert!(painted.contains("\x1b[0;38;2;255;85;85mred"));
+        // Cursor and the current attributes are restored at the end
+        assert!(painted.ends_with("\x1b[1;4H\x1b[0;1;38;2;255;85;85m"));
+        assert!(painted.contains("\x1b[1;3r"));
+        assert!(!painted.contains("\x1b[8;"));
+        // A terminal smaller than the recording clips it
+        let clipped = paint(&state, (2, 2));
+        assert!(clipped.contains("re") && !clipped.contains("red"));
+        assert!(clipped.contains("\x1b[1;2r"));
+    }
 }
@@ -0,0 +1,476 @@
+//! Staging, unstaging, and discarding the selected file from watch mode: which
+//! `git` command a key runs against a row, what is said before and after, and
+//! how it is run.
+//!
+//! These are `git` child processes for the same reason the push is. gix reads
+//! the index gsw renders, but writing one the way `git add` does — clean
+//! filters, `core.autocrlf`, the untracked cache, the index lock other tools
+//! wait on — is git's behavior to own, and a second implementation would be
+//! one that disagrees with the `git status` in the next pane. So everything
+//! that *decides* lives here as pure code, testable without a pty, and only
+//! [`spawn`] starts a process, through the same [`run_git`] the push uses.
+
+use std::path::PathBuf;
+
+use crate::git::FileStatus;
+use crate::push::{run_git, GitOutcome};
+use crate::render::RenderEntry;
+
+/// What a key asks to do to the selected row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileAction {
+    /// Copy the work-tree change into the index (`s`).
+    Stage,
+    /// Take the change back out of the index, leaving the work tree alone
+    /// (`S`).
+    Unstage,
+    /// Throw the work-tree change away (`d`). The one action that destroys
+    /// something the user wrote, and so the one that asks first.
+    Discard,
+}
+
+/// A file action ready to run: what it is, and the `git` arguments that carry
+/// it out.
+///
+/// Built only by [`prompt_for`], for the same reason a
+/// [`crate::push::PushCommand`] is: the arguments for a discard can then only
+/// exist alongside the question that described them.
+///
+/// Unlike a push, it carries no branch to re-check before it runs. A push reads
+/// HEAD at exec time, so a checkout between the question and the answer
+/// changes what it does; a path-limited `git add` or `git restore` does the
+/// same thing to the same file on any branch, and the file is what the
+/// question named.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileCommand {
+    /// Which action this is, for the notice shown while it runs.
+    action: FileAction,
+    /// Arguments to pass to `git`, not including the program name.
+    args: Vec<String>,
+}
+
+impl FileCommand {
+    /// `git <subcommand…> -- <paths>`, with every path taken literally.
+    ///
+    /// Literally is the load-bearing word. A path after `--` is still a
+    /// *pathspec*, so a file named `*.rs` would stage every Rust file in the
+    /// tree and a discard of it would restore them all. `:(literal)` turns the
+    /// glob magic off per path, which keeps the command about the one row the
+    /// user selected — and keeps it so on every git config, where the
+    /// `GIT_LITERAL_PATHSPECS` environment variable would depend on nothing
+    /// else in the child's environment overriding it.
+    fn new(action: FileAction, subcommand: &[&str], paths: &[&str]) -> Self {
+        let mut args: Vec<String> = subcommand.iter().map(|arg| (*arg).to_string()).collect();
+        args.push("--".to_string());
+        args.extend(paths.iter().map(|path| format!(":(literal){path}")));
+        Self { action, args }
+    }
+
+    /// The arguments to pass to `git`, not including the program name.
+    pub(crate) fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// What the overlay says while this runs. Usually for a frame or two: these
+    /// are local commands, but a large file through a clean filter is not
+    /// instant, and a key that appears to do nothing gets pressed again.
+    pub(crate) fn notice(&self) -> &'static str {
+        match self.action {
+            FileAction::Stage => "Staging…",
+            FileAction::Unstage => "Unstaging…",
+            FileAction::Discard => "Discarding…",
+        }
+    }
+}
+
+/// What the watch loop does with a file-action key.
+///
+/// The same shape as [`crate::push::PushPrompt`], with one more case: staging
+/// and unstaging are undone by the other key, so they run without a question,
+/// while a discard cannot be undone and always asks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FilePrompt {
+    /// Run now. The report comes when it finishes.
+    Run {
+        /// The command to run.
+        command: FileCommand,
+        /// What to show once it succeeds.
+        success_message: String,
+    },
+    /// Ask before running.
+    Confirm {
+        /// The question, without the key hint.
+        question: String,
+        /// The command this question described.
+        command: FileCommand,
+        /// What to show once it succeeds.
+        success_message: String,
+    },
+    /// Run nothing and show this instead.
+    Refuse {
+        /// Why nothing is going to happen.
+        message: String,
+    },
+}
+
+/// The key hint shown with a discard confirmation.
+///
+/// Spelled out for the reason [`crate::push`]'s is: Enter *confirms* here, and
+/// what it confirms is the loss of work nothing else has a copy of.
+pub(crate) const CONFIRM_HINT: &str = "[y/Enter = discard, n/Esc = cancel]";
+
+/// Decide what `action` does to `entry`, the row under the cursor.
+///
+/// Rows are already split the way the actions are: a path changed in both the
+/// index and the work tree is two rows, one staged and one not. So staging is
+/// only ever asked of an unstaged row and unstaging of a staged one, and the
+/// wrong pairing is refused by name rather than run as a no-op that reports
+/// success.
+///
+/// A discard only ever touches the work tree. A staged row is refused rather
+/// than reset, because the index is the copy of the work a discard would
+/// otherwise take with it — unstaging first is one key, and it makes the loss
+/// two deliberate steps. A conflicted row is refused too: throwing away a
+/// half-resolved merge is `git checkout --merge`'s job or `git merge --abort`'s,
+/// and both are decisions about the whole merge rather than one row of it.
+pub(crate) fn prompt_for(action: FileAction, entry: &RenderEntry) -> FilePrompt {
+    let path = entry.path.as_str();
+    let shown = match &entry.orig_path {
+        Some(orig) => format!("{orig} → {path}"),
+        None => path.to_string(),
+    };
+    // A rename is two paths in the index — the old one gone, the new one
+    // added — and moving it in or out has to move both, or it lands half done.
+    let paths: Vec<&str> = entry
+        .orig_path
+        .as_deref()
+        .into_iter()
+        .chain(std::iter::once(path))
+        .collect();
+
+    match action {
+        FileAction::Stage if entry.staged => FilePrompt::Refuse {
+            message: format!("{shown} is already staged"),
+        },
+        FileAction::Stage => FilePrompt::Run {
+            command: FileCommand::new(action, &["add"], &paths),
+            success_message: format!("Staged {shown}"),
+        },
+        FileAction::Unstage if !entry.staged => FilePrompt::Refuse {
+            message: format!("{shown} has nothing staged"),
+        },
+        // `reset` rather than `restore --staged`: on a branch with no commits
+        // yet there is no HEAD to restore from, and `reset` reads that as the
+        // empty tree — which is exactly "not staged".
+        FileAction::Unstage => FilePrompt::Run {
+            command: FileCommand::new(action, &["reset", "-q"], &paths),
+            success_message: format!("Unstaged {shown}"),
+        },
+        FileAction::Discard if entry.staged => FilePrompt::Refuse {
+            message: format!("{shown} is staged — unstage it before discarding"),
+        },
+        FileAction::Discard => discard(entry, &shown),
+    }
+}
+
+/// The discard half of [`prompt_for`], for an unstaged row.
+fn discard(entry: &RenderEntry, shown: &str) -> FilePrompt {
+    let path = entry.path.as_str();
+    match entry.status {
+        FileStatus::Conflicted => FilePrompt::Refuse {
+            message: format!("{shown} has conflicts — resolve them rather than discard"),
+        },
+        // A work-tree rename is a deletion and an untracked file that git
+        // paired up. Undoing it is two different commands on two paths, and
+        // the second deletes a file — so it is left to git, not guessed at.
+        FileStatus::Renamed | FileStatus::Copied => FilePrompt::Refuse {
+            message: format!("{shown} is a rename — discard the two paths with git"),
+        },
+        // Untracked: nothing in git has a copy, so "discard" is deletion and
+        // says so. `-f` is needed whatever `clean.requireForce` says, and
+        // ignored files inside a directory survive, since there is no `-x`.
+        FileStatus::Untracked => FilePrompt::Confirm {
+            question: format!("Delete untracked {path}?"),
+            command: FileCommand::new(FileAction::Discard, &["clean", "-f", "-q"], &[path]),
+            success_message: format!("Deleted {path}"),
+        },
+        FileStatus::UntrackedDir => FilePrompt::Confirm {
+            question: format!("Delete untracked directory {path} and everything in it?"),
+            command: FileCommand::new(FileAction::Discard, &["clean", "-f", "-d", "-q"], &[path]),
+            success_message: format!("Deleted {path}"),
+        },
+        // Tracked: restore the work tree from the index, which is the copy the
+        // staged row — if there is one — already shows.
+        FileStatus::Modified | FileStatus::Added | FileStatus::Deleted | FileStatus::TypeChange => {
+            FilePrompt::Confirm {
+                question: format!("Discard unstaged changes to {path}?"),
+                command: FileCommand::new(FileAction::Discard, &["restore"], &[path]),
+                success_message: format!("Discarded changes to {path}"),
+            }
+        }
+    }
+}
+
+/// Run a file action on a thread of its own and hand the outcome to
+/// `on_finish`.
+///
+/// Off the render thread like a push, although it is local: a discard of a
+/// large untracked directory or a stage through a slow clean filter is long
+/// enough to freeze the countdown, and the outcome re-enters the loop through
+/// the same channel either way.
+pub(crate) fn spawn<F>(command: FileCommand, workdir: PathBuf, on_finish: F)
+where
+    F: FnOnce(GitOutcome) + Send + 'static,
+{
+    std::thread::spawn(move || on_finish(run_git(command.args(), &workdir)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::collect_changes;
+    use crate::testrepo::{git, init_repo};
+    use std::path::Path;
+
+    fn row(path: &str, status: FileStatus, staged: bool) -> RenderEntry {
+        RenderEntry {
+            path: path.to_string(),
+            orig_path: None,
+            status,
+            staged,
+            adds: 0,
+            dels: 0,
+            binary: false,
+            age: None,
+        }
+    }
+
+    fn command(prompt: &FilePrompt) -> &FileCommand {
+        match prompt {
+            FilePrompt::Run { command, .. } | FilePrompt::Confirm { command, .. } => command,
+            FilePrompt::Refuse { message } => panic!("expected a command, got {message:?}"),
+        }
+    }
+
+    /// Run the command `prompt` planned in `dir`, asserting git accepted it.
+    fn run(prompt: &FilePrompt, dir: &Path) {
+        let outcome = run_git(command(prompt).args(), dir);
+        assert!(outcome.success, "git refused: {}", outcome.output);
+    }
+
+    /// The `(path, staged)` rows gsw's own status walk reports for `dir`.
+    fn rows(dir: &Path) -> Vec<(String, bool)> {
+        let repo = gix::open(dir).expect("open fixture");
+        collect_changes(&repo)
+            .expect("status walk")
+            .entries
+            .into_iter()
+            .map(|entry| (entry.path, entry.staged))
+            .collect()
+    }
+
+    #[test]
+    fn staging_an_unstaged_row_runs_without_asking() {
+        let prompt = prompt_for(
+            FileAction::Stage,
+            &row("a.txt", FileStatus::Modified, false),
+        );
+        let FilePrompt::Run {
+            command,
+            success_message,
+        } = prompt
+        else {
+            panic!("staging is undone by one key, so it must not ask: {prompt:?}");
+        };
+        assert_eq!(command.args(), ["add", "--", ":(literal)a.txt"]);
+        assert_eq!(success_message, "Staged a.txt");
+    }
+
+    #[test]
+    fn the_wrong_side_of_a_file_is_refused_by_name() {
+        assert_eq!(
+            prompt_for(FileAction::Stage, &row("a.txt", FileStatus::Modified, true)),
+            FilePrompt::Refuse {
+                message: "a.txt is already staged".to_string()
+            },
+        );
+        assert_eq!(
+            prompt_for(
+                FileAction::Unstage,
+                &row("a.txt", FileStatus::Modified, false)
+            ),
+            FilePrompt::Refuse {
+                message: "a.txt has nothing staged".to_string()
+            },
+        );
+    }
+
+    #[test]
+    fn a_staged_rename_moves_both_paths() {
+        let mut renamed = row("new.txt", FileStatus::Renamed, true);
+        renamed.orig_path = Some("old.txt".to_string());
+        let prompt = prompt_for(FileAction::Unstage, &renamed);
+        assert_eq!(
+            command(&prompt).args(),
+            [
+                "reset",
+                "-q",
+                "--",
+                ":(literal)old.txt",
+                ":(literal)new.txt"
+            ],
+        );
+    }
+
+    #[test]
+    fn discarding_always_asks_first() {
+        for entry in [
+            row("a.txt", FileStatus::Modified, false),
+            row("a.txt", FileStatus::Deleted, false),
+            row("new.txt", FileStatus::Untracked, false),
+            row("dir/", FileStatus::UntrackedDir, false),
+        ] {
+            let prompt = prompt_for(FileAction::Discard, &entry);
+            assert!(
+                matches!(prompt, FilePrompt::Confirm { .. }),
+                "a discard of {entry:?} must ask, got {prompt:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn discarding_an_untracked_file_says_it_deletes_it() {
+        let FilePrompt::Confirm {
+            question, command, ..
+        } = prompt_for(
+            FileAction::Discard,
+            &row("new.txt", FileStatus::Untracked, false),
+        )
+        else {
+            panic!("expected a confirmation");
+        };
+        assert_eq!(question, "Delete untracked new.txt?");
+        assert_eq!(
+            command.args(),
+            ["clean", "-f", "-q", "--", ":(literal)new.txt"]
+        );
+    }
+
+    #[test]
+    fn discarding_a_staged_or_conflicted_row_is_refused() {
+        let staged = prompt_for(
+            FileAction::Discard,
+            &row("a.txt", FileStatus::Modified, true),
+        );
+        assert!(
+            matches!(&staged, FilePrompt::Refuse { message } if message.contains("unstage it")),
+            "got {staged:?}",
+        );
+        let conflicted = prompt_for(
+            FileAction::Discard,
+            &row("a.txt", FileStatus::Conflicted, false),
+        );
+        assert!(
+            matches!(&conflicted, FilePrompt::Refuse { message } if message.contains("conflicts")),
+            "got {conflicted:?}",
+        );
+    }
+
+    #[test]
+    fn stage_then_unstage_round_trips_through_the_index() {
+        let dir = init_repo();
+        let p = dir.path();
+        std::fs::write(p.join("a.txt"), "changed\n").expect("modify a.txt");
+
+        run(
+            &prompt_for(
+                FileAction::Stage,
+                &row("a.txt", FileStatus::Modified, false),
+            ),
+            p,
+        );
+        assert_eq!(rows(p), [("a.txt".to_string(), true)]);
+
+        run(
+            &prompt_for(
+                FileAction::Unstage,
+                &row("a.txt", FileStatus::Modified, true),
+            ),
+            p,
+        );
+        assert_eq!(rows(p), [("a.txt".to_string(), false)]);
+    }
+
+    #[test]
+    fn unstaging_works_before_the_first_commit() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let p = dir.path();
+        git(p, &["init", "-q", "-b", "main"]);
+        std::fs::write(p.join("first.txt"), "hello\n").expect("write first.txt");
+        git(p, &["add", "first.txt"]);
+
+        run(
+            &prompt_for(
+                FileAction::Unstage,
+                &row("first.txt", FileStatus::Added, true),
+            ),
+            p,
+        );
+        assert_eq!(rows(p), [("first.txt".to_string(), false)]);
+    }
+
+    #[test]
+    fn discarding_restores_the_file_and_deletes_the_untracked_one() {
+        let dir = init_repo();
+        let p = dir.path();
+        std::fs::write(p.join("a.txt"), "scribble\n").expect("modify a.txt");
+        std::fs::write(p.join("scratch.txt"), "junk\n").expect("write scratch.txt");
+
+        run(
+            &prompt_for(
+                FileAction::Discard,
+                &row("a.txt", FileStatus::Modified, false),
+            ),
+            p,
+        );
+        run(
+            &prompt_for(
+                FileAction::Discard,
+                &row("scratch.txt", FileStatus::Untracked, false),
+            ),
+            p,
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(p.join("a.txt")).expect("read a.txt"),
+            "initial\n"
+        );
+        assert!(
+            !p.join("scratch.txt").exists(),
+            "the untracked file must go"
+        );
+        assert!(rows(p).is_empty(), "nothing may be left changed");
+    }
+
+    #[test]
+    fn a_path_that_looks_like_a_glob_touches_only_itself() {
+        // Without literal pathspecs, staging a file named `*.txt` would stage
+        // every text file in the tree.
+        let dir = init_repo();
+        let p = dir.path();
+        std::fs::write(p.join("a.txt"), "changed\n").expect("modify a.txt");
+        std::fs::write(p.join("*.txt"), "star\n").expect("write *.txt");
+
+        run(
+            &prompt_for(
+                FileAction::Stage,
+                &row("*.txt", FileStatus::Untracked, false),
+            ),
+            p,
+        );
+        assert_eq!(
+            rows(p),
+            [("*.txt".to_string(), true), ("a.txt".to_string(), false)],
+        );
+    }
+}
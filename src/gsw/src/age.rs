@@ -115,22 +115,62 @@ pub const FADE_FLOOR: f32 = 0.30;
 /// Age at which the fade reaches the dark floor and stops darkening further.
 pub const FADE_DARKEST_AT: Duration = Duration::from_secs(2 * 60 * 60);
 
-/// Continuous fade factor in `[0.0, 1.0]` for a commit `age`.
+/// The shape of the age fade: how dark a row gets, and how long it takes to
+/// get there.
 ///
-/// `0.0` means use the full base color; `1.0` means use the dark floor.
-/// One smooth linear ramp from age=0 to [`FADE_DARKEST_AT`], then clamped
-/// at the floor — no per-bucket checkpoints, just a single gradient.
-pub fn age_fade_factor(age: Duration) -> f32 {
-    let secs = age.as_secs_f32();
-    let end = FADE_DARKEST_AT.as_secs_f32();
-    (secs / end).clamp(0.0, 1.0)
+/// [`FadeCurve::DEFAULT`] is [`FADE_FLOOR`] reached at [`FADE_DARKEST_AT`];
+/// the `[fade]` table of a config file can move either. Both ends are checked
+/// when the file is read — a floor outside `[0, 1]` or a zero-length ramp is
+/// refused there — so the methods here can assume a sane curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FadeCurve {
+    /// Fraction of base brightness a row keeps once fully faded.
+    pub floor: f32,
+    /// Age at which the fade reaches `floor` and stops darkening.
+    pub darkest_at: Duration,
+}
+
+impl FadeCurve {
+    /// The built-in curve.
+    pub const DEFAULT: Self = Self {
+        floor: FADE_FLOOR,
+        darkest_at: FADE_DARKEST_AT,
+    };
+
+    /// Continuous fade factor in `[0.0, 1.0]` for an `age`.
+    ///
+    /// `0.0` means use the full base color; `1.0` means use the dark floor.
+    /// One smooth linear ramp from age=0 to [`darkest_at`](Self::darkest_at),
+    /// then clamped at the floor — no per-bucket checkpoints, just a single
+    /// gradient.
+    pub fn factor(&self, age: Duration) -> f32 {
+        let secs = age.as_secs_f32();
+        let end = self.darkest_at.as_secs_f32();
+        (secs / end).clamp(0.0, 1.0)
+    }
+
+    /// Linearly interpolate `base` toward this curve's floor by `factor` in
+    /// `[0,1]`.
+    ///
+    /// `factor = 0` returns `base` unchanged; `factor = 1` returns
+    /// `base * floor` (rounded). Out-of-range factors are clamped.
+    pub fn rgb(&self, base: (u8, u8, u8), factor: f32) -> (u8, u8, u8) {
+        let f = factor.clamp(0.0, 1.0);
+        scale_rgb(base, 1.0 - f * (1.0 - self.floor))
+    }
+}
+
+impl Default for FadeCurve {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
 /// Multiply the brightness of `base` by `scale`, clamped to `[0.0, 1.0]`.
 ///
 /// `scale = 1.0` returns `base` unchanged; `scale = 0.0` returns black. The one
 /// place a color is dimmed, so every fade in gsw rounds and clamps the same way
-/// — the commit-log ramp, which stops at [`FADE_FLOOR`], and the push status
+/// — the commit-log ramp, which stops at the curve's floor, and the push status
 /// message, which goes all the way to black.
 pub fn scale_rgb(base: (u8, u8, u8), scale: f32) -> (u8, u8, u8) {
     let scale = scale.clamp(0.0, 1.0);
@@ -145,19 +185,21 @@ pub fn scale_rgb(base: (u8, u8, u8), scale: f32) -> (u8, u8, u8) {
     (scl(base.0), scl(base.1), scl(base.2))
 }
 
-/// Linearly interpolate `base` toward a dark floor by `factor` in `[0,1]`.
-///
-/// `factor = 0` returns `base` unchanged; `factor = 1` returns
-/// `base * FADE_FLOOR` (rounded). Out-of-range factors are clamped.
-pub fn fade_rgb(base: (u8, u8, u8), factor: f32) -> (u8, u8, u8) {
-    let f = factor.clamp(0.0, 1.0);
-    scale_rgb(base, 1.0 - f * (1.0 - FADE_FLOOR))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The built-in curve's factor — what every frame used before the curve
+    /// was configurable, and what these tests pin.
+    fn age_fade_factor(age: Duration) -> f32 {
+        FadeCurve::DEFAULT.factor(age)
+    }
+
+    /// The built-in curve's fade.
+    fn fade_rgb(base: (u8, u8, u8), factor: f32) -> (u8, u8, u8) {
+        FadeCurve::DEFAULT.rgb(base, factor)
+    }
+
     #[test]
     fn detailed_age_seconds_only() {
         assert_eq!(format_age_detailed(Duration::from_secs(0)), "0s");
@@ -455,4 +497,14 @@ mod tests {
         let floored = fade_rgb(base, 1.0);
         assert_eq!(above, floored, "factor > 1 should clamp to the floor color");
     }
+
+    #[test]
+    fn a_longer_curve_fades_more_slowly_to_a_different_floor() {
+        let curve = FadeCurve {
+            floor: 0.5,
+            darkest_at: Duration::from_secs(4 * 60 * 60),
+        };
+        assert!((curve.factor(Duration::from_secs(60 * 60)) - 0.25).abs() < 1e-6);
+        assert_eq!(curve.rgb((200, 100, 50), 1.0), (100, 50, 25));
+    }
 }
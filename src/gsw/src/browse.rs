@@ -0,0 +1,453 @@
+//! The cursor over the file rows in watch mode, and the diff it opens.
+//!
+//! The frame is still the thing gsw draws; this module only remembers which of
+//! its file rows the user is pointing at and, when asked, which row's diff is
+//! taking over the pane. It owns no git work of its own — the diff text is
+//! read by the watch loop's hook and handed in — so everything here is pure
+//! and testable against a list of rows.
+
+use colored::Colorize;
+
+use crate::render::{truncate_right, RenderEntry};
+use crate::watch::Dimensions;
+
+/// Which file row something refers to: its path, and which side of the index
+/// it is on.
+///
+/// A path changed in both the index and the work tree is two rows, and the
+/// cursor has to stay on the one the user chose when the other is above it,
+/// so the side is part of the identity rather than a detail of the row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RowKey {
+    /// The path the row shows, after any rename.
+    pub(crate) path: String,
+    /// Whether it is the staged row.
+    pub(crate) staged: bool,
+}
+
+impl RowKey {
+    /// The key of `entry`'s row.
+    pub(crate) fn of(entry: &RenderEntry) -> Self {
+        Self {
+            path: entry.path.clone(),
+            staged: entry.staged,
+        }
+    }
+
+    /// Whether `entry` is the row this key names.
+    pub(crate) fn names(&self, entry: &RenderEntry) -> bool {
+        entry.staged == self.staged && entry.path == self.path
+    }
+}
+
+/// Where the cursor is: the row it is on, and the position that row was drawn
+/// at.
+///
+/// The position is the fallback. The repository moves under the cursor — the
+/// row it was on gets staged in another pane, or committed, or the list
+/// re-sorts because something else was saved — and a cursor that vanished
+/// whenever that happened would have to be found again after every walk. So a
+/// row that is gone hands the cursor to whatever is drawn at its old position,
+/// which is usually the next row down, the way a list view behaves when the
+/// selected item is deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cursor {
+    key: RowKey,
+    index: usize,
+}
+
+impl Cursor {
+    /// Which of `rows` the cursor is on: the row it was on, wherever that is
+    /// now drawn, or the row at its old position when that one is gone. `None`
+    /// only when no rows are drawn at all.
+    ///
+    /// The one rule for where the cursor lands, used both by the render that
+    /// highlights a row and by [`Browser::settle`], which records it — so the
+    /// row a key acts on is the row that was highlighted.
+    pub(crate) fn resolve(&self, rows: &[RowKey]) -> Option<usize> {
+        rows.iter()
+            .position(|row| *row == self.key)
+            .or_else(|| rows.len().checked_sub(1).map(|last| self.index.min(last)))
+    }
+}
+
+/// A cursor or scroll movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Motion {
+    /// One row or line up (`k`, ↑).
+    Up,
+    /// One row or line down (`j`, ↓).
+    Down,
+    /// A page up (PgUp).
+    PageUp,
+    /// A page down (PgDn, Space).
+    PageDown,
+    /// The first row or line (`g`, Home).
+    Top,
+    /// The last row or line (`G`, End).
+    Bottom,
+}
+
+impl Motion {
+    /// Where this motion lands from `at` in a list of `len` items scrolled a
+    /// `page` at a time. Never past either end; `len` of zero lands on zero.
+    fn apply(self, at: usize, len: usize, page: usize) -> usize {
+        let last = len.saturating_sub(1);
+        let page = page.max(1);
+        match self {
+            Self::Up => at.saturating_sub(1),
+            Self::Down => at.saturating_add(1).min(last),
+            Self::PageUp => at.saturating_sub(page),
+            Self::PageDown => at.saturating_add(page).min(last),
+            Self::Top => 0,
+            Self::Bottom => last,
+        }
+    }
+}
+
+/// The diff of one row, taking over the pane.
+struct DiffView {
+    /// The row the diff is of.
+    key: RowKey,
+    /// The diff, as [`crate::repo::file_diff`] read it.
+    lines: Vec<String>,
+    /// The first line on screen.
+    scroll: usize,
+    /// How many lines the last paint had room for, which is what a page is.
+    page: usize,
+}
+
+/// The cursor and the diff view, owned by the watch loop beside the push UI.
+///
+/// Like [`crate::push::PushUi`], the loop asks it what is on screen rather
+/// than reaching in: [`Browser::cursor`] for the render, [`Browser::paint_diff`]
+/// for the pane, and [`Browser::diff_open`] for the key table.
+#[derive(Default)]
+pub(crate) struct Browser {
+    /// Where the cursor is, or `None` until the user first moves it. A frame
+    /// with no cursor looks exactly like the frame gsw drew before there was
+    /// one.
+    cursor: Option<Cursor>,
+    /// The file rows the last frame drew, top to bottom — what the cursor
+    /// moves over.
+    rows: Vec<RowKey>,
+    /// The open diff, if any.
+    diff: Option<DiffView>,
+}
+
+/// Rows of the diff view spent on its title.
+const DIFF_TITLE_ROWS: usize = 1;
+
+/// What the diff view's title bar advertises.
+const DIFF_HINT: &str = "[j/k scroll, Esc back]";
+
+/// What an empty diff says, rather than leaving an empty pane under the title.
+const NO_CHANGES: &str = "no changes on this side any more";
+
+/// Columns a tab expands to in the diff view. A terminal's own tab stops would
+/// put the cursor somewhere the width arithmetic did not count.
+const TAB: &str = "    ";
+
+impl Browser {
+    /// Where the cursor is, for the render to highlight.
+    pub(crate) fn cursor(&self) -> Option<&Cursor> {
+        self.cursor.as_ref()
+    }
+
+    /// The row the cursor is on, as the last frame drew it.
+    pub(crate) fn selected(&self) -> Option<&RowKey> {
+        self.cursor.as_ref().map(|cursor| &cursor.key)
+    }
+
+    /// Record the rows a frame just drew, and move the cursor onto the one the
+    /// frame highlighted (see [`Cursor::resolve`]).
+    pub(crate) fn settle(&mut self, rows: Vec<RowKey>) {
+        self.cursor = self.cursor.take().and_then(|cursor| {
+            cursor.resolve(&rows).map(|index| Cursor {
+                key: rows[index].clone(),
+                index,
+            })
+        });
+        self.rows = rows;
+    }
+
+    /// Move the cursor — or, with a diff open, scroll it.
+    ///
+    /// The first motion on a frame with no cursor places one at the end the
+    /// motion points away from: down starts at the top, up at the bottom,
+    /// which is where the eye already is for each.
+    pub(crate) fn motion(&mut self, motion: Motion) {
+        if let Some(view) = &mut self.diff {
+            view.scroll = motion.apply(view.scroll, view.lines.len(), view.page);
+            return;
+        }
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        let index = match &self.cursor {
+            Some(cursor) => motion.apply(cursor.index, len, len),
+            None => match motion {
+                Motion::Down | Motion::PageDown | Motion::Top => 0,
+                Motion::Up | Motion::PageUp | Motion::Bottom => len - 1,
+            },
+        };
+        self.cursor = Some(Cursor {
+            key: self.rows[index].clone(),
+            index,
+        });
+    }
+
+    /// Drop the cursor, leaving the frame as it was before one was placed.
+    pub(crate) fn clear(&mut self) {
+        self.cursor = None;
+    }
+
+    /// The row whose diff is open, if one is.
+    pub(crate) fn diff_open(&self) -> Option<&RowKey> {
+        self.diff.as_ref().map(|view| &view.key)
+    }
+
+    /// Show `lines` as the diff of `key`, from the top.
+    pub(crate) fn open_diff(&mut self, key: RowKey, lines: Vec<String>) {
+        self.diff = Some(DiffView {
+            key,
+            lines,
+            scroll: 0,
+            page: 0,
+        });
+    }
+
+    /// Replace the open diff's text with a fresh read, keeping the scroll
+    /// position — a walk re-reads the diff so an edit in another pane shows
+    /// up, and jumping back to the top on every save would lose the reader's
+    /// place. Does nothing when no diff is open.
+    pub(crate) fn refresh_diff(&mut self, lines: Vec<String>) {
+        if let Some(view) = &mut self.diff {
+            view.scroll = view.scroll.min(lines.len().saturating_sub(1));
+            view.lines = lines;
+        }
+    }
+
+    /// Close the diff and go back to the frame.
+    pub(crate) fn close_diff(&mut self) {
+        self.diff = None;
+    }
+
+    /// The open diff, laid out to fill `dims` exactly, or `None` with no diff
+    /// open.
+    ///
+    /// A title row naming the file, the side, and where in the diff the pane
+    /// is, then as many diff lines as fit — each cut to the width, so nothing
+    /// wraps or scrolls the pane, the same rule the frame follows. Hunk headers
+    /// are cyan, removals red, additions green.
+    ///
+    /// Takes `&mut self` to remember how many lines fitted, which is what the
+    /// next page motion moves by.
+    pub(crate) fn paint_diff(&mut self, dims: Dimensions) -> Option<String> {
+        let view = self.diff.as_mut()?;
+        let body_rows = dims.height.saturating_sub(DIFF_TITLE_ROWS).max(1);
+        view.page = body_rows;
+        let total = view.lines.len();
+        let first = view.scroll.min(total.saturating_sub(1));
+        let last = (first + body_rows).min(total);
+
+        let side = if view.key.staged {
+            "staged"
+        } else {
+            "unstaged"
+        };
+        let position = if total == 0 {
+            String::new()
+        } else {
+            format!(" • lines {}–{last} of {total}", first + 1)
+        };
+        let title = format!("{} • {side}{position}  {DIFF_HINT}", view.key.path);
+        let mut rows = vec![truncate_right(&title, dims.width).bold().to_string()];
+
+        if total == 0 {
+            rows.push(truncate_right(NO_CHANGES, dims.width).dimmed().to_string());
+        }
+        for line in &view.lines[first..last] {
+            let plain: String = line
+                .replace('\t', TAB)
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect();
+            let cut = truncate_right(&plain, dims.width);
+            rows.push(match line.chars().next() {
+                Some('@') => cut.cyan().to_string(),
+                Some('+') => cut.green().to_string(),
+                Some('-') => cut.red().to_string(),
+                _ => cut,
+            });
+        }
+        Some(rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testcolor::strip_ansi;
+
+    fn key(path: &str, staged: bool) -> RowKey {
+        RowKey {
+            path: path.to_string(),
+            staged,
+        }
+    }
+
+    fn three_rows() -> Vec<RowKey> {
+        vec![key("a", false), key("b", true), key("b", false)]
+    }
+
+    fn pane(height: usize) -> Dimensions {
+        Dimensions { width: 40, height }
+    }
+
+    #[test]
+    fn the_first_motion_places_the_cursor_at_the_end_it_points_away_from() {
+        let mut down = Browser::default();
+        down.settle(three_rows());
+        assert_eq!(
+            down.selected(),
+            None,
+            "there is no cursor until one is asked for"
+        );
+        down.motion(Motion::Down);
+        assert_eq!(down.selected(), Some(&key("a", false)));
+
+        let mut up = Browser::default();
+        up.settle(three_rows());
+        up.motion(Motion::Up);
+        assert_eq!(up.selected(), Some(&key("b", false)));
+    }
+
+    #[test]
+    fn the_cursor_stops_at_either_end() {
+        let mut browser = Browser::default();
+        browser.settle(three_rows());
+        browser.motion(Motion::Down);
+        browser.motion(Motion::Up);
+        assert_eq!(browser.selected(), Some(&key("a", false)));
+        for _ in 0..5 {
+            browser.motion(Motion::Down);
+        }
+        assert_eq!(browser.selected(), Some(&key("b", false)));
+    }
+
+    #[test]
+    fn the_cursor_follows_its_row_when_the_list_reorders() {
+        let mut browser = Browser::default();
+        browser.settle(three_rows());
+        browser.motion(Motion::Bottom);
+        // Something new sorted in above it.
+        browser.settle(vec![
+            key("new", false),
+            key("a", false),
+            key("b", true),
+            key("b", false),
+        ]);
+        assert_eq!(browser.selected(), Some(&key("b", false)));
+        browser.motion(Motion::Up);
+        assert_eq!(browser.selected(), Some(&key("b", true)));
+    }
+
+    #[test]
+    fn a_row_that_goes_away_hands_the_cursor_to_the_row_now_in_its_place() {
+        let mut browser = Browser::default();
+        browser.settle(three_rows());
+        browser.motion(Motion::Down);
+        browser.motion(Motion::Down);
+        // The staged half of `b` was committed in another pane.
+        browser.settle(vec![key("a", false), key("b", false)]);
+        assert_eq!(browser.selected(), Some(&key("b", false)));
+        // And then everything was.
+        browser.settle(Vec::new());
+        assert_eq!(browser.selected(), None);
+    }
+
+    #[test]
+    fn resolve_agrees_with_settle() {
+        // The render highlights what `resolve` picks and the keys act on what
+        // `settle` records; the two must be the same row.
+        let mut browser = Browser::default();
+        browser.settle(three_rows());
+        browser.motion(Motion::Bottom);
+        let shrunk = vec![key("a", false)];
+        let highlighted = browser.cursor().and_then(|cursor| cursor.resolve(&shrunk));
+        browser.settle(shrunk.clone());
+        assert_eq!(highlighted.map(|index| &shrunk[index]), browser.selected());
+    }
+
+    #[test]
+    fn a_diff_fills_the_pane_exactly_and_scrolls_by_the_page_it_drew() {
+        let mut browser = Browser::default();
+        let lines: Vec<String> = (1..=10).map(|n| format!("+line {n}")).collect();
+        browser.open_diff(key("a", false), lines);
+
+        let painted = browser.paint_diff(pane(4)).expect("a diff is open");
+        let rows: Vec<String> = painted.lines().map(strip_ansi).collect();
+        assert_eq!(rows.len(), 4, "title plus three lines, got {rows:?}");
+        assert!(
+            rows[0].starts_with("a • unstaged • lines 1–3 of 10"),
+            "got {:?}",
+            rows[0]
+        );
+        assert_eq!(rows[1], "+line 1");
+
+        browser.motion(Motion::PageDown);
+        let painted = browser.paint_diff(pane(4)).expect("a diff is open");
+        assert_eq!(
+            strip_ansi(painted.lines().nth(1).expect("a body row")),
+            "+line 4"
+        );
+
+        browser.motion(Motion::Bottom);
+        let painted = browser.paint_diff(pane(4)).expect("a diff is open");
+        let rows: Vec<String> = painted.lines().map(strip_ansi).collect();
+        assert_eq!(rows.last().map(String::as_str), Some("+line 10"));
+    }
+
+    #[test]
+    fn diff_lines_never_wrap_the_pane() {
+        let mut browser = Browser::default();
+        browser.open_diff(key("a", false), vec![format!("+{}", "\tx".repeat(40))]);
+        let painted = browser.paint_diff(pane(5)).expect("a diff is open");
+        for row in painted.lines() {
+            let width = unicode_width::UnicodeWidthStr::width(strip_ansi(row).as_str());
+            assert!(width <= 40, "{row:?} is {width} columns wide");
+        }
+    }
+
+    #[test]
+    fn a_refreshed_diff_keeps_its_place_and_an_empty_one_says_so() {
+        let mut browser = Browser::default();
+        browser.open_diff(key("a", true), (1..=10).map(|n| format!(" {n}")).collect());
+        browser.motion(Motion::Down);
+        browser.motion(Motion::Down);
+        browser.refresh_diff((1..=10).map(|n| format!(" {n}!")).collect());
+        let painted = browser.paint_diff(pane(3)).expect("a diff is open");
+        assert_eq!(
+            strip_ansi(painted.lines().nth(1).expect("a body row")),
+            " 3!"
+        );
+
+        browser.refresh_diff(Vec::new());
+        let painted = strip_ansi(&browser.paint_diff(pane(3)).expect("a diff is open"));
+        assert!(painted.contains(NO_CHANGES), "got {painted:?}");
+    }
+
+    #[test]
+    fn motions_scroll_the_diff_instead_of_moving_the_cursor() {
+        let mut browser = Browser::default();
+        browser.settle(three_rows());
+        browser.motion(Motion::Down);
+        browser.open_diff(key("a", false), vec![" x".to_string(), " y".to_string()]);
+        browser.motion(Motion::Down);
+        assert_eq!(browser.selected(), Some(&key("a", false)));
+        browser.close_diff();
+        assert_eq!(browser.paint_diff(pane(3)), None);
+    }
+}
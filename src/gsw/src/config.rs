@@ -0,0 +1,535 @@
+//! Config files: defaults for gsw's flags, and the [`Theme`] a frame is drawn
+//! with.
+//!
+//! Two files are read, both optional and both TOML: the per-user
+//! `<config_dir>/gsw/config.toml`, then the repository's own `.gsw.toml` at the
+//! root of its work tree. Each is laid over what came before it, key by key —
+//! a repository that only sets `[defaults] base` keeps the user's palette —
+//! and the command line goes on top of both. `--print-config` writes the
+//! result back out in the same format, so the quickest way to start a file is
+//! to redirect it.
+//!
+//! ```toml
+//! [defaults]           # any flag gsw takes a value for
+//! log_lines = 5
+//! truecolor = true
+//!
+//! [layout]
+//! sections = ["files", "log", "reflog", "stash"]
+//!
+//! [fade]
+//! floor = 0.3          # brightness a row keeps once fully faded
+//! darkest_at_secs = 7200
+//!
+//! [colors]             # truecolor bases, `#rrggbb`
+//! bar = "#3cc8c8"
+//! ```
+//!
+//! Unknown keys are refused rather than ignored: a typo that silently fell
+//! back to the default would look exactly like a setting that does not work.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::theme::{Hue, Rgb, Section, Theme};
+use crate::{DEFAULT_REFRESH_SECS, MAX_REFRESH_SECS};
+
+/// The per-repository config file, at the root of the work tree.
+pub(crate) const REPO_CONFIG_FILE: &str = ".gsw.toml";
+
+/// Defaults for the flags that take a value. A flag given on the command line
+/// wins over all of these.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Defaults {
+    /// `--base`; `None` resolves main, then master, then origin/HEAD.
+    pub base: Option<String>,
+    /// `--max-files`; `None` lets the file list take what the terminal leaves.
+    pub max_files: Option<usize>,
+    /// `--bar-width`.
+    pub bar_width: usize,
+    /// `--width-offset`.
+    pub width_offset: usize,
+    /// `--log-lines`; `--no-log` is `0`.
+    pub log_lines: usize,
+    /// `--reflog-lines`.
+    pub reflog_lines: usize,
+    /// `--stash-lines`.
+    pub stash_lines: usize,
+    /// `--refresh-interval`, in seconds.
+    pub refresh_interval: u64,
+    /// `--truecolor` (`Some(true)`) or `--no-truecolor` (`Some(false)`);
+    /// `None` leaves it to `COLORTERM`.
+    pub truecolor: Option<bool>,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            base: None,
+            max_files: None,
+            bar_width: 6,
+            width_offset: 0,
+            log_lines: 20,
+            reflog_lines: 0,
+            stash_lines: 0,
+            refresh_interval: DEFAULT_REFRESH_SECS,
+            truecolor: None,
+        }
+    }
+}
+
+/// Everything the config files decide, resolved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Settings {
+    /// Flag defaults.
+    pub defaults: Defaults,
+    /// The palette, fade curve, and section order.
+    pub theme: Theme,
+}
+
+/// [`Settings`] plus the files they were read from, in the order applied.
+#[derive(Debug, Default)]
+pub(crate) struct Loaded {
+    /// The resolved settings.
+    pub settings: Settings,
+    /// Every file that existed and was applied.
+    pub sources: Vec<PathBuf>,
+}
+
+/// One config file, as written. Every key is optional; an absent one leaves
+/// whatever the layers under it decided.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    defaults: RawDefaults,
+    layout: RawLayout,
+    fade: RawFade,
+    /// Hue key to `#rrggbb`. A map rather than a struct so an unknown key can
+    /// be answered with the list of known ones.
+    colors: BTreeMap<String, String>,
+}
+
+/// The `[defaults]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawDefaults {
+    base: Option<String>,
+    max_files: Option<usize>,
+    bar_width: Option<usize>,
+    width_offset: Option<usize>,
+    log_lines: Option<usize>,
+    reflog_lines: Option<usize>,
+    stash_lines: Option<usize>,
+    refresh_interval: Option<u64>,
+    truecolor: Option<bool>,
+}
+
+/// The `[layout]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawLayout {
+    sections: Option<Vec<String>>,
+}
+
+/// The `[fade]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawFade {
+    floor: Option<f32>,
+    darkest_at_secs: Option<u64>,
+}
+
+impl FileConfig {
+    /// Parse one file's text.
+    fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Lay this file over `settings`, checking every value it sets.
+    ///
+    /// Nothing is applied unless everything is valid, so a bad file cannot
+    /// leave the settings half-changed.
+    fn apply(self, settings: &Settings) -> Result<Settings> {
+        let mut next = settings.clone();
+        let defaults = &mut next.defaults;
+        let raw = self.defaults;
+        if raw.base.is_some() {
+            defaults.base = raw.base;
+        }
+        if raw.max_files.is_some() {
+            defaults.max_files = raw.max_files;
+        }
+        if let Some(refresh_interval) = raw.refresh_interval {
+            if refresh_interval > MAX_REFRESH_SECS {
+                bail!(
+                    "[defaults] refresh_interval = {refresh_interval}: at most \
+                     {MAX_REFRESH_SECS} seconds (a year)"
+                );
+            }
+            defaults.refresh_interval = refresh_interval;
+        }
+        defaults.bar_width = raw.bar_width.unwrap_or(defaults.bar_width);
+        defaults.width_offset = raw.width_offset.unwrap_or(defaults.width_offset);
+        defaults.log_lines = raw.log_lines.unwrap_or(defaults.log_lines);
+        defaults.reflog_lines = raw.reflog_lines.unwrap_or(defaults.reflog_lines);
+        defaults.stash_lines = raw.stash_lines.unwrap_or(defaults.stash_lines);
+        if raw.truecolor.is_some() {
+            defaults.truecolor = raw.truecolor;
+        }
+
+        let theme = &mut next.theme;
+        if let Some(sections) = self.layout.sections {
+            theme.sections = parse_sections(&sections)?;
+        }
+        if let Some(floor) = self.fade.floor {
+            if !(0.0..=1.0).contains(&floor) {
+                bail!("[fade] floor = {floor}: expected a fraction from 0.0 to 1.0");
+            }
+            theme.fade.floor = floor;
+        }
+        if let Some(secs) = self.fade.darkest_at_secs {
+            if secs == 0 {
+                bail!("[fade] darkest_at_secs = 0: the fade needs at least a second to run");
+            }
+            theme.fade.darkest_at = Duration::from_secs(secs);
+        }
+        for (key, value) in self.colors {
+            let Some(hue) = Hue::parse(&key) else {
+                let known: Vec<&str> = Hue::ALL.into_iter().map(Hue::key).collect();
+                bail!(
+                    "unknown color {key:?} in [colors]; known colors are: {}",
+                    known.join(", ")
+                );
+            };
+            let Some(rgb) = parse_hex(&value) else {
+                bail!("[colors] {key} = {value:?}: expected a color like \"#5adc6e\"");
+            };
+            theme.palette[hue] = rgb;
+        }
+        Ok(next)
+    }
+}
+
+/// The `[layout] sections` list, which must name every section exactly once.
+///
+/// Dropping a section here is refused rather than read as "hide it": each
+/// section already has its own switch (`log_lines = 0`, and so on), and a
+/// second way to hide one would leave the row allocator planning rows for
+/// something that never draws.
+fn parse_sections(names: &[String]) -> Result<[Section; 4]> {
+    let every = || {
+        Section::ALL
+            .into_iter()
+            .map(Section::key)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut sections = Vec::with_capacity(Section::ALL.len());
+    for name in names {
+        let Some(section) = Section::parse(name) else {
+            bail!(
+                "unknown section {name:?} in [layout] sections; sections are: {}",
+                every()
+            );
+        };
+        if sections.contains(&section) {
+            bail!("[layout] sections lists {name:?} twice");
+        }
+        sections.push(section);
+    }
+    sections.try_into().map_err(|_: Vec<Section>| {
+        anyhow::anyhow!(
+            "[layout] sections must list each of {} exactly once",
+            every()
+        )
+    })
+}
+
+/// `#rrggbb`, case-insensitive.
+fn parse_hex(value: &str) -> Option<Rgb> {
+    let digits = value.strip_prefix('#')?;
+    if digits.len() != 6 {
+        return None;
+    }
+    let channel = |at: usize| u8::from_str_radix(digits.get(at..at + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// The per-user config file: `<config_dir>/gsw/config.toml`, where
+/// `<config_dir>` is the platform's ([`dirs::config_dir`]). `None` when the
+/// platform has none.
+pub(crate) fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("gsw").join("config.toml"))
+}
+
+/// Read the per-user file, then `repo_root`'s `.gsw.toml` when there is a
+/// repository to read it from.
+///
+/// # Errors
+///
+/// A file that exists but cannot be read, is not valid TOML, or sets something
+/// gsw does not accept. The error names the file.
+pub(crate) fn load(repo_root: Option<&Path>) -> Result<Loaded> {
+    let candidates = user_config_path()
+        .into_iter()
+        .chain(repo_root.map(|root| root.join(REPO_CONFIG_FILE)));
+    load_from(candidates)
+}
+
+/// The testable core of [`load`]: apply every candidate that exists, in order.
+/// A candidate that is not there is skipped — neither file is required.
+fn load_from(candidates: impl IntoIterator<Item = PathBuf>) -> Result<Loaded> {
+    let mut loaded = Loaded::default();
+    for path in candidates {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("reading {}", path.display()));
+            }
+        };
+        loaded.settings = FileConfig::parse(&text)
+            .and_then(|file| file.apply(&loaded.settings))
+            .with_context(|| format!("in config file {}", path.display()))?;
+        loaded.sources.push(path);
+    }
+    Ok(loaded)
+}
+
+/// The effective settings as a config file — what `--print-config` prints.
+///
+/// Every key is written out, so the output doubles as a starting point: saved
+/// as a config file it reproduces these settings exactly. A setting that is
+/// unset (`base`, `max_files`, `truecolor`) has no TOML spelling, so it is
+/// written as a comment saying what happens instead.
+pub(crate) fn describe(loaded: &Loaded) -> String {
+    let mut out = String::new();
+    let Settings { defaults, theme } = &loaded.settings;
+    let _ = writeln!(
+        out,
+        "# gsw settings: the built-in defaults, then each file below, then the command line."
+    );
+    if loaded.sources.is_empty() {
+        let _ = writeln!(out, "# (no config files were found)");
+    }
+    for source in &loaded.sources {
+        let _ = writeln!(out, "# {}", source.display());
+    }
+
+    let _ = writeln!(out, "\n[defaults]");
+    match &defaults.base {
+        Some(base) => {
+            let _ = writeln!(out, "base = {}", toml_string(base));
+        }
+        None => {
+            let _ = writeln!(out, "# base is unset: main, then master, then origin/HEAD");
+        }
+    }
+    match defaults.max_files {
+        Some(max_files) => {
+            let _ = writeln!(out, "max_files = {max_files}");
+        }
+        None => {
+            let _ = writeln!(out, "# max_files is unset: the file list takes what fits");
+        }
+    }
+    let _ = writeln!(out, "bar_width = {}", defaults.bar_width);
+    let _ = writeln!(out, "width_offset = {}", defaults.width_offset);
+    let _ = writeln!(out, "log_lines = {}", defaults.log_lines);
+    let _ = writeln!(out, "reflog_lines = {}", defaults.reflog_lines);
+    let _ = writeln!(out, "stash_lines = {}", defaults.stash_lines);
+    let _ = writeln!(out, "refresh_interval = {}", defaults.refresh_interval);
+    match defaults.truecolor {
+        Some(truecolor) => {
+            let _ = writeln!(out, "truecolor = {truecolor}");
+        }
+        None => {
+            let _ = writeln!(out, "# truecolor is unset: on when COLORTERM says so");
+        }
+    }
+
+    let sections: Vec<String> = theme
+        .sections
+        .iter()
+        .map(|section| toml_string(section.key()))
+        .collect();
+    let _ = writeln!(out, "\n[layout]\nsections = [{}]", sections.join(", "));
+
+    let _ = writeln!(
+        out,
+        "\n[fade]\nfloor = {}\ndarkest_at_secs = {}",
+        theme.fade.floor,
+        theme.fade.darkest_at.as_secs()
+    );
+
+    let _ = writeln!(out, "\n[colors]");
+    for hue in Hue::ALL {
+        let (r, g, b) = theme.palette[hue];
+        let _ = writeln!(out, "{} = \"#{r:02x}{g:02x}{b:02x}\"", hue.key());
+    }
+    out
+}
+
+/// `value` as a TOML basic string, escaped.
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Palette;
+
+    fn applied(text: &str) -> Result<Settings> {
+        FileConfig::parse(text)?.apply(&Settings::default())
+    }
+
+    #[test]
+    fn an_empty_file_changes_nothing() {
+        assert_eq!(applied("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn every_table_reaches_the_settings() {
+        let settings = applied(
+            r##"
+            [defaults]
+            base = "develop"
+            max_files = 12
+            log_lines = 5
+            truecolor = false
+
+            [layout]
+            sections = ["files", "log", "reflog", "stash"]
+
+            [fade]
+            floor = 0.5
+            darkest_at_secs = 600
+
+            [colors]
+            bar = "#FF8800"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(settings.defaults.base.as_deref(), Some("develop"));
+        assert_eq!(settings.defaults.max_files, Some(12));
+        assert_eq!(settings.defaults.log_lines, 5);
+        assert_eq!(settings.defaults.truecolor, Some(false));
+        assert_eq!(settings.defaults.bar_width, Defaults::default().bar_width);
+        assert_eq!(settings.theme.sections[0], Section::Files);
+        assert!((settings.theme.fade.floor - 0.5).abs() < f32::EPSILON);
+        assert_eq!(settings.theme.fade.darkest_at, Duration::from_secs(600));
+        assert_eq!(settings.theme.palette[Hue::Bar], (255, 136, 0));
+        assert_eq!(
+            settings.theme.palette[Hue::Adds],
+            Palette::DEFAULT[Hue::Adds]
+        );
+    }
+
+    #[test]
+    fn a_typo_is_refused_rather_than_ignored() {
+        let err = applied("[defaults]\nlog_line = 5\n").unwrap_err();
+        assert!(format!("{err:#}").contains("log_line"), "{err:#}");
+
+        let err = applied("[colors]\nbars = \"#000000\"\n").unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("\"bars\""), "{message}");
+        assert!(
+            message.contains("bar_conflict"),
+            "names the known colors: {message}"
+        );
+    }
+
+    #[test]
+    fn out_of_range_values_are_refused() {
+        for text in [
+            "[colors]\nbar = \"3cc8c8\"\n",
+            "[colors]\nbar = \"#3cc8c\"\n",
+            "[colors]\nbar = \"#3cc8zz\"\n",
+            "[fade]\nfloor = 1.5\n",
+            "[fade]\ndarkest_at_secs = 0\n",
+            "[defaults]\nrefresh_interval = 999999999\n",
+        ] {
+            assert!(applied(text).is_err(), "{text:?} should be refused");
+        }
+    }
+
+    #[test]
+    fn the_section_order_names_every_section_once() {
+        assert!(applied("[layout]\nsections = [\"files\", \"log\"]\n").is_err());
+        assert!(
+            applied("[layout]\nsections = [\"log\", \"log\", \"reflog\", \"stash\"]\n").is_err()
+        );
+        assert!(
+            applied("[layout]\nsections = [\"log\", \"reflog\", \"stashes\", \"files\"]\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn later_files_win_key_by_key_and_missing_ones_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("user.toml");
+        let repo = dir.path().join("repo.toml");
+        std::fs::write(&user, "[defaults]\nlog_lines = 5\nbar_width = 9\n").unwrap();
+        std::fs::write(&repo, "[defaults]\nlog_lines = 2\n").unwrap();
+
+        let loaded =
+            load_from([user.clone(), dir.path().join("absent.toml"), repo.clone()]).unwrap();
+        assert_eq!(loaded.sources, [user, repo]);
+        assert_eq!(
+            loaded.settings.defaults.log_lines, 2,
+            "the repository's file wins"
+        );
+        assert_eq!(
+            loaded.settings.defaults.bar_width, 9,
+            "what it leaves alone stays"
+        );
+    }
+
+    #[test]
+    fn a_bad_file_is_named_in_the_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(REPO_CONFIG_FILE);
+        std::fs::write(&path, "[fade]\nfloor = -1.0\n").unwrap();
+        let err = load_from([path]).unwrap_err();
+        assert!(format!("{err:#}").contains(REPO_CONFIG_FILE), "{err:#}");
+    }
+
+    #[test]
+    fn the_printed_settings_read_back_as_the_same_settings() {
+        let mut settings = applied(
+            "[defaults]\nbase = \"trunk \\\"x\\\"\"\nmax_files = 3\ntruecolor = true\n\
+             [fade]\nfloor = 0.45\n[colors]\nlog_hash = \"#010203\"\n",
+        )
+        .unwrap();
+        settings.theme.sections = [
+            Section::Stash,
+            Section::Files,
+            Section::Reflog,
+            Section::Log,
+        ];
+        let printed = describe(&Loaded {
+            settings: settings.clone(),
+            sources: Vec::new(),
+        });
+        assert_eq!(applied(&printed).unwrap(), settings, "{printed}");
+    }
+
+    #[test]
+    fn unset_settings_print_as_comments() {
+        let printed = describe(&Loaded::default());
+        assert!(printed.contains("# base is unset"), "{printed}");
+        assert!(printed.contains("# truecolor is unset"), "{printed}");
+        assert!(
+            printed.contains("(no config files were found)"),
+            "{printed}"
+        );
+        assert_eq!(applied(&printed).unwrap(), Settings::default(), "{printed}");
+    }
+}
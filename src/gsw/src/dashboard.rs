@@ -0,0 +1,741 @@
+//! Multi-repository dashboard: one compact block per repository in a single
+//! gsw session.
+//!
+//! Running one gsw per pane for every repository in play costs a pane, a
+//! process, and a `notify` watcher apiece. The dashboard folds them into one:
+//! each repository keeps its own [`Snapshot`], walked and rendered exactly as a
+//! single-repository session would, but they share the watch loop in
+//! [`crate::watch`] — one channel, one walk schedule, one filesystem watcher —
+//! and the terminal's rows are divided between them by [`fair_shares`] before
+//! each block splits its share between files and commits with the same
+//! [`plan_section_caps`](crate::render::plan_section_caps) planning every gsw
+//! frame uses.
+//!
+//! A repository stays a [`Snapshot`] here, not a summary of one, so everything
+//! a block shows — fades, ages, the operation indicator, the `+N more files`
+//! footer — is the single-repository renderer's and cannot drift from it.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use repowalker::RepoWalker;
+
+use crate::browse::Cursor;
+use crate::render::{truncate_left, RenderEntry, Snapshot};
+use crate::repo::RepoHandle;
+use crate::watch::{
+    self, Changed, Dimensions, LiveIgnore, Seed, TerminalGuard, WatchRoot, Watched,
+};
+use crate::{frame_chrome, render_frame, section_demand, FrameTiming, Render, RenderConfig};
+
+/// One repository on the dashboard, as the user will see it named.
+pub(crate) struct Member {
+    /// What the block's title row calls the repository: the path as it was
+    /// typed, or its path under the `--discover` root.
+    pub(crate) label: String,
+    /// The open repository.
+    pub(crate) handle: RepoHandle,
+}
+
+/// Open every repository the command line names, in the order it names them:
+/// the positional paths first, then whatever `--discover` finds under `root`.
+///
+/// Two paths that land on the same work tree — `.` and its absolute spelling,
+/// or a discovered repository also named outright — are one repository, and
+/// are shown once under the first label they were given. Watching one work
+/// tree twice would double its walk for two identical blocks.
+///
+/// # Errors
+///
+/// A path named outright that is not inside a repository with a work tree is an
+/// error: the user asked for that repository specifically, and quietly leaving
+/// it off the dashboard would look like a repository with nothing to show.
+/// Discovery has no such contract — a directory that fails to open is skipped.
+pub(crate) fn open_members(paths: &[PathBuf], root: Option<&Path>) -> Result<Vec<Member>> {
+    let mut members: Vec<Member> = Vec::new();
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut admit = |label: String, handle: RepoHandle, members: &mut Vec<Member>| {
+        let Some(workdir) = handle.repo().workdir() else {
+            return;
+        };
+        let key = workdir
+            .canonicalize()
+            .unwrap_or_else(|_| workdir.to_path_buf());
+        if !seen.contains(&key) {
+            seen.push(key);
+            members.push(Member { label, handle });
+        }
+    };
+
+    for path in paths {
+        let Some(handle) = RepoHandle::discover(path) else {
+            bail!("{}: not a git repository", path.display());
+        };
+        admit(path.display().to_string(), handle, &mut members);
+    }
+    if let Some(root) = root {
+        for found in discover(root) {
+            if let Some(handle) = RepoHandle::discover(&found) {
+                admit(discovered_label(root, &found), handle, &mut members);
+            }
+        }
+    }
+    Ok(members)
+}
+
+/// Every repository and linked worktree under `root`, sorted by path.
+///
+/// Walks with [`RepoWalker`] so the usual exclusions hold — `node_modules`,
+/// hidden directories, and anything a `.gitignore` on the way down ignores —
+/// except that worktrees are *kept*: RepoWalker skips them by default because
+/// its other callers want each repository once, but a worktree is exactly what
+/// someone juggling several branches wants a block for. A directory counts when
+/// it holds a `.git` entry of either kind: a directory for a main checkout, a
+/// `gitdir:` file for a linked worktree or submodule.
+///
+/// `root` itself counts too, so pointing `--discover` at a single repository
+/// finds it rather than nothing.
+pub(crate) fn discover(root: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = RepoWalker::new(root.to_path_buf())
+        .skip_worktrees(false)
+        .walk_with_ignore()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_dir()))
+        .map(ignore::DirEntry::into_path)
+        .filter(|dir| dir.join(".git").exists())
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// The title a discovered repository is shown under: its path relative to the
+/// `--discover` root, which is the part that tells sibling checkouts apart.
+/// The root itself, which has no relative path, keeps the root as typed.
+fn discovered_label(root: &Path, found: &Path) -> String {
+    match found.strip_prefix(root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+        _ => root.display().to_string(),
+    }
+}
+
+/// Everything the dashboard shows: one pane per repository, in command-line
+/// order.
+pub(crate) struct Dashboard {
+    /// The blocks, top to bottom.
+    pub(crate) panes: Vec<Pane>,
+}
+
+/// One repository's block as of the last walk.
+pub(crate) struct Pane {
+    /// The block's title.
+    pub(crate) label: String,
+    /// The last snapshot this repository produced, or `None` when it has never
+    /// been readable.
+    pub(crate) snapshot: Option<Snapshot>,
+    /// How old `snapshot` already was when the dashboard was collected —
+    /// `Duration::ZERO` for a repository this walk read, and the time since its
+    /// last good walk for one it could not.
+    ///
+    /// The watch loop ages the whole dashboard from one collection instant, so
+    /// without this a repository that failed a walk would have its old
+    /// snapshot painted as though it had just been read. Added to every age
+    /// the block paints, it keeps a stale block visibly stale — the same
+    /// "wrong but labeled old" rule the single-repository loop follows. A
+    /// repository the walk skipped because nothing under it changed is aged
+    /// the same way, from the walk that last read it.
+    pub(crate) lag: Duration,
+    /// Whether the last attempt to read this repository failed, so `snapshot`
+    /// is one kept from an earlier walk rather than merely an unchanged one.
+    pub(crate) stale: bool,
+}
+
+impl Watched for Dashboard {
+    /// Never a single repository: `p` and the file-action keys on the
+    /// dashboard are refused rather than aimed at whichever block happens to be
+    /// first.
+    fn target(&self) -> Option<&Snapshot> {
+        None
+    }
+}
+
+/// The dashboard's repositories plus what each needs to be re-walked: the
+/// live ignore matcher its share of the watcher filters with, and its last good
+/// snapshot.
+pub(crate) struct Board {
+    /// One entry per repository, in display order.
+    tracked: Vec<Tracked>,
+}
+
+/// One repository under watch.
+struct Tracked {
+    /// The block's title.
+    label: String,
+    /// The repository, re-opened on every walk.
+    handle: RepoHandle,
+    /// The ignore set the watcher filters this repository's events with.
+    ignore: LiveIgnore,
+    /// The last snapshot a walk produced and when that walk started.
+    last: Option<(Snapshot, Instant)>,
+    /// Whether the last walk of this repository failed. A failing repository
+    /// is retried on every pass, changed or not.
+    failing: bool,
+}
+
+impl Board {
+    /// Start tracking `members`, building each one's ignore matcher from the
+    /// handle just opened.
+    pub(crate) fn new(members: Vec<Member>) -> Self {
+        let tracked = members
+            .into_iter()
+            .map(|Member { label, handle }| Tracked {
+                ignore: LiveIgnore::new(handle.repo()),
+                label,
+                handle,
+                last: None,
+                failing: false,
+            })
+            .collect();
+        Self { tracked }
+    }
+
+    /// What the shared filesystem watcher should cover: every repository's
+    /// work tree and git dirs, each filtered by its own ignore set and known by
+    /// its index on the board.
+    pub(crate) fn watch_roots(&self) -> Vec<WatchRoot> {
+        self.tracked
+            .iter()
+            .enumerate()
+            .filter_map(|(id, t)| WatchRoot::of(t.handle.repo(), &t.ignore, id))
+            .collect()
+    }
+
+    /// Walk the repositories `changed` names, the way [`watch::walk`] walks
+    /// one, and keep every other block as its last walk left it.
+    ///
+    /// The watcher tags each event with the root it fell under, so a change in
+    /// one repository re-reads that repository alone; the loop's coalescing
+    /// still makes a burst one pass, over however many repositories it
+    /// touched. The duty-cycle budget measures the whole pass, so a dashboard
+    /// of slow repositories backs off as one.
+    ///
+    /// A repository whose walk fails keeps its last good snapshot, aged by
+    /// [`Pane::lag`], so one repository mid-`gc` never blanks its block — let
+    /// alone the dashboard — and is retried on every pass until it reads
+    /// again. `now` is when the pass started.
+    pub(crate) fn walk(
+        &mut self,
+        cfg: &RenderConfig,
+        now: Instant,
+        changed: &Changed,
+    ) -> Dashboard {
+        let panes = self
+            .tracked
+            .iter_mut()
+            .enumerate()
+            .map(|(id, t)| {
+                if changed.includes(id) || t.failing {
+                    match watch::walk(&mut t.handle, &t.ignore, cfg) {
+                        Ok(snapshot) => {
+                            t.last = Some((snapshot, now));
+                            t.failing = false;
+                        }
+                        Err(_) => t.failing = true,
+                    }
+                }
+                Pane {
+                    label: t.label.clone(),
+                    snapshot: t.last.as_ref().map(|(snapshot, _)| snapshot.clone()),
+                    lag: t
+                        .last
+                        .as_ref()
+                        .map_or(Duration::ZERO, |(_, at)| now.saturating_duration_since(*at)),
+                    stale: t.failing,
+                }
+            })
+            .collect();
+        Dashboard { panes }
+    }
+}
+
+/// Render the dashboard once and return the frame — the one-shot path.
+///
+/// # Errors
+///
+/// Fails when [`collect_once`] does.
+pub(crate) fn build_output(
+    members: Vec<Member>,
+    cfg: &RenderConfig,
+    dims: Dimensions,
+) -> Result<Render> {
+    Ok(render_dashboard(
+        &collect_once(members, cfg)?,
+        cfg,
+        dims,
+        FrameTiming::at_walk(None),
+    ))
+}
+
+/// Walk every repository once — the one-shot paths, text and JSON alike.
+///
+/// # Errors
+///
+/// Fails only when no repository could be read at all: a dashboard of
+/// placeholder rows says less than the error would.
+pub(crate) fn collect_once(members: Vec<Member>, cfg: &RenderConfig) -> Result<Dashboard> {
+    let mut board = Board::new(members);
+    let dashboard = board.walk(cfg, Instant::now(), &Changed::All);
+    if dashboard.panes.iter().all(|pane| pane.snapshot.is_none()) {
+        bail!("none of the repositories could be read");
+    }
+    Ok(dashboard)
+}
+
+/// Run the dashboard as a live watch session until the user quits.
+///
+/// Everything but the collect and the render is the single-repository session's
+/// (see [`watch::session`]): the same keys, the same walk schedule and refresh
+/// clock, and one filesystem watcher over every repository. `p` is refused —
+/// see [`Dashboard`]'s [`Watched`] impl.
+///
+/// # Errors
+///
+/// Fails when the terminal cannot be taken over or the watcher cannot start.
+/// A repository that cannot be read is not an error; its block says so.
+pub(crate) fn run(members: Vec<Member>, cfg: &RenderConfig) -> Result<()> {
+    let _guard = TerminalGuard::enter()?;
+
+    let mut board = Board::new(members);
+    let roots = board.watch_roots();
+    // The seed goes through the same walk as every refresh. A single repository
+    // skips the re-open on its seed because its handle is microseconds old;
+    // here the handles were opened one after another and the first may not be,
+    // and one code path for every pass is worth a config parse per repository.
+    let collected_at = Instant::now();
+    let state = board.walk(cfg, collected_at, &Changed::All);
+
+    watch::session(
+        cfg,
+        Seed {
+            state,
+            collected_at,
+        },
+        roots,
+        None,
+        |changed: &Changed| Ok(board.walk(cfg, Instant::now(), changed)),
+        // With no target there is nothing to select, so the cursor the loop
+        // passes is always `None` here, and there is no diff to read.
+        |dashboard: &Dashboard, dims: Dimensions, timing: FrameTiming, _cursor: Option<&Cursor>| {
+            render_dashboard(dashboard, cfg, dims, timing)
+        },
+        |_entry: &RenderEntry| Ok(Vec::new()),
+    )
+}
+
+/// Stream the dashboard as NDJSON until interrupted: one `{"repositories": …}`
+/// line per walk that changed anything (see [`watch::stream_session`]).
+///
+/// # Errors
+///
+/// Fails when the filesystem watcher cannot start. A repository that cannot be
+/// read is not an error; its entry says so.
+pub(crate) fn stream(members: Vec<Member>, cfg: &RenderConfig) -> Result<()> {
+    let mut board = Board::new(members);
+    let roots = board.watch_roots();
+    let collected_at = Instant::now();
+    let state = board.walk(cfg, collected_at, &Changed::All);
+
+    watch::stream_session(
+        cfg,
+        Seed {
+            state,
+            collected_at,
+        },
+        roots,
+        |changed: &Changed| Ok(board.walk(cfg, Instant::now(), changed)),
+        |dashboard: &Dashboard| crate::json::dashboard(dashboard, false),
+    )
+}
+
+/// Rows a block spends on its title, above the repository's own frame.
+const TITLE_ROWS: usize = 1;
+
+/// Rows a block needs to show everything it has: its title plus the whole
+/// frame [`render_frame`] would draw with room to spare. An unreadable
+/// repository needs its title and the one row that says so.
+fn pane_demand(pane: &Pane) -> usize {
+    TITLE_ROWS
+        + pane.snapshot.as_ref().map_or(1, |snapshot| {
+            let demand = section_demand(snapshot);
+            frame_chrome(snapshot) + demand.files + demand.context()
+        })
+}
+
+/// Divide `available` rows between blocks that want `demands` rows each.
+///
+/// Water-filling: each block is offered an even share of what is left, a
+/// block that wants less takes only what it wants, and the rows it leaves go
+/// back into the pot for the blocks still hungry. So a clean repository with
+/// three commits never holds rows a busy one could use, and no busy repository
+/// can starve the rest — the fairness the single-frame planner's "file list
+/// first" rule gives the sections of one repository, applied one level up.
+/// Blocks are filled smallest demand first, which is what lets every leftover
+/// row be redistributed in one pass; the rows that integer division strands
+/// go one each to the hungry blocks in display order, top first.
+///
+/// Never gives a block more than it asked for, and never hands out more than
+/// `available` in total.
+pub(crate) fn fair_shares(demands: &[usize], available: usize) -> Vec<usize> {
+    let mut shares = vec![0; demands.len()];
+    let mut order: Vec<usize> = (0..demands.len()).collect();
+    order.sort_by_key(|&i| demands[i]);
+
+    // Satisfy every block whose whole demand fits under an even split of what
+    // is left; the first that does not marks the level everyone after it —
+    // all hungrier still — is cut to.
+    let mut remaining = available;
+    let mut hungry = Vec::new();
+    for (filled, &i) in order.iter().enumerate() {
+        if demands[i] <= remaining / (demands.len() - filled) {
+            shares[i] = demands[i];
+            remaining -= demands[i];
+        } else {
+            hungry.extend_from_slice(&order[filled..]);
+            break;
+        }
+    }
+    if hungry.is_empty() {
+        return shares;
+    }
+    hungry.sort_unstable();
+    let level = remaining / hungry.len();
+    let stranded = remaining % hungry.len();
+    for (rank, &i) in hungry.iter().enumerate() {
+        shares[i] = level + usize::from(rank < stranded);
+    }
+    shares
+}
+
+/// Render the dashboard into one frame for `dims`.
+///
+/// Each block is a title row over the repository's own [`render_frame`] at the
+/// height [`fair_shares`] gave it, so inside a block the file list and the log
+/// split their rows with the same planner a single-repository frame uses. The
+/// refresh clock belongs to the frame, not to a repository, so only the top
+/// block's separator carries it; every block ages by the same offset, plus its
+/// own [`Pane::lag`].
+///
+/// A terminal too short to give every block its header still draws every
+/// block's header, and the terminal clips the bottom — a repository silently
+/// missing from the dashboard is worse than one scrolled off it.
+pub(crate) fn render_dashboard(
+    dashboard: &Dashboard,
+    cfg: &RenderConfig,
+    dims: Dimensions,
+    timing: FrameTiming,
+) -> Render {
+    let demands: Vec<usize> = dashboard.panes.iter().map(pane_demand).collect();
+    let shares = fair_shares(&demands, dims.height);
+
+    let mut blocks: Vec<String> = Vec::with_capacity(dashboard.panes.len());
+    let mut freshest: Option<Duration> = None;
+    for (index, (pane, share)) in dashboard.panes.iter().zip(shares).enumerate() {
+        let title = truncate_left(&pane.label, dims.width)
+            .cyan()
+            .bold()
+            .to_string();
+        let Some(snapshot) = &pane.snapshot else {
+            let notice = "  unreadable — retrying on the next refresh".dimmed();
+            blocks.push(format!("{title}\n{notice}"));
+            continue;
+        };
+        let pane_timing = FrameTiming {
+            age_offset: timing.age_offset.saturating_add(pane.lag),
+            next_refresh_in: if index == 0 {
+                timing.next_refresh_in
+            } else {
+                None
+            },
+        };
+        let frame_dims = Dimensions {
+            height: share.saturating_sub(TITLE_ROWS),
+            ..dims
+        };
+        let render = render_frame(snapshot, cfg, frame_dims, pane_timing);
+        freshest = [freshest, render.freshest_age].into_iter().flatten().min();
+        blocks.push(format!("{title}\n{}", render.output));
+    }
+
+    Render {
+        output: blocks.join("\n"),
+        freshest_age: freshest,
+        // No cursor on the dashboard: no row is ever selectable.
+        rows: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileStatus;
+    use crate::render::{LogEntry, RenderEntry};
+    use crate::testrepo;
+    use testcolor::strip_ansi;
+
+    fn cfg() -> RenderConfig {
+        RenderConfig {
+            base: None,
+            max_files: None,
+            bar_width: 6,
+            log_lines: 20,
+            reflog_lines: 0,
+            stash_lines: 0,
+            truecolor: false,
+            width_offset: 0,
+            refresh_interval: None,
+            theme: crate::theme::Theme::DEFAULT,
+        }
+    }
+
+    fn snapshot(branch: &str, files: usize, commits: usize) -> Snapshot {
+        Snapshot {
+            branch: branch.into(),
+            base: "main".into(),
+            commits_ahead: 0,
+            commits_behind: 0,
+            files: (0..files)
+                .map(|i| RenderEntry {
+                    path: format!("{branch}-file{i}.rs"),
+                    orig_path: None,
+                    status: FileStatus::Modified,
+                    staged: false,
+                    adds: 1,
+                    dels: 0,
+                    binary: false,
+                    age: Some(Duration::from_secs(60)),
+                })
+                .collect(),
+            log: (0..commits)
+                .map(|i| LogEntry {
+                    hash: format!("abc{i:04}"),
+                    subject: format!("{branch} commit {i}"),
+                    age: Some(Duration::from_secs(3600)),
+                })
+                .collect(),
+            upstream: None,
+            operation: None,
+            push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
+        }
+    }
+
+    fn pane(label: &str, snapshot: Option<Snapshot>) -> Pane {
+        Pane {
+            label: label.into(),
+            snapshot,
+            lag: Duration::ZERO,
+            stale: false,
+        }
+    }
+
+    fn dims(height: usize) -> Dimensions {
+        Dimensions { width: 80, height }
+    }
+
+    #[test]
+    fn shares_are_demands_when_everything_fits() {
+        assert_eq!(fair_shares(&[3, 7, 5], 40), vec![3, 7, 5]);
+    }
+
+    #[test]
+    fn a_small_block_gives_its_unused_rows_to_the_hungry_ones() {
+        // An even split of 30 is 10 apiece; the 4-row block takes only 4, and
+        // its six spare rows go to the two blocks that want more.
+        assert_eq!(fair_shares(&[40, 4, 40], 30), vec![13, 4, 13]);
+    }
+
+    #[test]
+    fn rows_stranded_by_division_go_to_the_top_blocks_first() {
+        assert_eq!(fair_shares(&[50, 50, 50], 32), vec![11, 11, 10]);
+    }
+
+    #[test]
+    fn shares_never_exceed_what_is_available() {
+        let shares = fair_shares(&[9, 1, 30, 6], 17);
+        assert_eq!(shares.iter().sum::<usize>(), 17);
+        assert!(shares.iter().zip([9, 1, 30, 6]).all(|(s, d)| *s <= d));
+    }
+
+    #[test]
+    fn every_repository_gets_a_titled_block() {
+        let dashboard = Dashboard {
+            panes: vec![
+                pane("api", Some(snapshot("feature-a", 2, 1))),
+                pane("web", Some(snapshot("feature-b", 1, 2))),
+            ],
+        };
+        let out = strip_ansi(
+            &render_dashboard(&dashboard, &cfg(), dims(40), FrameTiming::at_walk(None)).output,
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "api");
+        assert!(lines[1].contains("feature-a"), "got {lines:?}");
+        let web = lines.iter().position(|l| *l == "web").expect("web block");
+        assert!(lines[web + 1].contains("feature-b"), "got {lines:?}");
+        assert!(out.contains("feature-b-file0.rs"));
+    }
+
+    #[test]
+    fn a_busy_repository_cannot_crowd_out_the_others() {
+        // Forty changed files in the first repository would fill a 24-row pane
+        // on their own; the second repository must still show its file.
+        let dashboard = Dashboard {
+            panes: vec![
+                pane("busy", Some(snapshot("busy", 40, 0))),
+                pane("quiet", Some(snapshot("quiet", 1, 0))),
+            ],
+        };
+        let out = strip_ansi(
+            &render_dashboard(&dashboard, &cfg(), dims(24), FrameTiming::at_walk(None)).output,
+        );
+        assert!(out.contains("quiet-file0.rs"), "got:\n{out}");
+        assert!(out.contains("more files"), "busy must be truncated:\n{out}");
+        assert!(
+            out.lines().count() <= 24,
+            "got {} rows",
+            out.lines().count()
+        );
+    }
+
+    #[test]
+    fn only_the_top_block_carries_the_refresh_clock() {
+        let dashboard = Dashboard {
+            panes: vec![
+                pane("one", Some(snapshot("a", 1, 1))),
+                pane("two", Some(snapshot("b", 1, 1))),
+            ],
+        };
+        let timing = FrameTiming::at_walk(Some(Duration::from_secs(60)));
+        let out = strip_ansi(&render_dashboard(&dashboard, &cfg(), dims(40), timing).output);
+        assert_eq!(out.matches("next refresh").count(), 1, "got:\n{out}");
+    }
+
+    #[test]
+    fn an_unreadable_repository_keeps_its_block() {
+        let dashboard = Dashboard {
+            panes: vec![pane("gone", None), pane("here", Some(snapshot("x", 1, 0)))],
+        };
+        let out = strip_ansi(
+            &render_dashboard(&dashboard, &cfg(), dims(24), FrameTiming::at_walk(None)).output,
+        );
+        assert!(out.starts_with("gone\n  unreadable"), "got:\n{out}");
+        assert!(out.contains("x-file0.rs"));
+    }
+
+    #[test]
+    fn a_stale_block_is_aged_by_its_lag() {
+        let mut stale = pane("stale", Some(snapshot("s", 1, 0)));
+        stale.lag = Duration::from_secs(3600);
+        let dashboard = Dashboard { panes: vec![stale] };
+        let render = render_dashboard(&dashboard, &cfg(), dims(24), FrameTiming::at_walk(None));
+        // The file was 60s old when last read, an hour before this collection.
+        assert_eq!(render.freshest_age, Some(Duration::from_secs(3660)));
+    }
+
+    #[test]
+    fn a_change_under_one_root_walks_only_that_repository() {
+        let (api, web) = (testrepo::init_repo(), testrepo::init_repo());
+        let members = [&api, &web]
+            .iter()
+            .map(|repo| Member {
+                label: repo.path().display().to_string(),
+                handle: RepoHandle::discover(repo.path()).expect("a repository"),
+            })
+            .collect();
+        let mut board = Board::new(members);
+        let start = Instant::now();
+        board.walk(&cfg(), start, &Changed::All);
+
+        std::fs::write(api.path().join("new.rs"), "api").expect("write api file");
+        std::fs::write(web.path().join("new.rs"), "web").expect("write web file");
+        let later = start + Duration::from_secs(5);
+        let dashboard = board.walk(&cfg(), later, &Changed::Roots([0].into()));
+        let has_new = |pane: &Pane| {
+            pane.snapshot
+                .as_ref()
+                .is_some_and(|snapshot| snapshot.files.iter().any(|f| f.path == "new.rs"))
+        };
+        assert!(has_new(&dashboard.panes[0]), "the changed root is read");
+        assert!(!has_new(&dashboard.panes[1]), "the other is left alone");
+        // ... and aged from the walk that last read it, without being stale.
+        assert_eq!(dashboard.panes[1].lag, Duration::from_secs(5));
+        assert!(!dashboard.panes[1].stale);
+
+        let dashboard = board.walk(&cfg(), later, &Changed::All);
+        assert!(has_new(&dashboard.panes[1]));
+    }
+
+    #[test]
+    fn the_dashboard_never_names_a_push_target() {
+        let dashboard = Dashboard {
+            panes: vec![pane("only", Some(snapshot("a", 0, 0)))],
+        };
+        assert!(dashboard.target().is_none());
+    }
+
+    #[test]
+    fn discover_finds_repositories_and_linked_worktrees() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let main = testrepo::init_repo();
+        let checkout = root.path().join("app");
+        testrepo::git(
+            main.path(),
+            &["clone", "-q", ".", checkout.to_str().expect("utf-8 path")],
+        );
+        let worktree = root.path().join("app-feature");
+        testrepo::git(
+            &checkout,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                worktree.to_str().expect("utf-8 path"),
+            ],
+        );
+        std::fs::create_dir(root.path().join("notes")).expect("plain dir");
+
+        assert_eq!(discover(root.path()), vec![checkout, worktree]);
+    }
+
+    #[test]
+    fn a_repository_named_twice_is_watched_once() {
+        let repo = testrepo::init_repo();
+        let subdir = repo.path().join("sub");
+        std::fs::create_dir(&subdir).expect("subdir");
+        let members =
+            open_members(&[repo.path().to_path_buf(), subdir], None).expect("both paths open");
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].label, repo.path().display().to_string());
+    }
+
+    #[test]
+    fn a_named_path_outside_any_repository_is_an_error() {
+        let plain = tempfile::tempdir().expect("tempdir");
+        let err = open_members(&[plain.path().to_path_buf()], None)
+            .err()
+            .expect("not a repository");
+        assert!(err.to_string().contains("not a git repository"));
+    }
+
+    #[test]
+    fn discovered_repositories_are_labelled_relative_to_the_root() {
+        let root = Path::new("/src");
+        assert_eq!(discovered_label(root, Path::new("/src/app")), "app");
+        assert_eq!(discovered_label(root, Path::new("/src")), "/src");
+    }
+}
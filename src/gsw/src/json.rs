@@ -0,0 +1,517 @@
+//! Machine-readable output: a [`Snapshot`] — or a whole dashboard of them — as
+//! JSON, for the scripts that would otherwise scrape the colored frame.
+//!
+//! The shapes here are a published interface, not a dump of the in-memory
+//! types. They are built from borrowed views rather than by deriving
+//! `Serialize` on [`Snapshot`] itself, so a field renamed or reshaped for the
+//! renderer's convenience cannot silently change what a tmux status line or an
+//! editor plugin reads. Durations are whole seconds (`*_secs`), statuses are
+//! `snake_case` strings, and an absent value is `null` rather than a missing
+//! key, so a consumer can index every field without checking for it first.
+//!
+//! Every age is as of the walk that produced the object. Nothing here is aged
+//! forward the way the frame is between walks: a consumer that wants a live
+//! age adds the time since it read the line, and the stream (`--format
+//! ndjson`) only writes a line when a walk produced one that differs.
+
+use serde::Serialize;
+
+use crate::dashboard::Dashboard;
+use crate::git::FileStatus;
+use crate::render::{
+    LogEntry, Operation, ReflogEntry, RenderEntry, Snapshot, SubmoduleGroup, UpstreamStatus,
+};
+
+/// How gsw writes what it found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub(crate) enum Format {
+    /// The colored frame: a live watch on a terminal, one render otherwise.
+    #[default]
+    Text,
+    /// One JSON object, then exit.
+    Json,
+    /// One JSON object per line, a new one on every refresh that changed
+    /// anything, until interrupted. Streams whether or not stdout is a
+    /// terminal, and never takes the terminal over.
+    Ndjson,
+}
+
+/// One repository, as the JSON output describes it.
+#[derive(Serialize)]
+struct SnapshotJson<'a> {
+    branch: &'a str,
+    base: BaseJson<'a>,
+    upstream: Option<UpstreamJson<'a>>,
+    push_remote: Option<&'a str>,
+    operation: Option<OperationJson>,
+    files: Vec<FileJson<'a>>,
+    submodules: Vec<SubmoduleJson<'a>>,
+    log: Vec<LogJson<'a>>,
+    reflog: Vec<ReflogJson<'a>>,
+    stashes: Vec<ReflogJson<'a>>,
+}
+
+/// The branch against its base ref — the header's `↑N ↓M main`.
+#[derive(Serialize)]
+struct BaseJson<'a> {
+    name: &'a str,
+    ahead: u32,
+    behind: u32,
+}
+
+/// The branch against its upstream tracking ref.
+#[derive(Serialize)]
+struct UpstreamJson<'a> {
+    name: &'a str,
+    ahead: u32,
+    behind: u32,
+}
+
+/// An in-progress merge or rebase, tagged by `kind`.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum OperationJson {
+    Merge {
+        conflicts: u32,
+    },
+    Rebase {
+        step: Option<StepJson>,
+        conflicts: u32,
+    },
+}
+
+/// Git's rebase step counter, `current` of `total`.
+#[derive(Serialize)]
+struct StepJson {
+    current: u32,
+    total: u32,
+}
+
+/// One file row.
+#[derive(Serialize)]
+struct FileJson<'a> {
+    path: &'a str,
+    orig_path: Option<&'a str>,
+    status: &'static str,
+    staged: bool,
+    adds: u32,
+    dels: u32,
+    binary: bool,
+    age_secs: Option<u64>,
+}
+
+/// A changed submodule, expanded. `ahead`/`behind` count the submodule's HEAD
+/// against the commit the superproject records for it, and `files` are its own
+/// rows, with paths relative to the submodule. A submodule expanded here is not
+/// repeated as an unstaged row in the superproject's `files`.
+#[derive(Serialize)]
+struct SubmoduleJson<'a> {
+    path: &'a str,
+    branch: &'a str,
+    ahead: u32,
+    behind: u32,
+    files: Vec<FileJson<'a>>,
+}
+
+/// One recent commit.
+#[derive(Serialize)]
+struct LogJson<'a> {
+    hash: &'a str,
+    subject: &'a str,
+    age_secs: Option<u64>,
+}
+
+/// One HEAD reflog or stash entry. `selector` is git's name for it
+/// (`HEAD@{2}`, `stash@{0}`). Both lists are empty unless their section was
+/// asked for (`--reflog-lines`, `--stash-lines`).
+#[derive(Serialize)]
+struct ReflogJson<'a> {
+    selector: &'a str,
+    message: &'a str,
+    age_secs: Option<u64>,
+}
+
+/// The dashboard: every repository in command-line order.
+#[derive(Serialize)]
+struct DashboardJson<'a> {
+    repositories: Vec<PaneJson<'a>>,
+}
+
+/// One dashboard repository. `snapshot` is `null` for a repository that has
+/// never been readable; `stale_secs` is how old a snapshot kept from an earlier
+/// walk already was when this walk failed to read the repository, `0` for one
+/// that read, or that nothing changed under since it last did.
+#[derive(Serialize)]
+struct PaneJson<'a> {
+    label: &'a str,
+    snapshot: Option<SnapshotJson<'a>>,
+    stale_secs: u64,
+}
+
+impl<'a> From<&'a Snapshot> for SnapshotJson<'a> {
+    fn from(snapshot: &'a Snapshot) -> Self {
+        Self {
+            branch: &snapshot.branch,
+            base: BaseJson {
+                name: &snapshot.base,
+                ahead: snapshot.commits_ahead,
+                behind: snapshot.commits_behind,
+            },
+            upstream: snapshot.upstream.as_ref().map(UpstreamJson::from),
+            push_remote: snapshot.push_remote.as_deref(),
+            operation: snapshot.operation.as_ref().map(OperationJson::from),
+            files: snapshot.files.iter().map(FileJson::from).collect(),
+            submodules: snapshot
+                .submodules
+                .iter()
+                .map(SubmoduleJson::from)
+                .collect(),
+            log: snapshot.log.iter().map(LogJson::from).collect(),
+            reflog: snapshot.reflog.iter().map(ReflogJson::from).collect(),
+            stashes: snapshot.stashes.iter().map(ReflogJson::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a UpstreamStatus> for UpstreamJson<'a> {
+    fn from(upstream: &'a UpstreamStatus) -> Self {
+        Self {
+            name: &upstream.name,
+            ahead: upstream.ahead,
+            behind: upstream.behind,
+        }
+    }
+}
+
+impl From<&Operation> for OperationJson {
+    fn from(operation: &Operation) -> Self {
+        match *operation {
+            Operation::Merge { conflicts } => Self::Merge { conflicts },
+            Operation::Rebase { step, conflicts } => Self::Rebase {
+                step: step.map(|step| StepJson {
+                    current: step.current,
+                    total: step.total,
+                }),
+                conflicts,
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a RenderEntry> for FileJson<'a> {
+    fn from(entry: &'a RenderEntry) -> Self {
+        Self {
+            path: &entry.path,
+            orig_path: entry.orig_path.as_deref(),
+            status: status_name(entry.status),
+            staged: entry.staged,
+            adds: entry.adds,
+            dels: entry.dels,
+            binary: entry.binary,
+            age_secs: entry.age.map(|age| age.as_secs()),
+        }
+    }
+}
+
+impl<'a> From<&'a SubmoduleGroup> for SubmoduleJson<'a> {
+    fn from(group: &'a SubmoduleGroup) -> Self {
+        Self {
+            path: &group.path,
+            branch: &group.branch,
+            ahead: group.ahead,
+            behind: group.behind,
+            files: group.files.iter().map(FileJson::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a LogEntry> for LogJson<'a> {
+    fn from(entry: &'a LogEntry) -> Self {
+        Self {
+            hash: &entry.hash,
+            subject: &entry.subject,
+            age_secs: entry.age.map(|age| age.as_secs()),
+        }
+    }
+}
+
+impl<'a> From<&'a ReflogEntry> for ReflogJson<'a> {
+    fn from(entry: &'a ReflogEntry) -> Self {
+        Self {
+            selector: &entry.selector,
+            message: &entry.message,
+            age_secs: entry.age.map(|age| age.as_secs()),
+        }
+    }
+}
+
+/// The stable name a [`FileStatus`] goes by in the output.
+fn status_name(status: FileStatus) -> &'static str {
+    match status {
+        FileStatus::Modified => "modified",
+        FileStatus::Added => "added",
+        FileStatus::Deleted => "deleted",
+        FileStatus::Renamed => "renamed",
+        FileStatus::Copied => "copied",
+        FileStatus::TypeChange => "type_change",
+        FileStatus::Untracked => "untracked",
+        FileStatus::UntrackedDir => "untracked_dir",
+        FileStatus::Conflicted => "conflicted",
+    }
+}
+
+/// Serialize a view the output owns. Every field is a plain string, number,
+/// bool, or a nesting of them, so the only way `serde_json` can fail here is a
+/// bug in this module; an empty object keeps the line parseable even then.
+fn encode(value: &impl Serialize, pretty: bool) -> String {
+    let encoded = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    encoded.unwrap_or_else(|_| "{}".to_string())
+}
+
+/// One repository as JSON — indented for `--format json`, on one line for the
+/// stream, where a newline inside the object would end the record.
+pub(crate) fn snapshot(snapshot: &Snapshot, pretty: bool) -> String {
+    encode(&SnapshotJson::from(snapshot), pretty)
+}
+
+/// A dashboard as JSON: `{"repositories": [...]}`, one entry per repository in
+/// command-line order.
+pub(crate) fn dashboard(dashboard: &Dashboard, pretty: bool) -> String {
+    let repositories = dashboard
+        .panes
+        .iter()
+        .map(|pane| PaneJson {
+            label: &pane.label,
+            snapshot: pane.snapshot.as_ref().map(SnapshotJson::from),
+            stale_secs: if pane.stale { pane.lag.as_secs() } else { 0 },
+        })
+        .collect();
+    encode(&DashboardJson { repositories }, pretty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard::Pane;
+    use crate::render::StepProgress;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn entry(path: &str, status: FileStatus, staged: bool) -> RenderEntry {
+        RenderEntry {
+            path: path.to_string(),
+            orig_path: None,
+            status,
+            staged,
+            adds: 3,
+            dels: 1,
+            binary: false,
+            age: Some(Duration::from_millis(90_500)),
+        }
+    }
+
+    fn sample() -> Snapshot {
+        Snapshot {
+            branch: "feature".into(),
+            base: "main".into(),
+            commits_ahead: 2,
+            commits_behind: 1,
+            files: vec![
+                entry("src/lib.rs", FileStatus::Modified, true),
+                RenderEntry {
+                    age: None,
+                    ..entry("gone.rs", FileStatus::Deleted, false)
+                },
+            ],
+            log: vec![LogEntry {
+                hash: "abc1234".into(),
+                subject: "Add the thing".into(),
+                age: Some(Duration::from_secs(3600)),
+            }],
+            upstream: Some(UpstreamStatus {
+                name: "origin/feature".into(),
+                ahead: 1,
+                behind: 0,
+            }),
+            reflog: vec![ReflogEntry {
+                selector: "HEAD@{0}".into(),
+                message: "rebase (pick): Add the thing".into(),
+                age: Some(Duration::from_secs(5)),
+            }],
+            stashes: vec![ReflogEntry {
+                selector: "stash@{0}".into(),
+                message: "WIP on feature: abc1234 Add the thing".into(),
+                age: None,
+            }],
+            submodules: vec![SubmoduleGroup {
+                path: "libs/lib".into(),
+                branch: "main".into(),
+                ahead: 1,
+                behind: 0,
+                files: vec![entry("l.txt", FileStatus::Modified, false)],
+            }],
+            push_remote: Some("origin".into()),
+            operation: Some(Operation::Rebase {
+                step: Some(StepProgress {
+                    current: 3,
+                    total: 10,
+                }),
+                conflicts: 2,
+            }),
+        }
+    }
+
+    fn parse(text: &str) -> Value {
+        serde_json::from_str(text).expect("the output is JSON")
+    }
+
+    #[test]
+    fn a_snapshot_serializes_every_section_the_frame_shows() {
+        let value = parse(&snapshot(&sample(), false));
+        assert_eq!(
+            value,
+            json!({
+                "branch": "feature",
+                "base": { "name": "main", "ahead": 2, "behind": 1 },
+                "upstream": { "name": "origin/feature", "ahead": 1, "behind": 0 },
+                "push_remote": "origin",
+                "operation": {
+                    "kind": "rebase",
+                    "step": { "current": 3, "total": 10 },
+                    "conflicts": 2,
+                },
+                "files": [
+                    {
+                        "path": "src/lib.rs",
+                        "orig_path": null,
+                        "status": "modified",
+                        "staged": true,
+                        "adds": 3,
+                        "dels": 1,
+                        "binary": false,
+                        "age_secs": 90,
+                    },
+                    {
+                        "path": "gone.rs",
+                        "orig_path": null,
+                        "status": "deleted",
+                        "staged": false,
+                        "adds": 3,
+                        "dels": 1,
+                        "binary": false,
+                        "age_secs": null,
+                    },
+                ],
+                "submodules": [
+                    {
+                        "path": "libs/lib",
+                        "branch": "main",
+                        "ahead": 1,
+                        "behind": 0,
+                        "files": [
+                            {
+                                "path": "l.txt",
+                                "orig_path": null,
+                                "status": "modified",
+                                "staged": false,
+                                "adds": 3,
+                                "dels": 1,
+                                "binary": false,
+                                "age_secs": 90,
+                            },
+                        ],
+                    },
+                ],
+                "log": [
+                    { "hash": "abc1234", "subject": "Add the thing", "age_secs": 3600 },
+                ],
+                "reflog": [
+                    {
+                        "selector": "HEAD@{0}",
+                        "message": "rebase (pick): Add the thing",
+                        "age_secs": 5,
+                    },
+                ],
+                "stashes": [
+                    {
+                        "selector": "stash@{0}",
+                        "message": "WIP on feature: abc1234 Add the thing",
+                        "age_secs": null,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn absent_sections_are_null_rather_than_missing() {
+        // A consumer indexes `.upstream.ahead` without checking the key exists;
+        // `null` tells it there is no upstream, a missing key would look like a
+        // different version of the format.
+        let mut bare = sample();
+        bare.upstream = None;
+        bare.operation = None;
+        bare.push_remote = None;
+        let value = parse(&snapshot(&bare, false));
+        assert_eq!(value["upstream"], Value::Null);
+        assert_eq!(value["operation"], Value::Null);
+        assert_eq!(value["push_remote"], Value::Null);
+    }
+
+    #[test]
+    fn a_merge_is_tagged_by_kind() {
+        let mut merging = sample();
+        merging.operation = Some(Operation::Merge { conflicts: 4 });
+        assert_eq!(
+            parse(&snapshot(&merging, false))["operation"],
+            json!({ "kind": "merge", "conflicts": 4 }),
+        );
+    }
+
+    #[test]
+    fn the_stream_form_is_one_line_and_the_pretty_form_is_the_same_value() {
+        let line = snapshot(&sample(), false);
+        assert!(!line.contains('\n'), "a record must not span lines: {line}");
+        assert_eq!(parse(&snapshot(&sample(), true)), parse(&line));
+    }
+
+    #[test]
+    fn a_dashboard_lists_every_repository_including_the_unreadable() {
+        let board = Dashboard {
+            panes: vec![
+                Pane {
+                    label: "api".into(),
+                    snapshot: Some(sample()),
+                    lag: Duration::ZERO,
+                    stale: false,
+                },
+                Pane {
+                    label: "web".into(),
+                    snapshot: None,
+                    lag: Duration::from_secs(30),
+                    stale: true,
+                },
+                // Not re-read because nothing changed: older, but not stale
+                Pane {
+                    label: "docs".into(),
+                    snapshot: Some(sample()),
+                    lag: Duration::from_secs(30),
+                    stale: false,
+                },
+            ],
+        };
+        let value = parse(&dashboard(&board, false));
+        let repositories = value["repositories"].as_array().expect("an array");
+        assert_eq!(repositories.len(), 3);
+        assert_eq!(repositories[0]["label"], "api");
+        assert_eq!(repositories[0]["snapshot"]["branch"], "feature");
+        assert_eq!(repositories[0]["stale_secs"], 0);
+        assert_eq!(repositories[1]["snapshot"], Value::Null);
+        assert_eq!(repositories[1]["stale_secs"], 30);
+        assert_eq!(repositories[2]["stale_secs"], 0);
+    }
+}
@@ -3,28 +3,40 @@ use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use buildinfo::version_string;
 use clap::Parser;
 use colored::Colorize;
 
+use crate::browse::{Cursor, RowKey};
+use crate::config::{Defaults, Settings};
 use crate::git::{FileEntry, FileStatus};
+use crate::json::Format;
 use crate::render::{
-    plan_section_caps, render, render_with_offset, LogEntry, RefreshStatus, RenderOptions, Snapshot,
+    file_section_rows, plan_section_caps, render, render_with_offset, share_context, LogEntry,
+    ReflogEntry, RefreshStatus, RenderOptions, SectionRows, Snapshot, SubmoduleGroup,
 };
-use crate::snapshot::build_snapshot;
+use crate::snapshot::{build_rows, build_snapshot};
+use crate::theme::Theme;
 
+mod action;
 mod age;
 mod bar;
+mod browse;
+mod config;
+mod dashboard;
 mod git;
+mod json;
 mod push;
 mod render;
 mod repo;
 mod snapshot;
+mod sync;
 /// Shared git fixtures for the unit tests. Test-only: it shells out to `git` to
 /// build throwaway repositories, which the shipped binary never does.
 #[cfg(test)]
 mod testrepo;
+mod theme;
 mod watch;
 
 #[derive(Parser)]
@@ -40,13 +52,50 @@ mod watch;
                   separator under the header showing how stale the screen is and how long \
                   until the next refresh; with `--one-shot` (or when its output is piped) \
                   it renders once and exits.\n\n\
+                  A submodule with changes inside it is expanded under the file list: a title \
+                  row with its branch and how far it has moved from the commit the \
+                  superproject records, then its own changed files, indented.\n\n\
                   Watch-mode keys: q or Ctrl-C quits, r refreshes now, and p pushes the current \
                   branch after a confirmation that names what it will do — a branch not yet on \
                   the remote is confirmed as creating one. A push whose branch stopped being \
                   checked out between the question and the answer is refused, not redirected. \
-                  p never force-pushes."
+                  p never force-pushes. f fetches and re-reads the branch's ahead/behind \
+                  counts; u fast-forwards a branch that is strictly behind its upstream, after \
+                  asking, and refuses one that is ahead or has diverged.\n\n\
+                  j/k (or the arrows) move a highlight over the file rows and Esc drops it. s \
+                  stages the highlighted row, S unstages it, Space flips it to the other side \
+                  of the index, and d discards its work-tree changes after asking. Enter opens \
+                  the row's diff in place of the frame; j/k and PgUp/PgDn scroll it, and Esc, \
+                  q, or Enter close it.\n\n\
+                  Given one REPO path, gsw watches that repository instead of the current \
+                  directory's. Given several — or --discover DIR, which finds every repository \
+                  and linked worktree under DIR — it runs as a dashboard: one compact block per \
+                  repository in a single session, with the rows shared fairly between them and \
+                  one filesystem watcher for all of them. The dashboard refuses p, f, u, and the \
+                  file keys; run gsw inside the repository you want to act on.\n\n\
+                  --format json prints the same state as one JSON object and exits; --format \
+                  ndjson streams one object per line, a new one whenever a refresh changed \
+                  anything, until interrupted. Both describe the dashboard as \
+                  {\"repositories\": [...]} when there is one.\n\n\
+                  Settings come from the per-user config.toml in gsw's config directory, then \
+                  the repository's .gsw.toml, then the command line, each overriding the last: \
+                  [defaults] for any flag that takes a value, [layout] sections for the section \
+                  order, [fade] floor and darkest_at_secs for the age fade, and [colors] for the \
+                  truecolor palette. --print-config prints the result as a config file."
 )]
 struct Cli {
+    /// Repositories to watch. One path watches that repository in place of
+    /// the current directory's; two or more open the multi-repository
+    /// dashboard. Any path inside a repository works, as it does for `git`.
+    #[arg(value_name = "REPO")]
+    repos: Vec<PathBuf>,
+
+    /// Add every repository and linked worktree found under DIR to the
+    /// dashboard. Hidden directories, `node_modules`, and gitignored paths
+    /// are not searched.
+    #[arg(long, value_name = "DIR")]
+    discover: Option<PathBuf>,
+
     /// Render once and exit instead of entering the live watch loop. This is
     /// the classic behavior; on a TTY, watch mode is the default. Output that
     /// is piped/captured (not a TTY) always falls back to this single render.
@@ -57,6 +106,15 @@ struct Cli {
     #[arg(long)]
     no_color: bool,
 
+    /// How to write the result. `json` prints one indented object describing
+    /// the repository — or, with several, `{"repositories": [...]}` — and
+    /// exits. `ndjson` streams the same object one per line, a new one
+    /// whenever a refresh finds something changed, until interrupted; it never
+    /// takes over the terminal, so it can feed a status line through a pipe.
+    /// With `--one-shot`, `ndjson` writes its first line and exits.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
     /// Base ref to compare against (default: main, then master, then origin/HEAD).
     #[arg(long)]
     base: Option<String>,
@@ -65,20 +123,20 @@ struct Cli {
     #[arg(long)]
     max_files: Option<usize>,
 
-    /// Width of the magnitude bar in cells.
-    #[arg(long, default_value_t = 6)]
-    bar_width: usize,
+    /// Width of the magnitude bar in cells (default: 6).
+    #[arg(long)]
+    bar_width: Option<usize>,
 
     /// Columns to subtract from the detected terminal width. Useful when a
     /// wrapping TUI (e.g. viddy) eats a column for its own chrome that the
-    /// child process can't see.
-    #[arg(long, default_value_t = 0)]
-    width_offset: usize,
+    /// child process can't see. Default: 0.
+    #[arg(long)]
+    width_offset: Option<usize>,
 
     /// Number of recent commits to show in the `git log --oneline`-style
-    /// section appended after the file list.
-    #[arg(long, default_value_t = 20)]
-    log_lines: usize,
+    /// section appended after the file list (default: 20).
+    #[arg(long)]
+    log_lines: Option<usize>,
 
     /// Disable the recent-commit section entirely. The newest commit's age
     /// lives on the first row of that section, so this takes the commit age
@@ -86,6 +144,17 @@ struct Cli {
     #[arg(long)]
     no_log: bool,
 
+    /// Number of recent HEAD reflog entries to show under the commit log —
+    /// where HEAD moved, as `git reflog` lists it, newest first. `0` (the
+    /// default) leaves the section out.
+    #[arg(long)]
+    reflog_lines: Option<usize>,
+
+    /// Number of stash entries to show above the file list, newest first.
+    /// `0` (the default) leaves the section out.
+    #[arg(long)]
+    stash_lines: Option<usize>,
+
     /// Force the 24-bit truecolor fades on, regardless of what `COLORTERM`
     /// says. The fades are the commit-log gradient, the recency fade on the
     /// file rows, and the fade on the push status message. This flag helps
@@ -106,19 +175,66 @@ struct Cli {
     /// them, and it is what the "next refresh" countdown in the separator
     /// counts down to. `0` turns the timed refresh off, which also removes the
     /// countdown and leaves gsw purely event-driven. Accepts up to a year.
+    /// Default: 60.
     #[arg(
         long,
-        default_value_t = DEFAULT_REFRESH_SECS,
         value_parser = clap::value_parser!(u64).range(0..=MAX_REFRESH_SECS),
     )]
-    refresh_interval: u64,
+    refresh_interval: Option<u64>,
+
+    /// Print the effective settings — built-in defaults, then the config
+    /// files, then the flags on this command line — as a config file, and
+    /// exit. The header lists the files that were read.
+    #[arg(long)]
+    print_config: bool,
+}
+
+impl Cli {
+    /// Lay the flags given on the command line over the config files'
+    /// defaults. A flag left off changes nothing, so a config file's value
+    /// survives unless it is overridden here.
+    fn override_defaults(&self, defaults: &mut Defaults) {
+        if self.base.is_some() {
+            defaults.base.clone_from(&self.base);
+        }
+        if self.max_files.is_some() {
+            defaults.max_files = self.max_files;
+        }
+        defaults.bar_width = self.bar_width.unwrap_or(defaults.bar_width);
+        defaults.width_offset = self.width_offset.unwrap_or(defaults.width_offset);
+        defaults.log_lines = self.log_lines.unwrap_or(defaults.log_lines);
+        if self.no_log {
+            defaults.log_lines = 0;
+        }
+        defaults.reflog_lines = self.reflog_lines.unwrap_or(defaults.reflog_lines);
+        defaults.stash_lines = self.stash_lines.unwrap_or(defaults.stash_lines);
+        defaults.refresh_interval = self.refresh_interval.unwrap_or(defaults.refresh_interval);
+        if self.truecolor {
+            defaults.truecolor = Some(true);
+        } else if self.no_truecolor {
+            defaults.truecolor = Some(false);
+        }
+    }
+
+    /// The work tree whose `.gsw.toml` applies: the one repository being
+    /// watched, or `None` for a dashboard — whose members would each bring a
+    /// file of their own, with no one of them entitled to decide for the rest —
+    /// and for a directory that is not in a repository at all.
+    fn config_root(&self) -> Option<PathBuf> {
+        let handle = match (self.repos.as_slice(), &self.discover) {
+            ([], None) => repo::RepoHandle::open()?,
+            ([path], None) => repo::RepoHandle::discover(path)?,
+            _ => return None,
+        };
+        handle.repo().workdir().map(Path::to_path_buf)
+    }
 }
 
 /// Default seconds between timed watch-mode refreshes. A minute keeps a screen
 /// left open in a pane honest without walking git often enough to matter — and
 /// the duty-cycle budget still overrides it on a repository where a walk is
 /// expensive.
-const DEFAULT_REFRESH_SECS: u64 = 60;
+pub(crate) const DEFAULT_REFRESH_SECS: u64 = 60;
 
 /// Largest accepted `--refresh-interval`, in seconds: one year.
 ///
@@ -128,7 +244,7 @@ const DEFAULT_REFRESH_SECS: u64 = 60;
 /// A year is far past the point where a timed refresh is distinguishable from
 /// `0`, and small enough to be representable on any clock, so rejecting
 /// anything larger costs nothing real and removes the panic.
-const MAX_REFRESH_SECS: u64 = 365 * 24 * 60 * 60;
+pub(crate) const MAX_REFRESH_SECS: u64 = 365 * 24 * 60 * 60;
 
 /// Resolve `--refresh-interval` seconds into the schedule watch mode runs on.
 ///
@@ -259,14 +375,22 @@ fn effective_truecolor(
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    let mut loaded = config::load(cli.config_root().as_deref())?;
+    cli.override_defaults(&mut loaded.settings.defaults);
+    if cli.print_config {
+        print!("{}", config::describe(&loaded));
+        return Ok(());
+    }
+    let Settings { defaults, theme } = loaded.settings;
+
     let stdout_is_tty = std::io::stdout().is_terminal();
     let columns_env: Option<usize> = std::env::var("COLUMNS").ok().and_then(|s| s.parse().ok());
     let no_color_env = std::env::var_os("NO_COLOR").is_some();
     let colorterm_env = std::env::var("COLORTERM").ok();
     let truecolor = effective_truecolor(
         cli.no_color,
-        cli.truecolor,
-        cli.no_truecolor,
+        defaults.truecolor == Some(true),
+        defaults.truecolor == Some(false),
         no_color_env,
         colorterm_env.as_deref(),
     );
@@ -288,23 +412,77 @@ fn main() -> Result<()> {
         colored::control::set_override(true);
     }
 
-    let Some(handle) = repo::RepoHandle::open() else {
-        println!("{}", "gsw • not a git repository".dimmed());
-        return Ok(());
+    // No paths and no discovery is the classic invocation: the repository the
+    // current directory is in. Anything else names the repositories outright,
+    // and more than one of them is a dashboard.
+    let mut members = if cli.repos.is_empty() && cli.discover.is_none() {
+        let Some(handle) = repo::RepoHandle::open() else {
+            // A program reading JSON learns this from the exit status; the
+            // friendly line is for a person looking at a pane.
+            if cli.format != Format::Text {
+                bail!("not a git repository");
+            }
+            println!("{}", "gsw • not a git repository".dimmed());
+            return Ok(());
+        };
+        vec![dashboard::Member {
+            label: String::new(),
+            handle,
+        }]
+    } else {
+        dashboard::open_members(&cli.repos, cli.discover.as_deref())?
     };
+    if members.is_empty() {
+        // Only discovery can come back empty: a named path that is not a
+        // repository is already an error.
+        let root = cli.discover.unwrap_or_default();
+        if cli.format != Format::Text {
+            bail!("no git repositories under {}", root.display());
+        }
+        println!(
+            "{}",
+            format!("gsw • no git repositories under {}", root.display()).dimmed()
+        );
+        return Ok(());
+    }
+    let handle = (members.len() == 1).then(|| members.remove(0).handle);
 
     // Everything the renderer needs that doesn't depend on the live terminal
     // size. In watch mode this is computed once and reused for every repaint.
     let cfg = RenderConfig {
-        base: cli.base,
-        max_files: cli.max_files,
-        bar_width: cli.bar_width,
-        log_lines: if cli.no_log { 0 } else { cli.log_lines },
+        base: defaults.base,
+        max_files: defaults.max_files,
+        bar_width: defaults.bar_width,
+        log_lines: defaults.log_lines,
+        reflog_lines: defaults.reflog_lines,
+        stash_lines: defaults.stash_lines,
         truecolor,
-        width_offset: cli.width_offset,
-        refresh_interval: refresh_interval(cli.refresh_interval),
+        width_offset: defaults.width_offset,
+        refresh_interval: refresh_interval(defaults.refresh_interval),
+        theme,
     };
 
+    match cli.format {
+        Format::Text => {}
+        // The structured formats never paint a frame, so the terminal's size
+        // and whether stdout is one do not enter into it: `json` is always a
+        // single object, and `ndjson` streams unless told not to.
+        Format::Json => {
+            println!("{}", one_shot_json(handle, members, &cfg, true)?);
+            return Ok(());
+        }
+        Format::Ndjson if cli.one_shot => {
+            println!("{}", one_shot_json(handle, members, &cfg, false)?);
+            return Ok(());
+        }
+        Format::Ndjson => {
+            return match handle {
+                Some(handle) => watch::stream(handle, &cfg),
+                None => dashboard::stream(members, &cfg),
+            };
+        }
+    }
+
     match decide_mode(cli.one_shot, stdout_is_tty) {
         watch::Mode::OneShot => {
             // Preserve the viddy-aware env sizing and the trailing newline of
@@ -326,15 +504,37 @@ fn main() -> Result<()> {
             // A fresh process opened this handle a moment ago, so its cached
             // config is current by construction — one render, then exit. Only
             // watch mode, which outlives config edits, needs to re-open.
-            println!("{}", build_output(handle.repo(), &cfg, dims)?.output);
+            let render = match handle {
+                Some(handle) => build_output(handle.repo(), &cfg, dims)?,
+                None => dashboard::build_output(members, &cfg, dims)?,
+            };
+            println!("{}", render.output);
             Ok(())
         }
         // Hand the handle over: watch mode owns the repository from here and
         // re-opens it on every refresh.
-        watch::Mode::Watch => watch::run(handle, &cfg),
+        watch::Mode::Watch => match handle {
+            Some(handle) => watch::run(handle, &cfg),
+            None => dashboard::run(members, &cfg),
+        },
     }
 }
 
+/// Walk once and describe the result as JSON: the repository's object, or the
+/// dashboard's when there are several. `pretty` indents it for `--format json`;
+/// the `ndjson` one-shot keeps it on one line.
+fn one_shot_json(
+    handle: Option<repo::RepoHandle>,
+    members: Vec<dashboard::Member>,
+    cfg: &RenderConfig,
+    pretty: bool,
+) -> Result<String> {
+    Ok(match handle {
+        Some(handle) => json::snapshot(&collect_snapshot(handle.repo(), cfg)?, pretty),
+        None => json::dashboard(&dashboard::collect_once(members, cfg)?, pretty),
+    })
+}
+
 /// Which rendering mode to run in once a working-tree repo is in hand.
 ///
 /// Watch mode is the default, but it only makes sense when there is a live
@@ -361,6 +561,10 @@ pub(crate) struct RenderConfig {
     pub bar_width: usize,
     /// Recent-commit rows to request; `0` when `--no-log` suppressed the section.
     pub log_lines: usize,
+    /// HEAD reflog rows to request (`--reflog-lines`); `0` reads no reflog.
+    pub reflog_lines: usize,
+    /// Stash rows to request (`--stash-lines`); `0` reads no stash.
+    pub stash_lines: usize,
     /// Whether the 24-bit truecolor fades are in effect. The fades are the
     /// commit-log gradient, the recency fade on the file rows, and the fade
     /// on the push status message.
@@ -370,6 +574,9 @@ pub(crate) struct RenderConfig {
     /// How often watch mode re-walks the repository with no filesystem event to
     /// prompt it (`--refresh-interval`), or `None` to stay purely event-driven.
     pub refresh_interval: Option<Duration>,
+    /// The palette, fade curve, and section order, as the config files left
+    /// them.
+    pub theme: Theme,
 }
 
 /// Where a frame sits in time: how stale the snapshot behind it is, and when
@@ -414,6 +621,10 @@ pub(crate) struct Render {
     /// change), or `None` when nothing aging is on screen. Drives the adaptive
     /// decay-timer cadence via [`watch::next_tick`].
     pub freshest_age: Option<Duration>,
+    /// The file rows the frame drew, top to bottom — the rows watch mode's
+    /// cursor can move over. A row the section caps left off the screen is not
+    /// here, so the cursor never lands somewhere the user cannot see it.
+    pub rows: Vec<RowKey>,
 }
 
 /// Age of the freshest item the frame displays — the newest commit or the most
@@ -435,9 +646,20 @@ pub(crate) struct Render {
 fn snapshot_freshest_age(snapshot: &Snapshot) -> Option<Duration> {
     // The youngest item wins, so the timer ticks fast enough for whatever is
     // freshest. The log is newest-first, so its head is the newest commit.
-    let freshest_change = snapshot.files.iter().filter_map(|f| f.age).min();
+    // The reflog and the stash list are newest-first the same way.
+    let freshest_change = snapshot
+        .files
+        .iter()
+        .chain(snapshot.submodules.iter().flat_map(|group| &group.files))
+        .filter_map(|f| f.age)
+        .min();
     let newest_commit = snapshot.log.first().and_then(|entry| entry.age);
-    [newest_commit, freshest_change].into_iter().flatten().min()
+    let newest_move = snapshot.reflog.first().and_then(|entry| entry.age);
+    let newest_stash = snapshot.stashes.first().and_then(|entry| entry.age);
+    [newest_commit, freshest_change, newest_move, newest_stash]
+        .into_iter()
+        .flatten()
+        .min()
 }
 
 /// Walk the repository and render the full status frame for `dims`.
@@ -467,12 +689,15 @@ pub(crate) fn build_output(
 ///
 /// This is the expensive, side-effecting step: it queries the current branch,
 /// resolves the base ref and counts commits ahead/behind, collects working-tree
-/// changes and their mtimes, and fetches the recent-commit log and upstream
-/// status. The result is a pure description of
+/// changes and their mtimes — expanding each changed submodule with a walk of
+/// its own — and fetches the recent-commit log and upstream status, and — when
+/// asked for — the HEAD reflog and the stash list. The
+/// result is a pure description of
 /// repository state, independent of the live terminal — turning it into a frame
 /// for a given [`watch::Dimensions`] is the separate, cheap [`render_frame`]
 /// half. Watch mode collects once per filesystem change and re-renders the
-/// cached snapshot many times. Uses only `cfg.base` and `cfg.log_lines`.
+/// cached snapshot many times. Uses only `cfg.base` and the section line
+/// counts (`cfg.log_lines`, `cfg.reflog_lines`, `cfg.stash_lines`).
 pub(crate) fn collect_snapshot(repo: &gix::Repository, cfg: &RenderConfig) -> Result<Snapshot> {
     let branch = repo::branch_name(repo);
 
@@ -499,6 +724,8 @@ pub(crate) fn collect_snapshot(repo: &gix::Repository, cfg: &RenderConfig) -> Re
     );
 
     snapshot.log = fetch_log(repo, cfg.log_lines);
+    snapshot.reflog = fetch_reflog(repo, "HEAD", cfg.reflog_lines);
+    snapshot.stashes = fetch_reflog(repo, "refs/stash", cfg.stash_lines);
 
     snapshot.upstream = repo::upstream_status(repo);
     snapshot.push_remote = repo::push_remote(repo);
@@ -516,9 +743,59 @@ pub(crate) fn collect_snapshot(repo: &gix::Repository, cfg: &RenderConfig) -> Re
     .unwrap_or(u32::MAX);
     snapshot.operation = repo::operation_state(repo, conflicts);
 
+    // Only the submodules the walk above flagged are walked themselves: a
+    // clean submodule at its recorded commit has nothing to expand, and a
+    // monorepo's worth of them would otherwise each cost a status traversal.
+    // A group then stands in for the opaque unstaged row it expands.
+    let flagged: Vec<&str> = snapshot
+        .files
+        .iter()
+        .filter(|row| !row.staged)
+        .map(|row| row.path.as_str())
+        .collect();
+    let submodules = collect_submodules(repo, &flagged);
+    snapshot
+        .files
+        .retain(|row| row.staged || !submodules.iter().any(|group| group.path == row.path));
+    snapshot.submodules = submodules;
+
     Ok(snapshot)
 }
 
+/// Walk each checked-out submodule whose path is in `flagged` into a
+/// [`SubmoduleGroup`]: its branch, its drift from the recorded gitlink, and its
+/// own file rows, aged and ordered like the superproject's.
+///
+/// A submodule whose walk fails is left out rather than failing the frame, and
+/// so keeps the superproject's opaque row — less detail, but never less than
+/// gsw showed before it expanded anything. One that turns out to have nothing
+/// to show (a stat-only change) is left out the same way.
+fn collect_submodules(repo: &gix::Repository, flagged: &[&str]) -> Vec<SubmoduleGroup> {
+    repo::submodule_checkouts(repo, |path| flagged.contains(&path))
+        .into_iter()
+        .filter_map(|checkout| {
+            let repo::Changes {
+                entries,
+                staged_numstat,
+                unstaged_numstat,
+            } = repo::collect_changes(&checkout.repo).ok()?;
+            let ages = collect_ages(&entries, checkout.repo.workdir());
+            let files = build_rows(entries, &staged_numstat, &unstaged_numstat, &ages);
+            let (ahead, behind) = checkout.drift();
+            if files.is_empty() && ahead == 0 && behind == 0 {
+                return None;
+            }
+            Some(SubmoduleGroup {
+                branch: repo::branch_name(&checkout.repo),
+                path: checkout.path,
+                ahead,
+                behind,
+                files,
+            })
+        })
+        .collect()
+}
+
 /// Render an already-collected [`Snapshot`] into a frame for `dims`, advancing
 /// every displayed age — and the returned [`Render::freshest_age`] — by
 /// `age_offset`.
@@ -537,63 +814,69 @@ pub(crate) fn render_frame(
     cfg: &RenderConfig,
     dims: watch::Dimensions,
     timing: FrameTiming,
+) -> Render {
+    render_frame_with_cursor(snapshot, cfg, dims, timing, None)
+}
+
+/// [`render_frame`], with watch mode's cursor drawn on the row it resolves to
+/// among the rows that fit (see [`Cursor::resolve`]). `None` is exactly
+/// [`render_frame`], which is what every caller without a cursor goes through.
+pub(crate) fn render_frame_with_cursor(
+    snapshot: &Snapshot,
+    cfg: &RenderConfig,
+    dims: watch::Dimensions,
+    timing: FrameTiming,
+    cursor: Option<&Cursor>,
 ) -> Render {
     let age_offset = timing.age_offset;
     let terminal_width = dims.width;
     let terminal_height = dims.height;
 
-    // Split available terminal rows between the file list and the log
-    // section based on what each actually needs to show. Chrome we
-    // deduct up front:
-    //   header                                                          1
-    //   post-header separator                                            1
-    //   inter-section separator (only when both sections render)         0 or 1
-    //   reserved row for a `+N more files` footer (only when files > 0)  0 or 1
-    // Whatever's left goes to the file list first — it's the primary
+    // Split available terminal rows between the file list and the sections
+    // above it based on what each actually needs to show. Chrome (see
+    // `frame_chrome`) is deducted up front. Whatever's left goes to the file list first — it's the primary
     // content and renders at the bottom, so it must stay fully on-screen
     // rather than being squeezed by a long log (`--log-lines` defaults to
-    // 20). The log takes the remaining rows; only when the file list is
-    // itself truncated does a floor claw rows back to it. See
-    // `plan_section_caps`.
+    // 20). The log, reflog, and stash sections share the remaining rows; only
+    // when the file list is itself truncated does a floor claw rows back to
+    // them. See `plan_section_caps`.
     let file_count = snapshot.files.len();
-    let log_count = snapshot.log.len();
-    // The operation indicator (merge/rebase) is one extra chrome row between
-    // the header and the separator, present only when the snapshot carries an
-    // in-progress operation. Reserve it so the file list at the bottom isn't
-    // pushed past the fold.
-    let header_chrome: usize = 2 + usize::from(snapshot.operation.is_some());
-    let inter_chrome: usize = if file_count > 0 && log_count > 0 {
-        1
-    } else {
-        0
-    };
-    let footer_chrome: usize = if file_count > 0 { 1 } else { 0 };
-    let chrome = header_chrome + inter_chrome + footer_chrome;
-    let available_rows = terminal_height.saturating_sub(chrome).max(1);
-    let (planned_file_cap, planned_log_cap) =
-        plan_section_caps(file_count, log_count, available_rows);
+    let demand = section_demand(snapshot);
+    let file_rows = demand.files;
+    let available_rows = terminal_height
+        .saturating_sub(frame_chrome(snapshot))
+        .max(1);
+    let planned = plan_section_caps(demand, available_rows);
 
     // `--max-files` always wins when the user has set it (including 0,
-    // which means unlimited). When the user pinned a file cap, the log
-    // section just takes whatever rows are left over up to its demand.
-    let (file_cap_opt, log_cap) = match cfg.max_files {
+    // which means unlimited). When the user pinned a file cap, the sections
+    // above the files just share whatever rows are left over, each up to its
+    // demand.
+    let (file_cap_opt, caps) = match cfg.max_files {
         Some(n) => {
-            let consumed_by_files = if n == 0 {
-                file_count
-            } else {
-                n.min(file_count)
-            };
-            let log_budget = available_rows.saturating_sub(consumed_by_files);
-            (Some(n), log_count.min(log_budget))
+            let consumed_by_files = if n == 0 { file_rows } else { n.min(file_rows) };
+            let context_budget = available_rows.saturating_sub(consumed_by_files);
+            (Some(n), share_context(demand, context_budget))
         }
-        None => (Some(planned_file_cap), planned_log_cap),
+        None => (Some(planned.files), planned),
+    };
+
+    // The rows that fit, by the same cap `render_with_offset` applies — the
+    // cursor resolves against these, and the caller moves it over them.
+    let drawn = match file_cap_opt {
+        Some(0) | None => file_count,
+        Some(n) => n.min(file_count),
     };
+    let rows: Vec<RowKey> = snapshot.files.iter().take(drawn).map(RowKey::of).collect();
+    let selected = cursor.and_then(|cursor| cursor.resolve(&rows));
 
     let opts = RenderOptions {
         terminal_width,
         bar_width: cfg.bar_width,
         max_files: file_cap_opt,
-        log_lines: log_cap,
+        log_lines: caps.log,
+        reflog_lines: caps.reflog,
+        stash_lines: caps.stashes,
         truecolor: cfg.truecolor,
         // The clock renders only when a walk is actually scheduled, and it
         // reports the same offset every age on this frame was advanced by.
@@ -601,6 +884,8 @@ pub(crate) fn render_frame(
             last_refresh_ago: age_offset,
             next_refresh_in,
         }),
+        selected,
+        theme: cfg.theme,
     };
 
     // One-shot mode and the watch seed walk render at offset zero, which is
@@ -619,6 +904,51 @@ pub(crate) fn render_frame(
     Render {
         output,
         freshest_age,
+        rows,
+    }
+}
+
+/// Rows [`render_frame`] spends on chrome rather than on files or commits:
+///
+/// ```text
+///   header                                                          1
+///   operation indicator (only mid-merge/rebase)                     0 or 1
+///   post-header separator                                           1
+///   separator between each two sections that render (log, reflog,  0 to 3
+///     stashes, files)
+///   reserved row for a `+N more files` footer (only when files > 0) 0 or 1
+/// ```
+///
+/// The operation indicator sits between the header and the separator, present
+/// only when the snapshot carries an in-progress operation; it is reserved so
+/// the file list at the bottom isn't pushed past the fold. Shared with the
+/// dashboard, which sizes each repository's block from the same count — a
+/// second tally of the same rows is one that could disagree with this one.
+///
+/// A section the row budget then squeezes to nothing draws no separator, so
+/// the count can run one row over what a cramped frame spends — a blank row at
+/// the bottom, never a clipped one.
+pub(crate) fn frame_chrome(snapshot: &Snapshot) -> usize {
+    let demand = section_demand(snapshot);
+    let header_chrome: usize = 2 + usize::from(snapshot.operation.is_some());
+    let sections = [demand.log, demand.reflog, demand.stashes, demand.files]
+        .into_iter()
+        .filter(|&rows| rows > 0)
+        .count();
+    let inter_chrome = sections.saturating_sub(1);
+    let footer_chrome = usize::from(demand.files > 0);
+    header_chrome + inter_chrome + footer_chrome
+}
+
+/// Rows each section of `snapshot`'s frame would draw given unlimited height:
+/// one per file, commit, reflog entry, and stash, plus a title row per
+/// submodule group.
+pub(crate) fn section_demand(snapshot: &Snapshot) -> SectionRows {
+    SectionRows {
+        files: file_section_rows(snapshot),
+        log: snapshot.log.len(),
+        reflog: snapshot.reflog.len(),
+        stashes: snapshot.stashes.len(),
     }
 }
 
@@ -635,12 +965,39 @@ fn fetch_log(repo: &gix::Repository, n: usize) -> Vec<LogEntry> {
     let now = SystemTime::now();
     repo::recent_log(repo, n)
         .into_iter()
-        .map(|(hash, secs, subject)| {
-            let age = u64::try_from(secs)
-                .ok()
-                .map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s))
-                .and_then(|when| now.duration_since(when).ok());
-            LogEntry { hash, subject, age }
+        .map(|(hash, secs, subject)| LogEntry {
+            hash,
+            subject,
+            age: elapsed_since(secs, now),
+        })
+        .collect()
+}
+
+/// How long before `now` the Unix timestamp `secs` was, or `None` when it is
+/// negative or in the future — see [`fetch_log`] for why that is not zero.
+fn elapsed_since(secs: i64, now: SystemTime) -> Option<Duration> {
+    u64::try_from(secs)
+        .ok()
+        .map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s))
+        .and_then(|when| now.duration_since(when).ok())
+}
+
+/// Fetch the `n` newest entries of the `reference` reflog (`HEAD`,
+/// `refs/stash`) as [`ReflogEntry`] records, each named by the selector git
+/// accepts back: `HEAD@{2}`, `stash@{0}`.
+///
+/// Returns an empty list when `n == 0` or the reference has no reflog — no
+/// stash, or `core.logAllRefUpdates` off. Ages follow [`fetch_log`]'s rule: a
+/// timestamp that does not resolve into an elapsed duration is `None`.
+fn fetch_reflog(repo: &gix::Repository, reference: &str, n: usize) -> Vec<ReflogEntry> {
+    let now = SystemTime::now();
+    let short = reference.strip_prefix("refs/").unwrap_or(reference);
+    repo::reflog(repo, reference, n)
+        .into_iter()
+        .map(|(index, secs, message)| ReflogEntry {
+            selector: format!("{short}@{{{index}}}"),
+            message,
+            age: elapsed_since(secs, now),
         })
         .collect()
 }
@@ -748,19 +1105,43 @@ mod tests {
 
     #[test]
     fn refresh_interval_defaults_to_a_minute() {
-        let cli = Cli::parse_from(["gsw"]);
+        let mut defaults = Defaults::default();
+        Cli::parse_from(["gsw"]).override_defaults(&mut defaults);
         assert_eq!(
-            refresh_interval(cli.refresh_interval),
+            refresh_interval(defaults.refresh_interval),
             Some(Duration::from_secs(60)),
             "gsw with no flags should refresh once a minute",
         );
-        let explicit = Cli::parse_from(["gsw", "--refresh-interval", "5"]);
+        Cli::parse_from(["gsw", "--refresh-interval", "5"]).override_defaults(&mut defaults);
         assert_eq!(
-            refresh_interval(explicit.refresh_interval),
+            refresh_interval(defaults.refresh_interval),
             Some(Duration::from_secs(5)),
         );
     }
 
+    #[test]
+    fn flags_override_the_config_files_and_absent_flags_do_not() {
+        let mut defaults = Defaults {
+            base: Some("develop".to_string()),
+            log_lines: 5,
+            bar_width: 9,
+            truecolor: Some(true),
+            ..Defaults::default()
+        };
+        Cli::parse_from(["gsw", "--log-lines", "3", "--no-truecolor"])
+            .override_defaults(&mut defaults);
+        assert_eq!(defaults.log_lines, 3);
+        assert_eq!(defaults.truecolor, Some(false));
+        assert_eq!(
+            defaults.bar_width, 9,
+            "a flag left off keeps the file's value"
+        );
+        assert_eq!(defaults.base.as_deref(), Some("develop"));
+
+        Cli::parse_from(["gsw", "--no-log"]).override_defaults(&mut defaults);
+        assert_eq!(defaults.log_lines, 0, "--no-log beats any log_lines");
+    }
+
     #[test]
     fn operation_line_reserves_a_chrome_row_so_file_list_is_not_clipped() {
         // When an in-progress operation adds its indicator line between the
@@ -786,9 +1167,12 @@ mod tests {
             max_files: None,
             bar_width: 6,
             log_lines: 0,
+            reflog_lines: 0,
+            stash_lines: 0,
             truecolor: false,
             refresh_interval: None,
             width_offset: 0,
+            theme: Theme::DEFAULT,
         };
         let dims = watch::Dimensions {
             width: 80,
@@ -804,6 +1188,9 @@ mod tests {
             upstream: None,
             operation: Some(Operation::Merge { conflicts: 1 }),
             push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         };
         let frame = render_frame(&snap, &cfg, dims, FrameTiming::at_walk(None));
         let lines = frame.output.lines().count();
@@ -816,6 +1203,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_frame_with_every_section_fits_the_terminal_at_any_height() {
+        // Four sections means three rules between them, all of which
+        // `frame_chrome` must reserve, or the file list at the bottom would be
+        // pushed past the fold on exactly the frames with the most to show.
+        let mut snap = snapshot_with(Some(Duration::from_secs(60)), &[Some(Duration::ZERO); 30]);
+        let entry = |selector: String| ReflogEntry {
+            selector,
+            message: "checkout: moving from main to topic".into(),
+            age: Some(Duration::from_secs(5)),
+        };
+        snap.reflog = (0..5).map(|i| entry(format!("HEAD@{{{i}}}"))).collect();
+        snap.stashes = (0..3).map(|i| entry(format!("stash@{{{i}}}"))).collect();
+        let cfg = RenderConfig {
+            base: None,
+            max_files: None,
+            bar_width: 6,
+            log_lines: 20,
+            reflog_lines: 5,
+            stash_lines: 3,
+            truecolor: false,
+            refresh_interval: None,
+            width_offset: 0,
+            theme: Theme::DEFAULT,
+        };
+        // Ten rows is the first height with a content row for every section
+        // once the header, the rules, and the footer are paid for.
+        for height in 10..50 {
+            let dims = watch::Dimensions { width: 80, height };
+            let frame = render_frame(&snap, &cfg, dims, FrameTiming::at_walk(None));
+            let out = frame.output;
+            assert!(out.lines().count() <= height, "height {height}:\n{out}");
+            for first in ["abc1234", "HEAD@{0}", "stash@{0}", "f0.rs"] {
+                assert!(out.contains(first), "height {height} lost {first}:\n{out}");
+            }
+        }
+    }
+
+    #[test]
+    fn a_fresh_move_of_head_paces_the_decay_timer() {
+        // A checkout seconds ago shows a live `5s` in the reflog section,
+        // which has to keep counting even when the newest commit is old.
+        let mut snap = snapshot_with(Some(Duration::from_secs(3600)), &[]);
+        snap.reflog = vec![ReflogEntry {
+            selector: "HEAD@{0}".into(),
+            message: "checkout: moving from main to topic".into(),
+            age: Some(Duration::from_secs(5)),
+        }];
+        assert_eq!(snapshot_freshest_age(&snap), Some(Duration::from_secs(5)));
+    }
+
     /// Build a minimal [`Snapshot`] with the given HEAD-commit age and a file
     /// row per supplied mtime age, so the freshest-age tests can exercise the
     /// commit-vs-change comparison without walking a real repo.
@@ -860,6 +1298,9 @@ mod tests {
             upstream: None,
             operation: None,
             push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         }
     }
 
@@ -982,15 +1423,21 @@ mod tests {
             upstream: None,
             operation: None,
             push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         };
         let cfg = RenderConfig {
             base: None,
             max_files: None,
             bar_width: 6,
             log_lines: 1,
+            reflog_lines: 0,
+            stash_lines: 0,
             truecolor: false,
             refresh_interval: None,
             width_offset: 0,
+            theme: Theme::DEFAULT,
         };
         let dims = watch::Dimensions {
             width: 80,
@@ -14,9 +14,11 @@ use std::time::{Duration, Instant};
 
 use colored::{ColoredString, Colorize};
 
+use crate::action::{FileCommand, FilePrompt};
 use crate::age::{format_age_detailed, scale_rgb};
 use crate::render::{truncate_right, Snapshot, UpstreamStatus};
 use crate::repo::DETACHED_HEAD;
+use crate::sync::{SyncCommand, SyncPrompt};
 use crate::watch::{Dimensions, InputMode};
 
 /// Most rows a status message is allowed to occupy under the frame.
@@ -252,7 +254,7 @@ pub(crate) fn prompt_for(
 /// refuse a repository that moved on in the meantime.
 pub(crate) fn spawn<F>(command: PushCommand, workdir: PathBuf, on_finish: F)
 where
-    F: FnOnce(PushOutcome) + Send + 'static,
+    F: FnOnce(GitOutcome) + Send + 'static,
 {
     std::thread::spawn(move || on_finish(run_push(&command, &workdir)));
 }
@@ -276,8 +278,49 @@ const RETRY_ADVICE: &str = "press p again";
 /// own is microseconds rather than seconds; nothing here can close it entirely,
 /// short of a lock git does not offer.
 ///
-/// Three things are forced on the child, and all of them matter because gsw is
-/// holding the alternate screen in raw mode:
+/// The push itself goes through [`run_git`], which is where the child is kept
+/// away from the terminal.
+fn run_push(command: &PushCommand, workdir: &Path) -> GitOutcome {
+    run_on_branch(command.branch(), command.args(), workdir, RETRY_ADVICE)
+}
+
+/// Run `git <args>` in `workdir` only if `branch` is still the one checked out,
+/// and otherwise refuse with a message that ends in `retry`.
+///
+/// The check [`run_push`] describes, pulled out so every command whose meaning
+/// depends on HEAD gets the same one: a fetch or fast-forward in
+/// [`crate::sync`] resolves `@{upstream}` against whatever is checked out when
+/// git starts, exactly as a bare `git push` does. `retry` names the key that
+/// re-asks, since that is the whole remedy and it differs per command.
+pub(crate) fn run_on_branch(
+    branch: &str,
+    args: &[String],
+    workdir: &Path,
+    retry: &str,
+) -> GitOutcome {
+    // `None` means git could not be run at all, which the command below reports
+    // in git's own terms. Refusing here instead would blame a branch change
+    // that did not happen — and a git that cannot start cannot run it either.
+    if let Some(current) = current_branch(workdir) {
+        if current != branch {
+            return GitOutcome {
+                success: false,
+                output: format!(
+                    "branch changed from {branch} to {current} since the confirmation — {retry}",
+                ),
+            };
+        }
+    }
+    run_git(args, workdir)
+}
+
+/// Run `git <args>` in `workdir` to completion and describe how it went.
+///
+/// Every `git` child watch mode starts goes through here — the push, and the
+/// file actions in [`crate::action`] — because every one of them runs while gsw
+/// is holding the alternate screen in raw mode, and the rules below are what
+/// keep a child from fighting gsw for the terminal. Three things are forced on
+/// the child, and all of them matter:
 ///
 /// - **The child is detached from the terminal** ([`detach_from_terminal`]) —
 ///   its own session on Unix, no inherited console on Windows — so the terminal
@@ -301,25 +344,15 @@ const RETRY_ADVICE: &str = "press p again";
 /// - **Both streams are captured**, which also suppresses git's progress meter:
 ///   it renders only to a terminal, so a pipe removes the carriage-return
 ///   redraws that would otherwise arrive as unreadable status rows.
-fn run_push(command: &PushCommand, workdir: &Path) -> PushOutcome {
-    // `None` means git could not be run at all, which the push below reports in
-    // git's own terms. Refusing here instead would blame a branch change that
-    // did not happen — and a git that cannot start cannot push either.
-    if let Some(current) = current_branch(workdir) {
-        if current != command.branch() {
-            return PushOutcome {
-                success: false,
-                output: format!(
-                    "branch changed from {} to {current} since the confirmation — {RETRY_ADVICE}",
-                    command.branch(),
-                ),
-            };
-        }
-    }
-
+///
+/// A local command like `git add` never prompts and never draws a meter, so for
+/// it the rules are free — and applying them to every child, rather than to the
+/// ones that look like they need it, is what keeps the next command added here
+/// from being the one that did.
+pub(crate) fn run_git(args: &[String], workdir: &Path) -> GitOutcome {
     let mut child = Command::new("git");
     child
-        .args(command.args())
+        .args(args)
         .current_dir(workdir)
         .stdin(Stdio::null())
         .env("GIT_TERMINAL_PROMPT", "0");
@@ -332,7 +365,7 @@ fn run_push(command: &PushCommand, workdir: &Path) -> PushOutcome {
         // every other failure here is git's own words, and this one would
         // otherwise arrive as an empty message.
         Err(error) => {
-            return PushOutcome {
+            return GitOutcome {
                 success: false,
                 output: format!("cannot run git: {error}"),
             }
@@ -343,7 +376,8 @@ fn run_push(command: &PushCommand, workdir: &Path) -> PushOutcome {
     // updates, and every rejection — on stderr, and writes to stdout only under
     // flags gsw does not pass. Leading with it puts the useful lines at the
     // head, which is the part a status message has room for — at most
-    // [`MAX_STATUS_ROWS`], and fewer on a short pane.
+    // [`MAX_STATUS_ROWS`], and fewer on a short pane. The local commands agree:
+    // `git restore` and `git clean` put their refusals on stderr too.
     let mut text = String::from_utf8_lossy(&output.stderr).into_owned();
     text.push_str(&String::from_utf8_lossy(&output.stdout));
     // Carriage returns are how a progress meter redraws in place. Capturing
@@ -355,11 +389,13 @@ fn run_push(command: &PushCommand, workdir: &Path) -> PushOutcome {
     let success = output.status.success();
     if !success && text.trim().is_empty() {
         // A failure with nothing to show would render as a blank row, which
-        // reads as success. The exit status is all git left us.
-        text = format!("git push failed ({})", output.status);
+        // reads as success. The exit status is all git left us — named after
+        // the subcommand, so a failed `git add` does not read as a failed push.
+        let subcommand = args.first().map_or("", String::as_str);
+        text = format!("git {subcommand} failed ({})", output.status);
     }
 
-    PushOutcome {
+    GitOutcome {
         success,
         output: text,
     }
@@ -489,21 +525,23 @@ fn current_branch(workdir: &Path) -> Option<String> {
     Some(name)
 }
 
-/// How a finished `git push` came out.
+/// How a finished `git` child — a push, or a file action — came out.
 ///
 /// `output` is git's own stdout and stderr, kept whole: choosing which of it to
 /// show is [`PushUi`]'s job, and a runner that pre-digested it would decide the
 /// wording from a place with no idea how many rows are free.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct PushOutcome {
-    /// Whether `git push` exited zero.
+pub(crate) struct GitOutcome {
+    /// Whether `git` exited zero.
     pub success: bool,
     /// Everything git wrote, both streams, in the order they were captured.
     pub output: String,
 }
 
 /// Everything the push feature puts on screen, and the input mode that goes
-/// with it.
+/// with it. The file actions on the selected row ride the same machinery — a
+/// discard asks the same way a push does, and both report the same way — so
+/// they share this rather than keeping a second overlay of their own.
 ///
 /// Watch mode holds one of these and asks it two questions — what mode are we
 /// in, and what does the pane show. It never learns whether a prompt or an
@@ -541,12 +579,71 @@ enum State {
     /// mode below can never promise a question the user was not shown.
     Asking {
         question: String,
-        creates_remote_branch: bool,
-        command: PushCommand,
+        /// Drawn yellow: the answer puts something new on a shared remote or
+        /// throws work away.
+        caution: bool,
+        job: Job,
+        success_message: String,
+    },
+    /// A confirmed job is running, saying `notice` until it finishes.
+    Running {
         success_message: String,
+        notice: &'static str,
     },
-    /// `git push` is running.
-    Running { success_message: String },
+}
+
+/// Work the watch loop hands to a `git` child once the user has asked for it:
+/// a push, a file action on the selected row, or a fetch or fast-forward.
+///
+/// One type because the two share everything downstream of the key — the
+/// confirmation, the running notice, the thread, the outcome, and the status it
+/// leaves behind — and a second state machine beside [`PushUi`] would be a
+/// second opinion about which of them is on screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Job {
+    /// `git push`, re-checked against the branch it was confirmed for.
+    Push(PushCommand),
+    /// `git add`, `git reset`, `git restore`, or `git clean` on one row.
+    File(FileCommand),
+    /// `git fetch` or `git merge --ff-only`, re-checked against the branch it
+    /// was decided for.
+    Sync(SyncCommand),
+}
+
+impl Job {
+    /// The key hint a confirmation of this job is drawn with.
+    fn hint(&self) -> &'static str {
+        match self {
+            Self::Push(_) => CONFIRM_HINT,
+            Self::File(_) => crate::action::CONFIRM_HINT,
+            Self::Sync(_) => crate::sync::CONFIRM_HINT,
+        }
+    }
+
+    /// What the overlay says while this job runs.
+    fn notice(&self) -> &'static str {
+        match self {
+            Self::Push(_) => RUNNING_NOTICE,
+            Self::File(command) => command.notice(),
+            Self::Sync(command) => command.notice(),
+        }
+    }
+}
+
+/// A file-action or sync prompt with the module it came from erased: what
+/// [`PushUi::start`] needs, and nothing it would have to match twice.
+enum Step {
+    /// Start `job` now.
+    Run { job: Job, success_message: String },
+    /// Ask about `job` first.
+    Ask {
+        question: String,
+        caution: bool,
+        job: Job,
+        success_message: String,
+    },
+    /// Say why nothing runs.
+    Refuse { message: String },
 }
 
 /// How long a [`State::Status`] message stays under the frame, and how it is
@@ -685,8 +782,8 @@ impl PushUi {
                 success_message,
             } => State::Asking {
                 question,
-                creates_remote_branch,
-                command,
+                caution: creates_remote_branch,
+                job: Job::Push(command),
                 success_message,
             },
             // A refusal describes the repository as it stood when `p` was
@@ -699,23 +796,150 @@ impl PushUi {
         };
     }
 
-    /// Handle `y`: start the push, returning the [`PushCommand`] to run, or
+    /// Handle `p` on a view with nothing to push: post `message` as a fading
+    /// status, exactly as a [`PushPrompt::Refuse`] from [`PushUi::request`]
+    /// would be. Replaces whatever was on screen, for the same reason.
+    pub(crate) fn refuse(&mut self, message: &str, now: Instant) {
+        self.state = State::Status {
+            lines: vec![message.to_string()],
+            life: Life::Fading { posted_at: now },
+        };
+    }
+
+    /// Handle a file-action key on the selected row: run it, ask about it, or
+    /// say why not, as [`crate::action::prompt_for`] decided. Returns the
+    /// [`Job`] to start when it runs without a question.
+    ///
+    /// The question follows every rule [`PushUi::request`] states for its own —
+    /// it replaces whatever was on screen, and a pane with no row to draw it in
+    /// is never asked it — because it is the same row and the same Enter.
+    pub(crate) fn act(
+        &mut self,
+        prompt: FilePrompt,
+        dims: Dimensions,
+        now: Instant,
+    ) -> Option<Job> {
+        let step = match prompt {
+            FilePrompt::Run {
+                command,
+                success_message,
+            } => Step::Run {
+                job: Job::File(command),
+                success_message,
+            },
+            FilePrompt::Confirm {
+                question,
+                command,
+                success_message,
+            } => Step::Ask {
+                question,
+                caution: true,
+                job: Job::File(command),
+                success_message,
+            },
+            FilePrompt::Refuse { message } => Step::Refuse { message },
+        };
+        self.start(step, dims, now)
+    }
+
+    /// Handle `f` or `u`: fetch, ask about a fast-forward, or say why not, as
+    /// [`crate::sync::prompt_for`] decided. Returns the [`Job`] to start when
+    /// it runs without a question.
+    ///
+    /// Every rule [`PushUi::act`] follows applies here too. A fast-forward is
+    /// not drawn in caution yellow: it moves the branch only where its upstream
+    /// already went, and loses nothing `git reflog` cannot give back.
+    pub(crate) fn sync(
+        &mut self,
+        prompt: SyncPrompt,
+        dims: Dimensions,
+        now: Instant,
+    ) -> Option<Job> {
+        let step = match prompt {
+            SyncPrompt::Run {
+                command,
+                success_message,
+            } => Step::Run {
+                job: Job::Sync(command),
+                success_message,
+            },
+            SyncPrompt::Confirm {
+                question,
+                command,
+                success_message,
+            } => Step::Ask {
+                question,
+                caution: false,
+                job: Job::Sync(command),
+                success_message,
+            },
+            SyncPrompt::Refuse { message } => Step::Refuse { message },
+        };
+        self.start(step, dims, now)
+    }
+
+    /// Move to whatever `step` calls for, returning the [`Job`] to start when
+    /// it runs without a question. The one place [`PushUi::act`] and
+    /// [`PushUi::sync`] differ from each other is the prompt type they read.
+    fn start(&mut self, step: Step, dims: Dimensions, now: Instant) -> Option<Job> {
+        let (state, job) = match step {
+            Step::Run {
+                job,
+                success_message,
+            } => (
+                State::Running {
+                    success_message,
+                    notice: job.notice(),
+                },
+                Some(job),
+            ),
+            Step::Ask { .. } if Overlay::rows_to_spare(dims) == 0 => (State::Idle, None),
+            Step::Ask {
+                question,
+                caution,
+                job,
+                success_message,
+            } => (
+                State::Asking {
+                    question,
+                    caution,
+                    job,
+                    success_message,
+                },
+                None,
+            ),
+            Step::Refuse { message } => (
+                State::Status {
+                    lines: vec![message],
+                    life: Life::Fading { posted_at: now },
+                },
+                None,
+            ),
+        };
+        self.state = state;
+        job
+    }
+
+    /// Handle `y`: start the confirmed job, returning the [`Job`] to run, or
     /// `None` when no confirmation was on screen to accept.
     ///
-    /// Moving to [`State::Running`] as it hands the command over is what makes
-    /// a second `y` — one that raced the mode change — return `None` rather than
-    /// start an overlapping push.
-    pub(crate) fn confirm(&mut self) -> Option<PushCommand> {
+    /// Moving to [`State::Running`] as it hands the job over is what makes a
+    /// second `y` — one that raced the mode change — return `None` rather than
+    /// start an overlapping one.
+    pub(crate) fn confirm(&mut self) -> Option<Job> {
         let State::Asking {
-            command,
+            job,
             success_message,
             ..
         } = std::mem::replace(&mut self.state, State::Idle)
         else {
             return None;
         };
-        self.state = State::Running { success_message };
-        Some(command)
+        self.state = State::Running {
+            success_message,
+            notice: job.notice(),
+        };
+        Some(job)
     }
 
     /// Drop a message that has outlived [`STATUS_LIFETIME`].
@@ -745,7 +969,7 @@ impl PushUi {
         }
     }
 
-    /// Handle a finished push: replace the running notice with the outcome.
+    /// Handle a finished job: replace the running notice with the outcome.
     ///
     /// On success the wording comes from the plan that was confirmed, not from
     /// git's output, so a create reports itself as a create. On failure it is
@@ -755,9 +979,11 @@ impl PushUi {
     /// The same split decides how long the message stays: `now` starts the
     /// countdown on a success, and a failure gets no countdown at all. See
     /// [`Life`] for why those are one decision.
-    pub(crate) fn finished(&mut self, outcome: PushOutcome, now: Instant) {
+    pub(crate) fn finished(&mut self, outcome: GitOutcome, now: Instant) {
         let success_message = match std::mem::replace(&mut self.state, State::Idle) {
-            State::Running { success_message } => success_message,
+            State::Running {
+                success_message, ..
+            } => success_message,
             // A finish with no push running: nothing to report against, so
             // leave the screen as it is rather than inventing a message.
             other => {
@@ -840,20 +1066,22 @@ impl PushUi {
             State::Idle => Vec::new(),
             State::Asking {
                 question,
-                creates_remote_branch,
+                caution,
+                job,
                 ..
             } => {
-                let line = truncate_right(&format!("{question}  {CONFIRM_HINT}"), width);
+                let line = truncate_right(&format!("{question}  {}", job.hint()), width);
                 // Yellow marks the push that puts something new on a shared
-                // remote. The wording says so too — the color is what carries
-                // it in the half second before the words are read.
-                vec![if *creates_remote_branch {
+                // remote, and the discard that throws work away. The wording
+                // says so too — the color is what carries it in the half second
+                // before the words are read.
+                vec![if *caution {
                     line.yellow().to_string()
                 } else {
                     line
                 }]
             }
-            State::Running { .. } => vec![truncate_right(RUNNING_NOTICE, width)],
+            State::Running { notice, .. } => vec![truncate_right(notice, width)],
             State::Status { lines, life } => {
                 let elapsed = life.elapsed(now);
                 // The age goes on the last row, which for every message that
@@ -1411,7 +1639,9 @@ mod tests {
 #[cfg(test)]
 mod ui_tests {
     use super::*;
+    use crate::action::FileAction;
     use crate::render::Snapshot;
+    use crate::sync::SyncAction;
     use testcolor::{max_red_channel, TRUECOLOR_FG};
 
     /// A snapshot on `gsw-push` with `origin` available and the given tracking
@@ -1427,6 +1657,9 @@ mod ui_tests {
             upstream,
             operation: None,
             push_remote: Some("origin".to_string()),
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         }
     }
 
@@ -1548,7 +1781,9 @@ mod ui_tests {
     #[test]
     fn confirming_hands_back_the_command_and_switches_to_pushing() {
         let mut ui = asking();
-        let command = ui.confirm().expect("a question on screen must confirm");
+        let Some(Job::Push(command)) = ui.confirm() else {
+            panic!("a question on screen must confirm the push it asked about");
+        };
         assert_eq!(command.args(), ["push", "-u", "origin", "gsw-push"]);
         assert_eq!(
             command.branch(),
@@ -1601,7 +1836,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: true,
                 output: "To /tmp/origin\n * [new branch] gsw-push -> gsw-push\n".to_string(),
             },
@@ -1620,7 +1855,7 @@ mod ui_tests {
         ui.request(&snapshot(tracked(3)), tall_pane(80), t0());
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: true,
                 output: String::new(),
             },
@@ -1639,7 +1874,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "To /tmp/origin\n ! [rejected] gsw-push -> gsw-push (fetch first)\n\
                      error: failed to push some refs to '/tmp/origin'\n"
@@ -1663,7 +1898,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "To /tmp/origin\n\
                      hint: Updates were rejected because the tip is behind\n\
@@ -1697,7 +1932,7 @@ mod ui_tests {
             .map(|n| format!("error: line {n}\n"))
             .collect::<String>();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output,
             },
@@ -1718,7 +1953,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "To /tmp/origin\n\
                      ! [rejected] gsw-push -> gsw-push (fetch first)\n\
@@ -1825,7 +2060,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: (1..=20)
                     .map(|n| format!("error: line {n}\n"))
@@ -1865,7 +2100,7 @@ mod ui_tests {
     const SWEEP_MAX_HEIGHT: usize = 8;
 
     /// A UI reporting the outcome of a push it asked about and ran.
-    fn reporting(outcome: PushOutcome) -> PushUi {
+    fn reporting(outcome: GitOutcome) -> PushUi {
         let mut ui = asking();
         ui.confirm();
         ui.finished(outcome, t0());
@@ -1887,7 +2122,7 @@ mod ui_tests {
             ui
         };
         let dismissed = {
-            let mut ui = reporting(PushOutcome {
+            let mut ui = reporting(GitOutcome {
                 success: false,
                 output: REJECTION.to_string(),
             });
@@ -1916,14 +2151,14 @@ mod ui_tests {
             ("running a push", running),
             (
                 "reporting a successful push",
-                reporting(PushOutcome {
+                reporting(GitOutcome {
                     success: true,
                     output: String::new(),
                 }),
             ),
             (
                 "reporting a failed push",
-                reporting(PushOutcome {
+                reporting(GitOutcome {
                     success: false,
                     output: REJECTION.to_string(),
                 }),
@@ -2030,7 +2265,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "   \n\n".to_string(),
             },
@@ -2050,7 +2285,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             },
@@ -2139,7 +2374,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             },
@@ -2172,7 +2407,7 @@ mod ui_tests {
         ui.request(&snapshot(tracked(3)), tall_pane(80), at);
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: true,
                 output: String::new(),
             },
@@ -2272,7 +2507,7 @@ mod ui_tests {
         let mut ui = asking();
         ui.confirm();
         ui.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             },
@@ -2490,7 +2725,7 @@ mod ui_tests {
         let mut failed = asking();
         failed.confirm();
         failed.finished(
-            PushOutcome {
+            GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             },
@@ -2524,6 +2759,140 @@ mod ui_tests {
             }
         }
     }
+
+    /// An unstaged, modified row at `path`, for the file-action tests.
+    fn unstaged(path: &str) -> crate::render::RenderEntry {
+        crate::render::RenderEntry {
+            path: path.to_string(),
+            orig_path: None,
+            status: crate::git::FileStatus::Modified,
+            staged: false,
+            adds: 1,
+            dels: 1,
+            binary: false,
+            age: None,
+        }
+    }
+
+    #[test]
+    fn a_file_action_that_needs_no_question_starts_at_once() {
+        // Staging is undone by one key, so there is nothing to confirm: the
+        // job is handed straight back and the keys go to the running mode.
+        let mut ui = PushUi::new(false);
+        let prompt = crate::action::prompt_for(FileAction::Stage, &unstaged("a.txt"));
+        let job = ui.act(prompt, tall_pane(80), t0());
+        assert!(matches!(job, Some(Job::File(_))), "got {job:?}");
+        assert_eq!(ui.mode(), InputMode::Pushing);
+        let overlay = ui.overlay(tall_pane(80), t0()).text();
+        assert!(overlay.contains("Staging"), "got {overlay:?}");
+    }
+
+    #[test]
+    fn a_discard_asks_with_its_own_hint_and_starts_only_on_yes() {
+        let mut ui = PushUi::new(false);
+        let prompt = crate::action::prompt_for(FileAction::Discard, &unstaged("a.txt"));
+        assert!(ui.act(prompt, tall_pane(80), t0()).is_none());
+        assert_eq!(ui.mode(), InputMode::Confirm);
+        let overlay = ui.overlay(tall_pane(80), t0()).text();
+        assert!(
+            overlay.contains("a.txt?"),
+            "the question names the file: {overlay:?}"
+        );
+        // The push's hint says "push"; a discard's must say what `y` does here.
+        assert!(
+            overlay.contains(crate::action::CONFIRM_HINT),
+            "got {overlay:?}",
+        );
+        assert!(matches!(ui.confirm(), Some(Job::File(_))));
+        assert!(ui.confirm().is_none(), "a second yes starts nothing");
+    }
+
+    #[test]
+    fn a_discard_is_never_asked_in_a_pane_with_no_row_for_the_question() {
+        // The rule `p` follows, for the same reason: an unseen question would
+        // let the Enter behind the key answer it.
+        let mut ui = PushUi::new(false);
+        let prompt = crate::action::prompt_for(FileAction::Discard, &unstaged("a.txt"));
+        let flat = Dimensions {
+            width: 80,
+            height: 1,
+        };
+        assert!(ui.act(prompt, flat, t0()).is_none());
+        assert_eq!(ui.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn a_refused_file_action_says_why_and_leaves_the_keys_alone() {
+        let mut ui = PushUi::new(false);
+        let prompt = crate::action::prompt_for(FileAction::Unstage, &unstaged("a.txt"));
+        assert!(ui.act(prompt, tall_pane(80), t0()).is_none());
+        assert_eq!(ui.mode(), InputMode::Normal);
+        let overlay = ui.overlay(tall_pane(80), t0()).text();
+        assert!(
+            overlay.contains("a.txt has nothing staged"),
+            "got {overlay:?}"
+        );
+    }
+
+    fn behind_origin(behind: u32) -> UpstreamStatus {
+        UpstreamStatus {
+            name: "origin/main".to_string(),
+            ahead: 0,
+            behind,
+        }
+    }
+
+    #[test]
+    fn a_fetch_starts_at_once_and_says_so_while_it_runs() {
+        let mut ui = PushUi::new(false);
+        let prompt = crate::sync::prompt_for(
+            SyncAction::Fetch,
+            "main",
+            Some("origin"),
+            Some(&behind_origin(0)),
+        );
+        let job = ui.sync(prompt, tall_pane(80), t0());
+        assert!(matches!(job, Some(Job::Sync(_))), "got {job:?}");
+        assert_eq!(ui.mode(), InputMode::Pushing);
+        let overlay = ui.overlay(tall_pane(80), t0()).text();
+        assert!(overlay.contains("Fetching"), "got {overlay:?}");
+    }
+
+    #[test]
+    fn a_fast_forward_asks_with_its_own_hint_and_reports_what_it_did() {
+        let mut ui = PushUi::new(false);
+        let prompt = crate::sync::prompt_for(
+            SyncAction::FastForward,
+            "main",
+            Some("origin"),
+            Some(&behind_origin(2)),
+        );
+        assert!(ui.sync(prompt, tall_pane(120), t0()).is_none());
+        assert_eq!(ui.mode(), InputMode::Confirm);
+        let overlay = ui.overlay(tall_pane(120), t0()).text();
+        assert!(
+            overlay.contains("Fast-forward main by 2 commits from origin/main?"),
+            "got {overlay:?}",
+        );
+        assert!(
+            overlay.contains(crate::sync::CONFIRM_HINT),
+            "got {overlay:?}"
+        );
+
+        assert!(matches!(ui.confirm(), Some(Job::Sync(_))));
+        ui.finished(
+            GitOutcome {
+                success: true,
+                output: String::new(),
+            },
+            t0(),
+        );
+        let overlay = ui.overlay(tall_pane(120), t0()).text();
+        assert!(
+            overlay.contains("Fast-forwarded main by 2 commits"),
+            "got {overlay:?}",
+        );
+    }
 }
 
 #[cfg(test)]
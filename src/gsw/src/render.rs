@@ -2,14 +2,13 @@
 
 use std::time::Duration;
 
-use colored::{ColoredString, Colorize};
+use colored::{Color, ColoredString, Colorize};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::age::{
-    age_dim_level, age_fade_factor, fade_rgb, format_age_detailed, AgeDim, AGE_WIDTH,
-};
+use crate::age::{age_dim_level, format_age_detailed, AgeDim, FadeCurve, AGE_WIDTH};
 use crate::bar::render_bar;
 use crate::git::FileStatus;
+use crate::theme::{Hue, Section, Theme};
 
 /// Everything render() needs to draw one frame.
 #[derive(Debug, Clone)]
@@ -27,6 +26,21 @@ pub struct Snapshot {
     /// frame shows that age here and nowhere else, and the watch-mode decay
     /// timer reads it from here too.
     pub log: Vec<LogEntry>,
+    /// Where HEAD recently moved, newest first — the head of `git reflog`.
+    /// Empty when not requested (`--reflog-lines 0`, the default).
+    ///
+    /// Mid-rebase this is the one place the frame shows how far the replay
+    /// has got and where the branch stood before it started, which the log
+    /// cannot: the log is HEAD's ancestry, and a rebase rewrites it.
+    pub reflog: Vec<ReflogEntry>,
+    /// The `refs/stash` entries, newest first (`stash@{0}` leads). Empty when
+    /// not requested (`--stash-lines 0`, the default) or nothing is stashed.
+    pub stashes: Vec<ReflogEntry>,
+    /// Checked-out submodules with something to show — changed files, or a
+    /// checkout that is not the commit the superproject records — each drawn
+    /// as an indented group under the superproject's own file rows. A group
+    /// replaces the superproject's opaque unstaged row for the same path.
+    pub submodules: Vec<SubmoduleGroup>,
     /// Upstream tracking branch status (ahead/behind). `None` when the
     /// current branch has no configured upstream.
     pub upstream: Option<UpstreamStatus>,
@@ -93,6 +107,44 @@ pub struct LogEntry {
     pub age: Option<Duration>,
 }
 
+/// One reflog row: a move of HEAD, or a stash.
+///
+/// Both sections read a reflog — `HEAD`'s and `refs/stash`'s — and draw the
+/// same row, so they share one type.
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    /// The entry's reflog selector, `HEAD@{3}` or `stash@{1}`. It is what the
+    /// row leads with because it is what git takes back: `git reset --hard
+    /// HEAD@{3}` and `git stash pop stash@{1}` name exactly the row read off
+    /// the screen, where a hash would need the index appended by hand.
+    pub selector: String,
+    /// The message git wrote with the entry: `checkout: moving from main to
+    /// topic`, `rebase (pick): Fix the thing`, `WIP on main: 1a2b3c4 Subject`.
+    pub message: String,
+    /// How long ago the entry was written, with the same `None` rule as
+    /// [`LogEntry::age`]: a timestamp ahead of the local clock, or one that
+    /// does not resolve, is an age gsw does not have.
+    pub age: Option<Duration>,
+}
+
+/// A submodule expanded under the file list: a title row naming it, then its
+/// own file rows, indented.
+#[derive(Debug, Clone)]
+pub struct SubmoduleGroup {
+    /// Where the submodule lives, relative to the superproject's work tree.
+    pub path: String,
+    /// The submodule's checked-out branch, or `HEAD` when detached — which,
+    /// after a plain `git submodule update`, is the usual state.
+    pub branch: String,
+    /// Commits the submodule's HEAD has that the superproject's recorded
+    /// gitlink does not.
+    pub ahead: u32,
+    /// Commits the recorded gitlink has that the submodule's HEAD does not.
+    pub behind: u32,
+    /// The submodule's own changed files, ordered like the superproject's.
+    pub files: Vec<RenderEntry>,
+}
+
 /// One file row in the frame.
 #[derive(Debug, Clone)]
 pub struct RenderEntry {
@@ -116,6 +168,10 @@ pub struct RenderOptions {
     pub max_files: Option<usize>,
     /// Maximum recent-commit rows to render. 0 disables the log section.
     pub log_lines: usize,
+    /// Maximum HEAD reflog rows to render. 0 disables the section.
+    pub reflog_lines: usize,
+    /// Maximum stash rows to render. 0 disables the section.
+    pub stash_lines: usize,
     /// When true, the commit-log rows and the file rows fade from a bright
     /// base color toward a dark floor as they age. The fade uses 24-bit
     /// (truecolor) ANSI. When false, both use the same 8-color/dim styling
@@ -126,6 +182,13 @@ pub struct RenderOptions {
     /// exits has no next refresh to count down to, and stamping a countdown
     /// into piped output would date the capture the moment it is read.
     pub refresh: Option<RefreshStatus>,
+    /// Index into the snapshot's files of the row watch mode's cursor is on,
+    /// drawn highlighted. `None` — always, outside watch mode — draws every
+    /// row as it was drawn before there was a cursor. An index past the rows
+    /// that fit highlights nothing.
+    pub selected: Option<usize>,
+    /// The palette, fade curve, and section order to draw with.
+    pub theme: Theme,
 }
 
 /// When the displayed snapshot was collected, and when the next one is due.
@@ -207,65 +270,206 @@ pub(crate) fn render_with_offset(
     }
     lines.push(render_separator(opts.terminal_width, opts.refresh.as_ref()));
 
-    let display_count = match opts.max_files {
-        Some(0) | None => snapshot.files.len(),
-        Some(n) => n.min(snapshot.files.len()),
+    // Each section renders to a block of its own, and the blocks stack in the
+    // theme's order. The built-in order keeps the recent-commit log directly
+    // under the header, so it stays anchored in place, and the file list at
+    // the bottom: as files appear and disappear during work, they grow and
+    // shrink downward without shoving the log around. The reflog and the
+    // stash list sit between them, in that order — the reflog next to the
+    // history it explains.
+    //
+    // A rule separates each two blocks that drew anything. The first needs
+    // none: the post-header separator already sits directly above it, and
+    // another would produce a double rule with nothing between them. The
+    // inter-section rules stay blank: the refresh clock belongs to the frame,
+    // and printing it twice would make a reader check whether the copies agree.
+    let mut above = false;
+    for section in opts.theme.sections {
+        let block = match section {
+            Section::Log => snapshot
+                .log
+                .iter()
+                .take(opts.log_lines)
+                .map(|entry| render_log_row(entry, opts, age_offset))
+                .collect(),
+            Section::Reflog => render_selector_section(
+                &snapshot.reflog,
+                opts.reflog_lines,
+                (Hue::ReflogSelector, Color::Cyan),
+                opts,
+                age_offset,
+            ),
+            Section::Stash => render_selector_section(
+                &snapshot.stashes,
+                opts.stash_lines,
+                (Hue::StashSelector, Color::Magenta),
+                opts,
+                age_offset,
+            ),
+            Section::Files => render_file_section(snapshot, opts, age_offset),
+        };
+        if block.is_empty() {
+            continue;
+        }
+        if above {
+            lines.push(render_separator(opts.terminal_width, None));
+        }
+        lines.extend(block);
+        above = true;
+    }
+
+    lines.join("\n")
+}
+
+/// The reflog or stash section: up to `cap` of `entries`, each led by its
+/// selector in the section's `(hue, fallback)` color. Empty when the section
+/// is off or has nothing to list.
+fn render_selector_section(
+    entries: &[ReflogEntry],
+    cap: usize,
+    (hue, fallback): (Hue, Color),
+    opts: &RenderOptions,
+    age_offset: Duration,
+) -> Vec<String> {
+    entries
+        .iter()
+        .take(cap)
+        .map(|entry| {
+            let age = entry.age.map(|age| age.saturating_add(age_offset));
+            render_history_row(
+                &entry.selector,
+                colorize_selector(
+                    &entry.selector,
+                    age,
+                    opts.truecolor,
+                    hue,
+                    fallback,
+                    &opts.theme,
+                ),
+                &entry.message,
+                age,
+                opts,
+            )
+        })
+        .collect()
+}
+
+/// The file section: the superproject's rows, then each submodule group, then
+/// a `+N more files` footer for whatever the budget left off. Empty when there
+/// is nothing changed to list.
+fn render_file_section(
+    snapshot: &Snapshot,
+    opts: &RenderOptions,
+    age_offset: Duration,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    if snapshot.files.is_empty() && snapshot.submodules.is_empty() {
+        return lines;
+    }
+
+    // Rows the file section may draw: the superproject's files first, then
+    // the submodule groups out of whatever is left.
+    let file_budget = match opts.max_files {
+        Some(0) | None => usize::MAX,
+        Some(n) => n,
     };
+    let display_count = file_budget.min(snapshot.files.len());
+    // One scale for every bar in the section, so a submodule's rows compare
+    // with the superproject's at a glance.
     let max_change = snapshot
         .files
         .iter()
+        .chain(snapshot.submodules.iter().flat_map(|group| &group.files))
         .map(|e| e.adds.saturating_add(e.dels))
         .max()
         .unwrap_or(0)
         .max(1);
     let path_width = compute_path_width(opts);
 
-    // The recent-commit log renders first, directly under the header, so it
-    // stays anchored in place. The file list renders at the bottom: as files
-    // appear and disappear during work, they grow and shrink downward without
-    // shoving the log around.
-    let log_rendered = opts.log_lines > 0 && !snapshot.log.is_empty();
-    if log_rendered {
-        for entry in snapshot.log.iter().take(opts.log_lines) {
-            lines.push(render_log_row(
-                entry,
-                opts.terminal_width,
-                opts.truecolor,
-                age_offset,
-            ));
-        }
+    for (index, entry) in snapshot.files.iter().take(display_count).enumerate() {
+        let row = render_row(entry, opts, max_change, path_width, age_offset);
+        lines.push(if opts.selected == Some(index) {
+            highlight_row(&row)
+        } else {
+            row
+        });
     }
 
-    if !snapshot.files.is_empty() {
-        // Separate the file list from the log above it. When there's no log,
-        // the post-header separator already sits directly above the files, so
-        // adding another would produce a double rule with nothing between them.
-        if log_rendered {
-            // The inter-section rule stays blank: the refresh clock belongs to
-            // the frame, not to the file list, and printing it twice would make
-            // a reader check whether the two copies agree.
-            lines.push(render_separator(opts.terminal_width, None));
+    // Submodule groups spend what the superproject's rows left of the
+    // same budget, a title row each before their files. A group whose
+    // title does not fit is hidden whole, and its files are counted below.
+    let mut budget = file_budget.saturating_sub(display_count);
+    let mut hidden = snapshot.files.len() - display_count;
+    let group_path_width = path_width.saturating_sub(SUBMODULE_INDENT).max(1);
+    for group in &snapshot.submodules {
+        if budget == 0 {
+            hidden += group.files.len();
+            continue;
         }
-        for entry in snapshot.files.iter().take(display_count) {
-            lines.push(render_row(entry, opts, max_change, path_width, age_offset));
+        lines.push(render_submodule_title(group, opts.terminal_width));
+        budget -= 1;
+        let shown = budget.min(group.files.len());
+        budget -= shown;
+        hidden += group.files.len() - shown;
+        for entry in group.files.iter().take(shown) {
+            let row = render_row(entry, opts, max_change, group_path_width, age_offset);
+            lines.push(format!("{}{row}", " ".repeat(SUBMODULE_INDENT)));
         }
+    }
 
-        let hidden = snapshot.files.len().saturating_sub(display_count);
-        if hidden > 0 {
-            lines.push(
-                format!(
-                    "  +{hidden} more file{}",
-                    if hidden == 1 { "" } else { "s" }
-                )
-                .dimmed()
-                .to_string(),
-            );
-        }
+    if hidden > 0 {
+        lines.push(
+            format!(
+                "  +{hidden} more file{}",
+                if hidden == 1 { "" } else { "s" }
+            )
+            .dimmed()
+            .to_string(),
+        );
     }
+    lines
+}
 
-    lines.join("\n")
+/// Columns a submodule's file rows are indented by, under its title row.
+const SUBMODULE_INDENT: usize = 2;
+
+/// Rows the file section would draw given unlimited height: one per file, the
+/// superproject's and every submodule's, plus one title row per submodule
+/// group.
+pub(crate) fn file_section_rows(snapshot: &Snapshot) -> usize {
+    snapshot.files.len()
+        + snapshot
+            .submodules
+            .iter()
+            .map(|group| 1 + group.files.len())
+            .sum::<usize>()
+}
+
+/// Render a submodule group's title row: `▸ libs/core (HEAD) ↑2 ↓0 vs
+/// recorded`.
+///
+/// The drift against the recorded gitlink is yellow, like the header's behind
+/// segment, because it is the same kind of news: the superproject and the
+/// checkout disagree, and the next superproject commit either records the move
+/// or a `git submodule update` undoes it. It is kept whole or dropped, never
+/// cut — a clipped count is a wrong count — and the name gives way first.
+fn render_submodule_title(group: &SubmoduleGroup, width: usize) -> String {
+    let title = format!("▸ {} ({})", group.path, group.branch);
+    let drift = (group.ahead > 0 || group.behind > 0)
+        .then(|| format!(" ↑{} ↓{} vs recorded", group.ahead, group.behind))
+        .filter(|drift| UnicodeWidthStr::width(drift.as_str()) + SUBMODULE_TITLE_FLOOR <= width);
+    let drift_width = drift.as_deref().map_or(0, UnicodeWidthStr::width);
+    let title = truncate_right(&title, width.saturating_sub(drift_width));
+    match drift {
+        Some(drift) => format!("{}{}", title.bold(), drift.yellow()),
+        None => title.bold().to_string(),
+    }
 }
 
+/// Columns a submodule title keeps for its name before the drift segment is
+/// dropped to make room.
+const SUBMODULE_TITLE_FLOOR: usize = 12;
+
 /// Visible gap between the short hash and the subject in a log row.
 const LOG_HASH_SUBJECT_SEP: &str = "  ";
 
@@ -277,28 +481,46 @@ const LOG_HASH_SUBJECT_SEP: &str = "  ";
 /// row byte-identical to the un-offset render. An age the repository could not
 /// give us ([`LogEntry::age`] of `None`) stays unknown whatever the offset —
 /// advancing a duration gsw never had produces a number it still cannot back.
-fn render_log_row(entry: &LogEntry, width: usize, truecolor: bool, age_offset: Duration) -> String {
-    // Layout: `{hash}  {subject…}   {age}` — the rightmost AGE_FIELD cells
-    // hold the right-aligned age, matching the file-row age column exactly.
-    // The subject is padded to fill the gap so the age column lines up.
+fn render_log_row(entry: &LogEntry, opts: &RenderOptions, age_offset: Duration) -> String {
     let effective_age = entry.age.map(|age| age.saturating_add(age_offset));
-    let hash_width = UnicodeWidthStr::width(entry.hash.as_str());
-    let hash_sep_width = LOG_HASH_SUBJECT_SEP.chars().count();
+    let hash = colorize_log_hash(&entry.hash, effective_age, opts.truecolor, &opts.theme);
+    render_history_row(&entry.hash, hash, &entry.subject, effective_age, opts)
+}
+
+/// Lay out one row of the sections above the file list: a leading `label`
+/// (a commit's hash, a reflog selector) already colored by the caller, the
+/// `text` truncated to fit, and the right-aligned age, all faded by `age`.
+///
+/// `age` is the displayed age — already advanced by the frame's offset — so
+/// every section ages forward in step and fades on the same curve as the file
+/// list.
+fn render_history_row(
+    label: &str,
+    colored_label: ColoredString,
+    text: &str,
+    age: Option<Duration>,
+    opts: &RenderOptions,
+) -> String {
+    let width = opts.terminal_width;
+    // Layout: `{label}  {text…}   {age}` — the rightmost AGE_FIELD cells
+    // hold the right-aligned age, matching the file-row age column exactly.
+    // The text is padded to fill the gap so the age column lines up.
+    let label_width = UnicodeWidthStr::width(label);
+    let label_sep_width = LOG_HASH_SUBJECT_SEP.chars().count();
     let sep_to_age = " ".repeat(SEP_DELS_AGE);
 
-    let subject_budget = width
-        .saturating_sub(hash_width + hash_sep_width + SEP_DELS_AGE + AGE_FIELD)
+    let text_budget = width
+        .saturating_sub(label_width + label_sep_width + SEP_DELS_AGE + AGE_FIELD)
         .max(1);
-    let subject_truncated = truncate_right(&entry.subject, subject_budget);
-    let subject_padded = pad_right(&subject_truncated, subject_budget);
+    let text_truncated = truncate_right(text, text_budget);
+    let text_padded = pad_right(&text_truncated, text_budget);
 
-    let age_raw = effective_age.map_or_else(|| UNKNOWN_AGE.to_string(), format_age_detailed);
+    let age_raw = age.map_or_else(|| UNKNOWN_AGE.to_string(), format_age_detailed);
     let age_field = format!("{age_raw:>width$}", width = AGE_FIELD);
 
-    let hash_str = colorize_log_hash(&entry.hash, effective_age, truecolor);
-    let subject_str = colorize_log_subject(&subject_padded, effective_age, truecolor);
-    let age_str = colorize_log_age(&age_field, effective_age, truecolor);
-    format!("{hash_str}{LOG_HASH_SUBJECT_SEP}{subject_str}{sep_to_age}{age_str}")
+    let text_str = colorize_log_subject(&text_padded, age, opts.truecolor, &opts.theme);
+    let age_str = colorize_log_age(&age_field, age, opts.truecolor, &opts.theme);
+    format!("{colored_label}{LOG_HASH_SUBJECT_SEP}{text_str}{sep_to_age}{age_str}")
 }
 
 /// Total width of everything to the right of the path column: the bar plus
@@ -567,6 +789,27 @@ fn render_operation_line(op: &Operation, width: usize) -> String {
     label_styled.to_string()
 }
 
+/// Mark an already-rendered file row as the one under watch mode's cursor.
+///
+/// Reverse video over the whole row, re-applied after every reset the row's
+/// own coloring emits — a single reverse at the start would end at the first
+/// colored cell. Width is untouched: only escape sequences are added, so the
+/// highlighted row lines up with every row around it.
+///
+/// Asked of `colored` first, so a session with color turned off gets the row
+/// back unchanged rather than escapes it did not want.
+fn highlight_row(row: &str) -> String {
+    const REVERSE: &str = "\x1b[7m";
+    const RESET: &str = "\x1b[0m";
+    if row.reversed().to_string() == row {
+        return row.to_string();
+    }
+    format!(
+        "{REVERSE}{}{RESET}",
+        row.replace(RESET, &format!("{RESET}{REVERSE}"))
+    )
+}
+
 /// Render one file row.
 ///
 /// `age_offset` is added (saturating) to the file's mtime age before it is
@@ -584,8 +827,9 @@ fn render_row(
 ) -> String {
     let (icon, letter) = icon_and_letter(entry);
     let effective_age = entry.age.map(|a| a.saturating_add(age_offset));
-    let factor = fade_factor(effective_age);
+    let factor = fade_factor(effective_age, &opts.theme.fade);
     let truecolor = opts.truecolor;
+    let theme = &opts.theme;
 
     let path_display_raw = match &entry.orig_path {
         Some(orig) => format!("{orig} → {new}", new = entry.path),
@@ -594,9 +838,9 @@ fn render_row(
     let path_truncated = truncate_left(&path_display_raw, path_width);
     let path_padded = pad_right(&path_truncated, path_width);
 
-    let icon_str = colorize_icon(icon, entry, factor, truecolor);
-    let letter_str = colorize_letter(letter, entry, factor, truecolor);
-    let path_str = colorize_path(&path_padded, entry, factor, truecolor);
+    let icon_str = colorize_icon(icon, entry, factor, truecolor, theme);
+    let letter_str = colorize_letter(letter, entry, factor, truecolor, theme);
+    let path_str = colorize_path(&path_padded, entry, factor, truecolor, theme);
 
     if matches!(
         entry.status,
@@ -606,7 +850,7 @@ fn render_row(
         let gutter = " ".repeat(gutter_width);
         let age = effective_age.map(format_age_detailed).unwrap_or_default();
         let age_field = format!("{age:>width$}", width = AGE_FIELD);
-        let age_str = colorize_age(&age_field, effective_age, factor, truecolor);
+        let age_str = colorize_age(&age_field, effective_age, factor, truecolor, theme);
         return format!("{icon_str} {letter_str} {path_str}{gutter}{age_str}");
     }
 
@@ -619,7 +863,7 @@ fn render_row(
             opts.bar_width,
         )
     };
-    let bar_str = colorize_bar(&bar_raw, entry, factor, truecolor);
+    let bar_str = colorize_bar(&bar_raw, entry, factor, truecolor, theme);
 
     let adds_raw = if entry.adds > 0 {
         format!("+{}", entry.adds)
@@ -635,19 +879,19 @@ fn render_row(
     let dels_field = format!("{dels_raw:>width$}", width = DELS_FIELD);
 
     let adds_str = if entry.adds > 0 {
-        colorize_adds(&adds_field, factor, truecolor).to_string()
+        colorize_adds(&adds_field, factor, truecolor, theme).to_string()
     } else {
         adds_field
     };
     let dels_str = if entry.dels > 0 {
-        colorize_dels(&dels_field, factor, truecolor).to_string()
+        colorize_dels(&dels_field, factor, truecolor, theme).to_string()
     } else {
         dels_field
     };
 
     let age_raw = effective_age.map(format_age_detailed).unwrap_or_default();
     let age_field = format!("{age_raw:>width$}", width = AGE_FIELD);
-    let age_str = colorize_age(&age_field, effective_age, factor, truecolor);
+    let age_str = colorize_age(&age_field, effective_age, factor, truecolor, theme);
 
     let sep_bar_adds = " ".repeat(SEP_BAR_ADDS);
     let sep_adds_dels = " ".repeat(SEP_ADDS_DELS);
@@ -678,16 +922,22 @@ fn icon_and_letter(entry: &RenderEntry) -> (char, char) {
     (icon, letter)
 }
 
-fn colorize_icon(icon: char, entry: &RenderEntry, factor: f32, truecolor: bool) -> ColoredString {
+fn colorize_icon(
+    icon: char,
+    entry: &RenderEntry,
+    factor: f32,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     let s = icon.to_string();
     if truecolor {
         let base = match entry.status {
-            FileStatus::Conflicted => FILE_ICON_CONFLICT_RGB,
-            FileStatus::Untracked | FileStatus::UntrackedDir => FILE_ICON_UNTRACKED_RGB,
-            _ if entry.staged => FILE_ICON_STAGED_RGB,
-            _ => FILE_ICON_UNSTAGED_RGB,
+            FileStatus::Conflicted => Hue::IconConflict,
+            FileStatus::Untracked | FileStatus::UntrackedDir => Hue::IconUntracked,
+            _ if entry.staged => Hue::IconStaged,
+            _ => Hue::IconUnstaged,
         };
-        let (r, g, b) = fade_rgb(base, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[base], factor);
         return s.truecolor(r, g, b);
     }
     match entry.status {
@@ -703,18 +953,19 @@ fn colorize_letter(
     entry: &RenderEntry,
     factor: f32,
     truecolor: bool,
+    theme: &Theme,
 ) -> ColoredString {
     let s = letter.to_string();
     if truecolor {
         let base = match entry.status {
-            FileStatus::Conflicted => FILE_LETTER_CONFLICT_RGB,
-            FileStatus::Untracked | FileStatus::UntrackedDir => FILE_LETTER_UNTRACKED_RGB,
-            FileStatus::Added => FILE_LETTER_ADDED_RGB,
-            FileStatus::Deleted => FILE_LETTER_DELETED_RGB,
-            FileStatus::Renamed | FileStatus::Copied => FILE_LETTER_RENAMED_RGB,
-            _ => FILE_LETTER_DEFAULT_RGB,
+            FileStatus::Conflicted => Hue::LetterConflict,
+            FileStatus::Untracked | FileStatus::UntrackedDir => Hue::LetterUntracked,
+            FileStatus::Added => Hue::LetterAdded,
+            FileStatus::Deleted => Hue::LetterDeleted,
+            FileStatus::Renamed | FileStatus::Copied => Hue::LetterRenamed,
+            _ => Hue::LetterOther,
         };
-        let (r, g, b) = fade_rgb(base, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[base], factor);
         return s.truecolor(r, g, b);
     }
     match entry.status {
@@ -727,15 +978,21 @@ fn colorize_letter(
     }
 }
 
-fn colorize_path(path: &str, entry: &RenderEntry, factor: f32, truecolor: bool) -> ColoredString {
+fn colorize_path(
+    path: &str,
+    entry: &RenderEntry,
+    factor: f32,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     if truecolor {
         let base = match entry.status {
-            FileStatus::Conflicted => FILE_PATH_CONFLICT_RGB,
-            FileStatus::Untracked | FileStatus::UntrackedDir => FILE_PATH_UNTRACKED_RGB,
-            _ if entry.staged => FILE_PATH_STAGED_RGB,
-            _ => FILE_PATH_UNSTAGED_RGB,
+            FileStatus::Conflicted => Hue::PathConflict,
+            FileStatus::Untracked | FileStatus::UntrackedDir => Hue::PathUntracked,
+            _ if entry.staged => Hue::PathStaged,
+            _ => Hue::PathUnstaged,
         };
-        let (r, g, b) = fade_rgb(base, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[base], factor);
         return path.truecolor(r, g, b);
     }
     match entry.status {
@@ -746,15 +1003,6 @@ fn colorize_path(path: &str, entry: &RenderEntry, factor: f32, truecolor: bool)
     }
 }
 
-/// Dim cyan used as the background under a partial-fill cell so the
-/// unpainted right portion of glyphs like `▍` blends into the dim `░`
-/// empty cells beside them instead of showing through as terminal black.
-/// `░` is the LIGHT SHADE character (~25% pixel coverage), so this is
-/// roughly a quarter of typical ANSI cyan's brightness.
-const BAR_PARTIAL_BG_CYAN: (u8, u8, u8) = (0, 48, 48);
-/// Same idea for the conflicted-file bar, which paints in red.
-const BAR_PARTIAL_BG_RED: (u8, u8, u8) = (48, 0, 0);
-
 /// Build one `ColoredString` per styling region of `bar`. For normal bars
 /// each visible cell needs its own region because partial-fill cells get a
 /// distinct background color; for binary bars there's no per-cell variation
@@ -768,32 +1016,33 @@ fn colorize_bar_styled(
     entry: &RenderEntry,
     factor: f32,
     truecolor: bool,
+    theme: &Theme,
 ) -> Vec<ColoredString> {
     if entry.binary {
         if truecolor {
-            let (r, g, b) = fade_rgb(FILE_BIN_RGB, factor);
+            let (r, g, b) = theme.fade.rgb(theme.palette[Hue::Binary], factor);
             return vec![bar.to_string().truecolor(r, g, b)];
         }
         return vec![bar.to_string().dimmed()];
     }
     let is_conflicted = matches!(entry.status, FileStatus::Conflicted);
     let (bg_br, bg_bg, bg_bb) = if is_conflicted {
-        BAR_PARTIAL_BG_RED
+        theme.palette[Hue::BarConflictPartial]
     } else {
-        BAR_PARTIAL_BG_CYAN
+        theme.palette[Hue::BarPartial]
     };
     bar.chars()
         .map(|c| {
             let s = c.to_string();
             if truecolor {
                 let fg_base = if is_conflicted {
-                    FILE_BAR_CONFLICT_RGB
+                    Hue::BarConflict
                 } else {
-                    FILE_BAR_RGB
+                    Hue::Bar
                 };
-                let (fr, fg, fb) = fade_rgb(fg_base, factor);
+                let (fr, fg, fb) = theme.fade.rgb(theme.palette[fg_base], factor);
                 if is_partial_block(c) {
-                    let (pr, pg, pb) = fade_rgb((bg_br, bg_bg, bg_bb), factor);
+                    let (pr, pg, pb) = theme.fade.rgb((bg_br, bg_bg, bg_bb), factor);
                     s.truecolor(fr, fg, fb).on_truecolor(pr, pg, pb)
                 } else {
                     s.truecolor(fr, fg, fb)
@@ -813,8 +1062,14 @@ fn colorize_bar_styled(
         .collect()
 }
 
-fn colorize_bar(bar: &str, entry: &RenderEntry, factor: f32, truecolor: bool) -> String {
-    let cells = colorize_bar_styled(bar, entry, factor, truecolor);
+fn colorize_bar(
+    bar: &str,
+    entry: &RenderEntry,
+    factor: f32,
+    truecolor: bool,
+    theme: &Theme,
+) -> String {
+    let cells = colorize_bar_styled(bar, entry, factor, truecolor, theme);
     let mut out = String::with_capacity(bar.len() * 2);
     for c in cells {
         out.push_str(&c.to_string());
@@ -851,9 +1106,15 @@ fn colorize_age_ansi(text: &str, age: Option<Duration>) -> ColoredString {
 /// intentionally dropped because the gradient itself communicates
 /// freshness (no need to double-encode it with text decorations).
 /// Without truecolor, falls through to the legacy bucket styling.
-fn colorize_age(text: &str, age: Option<Duration>, factor: f32, truecolor: bool) -> ColoredString {
+fn colorize_age(
+    text: &str,
+    age: Option<Duration>,
+    factor: f32,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     if truecolor {
-        let (r, g, b) = fade_rgb(FILE_AGE_RGB, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[Hue::Age], factor);
         return text.truecolor(r, g, b);
     }
     colorize_age_ansi(text, age)
@@ -862,10 +1123,10 @@ fn colorize_age(text: &str, age: Option<Duration>, factor: f32, truecolor: bool)
 /// Color the `+adds` field for a file row.
 ///
 /// With `truecolor`, applies the age-driven fade starting from
-/// [`FILE_ADDS_RGB`]. Without, falls back to ANSI green.
-fn colorize_adds(text: &str, factor: f32, truecolor: bool) -> ColoredString {
+/// the palette's [`Hue::Adds`]. Without, falls back to ANSI green.
+fn colorize_adds(text: &str, factor: f32, truecolor: bool, theme: &Theme) -> ColoredString {
     if truecolor {
-        let (r, g, b) = fade_rgb(FILE_ADDS_RGB, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[Hue::Adds], factor);
         return text.truecolor(r, g, b);
     }
     text.green()
@@ -874,97 +1135,91 @@ fn colorize_adds(text: &str, factor: f32, truecolor: bool) -> ColoredString {
 /// Color the `-dels` field for a file row.
 ///
 /// With `truecolor`, applies the age-driven fade starting from
-/// [`FILE_DELS_RGB`]. Without, falls back to ANSI red.
-fn colorize_dels(text: &str, factor: f32, truecolor: bool) -> ColoredString {
+/// the palette's [`Hue::Dels`]. Without, falls back to ANSI red.
+fn colorize_dels(text: &str, factor: f32, truecolor: bool, theme: &Theme) -> ColoredString {
     if truecolor {
-        let (r, g, b) = fade_rgb(FILE_DELS_RGB, factor);
+        let (r, g, b) = theme.fade.rgb(theme.palette[Hue::Dels], factor);
         return text.truecolor(r, g, b);
     }
     text.red()
 }
 
-/// Base RGB for commit-log hashes when truecolor fading is on. Picked to
-/// match the perceptual feel of the legacy `yellow()` ANSI hash without
-/// depending on a specific terminal palette.
-const LOG_HASH_BASE_RGB: (u8, u8, u8) = (255, 215, 0);
-/// Base RGB for commit-log subjects — a near-white that fades visibly.
-const LOG_SUBJECT_BASE_RGB: (u8, u8, u8) = (220, 220, 220);
-/// Base RGB for the commit-log age column.
-const LOG_AGE_BASE_RGB: (u8, u8, u8) = (190, 190, 190);
-
-// --- File-row truecolor base palette ---------------------------------------
-//
-// Per-status base RGB values for the file list under truecolor mode. Each
-// base is tuned so factor=0 reads as the same hue family as the legacy
-// ANSI color, and factor=1 (× FADE_FLOOR) still keeps the hue visible.
-
-const FILE_PATH_UNSTAGED_RGB: (u8, u8, u8) = (220, 200, 100);
-const FILE_PATH_STAGED_RGB: (u8, u8, u8) = (200, 200, 200);
-const FILE_PATH_UNTRACKED_RGB: (u8, u8, u8) = (120, 200, 200);
-const FILE_PATH_CONFLICT_RGB: (u8, u8, u8) = (255, 90, 90);
-
-const FILE_ICON_STAGED_RGB: (u8, u8, u8) = (90, 220, 110);
-const FILE_ICON_UNSTAGED_RGB: (u8, u8, u8) = (220, 200, 100);
-const FILE_ICON_UNTRACKED_RGB: (u8, u8, u8) = (120, 200, 200);
-const FILE_ICON_CONFLICT_RGB: (u8, u8, u8) = (255, 80, 80);
-
-const FILE_LETTER_ADDED_RGB: (u8, u8, u8) = (90, 220, 110);
-const FILE_LETTER_DELETED_RGB: (u8, u8, u8) = (255, 80, 80);
-const FILE_LETTER_RENAMED_RGB: (u8, u8, u8) = (220, 120, 220);
-const FILE_LETTER_DEFAULT_RGB: (u8, u8, u8) = (230, 230, 230);
-const FILE_LETTER_CONFLICT_RGB: (u8, u8, u8) = (255, 80, 80);
-const FILE_LETTER_UNTRACKED_RGB: (u8, u8, u8) = (120, 200, 200);
-const FILE_AGE_RGB: (u8, u8, u8) = (190, 190, 190);
-const FILE_ADDS_RGB: (u8, u8, u8) = (90, 220, 110);
-const FILE_DELS_RGB: (u8, u8, u8) = (255, 90, 90);
-const FILE_BAR_RGB: (u8, u8, u8) = (60, 200, 200);
-const FILE_BAR_CONFLICT_RGB: (u8, u8, u8) = (255, 80, 80);
-const FILE_BIN_RGB: (u8, u8, u8) = (160, 160, 160);
-
 /// Fade factor for any row carrying an optional age.
 ///
-/// `Some(age)` rides the [`age_fade_factor`] ramp, so the file list and the
+/// `Some(age)` rides the curve's [`factor`](FadeCurve::factor) ramp, so the file list and the
 /// log section darken in lockstep. `None` returns `1.0` — the dark floor —
 /// for every row whose age gsw could not determine: files it will not stat
 /// (deleted entries, skipped untracked dirs) and commits whose timestamp does
 /// not resolve. One rule, so an unknown age looks the same in both sections.
-fn fade_factor(age: Option<Duration>) -> f32 {
-    age.map_or(1.0, age_fade_factor)
+fn fade_factor(age: Option<Duration>, fade: &FadeCurve) -> f32 {
+    age.map_or(1.0, |age| fade.factor(age))
 }
 
-/// Apply the age-driven truecolor fade to `s`, starting from `base`.
+/// Apply the age-driven truecolor fade to `s`, starting from the palette's
+/// `hue`.
 ///
 /// Shared by every truecolor commit-log colorizer so the fade math lives
 /// in exactly one place — keeps the per-column functions to a single
 /// readable `if truecolor { fade } else { fallback }` shape.
-fn fade_truecolor(s: &str, age: Option<Duration>, base: (u8, u8, u8)) -> ColoredString {
-    let (r, g, b) = fade_rgb(base, fade_factor(age));
+fn fade_truecolor(s: &str, age: Option<Duration>, hue: Hue, theme: &Theme) -> ColoredString {
+    let (r, g, b) = theme
+        .fade
+        .rgb(theme.palette[hue], fade_factor(age, &theme.fade));
     s.truecolor(r, g, b)
 }
 
 /// Color the short hash for a commit-log row.
 ///
-/// With `truecolor`, the hash starts at [`LOG_HASH_BASE_RGB`] and fades
+/// With `truecolor`, the hash starts at the palette's [`Hue::LogHash`] and fades
 /// toward the dark floor as `age` grows. Without, falls back to the
 /// legacy ANSI yellow so eight-color terminals still get a coloured hash.
 /// An unknown age (`None`) renders at the floor, like the oldest commit.
-fn colorize_log_hash(hash: &str, age: Option<Duration>, truecolor: bool) -> ColoredString {
+fn colorize_log_hash(
+    hash: &str,
+    age: Option<Duration>,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     if truecolor {
-        fade_truecolor(hash, age, LOG_HASH_BASE_RGB)
+        fade_truecolor(hash, age, Hue::LogHash, theme)
     } else {
         hash.yellow()
     }
 }
 
+/// Color the `HEAD@{n}`/`stash@{n}` selector leading a reflog or stash row.
+///
+/// With `truecolor`, it fades from the palette's `hue` toward the dark floor exactly as a
+/// log row's hash does. Without, it takes the flat `fallback` color.
+fn colorize_selector(
+    selector: &str,
+    age: Option<Duration>,
+    truecolor: bool,
+    hue: Hue,
+    fallback: Color,
+    theme: &Theme,
+) -> ColoredString {
+    if truecolor {
+        fade_truecolor(selector, age, hue, theme)
+    } else {
+        selector.color(fallback)
+    }
+}
+
 /// Color the subject line for a commit-log row.
 ///
 /// With `truecolor`, the subject fades from a near-white base toward the
 /// dark floor. Without, falls back to the same Aging/Stale dim styling as
 /// the file-row age column, so the row still gets quieter as it ages. An
 /// unknown age (`None`) takes the quietest styling, like the oldest commit.
-fn colorize_log_subject(subject: &str, age: Option<Duration>, truecolor: bool) -> ColoredString {
+fn colorize_log_subject(
+    subject: &str,
+    age: Option<Duration>,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     if truecolor {
-        fade_truecolor(subject, age, LOG_SUBJECT_BASE_RGB)
+        fade_truecolor(subject, age, Hue::LogSubject, theme)
     } else {
         match age.map(age_dim_level) {
             Some(AgeDim::Fresh | AgeDim::Recent) => subject.normal(),
@@ -974,9 +1229,14 @@ fn colorize_log_subject(subject: &str, age: Option<Duration>, truecolor: bool) -
 }
 
 /// Color the right-aligned age column for a commit-log row.
-fn colorize_log_age(text: &str, age: Option<Duration>, truecolor: bool) -> ColoredString {
+fn colorize_log_age(
+    text: &str,
+    age: Option<Duration>,
+    truecolor: bool,
+    theme: &Theme,
+) -> ColoredString {
     if truecolor {
-        fade_truecolor(text, age, LOG_AGE_BASE_RGB)
+        fade_truecolor(text, age, Hue::LogAge, theme)
     } else {
         colorize_age_ansi(text, age)
     }
@@ -1021,7 +1281,7 @@ pub(crate) fn truncate_right(s: &str, max_width: usize) -> String {
 
 /// Truncate `s` from the left to fit within `max_width` display columns,
 /// prefixing with `…` when truncation happens. UTF-8 safe.
-fn truncate_left(s: &str, max_width: usize) -> String {
+pub(crate) fn truncate_left(s: &str, max_width: usize) -> String {
     if UnicodeWidthStr::width(s) <= max_width {
         return s.to_string();
     }
@@ -1125,89 +1385,171 @@ fn center(text: &str, width: usize) -> String {
     result
 }
 
-/// Minimum rows the recent-commit section gets when it has content and
-/// the file list is also non-empty. Without this floor, a branch with
-/// hundreds of changed files would proportionally squeeze the log down
-/// to one or two rows, hiding the very context the log section exists
-/// to provide. The floor is capped by `log_demand` so a 3-commit branch
+/// Minimum rows the sections above the file list get together when they have
+/// content and the file list is also non-empty. Without this floor, a branch
+/// with hundreds of changed files would proportionally squeeze the log down
+/// to one or two rows, hiding the very context the log section exists to
+/// provide. The floor is capped by the sections' demand so a 3-commit branch
 /// doesn't render with blank padding.
 const LOG_FLOOR_ROWS: usize = 5;
 
-/// Plan how many file rows and how many log rows the frame should render,
-/// given the actual demand from each section and the total terminal rows
-/// available for content (i.e. terminal height minus chrome the caller has
-/// already deducted: header, post-header separator, inter-section
-/// separator, and a reserved row for a possible `+N more files` footer).
+/// Rows per frame section — what each section wants, or what it was granted.
+///
+/// The file list renders at the bottom; the log, the reflog, and the stash
+/// list stack above it in that order and share one budget, the *context*,
+/// because they answer the same question (how did the branch get here) and a
+/// frame squeezed for files squeezes them together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SectionRows {
+    pub files: usize,
+    pub log: usize,
+    pub reflog: usize,
+    pub stashes: usize,
+}
+
+impl SectionRows {
+    /// Rows the sections above the file list want between them.
+    pub(crate) fn context(&self) -> usize {
+        self.log + self.reflog + self.stashes
+    }
+
+    /// How many of the sections above the file list have anything to show.
+    fn context_sections(&self) -> usize {
+        [self.log, self.reflog, self.stashes]
+            .into_iter()
+            .filter(|&rows| rows > 0)
+            .count()
+    }
+}
+
+/// Plan how many rows each section of the frame should render, given the
+/// actual demand from each and the total terminal rows available for content
+/// (i.e. terminal height minus chrome the caller has already deducted: header,
+/// post-header separator, the separators between sections, and a reserved row
+/// for a possible `+N more files` footer).
 ///
-/// When everything fits, both sections are rendered in full. When the
-/// combined demand exceeds the available rows, the **file list wins**: it is
-/// the primary content (it shows what you're actively changing) and renders
-/// at the bottom, so it must stay fully on-screen rather than being clipped.
-/// Files get their full demand (leaving at least one row for a non-empty
-/// log), and the log takes whatever remains.
+/// When everything fits, every section is rendered in full. When the combined
+/// demand exceeds the available rows, the **file list wins**: it is the
+/// primary content (it shows what you're actively changing) and renders at
+/// the bottom, so it must stay fully on-screen rather than being clipped.
+/// Files get their full demand (leaving at least one row for each non-empty
+/// section above them), and the context takes whatever remains, divided by
+/// [`share_context`].
 ///
 /// The one exception is a genuinely long file list that can't all fit: once
 /// the file section is itself being truncated (a `+N more files` footer would
-/// show), the log is floored at `min(LOG_FLOOR_ROWS, log_demand)` rows so
-/// recent-commit context doesn't collapse to a sliver. A short file list
+/// show), the context is floored at `min(LOG_FLOOR_ROWS, context demand)` rows
+/// so recent-commit context doesn't collapse to a sliver. A short file list
 /// never gives up rows to that floor.
 ///
-/// Returns `(file_cap, log_cap)`. Each cap never exceeds the corresponding
-/// demand.
-pub fn plan_section_caps(
+/// Each cap never exceeds the corresponding demand.
+pub fn plan_section_caps(demand: SectionRows, available_rows: usize) -> SectionRows {
+    let (files, context) = split_files_and_context(
+        demand.files,
+        demand.context(),
+        demand.context_sections(),
+        available_rows,
+    );
+    SectionRows {
+        files,
+        ..share_context(demand, context)
+    }
+}
+
+/// The file-list-first split behind [`plan_section_caps`], with the sections
+/// above the files taken as one: `(file rows, context rows)`. `sections` is
+/// how many of them have content, each of which is owed a row.
+fn split_files_and_context(
     file_demand: usize,
-    log_demand: usize,
+    context_demand: usize,
+    sections: usize,
     available_rows: usize,
 ) -> (usize, usize) {
     if available_rows == 0 {
         return (0, 0);
     }
-    if file_demand + log_demand <= available_rows {
-        return (file_demand, log_demand);
+    if file_demand + context_demand <= available_rows {
+        return (file_demand, context_demand);
     }
     if file_demand == 0 {
-        return (0, available_rows.min(log_demand));
+        return (0, available_rows.min(context_demand));
     }
-    if log_demand == 0 {
+    if context_demand == 0 {
         return (available_rows.min(file_demand), 0);
     }
 
-    // Both sections want rows and the total overflows. Give the file list its
-    // full demand first, leaving at least one row for the log, then hand the
-    // log whatever remains. `.max(1)` keeps the file section visible even in a
-    // pathologically short budget.
-    let mut file_share = file_demand.min(available_rows.saturating_sub(1)).max(1);
-    let mut log_share = available_rows.saturating_sub(file_share);
+    // Both sides want rows and the total overflows. Give the file list its
+    // full demand first, leaving at least one row for each context section,
+    // then hand the context whatever remains. `.max(1)` keeps the file section
+    // visible even in a pathologically short budget.
+    let mut file_share = file_demand
+        .min(available_rows.saturating_sub(sections))
+        .max(1);
+    let mut context_share = available_rows.saturating_sub(file_share);
 
     // Only when the file list is itself truncated (a long list that can't all
-    // fit) do we floor the log, clawing rows back from the file section so
+    // fit) do we floor the context, clawing rows back from the file section so
     // recent-commit context survives a file-dominated frame.
     if file_share < file_demand {
-        let log_floor = LOG_FLOOR_ROWS
-            .min(log_demand)
+        let floor = LOG_FLOOR_ROWS
+            .min(context_demand)
             .min(available_rows.saturating_sub(1));
-        if log_share < log_floor {
-            log_share = log_floor;
-            file_share = available_rows.saturating_sub(log_share);
+        if context_share < floor {
+            context_share = floor;
+            file_share = available_rows.saturating_sub(context_share);
         }
     }
-    (file_share, log_share)
+    (file_share, context_share)
+}
+
+/// Divide `rows` between the log, the reflog, and the stash list, each capped
+/// at its `demand`. `files` in the result is always `0`; the caller owns it.
+///
+/// Every section with content gets a row before any gets a second, so a
+/// section the user asked for never silently vanishes. After that the reflog
+/// and the stash list fill up first and the log takes the rest: those two are
+/// short and sized by their own `--*-lines`, while the log defaults to twenty
+/// rows and its newest — the one that carries the last-commit age — survives
+/// any squeeze through the first pass.
+pub(crate) fn share_context(demand: SectionRows, rows: usize) -> SectionRows {
+    let mut left = rows;
+    let mut grant = |want: usize, granted: &mut usize| {
+        let take = want.saturating_sub(*granted).min(left);
+        *granted += take;
+        left -= take;
+    };
+    let mut caps = SectionRows::default();
+    grant(demand.log.min(1), &mut caps.log);
+    grant(demand.reflog.min(1), &mut caps.reflog);
+    grant(demand.stashes.min(1), &mut caps.stashes);
+    grant(demand.reflog, &mut caps.reflog);
+    grant(demand.stashes, &mut caps.stashes);
+    grant(demand.log, &mut caps.log);
+    caps
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::Palette;
 
     use testcolor::{max_red_channel, strip_ansi, TRUECOLOR_FG};
 
+    /// The built-in theme, for the colorizers' `theme` argument.
+    const THEME: &Theme = &Theme::DEFAULT;
+
     fn opts() -> RenderOptions {
         RenderOptions {
             terminal_width: 80,
             bar_width: 6,
             max_files: None,
             log_lines: 0,
+            reflog_lines: 0,
+            stash_lines: 0,
             truecolor: false,
             refresh: None,
+            selected: None,
+            theme: Theme::DEFAULT,
         }
     }
 
@@ -1222,6 +1564,9 @@ mod tests {
             upstream: None,
             operation: None,
             push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         }
     }
 
@@ -1895,8 +2240,12 @@ mod tests {
                 bar_width: 6,
                 max_files: None,
                 log_lines: 0,
+                reflog_lines: 0,
+                stash_lines: 0,
                 truecolor: false,
                 refresh: None,
+                selected: None,
+                theme: Theme::DEFAULT,
             },
         ));
         let row = out.lines().nth(2).unwrap_or("");
@@ -1918,8 +2267,12 @@ mod tests {
                 bar_width: 6,
                 max_files: None,
                 log_lines: 0,
+                reflog_lines: 0,
+                stash_lines: 0,
                 truecolor: false,
                 refresh: None,
+                selected: None,
+                theme: Theme::DEFAULT,
             },
         );
         let stripped = strip_ansi(&out);
@@ -2007,8 +2360,12 @@ mod tests {
                 bar_width: 6,
                 max_files: Some(3),
                 log_lines: 0,
+                reflog_lines: 0,
+                stash_lines: 0,
                 truecolor: false,
                 refresh: None,
+                selected: None,
+                theme: Theme::DEFAULT,
             },
         ));
         assert!(out.contains("f0.rs"));
@@ -2036,8 +2393,12 @@ mod tests {
                 bar_width: 6,
                 max_files: Some(0),
                 log_lines: 0,
+                reflog_lines: 0,
+                stash_lines: 0,
                 truecolor: false,
                 refresh: None,
+                selected: None,
+                theme: Theme::DEFAULT,
             },
         ));
         for i in 0..5 {
@@ -2052,6 +2413,88 @@ mod tests {
         );
     }
 
+    fn lib_group(files: Vec<RenderEntry>) -> SubmoduleGroup {
+        SubmoduleGroup {
+            path: "libs/lib".into(),
+            branch: "main".into(),
+            ahead: 2,
+            behind: 0,
+            files,
+        }
+    }
+
+    #[test]
+    fn a_submodule_group_is_a_title_then_its_indented_files() {
+        let mut snap = snap_with(vec![entry("a.rs", FileStatus::Modified, true, 1, 0)]);
+        snap.submodules = vec![lib_group(vec![entry(
+            "l.rs",
+            FileStatus::Modified,
+            false,
+            1,
+            0,
+        )])];
+        let out = strip_ansi(&render(&snap, &opts()));
+        let lines: Vec<&str> = out.lines().collect();
+        let title = lines
+            .iter()
+            .position(|line| line.starts_with("▸ libs/lib (main)"))
+            .unwrap_or_else(|| panic!("the group needs a title row: {out}"));
+        assert!(
+            lines[title].ends_with("↑2 ↓0 vs recorded"),
+            "the title carries the drift from the gitlink: {:?}",
+            lines[title],
+        );
+        assert!(
+            lines[..title].iter().any(|line| line.contains("a.rs")),
+            "the superproject's rows come first: {out}",
+        );
+        assert!(
+            lines[title + 1].starts_with("  ") && lines[title + 1].contains("l.rs"),
+            "the submodule's files sit indented under its title: {out}",
+        );
+    }
+
+    #[test]
+    fn submodule_groups_share_the_file_budget() {
+        let files = (0..2)
+            .map(|i| entry(&format!("f{i}.rs"), FileStatus::Modified, true, 1, 0))
+            .collect();
+        let mut snap = snap_with(files);
+        snap.submodules = vec![lib_group(
+            (0..3)
+                .map(|i| entry(&format!("l{i}.rs"), FileStatus::Modified, false, 1, 0))
+                .collect(),
+        )];
+        let out = strip_ansi(&render(
+            &snap,
+            &RenderOptions {
+                max_files: Some(4),
+                ..opts()
+            },
+        ));
+        assert!(out.contains("▸ libs/lib"), "{out}");
+        assert!(out.contains("l0.rs"), "{out}");
+        assert!(
+            !out.contains("l1.rs"),
+            "the title row spends the budget too: {out}",
+        );
+        assert!(
+            out.contains("+2 more files"),
+            "hidden submodule files are counted in the footer: {out}",
+        );
+        assert_eq!(file_section_rows(&snap), 6);
+    }
+
+    #[test]
+    fn a_submodule_title_drops_its_drift_rather_than_cutting_it() {
+        let title = strip_ansi(&render_submodule_title(&lib_group(Vec::new()), 20));
+        assert!(
+            !title.contains('↑'),
+            "a clipped count is a wrong count: {title:?}"
+        );
+        assert!(UnicodeWidthStr::width(title.as_str()) <= 20, "{title:?}");
+    }
+
     #[test]
     fn bar_scales_to_max_change_in_snapshot() {
         let big = entry("big.rs", FileStatus::Modified, true, 100, 0);
@@ -2091,9 +2534,9 @@ mod tests {
         // codes in a non-TTY test run.
         let (with_partial, all_full, all_empty) = testcolor::with_forced_ansi(|| {
             let e = entry("foo.rs", FileStatus::Modified, true, 9, 1);
-            let with_partial = colorize_bar("█████▍", &e, 0.0, false);
-            let all_full = colorize_bar("██████", &e, 0.0, false);
-            let all_empty = colorize_bar("░░░░░░", &e, 0.0, false);
+            let with_partial = colorize_bar("█████▍", &e, 0.0, false, THEME);
+            let all_full = colorize_bar("██████", &e, 0.0, false, THEME);
+            let all_empty = colorize_bar("░░░░░░", &e, 0.0, false, THEME);
             (with_partial, all_full, all_empty)
         });
 
@@ -2129,6 +2572,20 @@ mod tests {
         );
     }
 
+    /// [`plan_section_caps`] for a frame with only the file list and the log,
+    /// as `(file rows, log rows)`.
+    fn files_and_log(files: usize, log: usize, available_rows: usize) -> (usize, usize) {
+        let caps = plan_section_caps(
+            SectionRows {
+                files,
+                log,
+                ..SectionRows::default()
+            },
+            available_rows,
+        );
+        (caps.files, caps.log)
+    }
+
     #[test]
     fn plan_section_caps_shows_all_files_before_flooring_log() {
         // The file list is the primary content and now renders at the bottom,
@@ -2136,7 +2593,7 @@ mod tests {
         // single row by the log's floor. With 2 files competing against a
         // 12-commit log for 6 rows, both files show and the log takes the
         // remaining 4 rows — the user is never "limited to one file".
-        assert_eq!(plan_section_caps(2, 12, 6), (2, 4));
+        assert_eq!(files_and_log(2, 12, 6), (2, 4));
     }
 
     #[test]
@@ -2145,7 +2602,7 @@ mod tests {
         // files must show. The log yields rows to the file list because the
         // files fit; the floor only protects the log when files genuinely
         // can't all fit (see the "files dominate" case below).
-        let (f, _l) = plan_section_caps(3, 50, 8);
+        let (f, _l) = files_and_log(3, 50, 8);
         assert_eq!(
             f, 3,
             "all 3 files should render; the log floor must not steal rows from a short file list",
@@ -2155,7 +2612,7 @@ mod tests {
     #[test]
     fn plan_section_caps_returns_full_demand_when_room_is_plenty() {
         // No contention: 5 files + 20 log rows easily fit in 100 rows.
-        assert_eq!(plan_section_caps(5, 20, 100), (5, 20));
+        assert_eq!(files_and_log(5, 20, 100), (5, 20));
     }
 
     #[test]
@@ -2164,7 +2621,7 @@ mod tests {
         // primary content and fit entirely, so all 5 render; the log takes
         // the remaining 5 rows. (The file list is never truncated here, so
         // the log floor doesn't claw rows back from it.)
-        assert_eq!(plan_section_caps(5, 20, 10), (5, 5));
+        assert_eq!(files_and_log(5, 20, 10), (5, 5));
     }
 
     #[test]
@@ -2172,7 +2629,7 @@ mod tests {
         // file=1, log=100 would proportionally give file 0 rows (rounded
         // down). Each non-empty section must keep at least one row so we
         // never silently hide a section that has content.
-        let (f, l) = plan_section_caps(1, 100, 10);
+        let (f, l) = files_and_log(1, 100, 10);
         assert!(
             f >= 1,
             "non-empty file section must get at least 1 row, got {f}"
@@ -2184,15 +2641,15 @@ mod tests {
     fn plan_section_caps_grants_all_rows_to_lone_section() {
         // When only one section has content, it should claim every
         // available row up to its demand. The other section gets zero.
-        assert_eq!(plan_section_caps(0, 20, 10), (0, 10));
-        assert_eq!(plan_section_caps(20, 0, 10), (10, 0));
+        assert_eq!(files_and_log(0, 20, 10), (0, 10));
+        assert_eq!(files_and_log(20, 0, 10), (10, 0));
     }
 
     #[test]
     fn plan_section_caps_returns_zero_when_no_rows_available() {
         // Pathologically short terminal: nothing fits, so nothing is
         // promised. The caller will at least render header chrome.
-        assert_eq!(plan_section_caps(5, 5, 0), (0, 0));
+        assert_eq!(files_and_log(5, 5, 0), (0, 0));
     }
 
     #[test]
@@ -2201,7 +2658,7 @@ mod tests {
         // demand, even when the proportional formula would round up past
         // it. file=2, log=20, available=14 is the kind of edge case where
         // a naive proportional formula could produce a file cap > 2.
-        let (f, l) = plan_section_caps(2, 20, 14);
+        let (f, l) = files_and_log(2, 20, 14);
         assert!(f <= 2, "file cap must not exceed demand: got {f}");
         assert!(l <= 20, "log cap must not exceed demand: got {l}");
         assert!(f + l <= 14, "total must fit in available rows: {f}+{l}");
@@ -2214,7 +2671,7 @@ mod tests {
         // terminal. A naive proportional split would squeeze the log
         // section down to ~3 rows; the floor lifts that to 5 so the
         // recent-commit context stays visible.
-        let (f, l) = plan_section_caps(129, 20, 26);
+        let (f, l) = files_and_log(129, 20, 26);
         assert_eq!(l, 5, "log section should be floored at 5 rows, got {l}");
         assert_eq!(
             f, 21,
@@ -2227,7 +2684,7 @@ mod tests {
         // The floor is min(5, log_demand). With only 3 commits ahead,
         // the log section should get exactly those 3 rows rather than 5
         // rows with two empty lines at the bottom.
-        let (f, l) = plan_section_caps(100, 3, 20);
+        let (f, l) = files_and_log(100, 3, 20);
         assert_eq!(
             l, 3,
             "log cap should equal demand when demand < floor, got {l}"
@@ -2235,6 +2692,62 @@ mod tests {
         assert_eq!(f, 17, "file section should get the remaining rows, got {f}");
     }
 
+    #[test]
+    fn plan_section_caps_gives_every_context_section_a_row_before_a_second() {
+        // 30 files on a 10-row budget: the files are truncated, so the context
+        // gets its five-row floor, and a five-row reflog and three stashes
+        // must both still show next to the newest commit.
+        let caps = plan_section_caps(
+            SectionRows {
+                files: 30,
+                log: 20,
+                reflog: 5,
+                stashes: 3,
+            },
+            10,
+        );
+        assert_eq!(caps.files, 5);
+        assert_eq!(caps.log + caps.reflog + caps.stashes, 5);
+        assert!(
+            caps.log >= 1 && caps.reflog >= 1 && caps.stashes >= 1,
+            "{caps:?}"
+        );
+    }
+
+    #[test]
+    fn plan_section_caps_fills_the_reflog_and_stashes_before_the_log() {
+        // The short sections were asked for by size; the log yields to them.
+        let caps = plan_section_caps(
+            SectionRows {
+                files: 2,
+                log: 20,
+                reflog: 4,
+                stashes: 2,
+            },
+            12,
+        );
+        assert_eq!(
+            caps,
+            SectionRows {
+                files: 2,
+                log: 4,
+                reflog: 4,
+                stashes: 2,
+            },
+        );
+    }
+
+    #[test]
+    fn plan_section_caps_with_room_grants_every_section_its_demand() {
+        let demand = SectionRows {
+            files: 3,
+            log: 5,
+            reflog: 4,
+            stashes: 2,
+        };
+        assert_eq!(plan_section_caps(demand, 40), demand);
+    }
+
     fn log_entry(hash: &str, subject: &str, age_secs: u64) -> LogEntry {
         LogEntry {
             hash: hash.into(),
@@ -2488,8 +3001,20 @@ mod tests {
         // ANSI bytes, so it does not force the process-global `colored`
         // override and does not wait for the lock in `testcolor`.
         use colored::Styles;
-        let aging = colorize_age("12h0m", Some(Duration::from_secs(2 * 3600)), 0.0, false);
-        let stale = colorize_age("12h0m", Some(Duration::from_secs(2 * 86400)), 0.0, false);
+        let aging = colorize_age(
+            "12h0m",
+            Some(Duration::from_secs(2 * 3600)),
+            0.0,
+            false,
+            THEME,
+        );
+        let stale = colorize_age(
+            "12h0m",
+            Some(Duration::from_secs(2 * 86400)),
+            0.0,
+            false,
+            THEME,
+        );
         assert!(
             stale.style.contains(Styles::Italic),
             "Stale should be italicized",
@@ -2611,6 +3136,163 @@ mod tests {
         );
     }
 
+    fn reflog_entry(selector: &str, message: &str, age_secs: u64) -> ReflogEntry {
+        ReflogEntry {
+            selector: selector.into(),
+            message: message.into(),
+            age: Some(Duration::from_secs(age_secs)),
+        }
+    }
+
+    /// Every section populated: two commits, two moves of HEAD, a stash, and
+    /// one file, with room for all of them.
+    fn snap_with_every_section() -> (Snapshot, RenderOptions) {
+        let mut snap = snap_with(vec![entry("src/foo.rs", FileStatus::Modified, true, 1, 0)]);
+        snap.log = vec![
+            log_entry("abc1234", "newer commit", 30),
+            log_entry("def5678", "older commit", 300),
+        ];
+        snap.reflog = vec![
+            reflog_entry("HEAD@{0}", "rebase (pick): newer commit", 10),
+            reflog_entry("HEAD@{1}", "rebase (start): checkout main", 20),
+        ];
+        snap.stashes = vec![reflog_entry(
+            "stash@{0}",
+            "WIP on gsv: abc1234 half done",
+            600,
+        )];
+        let mut o = opts();
+        o.log_lines = 5;
+        o.reflog_lines = 5;
+        o.stash_lines = 5;
+        (snap, o)
+    }
+
+    fn is_rule(line: &str) -> bool {
+        line.chars().all(|c| c == '─' || c.is_whitespace())
+    }
+
+    #[test]
+    fn reflog_and_stashes_render_between_the_log_and_the_files_each_behind_a_rule() {
+        let (snap, o) = snap_with_every_section();
+        let out = strip_ansi(&render(&snap, &o));
+        let lines: Vec<&str> = out.lines().collect();
+        // header, rule, 2 log, rule, 2 reflog, rule, 1 stash, rule, 1 file.
+        assert_eq!(lines.len(), 11, "{out}");
+        assert!(lines[2].starts_with("abc1234"), "{out}");
+        assert!(is_rule(lines[4]), "{out}");
+        assert!(
+            lines[5].starts_with("HEAD@{0}  rebase (pick): newer commit"),
+            "{out}"
+        );
+        assert!(lines[6].starts_with("HEAD@{1}"), "{out}");
+        assert!(is_rule(lines[7]), "{out}");
+        assert!(lines[8].starts_with("stash@{0}  WIP on gsv"), "{out}");
+        assert!(is_rule(lines[9]), "{out}");
+        assert!(lines[10].contains("src/foo.rs"), "{out}");
+    }
+
+    #[test]
+    fn reflog_and_stash_rows_align_their_age_with_the_log() {
+        let (snap, o) = snap_with_every_section();
+        let out = strip_ansi(&render(&snap, &o));
+        for line in out
+            .lines()
+            .filter(|l| l.contains('@') || l.starts_with("abc"))
+        {
+            assert_eq!(
+                UnicodeWidthStr::width(line),
+                o.terminal_width,
+                "every history row spans the width, age flush right: {line:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn sections_with_no_lines_or_no_entries_draw_nothing_and_no_rule() {
+        let (mut snap, mut o) = snap_with_every_section();
+        o.reflog_lines = 0;
+        snap.stashes.clear();
+        let out = strip_ansi(&render(&snap, &o));
+        assert!(!out.contains("HEAD@{"), "{out}");
+        assert!(!out.contains("stash@{"), "{out}");
+        // header, rule, 2 log, rule, 1 file — exactly the frame without them.
+        assert_eq!(out.lines().count(), 6, "{out}");
+    }
+
+    #[test]
+    fn a_reflog_with_no_log_above_it_needs_no_rule_of_its_own() {
+        let (mut snap, mut o) = snap_with_every_section();
+        o.log_lines = 0;
+        let out = strip_ansi(&render(&snap, &o));
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[2].starts_with("HEAD@{0}"), "{out}");
+        snap.files.clear();
+        snap.stashes.clear();
+        let out = strip_ansi(&render(&snap, &o));
+        assert!(
+            !is_rule(out.lines().last().unwrap_or("")),
+            "no trailing rule:\n{out}"
+        );
+    }
+
+    #[test]
+    fn reflog_and_stash_caps_limit_their_rows() {
+        let (snap, mut o) = snap_with_every_section();
+        o.reflog_lines = 1;
+        let out = strip_ansi(&render(&snap, &o));
+        assert!(out.contains("HEAD@{0}"), "{out}");
+        assert!(!out.contains("HEAD@{1}"), "{out}");
+    }
+
+    #[test]
+    fn selectors_fall_back_to_flat_colors_without_truecolor() {
+        use colored::Color;
+        let fresh = Some(Duration::ZERO);
+        let reflog = colorize_selector(
+            "HEAD@{0}",
+            fresh,
+            false,
+            Hue::ReflogSelector,
+            Color::Cyan,
+            THEME,
+        );
+        let stash = colorize_selector(
+            "stash@{0}",
+            fresh,
+            false,
+            Hue::StashSelector,
+            Color::Magenta,
+            THEME,
+        );
+        assert_eq!(reflog.fgcolor, Some(Color::Cyan));
+        assert_eq!(stash.fgcolor, Some(Color::Magenta));
+    }
+
+    #[test]
+    fn a_stash_selector_darkens_with_age_under_truecolor() {
+        // The stash list fades on the same ramp as the log: a week-old stash
+        // is the one most likely forgotten, and it should look it.
+        use colored::Color;
+        let paint = |secs| {
+            colorize_selector(
+                "stash@{0}",
+                Some(Duration::from_secs(secs)),
+                true,
+                Hue::StashSelector,
+                Color::Magenta,
+                THEME,
+            )
+            .fgcolor
+        };
+        let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: or, .. })) =
+            (paint(0), paint(60 * 60 * 24 * 7))
+        else {
+            panic!("both should be TrueColor under truecolor=true");
+        };
+        assert!(or < fr, "a week-old stash must be darker: {fr} vs {or}");
+    }
+
     // --- truecolor commit-log fade ---------------------------------------
     //
     // These tests inspect the `ColoredString::fgcolor` field directly rather
@@ -2625,7 +3307,7 @@ mod tests {
         // 24-bit RGB value (not the legacy `Color::Yellow`), so the gradient
         // has somewhere to fade *from*.
         use colored::Color;
-        let cs = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), true);
+        let cs = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor when truecolor=true, got {other:?}"),
@@ -2638,7 +3320,7 @@ mod tests {
         // through — otherwise we silently drop hash colouring on terminals
         // that can't render 24-bit RGB.
         use colored::Color;
-        let cs = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), false);
+        let cs = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Yellow));
     }
 
@@ -2648,8 +3330,8 @@ mod tests {
         // out darker (lower channel values) than a fresh commit's hash on
         // every channel.
         use colored::Color;
-        let fresh = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), true);
-        let hour = colorize_log_hash("abc1234", Some(Duration::from_secs(60 * 60)), true);
+        let fresh = colorize_log_hash("abc1234", Some(Duration::from_secs(0)), true, THEME);
+        let hour = colorize_log_hash("abc1234", Some(Duration::from_secs(60 * 60)), true, THEME);
         let (
             Some(Color::TrueColor {
                 r: fr,
@@ -2678,14 +3360,19 @@ mod tests {
         // the FADE_FLOOR fraction of its base value.
         use crate::age::FADE_FLOOR;
         use colored::Color;
-        let cs = colorize_log_hash("abc1234", Some(Duration::from_secs(60 * 60 * 24 * 7)), true);
+        let cs = colorize_log_hash(
+            "abc1234",
+            Some(Duration::from_secs(60 * 60 * 24 * 7)),
+            true,
+            THEME,
+        );
         let Some(Color::TrueColor { r, g, b }) = cs.fgcolor else {
             panic!("expected TrueColor under truecolor=true");
         };
         // Derive the per-channel floor from the live base RGB so this
         // test asserts the invariant ("no channel drops below its
         // FADE_FLOOR fraction") rather than hardcoding numbers tied to
-        // today's choice of LOG_HASH_BASE_RGB. If the base later gains
+        // today's choice of log-hash base color. If the base later gains
         // a non-zero blue, the test still checks the right bound.
         #[allow(
             clippy::cast_possible_truncation,
@@ -2693,7 +3380,7 @@ mod tests {
             reason = "u8 × FADE_FLOOR ∈ [0, 1] stays in [0, 255]"
         )]
         let floor_of = |c: u8| (f32::from(c) * FADE_FLOOR).round() as u8;
-        let (base_r, base_g, base_b) = LOG_HASH_BASE_RGB;
+        let (base_r, base_g, base_b) = Palette::DEFAULT[Hue::LogHash];
         let min_r = floor_of(base_r);
         let min_g = floor_of(base_g);
         let min_b = floor_of(base_b);
@@ -2713,7 +3400,12 @@ mod tests {
         // Subjects need to fade too, otherwise the hash darkens while the
         // text next to it stays bright — visually inconsistent.
         use colored::Color;
-        let cs = colorize_log_subject("a commit subject", Some(Duration::from_secs(0)), true);
+        let cs = colorize_log_subject(
+            "a commit subject",
+            Some(Duration::from_secs(0)),
+            true,
+            THEME,
+        );
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor for subject when truecolor=true, got {other:?}"),
@@ -2723,8 +3415,8 @@ mod tests {
     #[test]
     fn log_subject_darkens_with_age_under_truecolor() {
         use colored::Color;
-        let fresh = colorize_log_subject("subj", Some(Duration::from_secs(0)), true);
-        let hour = colorize_log_subject("subj", Some(Duration::from_secs(60 * 60)), true);
+        let fresh = colorize_log_subject("subj", Some(Duration::from_secs(0)), true, THEME);
+        let hour = colorize_log_subject("subj", Some(Duration::from_secs(60 * 60)), true, THEME);
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: hr, .. })) =
             (fresh.fgcolor, hour.fgcolor)
         else {
@@ -2739,7 +3431,7 @@ mod tests {
     #[test]
     fn log_age_uses_truecolor_when_enabled() {
         use colored::Color;
-        let cs = colorize_log_age("5m23s", Some(Duration::from_secs(5 * 60 + 23)), true);
+        let cs = colorize_log_age("5m23s", Some(Duration::from_secs(5 * 60 + 23)), true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor for age when truecolor=true, got {other:?}"),
@@ -2749,8 +3441,8 @@ mod tests {
     #[test]
     fn log_age_darkens_with_age_under_truecolor() {
         use colored::Color;
-        let fresh = colorize_log_age("0s", Some(Duration::from_secs(0)), true);
-        let hour = colorize_log_age("1h0m", Some(Duration::from_secs(60 * 60)), true);
+        let fresh = colorize_log_age("0s", Some(Duration::from_secs(0)), true, THEME);
+        let hour = colorize_log_age("1h0m", Some(Duration::from_secs(60 * 60)), true, THEME);
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: hr, .. })) =
             (fresh.fgcolor, hour.fgcolor)
         else {
@@ -2765,7 +3457,13 @@ mod tests {
     #[test]
     fn file_age_uses_truecolor_when_enabled() {
         use colored::Color;
-        let cs = colorize_age("5m23s", Some(Duration::from_secs(5 * 60 + 23)), 0.0, true);
+        let cs = colorize_age(
+            "5m23s",
+            Some(Duration::from_secs(5 * 60 + 23)),
+            0.0,
+            true,
+            THEME,
+        );
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor for file age, got {other:?}"),
@@ -2776,7 +3474,7 @@ mod tests {
     fn file_age_falls_back_to_dim_buckets_without_truecolor() {
         // 8-color fallback must still bold a fresh row's age, matching today.
         use colored::Styles;
-        let fresh = colorize_age("30s", Some(Duration::from_secs(30)), 0.0, false);
+        let fresh = colorize_age("30s", Some(Duration::from_secs(30)), 0.0, false, THEME);
         assert!(
             fresh.style.contains(Styles::Bold),
             "fresh age should still be bolded in the 8-color path",
@@ -2786,8 +3484,14 @@ mod tests {
     #[test]
     fn file_age_darkens_with_factor_under_truecolor() {
         use colored::Color;
-        let fresh = colorize_age("30s", Some(Duration::from_secs(30)), 0.0, true);
-        let aged = colorize_age("3d0h", Some(Duration::from_secs(3 * 86400)), 1.0, true);
+        let fresh = colorize_age("30s", Some(Duration::from_secs(30)), 0.0, true, THEME);
+        let aged = colorize_age(
+            "3d0h",
+            Some(Duration::from_secs(3 * 86400)),
+            1.0,
+            true,
+            THEME,
+        );
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: ar, .. })) =
             (fresh.fgcolor, aged.fgcolor)
         else {
@@ -2809,6 +3513,7 @@ mod tests {
             "an old subject",
             Some(Duration::from_secs(60 * 60 * 24 * 7)),
             false,
+            THEME,
         );
         assert!(
             !stale.style.contains(Styles::Italic),
@@ -2821,7 +3526,7 @@ mod tests {
         // A file modified moments ago must render at full base brightness,
         // which means factor=0 — the no-fade end of the ramp.
         assert!(
-            (fade_factor(Some(Duration::from_secs(0))) - 0.0).abs() < 1e-6,
+            (fade_factor(Some(Duration::from_secs(0)), &FadeCurve::DEFAULT) - 0.0).abs() < 1e-6,
             "fresh file should produce factor=0",
         );
     }
@@ -2833,7 +3538,7 @@ mod tests {
         // (factor=1.0) so the row announces "this is an unusual state, not
         // something actively changing".
         assert!(
-            (fade_factor(None) - 1.0).abs() < 1e-6,
+            (fade_factor(None, &FadeCurve::DEFAULT) - 1.0).abs() < 1e-6,
             "None age should clamp to factor=1.0 (the floor)",
         );
     }
@@ -2843,8 +3548,8 @@ mod tests {
         // The file fade must share the *same* ramp as commit rows so the two
         // sections darken in lockstep under viddy. Spot-check the 1h midpoint.
         let one_hour = Duration::from_secs(60 * 60);
-        let file = fade_factor(Some(one_hour));
-        let commit = age_fade_factor(one_hour);
+        let file = fade_factor(Some(one_hour), &FadeCurve::DEFAULT);
+        let commit = FadeCurve::DEFAULT.factor(one_hour);
         assert!(
             (file - commit).abs() < 1e-6,
             "file fade must equal commit fade for matching Some(age): file={file}, commit={commit}",
@@ -2858,7 +3563,7 @@ mod tests {
         use colored::Color;
         let mut e = entry("src/foo.rs", FileStatus::Modified, false, 1, 0);
         e.age = Some(Duration::from_secs(0));
-        let cs = colorize_path("src/foo.rs", &e, 0.0, true);
+        let cs = colorize_path("src/foo.rs", &e, 0.0, true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor under truecolor=true, got {other:?}"),
@@ -2872,7 +3577,7 @@ mod tests {
         // 8-color path.
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, false, 1, 0);
-        let cs = colorize_path("src/foo.rs", &e, 0.0, false);
+        let cs = colorize_path("src/foo.rs", &e, 0.0, false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Yellow));
     }
 
@@ -2882,8 +3587,8 @@ mod tests {
         // every channel.
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, false, 1, 0);
-        let fresh = colorize_path("src/foo.rs", &e, 0.0, true);
-        let aged = colorize_path("src/foo.rs", &e, 1.0, true);
+        let fresh = colorize_path("src/foo.rs", &e, 0.0, true, THEME);
+        let aged = colorize_path("src/foo.rs", &e, 1.0, true, THEME);
         let (
             Some(Color::TrueColor {
                 r: fr,
@@ -2916,7 +3621,7 @@ mod tests {
         use crate::age::FADE_FLOOR;
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, false, 1, 0);
-        let cs = colorize_path("src/foo.rs", &e, 1.0, true);
+        let cs = colorize_path("src/foo.rs", &e, 1.0, true, THEME);
         let Some(Color::TrueColor { r, g, b }) = cs.fgcolor else {
             panic!("expected TrueColor under truecolor=true");
         };
@@ -2926,7 +3631,7 @@ mod tests {
             reason = "u8 × FADE_FLOOR ∈ [0, 1] stays in [0, 255]"
         )]
         let floor_of = |c: u8| (f32::from(c) * FADE_FLOOR).round() as u8;
-        let (br, bg, bb) = FILE_PATH_UNSTAGED_RGB;
+        let (br, bg, bb) = Palette::DEFAULT[Hue::PathUnstaged];
         assert!(
             r >= floor_of(br).saturating_sub(1)
                 && g >= floor_of(bg).saturating_sub(1)
@@ -2939,7 +3644,7 @@ mod tests {
     fn file_icon_uses_truecolor_when_enabled() {
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, true, 1, 0);
-        let cs = colorize_icon('●', &e, 0.0, true);
+        let cs = colorize_icon('●', &e, 0.0, true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor for icon under truecolor=true, got {other:?}"),
@@ -2951,7 +3656,7 @@ mod tests {
         // Staged-modified icon today is plain green. Regression guard.
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, true, 1, 0);
-        let cs = colorize_icon('●', &e, 0.0, false);
+        let cs = colorize_icon('●', &e, 0.0, false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Green));
     }
 
@@ -2959,8 +3664,8 @@ mod tests {
     fn file_icon_darkens_with_age_under_truecolor() {
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Modified, true, 1, 0);
-        let fresh = colorize_icon('●', &e, 0.0, true);
-        let aged = colorize_icon('●', &e, 1.0, true);
+        let fresh = colorize_icon('●', &e, 0.0, true, THEME);
+        let aged = colorize_icon('●', &e, 1.0, true, THEME);
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: ar, .. })) =
             (fresh.fgcolor, aged.fgcolor)
         else {
@@ -2973,7 +3678,7 @@ mod tests {
     fn file_letter_uses_truecolor_when_enabled() {
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Added, true, 1, 0);
-        let cs = colorize_letter('A', &e, 0.0, true);
+        let cs = colorize_letter('A', &e, 0.0, true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor under truecolor=true, got {other:?}"),
@@ -2984,7 +3689,7 @@ mod tests {
     fn file_letter_falls_back_to_ansi_without_truecolor() {
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Added, true, 1, 0);
-        let cs = colorize_letter('A', &e, 0.0, false);
+        let cs = colorize_letter('A', &e, 0.0, false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Green));
     }
 
@@ -2992,8 +3697,8 @@ mod tests {
     fn file_letter_darkens_with_age_under_truecolor() {
         use colored::Color;
         let e = entry("src/foo.rs", FileStatus::Deleted, true, 0, 1);
-        let fresh = colorize_letter('D', &e, 0.0, true);
-        let aged = colorize_letter('D', &e, 1.0, true);
+        let fresh = colorize_letter('D', &e, 0.0, true, THEME);
+        let aged = colorize_letter('D', &e, 1.0, true, THEME);
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: ar, .. })) =
             (fresh.fgcolor, aged.fgcolor)
         else {
@@ -3008,7 +3713,7 @@ mod tests {
     #[test]
     fn file_adds_uses_truecolor_when_enabled() {
         use colored::Color;
-        let cs = colorize_adds("  +12", 0.0, true);
+        let cs = colorize_adds("  +12", 0.0, true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor, got {other:?}"),
@@ -3018,7 +3723,7 @@ mod tests {
     #[test]
     fn file_dels_uses_truecolor_when_enabled() {
         use colored::Color;
-        let cs = colorize_dels(" -3", 0.0, true);
+        let cs = colorize_dels(" -3", 0.0, true, THEME);
         match cs.fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor, got {other:?}"),
@@ -3028,22 +3733,22 @@ mod tests {
     #[test]
     fn file_adds_falls_back_to_green_without_truecolor() {
         use colored::Color;
-        let cs = colorize_adds("  +12", 0.0, false);
+        let cs = colorize_adds("  +12", 0.0, false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Green));
     }
 
     #[test]
     fn file_dels_falls_back_to_red_without_truecolor() {
         use colored::Color;
-        let cs = colorize_dels(" -3", 0.0, false);
+        let cs = colorize_dels(" -3", 0.0, false, THEME);
         assert_eq!(cs.fgcolor, Some(Color::Red));
     }
 
     #[test]
     fn file_adds_darkens_with_factor_under_truecolor() {
         use colored::Color;
-        let fresh = colorize_adds("  +12", 0.0, true);
-        let aged = colorize_adds("  +12", 1.0, true);
+        let fresh = colorize_adds("  +12", 0.0, true, THEME);
+        let aged = colorize_adds("  +12", 1.0, true, THEME);
         let (
             Some(Color::TrueColor { r: fr, g: fg, .. }),
             Some(Color::TrueColor { r: ar, g: ag, .. }),
@@ -3058,8 +3763,8 @@ mod tests {
     fn file_bar_fill_fades_with_factor_under_truecolor() {
         use colored::Color;
         let e = entry("foo.rs", FileStatus::Modified, true, 6, 0);
-        let fresh = colorize_bar_styled("██████", &e, 0.0, true);
-        let aged = colorize_bar_styled("██████", &e, 1.0, true);
+        let fresh = colorize_bar_styled("██████", &e, 0.0, true, THEME);
+        let aged = colorize_bar_styled("██████", &e, 1.0, true, THEME);
         // We expect the first cell's fg to be TrueColor in both cases and
         // the aged channel to be strictly lower.
         let (
@@ -3087,10 +3792,10 @@ mod tests {
     fn file_bar_partial_bg_fades_with_factor_under_truecolor() {
         use colored::Color;
         // Use a partial-fill glyph (▍ = U+258D) so a background color is set.
-        // BAR_PARTIAL_BG_CYAN = (0, 48, 48): r=0 so check g channel instead.
+        // The default bar_partial color is (0, 48, 48): r=0 so check g channel instead.
         let e = entry("foo.rs", FileStatus::Modified, true, 6, 0);
-        let fresh = colorize_bar_styled("▍", &e, 0.0, true);
-        let aged = colorize_bar_styled("▍", &e, 1.0, true);
+        let fresh = colorize_bar_styled("▍", &e, 0.0, true, THEME);
+        let aged = colorize_bar_styled("▍", &e, 1.0, true, THEME);
         let (Some(Color::TrueColor { g: fg, .. }), Some(Color::TrueColor { g: ag, .. })) =
             (fresh[0].bgcolor, aged[0].bgcolor)
         else {
@@ -3106,7 +3811,7 @@ mod tests {
     fn file_bar_fallback_unchanged_without_truecolor() {
         // 8-color path returns the cyan-fill bytes today. Regression guard.
         let e = entry("foo.rs", FileStatus::Modified, true, 6, 0);
-        let cells = colorize_bar_styled("█", &e, 0.0, false);
+        let cells = colorize_bar_styled("█", &e, 0.0, false, THEME);
         use colored::Color;
         assert_eq!(cells[0].fgcolor, Some(Color::Cyan));
     }
@@ -3130,6 +3835,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_palette_override_reaches_the_truecolor_output() {
+        let out = testcolor::with_forced_ansi(|| {
+            let snap = snap_with(vec![entry("src/foo.rs", FileStatus::Modified, false, 5, 2)]);
+            let mut o = opts();
+            o.truecolor = true;
+            o.theme.palette[Hue::Adds] = (1, 2, 3);
+            render(&snap, &o)
+        });
+        // A zero-age row is unfaded, so the base color comes through exactly.
+        assert!(out.contains("\x1b[38;2;1;2;3m"), "got {out:?}");
+    }
+
+    #[test]
+    fn the_theme_decides_the_order_of_the_sections() {
+        let mut snap = snap_with(vec![entry("src/foo.rs", FileStatus::Modified, false, 5, 2)]);
+        snap.log = vec![log_entry("abc1234", "a commit", 60)];
+        let mut o = opts();
+        o.log_lines = 5;
+        let position = |out: &str, needle: &str| {
+            out.lines()
+                .position(|line| line.contains(needle))
+                .unwrap_or_else(|| panic!("{needle} should render: {out}"))
+        };
+
+        let out = strip_ansi(&render(&snap, &o));
+        assert!(position(&out, "abc1234") < position(&out, "src/foo.rs"));
+
+        o.theme.sections = [
+            Section::Files,
+            Section::Log,
+            Section::Reflog,
+            Section::Stash,
+        ];
+        let out = strip_ansi(&render(&snap, &o));
+        assert!(
+            position(&out, "src/foo.rs") < position(&out, "abc1234"),
+            "files first should draw the file list above the log: {out}",
+        );
+    }
+
+    #[test]
+    fn the_selected_row_is_reversed_end_to_end_and_no_wider() {
+        // The reverse has to survive every reset the row's own coloring emits,
+        // or the highlight would stop at the first colored cell.
+        let out = testcolor::with_forced_ansi(|| {
+            let snap = snap_with(vec![
+                entry("src/foo.rs", FileStatus::Modified, false, 5, 2),
+                entry("src/bar.rs", FileStatus::Modified, false, 1, 0),
+            ]);
+            let mut o = opts();
+            o.selected = Some(1);
+            render(&snap, &o)
+        });
+        let bar = out
+            .lines()
+            .find(|line| line.contains("src/bar.rs"))
+            .expect("the selected row");
+        assert!(bar.starts_with("\x1b[7m"), "got {bar:?}");
+        let resumed: Vec<&str> = bar.split("\x1b[0m").skip(1).collect();
+        let (closing, inside) = resumed.split_last().expect("the row ends with a reset");
+        assert!(closing.is_empty(), "the row ends on its reset: {bar:?}");
+        assert!(
+            inside.iter().all(|after| after.starts_with("\x1b[7m")),
+            "every reset inside the row re-applies the reverse: {bar:?}",
+        );
+        let foo = out
+            .lines()
+            .find(|line| line.contains("src/foo.rs"))
+            .expect("the other row");
+        assert!(
+            !foo.contains("\x1b[7m"),
+            "only the selected row is reversed: {foo:?}"
+        );
+        assert_eq!(
+            strip_ansi(&out),
+            strip_ansi(&testcolor::with_forced_ansi(|| {
+                let snap = snap_with(vec![
+                    entry("src/foo.rs", FileStatus::Modified, false, 5, 2),
+                    entry("src/bar.rs", FileStatus::Modified, false, 1, 0),
+                ]);
+                render(&snap, &opts())
+            })),
+            "a highlight adds escapes and nothing else",
+        );
+    }
+
     #[test]
     fn file_row_no_truecolor_in_8_color_mode() {
         let out = testcolor::with_forced_ansi(|| {
@@ -3219,8 +4011,8 @@ mod tests {
                 if abase == bbase {
                     continue;
                 }
-                let (ar, ag, ab) = fade_rgb(abase, 1.0);
-                let (br, bg, bb) = fade_rgb(bbase, 1.0);
+                let (ar, ag, ab) = FadeCurve::DEFAULT.rgb(abase, 1.0);
+                let (br, bg, bb) = FadeCurve::DEFAULT.rgb(bbase, 1.0);
                 let dist = (i32::from(ar) - i32::from(br)).abs()
                     + (i32::from(ag) - i32::from(bg)).abs()
                     + (i32::from(ab) - i32::from(bb)).abs();
@@ -3242,30 +4034,30 @@ mod tests {
         assert_palette_distinct_at_floor(
             "icon",
             &[
-                ("staged", FILE_ICON_STAGED_RGB),
-                ("unstaged", FILE_ICON_UNSTAGED_RGB),
-                ("untracked", FILE_ICON_UNTRACKED_RGB),
-                ("conflict", FILE_ICON_CONFLICT_RGB),
+                ("staged", Palette::DEFAULT[Hue::IconStaged]),
+                ("unstaged", Palette::DEFAULT[Hue::IconUnstaged]),
+                ("untracked", Palette::DEFAULT[Hue::IconUntracked]),
+                ("conflict", Palette::DEFAULT[Hue::IconConflict]),
             ],
         );
         assert_palette_distinct_at_floor(
             "path",
             &[
-                ("staged", FILE_PATH_STAGED_RGB),
-                ("unstaged", FILE_PATH_UNSTAGED_RGB),
-                ("untracked", FILE_PATH_UNTRACKED_RGB),
-                ("conflict", FILE_PATH_CONFLICT_RGB),
+                ("staged", Palette::DEFAULT[Hue::PathStaged]),
+                ("unstaged", Palette::DEFAULT[Hue::PathUnstaged]),
+                ("untracked", Palette::DEFAULT[Hue::PathUntracked]),
+                ("conflict", Palette::DEFAULT[Hue::PathConflict]),
             ],
         );
         assert_palette_distinct_at_floor(
             "letter",
             &[
-                ("added", FILE_LETTER_ADDED_RGB),
-                ("deleted", FILE_LETTER_DELETED_RGB),
-                ("renamed", FILE_LETTER_RENAMED_RGB),
-                ("default", FILE_LETTER_DEFAULT_RGB),
-                ("conflict", FILE_LETTER_CONFLICT_RGB),
-                ("untracked", FILE_LETTER_UNTRACKED_RGB),
+                ("added", Palette::DEFAULT[Hue::LetterAdded]),
+                ("deleted", Palette::DEFAULT[Hue::LetterDeleted]),
+                ("renamed", Palette::DEFAULT[Hue::LetterRenamed]),
+                ("default", Palette::DEFAULT[Hue::LetterOther]),
+                ("conflict", Palette::DEFAULT[Hue::LetterConflict]),
+                ("untracked", Palette::DEFAULT[Hue::LetterUntracked]),
             ],
         );
     }
@@ -3276,7 +4068,7 @@ mod tests {
         let mut e = entry("assets/logo.png", FileStatus::Modified, true, 0, 0);
         e.binary = true;
         // Use the same "bin" string render_row builds for binary entries.
-        let cells = colorize_bar_styled("bin", &e, 0.0, true);
+        let cells = colorize_bar_styled("bin", &e, 0.0, true, THEME);
         match cells[0].fgcolor {
             Some(Color::TrueColor { .. }) => {}
             other => panic!("expected TrueColor for binary marker, got {other:?}"),
@@ -3289,7 +4081,7 @@ mod tests {
         use colored::Styles;
         let mut e = entry("assets/logo.png", FileStatus::Modified, true, 0, 0);
         e.binary = true;
-        let cells = colorize_bar_styled("bin", &e, 0.0, false);
+        let cells = colorize_bar_styled("bin", &e, 0.0, false, THEME);
         assert!(
             cells[0].style.contains(Styles::Dimmed),
             "8-color binary marker should still be .dimmed()",
@@ -3301,8 +4093,8 @@ mod tests {
         use colored::Color;
         let mut e = entry("assets/logo.png", FileStatus::Modified, true, 0, 0);
         e.binary = true;
-        let fresh = colorize_bar_styled("bin", &e, 0.0, true);
-        let aged = colorize_bar_styled("bin", &e, 1.0, true);
+        let fresh = colorize_bar_styled("bin", &e, 0.0, true, THEME);
+        let aged = colorize_bar_styled("bin", &e, 1.0, true, THEME);
         let (Some(Color::TrueColor { r: fr, .. }), Some(Color::TrueColor { r: ar, .. })) =
             (fresh[0].fgcolor, aged[0].fgcolor)
         else {
@@ -3324,8 +4116,8 @@ mod tests {
         let (out_ansi, out_tc) = testcolor::with_forced_ansi(|| {
             let mut e = entry("assets/logo.png", FileStatus::Modified, true, 0, 0);
             e.binary = true;
-            let out_ansi = colorize_bar("bin", &e, 0.0, false);
-            let out_tc = colorize_bar("bin", &e, 0.0, true);
+            let out_ansi = colorize_bar("bin", &e, 0.0, false, THEME);
+            let out_tc = colorize_bar("bin", &e, 0.0, true, THEME);
             (out_ansi, out_tc)
         });
         assert_eq!(
@@ -8,7 +8,7 @@
 use std::collections::HashMap;
 
 use crate::git::{FileEntry, FileStatus, NumStat};
-use crate::render::{Operation, StepProgress, UpstreamStatus};
+use crate::render::{Operation, RenderEntry, StepProgress, UpstreamStatus};
 
 /// A repository handle that can be re-opened between reads.
 ///
@@ -184,6 +184,35 @@ pub fn recent_log(repo: &gix::Repository, n: usize) -> Vec<(String, i64, String)
         .collect()
 }
 
+/// The `n` newest entries of `reference`'s reflog as `(index, unix_secs,
+/// message)`, newest first — `index` is the `i` of git's `<reference>@{i}`.
+/// Empty when `n == 0` or the reference has no reflog.
+///
+/// A line that does not parse is skipped rather than ending the list, and the
+/// ones after it keep their own indices, so a selector built from one still
+/// names the entry git means by it.
+pub fn reflog(repo: &gix::Repository, reference: &str, n: usize) -> Vec<(usize, i64, String)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let Ok(reference) = repo.find_reference(reference) else {
+        return Vec::new();
+    };
+    let mut platform = reference.log_iter();
+    let Ok(Some(lines)) = platform.rev() else {
+        return Vec::new();
+    };
+    lines
+        .take(n)
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.ok()?;
+            let message = line.message.to_string().trim().to_string();
+            Some((index, line.signature.time.seconds, message))
+        })
+        .collect()
+}
+
 /// How HEAD relates to its base ref, as a pair of commit counts. See
 /// [`base_status`].
 pub struct BaseStatus {
@@ -348,6 +377,86 @@ fn pick_push_remote(names: &[String], push_default: Option<&str>) -> Option<Stri
 /// user has expressed no preference.
 const ORIGIN: &str = "origin";
 
+/// A checked-out submodule, opened as a repository of its own.
+///
+/// gsw's status walk of the superproject sees a dirty submodule as one path
+/// with a `SubmoduleModification` — the same opaque row `git status` prints.
+/// Expanding it means walking the submodule itself, so this hands back a
+/// handle to walk plus what the superproject alone can say about it.
+pub struct SubmoduleCheckout {
+    /// Where the submodule lives, relative to the superproject's work tree —
+    /// the same spelling as the superproject's row for it.
+    pub path: String,
+    /// The submodule, opened from its own git dir.
+    pub repo: gix::Repository,
+    /// The commit the superproject records for it, when there is one.
+    recorded: Option<gix::ObjectId>,
+}
+
+impl SubmoduleCheckout {
+    /// How far the checkout has moved from the recorded gitlink, as `(ahead,
+    /// behind)`: commits its HEAD has that the gitlink does not, and the
+    /// mirror — a `git submodule update` the checkout has not had yet.
+    ///
+    /// A gitlink the submodule has not fetched reads as level rather than as
+    /// a failure, since the walk that would count it cannot see the commit.
+    /// Two rev walks, so it is asked only of a submodule that is going to be
+    /// drawn.
+    pub fn drift(&self) -> (u32, u32) {
+        let head = self.repo.head_id().ok().map(gix::Id::detach);
+        match (head, self.recorded) {
+            (Some(head), Some(recorded)) => {
+                ahead_behind(&self.repo, head, recorded).unwrap_or((0, 0))
+            }
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Every submodule of `repo` that is checked out and that `wanted` accepts by
+/// path, in `.gitmodules` order.
+///
+/// `wanted` is consulted before a submodule is opened, so a caller that already
+/// knows which submodules changed — the superproject's status walk flags them —
+/// pays nothing for the hundred that did not.
+///
+/// "Recorded" is the superproject's *index*, falling back to its HEAD tree: the
+/// index is what the next superproject commit records, and what `git status`
+/// compares the checkout against when it says `new commits`. A submodule that
+/// is declared but not initialized has no repository to open and is skipped —
+/// there is nothing in it to change.
+///
+/// One level deep. A submodule's own submodules stay opaque rows inside its
+/// group: a monorepo nested that far is rare, and walking it would multiply the
+/// cost of every refresh by the depth.
+pub fn submodule_checkouts(
+    repo: &gix::Repository,
+    wanted: impl Fn(&str) -> bool,
+) -> Vec<SubmoduleCheckout> {
+    let Ok(Some(submodules)) = repo.submodules() else {
+        return Vec::new();
+    };
+    submodules
+        .filter_map(|submodule| {
+            let path = submodule.path().ok()?.to_string();
+            if !wanted(&path) {
+                return None;
+            }
+            let sub = submodule.open().ok()??;
+            let recorded = submodule
+                .index_id()
+                .ok()
+                .flatten()
+                .or_else(|| submodule.head_id().ok().flatten());
+            Some(SubmoduleCheckout {
+                path,
+                repo: sub,
+                recorded,
+            })
+        })
+        .collect()
+}
+
 /// The in-progress git operation gsw should surface in the header, or `None`
 /// for a clean tree or an out-of-scope operation.
 ///
@@ -674,6 +783,146 @@ pub fn collect_changes(repo: &gix::Repository) -> anyhow::Result<Changes> {
     })
 }
 
+/// Lines of context a [`file_diff`] hunk keeps around each change — git's own
+/// default, so the diff reads the way `git diff` would print it.
+const DIFF_CONTEXT: u32 = 3;
+
+/// The unified diff behind one file row, as plain lines: `@@` hunk headers,
+/// then ` `, `-`, and `+` lines, with no `diff --git` preamble — the row the
+/// user selected already names the file.
+///
+/// Which two sides are compared follows the row, the same pairs
+/// [`collect_changes`] counted its numstat from, so the `+`/`-` lines here add
+/// up to the counts the row shows:
+///
+/// - a **staged** row is the HEAD tree against the index (an unborn branch
+///   reads as the empty tree), following a rename back to its old path;
+/// - an **unstaged** row is the index against the work tree, and an untracked
+///   file is the empty file against the work tree;
+/// - a **conflicted** row is HEAD against the work tree, so the conflict
+///   markers show up as the lines they are; the index holds three stages and
+///   no one copy to compare with.
+///
+/// A binary on either side, or an untracked directory, comes back as one line
+/// saying so rather than a diff. No change at all comes back empty.
+///
+/// # Errors
+///
+/// Returns an error when the index cannot be read. A blob or a file that cannot
+/// be read is an empty side, exactly as it is for the numstat.
+pub fn file_diff(repo: &gix::Repository, entry: &RenderEntry) -> anyhow::Result<Vec<String>> {
+    if entry.status == FileStatus::UntrackedDir {
+        return Ok(vec![
+            "untracked directory — stage it to see what is inside".to_string()
+        ]);
+    }
+    let path = entry.path.as_str();
+    let orig = entry.orig_path.as_deref().unwrap_or(path);
+    let index = repo
+        .index_or_empty()
+        .map_err(|e| anyhow::anyhow!("index: {e}"))?;
+    let indexed = |path: &str| {
+        index
+            .entry_by_path(path.into())
+            .map(|found| blob_bytes(repo, found.id.as_ref()))
+            .unwrap_or_default()
+    };
+    let committed = |path: &str| {
+        repo.head_tree()
+            .ok()
+            .and_then(|tree| tree.lookup_entry_by_path(path).ok().flatten())
+            .map(|found| blob_bytes(repo, found.object_id().as_ref()))
+            .unwrap_or_default()
+    };
+    let on_disk = |path: &str| worktree_bytes(repo, &path.into());
+
+    let (old, new) = match entry.status {
+        _ if entry.staged => (committed(orig), indexed(path)),
+        FileStatus::Untracked => (Vec::new(), on_disk(path)),
+        FileStatus::Conflicted => (committed(path), on_disk(path)),
+        FileStatus::Deleted => (indexed(path), Vec::new()),
+        _ => (indexed(orig), on_disk(path)),
+    };
+    if is_binary(&old) || is_binary(&new) {
+        return Ok(vec!["binary file — no line diff to show".to_string()]);
+    }
+    Ok(unified_lines(&old, &new))
+}
+
+/// Format the line diff of `old` against `new` as unified-diff lines, hunks
+/// within `2 * DIFF_CONTEXT` lines of each other merged into one, as `git diff`
+/// does.
+fn unified_lines(old: &[u8], new: &[u8]) -> Vec<String> {
+    use gix::diff::blob::{sources::byte_lines, Algorithm, Diff, InternedInput};
+    let input = InternedInput::new(byte_lines(old), byte_lines(new));
+    let mut diff = Diff::compute(Algorithm::Histogram, &input);
+    diff.postprocess_lines(&input);
+    let hunks: Vec<_> = diff.hunks().collect();
+    let text = |marker: char, token| {
+        let line = String::from_utf8_lossy(input.interner[token]);
+        format!("{marker}{}", line.trim_end_matches(['\n', '\r']))
+    };
+    let before_len = u32::try_from(input.before.len()).unwrap_or(u32::MAX);
+
+    let mut lines = Vec::new();
+    let mut first = 0;
+    while first < hunks.len() {
+        // Grow the group while the next hunk's leading context would overlap
+        // this one's trailing context.
+        let mut last = first;
+        while hunks
+            .get(last + 1)
+            .is_some_and(|next| next.before.start - hunks[last].before.end <= 2 * DIFF_CONTEXT)
+        {
+            last += 1;
+        }
+        let (head, tail) = (&hunks[first], &hunks[last]);
+        // Unchanged lines map one to one, so the context either side is the
+        // same length in both files.
+        let lead = DIFF_CONTEXT.min(head.before.start);
+        let trail = DIFF_CONTEXT.min(before_len - tail.before.end);
+        let (before_start, after_start) = (head.before.start - lead, head.after.start - lead);
+        let before_end = tail.before.end + trail;
+        let after_end = tail.after.end + trail;
+        lines.push(format!(
+            "@@ -{} +{} @@",
+            hunk_range(before_start, before_end),
+            hunk_range(after_start, after_end),
+        ));
+
+        let context =
+            |from: u32, to: u32| (from..to).map(|at| text(' ', input.before[at as usize]));
+        let mut at = before_start;
+        for hunk in &hunks[first..=last] {
+            lines.extend(context(at, hunk.before.start));
+            lines.extend(
+                hunk.before
+                    .clone()
+                    .map(|i| text('-', input.before[i as usize])),
+            );
+            lines.extend(
+                hunk.after
+                    .clone()
+                    .map(|i| text('+', input.after[i as usize])),
+            );
+            at = hunk.before.end;
+        }
+        lines.extend(context(at, before_end));
+        first = last + 1;
+    }
+    lines
+}
+
+/// One side of a hunk header for the 0-based, half-open line range
+/// `start..end`. An empty side is numbered by the line it follows rather than
+/// the one it would start at — `-0,0` for a file that did not exist — which is
+/// how `git diff` writes it and what anyone reading the header expects.
+fn hunk_range(start: u32, end: u32) -> String {
+    let len = end - start;
+    let first = if len == 0 { start } else { start + 1 };
+    format!("{first},{len}")
+}
+
 /// Count added/removed lines between two blobs; flag binaries (NUL in first 8 KiB).
 fn line_counts(old: &[u8], new: &[u8]) -> NumStat {
     if is_binary(old) || is_binary(new) {
@@ -739,7 +988,8 @@ mod tests {
     use crate::git::FileStatus;
     use crate::render::{Operation, StepProgress};
     use crate::testrepo::{
-        git, git_allowing_failure, init_repo, init_repo_with_upstream, init_repo_with_worktree,
+        git, git_allowing_failure, init_repo, init_repo_with_submodule, init_repo_with_upstream,
+        init_repo_with_worktree,
     };
 
     /// Open a repo at an explicit path (tests can't rely on cwd under a
@@ -919,6 +1169,44 @@ mod tests {
         assert!(super::recent_log(&repo, 0).is_empty());
     }
 
+    #[test]
+    fn reflog_lists_moves_of_head_newest_first_with_their_indices() {
+        let dir = init_repo();
+        let p = dir.path();
+        git(p, &["checkout", "-q", "-b", "topic"]);
+        git(p, &["commit", "-q", "--allow-empty", "-m", "on topic"]);
+        let repo = open_at(p).unwrap();
+        let moves = super::reflog(&repo, "HEAD", 2);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].0, 0);
+        assert_eq!(moves[0].2, "commit: on topic");
+        assert_eq!(moves[1].0, 1);
+        assert_eq!(moves[1].2, "checkout: moving from main to topic");
+        assert!(moves[0].1 > 0, "the entry's timestamp is read");
+    }
+
+    #[test]
+    fn reflog_of_the_stash_lists_every_stash() {
+        let dir = init_repo();
+        let p = dir.path();
+        for edit in ["one\n", "two\n"] {
+            std::fs::write(p.join("a.txt"), edit).unwrap();
+            git(p, &["stash", "push", "-q", "-m", edit.trim()]);
+        }
+        let repo = open_at(p).unwrap();
+        let stashes = super::reflog(&repo, "refs/stash", 10);
+        let messages: Vec<&str> = stashes.iter().map(|(_, _, m)| m.as_str()).collect();
+        assert_eq!(messages, ["On main: two", "On main: one"]);
+    }
+
+    #[test]
+    fn reflog_without_a_stash_or_lines_is_empty() {
+        let dir = init_repo();
+        let repo = open_at(dir.path()).unwrap();
+        assert!(super::reflog(&repo, "refs/stash", 10).is_empty());
+        assert!(super::reflog(&repo, "HEAD", 0).is_empty());
+    }
+
     fn statuses(repo: &gix::Repository) -> Vec<(String, FileStatus, bool)> {
         super::collect_changes(repo)
             .unwrap()
@@ -1051,6 +1339,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_submodule_checkout_counts_its_drift_from_the_recorded_gitlink() {
+        let (_library, superproject) = init_repo_with_submodule();
+        let p = superproject.path();
+        let lib = p.join("libs").join("lib");
+        std::fs::write(lib.join("l.txt"), "more\n").expect("write l.txt");
+        git(&lib, &["add", "l.txt"]);
+        git(&lib, &["commit", "-q", "-m", "past the gitlink"]);
+
+        let repo = open_at(p).unwrap();
+        let checkouts = super::submodule_checkouts(&repo, |_| true);
+        assert_eq!(checkouts.len(), 1);
+        assert_eq!(checkouts[0].path, "libs/lib");
+        assert_eq!(super::branch_name(&checkouts[0].repo), "main");
+        assert_eq!(checkouts[0].drift(), (1, 0));
+    }
+
+    #[test]
+    fn submodule_checkouts_skips_what_the_caller_did_not_ask_for() {
+        let (_library, superproject) = init_repo_with_submodule();
+        let repo = open_at(superproject.path()).unwrap();
+        assert!(super::submodule_checkouts(&repo, |path| path != "libs/lib").is_empty());
+    }
+
+    #[test]
+    fn an_untouched_submodule_is_level_with_its_gitlink() {
+        let (_library, superproject) = init_repo_with_submodule();
+        let repo = open_at(superproject.path()).unwrap();
+        let checkouts = super::submodule_checkouts(&repo, |_| true);
+        assert_eq!(checkouts.len(), 1);
+        assert_eq!(checkouts[0].drift(), (0, 0));
+    }
+
+    #[test]
+    fn a_walk_expands_a_changed_submodule_in_place_of_its_opaque_row() {
+        let (_library, superproject) = init_repo_with_submodule();
+        let p = superproject.path();
+        let lib = p.join("libs").join("lib");
+        std::fs::write(lib.join("a.txt"), "edited in the submodule\n").expect("edit a.txt");
+        std::fs::write(lib.join("new.txt"), "new\n").expect("write new.txt");
+        std::fs::write(p.join("a.txt"), "edited in the superproject\n").expect("edit a.txt");
+
+        let handle = RepoHandle::discover(p).expect("superproject is a worktree repo");
+        let cfg = crate::RenderConfig {
+            base: None,
+            max_files: None,
+            bar_width: 20,
+            log_lines: 0,
+            reflog_lines: 0,
+            stash_lines: 0,
+            truecolor: false,
+            width_offset: 0,
+            refresh_interval: None,
+            theme: crate::theme::Theme::DEFAULT,
+        };
+        let snapshot = crate::collect_snapshot(handle.repo(), &cfg).expect("walk");
+        let parent: Vec<&str> = snapshot.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            parent,
+            ["a.txt"],
+            "the submodule's opaque row gives way to its group",
+        );
+        assert_eq!(snapshot.submodules.len(), 1);
+        let group = &snapshot.submodules[0];
+        assert_eq!(
+            (group.path.as_str(), group.branch.as_str()),
+            ("libs/lib", "main")
+        );
+        let mut inside: Vec<&str> = group.files.iter().map(|f| f.path.as_str()).collect();
+        inside.sort_unstable();
+        assert_eq!(inside, ["a.txt", "new.txt"]);
+    }
+
     #[test]
     fn numstat_staged_modification_counts_lines() {
         let dir = init_repo(); // a.txt = "initial\n"
@@ -1736,11 +2097,111 @@ mod push_remote_tests {
             max_files: None,
             bar_width: 20,
             log_lines: 0,
+            reflog_lines: 0,
+            stash_lines: 0,
             truecolor: false,
             width_offset: 0,
             refresh_interval: None,
+            theme: crate::theme::Theme::DEFAULT,
         };
         let snapshot = crate::collect_snapshot(handle.repo(), &cfg).expect("walk the clone");
         assert_eq!(snapshot.push_remote, Some("origin".to_string()));
     }
 }
+
+#[cfg(test)]
+mod file_diff_tests {
+    use super::{file_diff, RepoHandle};
+    use crate::git::FileStatus;
+    use crate::render::RenderEntry;
+    use crate::testrepo::{git, init_repo};
+
+    fn row(path: &str, status: FileStatus, staged: bool) -> RenderEntry {
+        RenderEntry {
+            path: path.to_string(),
+            orig_path: None,
+            status,
+            staged,
+            adds: 0,
+            dels: 0,
+            binary: false,
+            age: None,
+        }
+    }
+
+    fn diff_of(dir: &std::path::Path, entry: &RenderEntry) -> Vec<String> {
+        let handle = RepoHandle::discover(dir).expect("open the fixture");
+        file_diff(handle.repo(), entry).expect("read the diff")
+    }
+
+    #[test]
+    fn an_unstaged_edit_is_the_index_against_the_disk() {
+        let dir = init_repo();
+        std::fs::write(dir.path().join("a.txt"), "changed\n").expect("edit a.txt");
+        assert_eq!(
+            diff_of(dir.path(), &row("a.txt", FileStatus::Modified, false)),
+            ["@@ -1,1 +1,1 @@", "-initial", "+changed"],
+        );
+    }
+
+    #[test]
+    fn a_staged_edit_is_head_against_the_index_whatever_the_disk_says() {
+        // The two rows of a file changed on both sides must show two different
+        // diffs, or the staged one would repeat the work tree's.
+        let dir = init_repo();
+        std::fs::write(dir.path().join("a.txt"), "staged\n").expect("edit a.txt");
+        git(dir.path(), &["add", "a.txt"]);
+        std::fs::write(dir.path().join("a.txt"), "on disk\n").expect("edit a.txt again");
+        assert_eq!(
+            diff_of(dir.path(), &row("a.txt", FileStatus::Modified, true)),
+            ["@@ -1,1 +1,1 @@", "-initial", "+staged"],
+        );
+        assert_eq!(
+            diff_of(dir.path(), &row("a.txt", FileStatus::Modified, false)),
+            ["@@ -1,1 +1,1 @@", "-staged", "+on disk"],
+        );
+    }
+
+    #[test]
+    fn an_untracked_file_is_all_additions() {
+        let dir = init_repo();
+        std::fs::write(dir.path().join("new.txt"), "one\ntwo\n").expect("write new.txt");
+        assert_eq!(
+            diff_of(dir.path(), &row("new.txt", FileStatus::Untracked, false)),
+            ["@@ -0,0 +1,2 @@", "+one", "+two"],
+        );
+    }
+
+    #[test]
+    fn distant_edits_get_their_own_hunks_with_three_lines_of_context() {
+        let dir = init_repo();
+        let before: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        std::fs::write(dir.path().join("a.txt"), &before).expect("write a.txt");
+        git(dir.path(), &["commit", "-q", "-am", "twenty lines"]);
+        let after: String = (1..=20)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                n => format!("{n}\n"),
+            })
+            .collect();
+        std::fs::write(dir.path().join("a.txt"), after).expect("edit a.txt");
+
+        let lines = diff_of(dir.path(), &row("a.txt", FileStatus::Modified, false));
+        let headers: Vec<&str> = lines
+            .iter()
+            .map(String::as_str)
+            .filter(|line| line.starts_with("@@"))
+            .collect();
+        assert_eq!(headers, ["@@ -1,5 +1,5 @@", "@@ -16,5 +16,5 @@"]);
+    }
+
+    #[test]
+    fn a_binary_file_says_so_instead_of_a_line_diff() {
+        let dir = init_repo();
+        std::fs::write(dir.path().join("blob.bin"), b"\0\x01\x02").expect("write blob.bin");
+        let lines = diff_of(dir.path(), &row("blob.bin", FileStatus::Untracked, false));
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("binary"), "got {lines:?}");
+    }
+}
@@ -25,6 +25,34 @@ pub fn build_snapshot(
     unstaged_numstat: &HashMap<String, NumStat>,
     ages: &HashMap<String, Duration>,
 ) -> Snapshot {
+    let files = build_rows(status_entries, staged_numstat, unstaged_numstat, ages);
+    Snapshot {
+        branch,
+        base,
+        commits_ahead,
+        commits_behind,
+        files,
+        log: Vec::new(),
+        upstream: None,
+        operation: None,
+        push_remote: None,
+        reflog: Vec::new(),
+        stashes: Vec::new(),
+        submodules: Vec::new(),
+    }
+}
+
+/// Turn a status walk's entries into file rows, newest first.
+///
+/// The file-list half of [`build_snapshot`], shared with the submodule groups,
+/// whose rows come from a walk of their own and must be ordered and counted
+/// exactly like the superproject's.
+pub fn build_rows(
+    status_entries: Vec<FileEntry>,
+    staged_numstat: &HashMap<String, NumStat>,
+    unstaged_numstat: &HashMap<String, NumStat>,
+    ages: &HashMap<String, Duration>,
+) -> Vec<RenderEntry> {
     let mut files: Vec<RenderEntry> = status_entries
         .into_iter()
         .map(|e| {
@@ -58,17 +86,7 @@ pub fn build_snapshot(
         (None, Some(_)) => Ordering::Greater,
         (None, None) => Ordering::Equal,
     });
-    Snapshot {
-        branch,
-        base,
-        commits_ahead,
-        commits_behind,
-        files,
-        log: Vec::new(),
-        upstream: None,
-        operation: None,
-        push_remote: None,
-    }
+    files
 }
 
 #[cfg(test)]
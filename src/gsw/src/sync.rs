@@ -0,0 +1,419 @@
+//! Keeping the current branch current from watch mode: `f` fetches, and `u`
+//! fast-forwards a branch that is strictly behind its upstream.
+//!
+//! The push's mirror image, and built from the same parts. Both are `git`
+//! children for the reason the push is — gix can fetch, but the credential
+//! helpers, `url.*.insteadOf` rewrites, and ssh configuration a fetch has to
+//! honor are git's to own, and a fast-forward writes the index and the work
+//! tree the way `git merge` does. So everything that *decides* lives here as
+//! pure code, and only [`spawn`] starts a process, through the same
+//! branch-checked runner the push uses.
+
+use std::path::PathBuf;
+
+use crate::push::{run_on_branch, GitOutcome};
+use crate::render::UpstreamStatus;
+use crate::repo::DETACHED_HEAD;
+
+/// What a key asks to do to the current branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SyncAction {
+    /// Fetch, so the header's ahead/behind counts describe the remote as it is
+    /// now rather than as it was at the last fetch (`f`).
+    Fetch,
+    /// Move the branch forward to its upstream, when that loses nothing (`u`).
+    FastForward,
+}
+
+/// A fetch or fast-forward ready to run: the branch it was decided for, and the
+/// `git` arguments that carry it out.
+///
+/// Built only by [`prompt_for`], and carrying its branch for the reason a
+/// [`crate::push::PushCommand`] does. Both commands resolve `@{upstream}` — or,
+/// for a bare fetch, the branch's configured remote — against whatever HEAD
+/// points at when the child starts, and a checkout in another pane between the
+/// question and the answer would otherwise fast-forward a branch the question
+/// never named.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyncCommand {
+    /// Which action this is, for the notice and the retry advice.
+    action: SyncAction,
+    /// The branch this was decided for, as [`crate::repo::branch_name`]
+    /// reports it.
+    branch: String,
+    /// Arguments to pass to `git`, not including the program name.
+    args: Vec<String>,
+}
+
+impl SyncCommand {
+    /// The command that runs `git <args>` for `action` on `branch`.
+    fn new(action: SyncAction, branch: &str, args: &[&str]) -> Self {
+        Self {
+            action,
+            branch: branch.to_string(),
+            args: args.iter().map(|arg| (*arg).to_string()).collect(),
+        }
+    }
+
+    /// The branch this was decided for.
+    pub(crate) fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// The arguments to pass to `git`, not including the program name.
+    pub(crate) fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// What the overlay says while this runs. A fetch is a network round trip,
+    /// so this can be up for seconds.
+    pub(crate) fn notice(&self) -> &'static str {
+        match self.action {
+            SyncAction::Fetch => "Fetching…",
+            SyncAction::FastForward => "Fast-forwarding…",
+        }
+    }
+
+    /// What a refusal for a changed branch tells the user to do. Pressing the
+    /// same key again decides against the branch checked out now.
+    fn retry_advice(&self) -> &'static str {
+        match self.action {
+            SyncAction::Fetch => "press f again",
+            SyncAction::FastForward => "press u again",
+        }
+    }
+}
+
+/// What the watch loop does with `f` or `u`.
+///
+/// The same shape as [`crate::action::FilePrompt`]: a fetch only moves
+/// remote-tracking refs, which nothing local depends on, so it runs without a
+/// question; a fast-forward moves the branch and the work tree, and asks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SyncPrompt {
+    /// Run now. The report comes when it finishes.
+    Run {
+        /// The command to run.
+        command: SyncCommand,
+        /// What to show once it succeeds.
+        success_message: String,
+    },
+    /// Ask before running.
+    Confirm {
+        /// The question, without the key hint.
+        question: String,
+        /// The command this question described.
+        command: SyncCommand,
+        /// What to show once it succeeds.
+        success_message: String,
+    },
+    /// Run nothing and show this instead.
+    Refuse {
+        /// Why nothing is going to happen.
+        message: String,
+    },
+}
+
+/// The key hint shown with a fast-forward confirmation.
+pub(crate) const CONFIRM_HINT: &str = "[y/Enter = fast-forward, n/Esc = cancel]";
+
+/// Decide what `action` does, given the branch state gsw already renders —
+/// the same three inputs [`crate::push::prompt_for`] takes.
+///
+/// A fetch goes where the header's tracking segment comes from. With an
+/// upstream that is the branch's own remote, so a bare `git fetch` lets git
+/// read it from the branch config exactly as a bare push does; without one it
+/// is the push remote, the only remote gsw would name on its own.
+///
+/// A fast-forward is only offered for a branch that is behind and not ahead.
+/// Ahead and behind at once is a branch that has diverged, and joining it back
+/// up is a rebase or a merge — a decision about history that belongs to the
+/// user at a prompt of their own, not to one key on a monitor. The counts are
+/// the snapshot's, so they are only as fresh as the last fetch, which is what
+/// the up-to-date refusal points at.
+pub(crate) fn prompt_for(
+    action: SyncAction,
+    branch: &str,
+    remote: Option<&str>,
+    upstream: Option<&UpstreamStatus>,
+) -> SyncPrompt {
+    match action {
+        SyncAction::Fetch => match (upstream, remote) {
+            (Some(up), _) => SyncPrompt::Run {
+                command: SyncCommand::new(action, branch, &["fetch"]),
+                success_message: format!("Fetched {}", up.name),
+            },
+            (None, Some(remote)) => SyncPrompt::Run {
+                command: SyncCommand::new(action, branch, &["fetch", remote]),
+                success_message: format!("Fetched {remote}"),
+            },
+            (None, None) => SyncPrompt::Refuse {
+                message: "no remote to fetch from".to_string(),
+            },
+        },
+        SyncAction::FastForward => fast_forward(branch, upstream),
+    }
+}
+
+/// The fast-forward half of [`prompt_for`].
+fn fast_forward(branch: &str, upstream: Option<&UpstreamStatus>) -> SyncPrompt {
+    // Checked before the upstream, so a tracking status left over from before
+    // the checkout cannot make a detached HEAD look like a branch.
+    if branch == DETACHED_HEAD {
+        return SyncPrompt::Refuse {
+            message: format!("{DETACHED_HEAD} is detached — check out a branch to fast-forward"),
+        };
+    }
+    let Some(up) = upstream else {
+        return SyncPrompt::Refuse {
+            message: format!("{branch} has no upstream to fast-forward to"),
+        };
+    };
+    let target = &up.name;
+    match (up.ahead, up.behind) {
+        (0, 0) => SyncPrompt::Refuse {
+            message: format!("{branch} is up to date with {target} — f fetches anything newer"),
+        },
+        (_, 0) => SyncPrompt::Refuse {
+            message: format!("{branch} is ahead of {target} — nothing to fast-forward to"),
+        },
+        (0, commits) => {
+            let unit = if commits == 1 { "commit" } else { "commits" };
+            SyncPrompt::Confirm {
+                question: format!("Fast-forward {branch} by {commits} {unit} from {target}?"),
+                // `merge --ff-only` rather than `pull --ff-only`: a pull fetches
+                // first, and could move the branch further than the count the
+                // question just named. This merges what the header showed.
+                command: SyncCommand::new(
+                    SyncAction::FastForward,
+                    branch,
+                    &["merge", "--ff-only", "-q", "@{upstream}"],
+                ),
+                success_message: format!("Fast-forwarded {branch} by {commits} {unit}"),
+            }
+        }
+        _ => SyncPrompt::Refuse {
+            message: format!("{branch} has diverged from {target} — rebase or merge with git"),
+        },
+    }
+}
+
+/// Run a fetch or fast-forward on a thread of its own and hand the outcome to
+/// `on_finish`.
+///
+/// Off the render thread for the reason a push is: a fetch is a network round
+/// trip, and the loop is what keeps the countdown and the ages moving. The
+/// branch is re-checked on that thread, immediately before git runs, as
+/// [`crate::push::run_on_branch`] describes.
+pub(crate) fn spawn<F>(command: SyncCommand, workdir: PathBuf, on_finish: F)
+where
+    F: FnOnce(GitOutcome) + Send + 'static,
+{
+    std::thread::spawn(move || on_finish(run(&command, &workdir)));
+}
+
+/// The blocking half of [`spawn`], separated so it can be tested against a real
+/// repository without a thread in the way.
+fn run(command: &SyncCommand, workdir: &std::path::Path) -> GitOutcome {
+    run_on_branch(
+        command.branch(),
+        command.args(),
+        workdir,
+        command.retry_advice(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::upstream_status;
+    use crate::testrepo::{git, init_repo_with_upstream};
+    use std::path::Path;
+
+    fn tracking(ahead: u32, behind: u32) -> UpstreamStatus {
+        UpstreamStatus {
+            name: "origin/main".to_string(),
+            ahead,
+            behind,
+        }
+    }
+
+    fn refusal(prompt: &SyncPrompt) -> &str {
+        match prompt {
+            SyncPrompt::Refuse { message } => message,
+            other => panic!("expected a refusal, got {other:?}"),
+        }
+    }
+
+    fn command(prompt: SyncPrompt) -> SyncCommand {
+        match prompt {
+            SyncPrompt::Run { command, .. } | SyncPrompt::Confirm { command, .. } => command,
+            SyncPrompt::Refuse { message } => panic!("expected a command, got {message:?}"),
+        }
+    }
+
+    /// Add a commit to `origin`'s `main`, so a clone is one behind after a
+    /// fetch.
+    fn advance_origin(origin: &Path) {
+        std::fs::write(origin.join("upstream.txt"), "news\n").expect("write upstream.txt");
+        git(origin, &["add", "upstream.txt"]);
+        git(origin, &["commit", "-q", "-m", "upstream work"]);
+    }
+
+    /// The clone's tracking status, as the header would draw it.
+    fn status(clone: &Path) -> UpstreamStatus {
+        let repo = gix::open(clone).expect("open clone");
+        upstream_status(&repo).expect("main tracks origin/main")
+    }
+
+    #[test]
+    fn a_fetch_runs_without_asking() {
+        let prompt = prompt_for(
+            SyncAction::Fetch,
+            "main",
+            Some("origin"),
+            Some(&tracking(0, 0)),
+        );
+        let SyncPrompt::Run {
+            command,
+            success_message,
+        } = prompt
+        else {
+            panic!("a fetch moves only remote-tracking refs, so it must not ask: {prompt:?}");
+        };
+        // Bare: git reads the remote from the branch config, which is where
+        // the tracking segment's counts come from.
+        assert_eq!(command.args(), ["fetch"]);
+        assert_eq!(command.branch(), "main");
+        assert_eq!(success_message, "Fetched origin/main");
+    }
+
+    #[test]
+    fn a_branch_with_no_upstream_fetches_the_push_remote() {
+        let prompt = prompt_for(SyncAction::Fetch, "topic", Some("origin"), None);
+        assert_eq!(command(prompt).args(), ["fetch", "origin"]);
+        assert_eq!(
+            refusal(&prompt_for(SyncAction::Fetch, "topic", None, None)),
+            "no remote to fetch from",
+        );
+    }
+
+    #[test]
+    fn a_branch_strictly_behind_asks_to_fast_forward() {
+        let prompt = prompt_for(
+            SyncAction::FastForward,
+            "main",
+            Some("origin"),
+            Some(&tracking(0, 3)),
+        );
+        let SyncPrompt::Confirm {
+            question,
+            command,
+            success_message,
+        } = prompt
+        else {
+            panic!("a fast-forward moves the work tree, so it must ask: {prompt:?}");
+        };
+        assert_eq!(question, "Fast-forward main by 3 commits from origin/main?");
+        assert_eq!(command.args(), ["merge", "--ff-only", "-q", "@{upstream}"]);
+        assert_eq!(success_message, "Fast-forwarded main by 3 commits");
+    }
+
+    #[test]
+    fn a_fast_forward_is_refused_unless_the_branch_is_strictly_behind() {
+        for (ahead, behind, expected) in [
+            (0, 0, "up to date"),
+            (2, 0, "nothing to fast-forward"),
+            (2, 1, "diverged"),
+        ] {
+            let prompt = prompt_for(
+                SyncAction::FastForward,
+                "main",
+                Some("origin"),
+                Some(&tracking(ahead, behind)),
+            );
+            assert!(
+                refusal(&prompt).contains(expected),
+                "ahead {ahead} behind {behind}: got {prompt:?}",
+            );
+        }
+        assert!(refusal(&prompt_for(
+            SyncAction::FastForward,
+            "main",
+            Some("origin"),
+            None
+        ))
+        .contains("no upstream"));
+        assert!(refusal(&prompt_for(
+            SyncAction::FastForward,
+            DETACHED_HEAD,
+            Some("origin"),
+            Some(&tracking(0, 1)),
+        ))
+        .contains("detached"));
+    }
+
+    #[test]
+    fn fetch_then_fast_forward_brings_the_branch_level_with_its_upstream() {
+        let (origin, clone) = init_repo_with_upstream();
+        advance_origin(origin.path());
+
+        let fetch = prompt_for(
+            SyncAction::Fetch,
+            "main",
+            Some("origin"),
+            Some(&status(clone.path())),
+        );
+        let outcome = run(&command(fetch), clone.path());
+        assert!(outcome.success, "fetch failed: {}", outcome.output);
+        let behind = status(clone.path());
+        assert_eq!(
+            (behind.ahead, behind.behind),
+            (0, 1),
+            "the fetch must show the new commit"
+        );
+
+        let forward = prompt_for(
+            SyncAction::FastForward,
+            "main",
+            Some("origin"),
+            Some(&behind),
+        );
+        let outcome = run(&command(forward), clone.path());
+        assert!(outcome.success, "fast-forward failed: {}", outcome.output);
+        let level = status(clone.path());
+        assert_eq!((level.ahead, level.behind), (0, 0));
+        assert!(
+            clone.path().join("upstream.txt").exists(),
+            "the work tree must move too"
+        );
+    }
+
+    #[test]
+    fn a_branch_change_after_the_question_refuses_the_fast_forward() {
+        let (origin, clone) = init_repo_with_upstream();
+        let p = clone.path();
+        advance_origin(origin.path());
+        git(p, &["fetch", "-q"]);
+
+        let forward = prompt_for(
+            SyncAction::FastForward,
+            "main",
+            Some("origin"),
+            Some(&status(p)),
+        );
+        // Another pane checks out a different branch before `y` arrives.
+        git(p, &["checkout", "-q", "-b", "elsewhere"]);
+
+        let outcome = run(&command(forward), p);
+        assert!(!outcome.success);
+        assert_eq!(
+            outcome.output,
+            "branch changed from main to elsewhere since the confirmation — press u again",
+        );
+        assert!(
+            !p.join("upstream.txt").exists(),
+            "nothing may have been merged"
+        );
+    }
+}
@@ -137,6 +137,34 @@ pub(crate) fn init_repo_with_worktree() -> (TempDir, PathBuf) {
     (repo, linked)
 }
 
+/// An [`init_repo`] superproject with a second [`init_repo`] repo added as a
+/// submodule at `libs/lib` and committed, returning `(library, superproject)`.
+///
+/// `protocol.file.allow=always` is passed per command because git refuses
+/// local-path submodule clones by default, and the fixture must not rely on the
+/// host's (scrubbed) config to allow them. The submodule's checkout gets its own
+/// identity so a test can commit inside it. Both [`TempDir`]s must be held: the
+/// library is the submodule's `origin`.
+pub(crate) fn init_repo_with_submodule() -> (TempDir, TempDir) {
+    let library = init_repo();
+    let superproject = init_repo();
+    git(
+        superproject.path(),
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "-q",
+            library.path().to_str().expect("utf-8 tempdir path"),
+            "libs/lib",
+        ],
+    );
+    git(superproject.path(), &["commit", "-q", "-m", "add lib"]);
+    identity(&superproject.path().join("libs").join("lib"));
+    (library, superproject)
+}
+
 /// Give the repo at `dir` a committer identity and disable signing, so commits
 /// succeed no matter how the host's (scrubbed) global config is set up.
 fn identity(dir: &Path) {
@@ -0,0 +1,334 @@
+//! The look of a frame: the truecolor palette, the age fade, and the order the
+//! sections under the header are stacked in.
+//!
+//! [`Theme::DEFAULT`] is what gsw has always drawn. A config file (see
+//! [`crate::config`]) can move any part of it; the renderer only ever reads the
+//! resolved [`Theme`] it is handed, so a frame drawn with the defaults is
+//! byte-identical to one drawn before any of this was configurable.
+
+use std::ops::{Index, IndexMut};
+
+use crate::age::FadeCurve;
+
+/// A 24-bit color.
+pub type Rgb = (u8, u8, u8);
+
+/// One configurable color in the palette, named in a config file's `[colors]`
+/// table by [`Hue::key`].
+///
+/// Every hue is a truecolor *base*: the fade darkens it toward the floor as the
+/// row ages, and without truecolor the renderer falls back to the eight ANSI
+/// colors, which no palette can reach. [`Hue::ALL`], [`Hue::key`], and
+/// [`Hue::parse`] are kept in lock-step by a round-trip test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hue {
+    /// The `●` of a staged row.
+    IconStaged,
+    /// The `○` of an unstaged row.
+    IconUnstaged,
+    /// The `?` of an untracked row.
+    IconUntracked,
+    /// The `!` of a conflicted row.
+    IconConflict,
+    /// The `A` status letter.
+    LetterAdded,
+    /// The `D` status letter.
+    LetterDeleted,
+    /// The `R` and `C` status letters.
+    LetterRenamed,
+    /// `M`, `T`, and any other status letter without a hue of its own.
+    LetterOther,
+    /// The `U` status letter of a conflicted row.
+    LetterConflict,
+    /// The `?` status letter of an untracked row.
+    LetterUntracked,
+    /// The path of a staged row.
+    PathStaged,
+    /// The path of an unstaged row.
+    PathUnstaged,
+    /// The path of an untracked row.
+    PathUntracked,
+    /// The path of a conflicted row.
+    PathConflict,
+    /// The magnitude bar.
+    Bar,
+    /// The magnitude bar of a conflicted row.
+    BarConflict,
+    /// The background under a partly-filled bar cell.
+    BarPartial,
+    /// The background under a partly-filled cell of a conflicted row's bar.
+    BarConflictPartial,
+    /// The `bin` marker in place of a binary file's bar.
+    Binary,
+    /// The `+N` column.
+    Adds,
+    /// The `-N` column.
+    Dels,
+    /// A file row's age column.
+    Age,
+    /// A commit's short hash.
+    LogHash,
+    /// A commit's subject.
+    LogSubject,
+    /// A commit's age column.
+    LogAge,
+    /// A reflog row's `HEAD@{n}` selector.
+    ReflogSelector,
+    /// A stash row's `stash@{n}` selector.
+    StashSelector,
+}
+
+impl Hue {
+    /// Every hue, in the order `--print-config` lists them.
+    pub const ALL: [Hue; 27] = [
+        Hue::IconStaged,
+        Hue::IconUnstaged,
+        Hue::IconUntracked,
+        Hue::IconConflict,
+        Hue::LetterAdded,
+        Hue::LetterDeleted,
+        Hue::LetterRenamed,
+        Hue::LetterOther,
+        Hue::LetterConflict,
+        Hue::LetterUntracked,
+        Hue::PathStaged,
+        Hue::PathUnstaged,
+        Hue::PathUntracked,
+        Hue::PathConflict,
+        Hue::Bar,
+        Hue::BarConflict,
+        Hue::BarPartial,
+        Hue::BarConflictPartial,
+        Hue::Binary,
+        Hue::Adds,
+        Hue::Dels,
+        Hue::Age,
+        Hue::LogHash,
+        Hue::LogSubject,
+        Hue::LogAge,
+        Hue::ReflogSelector,
+        Hue::StashSelector,
+    ];
+
+    /// The hue's name in a config file's `[colors]` table.
+    pub fn key(self) -> &'static str {
+        match self {
+            Hue::IconStaged => "icon_staged",
+            Hue::IconUnstaged => "icon_unstaged",
+            Hue::IconUntracked => "icon_untracked",
+            Hue::IconConflict => "icon_conflict",
+            Hue::LetterAdded => "letter_added",
+            Hue::LetterDeleted => "letter_deleted",
+            Hue::LetterRenamed => "letter_renamed",
+            Hue::LetterOther => "letter_other",
+            Hue::LetterConflict => "letter_conflict",
+            Hue::LetterUntracked => "letter_untracked",
+            Hue::PathStaged => "path_staged",
+            Hue::PathUnstaged => "path_unstaged",
+            Hue::PathUntracked => "path_untracked",
+            Hue::PathConflict => "path_conflict",
+            Hue::Bar => "bar",
+            Hue::BarConflict => "bar_conflict",
+            Hue::BarPartial => "bar_partial",
+            Hue::BarConflictPartial => "bar_conflict_partial",
+            Hue::Binary => "binary",
+            Hue::Adds => "adds",
+            Hue::Dels => "dels",
+            Hue::Age => "age",
+            Hue::LogHash => "log_hash",
+            Hue::LogSubject => "log_subject",
+            Hue::LogAge => "log_age",
+            Hue::ReflogSelector => "reflog_selector",
+            Hue::StashSelector => "stash_selector",
+        }
+    }
+
+    /// The hue a `[colors]` key names, or `None` for a key that names none.
+    pub fn parse(key: &str) -> Option<Hue> {
+        Hue::ALL.into_iter().find(|hue| hue.key() == key)
+    }
+
+    /// The built-in base color.
+    ///
+    /// Each file-row base is tuned so the unfaded color reads as the same hue
+    /// family as the ANSI color the eight-color path uses, and the fully faded
+    /// one (× the fade floor) still keeps the hue visible.
+    const fn default_rgb(self) -> Rgb {
+        match self {
+            Hue::IconStaged | Hue::LetterAdded | Hue::Adds => (90, 220, 110),
+            Hue::IconUnstaged | Hue::PathUnstaged => (220, 200, 100),
+            Hue::IconUntracked | Hue::LetterUntracked | Hue::PathUntracked => (120, 200, 200),
+            Hue::IconConflict | Hue::LetterDeleted | Hue::LetterConflict | Hue::BarConflict => {
+                (255, 80, 80)
+            }
+            Hue::LetterRenamed => (220, 120, 220),
+            Hue::LetterOther => (230, 230, 230),
+            Hue::PathStaged => (200, 200, 200),
+            Hue::PathConflict | Hue::Dels => (255, 90, 90),
+            Hue::Bar => (60, 200, 200),
+            // Dim cyan under a partial-fill cell, so the unpainted right
+            // portion of glyphs like `▍` blends into the dim `░` empty cells
+            // beside them instead of showing through as terminal black. `░` is
+            // the LIGHT SHADE character (~25% pixel coverage), so this is
+            // roughly a quarter of typical ANSI cyan's brightness.
+            Hue::BarPartial => (0, 48, 48),
+            // Same idea for the conflicted-file bar, which paints in red.
+            Hue::BarConflictPartial => (48, 0, 0),
+            Hue::Binary => (160, 160, 160),
+            Hue::Age | Hue::LogAge => (190, 190, 190),
+            // Picked to match the perceptual feel of the ANSI `yellow()` hash
+            // without depending on a specific terminal palette.
+            Hue::LogHash => (255, 215, 0),
+            // A near-white that fades visibly.
+            Hue::LogSubject => (220, 220, 220),
+            // Cyan, so a move of HEAD never reads as one of the gold commit
+            // hashes above it.
+            Hue::ReflogSelector => (100, 200, 230),
+            Hue::StashSelector => (200, 150, 255),
+        }
+    }
+}
+
+/// A base color for every [`Hue`], indexed by hue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette([Rgb; Hue::ALL.len()]);
+
+impl Palette {
+    /// The built-in palette.
+    pub const DEFAULT: Self = {
+        let mut colors = [(0, 0, 0); Hue::ALL.len()];
+        let mut i = 0;
+        while i < Hue::ALL.len() {
+            colors[i] = Hue::ALL[i].default_rgb();
+            i += 1;
+        }
+        Self(colors)
+    };
+}
+
+impl Index<Hue> for Palette {
+    type Output = Rgb;
+
+    fn index(&self, hue: Hue) -> &Rgb {
+        &self.0[hue as usize]
+    }
+}
+
+impl IndexMut<Hue> for Palette {
+    fn index_mut(&mut self, hue: Hue) -> &mut Rgb {
+        &mut self.0[hue as usize]
+    }
+}
+
+/// A section of the frame under the header, named in a config file's
+/// `[layout] sections` list by [`Section::key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// The recent-commit log.
+    Log,
+    /// The HEAD reflog.
+    Reflog,
+    /// The stash list.
+    Stash,
+    /// The file rows, and any submodule groups under them.
+    Files,
+}
+
+impl Section {
+    /// Every section, in the built-in order: the log anchored under the
+    /// header, the reflog next to the history it explains, then the stash, and
+    /// the file list at the bottom, where it can grow and shrink without
+    /// shoving the rest around.
+    pub const ALL: [Section; 4] = [
+        Section::Log,
+        Section::Reflog,
+        Section::Stash,
+        Section::Files,
+    ];
+
+    /// The section's name in a config file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Section::Log => "log",
+            Section::Reflog => "reflog",
+            Section::Stash => "stash",
+            Section::Files => "files",
+        }
+    }
+
+    /// The section a name refers to, or `None` for a name that refers to none.
+    pub fn parse(key: &str) -> Option<Section> {
+        Section::ALL
+            .into_iter()
+            .find(|section| section.key() == key)
+    }
+}
+
+/// Everything about a frame's look that a config file can change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// The truecolor base colors.
+    pub palette: Palette,
+    /// How rows darken with age.
+    pub fade: FadeCurve,
+    /// The sections under the header, top to bottom. Always each section
+    /// exactly once: the config loader refuses a list that drops or repeats
+    /// one, so the renderer never has to decide what a missing section means.
+    pub sections: [Section; 4],
+}
+
+impl Theme {
+    /// The built-in look.
+    pub const DEFAULT: Self = Self {
+        palette: Palette::DEFAULT,
+        fade: FadeCurve::DEFAULT,
+        sections: Section::ALL,
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_hue_round_trips_through_its_key() {
+        for hue in Hue::ALL {
+            assert_eq!(Hue::parse(hue.key()), Some(hue));
+        }
+        assert_eq!(Hue::parse("icon"), None);
+    }
+
+    #[test]
+    fn the_catalog_is_in_declaration_order() {
+        // `Palette` indexes by discriminant, so `ALL` must list the hues in the
+        // order they are declared or a key would set some other hue's color.
+        for (i, hue) in Hue::ALL.into_iter().enumerate() {
+            assert_eq!(hue as usize, i, "{hue:?} is out of place in Hue::ALL");
+        }
+    }
+
+    #[test]
+    fn every_section_round_trips_through_its_key() {
+        for section in Section::ALL {
+            assert_eq!(Section::parse(section.key()), Some(section));
+        }
+        assert_eq!(Section::parse("stashes"), None);
+    }
+
+    #[test]
+    fn a_palette_entry_can_be_replaced_without_touching_the_rest() {
+        let mut palette = Palette::DEFAULT;
+        palette[Hue::Bar] = (1, 2, 3);
+        assert_eq!(palette[Hue::Bar], (1, 2, 3));
+        assert_eq!(
+            palette[Hue::BarConflict],
+            Palette::DEFAULT[Hue::BarConflict]
+        );
+    }
+}
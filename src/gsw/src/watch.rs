@@ -8,6 +8,7 @@
 //! ([`resolve_dimensions`], [`should_react`], [`next_tick`]) so it can be
 //! unit-tested without a pty.
 
+use std::collections::BTreeSet;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
@@ -25,12 +26,16 @@ use crossterm::terminal::{
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 
-use crate::push::{PushCommand, PushUi};
-use crate::render::Snapshot;
-use crate::repo::RepoHandle;
+use crate::action::FileAction;
+use crate::browse::{Browser, Cursor, Motion, RowKey};
+use crate::push::{Job, PushUi};
+use crate::render::{RenderEntry, Snapshot};
+use crate::repo::{self, RepoHandle};
+use crate::sync::SyncAction;
 use crate::{
-    collect_snapshot, effective_terminal_height, effective_terminal_width, render_frame,
-    FrameTiming, Render, RenderConfig, DEFAULT_TERMINAL_HEIGHT, DEFAULT_TERMINAL_WIDTH,
+    collect_snapshot, effective_terminal_height, effective_terminal_width,
+    render_frame_with_cursor, FrameTiming, Render, RenderConfig, DEFAULT_TERMINAL_HEIGHT,
+    DEFAULT_TERMINAL_WIDTH,
 };
 
 /// Which rendering mode `gsw` is running in. The mode — not ambient env
@@ -138,13 +143,56 @@ pub(crate) fn resolve_dimensions(mode: Mode, inputs: &SizeInputs) -> Dimensions
 /// magnitude, so the read side must not serialize them — hence a reader-writer
 /// lock rather than a `Mutex`.
 #[derive(Clone)]
-pub(crate) struct LiveIgnore(Arc<RwLock<Gitignore>>);
+pub(crate) struct LiveIgnore(Arc<RwLock<IgnoreSet>>);
+
+/// The matchers behind one [`LiveIgnore`]: the superproject's, plus one per
+/// initialized submodule.
+///
+/// A submodule is its own repository, and git applies *its* `.gitignore` and
+/// `info/exclude` to paths inside it — the superproject's rules stop at the
+/// gitlink. Consulting only the root matcher would wake the loop on every
+/// write into a submodule's `target/`, now that the view walks changed
+/// submodules too; each submodule therefore gets a matcher rooted at its own
+/// work tree, and a path is judged by the deepest one that contains it.
+struct IgnoreSet {
+    /// The superproject's matcher, rooted at its work tree.
+    root: Gitignore,
+    /// One matcher per initialized submodule, each rooted at that submodule's
+    /// work tree ([`Gitignore::path`]).
+    submodules: Vec<Gitignore>,
+}
+
+impl IgnoreSet {
+    /// Every matcher for `repo`, re-read from disk. See [`build_ignore_matcher`]
+    /// for the sources each one draws on.
+    fn build(repo: &gix::Repository) -> Self {
+        Self {
+            root: build_ignore_matcher(repo),
+            submodules: repo::submodule_checkouts(repo, |_| true)
+                .iter()
+                .map(|checkout| build_ignore_matcher(&checkout.repo))
+                .collect(),
+        }
+    }
+
+    /// The matcher whose rules govern `path`: the innermost submodule whose
+    /// work tree contains it, else the superproject's. Innermost rather than
+    /// first so the answer does not depend on the order submodules were
+    /// declared in.
+    fn governing(&self, path: &Path) -> &Gitignore {
+        self.submodules
+            .iter()
+            .filter(|matcher| path.starts_with(matcher.path()))
+            .max_by_key(|matcher| matcher.path().components().count())
+            .unwrap_or(&self.root)
+    }
+}
 
 impl LiveIgnore {
     /// Build the matcher from the repository's ignore sources as they are on
     /// disk right now. See [`build_ignore_matcher`] for which sources those are.
     pub(crate) fn new(repo: &gix::Repository) -> Self {
-        Self(Arc::new(RwLock::new(build_ignore_matcher(repo))))
+        Self(Arc::new(RwLock::new(IgnoreSet::build(repo))))
     }
 
     /// Re-read the repository's ignore sources so a rule added or removed since
@@ -152,14 +200,15 @@ impl LiveIgnore {
     /// restart.
     ///
     /// Called once per git walk, unconditionally. That is deliberate: rebuilding
-    /// reads at most three small files and recompiles a handful of globs, which
-    /// is negligible against the status traversal it rides along with — and
+    /// reads at most three small files per repository (the superproject and each
+    /// initialized submodule) and recompiles a handful of globs, which is
+    /// negligible against the status traversal it rides along with — and
     /// watch-mode walks are already gated to a ~1% duty cycle by [`WalkSchedule`], so
     /// the rebuild rate is bounded by the same budget. Do **not** "optimize" this
     /// into a build-once cache or an mtime check: building it exactly once is the
     /// staleness this method exists to fix.
     pub(crate) fn refresh(&self, repo: &gix::Repository) {
-        *self.write() = build_ignore_matcher(repo);
+        *self.write() = IgnoreSet::build(repo);
     }
 
     /// Whether the ignore set claims `path` — directly, or via a rule on any of
@@ -168,6 +217,7 @@ impl LiveIgnore {
     ///
     /// `is_dir` tells the matcher whether `path` itself is a directory, which
     /// decides whether directory-only rules (`build/`) can match it directly.
+    /// A path inside a submodule is judged by that submodule's own rules.
     ///
     /// # Panics
     ///
@@ -177,26 +227,27 @@ impl LiveIgnore {
     /// worktree paths before reaching here.
     fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
         self.read()
+            .governing(path)
             .matched_path_or_any_parents(path, is_dir)
             .is_ignore()
     }
 
     /// The read side of the shared matcher, recovering from lock poisoning.
     ///
-    /// A [`Gitignore`] is an immutable compiled glob set with no cross-field
-    /// invariant a panic could leave half-written: whatever is behind the lock is
-    /// always a complete matcher. Propagating poisoning instead would let an
+    /// An [`IgnoreSet`] is a handful of immutable compiled glob sets with no
+    /// cross-field invariant a panic could leave half-written: whatever is behind
+    /// the lock is always a complete matcher. Propagating poisoning instead would let an
     /// unrelated thread's panic wedge the monitor permanently — every subsequent
     /// event unwrapping on a poisoned lock — which is strictly worse than reading
     /// a perfectly valid matcher, so recover the inner value.
-    fn read(&self) -> RwLockReadGuard<'_, Gitignore> {
+    fn read(&self) -> RwLockReadGuard<'_, IgnoreSet> {
         self.0.read().unwrap_or_else(PoisonError::into_inner)
     }
 
     /// The write side of the shared matcher, recovering from lock poisoning for
     /// the same reason as [`read`](Self::read) — and with even less at stake
     /// here, since the write replaces the matcher wholesale.
-    fn write(&self) -> RwLockWriteGuard<'_, Gitignore> {
+    fn write(&self) -> RwLockWriteGuard<'_, IgnoreSet> {
         self.0.write().unwrap_or_else(PoisonError::into_inner)
     }
 }
@@ -222,7 +273,10 @@ impl From<Gitignore> for LiveIgnore {
     /// the pure [`should_react`] tests use to hand in a matcher assembled from
     /// raw gitignore lines instead of from a repository on disk.
     fn from(matcher: Gitignore) -> Self {
-        Self(Arc::new(RwLock::new(matcher)))
+        Self(Arc::new(RwLock::new(IgnoreSet {
+            root: matcher,
+            submodules: Vec::new(),
+        })))
     }
 }
 
@@ -303,14 +357,15 @@ fn should_repaint(new: &str, displayed: &str) -> bool {
 /// changes with the passage of time).
 ///
 /// The cadence mirrors the [`crate::age`] fade model — a linear ramp from age 0
-/// to [`FADE_DARKEST_AT`] (2 h), then frozen at the floor — so the timer stops
-/// ticking exactly when the fade stops moving:
+/// to `darkest_at` ([`FADE_DARKEST_AT`], 2 h, unless a config file moved it),
+/// then frozen at the floor — so the timer stops ticking exactly when the fade
+/// stops moving:
 ///
 /// | Freshest item age | Tick interval | Why |
 /// | --- | --- | --- |
 /// | `< 1 min` | 1 s | live seconds in the age text; fade moving fast |
-/// | `1 min – 2 h` | 60 s | minute text ticks over; fade moves ~1 RGB unit/min |
-/// | `≥ 2 h` | `None` | fade frozen at the floor — FS events only, idle ≈ 0 |
+/// | `1 min – darkest_at` | 60 s | minute text ticks over; fade moves ~1 RGB unit/min |
+/// | `≥ darkest_at` | `None` | fade frozen at the floor — FS events only, idle ≈ 0 |
 ///
 /// This is only one of the loop's deadline sources, and the least demanding of
 /// them: while a refresh countdown is on screen, [`CLOCK_CADENCE`] wakes the
@@ -319,10 +374,10 @@ fn should_repaint(new: &str, displayed: &str) -> bool {
 /// when `--refresh-interval 0` takes the countdown away too.
 ///
 /// [`FADE_DARKEST_AT`]: crate::age::FADE_DARKEST_AT
-pub(crate) fn next_tick(freshest_age: Duration) -> Option<Duration> {
+pub(crate) fn next_tick(freshest_age: Duration, darkest_at: Duration) -> Option<Duration> {
     if freshest_age < Duration::from_secs(60) {
         Some(Duration::from_secs(1))
-    } else if freshest_age < crate::age::FADE_DARKEST_AT {
+    } else if freshest_age < darkest_at {
         Some(Duration::from_secs(60))
     } else {
         None
@@ -581,11 +636,12 @@ fn wait_window(deadlines: &[Option<Duration>]) -> Option<Duration> {
 /// recomputed after every render with no extra thread to reconfigure (see
 /// [`event_loop`] and [`next_tick`]).
 enum Event {
-    /// A non-ignored filesystem path under the worktree or git dir changed.
-    /// The path was already classified by [`should_react`] before the event
-    /// was sent, so the loop only needs to know that *something* relevant
-    /// moved — it recomputes the whole render regardless of which path it was.
-    FsChanged,
+    /// A non-ignored filesystem path under the worktree or git dir of the
+    /// [`WatchRoot`] with this id changed. The path was already classified by
+    /// [`should_react`] before the event was sent, so the loop only needs to
+    /// know *which root* moved — the next walk re-reads that repository, and
+    /// only that one, whichever of its paths it was.
+    FsChanged(usize),
     /// The terminal was resized — repaint at the new dimensions.
     Resize,
     /// A key was pressed. Deliberately **unclassified**: what a key means
@@ -604,12 +660,30 @@ enum Event {
     /// The user asked to push (`p`) — show the confirmation, or say why there
     /// is nothing to confirm.
     PushRequested,
-    /// The user confirmed the push at the prompt (`y` or Enter).
+    /// The user confirmed the question at the prompt (`y` or Enter) — a push,
+    /// or a discard.
     PushConfirmed,
-    /// The user declined the push at the prompt (`n`, Esc, or `q`).
+    /// The user declined the question at the prompt (`n`, Esc, or `q`).
     PushCancelled,
-    /// A push that was running has finished, either way.
-    PushFinished(crate::push::PushOutcome),
+    /// A job that was running — a push, a file action, or a fetch or
+    /// fast-forward — has finished, either way.
+    JobFinished(crate::push::GitOutcome),
+    /// The user moved the cursor over the file rows, or scrolled the open diff.
+    Move(Motion),
+    /// The user dropped the cursor (Esc with no question up).
+    Deselect,
+    /// The user asked to stage, unstage, or discard the row under the cursor.
+    File(FileAction),
+    /// The user asked to move the row under the cursor to the other side of
+    /// the index (Space): stage an unstaged row, unstage a staged one.
+    ToggleStaged,
+    /// The user asked to fetch (`f`) or to fast-forward the current branch
+    /// (`u`).
+    Sync(SyncAction),
+    /// The user asked to see the diff of the row under the cursor (Enter).
+    OpenDiff,
+    /// The user closed the diff (Esc, `q`, or Enter again).
+    CloseDiff,
     /// A key press with no other meaning. Clears a status message if one is on
     /// screen and does nothing otherwise, which is what keeps a push error up
     /// until the user has actually looked at the screen.
@@ -626,12 +700,75 @@ enum Event {
 pub(crate) enum InputMode {
     /// Nothing is being asked. The monitor's ordinary keys apply.
     Normal,
-    /// A push confirmation is on screen and is waiting for an answer.
+    /// A question — confirm a push, or a discard — is on screen and is
+    /// waiting for an answer.
     Confirm,
-    /// A push is running. `p` is inert here, so two pushes cannot overlap.
+    /// A push — or a file action — is running. `p` and the file-action keys
+    /// are inert here, so two jobs cannot overlap.
     Pushing,
+    /// A diff has taken over the pane. The motion keys scroll it, and Esc, `q`,
+    /// and Enter go back to the frame.
+    Diff,
+}
+
+/// What keys mean right now, given everything on screen: a question or a job in
+/// flight decides first, and only then does an open diff take the motion keys.
+///
+/// The question outranks the diff because it is the thing the user must
+/// answer; a job in flight does not, because nothing about it needs a key —
+/// so a diff opened while a push runs scrolls like any other.
+fn input_mode(ui: &PushUi, browser: &Browser) -> InputMode {
+    match ui.mode() {
+        InputMode::Normal | InputMode::Pushing if browser.diff_open().is_some() => InputMode::Diff,
+        mode => mode,
+    }
 }
 
+/// What the watch loop caches between walks and re-renders on every tick.
+///
+/// The loop itself never looks inside the state — collecting and rendering it
+/// are hooks — with one exception: the keys that act on a repository have to
+/// know *which* one. `p` plans its push from it, and the file-action keys look
+/// up the row under the cursor in it. A single repository answers with its own
+/// [`Snapshot`]; the multi-repository dashboard answers `None`, because a push
+/// or a discard aimed at whichever block happens to be first is one the user
+/// did not choose.
+pub(crate) trait Watched {
+    /// The snapshot the keys act on, or `None` when this view has no single
+    /// repository to act on.
+    fn target(&self) -> Option<&Snapshot>;
+}
+
+impl Watched for Snapshot {
+    fn target(&self) -> Option<&Snapshot> {
+        Some(self)
+    }
+}
+
+/// What `p` says on a view that watches several repositories at once.
+///
+/// A refusal rather than silence so the key the footer of every other gsw
+/// session advertises does not look broken here, and worded as advice because
+/// the fix — a gsw inside the one repository — is always available.
+const MULTI_REPO_PUSH_REFUSAL: &str =
+    "p pushes a single repository — run gsw inside the one you want to push";
+
+/// What a file-action key says with no row under the cursor. The cursor only
+/// appears once it is moved, so the first `s` of a session lands here, and
+/// the answer is the key that fixes it.
+const NO_SELECTION: &str = "select a file with j/k first";
+
+/// What a file-action key says on the dashboard, which has no single
+/// repository to act in — the same answer `p` gets there, for the same reason.
+const MULTI_REPO_FILE_REFUSAL: &str =
+    "file actions work in a single repository — run gsw inside the one you want to change";
+
+/// What `f` and `u` say on the dashboard. One fetch per pane would be a burst
+/// of network round trips behind a single key, and a fast-forward asks about
+/// one branch — so both stay single-repository, like `p`.
+const MULTI_REPO_SYNC_REFUSAL: &str =
+    "f and u work in a single repository — run gsw inside the one you want to update";
+
 /// The git work one watch-mode refresh performs: re-open the repository so
 /// configuration written since the last refresh takes effect, rebuild the
 /// watcher's ignore matcher from that fresh handle, then collect the snapshot.
@@ -705,7 +842,7 @@ pub(crate) fn walk(
 /// re-opening it. Borrowing instead would make the caller hold a mutable borrow
 /// across a call that never returns until the user quits, for no gain — nothing
 /// is left for it to do with the handle afterward.
-pub(crate) fn run(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
+pub(crate) fn run(handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
     let _guard = TerminalGuard::enter()?;
 
     // Seed the cache with one git walk and paint the first frame at offset 0,
@@ -719,9 +856,238 @@ pub(crate) fn run(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
     // that same just-opened handle — so skipping `walk`'s rebuild costs nothing
     // either. Every *subsequent* refresh goes through `walk`, which re-opens the
     // handle and rebuilds the matcher.
-    let dims = current_dimensions(cfg.width_offset);
     let collected_at = Instant::now();
     let snapshot = collect_snapshot(handle.repo(), cfg)?;
+
+    // A push runs `git` with the work tree as its cwd, so the path is captured
+    // before the handle is borrowed for the rest of watch mode.
+    let workdir = handle.repo().workdir().map(Path::to_path_buf);
+
+    // The one ignore matcher both threads share: the watcher callback reads it
+    // per event, and every `walk` below rebuilds it from disk so a `.gitignore`
+    // edited in another pane takes effect without a restart.
+    let ignore = LiveIgnore::new(handle.repo());
+    // Built before the collect closure below takes its mutable borrow of the
+    // handle; the root clones everything it needs, so this borrow ends here.
+    let roots = WatchRoot::of(handle.repo(), &ignore, 0)
+        .into_iter()
+        .collect();
+
+    // The walk re-opens the handle and the diff reads through it, so the two
+    // hooks share it. Both run on the loop's thread, one after the other, so
+    // the cell is never borrowed twice.
+    let handle = std::cell::RefCell::new(handle);
+
+    session(
+        cfg,
+        Seed {
+            state: snapshot,
+            collected_at,
+        },
+        roots,
+        workdir,
+        |_changed: &Changed| walk(&mut handle.borrow_mut(), &ignore, cfg),
+        |snap: &Snapshot, dims: Dimensions, timing: FrameTiming, cursor: Option<&Cursor>| {
+            render_frame_with_cursor(snap, cfg, dims, timing, cursor)
+        },
+        |entry: &RenderEntry| crate::repo::file_diff(handle.borrow().repo(), entry),
+    )
+}
+
+/// Stream the repository as NDJSON until interrupted: one line for the state it
+/// is in now, then one more every time a walk finds it different
+/// (`--format ndjson`).
+///
+/// The walks are watch mode's — the same filesystem watcher, timed refresh,
+/// and duty-cycle throttle — so a status line fed from this costs what a gsw
+/// left open in a pane costs, and learns about a change as quickly. What it
+/// does not do is take the terminal over: there is no alternate screen, no raw
+/// mode, and no key reader, because the reader on the other end of stdout is a
+/// program, and stdout is often a pipe.
+///
+/// # Errors
+///
+/// Fails when the first walk does, or when the filesystem watcher cannot start.
+/// A walk that fails later is absorbed exactly as watch mode absorbs it: no line
+/// is written for it, and the next one retries.
+pub(crate) fn stream(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
+    // The seed skips `walk` for the reason `run` gives: the handle is fresh.
+    let collected_at = Instant::now();
+    let snapshot = collect_snapshot(handle.repo(), cfg)?;
+    let ignore = LiveIgnore::new(handle.repo());
+    let roots = WatchRoot::of(handle.repo(), &ignore, 0)
+        .into_iter()
+        .collect();
+    stream_session(
+        cfg,
+        Seed {
+            state: snapshot,
+            collected_at,
+        },
+        roots,
+        |_changed: &Changed| walk(&mut handle, &ignore, cfg),
+        |snapshot: &Snapshot| crate::json::snapshot(snapshot, false),
+    )
+}
+
+/// Everything a stream does once its seed is in hand: write the first line,
+/// start the filesystem watcher, and hand the rest of the process to
+/// [`event_loop`] — the loop watch mode runs, with the frame replaced by one
+/// JSON line and the screen by stdout.
+///
+/// The loop's own suppression is what makes this "a line per change" rather
+/// than a line per wake: the line is built from the snapshot alone, never aged
+/// forward, so the clock and decay ticks that re-render an unchanged snapshot
+/// build the identical line and write nothing. A walk that read the same state
+/// as the last one writes nothing either.
+///
+/// A reader that hangs up ends the stream quietly rather than as an error, so
+/// `gsw --format ndjson | head -1` exits the way `head` expects.
+pub(crate) fn stream_session<S, Collect, Line>(
+    cfg: &RenderConfig,
+    seed: Seed<S>,
+    roots: Vec<WatchRoot>,
+    collect: Collect,
+    mut line: Line,
+) -> Result<()>
+where
+    S: Watched,
+    Collect: FnMut(&Changed) -> Result<S>,
+    Line: FnMut(&S) -> String,
+{
+    let Seed {
+        state,
+        collected_at,
+    } = seed;
+    let schedule = WalkSchedule::new(
+        cfg.refresh_interval,
+        collected_at,
+        Instant::now().saturating_duration_since(collected_at),
+    );
+    let mut displayed = line(&state);
+    if write_line(&displayed)? == Written::HungUp {
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let hang_up = tx.clone();
+    let _watcher = spawn_fs_watcher(roots, tx)?;
+
+    // Nothing is laid out, so any size will do; the idle push overlay the loop
+    // consults takes no rows from it.
+    let dims = Dimensions {
+        width: DEFAULT_TERMINAL_WIDTH,
+        height: DEFAULT_TERMINAL_HEIGHT,
+    };
+    event_loop(
+        &rx,
+        DEBOUNCE,
+        &mut displayed,
+        LoopStart {
+            cache: SnapshotCache {
+                snapshot: state,
+                collected_at,
+                dims,
+            },
+            freshest: None,
+            schedule,
+            ui: PushUi::new(false),
+            rows: Vec::new(),
+        },
+        LoopHooks {
+            collect,
+            render: |state: &S,
+                     _dims: Dimensions,
+                     _timing: FrameTiming,
+                     _cursor: Option<&Cursor>| {
+                Render {
+                    output: line(state),
+                    freshest_age: None,
+                    rows: Vec::new(),
+                }
+            },
+            dimensions: move || dims,
+            paint: |output: &str| {
+                if write_line(output)? == Written::HungUp {
+                    let _ = hang_up.send(Event::Quit);
+                }
+                Ok(())
+            },
+            clock: Instant::now,
+            // Ages are never advanced between walks, so there is nothing for a
+            // decay tick to redraw.
+            next_tick: |_freshest: Option<Duration>| None,
+            run_job: |_job: Job| {},
+            diff: |_entry: &RenderEntry| Ok(Vec::new()),
+        },
+    )
+}
+
+/// Whether a line written to stdout reached a reader.
+#[derive(Debug, PartialEq, Eq)]
+enum Written {
+    /// The line went out.
+    Delivered,
+    /// The reader closed the pipe.
+    HungUp,
+}
+
+/// Write one record and flush it, so a reader sees each line the moment it is
+/// written rather than when a buffer fills.
+fn write_line(line: &str) -> Result<Written> {
+    let mut out = io::stdout().lock();
+    match writeln!(out, "{line}").and_then(|()| out.flush()) {
+        Ok(()) => Ok(Written::Delivered),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(Written::HungUp),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The state a watch session starts from: the first collection, and the instant
+/// it began. Collected by the caller, after it has taken over the terminal,
+/// because only the caller knows whether the seed needs a re-open (see
+/// [`run`]).
+pub(crate) struct Seed<S> {
+    /// The first collected state, painted as the opening frame.
+    pub(crate) state: S,
+    /// When the collection behind `state` started.
+    pub(crate) collected_at: Instant,
+}
+
+/// Everything a watch session does once its seed is in hand: paint the first
+/// frame, start the key reader and the filesystem watcher, and hand the rest of
+/// the process to [`event_loop`].
+///
+/// Shared by the single-repository [`run`] and the multi-repository dashboard,
+/// which differ only in what they collect and how they render it — the seed,
+/// the walk, and the render are the caller's, while the schedule, the channel,
+/// the watcher, and the push wiring are the same for both. `workdir` is where a
+/// confirmed push runs; a view that never confirms one (the dashboard refuses
+/// `p` outright, see [`Watched`]) passes `None`.
+///
+/// The caller must already hold the [`TerminalGuard`]: the seed walk it ran is
+/// what the opening frame is anchored to, and that walk belongs inside the
+/// alternate screen so a slow first walk is not spent on the user's shell.
+pub(crate) fn session<S, Collect, RenderFn, DiffFn>(
+    cfg: &RenderConfig,
+    seed: Seed<S>,
+    roots: Vec<WatchRoot>,
+    workdir: Option<PathBuf>,
+    collect: Collect,
+    mut render: RenderFn,
+    diff: DiffFn,
+) -> Result<()>
+where
+    S: Watched,
+    Collect: FnMut(&Changed) -> Result<S>,
+    RenderFn: FnMut(&S, Dimensions, FrameTiming, Option<&Cursor>) -> Render,
+    DiffFn: FnMut(&RenderEntry) -> Result<Vec<String>>,
+{
+    let Seed {
+        state,
+        collected_at,
+    } = seed;
+    let dims = current_dimensions(cfg.width_offset);
     // The seed walk pays into the duty-cycle budget like every walk after it,
     // so its cost is what the schedule's first timed walk is gated on. The seed
     // frame then counts down to that same schedule rather than to the raw
@@ -732,18 +1098,21 @@ pub(crate) fn run(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
         collected_at,
         Instant::now().saturating_duration_since(collected_at),
     );
-    let first = render_frame(
-        &snapshot,
-        cfg,
+    let first = render(
+        &state,
         dims,
         timing(Duration::ZERO, &schedule, collected_at),
+        None,
     );
     paint_output(&first.output)?;
-    let mut displayed = first.output;
-    let initial_freshest = first.freshest_age;
+    let Render {
+        output: mut displayed,
+        freshest_age: initial_freshest,
+        rows,
+    } = first;
 
     let cache = SnapshotCache {
-        snapshot,
+        snapshot: state,
         collected_at,
         dims,
     };
@@ -751,22 +1120,13 @@ pub(crate) fn run(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
     let (tx, rx) = mpsc::channel();
     spawn_event_reader(tx.clone());
 
-    // A push runs `git` with the work tree as its cwd, so the path is captured
-    // before the handle is borrowed for the rest of watch mode. The push thread
-    // reports back on the loop's own channel, so its outcome re-enters the loop
-    // exactly like a filesystem event — applied between frames, never during one.
-    let workdir = handle.repo().workdir().map(Path::to_path_buf);
-    let push_tx = tx.clone();
-
-    // The one ignore matcher both threads share: the watcher callback reads it
-    // per event, and every `walk` below rebuilds it from disk so a `.gitignore`
-    // edited in another pane takes effect without a restart.
-    let ignore = LiveIgnore::new(handle.repo());
+    // The job thread — a push or a file action — reports back on the loop's
+    // own channel, so its outcome re-enters the loop exactly like a filesystem
+    // event — applied between frames, never during one.
+    let job_tx = tx.clone();
 
     // The filesystem watcher must outlive the loop — dropping it stops watching.
-    // Started before the collect closure below takes its mutable borrow of the
-    // handle; the watcher clones everything it needs, so this borrow ends here.
-    let _watcher = spawn_fs_watcher(handle.repo(), ignore.clone(), tx)?;
+    let _watcher = spawn_fs_watcher(roots, tx)?;
 
     event_loop(
         &rx,
@@ -777,90 +1137,165 @@ pub(crate) fn run(mut handle: RepoHandle, cfg: &RenderConfig) -> Result<()> {
             freshest: initial_freshest,
             schedule,
             ui: PushUi::new(cfg.truecolor),
+            rows,
         },
         LoopHooks {
-            collect: || walk(&mut handle, &ignore, cfg),
-            render: |snap: &Snapshot, dims: Dimensions, timing: FrameTiming| {
-                render_frame(snap, cfg, dims, timing)
-            },
+            collect,
+            render,
             dimensions: || current_dimensions(cfg.width_offset),
             paint: |output: &str| paint_output(output),
             clock: Instant::now,
-            next_tick: |freshest: Option<Duration>| freshest.and_then(next_tick),
-            start_push: |command: PushCommand| {
-                // No work tree means nothing to push from. `RepoHandle` rejects
-                // a bare repository at discovery, so watch mode never gets here
+            next_tick: |freshest: Option<Duration>| {
+                freshest.and_then(|age| next_tick(age, cfg.theme.fade.darkest_at))
+            },
+            diff,
+            run_job: |job: Job| {
+                // No work tree means nothing to push or stage from.
+                // `RepoHandle` rejects a bare repository at discovery, and the
+                // dashboard never starts a job, so watch mode never gets here
                 // without one — this is the type's `Option` being honored, not
                 // a case the user can reach.
                 if let Some(workdir) = workdir.clone() {
-                    let tx = push_tx.clone();
-                    crate::push::spawn(command, workdir, move |outcome| {
-                        let _ = tx.send(Event::PushFinished(outcome));
-                    });
+                    let tx = job_tx.clone();
+                    let report = move |outcome| {
+                        let _ = tx.send(Event::JobFinished(outcome));
+                    };
+                    match job {
+                        Job::Push(command) => crate::push::spawn(command, workdir, report),
+                        Job::File(command) => crate::action::spawn(command, workdir, report),
+                        Job::Sync(command) => crate::sync::spawn(command, workdir, report),
+                    }
                 }
             },
         },
     )
 }
 
+/// Which repositories a walk has to read again, by [`WatchRoot`] id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Changed {
+    /// All of them: the first walk, a manual refresh, a timed one, or any
+    /// other walk no filesystem change asked for.
+    All,
+    /// Only the roots the watcher saw a change under since the last walk.
+    /// Everything else is as the last walk left it.
+    Roots(BTreeSet<usize>),
+}
+
+impl Changed {
+    /// Whether the repository watched as root `id` needs reading again.
+    pub(crate) fn includes(&self, id: usize) -> bool {
+        match self {
+            Self::All => true,
+            Self::Roots(roots) => roots.contains(&id),
+        }
+    }
+}
+
+/// One repository the filesystem watcher covers: where its files live, which
+/// git dirs carry state gsw renders, and the ignore matcher its events are
+/// classified against.
+///
+/// Captured up front, by value, because the watcher callback runs on notify's
+/// thread for the rest of the process and must not borrow the handle the walk
+/// keeps re-opening.
+pub(crate) struct WatchRoot {
+    /// What [`Event::FsChanged`] calls this root: the repository's index in
+    /// whatever the caller watches, so [`Changed::Roots`] can name it back.
+    id: usize,
+    /// The work-tree root.
+    workdir: PathBuf,
+    /// The per-worktree git dir plus, for a linked worktree, the shared common
+    /// dir. Both carry state we render.
+    git_dirs: Vec<PathBuf>,
+    /// The repository's live ignore set, shared with the walk that rebuilds it.
+    ignore: LiveIgnore,
+}
+
+impl WatchRoot {
+    /// The watch root for `repo`, known to the loop as `id`, or `None` when it
+    /// has no work tree — nothing gsw renders could change.
+    pub(crate) fn of(repo: &gix::Repository, ignore: &LiveIgnore, id: usize) -> Option<Self> {
+        let workdir = repo.workdir()?.to_path_buf();
+        // `git_dir()` is the per-worktree dir; `common_dir()` is the shared
+        // store (they're equal for a normal repo).
+        let mut git_dirs = vec![repo.git_dir().to_path_buf()];
+        let common = repo.common_dir().to_path_buf();
+        if !git_dirs.contains(&common) {
+            git_dirs.push(common);
+        }
+        Some(Self {
+            id,
+            workdir,
+            git_dirs,
+            ignore: ignore.clone(),
+        })
+    }
+
+    /// Whether a change at `path` is one this repository's frame could show.
+    fn reacts_to(&self, path: &Path) -> bool {
+        should_react(path, &self.ignore, &self.workdir, &self.git_dirs)
+    }
+}
+
 /// Start the recursive filesystem watcher that feeds [`Event::FsChanged`] into
 /// the loop. Returns the live watcher, which the caller must keep in scope: a
 /// dropped watcher stops delivering events.
 ///
-/// The watcher covers the worktree root and — for a linked worktree, whose
-/// `.git` lives outside the worktree — the git dir and shared common dir too,
-/// so commits (which write only under those) still register. Every event path
-/// is run through [`should_react`] *before* a wake-up is sent, so ignored
-/// build churn (`target/`, `node_modules/`) never even reaches the channel.
+/// One watcher covers every root, so a dashboard over a dozen repositories
+/// costs one notify instance rather than a dozen. Each root contributes its
+/// worktree and — for a linked worktree, whose `.git` lives outside the
+/// worktree — the git dir and shared common dir too, so commits (which write
+/// only under those) still register. Every event path is run through
+/// [`should_react`] against the root it falls under *before* a wake-up is
+/// sent, so ignored build churn (`target/`, `node_modules/`) never even
+/// reaches the channel.
 ///
-/// `ignore` is the caller's [`LiveIgnore`], not one built here: the callback
-/// thread only ever *reads* the matcher, while the render loop rebuilds it on
-/// every walk. Owning it here would pin the ignore set to whatever was on disk
-/// at spawn — precisely the staleness [`LiveIgnore`] exists to prevent.
+/// Each root's `ignore` is the caller's [`LiveIgnore`], not one built here: the
+/// callback thread only ever *reads* the matcher, while the render loop
+/// rebuilds it on every walk. Owning it here would pin the ignore set to
+/// whatever was on disk at spawn — precisely the staleness [`LiveIgnore`]
+/// exists to prevent.
 fn spawn_fs_watcher(
-    repo: &gix::Repository,
-    ignore: LiveIgnore,
+    roots: Vec<WatchRoot>,
     tx: Sender<Event>,
 ) -> Result<Option<RecommendedWatcher>> {
-    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+    if roots.is_empty() {
         return Ok(None);
-    };
+    }
 
-    // `git_dir()` is the per-worktree dir; `common_dir()` is the shared store
-    // (they're equal for a normal repo). Both carry state we render.
-    let mut git_dirs = vec![repo.git_dir().to_path_buf()];
-    let common = repo.common_dir().to_path_buf();
-    if !git_dirs.contains(&common) {
-        git_dirs.push(common);
+    // What gets registered with notify, computed before the roots move into
+    // the callback.
+    let mut watched: Vec<PathBuf> = Vec::new();
+    for root in &roots {
+        watched.push(root.workdir.clone());
+        // A normal repo's `.git` is covered by the recursive worktree watch; a
+        // linked worktree's dirs are not.
+        for git_dir in &root.git_dirs {
+            if !git_dir.starts_with(&root.workdir) && !watched.contains(git_dir) {
+                watched.push(git_dir.clone());
+            }
+        }
     }
 
-    let filter_workdir = workdir.clone();
-    let filter_git_dirs = git_dirs.clone();
     let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
         let Ok(event) = res else {
             return;
         };
-        // One wake-up per relevant event; the loop coalesces bursts anyway, so
-        // there's no value in sending once per path. A send error means the
-        // receiver is gone (loop ended) — nothing left to do.
-        let relevant = event
-            .paths
-            .iter()
-            .any(|path| should_react(path, &ignore, &filter_workdir, &filter_git_dirs));
-        if relevant {
-            let _ = tx.send(Event::FsChanged);
+        // One wake-up per root the event touched; the loop coalesces bursts
+        // anyway, so there's no value in sending once per path. A send error
+        // means the receiver is gone (loop ended) — nothing left to do.
+        for root in &roots {
+            if event.paths.iter().any(|path| root.reacts_to(path)) {
+                let _ = tx.send(Event::FsChanged(root.id));
+            }
         }
     })?;
 
-    // Watch the worktree, plus any git dir that isn't already inside it (a
-    // normal repo's `.git` is covered by the recursive worktree watch; a linked
-    // worktree's dirs are not). A failed watch on one root is non-fatal — the
-    // others still drive refreshes.
-    let _ = watcher.watch(&workdir, RecursiveMode::Recursive);
-    for git_dir in &git_dirs {
-        if !git_dir.starts_with(&workdir) {
-            let _ = watcher.watch(git_dir, RecursiveMode::Recursive);
-        }
+    // A failed watch on one root is non-fatal — the others still drive
+    // refreshes.
+    for path in &watched {
+        let _ = watcher.watch(path, RecursiveMode::Recursive);
     }
 
     Ok(Some(watcher))
@@ -920,9 +1355,12 @@ fn global_excludes_path(repo: &gix::Repository) -> Option<PathBuf> {
 /// re-render it without re-walking git. A decay tick or resize repaints from
 /// this cache, advancing every displayed age by `now - collected_at`; only a
 /// filesystem change re-collects and re-seeds it.
-struct SnapshotCache {
+///
+/// Generic over the [`Watched`] state so the multi-repository dashboard rides
+/// the same cache; the default is the single repository's [`Snapshot`].
+struct SnapshotCache<S = Snapshot> {
     /// The most recently collected repository state.
-    snapshot: Snapshot,
+    snapshot: S,
     /// When `snapshot` was collected, against the loop's injected clock. The age
     /// offset for a no-git re-render is `clock() - collected_at`.
     collected_at: Instant,
@@ -941,9 +1379,9 @@ struct SnapshotCache {
 /// to the snapshot it collected, the freshest age to the frame that snapshot
 /// rendered, the schedule to the cost that walk measured, and the push UI to
 /// the terminal's color depth, which is resolved from the CLI and nowhere else.
-struct LoopStart {
+struct LoopStart<S = Snapshot> {
     /// The snapshot a re-render can use without walking git again.
-    cache: SnapshotCache,
+    cache: SnapshotCache<S>,
     /// The freshest displayed age of the frame already painted, which seeds
     /// the decay-tick cadence for the loop's first wait.
     freshest: Option<Duration>,
@@ -954,6 +1392,10 @@ struct LoopStart {
     /// loop is the only place that knows what is displayed — see
     /// [`Event::Key`] for why the reader thread must not.
     ui: PushUi,
+    /// The file rows the frame already painted drew, so the very first `j`
+    /// has rows to land on — the loop renders nothing of its own until that key
+    /// has been absorbed.
+    rows: Vec<RowKey>,
 }
 
 /// The side-effecting hooks the watch loop drives, bundled so the loop stays one
@@ -961,10 +1403,14 @@ struct LoopStart {
 /// these to the real git collect, render, terminal-size query, painter, and
 /// clock; tests inject counters and a controllable clock to assert which hooks
 /// ran — and with what age offset — without a TTY or real time.
-struct LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, StartPush> {
-    /// Walk the repo into a fresh [`Snapshot`] (the expensive git work).
+struct LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, RunJob, DiffFn> {
+    /// Walk the repo into a fresh [`Watched`] state — a [`Snapshot`], or one
+    /// per repository on the dashboard (the expensive git work). The
+    /// [`Changed`] says which repositories need reading again; a single
+    /// repository has only the one and walks it regardless.
     collect: Collect,
-    /// Render a snapshot at the given dimensions and timing.
+    /// Render a snapshot at the given dimensions and timing, with the cursor
+    /// (if any) highlighting its row.
     render: RenderFn,
     /// Query the current terminal dimensions (re-evaluated on resize).
     dimensions: Dims,
@@ -974,12 +1420,16 @@ struct LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, StartPush> {
     clock: Clock,
     /// Map the freshest displayed age to the decay-tick interval (`None` = off).
     next_tick: Tick,
-    /// Start a confirmed push, given the [`PushCommand`] the confirmation
-    /// described — the `git` arguments and the branch they were written for.
-    /// Production spawns a thread that runs the push and sends the outcome back
-    /// as [`Event::PushFinished`]; tests record the command and decide for
-    /// themselves when — or whether — the outcome arrives.
-    start_push: StartPush,
+    /// Start a [`Job`] the user asked for: a confirmed push, given the
+    /// [`PushCommand`](crate::push::PushCommand) the confirmation described, or
+    /// a file action. Production spawns a thread that runs `git` and sends the
+    /// outcome back as [`Event::JobFinished`]; tests record the job and decide
+    /// for themselves when — or whether — the outcome arrives.
+    run_job: RunJob,
+    /// Read the diff of one file row, as the lines the diff view scrolls
+    /// through. Called on the loop's thread, when a diff opens and after every
+    /// successful walk while one is open.
+    diff: DiffFn,
 }
 
 /// The triggers one wake collected, before the render decides what to do with
@@ -987,12 +1437,15 @@ struct LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, StartPush> {
 /// forced by `r` and a resize can arrive together.
 #[derive(Default)]
 struct Pending {
-    /// A relevant filesystem path changed.
-    fs: bool,
+    /// The roots under which a relevant filesystem path changed.
+    fs: BTreeSet<usize>,
     /// The terminal was resized.
     resize: bool,
     /// A walk was demanded outright, bypassing the cooldown.
     force: bool,
+    /// A diff was opened and still needs its text. Read after the drain
+    /// rather than inside it, so a burst pays for one read at most.
+    open_diff: bool,
 }
 
 /// Whether the loop keeps running after an event.
@@ -1020,58 +1473,136 @@ enum Flow {
 /// A key arrives unclassified and is resolved here against `ui`'s *current*
 /// mode, which is what makes a burst read correctly: within one drain, the `p`
 /// ahead of a `y` has already switched the mode by the time the `y` is looked
-/// at. It then recurses exactly once — [`classify_input`] never returns
-/// [`Event::Key`], so there is no second hop.
+/// at. It then recurses — [`classify_input`] never returns [`Event::Key`], and
+/// the one event that re-enters by itself, [`Event::ToggleStaged`], resolves to
+/// an [`Event::File`], so the depth is bounded at two hops.
+///
+/// The cursor is resolved the same way, against the rows the last render drew:
+/// a file key looks its row up in `cache` — the snapshot that render was drawn
+/// from — so the file it acts on is the file that was highlighted, even when a
+/// walk is about to reorder the list.
 ///
 /// The exception worth naming is a `p` that switches nothing. Nothing renders
 /// between two keys of one drain, so a rule the render path applies is applied
-/// after the second key has already been classified — which is why `dims` is
-/// threaded down to [`PushUi::request`]: a pane with no row to draw the
-/// question in raises no question, the mode does not move, and the `y` or Enter
-/// behind that `p` is read as the ordinary key it is. `dims` is the pane the
-/// last render measured, which is the pane the user was looking at when they
-/// pressed the key — the loop re-measures after this drain, not during it.
-fn absorb<Clock, StartPush>(
+/// after the second key has already been classified — which is why the cached
+/// dimensions are threaded down to [`PushUi::request`]: a pane with no row to
+/// draw the question in raises no question, the mode does not move, and the
+/// `y` or Enter behind that `p` is read as the ordinary key it is. `cache.dims`
+/// is the pane the last render measured, which is the pane the user was looking
+/// at when they pressed the key — the loop re-measures after this drain, not
+/// during it. A discard's question is raised under the same rule.
+fn absorb<S, Clock, RunJob>(
     event: Event,
     pending: &mut Pending,
     ui: &mut PushUi,
-    snapshot: &Snapshot,
-    dims: Dimensions,
+    browser: &mut Browser,
+    cache: &SnapshotCache<S>,
     clock: &Clock,
-    start_push: &mut StartPush,
+    run_job: &mut RunJob,
 ) -> Flow
 where
+    S: Watched,
     Clock: Fn() -> Instant,
-    StartPush: FnMut(PushCommand),
+    RunJob: FnMut(Job),
 {
     match event {
         Event::Quit => return Flow::Quit,
-        Event::FsChanged => pending.fs = true,
+        Event::FsChanged(root) => {
+            pending.fs.insert(root);
+        }
         Event::Resize => pending.resize = true,
         Event::ForceRefresh => pending.force = true,
         Event::Key(key) => {
-            if let Some(action) = classify_input(key, ui.mode()) {
-                return absorb(action, pending, ui, snapshot, dims, clock, start_push);
+            if let Some(action) = classify_input(key, input_mode(ui, browser)) {
+                return absorb(action, pending, ui, browser, cache, clock, run_job);
             }
         }
-        Event::PushRequested => ui.request(snapshot, dims, clock()),
-        // `confirm` yields the command only once, so a second `y` that raced
-        // the mode change starts nothing.
+        // A view with no single repository to push answers `p` with a refusal
+        // rather than a question, so the key is never silently dead.
+        Event::PushRequested => match cache.snapshot.target() {
+            Some(snapshot) => ui.request(snapshot, cache.dims, clock()),
+            None => ui.refuse(MULTI_REPO_PUSH_REFUSAL, clock()),
+        },
+        // `confirm` yields the job only once, so a second `y` that raced the
+        // mode change starts nothing.
         Event::PushConfirmed => {
-            if let Some(command) = ui.confirm() {
-                start_push(command);
+            if let Some(job) = ui.confirm() {
+                run_job(job);
             }
         }
         Event::PushCancelled => ui.cancel(),
         Event::Dismiss => ui.dismiss(),
-        Event::PushFinished(outcome) => {
+        // Moving and deselecting are presses like any other as far as a status
+        // message goes: the user has looked at the screen, so it can go.
+        Event::Move(motion) => {
+            ui.dismiss();
+            browser.motion(motion);
+        }
+        Event::Deselect => {
+            ui.dismiss();
+            browser.clear();
+        }
+        Event::File(action) => match selected_entry(browser, &cache.snapshot) {
+            Ok(entry) => {
+                let prompt = crate::action::prompt_for(action, entry);
+                if let Some(job) = ui.act(prompt, cache.dims, clock()) {
+                    run_job(job);
+                }
+            }
+            Err(refusal) => ui.refuse(refusal, clock()),
+        },
+        Event::Sync(action) => match cache.snapshot.target() {
+            Some(snapshot) => {
+                let prompt = crate::sync::prompt_for(
+                    action,
+                    &snapshot.branch,
+                    snapshot.push_remote.as_deref(),
+                    snapshot.upstream.as_ref(),
+                );
+                if let Some(job) = ui.sync(prompt, cache.dims, clock()) {
+                    run_job(job);
+                }
+            }
+            None => ui.refuse(MULTI_REPO_SYNC_REFUSAL, clock()),
+        },
+        Event::ToggleStaged => {
+            let action = match selected_entry(browser, &cache.snapshot) {
+                Ok(entry) if entry.staged => FileAction::Unstage,
+                _ => FileAction::Stage,
+            };
+            return absorb(
+                Event::File(action),
+                pending,
+                ui,
+                browser,
+                cache,
+                clock,
+                run_job,
+            );
+        }
+        // The view opens here, empty, so the rest of this burst already reads
+        // keys as the diff's — an Enter-`q` typed faster than the debounce
+        // closes the diff rather than quitting. The text is read after the
+        // drain, once, of whichever row the burst ended up opening.
+        Event::OpenDiff => match selected_entry(browser, &cache.snapshot) {
+            Ok(entry) => {
+                browser.open_diff(RowKey::of(entry), Vec::new());
+                pending.open_diff = true;
+            }
+            Err(refusal) => ui.refuse(refusal, clock()),
+        },
+        Event::CloseDiff => browser.close_diff(),
+        Event::JobFinished(outcome) => {
             let succeeded = outcome.success;
             ui.finished(outcome, clock());
             // A successful push moved the upstream, so the header's arrows and
             // tracking segment are stale the moment it lands — walk now rather
             // than leaving a wrong count on screen until the next refresh. A
-            // failure changed nothing in the repository, so walking would only
-            // pay for a status traversal to redraw the identical frame.
+            // file action moved a row across the index, which the watcher will
+            // also see, but walking here keeps the cursor's row from lagging a
+            // debounce behind the "staged" the user just read. A failure
+            // changed nothing in the repository, so walking would only pay for
+            // a status traversal to redraw the identical frame.
             if succeeded {
                 pending.force = true;
             }
@@ -1080,6 +1611,25 @@ where
     Flow::Continue
 }
 
+/// The row under the cursor, looked up in the snapshot the keys act on — or
+/// the refusal that explains why there is none.
+///
+/// The lookup goes by [`RowKey`] rather than by index, so a row that moved in
+/// the list between the render and the key is still the row acted on, and a row
+/// that disappeared is reported rather than swapped for its neighbour.
+fn selected_entry<'a, S: Watched>(
+    browser: &Browser,
+    watched: &'a S,
+) -> Result<&'a RenderEntry, &'static str> {
+    let snapshot = watched.target().ok_or(MULTI_REPO_FILE_REFUSAL)?;
+    let key = browser.selected().ok_or(NO_SELECTION)?;
+    snapshot
+        .files
+        .iter()
+        .find(|entry| key.names(entry))
+        .ok_or(NO_SELECTION)
+}
+
 /// The render loop's terminal-free core: wait for a filesystem event, a resize,
 /// or a timeout, then update the screen. A filesystem change walks git, and so
 /// does a timeout at which [`WalkSchedule`] owes a walk — a timed refresh, or a
@@ -1136,28 +1686,39 @@ where
 /// to zero. The accepted cost: a repository deleted for good leaves a frozen
 /// (but visibly aging) frame until the user quits. That is the right failure for
 /// a monitor — a wrong-but-labeled-old screen beats no screen.
-fn event_loop<Collect, RenderFn, Dims, Paint, Clock, Tick, StartPush>(
+fn event_loop<S, Collect, RenderFn, Dims, Paint, Clock, Tick, RunJob, DiffFn>(
     rx: &Receiver<Event>,
     debounce: Duration,
     displayed: &mut String,
-    start: LoopStart,
-    mut hooks: LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, StartPush>,
+    start: LoopStart<S>,
+    mut hooks: LoopHooks<Collect, RenderFn, Dims, Paint, Clock, Tick, RunJob, DiffFn>,
 ) -> Result<()>
 where
-    Collect: FnMut() -> Result<Snapshot>,
-    RenderFn: FnMut(&Snapshot, Dimensions, FrameTiming) -> Render,
+    S: Watched,
+    Collect: FnMut(&Changed) -> Result<S>,
+    RenderFn: FnMut(&S, Dimensions, FrameTiming, Option<&Cursor>) -> Render,
     Dims: Fn() -> Dimensions,
     Paint: FnMut(&str) -> Result<()>,
     Clock: Fn() -> Instant,
     Tick: Fn(Option<Duration>) -> Option<Duration>,
-    StartPush: FnMut(PushCommand),
+    RunJob: FnMut(Job),
+    DiffFn: FnMut(&RenderEntry) -> Result<Vec<String>>,
 {
     let LoopStart {
         mut cache,
         mut freshest,
         mut schedule,
         mut ui,
+        rows,
     } = start;
+    // The cursor and the diff view live only as long as the loop: they are
+    // about what is on screen, and nothing outside the loop draws it.
+    let mut browser = Browser::default();
+    browser.settle(rows);
+    // The roots whose changes no walk has read yet. Kept across wakes: a
+    // change the throttle defers is walked by a later wake, and that walk has
+    // to know where it happened.
+    let mut changed_roots: BTreeSet<usize> = BTreeSet::new();
     loop {
         // Wait for the first event, or — when the decay timer is enabled — wake
         // after `interval` of quiet for a tick.
@@ -1186,10 +1747,10 @@ where
                         event,
                         &mut pending,
                         &mut ui,
-                        &cache.snapshot,
-                        cache.dims,
+                        &mut browser,
+                        &cache,
                         &hooks.clock,
-                        &mut hooks.start_push,
+                        &mut hooks.run_job,
                     ) == Flow::Quit
                     {
                         break;
@@ -1205,10 +1766,10 @@ where
                         event,
                         &mut pending,
                         &mut ui,
-                        &cache.snapshot,
-                        cache.dims,
+                        &mut browser,
+                        &cache,
                         &hooks.clock,
-                        &mut hooks.start_push,
+                        &mut hooks.run_job,
                     ) == Flow::Quit
                     {
                         break;
@@ -1230,10 +1791,10 @@ where
                             event,
                             &mut pending,
                             &mut ui,
-                            &cache.snapshot,
-                            cache.dims,
+                            &mut browser,
+                            &cache,
                             &hooks.clock,
-                            &mut hooks.start_push,
+                            &mut hooks.run_job,
                         ) == Flow::Quit
                         {
                             // Unlike the first wake, a quit that arrives inside
@@ -1253,7 +1814,14 @@ where
                 }
             }
         }
-        let (saw_fs, saw_resize, saw_force) = (pending.fs, pending.resize, pending.force);
+        let (saw_fs, saw_resize, saw_force) =
+            (!pending.fs.is_empty(), pending.resize, pending.force);
+        changed_roots.append(&mut pending.fs);
+
+        // Fill in the diff the burst opened.
+        if pending.open_diff {
+            reread_diff(&mut browser, &cache.snapshot, &mut hooks.diff);
+        }
 
         // Read the clock once for this wake: the throttle decision, a walk's
         // start, and any age offset all key off the same instant.
@@ -1321,7 +1889,15 @@ where
         };
 
         let render = if walk_now {
-            let collected = (hooks.collect)();
+            // A walk with no change behind it — forced, timed, or owed by a
+            // refresh — reads everything; a change reads only where it fell.
+            let changed = if saw_force || changed_roots.is_empty() {
+                changed_roots.clear();
+                Changed::All
+            } else {
+                Changed::Roots(std::mem::take(&mut changed_roots))
+            };
+            let collected = (hooks.collect)(&changed);
             // Measure the walk's wall-clock cost around collect and feed it to
             // the throttle, which arms the next cooldown (= 100·cost) from it.
             // Deliberately outside the match: a *failed* walk still paid for a
@@ -1337,10 +1913,14 @@ where
                     // the previous one.
                     cache.collected_at = now;
                     cache.snapshot = snapshot;
+                    // An open diff is re-read from the same walk, so it can
+                    // never show a file the frame behind it has moved on from.
+                    reread_diff(&mut browser, &cache.snapshot, &mut hooks.diff);
                     (hooks.render)(
                         &cache.snapshot,
                         frame_dims,
                         timing(Duration::ZERO, &schedule, now),
+                        browser.cursor(),
                     )
                 }
                 // A walk can fail for reasons that are none of the user's
@@ -1360,6 +1940,7 @@ where
                         &cache.snapshot,
                         frame_dims,
                         timing(age_offset, &schedule, now),
+                        browser.cursor(),
                     )
                 }
             }
@@ -1369,6 +1950,7 @@ where
                 &cache.snapshot,
                 frame_dims,
                 timing(age_offset, &schedule, now),
+                browser.cursor(),
             )
         } else {
             // Decay tick, or an FS change the throttle deferred: re-render the
@@ -1378,19 +1960,32 @@ where
                 &cache.snapshot,
                 frame_dims,
                 timing(age_offset, &schedule, now),
+                browser.cursor(),
             )
         };
 
-        // The painted screen is the frame with the push overlay under it. They
-        // are compared as one string, so a frame that did not change but an
-        // overlay that did still repaints — and neither can repaint alone and
-        // leave the other stale.
-        let output = compose(render.output, &overlay.text());
+        let Render {
+            output: frame,
+            freshest_age,
+            rows,
+        } = render;
+        // The cursor settles on the rows this frame actually drew, so the next
+        // key moves over what the user can see. The frame is rendered even
+        // under an open diff for exactly this: the cursor the diff returns to
+        // follows the live list.
+        browser.settle(rows);
+
+        // The painted screen is the frame — or the diff in its place — with
+        // the push overlay under it. They are compared as one string, so a
+        // frame that did not change but an overlay that did still repaints —
+        // and neither can repaint alone and leave the other stale.
+        let frame = browser.paint_diff(frame_dims).unwrap_or(frame);
+        let output = compose(frame, &overlay.text());
         if should_repaint(&output, displayed) {
             (hooks.paint)(&output)?;
             *displayed = output;
         }
-        freshest = render.freshest_age;
+        freshest = freshest_age;
 
         if quitting {
             break;
@@ -1399,6 +1994,32 @@ where
     Ok(())
 }
 
+/// Re-read the open diff, if any, from `watched` through the loop's hook.
+///
+/// A row that has left the list — staged away, committed, reverted — leaves
+/// its diff empty rather than closing it out from under the user, and a read
+/// that fails puts the error where the diff would be: an unreadable blob is
+/// worth saying so, not worth ending watch mode over.
+fn reread_diff<S, DiffFn>(browser: &mut Browser, watched: &S, diff: &mut DiffFn)
+where
+    S: Watched,
+    DiffFn: FnMut(&RenderEntry) -> Result<Vec<String>>,
+{
+    let Some(key) = browser.diff_open() else {
+        return;
+    };
+    let entry = watched
+        .target()
+        .and_then(|snapshot| snapshot.files.iter().find(|entry| key.names(entry)));
+    let lines = match entry {
+        Some(entry) => {
+            diff(entry).unwrap_or_else(|err| vec![format!("could not read the diff: {err:#}")])
+        }
+        None => Vec::new(),
+    };
+    browser.refresh_diff(lines);
+}
+
 /// Join a frame and the push overlay into the one string that gets painted.
 ///
 /// An empty overlay returns the frame untouched, byte for byte. That is what
@@ -1472,12 +2093,24 @@ fn forward_input(event: CtEvent) -> Option<Event> {
 /// - **Ctrl-C quits from every mode**, including mid-push. A monitor that
 ///   cannot be quit while it waits on the network is a monitor that has to be
 ///   killed from another pane.
-/// - [`InputMode::Normal`]: `q` quits, `r` forces a refresh, `p` asks to push.
-/// - [`InputMode::Confirm`]: `y` and Enter push, `n`, Esc, and `q` cancel.
-///   Nothing else acts — with a question on screen, `q` is the answer "no",
-///   not "quit", and `r` is not a refresh. That is why the mode exists.
-/// - [`InputMode::Pushing`]: `q` quits and `r` refreshes, but `p` is inert, so
-///   an impatient second press cannot start an overlapping push.
+/// - [`InputMode::Normal`]: `q` quits, `r` forces a refresh, `p` asks to push,
+///   `f` fetches, and `u` asks to fast-forward a branch that is behind.
+///   `j`/`k` (or the arrows), `g`/`G` (or Home/End) move the cursor over the
+///   file rows and Esc drops it; `s` stages the row under it, `S` unstages it,
+///   Space moves it to the other side of the index, `d` discards it, and Enter
+///   opens its diff.
+/// - [`InputMode::Confirm`]: `y` and Enter answer yes, `n`, Esc, and `q`
+///   cancel. Nothing else acts — with a question on screen, `q` is the answer
+///   "no", not "quit", and `r` is not a refresh. That is why the mode exists.
+/// - [`InputMode::Pushing`]: as Normal, except that `p`, `f`, `u`, and the
+///   file-action keys are inert, so an impatient second press cannot start an overlapping job.
+///   The cursor still moves and a diff still opens; neither touches the
+///   repository.
+/// - [`InputMode::Diff`]: the motion keys scroll the diff, with PgUp/PgDn and
+///   Space paging; Esc, `q`, and Enter close it, and `r` refreshes. `q` closes
+///   rather than quits for the same reason it cancels a question: the user is
+///   looking at something the frame does not show, and the key that leaves it
+///   should not also leave gsw.
 /// - Every other press is [`Event::Dismiss`], which clears a status message and
 ///   otherwise does nothing.
 fn classify_input(key: KeyEvent, mode: InputMode) -> Option<Event> {
@@ -1498,13 +2131,26 @@ fn classify_input(key: KeyEvent, mode: InputMode) -> Option<Event> {
         return Some(Event::Quit);
     }
 
+    let idle = mode == InputMode::Normal;
     let event = match mode {
         InputMode::Normal | InputMode::Pushing => match code {
             KeyCode::Char('q') => Event::Quit,
             KeyCode::Char('r') => Event::ForceRefresh,
-            // The one key the two modes disagree on: a push already running
-            // makes a second request meaningless rather than harmless.
-            KeyCode::Char('p') if mode == InputMode::Normal => Event::PushRequested,
+            KeyCode::Char('j') | KeyCode::Down => Event::Move(Motion::Down),
+            KeyCode::Char('k') | KeyCode::Up => Event::Move(Motion::Up),
+            KeyCode::Char('g') | KeyCode::Home => Event::Move(Motion::Top),
+            KeyCode::Char('G') | KeyCode::End => Event::Move(Motion::Bottom),
+            KeyCode::Esc => Event::Deselect,
+            KeyCode::Enter => Event::OpenDiff,
+            // The keys the two modes disagree on: a job already running makes
+            // a second one meaningless rather than harmless.
+            KeyCode::Char('p') if idle => Event::PushRequested,
+            KeyCode::Char('s') if idle => Event::File(FileAction::Stage),
+            KeyCode::Char('S') if idle => Event::File(FileAction::Unstage),
+            KeyCode::Char('d') if idle => Event::File(FileAction::Discard),
+            KeyCode::Char(' ') if idle => Event::ToggleStaged,
+            KeyCode::Char('f') if idle => Event::Sync(SyncAction::Fetch),
+            KeyCode::Char('u') if idle => Event::Sync(SyncAction::FastForward),
             _ => Event::Dismiss,
         },
         InputMode::Confirm => match code {
@@ -1512,6 +2158,17 @@ fn classify_input(key: KeyEvent, mode: InputMode) -> Option<Event> {
             KeyCode::Char('n' | 'N' | 'q') | KeyCode::Esc => Event::PushCancelled,
             _ => Event::Dismiss,
         },
+        InputMode::Diff => match code {
+            KeyCode::Char('j') | KeyCode::Down => Event::Move(Motion::Down),
+            KeyCode::Char('k') | KeyCode::Up => Event::Move(Motion::Up),
+            KeyCode::PageDown | KeyCode::Char(' ') => Event::Move(Motion::PageDown),
+            KeyCode::PageUp => Event::Move(Motion::PageUp),
+            KeyCode::Char('g') | KeyCode::Home => Event::Move(Motion::Top),
+            KeyCode::Char('G') | KeyCode::End => Event::Move(Motion::Bottom),
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Event::CloseDiff,
+            KeyCode::Char('r') => Event::ForceRefresh,
+            _ => Event::Dismiss,
+        },
     };
     Some(event)
 }
@@ -1547,14 +2204,14 @@ type PanicHook = Arc<dyn Fn(&std::panic::PanicHookInfo<'_>) + Sync + Send>;
 /// alternate one. On drop the pre-watch panic hook is reinstated, so our
 /// terminal-restoring wrapper never lingers as global process state once the
 /// guard is gone.
-struct TerminalGuard {
+pub(crate) struct TerminalGuard {
     /// The panic hook in effect before [`TerminalGuard::enter`] wrapped it,
     /// reinstated on drop. `Option` only so `Drop` can move it back out.
     previous_hook: Option<PanicHook>,
 }
 
 impl TerminalGuard {
-    fn enter() -> Result<Self> {
+    pub(crate) fn enter() -> Result<Self> {
         enable_raw_mode()?;
         execute!(io::stdout(), EnterAlternateScreen, Hide)?;
 
@@ -1595,7 +2252,7 @@ fn restore_terminal() {
 mod tests {
     use super::*;
     use crate::testrepo;
-    use crate::WRAPPER_CHROME_ROWS;
+    use crate::{render_frame, WRAPPER_CHROME_ROWS};
     use ignore::gitignore::GitignoreBuilder;
 
     /// A [`RenderConfig`] for the fixture-backed walk tests: no explicit base,
@@ -1607,9 +2264,12 @@ mod tests {
             max_files: None,
             bar_width: 20,
             log_lines: 0,
+            reflog_lines: 0,
+            stash_lines: 0,
             truecolor: false,
             width_offset: 0,
             refresh_interval: None,
+            theme: crate::theme::Theme::DEFAULT,
         }
     }
 
@@ -1962,15 +2622,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_submodule_is_filtered_by_its_own_ignore_rules() {
+        // Git applies a submodule's `.gitignore` to paths inside it, and the
+        // superproject's rules stop at the gitlink. Now that the view expands
+        // changed submodules, a write into the submodule's build output must be
+        // dropped by *its* rule, while its real files still wake the loop.
+        let (_library, superproject) = testrepo::init_repo_with_submodule();
+        let p = superproject.path();
+        let lib = p.join("libs").join("lib");
+        std::fs::write(lib.join(".gitignore"), "out/\n").expect("write lib .gitignore");
+        std::fs::write(p.join(".gitignore"), "*.log\n").expect("write .gitignore");
+
+        let (_handle, ignore, workdir, git_dirs) = watching(p);
+        let lib = workdir.join("libs").join("lib");
+
+        assert!(
+            !should_react(&lib.join("out").join("lib.o"), &ignore, &workdir, &git_dirs),
+            "the submodule's own rule must filter its build output",
+        );
+        assert!(
+            should_react(&lib.join("a.txt"), &ignore, &workdir, &git_dirs),
+            "a tracked file inside the submodule is a change the view renders",
+        );
+        assert!(
+            should_react(&lib.join("debug.log"), &ignore, &workdir, &git_dirs),
+            "the superproject's rules stop at the gitlink, as they do for git",
+        );
+        assert!(
+            !should_react(&workdir.join("debug.log"), &ignore, &workdir, &git_dirs),
+            "while still applying to the superproject itself",
+        );
+    }
+
     #[test]
     fn next_tick_boundaries_follow_the_fade_model() {
         use crate::age::FADE_DARKEST_AT;
 
         // `< 1 min`: tick every second so the live seconds in the age text and
         // the fast early fade both stay current.
-        assert_eq!(next_tick(Duration::ZERO), Some(Duration::from_secs(1)));
         assert_eq!(
-            next_tick(Duration::from_secs(59)),
+            next_tick(Duration::ZERO, FADE_DARKEST_AT),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            next_tick(Duration::from_secs(59), FADE_DARKEST_AT),
             Some(Duration::from_secs(1)),
             "just under a minute is still in the 1 s band",
         );
@@ -1978,17 +2674,17 @@ mod tests {
         // At and past 1 min, drop to the ~60 s cadence: the minute text changes
         // only once a minute and the fade moves ~1 RGB unit/min.
         assert_eq!(
-            next_tick(Duration::from_secs(60)),
+            next_tick(Duration::from_secs(60), FADE_DARKEST_AT),
             Some(Duration::from_secs(60)),
             "exactly one minute crosses into the 60 s band",
         );
         assert_eq!(
-            next_tick(Duration::from_secs(60 * 60)),
+            next_tick(Duration::from_secs(60 * 60), FADE_DARKEST_AT),
             Some(Duration::from_secs(60)),
             "an hour old still ticks every 60 s",
         );
         assert_eq!(
-            next_tick(FADE_DARKEST_AT - Duration::from_secs(1)),
+            next_tick(FADE_DARKEST_AT - Duration::from_secs(1), FADE_DARKEST_AT),
             Some(Duration::from_secs(60)),
             "just under 2 h is still in the 60 s band",
         );
@@ -1996,20 +2692,27 @@ mod tests {
         // At [`FADE_DARKEST_AT`] (2 h) and beyond the fade is frozen at the
         // floor: nothing visible changes with time, so the timer is disabled.
         assert_eq!(
-            next_tick(FADE_DARKEST_AT),
+            next_tick(FADE_DARKEST_AT, FADE_DARKEST_AT),
             None,
             "the fade-floor boundary disables the timer",
         );
         assert_eq!(
-            next_tick(FADE_DARKEST_AT + Duration::from_secs(1)),
+            next_tick(FADE_DARKEST_AT + Duration::from_secs(1), FADE_DARKEST_AT),
             None,
             "past the floor the timer stays disabled",
         );
         assert_eq!(
-            next_tick(Duration::from_secs(60 * 60 * 24 * 30)),
+            next_tick(Duration::from_secs(60 * 60 * 24 * 30), FADE_DARKEST_AT),
             None,
             "a month-old freshest item produces no ticks",
         );
+
+        // A config file that stretches the fade stretches the ticking with it.
+        assert_eq!(
+            next_tick(FADE_DARKEST_AT, 2 * FADE_DARKEST_AT),
+            Some(Duration::from_secs(60)),
+            "a longer fade is still moving where the built-in one has stopped",
+        );
     }
 
     #[test]
@@ -2527,6 +3230,114 @@ mod tests {
         }
     }
 
+    #[test]
+    fn f_and_u_act_only_when_nothing_else_is_happening() {
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('f')), InputMode::Normal),
+            Some(Event::Sync(SyncAction::Fetch)),
+        ));
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('u')), InputMode::Normal),
+            Some(Event::Sync(SyncAction::FastForward)),
+        ));
+        // Mid-job they are inert like `p`; at a question or in the diff they
+        // are ordinary keys.
+        for mode in [InputMode::Pushing, InputMode::Confirm, InputMode::Diff] {
+            for code in [KeyCode::Char('f'), KeyCode::Char('u')] {
+                assert!(
+                    matches!(classify_input(press(code), mode), Some(Event::Dismiss)),
+                    "{code:?} must be inert in {mode:?}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn the_file_keys_act_only_when_nothing_else_is_happening() {
+        // Like `p`: a job already running makes a second one meaningless, so
+        // the file-action keys go inert mid-push, while the motion keys and
+        // Enter — which touch nothing — keep working.
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('s')), InputMode::Normal),
+            Some(Event::File(FileAction::Stage)),
+        ));
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('S')), InputMode::Normal),
+            Some(Event::File(FileAction::Unstage)),
+        ));
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('d')), InputMode::Normal),
+            Some(Event::File(FileAction::Discard)),
+        ));
+        assert!(matches!(
+            classify_input(press(KeyCode::Char(' ')), InputMode::Normal),
+            Some(Event::ToggleStaged),
+        ));
+        for code in [
+            KeyCode::Char('s'),
+            KeyCode::Char('S'),
+            KeyCode::Char('d'),
+            KeyCode::Char(' '),
+        ] {
+            assert!(
+                matches!(
+                    classify_input(press(code), InputMode::Pushing),
+                    Some(Event::Dismiss),
+                ),
+                "{code:?} must be inert while a job runs",
+            );
+        }
+        for mode in [InputMode::Normal, InputMode::Pushing] {
+            assert!(matches!(
+                classify_input(press(KeyCode::Char('j')), mode),
+                Some(Event::Move(Motion::Down)),
+            ));
+            assert!(matches!(
+                classify_input(press(KeyCode::Up), mode),
+                Some(Event::Move(Motion::Up)),
+            ));
+            assert!(matches!(
+                classify_input(press(KeyCode::Enter), mode),
+                Some(Event::OpenDiff),
+            ));
+            assert!(matches!(
+                classify_input(press(KeyCode::Esc), mode),
+                Some(Event::Deselect),
+            ));
+        }
+    }
+
+    #[test]
+    fn the_diff_view_scrolls_and_q_closes_it_instead_of_quitting() {
+        for code in [KeyCode::Char('q'), KeyCode::Esc, KeyCode::Enter] {
+            assert!(
+                matches!(
+                    classify_input(press(code), InputMode::Diff),
+                    Some(Event::CloseDiff),
+                ),
+                "{code:?} must close the diff",
+            );
+        }
+        assert!(matches!(
+            classify_input(press(KeyCode::Char(' ')), InputMode::Diff),
+            Some(Event::Move(Motion::PageDown)),
+        ));
+        assert!(matches!(
+            classify_input(press(KeyCode::Char('G')), InputMode::Diff),
+            Some(Event::Move(Motion::Bottom)),
+        ));
+        // Nothing in the diff view changes the repository.
+        for code in [KeyCode::Char('s'), KeyCode::Char('d'), KeyCode::Char('p')] {
+            assert!(
+                matches!(
+                    classify_input(press(code), InputMode::Diff),
+                    Some(Event::Dismiss),
+                ),
+                "{code:?} must be inert in the diff view",
+            );
+        }
+    }
+
     #[test]
     fn the_confirmation_ignores_the_ordinary_keys() {
         // With a question on screen, `r` must not refresh and `p` must not
@@ -2686,12 +3497,18 @@ mod tests {
         None
     }
 
+    /// A `diff` hook for the loop tests that never open one.
+    pub(super) fn no_diff(_entry: &RenderEntry) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     /// Build a [`Render`] with the given frame and no freshest age — enough for
     /// the event-driven loop tests, which don't exercise the cadence.
     pub(super) fn frame(output: &str) -> Render {
         Render {
             output: output.to_string(),
             freshest_age: None,
+            rows: Vec::new(),
         }
     }
 
@@ -2708,6 +3525,9 @@ mod tests {
             upstream: None,
             operation: None,
             push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         }
     }
 
@@ -2762,13 +3582,17 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: WalkSchedule::new(Some(interval), base, Duration::ZERO),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     // One wake is enough to decide: a decay tick alone never
                     // walks, so any collect at all came from the timed deadline.
                     let _ = tx.send(Event::Quit);
@@ -2781,7 +3605,8 @@ mod tests {
                 // A decay tick on the same cadence, so the loop always wakes:
                 // the test must fail when no walk is scheduled, not block.
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -2815,10 +3640,14 @@ mod tests {
                     Duration::ZERO,
                 ),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || Ok(empty_snapshot()),
-                render: |_snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                collect: |_changed: &Changed| Ok(empty_snapshot()),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     seen = Some(timing);
                     let _ = tx.send(Event::Quit);
                     frame("tick")
@@ -2827,7 +3656,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: || clock_at,
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -2861,13 +3691,17 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     seen = Some(timing);
                     let _ = tx.send(Event::Quit);
                     frame("tick")
@@ -2876,7 +3710,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: stepping_clock(base, Duration::from_secs(60)),
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -2896,7 +3731,7 @@ mod tests {
         // collect and a single repaint, not one per event.
         let (tx, rx) = mpsc::channel();
         for _ in 0..5 {
-            tx.send(Event::FsChanged).expect("queue event");
+            tx.send(Event::FsChanged(0)).expect("queue event");
         }
         drop(tx);
 
@@ -2913,13 +3748,17 @@ mod tests {
                 freshest: None,
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| frame("frame"),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| frame("frame"),
                 dimensions: || TEST_DIMS,
                 paint: |_output: &str| {
                     paints += 1;
@@ -2927,7 +3766,8 @@ mod tests {
                 },
                 clock: || now,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -2937,12 +3777,73 @@ mod tests {
         assert_eq!(displayed, "frame");
     }
 
+    /// Run the loop over `events`, queued up front, and return the
+    /// [`Changed`] each walk was asked for.
+    fn walks_for(events: Vec<Event>) -> Vec<Changed> {
+        let (tx, rx) = mpsc::channel();
+        for event in events {
+            tx.send(event).expect("queue event");
+        }
+        drop(tx);
+
+        let mut displayed = String::new();
+        let mut walks = Vec::new();
+        let now = Instant::now();
+        event_loop(
+            &rx,
+            TEST_DEBOUNCE,
+            &mut displayed,
+            LoopStart {
+                cache: seeded_cache(now),
+                freshest: None,
+                schedule: no_timed_refresh(),
+                ui: PushUi::new(false),
+                rows: Vec::new(),
+            },
+            LoopHooks {
+                collect: |changed: &Changed| {
+                    walks.push(changed.clone());
+                    Ok(empty_snapshot())
+                },
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| frame("frame"),
+                dimensions: || TEST_DIMS,
+                paint: |_output: &str| Ok(()),
+                clock: || now,
+                next_tick: timer_off,
+                run_job: |_job: Job| {},
+                diff: no_diff,
+            },
+        )
+        .expect("loop");
+        walks
+    }
+
+    #[test]
+    fn event_loop_walks_only_the_roots_a_burst_touched() {
+        // Root 1 saw nothing, so the one walk the burst earns leaves it alone.
+        let walks = walks_for(vec![
+            Event::FsChanged(2),
+            Event::FsChanged(0),
+            Event::FsChanged(2),
+        ]);
+        assert_eq!(walks, vec![Changed::Roots(BTreeSet::from([0, 2]))]);
+    }
+
+    #[test]
+    fn a_manual_refresh_walks_every_root() {
+        let walks = walks_for(vec![Event::FsChanged(1), Event::ForceRefresh]);
+        assert_eq!(walks, vec![Changed::All]);
+    }
+
     #[test]
     fn event_loop_suppresses_when_recompute_is_unchanged() {
         // FS churn that doesn't change the visible state must still collect but
         // produce no repaint.
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue event");
+        tx.send(Event::FsChanged(0)).expect("queue event");
         drop(tx);
 
         let mut displayed = "unchanged".to_string();
@@ -2958,15 +3859,17 @@ mod tests {
                 freshest: None,
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
-                    frame("unchanged")
-                },
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| { frame("unchanged") },
                 dimensions: || TEST_DIMS,
                 paint: |_output: &str| {
                     paints += 1;
@@ -2974,7 +3877,8 @@ mod tests {
                 },
                 clock: || now,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3004,13 +3908,17 @@ mod tests {
                 freshest: None,
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| frame("frame"),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| frame("frame"),
                 dimensions: || TEST_DIMS,
                 paint: |_output: &str| {
                     paints += 1;
@@ -3018,7 +3926,8 @@ mod tests {
                 },
                 clock: || now,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3046,10 +3955,14 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || Ok(empty_snapshot()),
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                collect: |_changed: &Changed| Ok(empty_snapshot()),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     renders += 1;
                     // End the loop right after this first tick-driven render.
                     let _ = tx.send(Event::Quit);
@@ -3064,7 +3977,8 @@ mod tests {
                 // Tiny interval so the tick fires fast; the cadence-vs-age
                 // mapping is covered by the next_tick tests.
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3095,10 +4009,14 @@ mod tests {
                 freshest: Some(Duration::from_secs(30)),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || Ok(empty_snapshot()),
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                collect: |_changed: &Changed| Ok(empty_snapshot()),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     renders += 1;
                     let _ = tx.send(Event::Quit);
                     frame("steady")
@@ -3110,7 +4028,8 @@ mod tests {
                 },
                 clock: || now,
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3141,13 +4060,17 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     renders += 1;
                     seen_offset = Some(timing.age_offset);
                     let _ = tx.send(Event::Quit);
@@ -3157,7 +4080,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: || clock_at,
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3196,13 +4120,17 @@ mod tests {
                 freshest: None,
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     seen_dims = Some(dims);
                     frame("resized")
                 },
@@ -3210,7 +4138,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: || now,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3230,7 +4159,7 @@ mod tests {
         // seed (Part A). We drive: FsChanged (collected at t+10s), then a tick
         // (clock at t+15s) whose render must see a 5s offset — not 15s.
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue fs change");
+        tx.send(Event::FsChanged(0)).expect("queue fs change");
 
         let base = Instant::now();
         // Clock returns t+10s for the FS collect, then t+15s for the tick.
@@ -3251,10 +4180,14 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || Ok(empty_snapshot()),
-                render: |_snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                collect: |_changed: &Changed| Ok(empty_snapshot()),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     offsets.push(timing.age_offset);
                     // First render is the FS walk (offset 0); the next wake is a
                     // decay tick. End the loop once the tick render has happened.
@@ -3271,7 +4204,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3315,7 +4249,7 @@ mod tests {
         let clock_calls = std::cell::Cell::new(0_usize);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue first change");
+        tx.send(Event::FsChanged(0)).expect("queue first change");
 
         let mut displayed = String::new();
         let mut collects = 0_usize;
@@ -3329,18 +4263,22 @@ mod tests {
                 freshest: None, // decay timer off: isolate the throttle from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     // Deliver the next change in its own iteration so the three
                     // never coalesce; quit once all three have been processed.
                     if changes_sent < 3 {
                         changes_sent += 1;
-                        let _ = tx.send(Event::FsChanged);
+                        let _ = tx.send(Event::FsChanged(0));
                     } else {
                         let _ = tx.send(Event::Quit);
                     }
@@ -3354,7 +4292,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3392,7 +4331,7 @@ mod tests {
         let clock_calls = std::cell::Cell::new(0_usize);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue arming change");
+        tx.send(Event::FsChanged(0)).expect("queue arming change");
 
         let mut displayed = String::new();
         let mut collects = 0_usize;
@@ -3406,20 +4345,24 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     stage += 1;
                     match stage {
                         // After the arming walk: fire a burst of three changes
                         // that coalesce inside one debounce window.
                         1 => {
                             for _ in 0..3 {
-                                let _ = tx.send(Event::FsChanged);
+                                let _ = tx.send(Event::FsChanged(0));
                             }
                         }
                         // The deferred re-render of the coalesced burst: do
@@ -3441,7 +4384,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3466,7 +4410,7 @@ mod tests {
         let base = Instant::now();
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue arming change");
+        tx.send(Event::FsChanged(0)).expect("queue arming change");
 
         let mut displayed = String::new();
         let mut collects = 0_usize;
@@ -3480,19 +4424,23 @@ mod tests {
                 freshest: Some(Duration::ZERO),
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     stage += 1;
                     match stage {
                         // After the arming walk: one FS change lands mid-cooldown
                         // (it is deferred, setting the dirty flag).
                         1 => {
-                            let _ = tx.send(Event::FsChanged);
+                            let _ = tx.send(Event::FsChanged(0));
                         }
                         // The deferred re-render: do nothing, let a decay tick fire
                         // while the cooldown is still active.
@@ -3509,7 +4457,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: || base,
                 next_tick: |_freshest| Some(Duration::from_millis(5)),
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3541,7 +4490,7 @@ mod tests {
         let clock_calls = std::cell::Cell::new(0_usize);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue arming change");
+        tx.send(Event::FsChanged(0)).expect("queue arming change");
 
         let mut displayed = String::new();
         let mut collects = 0_usize;
@@ -3555,13 +4504,17 @@ mod tests {
                 freshest: None, // decay timer off: isolate the throttle from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     stage += 1;
                     match stage {
                         // After the arming walk: a manual refresh lands mid-cooldown.
@@ -3584,7 +4537,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3630,18 +4584,22 @@ mod tests {
                 freshest: None, // decay timer off: isolate the throttle from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     stage += 1;
                     match stage {
                         // After the forced walk: an FS change lands mid-cooldown.
                         1 => {
-                            let _ = tx.send(Event::FsChanged);
+                            let _ = tx.send(Event::FsChanged(0));
                         }
                         // The deferred re-render of that FS change: end the loop.
                         _ => {
@@ -3658,7 +4616,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3694,13 +4653,17 @@ mod tests {
                 freshest: None, // decay timer off: isolate the forced walk from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Ok(empty_snapshot())
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     // End the loop right after the forced walk's render.
                     let _ = tx.send(Event::Quit);
                     frame("forced")
@@ -3712,7 +4675,8 @@ mod tests {
                 },
                 clock: || base,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -3743,7 +4707,7 @@ mod tests {
         let clock_at = base + Duration::from_secs(50);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue fs change");
+        tx.send(Event::FsChanged(0)).expect("queue fs change");
         drop(tx);
 
         let mut cache = seeded_cache(base);
@@ -3761,9 +4725,10 @@ mod tests {
                 freshest: None, // decay timer off: isolate the failed walk from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     // The exact shape `collect_snapshot` produces when the ref
                     // store has gone missing mid-walk.
@@ -3771,7 +4736,10 @@ mod tests {
                         "status iter: The reference 'HEAD' did not exist"
                     ))
                 },
-                render: |snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                render: |snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     rendered.push((snap.branch.clone(), timing.age_offset));
                     frame("last good frame")
                 },
@@ -3779,7 +4747,8 @@ mod tests {
                 paint: |_output: &str| Ok(()),
                 clock: || clock_at,
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         );
 
@@ -3831,7 +4800,7 @@ mod tests {
         let clock_calls = std::cell::Cell::new(0_usize);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue first change");
+        tx.send(Event::FsChanged(0)).expect("queue first change");
 
         let mut displayed = String::new();
         let mut collects = 0_usize;
@@ -3845,18 +4814,22 @@ mod tests {
                 freshest: None, // decay timer off: isolate the throttle from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     Err(anyhow::anyhow!("status platform: repository is gone"))
                 },
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     // Deliver the next change in its own iteration so the three
                     // never coalesce; quit once all three have been processed.
                     if changes_sent < 3 {
                         changes_sent += 1;
-                        let _ = tx.send(Event::FsChanged);
+                        let _ = tx.send(Event::FsChanged(0));
                     } else {
                         let _ = tx.send(Event::Quit);
                     }
@@ -3870,7 +4843,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         );
 
@@ -3922,7 +4896,7 @@ mod tests {
         let clock_calls = std::cell::Cell::new(0_usize);
 
         let (tx, rx) = mpsc::channel();
-        tx.send(Event::FsChanged).expect("queue first change");
+        tx.send(Event::FsChanged(0)).expect("queue first change");
 
         let mut cache = seeded_cache(base);
         cache.snapshot.branch = "last-good".to_string();
@@ -3939,9 +4913,10 @@ mod tests {
                 freshest: None, // decay timer off: isolate recovery from tick behavior
                 schedule: no_timed_refresh(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     collects += 1;
                     if collects == 1 {
                         // The repo is momentarily unreadable — mid-`gc`, say.
@@ -3956,13 +4931,16 @@ mod tests {
                         Ok(fresh)
                     }
                 },
-                render: |snap: &Snapshot, _dims: Dimensions, timing: FrameTiming| {
+                render: |snap: &Snapshot,
+                         _dims: Dimensions,
+                         timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     rendered.push((snap.branch.clone(), timing.age_offset));
                     match rendered.len() {
                         // Deliver the retry in its own iteration so it lands in
                         // a separate debounce window instead of coalescing.
                         1 => {
-                            let _ = tx.send(Event::FsChanged);
+                            let _ = tx.send(Event::FsChanged(0));
                             frame("stale")
                         }
                         // A resize forces one more cached re-render (no walk),
@@ -3985,7 +4963,8 @@ mod tests {
                     times[i.min(times.len() - 1)]
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         );
 
@@ -4080,9 +5059,10 @@ mod tests {
 
 #[cfg(test)]
 mod push_loop_tests {
-    use super::tests::{frame, timer_off, TEST_DEBOUNCE, TEST_DIMS};
+    use super::tests::{frame, no_diff, timer_off, TEST_DEBOUNCE, TEST_DIMS};
     use super::*;
-    use crate::push::PushOutcome;
+    use crate::push::{GitOutcome, PushCommand};
+    use crate::sync::SyncCommand;
     use crossterm::event::{KeyCode, KeyModifiers};
     use std::cell::RefCell;
     use testcolor::strip_ansi;
@@ -4100,6 +5080,9 @@ mod push_loop_tests {
             upstream: None,
             operation: None,
             push_remote: Some("origin".into()),
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
         }
     }
 
@@ -4126,7 +5109,7 @@ mod push_loop_tests {
         ui.request(&pushable_snapshot(), TEST_DIMS, at);
         ui.confirm();
         ui.finished(
-            crate::push::PushOutcome {
+            crate::push::GitOutcome {
                 success: true,
                 output: String::new(),
             },
@@ -4141,6 +5124,7 @@ mod push_loop_tests {
     struct Seen {
         collects: usize,
         pushes: Vec<PushCommand>,
+        syncs: Vec<SyncCommand>,
         frame_heights: Vec<usize>,
     }
 
@@ -4194,13 +5178,17 @@ mod push_loop_tests {
                 freshest: None,
                 schedule: no_timed_refresh_for_push(),
                 ui: PushUi::new(false),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || {
+                collect: |_changed: &Changed| {
                     seen.borrow_mut().collects += 1;
                     Ok(pushable_snapshot())
                 },
-                render: |_snap: &Snapshot, frame_dims: Dimensions, _timing: FrameTiming| {
+                render: |_snap: &Snapshot,
+                         frame_dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| {
                     seen.borrow_mut().frame_heights.push(frame_dims.height);
                     frame(&render_frame(frame_dims))
                 },
@@ -4208,7 +5196,12 @@ mod push_loop_tests {
                 paint: |_output: &str| Ok(()),
                 clock: move || base,
                 next_tick: timer_off,
-                start_push: |command: PushCommand| seen.borrow_mut().pushes.push(command),
+                run_job: |job: Job| match job {
+                    Job::Push(command) => seen.borrow_mut().pushes.push(command),
+                    Job::Sync(command) => seen.borrow_mut().syncs.push(command),
+                    Job::File(_) => {}
+                },
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -4271,10 +5264,14 @@ mod push_loop_tests {
                 freshest: None, // decay timer off: the message is the only thing that ages
                 schedule: no_timed_refresh_for_push(),
                 ui: pushed_ui(base),
+                rows: Vec::new(),
             },
             LoopHooks {
-                collect: || Ok(pushable_snapshot()),
-                render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| frame("FRAME"),
+                collect: |_changed: &Changed| Ok(pushable_snapshot()),
+                render: |_snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         _cursor: Option<&Cursor>| frame("FRAME"),
                 dimensions: || TEST_DIMS,
                 paint: |output: &str| {
                     painted.borrow_mut().push(output.to_string());
@@ -4294,7 +5291,8 @@ mod push_loop_tests {
                     }
                 },
                 next_tick: timer_off,
-                start_push: |_command: PushCommand| {},
+                run_job: |_job: Job| {},
+                diff: no_diff,
             },
         )
         .expect("loop");
@@ -4316,6 +5314,54 @@ mod push_loop_tests {
         );
     }
 
+    #[test]
+    fn p_on_a_view_with_no_single_repository_refuses_instead_of_asking() {
+        // The dashboard watches several repositories, so `p` has nothing to
+        // plan a push from. It must say so — and must not leave a question
+        // up that the `y` right behind it could answer.
+        struct Several;
+        impl Watched for Several {
+            fn target(&self) -> Option<&Snapshot> {
+                None
+            }
+        }
+
+        let now = Instant::now();
+        let mut ui = PushUi::new(false);
+        let mut pending = Pending::default();
+        let mut browser = Browser::default();
+        let cache = SnapshotCache {
+            snapshot: Several,
+            collected_at: now,
+            dims: TEST_DIMS,
+        };
+        let mut pushes = Vec::new();
+        let mut press = |code: KeyCode, ui: &mut PushUi| {
+            absorb(
+                key(code),
+                &mut pending,
+                ui,
+                &mut browser,
+                &cache,
+                &|| now,
+                &mut |job: Job| pushes.push(job),
+            );
+        };
+
+        press(KeyCode::Char('p'), &mut ui);
+        assert_eq!(ui.mode(), InputMode::Normal);
+        let overlay = strip_ansi(&ui.overlay(TEST_DIMS, now).text());
+        assert!(
+            overlay.contains(MULTI_REPO_PUSH_REFUSAL),
+            "the refusal must be on screen, got {overlay:?}",
+        );
+
+        // With no question up, `y` is an ordinary key: it dismisses the
+        // refusal and starts nothing.
+        press(KeyCode::Char('y'), &mut ui);
+        assert!(pushes.is_empty(), "nothing may be pushed: {pushes:?}");
+    }
+
     #[test]
     fn composing_does_not_double_the_frames_trailing_newline() {
         // A real gsw frame ends with a newline. Joining with another one would
@@ -4397,7 +5443,7 @@ mod push_loop_tests {
         let (displayed, seen) = run_loop(vec![
             key(KeyCode::Char('p')),
             key(KeyCode::Char('y')),
-            Event::PushFinished(PushOutcome {
+            Event::JobFinished(GitOutcome {
                 success: true,
                 output: String::new(),
             }),
@@ -4413,6 +5459,38 @@ mod push_loop_tests {
         );
     }
 
+    #[test]
+    fn f_fetches_at_once_and_a_successful_fetch_walks_git_again() {
+        // The fetch is what moves the remote-tracking ref, so the header's
+        // behind count is only right once the walk after it lands.
+        let (displayed, seen) = run_loop(vec![
+            key(KeyCode::Char('f')),
+            Event::JobFinished(GitOutcome {
+                success: true,
+                output: String::new(),
+            }),
+            Event::Quit,
+        ]);
+        let [command] = seen.syncs.as_slice() else {
+            panic!("f must start one fetch, got {:?}", seen.syncs);
+        };
+        assert_eq!(command.args(), ["fetch", "origin"]);
+        assert_eq!(seen.collects, 1, "a fetch must re-walk so the counts move");
+        assert!(displayed.contains("Fetched origin"), "got {displayed:?}");
+    }
+
+    #[test]
+    fn u_on_a_branch_with_nothing_to_fast_forward_says_why() {
+        // The pushable fixture has no upstream, so there is nothing to move
+        // the branch to — and no question to answer.
+        let (displayed, seen) = run_loop(vec![key(KeyCode::Char('u')), Event::Quit]);
+        assert!(seen.syncs.is_empty(), "got {:?}", seen.syncs);
+        assert!(
+            displayed.contains("gsw-push has no upstream to fast-forward to"),
+            "got {displayed:?}",
+        );
+    }
+
     #[test]
     fn a_failed_push_shows_the_error_and_does_not_walk() {
         // Nothing changed in the repository, so a walk would cost a status
@@ -4420,7 +5498,7 @@ mod push_loop_tests {
         let (displayed, seen) = run_loop(vec![
             key(KeyCode::Char('p')),
             key(KeyCode::Char('y')),
-            Event::PushFinished(PushOutcome {
+            Event::JobFinished(GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             }),
@@ -4451,7 +5529,7 @@ mod push_loop_tests {
         let (_, seen) = run_loop(vec![
             key(KeyCode::Char('p')),
             key(KeyCode::Char('y')),
-            Event::PushFinished(PushOutcome {
+            Event::JobFinished(GitOutcome {
                 success: false,
                 output: "error: one\nerror: two\nerror: three\nerror: four\n".to_string(),
             }),
@@ -4477,7 +5555,7 @@ mod push_loop_tests {
         vec![
             key(KeyCode::Char('p')),
             key(KeyCode::Char('y')),
-            Event::PushFinished(PushOutcome {
+            Event::JobFinished(GitOutcome {
                 success: false,
                 output: "To /tmp/origin\n\
                          ! [rejected] gsw-push -> gsw-push (fetch first)\n\
@@ -4560,7 +5638,7 @@ mod push_loop_tests {
         let (displayed, seen) = run_loop(vec![
             key(KeyCode::Char('p')),
             key(KeyCode::Char('y')),
-            Event::PushFinished(PushOutcome {
+            Event::JobFinished(GitOutcome {
                 success: false,
                 output: "error: failed to push some refs\n".to_string(),
             }),
@@ -4606,17 +5684,26 @@ mod push_loop_tests {
                     freshest: None,
                     schedule: no_timed_refresh_for_push(),
                     ui: PushUi::new(false),
+                    rows: Vec::new(),
                 },
                 LoopHooks {
-                    collect: || Ok(pushable_snapshot()),
-                    render: |_snap: &Snapshot, _dims: Dimensions, _timing: FrameTiming| {
+                    collect: |_changed: &Changed| Ok(pushable_snapshot()),
+                    render: |_snap: &Snapshot,
+                             _dims: Dimensions,
+                             _timing: FrameTiming,
+                             _cursor: Option<&Cursor>| {
                         frame("FRAME")
                     },
                     dimensions: || TEST_DIMS,
                     paint: |_output: &str| Ok(()),
                     clock: move || base,
                     next_tick: timer_off,
-                    start_push: |command: PushCommand| seen.borrow_mut().pushes.push(command),
+                    run_job: |job: Job| {
+                        if let Job::Push(command) = job {
+                            seen.borrow_mut().pushes.push(command);
+                        }
+                    },
+                    diff: no_diff,
                 },
             )
             .expect("loop");
@@ -4630,3 +5717,215 @@ mod push_loop_tests {
         assert!(pushes.is_empty(), "ctrl-c must not push");
     }
 }
+
+#[cfg(test)]
+mod browse_loop_tests {
+    use super::tests::{timer_off, TEST_DEBOUNCE, TEST_DIMS};
+    use super::*;
+    use crate::git::FileStatus;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use std::cell::RefCell;
+    use testcolor::strip_ansi;
+
+    fn row(path: &str, staged: bool) -> RenderEntry {
+        RenderEntry {
+            path: path.to_string(),
+            orig_path: None,
+            status: FileStatus::Modified,
+            staged,
+            adds: 1,
+            dels: 0,
+            binary: false,
+            age: None,
+        }
+    }
+
+    /// Two unstaged rows and one staged, in the order the render draws them.
+    fn snapshot_with_files() -> Snapshot {
+        Snapshot {
+            branch: "main".into(),
+            base: "main".into(),
+            commits_ahead: 0,
+            commits_behind: 0,
+            files: vec![row("a.txt", false), row("b.txt", false), row("c.txt", true)],
+            log: Vec::new(),
+            upstream: None,
+            operation: None,
+            push_remote: None,
+            reflog: Vec::new(),
+            stashes: Vec::new(),
+            submodules: Vec::new(),
+        }
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    /// What one loop run observed.
+    #[derive(Default)]
+    struct Seen {
+        jobs: Vec<Job>,
+        /// The row the render was asked to highlight, frame by frame.
+        highlighted: Vec<Option<usize>>,
+        diffs: Vec<String>,
+    }
+
+    /// Run the loop over a pre-loaded queue of keys — which must end with
+    /// [`Event::Quit`] — against [`snapshot_with_files`], and report the last
+    /// painted screen plus what the hooks saw. The render draws every file row,
+    /// the way the real one does with no cap.
+    fn run_keys(events: Vec<Event>) -> (String, Seen) {
+        let (tx, rx) = mpsc::channel();
+        for event in events {
+            tx.send(event).expect("queue event");
+        }
+        let seen = RefCell::new(Seen::default());
+        let mut displayed = String::new();
+        let base = Instant::now();
+        let snapshot = snapshot_with_files();
+        let rows = snapshot.files.iter().map(RowKey::of).collect();
+
+        event_loop(
+            &rx,
+            TEST_DEBOUNCE,
+            &mut displayed,
+            LoopStart {
+                cache: SnapshotCache {
+                    snapshot,
+                    collected_at: base,
+                    dims: TEST_DIMS,
+                },
+                freshest: None,
+                schedule: WalkSchedule::unscheduled(),
+                ui: PushUi::new(false),
+                rows,
+            },
+            LoopHooks {
+                collect: |_changed: &Changed| Ok(snapshot_with_files()),
+                render: |snap: &Snapshot,
+                         _dims: Dimensions,
+                         _timing: FrameTiming,
+                         cursor: Option<&Cursor>| {
+                    let rows: Vec<RowKey> = snap.files.iter().map(RowKey::of).collect();
+                    seen.borrow_mut()
+                        .highlighted
+                        .push(cursor.and_then(|cursor| cursor.resolve(&rows)));
+                    Render {
+                        output: "FRAME".to_string(),
+                        freshest_age: None,
+                        rows,
+                    }
+                },
+                dimensions: || TEST_DIMS,
+                paint: |_output: &str| Ok(()),
+                clock: move || base,
+                next_tick: timer_off,
+                run_job: |job: Job| seen.borrow_mut().jobs.push(job),
+                diff: |entry: &RenderEntry| {
+                    seen.borrow_mut().diffs.push(entry.path.clone());
+                    Ok(vec!["@@ -1 +1 @@".to_string(), format!("+{}", entry.path)])
+                },
+            },
+        )
+        .expect("loop");
+
+        (strip_ansi(&displayed), seen.into_inner())
+    }
+
+    fn file_args(job: &Job) -> &[String] {
+        match job {
+            Job::File(command) => command.args(),
+            other => panic!("expected a file action, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn j_moves_the_highlight_and_s_stages_the_row_under_it() {
+        let (_, seen) = run_keys(vec![
+            key(KeyCode::Char('j')),
+            key(KeyCode::Char('j')),
+            key(KeyCode::Char('s')),
+            Event::Quit,
+        ]);
+        assert_eq!(seen.highlighted.last(), Some(&Some(1)));
+        assert_eq!(seen.jobs.len(), 1, "one key, one job");
+        let args = file_args(&seen.jobs[0]);
+        assert_eq!(args[0], "add");
+        assert_eq!(args.last().map(String::as_str), Some(":(literal)b.txt"));
+    }
+
+    #[test]
+    fn space_unstages_a_staged_row() {
+        // `k` from no cursor starts at the bottom, which is the staged row.
+        let (_, seen) = run_keys(vec![
+            key(KeyCode::Char('k')),
+            key(KeyCode::Char(' ')),
+            Event::Quit,
+        ]);
+        let args = file_args(&seen.jobs[0]);
+        assert_eq!(&args[..2], ["reset", "-q"]);
+        assert_eq!(args.last().map(String::as_str), Some(":(literal)c.txt"));
+    }
+
+    #[test]
+    fn a_file_key_with_no_cursor_says_how_to_get_one() {
+        let (screen, seen) = run_keys(vec![key(KeyCode::Char('s')), Event::Quit]);
+        assert!(seen.jobs.is_empty(), "nothing may run: {:?}", seen.jobs);
+        assert!(screen.contains(NO_SELECTION), "got {screen:?}");
+    }
+
+    #[test]
+    fn discard_waits_for_a_yes() {
+        let (screen, seen) = run_keys(vec![
+            key(KeyCode::Char('j')),
+            key(KeyCode::Char('d')),
+            Event::Quit,
+        ]);
+        assert!(
+            seen.jobs.is_empty(),
+            "d alone must not discard: {:?}",
+            seen.jobs
+        );
+        assert!(
+            screen.contains("a.txt?"),
+            "the question names the file: {screen:?}"
+        );
+
+        let (_, seen) = run_keys(vec![
+            key(KeyCode::Char('j')),
+            key(KeyCode::Char('d')),
+            key(KeyCode::Char('y')),
+            Event::Quit,
+        ]);
+        assert_eq!(seen.jobs.len(), 1);
+        assert_eq!(file_args(&seen.jobs[0])[0], "restore");
+    }
+
+    #[test]
+    fn enter_opens_the_diff_of_the_highlighted_row_and_q_closes_it() {
+        let (screen, seen) = run_keys(vec![
+            key(KeyCode::Char('j')),
+            key(KeyCode::Char('j')),
+            key(KeyCode::Enter),
+            Event::Quit,
+        ]);
+        assert_eq!(
+            seen.diffs,
+            ["b.txt"],
+            "one diff read, of the row the burst ended on"
+        );
+        assert!(screen.starts_with("b.txt • unstaged"), "got {screen:?}");
+        assert!(screen.contains("+b.txt"), "got {screen:?}");
+
+        // `q` inside the diff closes it rather than quitting, so the frame is
+        // what the final paint shows.
+        let (screen, _) = run_keys(vec![
+            key(KeyCode::Char('j')),
+            key(KeyCode::Enter),
+            key(KeyCode::Char('q')),
+            Event::Quit,
+        ]);
+        assert_eq!(screen, "FRAME");
+    }
+}
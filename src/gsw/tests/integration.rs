@@ -918,3 +918,184 @@ fn a_future_dated_commit_renders_an_unknown_log_age_not_zero_seconds() {
         "an unresolvable commit age must not be misrepresented as 0s: {row:?}",
     );
 }
+
+#[test]
+fn several_repo_paths_render_one_titled_block_each() {
+    let root = tempfile::tempdir().expect("tempdir");
+    let api = setup_repo();
+    let web = setup_repo();
+    fs::write(api.path().join("api.rs"), "fn main() {}\n").unwrap();
+    run_git(web.path(), &["checkout", "-q", "-b", "redesign"]);
+    fs::write(web.path().join("web.css"), "body {}\n").unwrap();
+
+    let out = run_gsw_args(
+        root.path(),
+        &[api.path().to_str().unwrap(), web.path().to_str().unwrap()],
+    );
+    let api_title = out
+        .lines()
+        .position(|line| line == api.path().display().to_string())
+        .unwrap_or_else(|| panic!("the first repo should have a title row:\n{out}"));
+    let web_title = out
+        .lines()
+        .position(|line| line == web.path().display().to_string())
+        .unwrap_or_else(|| panic!("the second repo should have a title row:\n{out}"));
+    assert!(
+        api_title < web_title,
+        "blocks keep command-line order:\n{out}"
+    );
+    assert!(out.contains("api.rs") && out.contains("web.css"), "{out}");
+    assert!(
+        out.contains("redesign"),
+        "each block has its own header:\n{out}"
+    );
+}
+
+#[test]
+fn a_single_repo_path_renders_that_repo_without_a_title() {
+    let elsewhere = tempfile::tempdir().expect("tempdir");
+    let repo = setup_repo();
+    fs::write(repo.path().join("only.txt"), "x\n").unwrap();
+
+    let out = run_gsw_args(elsewhere.path(), &[repo.path().to_str().unwrap()]);
+    assert!(
+        out.starts_with("gsw • main"),
+        "classic frame expected:\n{out}"
+    );
+    assert!(out.contains("only.txt"), "{out}");
+}
+
+#[test]
+fn discover_finds_every_repository_under_a_directory() {
+    let root = tempfile::tempdir().expect("tempdir");
+    for name in ["alpha", "beta"] {
+        let repo = setup_repo();
+        run_git(
+            repo.path(),
+            &["clone", "-q", ".", root.path().join(name).to_str().unwrap()],
+        );
+    }
+
+    let out = run_gsw_args(root.path(), &["--discover", "."]);
+    let titles: Vec<&str> = out
+        .lines()
+        .filter(|line| *line == "alpha" || *line == "beta")
+        .collect();
+    assert_eq!(titles, ["alpha", "beta"], "got:\n{out}");
+}
+
+#[test]
+fn discover_with_nothing_to_find_says_so_and_exits_zero() {
+    let root = tempfile::tempdir().expect("tempdir");
+    let out = run_gsw_args(root.path(), &["--discover", "."]);
+    assert!(out.contains("no git repositories under ."), "got:\n{out}");
+}
+
+fn parse_json(text: &str) -> serde_json::Value {
+    serde_json::from_str(text).unwrap_or_else(|e| panic!("not JSON ({e}):\n{text}"))
+}
+
+#[test]
+fn format_json_describes_the_repository_and_exits() {
+    let repo = setup_repo();
+    fs::write(repo.path().join("a.txt"), "initial\nchanged\n").unwrap();
+    fs::write(repo.path().join("new.txt"), "x\n").unwrap();
+
+    let value = parse_json(&run_gsw_args(repo.path(), &["--format", "json"]));
+    assert_eq!(value["branch"], "main");
+    assert_eq!(value["log"][0]["subject"], "initial");
+    let files = value["files"].as_array().expect("files is an array");
+    let status_of = |path: &str| {
+        files
+            .iter()
+            .find(|file| file["path"] == path)
+            .map(|file| file["status"].clone())
+    };
+    assert_eq!(status_of("a.txt"), Some("modified".into()), "{value}");
+    assert_eq!(status_of("new.txt"), Some("untracked".into()), "{value}");
+}
+
+#[test]
+fn format_ndjson_with_one_shot_prints_a_single_line() {
+    let repo = setup_repo();
+    let out = run_gsw_args(repo.path(), &["--format", "ndjson", "--one-shot"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 1, "got:\n{out}");
+    assert_eq!(parse_json(lines[0])["branch"], "main");
+}
+
+#[test]
+fn format_ndjson_streams_a_new_line_when_the_repository_changes() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let repo = setup_repo();
+    let mut child = gsw_command(repo.path())
+        .args(["--format", "ndjson"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to invoke gsw");
+    let stdout = child.stdout.take().expect("piped stdout");
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if tx.send(line.expect("readable stdout")).is_err() {
+                break;
+            }
+        }
+    });
+
+    let first = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("the first line arrives without any change");
+    assert_eq!(parse_json(&first)["files"], serde_json::json!([]));
+
+    fs::write(repo.path().join("appeared.txt"), "x\n").unwrap();
+    let next = rx.recv_timeout(Duration::from_secs(10));
+    let _ = child.kill();
+    let _ = child.wait();
+
+    let next = next.expect("a change writes another line");
+    assert_eq!(parse_json(&next)["files"][0]["path"], "appeared.txt");
+}
+
+#[test]
+fn format_json_outside_a_repository_fails_rather_than_printing_a_header() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let parent = dir.path().parent().unwrap_or(Path::new("/"));
+    let output = gsw_command(dir.path())
+        .args(["--format", "json"])
+        .env("GIT_CEILING_DIRECTORIES", parent)
+        .output()
+        .expect("failed to invoke gsw");
+    assert!(!output.status.success(), "a script must see the failure");
+    assert!(output.stdout.is_empty(), "nothing a parser could misread");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("not a git repository"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr),
+    );
+}
+
+#[test]
+fn reflog_and_stash_sections_appear_only_when_asked_for() {
+    let repo = setup_repo();
+    let p = repo.path();
+    fs::write(p.join("a.txt"), "parked\n").unwrap();
+    run_git(p, &["stash", "push", "-q", "-m", "parked work"]);
+    run_git(p, &["checkout", "-q", "-b", "topic"]);
+
+    let plain = run_gsw(p);
+    assert!(!plain.contains("stash@{"), "off by default:\n{plain}");
+    assert!(!plain.contains("HEAD@{"), "off by default:\n{plain}");
+
+    let out = run_gsw_args(p, &["--stash-lines", "3", "--reflog-lines", "2"]);
+    assert!(
+        out.contains("HEAD@{0}  checkout: moving from main to topic"),
+        "{out}"
+    );
+    assert!(out.contains("stash@{0}  On main: parked work"), "{out}");
+    assert!(!out.contains("HEAD@{2}"), "capped at two moves:\n{out}");
+}
@@ -0,0 +1,308 @@
+//! The comparison of two runs, TTL by TTL.
+//!
+//! Each TTL that either run answered at takes one line. The line names the
+//! addresses that answered in the baseline and in the run, their mean round
+//! trip times, and their losses, and it ends with what changed. A change of
+//! the addresses is a change of the path. A mean that grew by both a margin in
+//! milliseconds and a margin in percent is a regression, and a mean that shrank
+//! by both is an improvement. The two margins together keep the noise of a
+//! fast hop and the noise of a slow hop from reading as a change: a hop at
+//! 1 ms that moves by 2 ms moved by no margin in milliseconds, and a hop at
+//! 200 ms that moves by 10 ms moved by no margin in percent.
+//!
+//! A load balancer can answer at one TTL from several addresses, and the two
+//! runs can hear them in another order. The comparison reads the addresses of
+//! a TTL as a set, so a new order of the same routers is no change.
+
+use crate::stats::{HopRow, PathStats};
+use crate::table::{aligned, NO_ANSWER};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::net::IpAddr;
+
+/// The title of each column.
+const HEADERS: [&str; COLUMNS] = [
+    "TTL",
+    "Baseline",
+    "Run",
+    "Base Avg",
+    "Avg",
+    "Base Loss%",
+    "Loss%",
+    "Change",
+];
+
+/// The number of columns.
+const COLUMNS: usize = 8;
+
+/// The columns that hold text and not a number.
+const TEXT_COLUMNS: [usize; 3] = [1, 2, 7];
+
+/// The text between two addresses of one TTL.
+const ADDRESS_SEPARATOR: &str = " ";
+
+/// The text between two changes of one TTL.
+const CHANGE_SEPARATOR: &str = ", ";
+
+/// The least growth of a mean, in milliseconds, that counts as a regression.
+const SLOWER_MS: f64 = 5.0;
+
+/// The least growth of a mean, in percent of the baseline, that counts as a
+/// regression.
+const SLOWER_PERCENT: f64 = 20.0;
+
+/// The least growth of a loss, in points of percent, that counts as a change.
+const LOSS_POINTS: f64 = 10.0;
+
+/// The number of parts in one whole, for a change in percent.
+const PERCENT: f64 = 100.0;
+
+/// The change of a TTL whose addresses differ.
+const PATH_CHANGED: &str = "path changed";
+
+/// The change of a TTL that answered in the baseline and not in the run.
+const NO_LONGER_ANSWERS: &str = "no longer answers";
+
+/// The change of a TTL that answered in the run and not in the baseline.
+const NOW_ANSWERS: &str = "now answers";
+
+/// The change of a TTL whose mean grew past both margins.
+const SLOWER: &str = "slower";
+
+/// The change of a TTL whose mean shrank past both margins.
+const FASTER: &str = "faster";
+
+/// The change of a TTL whose loss grew past its margin.
+const MORE_LOSS: &str = "more loss";
+
+/// The change of a TTL whose loss shrank past its margin.
+const LESS_LOSS: &str = "less loss";
+
+/// Every change, in the order that the last line counts them.
+const CHANGES: [&str; 7] = [
+    PATH_CHANGED,
+    NO_LONGER_ANSWERS,
+    NOW_ANSWERS,
+    SLOWER,
+    FASTER,
+    MORE_LOSS,
+    LESS_LOSS,
+];
+
+/// The last line of a comparison that found no change.
+const NO_CHANGE: &str = "no TTL changed";
+
+/// Writes the comparison of a run against its baseline.
+///
+/// The table comes first. The last line counts the TTLs that changed, and
+/// names how many of them changed their path, slowed, and lost more probes.
+pub(crate) fn compare(baseline: &PathStats, run: &PathStats) -> String {
+    let mut ttls: BTreeMap<u8, (Option<HopRow>, Option<HopRow>)> = BTreeMap::new();
+    for row in baseline.rows() {
+        let ttl = row.ttl;
+        ttls.entry(ttl).or_default().0 = Some(row);
+    }
+    for row in run.rows() {
+        let ttl = row.ttl;
+        ttls.entry(ttl).or_default().1 = Some(row);
+    }
+    let mut lines = Vec::new();
+    let mut tally: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut changed = 0_usize;
+    for (ttl, (before, after)) in &ttls {
+        let found = changes(before.as_ref(), after.as_ref());
+        if !found.is_empty() {
+            changed += 1;
+        }
+        for change in &found {
+            *tally.entry(change).or_default() += 1;
+        }
+        let ms = |row: Option<&HopRow>| {
+            row.and_then(|row| row.avg)
+                .map_or_else(String::new, |avg| format!("{avg:.1}"))
+        };
+        let loss =
+            |row: Option<&HopRow>| row.map_or_else(String::new, |row| format!("{:.1}", row.loss));
+        lines.push([
+            ttl.to_string(),
+            addresses(before.as_ref()),
+            addresses(after.as_ref()),
+            ms(before.as_ref()),
+            ms(after.as_ref()),
+            loss(before.as_ref()),
+            loss(after.as_ref()),
+            found.join(CHANGE_SEPARATOR),
+        ]);
+    }
+    let mut out = aligned(&HEADERS, &lines, &TEXT_COLUMNS);
+    out.push('\n');
+    if changed == 0 {
+        out.push_str(NO_CHANGE);
+    } else {
+        let plural = if changed == 1 { "" } else { "s" };
+        let _ = write!(out, "{changed} TTL{plural} changed: ");
+        let counts: Vec<String> = CHANGES
+            .iter()
+            .filter_map(|change| tally.get(change).map(|count| format!("{count} {change}")))
+            .collect();
+        out.push_str(&counts.join(CHANGE_SEPARATOR));
+    }
+    out.push('\n');
+    out
+}
+
+/// The addresses that answered at one TTL, the last one first.
+fn addresses(row: Option<&HopRow>) -> String {
+    let addrs: Vec<String> = row
+        .map(|row| {
+            row.responders
+                .iter()
+                .map(|(addr, _)| addr.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    if addrs.is_empty() {
+        NO_ANSWER.to_owned()
+    } else {
+        addrs.join(ADDRESS_SEPARATOR)
+    }
+}
+
+/// What changed at one TTL between the baseline and the run.
+///
+/// A TTL that one run never reached holds no line of that run, and it reads
+/// as a TTL that never answered there.
+fn changes(before: Option<&HopRow>, after: Option<&HopRow>) -> Vec<&'static str> {
+    let set = |row: Option<&HopRow>| -> BTreeSet<IpAddr> {
+        row.map(|row| row.responders.iter().map(|(addr, _)| *addr).collect())
+            .unwrap_or_default()
+    };
+    let (was, is) = (set(before), set(after));
+    let mut found = Vec::new();
+    match (was.is_empty(), is.is_empty()) {
+        (false, true) => found.push(NO_LONGER_ANSWERS),
+        (true, false) => found.push(NOW_ANSWERS),
+        (false, false) if was != is => found.push(PATH_CHANGED),
+        _ => {}
+    }
+    if let (Some(was), Some(is)) = (
+        before.and_then(|row| row.avg),
+        after.and_then(|row| row.avg),
+    ) {
+        let margin = |from: f64, to: f64| {
+            to - from >= SLOWER_MS && (to - from) / from * PERCENT >= SLOWER_PERCENT
+        };
+        if margin(was, is) {
+            found.push(SLOWER);
+        } else if margin(is, was) {
+            found.push(FASTER);
+        }
+    }
+    if let (Some(was), Some(is)) = (before.map(|row| row.loss), after.map(|row| row.loss)) {
+        if is - was >= LOSS_POINTS {
+            found.push(MORE_LOSS);
+        } else if was - is >= LOSS_POINTS {
+            found.push(LESS_LOSS);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, NO_CHANGE};
+    use crate::record::{Hop, RoundRecord, RunId, TtlRange};
+    use crate::stats::PathStats;
+    use chrono::Utc;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// The router at TTL 1.
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// The router that takes over TTL 1 in some tests.
+    const OTHER_ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    /// The target, at TTL 2.
+    const TARGET: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    /// Folds one round over TTLs 1 and 2 from its hops.
+    fn run_of(hops: &[(u8, IpAddr, f64)]) -> PathStats {
+        let mut stats = PathStats::default();
+        stats.add_round(&RoundRecord {
+            run: RunId::from("2026-08-18T12:00:00.000Z"),
+            seq: 1,
+            ts: Utc::now(),
+            dur_ms: 10,
+            ttl_range: TtlRange::new(1, 2).unwrap_or_else(|error| panic!("{error}")),
+            reached: true,
+            hops: hops
+                .iter()
+                .map(|(ttl, addr, rtt_ms)| Hop {
+                    ttl: *ttl,
+                    addr: *addr,
+                    rtt_ms: *rtt_ms,
+                    icmp: "time_exceeded".to_owned(),
+                    flow: None,
+                })
+                .collect(),
+            flows: None,
+        });
+        stats
+    }
+
+    /// The change column of the line of one TTL.
+    fn change_of(text: &str, ttl: u8) -> String {
+        let line = text
+            .lines()
+            .find(|line| line.split_whitespace().next() == Some(&ttl.to_string()))
+            .unwrap_or_else(|| panic!("no line for TTL {ttl} in {text}"));
+        line.split("  ")
+            .filter(|cell| !cell.is_empty())
+            .last()
+            .unwrap_or_default()
+            .trim()
+            .to_owned()
+    }
+
+    #[test]
+    fn two_equal_runs_change_nothing() {
+        let run = run_of(&[(1, ROUTER, 1.0), (2, TARGET, 10.0)]);
+        let text = compare(&run, &run);
+        assert!(text.ends_with(&format!("{NO_CHANGE}\n")), "{text}");
+    }
+
+    #[test]
+    fn a_new_router_is_a_change_of_the_path() {
+        let baseline = run_of(&[(1, ROUTER, 1.0), (2, TARGET, 10.0)]);
+        let run = run_of(&[(1, OTHER_ROUTER, 1.0), (2, TARGET, 10.0)]);
+        let text = compare(&baseline, &run);
+        assert_eq!(change_of(&text, 1), "path changed");
+        assert!(text.contains("10.0.0.1   10.0.0.2"), "{text}");
+        assert!(text.ends_with("1 TTL changed: 1 path changed\n"), "{text}");
+    }
+
+    // The target moved from 10 ms to 30 ms, past both margins. The router
+    // moved from 1 ms to 3 ms, which is 200 percent but under 5 ms.
+    #[test]
+    fn a_mean_that_grew_past_both_margins_is_slower() {
+        let baseline = run_of(&[(1, ROUTER, 1.0), (2, TARGET, 10.0)]);
+        let run = run_of(&[(1, ROUTER, 3.0), (2, TARGET, 30.0)]);
+        let text = compare(&baseline, &run);
+        assert_eq!(change_of(&text, 2), "slower");
+        assert!(text.ends_with("1 TTL changed: 1 slower\n"), "{text}");
+        let text = compare(&run, &baseline);
+        assert_eq!(change_of(&text, 2), "faster");
+    }
+
+    #[test]
+    fn a_ttl_that_stopped_answering_loses_every_probe() {
+        let baseline = run_of(&[(1, ROUTER, 1.0), (2, TARGET, 10.0)]);
+        let run = run_of(&[(2, TARGET, 10.0)]);
+        let text = compare(&baseline, &run);
+        assert_eq!(change_of(&text, 1), "no longer answers, more loss");
+        assert!(
+            text.ends_with("1 TTL changed: 1 no longer answers, 1 more loss\n"),
+            "{text}"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Reverse DNS for the addresses of a trace, off the path of the probes.
+//!
+//! A lookup can take seconds when a name server does not answer, and a round
+//! never waits for one. One thread of its own reads each address, asks the
+//! system resolver for its name, and hands back each name it found. The
+//! tracer asks once for each address it sees, and it reads the answers between
+//! two rounds.
+//!
+//! The system resolver reads `/etc/hosts` and every other source that the
+//! system names, so a trace shows the names that every other tool on the
+//! machine shows. An address with no name gives no answer, and the table shows
+//! the address alone.
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// The names of the addresses of one trace.
+pub(crate) struct Resolver {
+    /// The addresses that the tracer asked for, so each one is asked once.
+    asked: HashSet<IpAddr>,
+    /// The channel to the thread that looks the names up.
+    questions: Sender<IpAddr>,
+    /// The channel of the names that the thread found.
+    answers: Receiver<(IpAddr, String)>,
+}
+
+impl Resolver {
+    /// Starts the thread that looks the names up. The thread ends when the
+    /// resolver drops.
+    pub(crate) fn start() -> Self {
+        let (questions, asked) = mpsc::channel::<IpAddr>();
+        let (found, answers) = mpsc::channel();
+        thread::spawn(move || {
+            for addr in asked {
+                if let Ok(host) = dns_lookup::lookup_addr(&addr) {
+                    if found.send((addr, host)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Self {
+            asked: HashSet::new(),
+            questions,
+            answers,
+        }
+    }
+
+    /// Asks for the name of an address. An address asked before is not asked
+    /// again.
+    pub(crate) fn ask(&mut self, addr: IpAddr) {
+        if self.asked.insert(addr) {
+            // The thread ends only when the resolver drops, so the send does
+            // not fail while the resolver lives.
+            let _ = self.questions.send(addr);
+        }
+    }
+
+    /// Takes every name that arrived since the last call.
+    pub(crate) fn answers(&self) -> Vec<(IpAddr, String)> {
+        self.answers.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resolver;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, Instant};
+
+    // The loopback address has a name in the hosts file of every unix system,
+    // so the test needs no network.
+    #[test]
+    fn the_loopback_address_comes_back_with_its_name() {
+        let mut resolver = Resolver::start();
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        resolver.ask(loopback);
+        resolver.ask(loopback);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut names = Vec::new();
+        while names.is_empty() && Instant::now() < deadline {
+            names = resolver.answers();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(names.len(), 1, "one address asked twice gives one name");
+        assert_eq!(names[0].0, loopback);
+        assert!(!names[0].1.is_empty());
+    }
+}
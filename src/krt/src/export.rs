@@ -0,0 +1,325 @@
+//! The export of one recorded run, as CSV text or as a Graphviz DOT graph.
+//!
+//! The CSV holds the table of statistics, for a spreadsheet or a script. It
+//! has one line for each address that answered at a TTL, and the TTL repeats
+//! on every line, so each line stands alone when a reader sorts or filters
+//! them. The statistics belong to the TTL and not to one address, so they ride
+//! on the first line of the TTL alone, and a sum over a column counts each TTL
+//! once. The numbers keep three decimal places, where the table keeps one.
+//!
+//! The DOT graph holds the path. Each address that answered is a node, and an
+//! edge joins two hops that one probe flow met at two TTLs in a row. The label
+//! of an edge counts the walks that took it, so the edges of a load balancer
+//! show how the flows split between its branches. An edge that skips a TTL
+//! that never answered is dashed, because the graph cannot know which router
+//! stood in the gap.
+
+use crate::record::{NameRecord, RoundRecord, Run};
+use crate::stats::PathStats;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::net::IpAddr;
+
+/// The first line of the CSV export.
+const CSV_HEADER: &str =
+    "ttl,address,name,loss_pct,sent,last_ms,avg_ms,best_ms,worst_ms,stddev_ms,jitter_ms";
+
+/// The identifier of the node of the source in the DOT graph.
+///
+/// Every other node is named by an address, and no address reads as this
+/// word, so the source never merges with a hop.
+const SOURCE_NODE: &str = "source";
+
+/// The label of the source node, when the run names no source address.
+const SOURCE_LABEL: &str = "source";
+
+/// The text that introduces the TTLs of a node in its label.
+const TTL_LABEL: &str = "ttl";
+
+/// Writes the table of statistics of a run as CSV text.
+pub(crate) fn csv(stats: &PathStats) -> String {
+    let ms = |value: Option<f64>| value.map_or_else(String::new, |value| format!("{value:.3}"));
+    let mut out = format!("{CSV_HEADER}\n");
+    for row in stats.rows() {
+        let mut statistics = vec![
+            format!("{:.3}", row.loss),
+            row.sent.to_string(),
+            ms(row.last),
+            ms(row.avg),
+            ms(row.best),
+            ms(row.worst),
+            ms(row.stddev),
+            ms(row.jitter),
+        ];
+        let mut line = |address: String, name: &str, statistics: &[String]| {
+            let mut fields = vec![row.ttl.to_string(), address, csv_field(name)];
+            fields.extend_from_slice(statistics);
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        };
+        if row.responders.is_empty() {
+            line(String::new(), "", &statistics);
+        }
+        for (addr, name) in &row.responders {
+            line(addr.to_string(), name.as_deref().unwrap_or(""), &statistics);
+            // The lines after the first one carry no statistics.
+            statistics.iter_mut().for_each(String::clear);
+        }
+    }
+    out
+}
+
+/// Quotes one field of CSV text when it holds a comma, a quote, or a line
+/// break, and doubles each quote inside it, as RFC 4180 asks.
+///
+/// A name comes from reverse DNS, and the DNS does not stop a name from
+/// holding any of the three.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Writes the path of a run as a Graphviz DOT graph.
+pub(crate) fn dot(run: &Run<'_>) -> String {
+    let start = run.start();
+    graph(
+        start.map(|start| start.source.addr),
+        start.map(|start| start.target.addr),
+        run.rounds(),
+        run.names(),
+    )
+}
+
+/// Writes the graph of the rounds of a run.
+///
+/// The target, when the run names one, takes a double border.
+fn graph(
+    source: Option<IpAddr>,
+    target: Option<IpAddr>,
+    rounds: &[&RoundRecord],
+    names: &[&NameRecord],
+) -> String {
+    let names: HashMap<IpAddr, &str> = names
+        .iter()
+        .map(|name| (name.addr, name.host.as_str()))
+        .collect();
+    // The TTLs at which each address answered.
+    let mut nodes: BTreeMap<IpAddr, BTreeSet<u8>> = BTreeMap::new();
+    // Each edge, from the source or from an address, with the number of walks
+    // that took it and the fewest TTLs that one walk of it spanned.
+    let mut edges: BTreeMap<(Option<IpAddr>, IpAddr), (u64, u8)> = BTreeMap::new();
+    for round in rounds {
+        let mut flows: BTreeMap<Option<u16>, Vec<(u8, IpAddr)>> = BTreeMap::new();
+        for hop in &round.hops {
+            nodes.entry(hop.addr).or_default().insert(hop.ttl);
+            flows.entry(hop.flow).or_default().push((hop.ttl, hop.addr));
+        }
+        for mut walk in flows.into_values() {
+            walk.sort_unstable();
+            // The source stands one TTL below the first TTL of the round, so
+            // a round whose first TTL never answered starts with a gap.
+            let mut from = (None, round.ttl_range.first().saturating_sub(1));
+            for (ttl, addr) in walk {
+                let span = ttl.saturating_sub(from.1);
+                let edge = edges.entry((from.0, addr)).or_insert((0, span));
+                edge.0 += 1;
+                edge.1 = edge.1.min(span);
+                from = (Some(addr), ttl);
+            }
+        }
+    }
+
+    let mut out = String::from("digraph krt {\n  rankdir=LR;\n  node [shape=box];\n");
+    let source_label = source.map_or_else(|| SOURCE_LABEL.to_owned(), |addr| addr.to_string());
+    let _ = writeln!(
+        out,
+        "  {} [label={}, shape=ellipse];",
+        quoted(SOURCE_NODE),
+        quoted(&source_label)
+    );
+    for (addr, ttls) in &nodes {
+        let ttls: Vec<String> = ttls.iter().map(u8::to_string).collect();
+        let mut label = vec![addr.to_string()];
+        if let Some(name) = names.get(addr) {
+            label.push((*name).to_owned());
+        }
+        label.push(format!("{TTL_LABEL} {}", ttls.join(", ")));
+        let border = if Some(*addr) == target {
+            ", peripheries=2"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  {} [label={}{border}];",
+            quoted(&addr.to_string()),
+            multiline(&label)
+        );
+    }
+    for ((from, to), (walks, span)) in &edges {
+        let from = from.map_or_else(|| SOURCE_NODE.to_owned(), |addr| addr.to_string());
+        let style = if *span > 1 { ", style=dashed" } else { "" };
+        let _ = writeln!(
+            out,
+            "  {} -> {} [label={}{style}];",
+            quoted(&from),
+            quoted(&to.to_string()),
+            quoted(&walks.to_string())
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Writes a quoted DOT string, with each backslash and each quote escaped.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", escaped(text))
+}
+
+/// Writes a quoted DOT string whose lines Graphviz centers one above another.
+fn multiline(lines: &[String]) -> String {
+    let lines: Vec<String> = lines.iter().map(|line| escaped(line)).collect();
+    format!("\"{}\"", lines.join("\\n"))
+}
+
+/// Escapes each backslash and each quote of a DOT string.
+fn escaped(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv, csv_field, graph, CSV_HEADER};
+    use crate::record::{Hop, NameRecord, RoundRecord, RunId, TtlRange};
+    use crate::stats::PathStats;
+    use chrono::Utc;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// The identifier of the run of every test record.
+    const RUN: &str = "2026-08-18T12:00:00.000Z";
+
+    /// The address that the probes leave from.
+    const SOURCE: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+
+    /// The router at TTL 1.
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// A second router at TTL 1, behind a load balancer.
+    const OTHER_ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    /// The target, at TTL 3.
+    const TARGET: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    /// Builds one round over TTLs 1 to 3 from its hops.
+    fn round(seq: u64, hops: &[(u8, IpAddr, Option<u16>)]) -> RoundRecord {
+        RoundRecord {
+            run: RunId::from(RUN),
+            seq,
+            ts: Utc::now(),
+            dur_ms: 10,
+            ttl_range: TtlRange::new(1, 3).unwrap_or_else(|error| panic!("{error}")),
+            reached: hops.iter().any(|(_, addr, _)| *addr == TARGET),
+            hops: hops
+                .iter()
+                .map(|(ttl, addr, flow)| Hop {
+                    ttl: *ttl,
+                    addr: *addr,
+                    rtt_ms: 1.5,
+                    icmp: "time_exceeded".to_owned(),
+                    flow: *flow,
+                })
+                .collect(),
+            flows: None,
+        }
+    }
+
+    /// The name of the router.
+    fn router_name(host: &str) -> NameRecord {
+        NameRecord {
+            run: RunId::from(RUN),
+            ts: Utc::now(),
+            addr: ROUTER,
+            host: host.to_owned(),
+        }
+    }
+
+    #[test]
+    fn a_field_that_holds_a_comma_or_a_quote_is_quoted() {
+        assert_eq!(csv_field("gw.example.net"), "gw.example.net");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn the_csv_holds_a_line_for_each_responder_and_the_statistics_once() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, &[(1, ROUTER, None), (3, TARGET, None)]));
+        stats.add_round(&round(2, &[(1, OTHER_ROUTER, None)]));
+        stats.add_name(&router_name("gw,one"));
+        assert_eq!(
+            csv(&stats),
+            format!(
+                "{CSV_HEADER}\n\
+                 1,10.0.0.2,,0.000,2,1.500,1.500,1.500,1.500,0.000,0.000\n\
+                 1,10.0.0.1,\"gw,one\",,,,,,,,\n\
+                 2,,,100.000,2,,,,,,\n\
+                 3,192.0.2.1,,50.000,2,1.500,1.500,1.500,1.500,0.000,\n"
+            )
+        );
+    }
+
+    #[test]
+    fn the_graph_joins_each_flow_and_dashes_a_gap() {
+        let first = round(1, &[(1, ROUTER, None), (3, TARGET, None)]);
+        let second = round(2, &[(1, ROUTER, None), (3, TARGET, None)]);
+        let name = router_name("gw.example.net");
+        let text = graph(Some(SOURCE), Some(TARGET), &[&first, &second], &[&name]);
+        for expected in [
+            "\"source\" [label=\"192.168.1.10\", shape=ellipse];",
+            "\"10.0.0.1\" [label=\"10.0.0.1\\ngw.example.net\\nttl 1\"];",
+            "\"192.0.2.1\" [label=\"192.0.2.1\\nttl 3\", peripheries=2];",
+            "\"source\" -> \"10.0.0.1\" [label=\"2\"];",
+            "\"10.0.0.1\" -> \"192.0.2.1\" [label=\"2\", style=dashed];",
+        ] {
+            assert!(text.contains(expected), "{expected} missing from {text}");
+        }
+    }
+
+    // Two flows crossed a load balancer at TTL 1 and met again at TTL 2, so
+    // the graph holds both branches and no edge between the two routers.
+    #[test]
+    fn the_graph_keeps_the_branches_of_each_flow_apart() {
+        let walk = round(
+            1,
+            &[
+                (1, ROUTER, Some(0)),
+                (1, OTHER_ROUTER, Some(1)),
+                (2, TARGET, Some(0)),
+                (2, TARGET, Some(1)),
+            ],
+        );
+        let text = graph(None, None, &[&walk], &[]);
+        for expected in [
+            "\"source\" [label=\"source\", shape=ellipse];",
+            "\"10.0.0.1\" -> \"192.0.2.1\" [label=\"1\"];",
+            "\"10.0.0.2\" -> \"192.0.2.1\" [label=\"1\"];",
+        ] {
+            assert!(text.contains(expected), "{expected} missing from {text}");
+        }
+        assert!(
+            !text.contains("\"10.0.0.1\" -> \"10.0.0.2\""),
+            "two flows never join: {text}"
+        );
+    }
+
+    #[test]
+    fn a_quote_in_a_name_is_escaped_in_the_graph() {
+        let walk = round(1, &[(1, ROUTER, None)]);
+        let name = router_name("a\"b");
+        let text = graph(None, None, &[&walk], &[&name]);
+        assert!(text.contains("\\na\\\"b\\n"), "{text}");
+    }
+}
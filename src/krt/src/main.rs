@@ -1,21 +1,38 @@
 //! `krt` (Knights of the Round Trip) records the network path to a
 //! destination, hop by hop.
 //!
-//! No tracer exists yet, so a command line that names a destination prints the
-//! configuration that it resolved. The `replay` command reads a recorded file
-//! and prints one summary line for one run of it. Later slices add the tracer
-//! and the table.
+//! A command line that names a destination prints the configuration that it
+//! resolved, then traces the destination once per round and appends each round
+//! to the recorded file. A live table shows the statistics of each hop while
+//! the trace runs. The `replay` command reads a recorded file and prints the
+//! table of one run of it, exports the run as CSV or as a Graphviz graph, or
+//! compares the run hop by hop with a baseline run.
 
 // Stricter than the inherited `[workspace.lints]` set; see "Lint Configuration" in CLAUDE.md.
 #![deny(unsafe_code)]
 #![warn(clippy::pedantic)]
 
+mod diff;
+#[cfg(unix)]
+mod dns;
+mod export;
+#[cfg(unix)]
+mod packet;
+#[cfg(unix)]
+mod probe;
 mod record;
+mod stats;
+mod table;
+#[cfg(unix)]
+mod trace;
+#[cfg(unix)]
+mod ui;
 
 use buildinfo::version_string;
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
-use record::{Recording, Run, RunId};
+use record::{EndReason, Recording, Run, RunId};
 use serde::{Deserialize, Serialize};
+use stats::PathStats;
 use std::collections::BTreeSet;
 use std::fmt;
 use std::net::IpAddr;
@@ -94,6 +111,28 @@ const REACHED: &str = "reached";
 /// The last field of a summary line, when no round reached the target.
 const NEVER_REACHED: &str = "never reached";
 
+/// The field of a summary line, when the user stopped the run.
+const STOPPED_BY_QUIT: &str = "stopped by the user";
+
+/// The field of a summary line, when the time limit stopped the run.
+const STOPPED_BY_DURATION: &str = "stopped by the time limit";
+
+/// The field of a summary line, when the round limit stopped the run.
+const STOPPED_BY_ROUNDS: &str = "stopped by the round limit";
+
+/// The field of a summary line, when a fault stopped the run.
+const STOPPED_BY_ERROR: &str = "stopped by a fault";
+
+/// The field of a summary line, when the file holds no `end` record of the
+/// run. The run still goes, or a `kill -9` stopped it.
+const NO_END: &str = "no end record";
+
+/// What starts the summary line of the baseline in a comparison.
+const BASELINE_LABEL: &str = "baseline";
+
+/// What starts the summary line of the run in a comparison.
+const RUN_LABEL: &str = "run";
+
 /// The reason of a file that holds no run.
 ///
 /// A file that holds no run at all stops the message here. A `--run` that names
@@ -106,6 +145,10 @@ const THE_RUNS_OF_THE_FILE: &str = "The runs of this file are";
 /// The text between two run identifiers of a message.
 const RUN_LIST_SEPARATOR: &str = ", ";
 
+/// The reason of a trace on a system that has no tracer.
+#[cfg(not(unix))]
+const NO_TRACER: &str = "the tracer needs the sockets of a unix system, and this system has none";
+
 /// What a warning says about the rounds before a cut final line.
 const RECORDS_BEFORE_THE_CUT: &str = "The records before the cut still read.";
 
@@ -121,6 +164,17 @@ enum Protocol {
     Tcp,
 }
 
+/// The form of the report of a replay.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Print the summary line and the table of statistics.
+    Table,
+    /// Print the table of statistics as CSV.
+    Csv,
+    /// Print the path as a Graphviz DOT graph.
+    Dot,
+}
+
 /// The way a probe keeps or varies the flow of a packet.
 #[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -175,9 +229,9 @@ fn value_name<T: ValueEnum>(value: &T) -> String {
 ///
 /// `krt` probes every hop to the destination once per round, and it records
 /// each round in a file. The `replay` command reads a file that an earlier run
-/// wrote, so it takes no destination and no flag of a probe. This build prints
-/// the configuration that a trace resolved, because no tracer exists yet, and
-/// it prints one summary line for a replay.
+/// wrote, so it takes no destination and no flag of a probe. A trace shows a
+/// live table of the statistics of each hop, and a replay prints the same
+/// table for a recorded run.
 #[derive(Parser, Debug)]
 // `args_conflicts_with_subcommands` rejects a flag of a probe beside a command,
 // because a replay probes nothing. `subcommand_negates_reqs` lifts the demand
@@ -289,6 +343,18 @@ enum Command {
         /// Pick which run in the file to fold. The last run is the default.
         #[arg(long, value_name = "ID")]
         run: Option<String>,
+
+        /// The form of the report.
+        #[arg(long, value_name = "F", value_enum, default_value_t = Format::Table)]
+        format: Format,
+
+        /// Compare hop by hop with this run of the file.
+        #[arg(long, value_name = "ID", conflicts_with = "format")]
+        baseline: Option<String>,
+
+        /// Compare with a run of this file. Its last run is the default.
+        #[arg(long, value_name = "FILE", conflicts_with = "format")]
+        baseline_file: Option<PathBuf>,
     },
 }
 
@@ -329,6 +395,20 @@ struct ResolvedConfig {
     replay: Option<PathBuf>,
     /// The run in the recorded file to fold.
     run: Option<String>,
+    /// The form of the report of a replay.
+    format: Format,
+    /// The run to compare the replayed run with, when the replay compares.
+    baseline: Option<Baseline>,
+}
+
+/// The run that a replay compares its run with.
+#[derive(Debug, PartialEq, Eq)]
+struct Baseline {
+    /// The file that holds the baseline. An absent file is the file of the
+    /// replay.
+    file: Option<PathBuf>,
+    /// The baseline run. An absent run is the last run of the file.
+    run: Option<String>,
 }
 
 impl Cli {
@@ -336,9 +416,11 @@ impl Cli {
     ///
     /// The two flags of the address family collapse into one value, and the
     /// `--no-dns` switch becomes the behavior it controls. The `replay` command
-    /// becomes the recorded file and the run to fold, so every later slice
-    /// reads one flat configuration and never reads the shape of the command
-    /// line.
+    /// becomes the recorded file, the run to fold, the form of the report, and
+    /// the baseline, so every later slice reads one flat configuration and
+    /// never reads the shape of the command line. The two flags of the
+    /// baseline collapse into one value, because either one alone asks for a
+    /// comparison.
     ///
     /// # Errors
     ///
@@ -372,9 +454,22 @@ impl Cli {
             AddressFamily::Auto
         };
 
-        let (replay, run) = match self.command {
-            Some(Command::Replay { file, run }) => (Some(file), run),
-            None => (None, None),
+        let (replay, run, format, baseline) = match self.command {
+            Some(Command::Replay {
+                file,
+                run,
+                format,
+                baseline,
+                baseline_file,
+            }) => {
+                let baseline =
+                    (baseline.is_some() || baseline_file.is_some()).then_some(Baseline {
+                        file: baseline_file,
+                        run: baseline,
+                    });
+                (Some(file), run, format, baseline)
+            }
+            None => (None, None, Format::Table, None),
         };
 
         Ok(ResolvedConfig {
@@ -393,16 +488,19 @@ impl Cli {
             rounds: self.rounds,
             replay,
             run,
+            format,
+            baseline,
         })
     }
 }
 
 /// Writes the block that a trace prints before it probes.
 ///
-/// The block names no `replay` and no `run`. `main` prints the block only when
-/// the command line names no `replay`, and `resolve` fills the run only inside
-/// a `replay`, so neither field can reach the block with a value. A replay
-/// prints its own summary line in the place of the block.
+/// The block names no `replay`, no `run`, and nothing else of a replay. `main`
+/// prints the block only when the command line names no `replay`, and
+/// `resolve` fills the fields of a replay only inside a `replay`, so none of
+/// them can reach the block with a value. A replay prints its own report in
+/// the place of the block.
 impl fmt::Display for ResolvedConfig {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let path_or = |path: Option<&PathBuf>, absent: &str| {
@@ -561,21 +659,19 @@ fn counted(count: usize, name: &str) -> String {
     format!("{count} {name}{plural}")
 }
 
-/// Writes the one line that a replay prints for one run.
+/// Writes the line that names one run, above the table of a replay.
 ///
 /// The line holds the identifier of the run, the target, the number of rounds,
-/// the number of TTLs that answered, and whether the run reached the target.
-/// Two spaces separate the fields. A run whose `run` record is absent names no
-/// target, and the field then holds one word.
+/// the number of TTLs that answered, whether the run reached the target, and
+/// what stopped the run. Two spaces separate the fields. A run whose `run`
+/// record is absent names no target, and the field then holds one word. A run
+/// whose `end` record is absent says so in the last field, because the file
+/// cannot tell a run that still goes from a run that a `kill -9` stopped.
 ///
 /// A TTL counts once, however many rounds answered at it, so the count is the
 /// number of TTLs that answered and not the number of answers. A TTL that the
 /// run probed and that never answered counts for nothing, so the count is at or
 /// below the length of the path.
-///
-/// The design puts the fold in `stats.rs` and the render in `ui.rs`. This
-/// slice builds neither module, so the summary lives here. A later slice
-/// replaces this line with the aggregate table.
 fn summarize(run: &Run<'_>) -> String {
     let target = run.start().map_or_else(
         || TARGET_UNKNOWN.to_owned(),
@@ -592,12 +688,19 @@ fn summarize(run: &Run<'_>) -> String {
     } else {
         NEVER_REACHED
     };
+    let stopped = run.end().map_or(NO_END, |end| match end.reason {
+        EndReason::Quit => STOPPED_BY_QUIT,
+        EndReason::Duration => STOPPED_BY_DURATION,
+        EndReason::Rounds => STOPPED_BY_ROUNDS,
+        EndReason::Error => STOPPED_BY_ERROR,
+    });
     [
         run.id().to_string(),
         target,
         counted(run.rounds().len(), ROUND),
         counted(ttls.len(), HOP),
         reached.to_owned(),
+        stopped.to_owned(),
     ]
     .join(SUMMARY_SEPARATOR)
 }
@@ -623,52 +726,117 @@ fn no_run_message(path: &Path, wanted: Option<&str>, held: &[RunId]) -> String {
     }
 }
 
-/// Reads a recorded file and folds one run of it.
+/// Reads a recorded file and writes the report of one run of it.
 ///
 /// The run that `--run` names is the run to fold, and the last run of the file
-/// is the run to fold when the flag is absent.
+/// is the run to fold when the flag is absent. A baseline picks its run from
+/// its own file the same way, and the report is then the comparison of the two
+/// runs and not the table of one.
 ///
 /// A file that does not read at all, a file that holds no run, and a file that
-/// does not hold the run that `--run` names each give the reason in the
+/// does not hold the run that a flag names each give the reason in the
 /// outcome. The warning of a cut final line rides beside the outcome and not
 /// inside it, because a cut is often the reason that the file holds no run to
 /// fold: a `kill -9` during the first record leaves a file that holds no
 /// complete record, and such a file reads as an empty one until the warning
 /// says otherwise.
-fn replay(path: &Path, wanted: Option<&str>) -> Replay {
-    let recording = match Recording::read(path) {
-        Ok(recording) => recording,
-        Err(error) => {
-            return Replay {
-                warning: None,
-                outcome: Err(error.to_string()),
-            };
+fn replay(path: &Path, config: &ResolvedConfig) -> Replay {
+    let mut warnings = Vec::new();
+    let outcome = report(path, config, &mut warnings);
+    Replay { warnings, outcome }
+}
+
+/// Writes the report that the configuration asks for, and adds the warning of
+/// each file that it read.
+fn report(
+    path: &Path,
+    config: &ResolvedConfig,
+    warnings: &mut Vec<String>,
+) -> Result<String, String> {
+    let recording = read(path, warnings)?;
+    let run = pick(&recording, path, config.run.as_deref())?;
+    let Some(baseline) = &config.baseline else {
+        return Ok(match config.format {
+            Format::Table => format!(
+                "{}\n\n{}",
+                summarize(&run),
+                table::text(&PathStats::of(&run))
+            ),
+            Format::Csv => export::csv(&PathStats::of(&run)),
+            Format::Dot => export::dot(&run),
+        });
+    };
+    let other;
+    let (held, held_path) = match &baseline.file {
+        Some(file) => {
+            other = read(file, warnings)?;
+            (&other, file.as_path())
         }
+        None => (&recording, path),
     };
+    let base = pick(held, held_path, baseline.run.as_deref())?;
+    let width = BASELINE_LABEL.len();
+    Ok(format!(
+        "{BASELINE_LABEL:<width$}{SUMMARY_SEPARATOR}{}\n{RUN_LABEL:<width$}{SUMMARY_SEPARATOR}{}\n\n{}",
+        summarize(&base),
+        summarize(&run),
+        diff::compare(&PathStats::of(&base), &PathStats::of(&run))
+    ))
+}
+
+/// Reads a recorded file, and adds a warning when its final line is cut.
+fn read(path: &Path, warnings: &mut Vec<String>) -> Result<Recording, String> {
+    let recording = Recording::read(path).map_err(|error| error.to_string())?;
     // A `kill -9` leaves a file whose final line is cut short. Every round
     // before the cut still reads, so the replay reports the cut and goes on.
-    let warning = recording
-        .truncated()
-        .map(|truncated| format!("{}: {truncated}. {RECORDS_BEFORE_THE_CUT}", path.display()));
+    if let Some(truncated) = recording.truncated() {
+        warnings.push(format!(
+            "{}: {truncated}. {RECORDS_BEFORE_THE_CUT}",
+            path.display()
+        ));
+    }
+    Ok(recording)
+}
+
+/// Picks the run that a flag names, or the last run of the file.
+fn pick<'a>(
+    recording: &'a Recording,
+    path: &Path,
+    wanted: Option<&str>,
+) -> Result<Run<'a>, String> {
     let found = match wanted {
         Some(wanted) => recording.run(&RunId::from(wanted)),
         None => recording.last_run(),
     };
-    let outcome = match found {
-        Some(run) => Ok(summarize(&run)),
-        None => Err(no_run_message(path, wanted, &recording.run_ids())),
-    };
-    Replay { warning, outcome }
+    found.ok_or_else(|| no_run_message(path, wanted, &recording.run_ids()))
 }
 
-/// The warning that a recorded file raised, and what the replay of it found.
+/// The warnings that the recorded files raised, and what the replay of them
+/// found.
 struct Replay {
-    /// The warning about the file, when the file holds a cut final line.
-    warning: Option<String>,
-    /// The one line that names the run, or the reason that no run folds.
+    /// The warning about each file that holds a cut final line.
+    warnings: Vec<String>,
+    /// The report, or the reason that no run folds.
     outcome: Result<String, String>,
 }
 
+/// Runs the trace that the configuration names, and exits with a failure when
+/// a fault stops it.
+#[cfg(unix)]
+fn trace(config: &ResolvedConfig) {
+    if let Err(error) = trace::run(config) {
+        eprintln!("{PROGRAM}: {error}");
+        std::process::exit(EXIT_FAILURE);
+    }
+}
+
+/// Reports that this system has no tracer, and exits with a failure.
+#[cfg(not(unix))]
+fn trace(_config: &ResolvedConfig) {
+    eprintln!("{PROGRAM}: {NO_TRACER}");
+    std::process::exit(EXIT_FAILURE);
+}
+
 fn main() {
     // The parse handles `--version`, `-V`, and `--help` on its own. A
     // contradiction between two flags leaves the parser, so `clap` writes it to
@@ -681,18 +849,18 @@ fn main() {
             .exit(),
     };
     let Some(path) = config.replay.as_deref() else {
-        // No tracer exists yet, so a trace prints what it resolved.
-        print!("{config}");
+        trace(&config);
         return;
     };
     // The warning comes before the outcome, so a reader of standard error sees
     // the state of the file before the answer that state produced.
-    let result = replay(path, config.run.as_deref());
-    if let Some(warning) = result.warning {
+    let result = replay(path, &config);
+    for warning in result.warnings {
         eprintln!("{PROGRAM}: {warning}");
     }
     match result.outcome {
-        Ok(summary) => println!("{summary}"),
+        // Every report ends with its own newline.
+        Ok(report) => print!("{report}"),
         Err(reason) => {
             eprintln!("{PROGRAM}: {reason}");
             std::process::exit(EXIT_FAILURE);
@@ -703,8 +871,8 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::{
-        parse_duration, render_duration, AddressFamily, Cli, Command, Multipath, Protocol,
-        ResolvedConfig,
+        parse_duration, render_duration, AddressFamily, Baseline, Cli, Command, Format, Multipath,
+        Protocol, ResolvedConfig,
     };
     use clap::error::{ContextKind, ContextValue, ErrorKind};
     use clap::{CommandFactory, Parser};
@@ -1145,6 +1313,9 @@ resolved configuration:
             Some(Command::Replay {
                 file: PathBuf::from("path.jsonl"),
                 run: None,
+                format: Format::Table,
+                baseline: None,
+                baseline_file: None,
             })
         );
     }
@@ -1200,10 +1371,66 @@ resolved configuration:
             Some(Command::Replay {
                 file: PathBuf::from("path.jsonl"),
                 run: Some("2026-08-19T12:00:00Z".to_owned()),
+                format: Format::Table,
+                baseline: None,
+                baseline_file: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_every_format_of_a_replay() {
+        for (text, format) in [
+            ("table", Format::Table),
+            ("csv", Format::Csv),
+            ("dot", Format::Dot),
+        ] {
+            let config = resolve(&["krt", "replay", "path.jsonl", "--format", text]);
+            assert_eq!(config.format, format, "`--format {text}`");
+        }
+    }
+
+    #[test]
+    fn either_flag_of_the_baseline_asks_for_a_comparison() {
+        let config = resolve(&[
+            "krt",
+            "replay",
+            "b.jsonl",
+            "--baseline",
+            "2026-08-18T12:00:00Z",
+        ]);
+        assert_eq!(
+            config.baseline,
+            Some(Baseline {
+                file: None,
+                run: Some("2026-08-18T12:00:00Z".to_owned()),
+            })
+        );
+        let config = resolve(&["krt", "replay", "b.jsonl", "--baseline-file", "a.jsonl"]);
+        assert_eq!(
+            config.baseline,
+            Some(Baseline {
+                file: Some(PathBuf::from("a.jsonl")),
+                run: None,
             })
         );
     }
 
+    /// A comparison prints its own table, so it takes no format of a report.
+    #[test]
+    fn rejects_a_format_beside_a_baseline() {
+        let error = rejection(&[
+            "krt",
+            "replay",
+            "b.jsonl",
+            "--baseline-file",
+            "a.jsonl",
+            "--format",
+            "csv",
+        ]);
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+    }
+
     /// The verdict the parser must reach for one row of the argument matrix.
     #[derive(Debug)]
     enum Verdict {
@@ -1574,6 +1801,8 @@ resolved configuration:
         assert_eq!(config.rounds, None);
         assert_eq!(config.replay, None);
         assert_eq!(config.run, None);
+        assert_eq!(config.format, Format::Table);
+        assert_eq!(config.baseline, None);
     }
 
     #[test]
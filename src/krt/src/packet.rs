@@ -0,0 +1,867 @@
+//! The bytes of a probe, and the reading of the ICMP message that answers one.
+//!
+//! This module holds no socket. It builds the echo request that an ICMP probe
+//! sends, and the whole UDP datagram or TCP segment that a probe of a fixed
+//! flow sends. It reads the packet that a raw socket hands back: the answer,
+//! and the probe that the answer quotes. The tracer in `probe.rs` owns every
+//! socket, so every rule of the wire lives here, where a test feeds it bytes.
+//!
+//! A probe of a fixed flow carries the same ports as every other probe of its
+//! flow, so its ports do not name it. The number that names it is its mark: the
+//! checksum of a UDP datagram, or the sequence number of a TCP segment. Both
+//! lie in the first eight bytes of the transport header, which every ICMP error
+//! quotes, and a load balancer hashes neither. Paris traceroute names its
+//! probes the same way.
+
+use std::net::{IpAddr, SocketAddr};
+
+/// The type of an `ICMPv4` echo reply.
+const V4_ECHO_REPLY: u8 = 0;
+
+/// The type of an `ICMPv4` destination unreachable message.
+const V4_UNREACHABLE: u8 = 3;
+
+/// The type of an `ICMPv4` echo request.
+const V4_ECHO_REQUEST: u8 = 8;
+
+/// The type of an `ICMPv4` time exceeded message.
+const V4_TIME_EXCEEDED: u8 = 11;
+
+/// The code of an `ICMPv4` unreachable message for a network.
+const V4_NET_UNREACHABLE: u8 = 0;
+
+/// The code of an `ICMPv4` unreachable message for a host.
+const V4_HOST_UNREACHABLE: u8 = 1;
+
+/// The code of an `ICMPv4` unreachable message for a port.
+const V4_PORT_UNREACHABLE: u8 = 3;
+
+/// The codes of an `ICMPv4` unreachable message for a filter, from 9 to 13.
+const V4_PROHIBITED: [u8; 5] = [9, 10, 11, 12, 13];
+
+/// The type of an `ICMPv6` destination unreachable message.
+const V6_UNREACHABLE: u8 = 1;
+
+/// The type of an `ICMPv6` time exceeded message.
+const V6_TIME_EXCEEDED: u8 = 3;
+
+/// The type of an `ICMPv6` echo request.
+const V6_ECHO_REQUEST: u8 = 128;
+
+/// The type of an `ICMPv6` echo reply.
+const V6_ECHO_REPLY: u8 = 129;
+
+/// The code of an `ICMPv6` unreachable message for a route.
+const V6_NO_ROUTE: u8 = 0;
+
+/// The code of an `ICMPv6` unreachable message for a filter.
+const V6_PROHIBITED: u8 = 1;
+
+/// The code of an `ICMPv6` unreachable message for an address.
+const V6_ADDRESS_UNREACHABLE: u8 = 3;
+
+/// The code of an `ICMPv6` unreachable message for a port.
+const V6_PORT_UNREACHABLE: u8 = 4;
+
+/// The length of an ICMP header, in both versions. The header of an echo
+/// message holds the identifier and the sequence number, and the header of an
+/// error holds four bytes that `krt` never reads.
+const ICMP_HEADER_LEN: usize = 8;
+
+/// The length of an IPv6 header. The header carries no length field of its
+/// own, and `krt` sends no extension header, so the quote of a probe holds
+/// this many bytes before the transport header.
+const V6_HEADER_LEN: usize = 40;
+
+/// The lowest length of an IPv4 header, in bytes.
+const V4_HEADER_MIN_LEN: usize = 20;
+
+/// The byte of an IPv4 header that holds the version and the header length.
+const V4_VERSION_AND_LENGTH: usize = 0;
+
+/// The byte of an IPv4 header that names the protocol of the payload.
+const V4_PROTOCOL: usize = 9;
+
+/// The byte of an IPv6 header that names the protocol of the payload.
+const V6_NEXT_HEADER: usize = 6;
+
+/// The mask of the header length in the first byte of an IPv4 header. The
+/// length counts words of four bytes.
+const V4_LENGTH_MASK: u8 = 0x0f;
+
+/// The number of bytes in one word of an IPv4 header length.
+const V4_WORD: usize = 4;
+
+/// The protocol number of `ICMPv4`.
+const PROTOCOL_ICMP_V4: u8 = 1;
+
+/// The protocol number of TCP.
+const PROTOCOL_TCP: u8 = 6;
+
+/// The protocol number of UDP.
+const PROTOCOL_UDP: u8 = 17;
+
+/// The protocol number of `ICMPv6`.
+const PROTOCOL_ICMP_V6: u8 = 58;
+
+/// The length of a UDP header.
+const UDP_HEADER_LEN: usize = 8;
+
+/// The byte of a UDP header that starts the checksum.
+const UDP_CHECKSUM: usize = 6;
+
+/// The length of a TCP header with no option.
+const TCP_HEADER_LEN: usize = 20;
+
+/// The byte of a TCP header that starts the checksum.
+const TCP_CHECKSUM: usize = 16;
+
+/// The byte of a TCP header that starts the sequence number.
+const TCP_SEQUENCE: usize = 4;
+
+/// The byte of a TCP header that starts the acknowledgment number.
+const TCP_ACKNOWLEDGMENT: usize = 8;
+
+/// The byte of a TCP header that holds the header length, in words of four
+/// bytes, in its upper four bits.
+const TCP_DATA_OFFSET: usize = 12;
+
+/// The byte of a TCP header that holds the flags.
+const TCP_FLAGS: usize = 13;
+
+/// The byte of a TCP header that starts the window.
+const TCP_WINDOW: usize = 14;
+
+/// The flag of a TCP segment that opens a connection.
+const TCP_SYN: u8 = 0x02;
+
+/// The flag of a TCP segment that refuses a connection.
+const TCP_RST: u8 = 0x04;
+
+/// The flag of a TCP segment that acknowledges.
+const TCP_ACK: u8 = 0x10;
+
+/// The window of a TCP probe. The target never sends data to the probe, so any
+/// window serves, and this one is the window of a common first segment.
+const TCP_PROBE_WINDOW: u16 = 64240;
+
+/// The number of bytes at the end of a UDP probe that set its checksum.
+const MARK_ADJUST_LEN: usize = 2;
+
+/// The bytes that follow the header of an echo request. The text names the
+/// tool, so a capture on the path shows who sent the probe.
+pub(crate) const PROBE_PAYLOAD: &[u8] = b"krt round trip probe";
+
+/// The ICMP message that answered a probe, or the TCP reply that stood in for
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Answer {
+    /// A router on the path dropped the probe, because its TTL ran out.
+    TimeExceeded,
+    /// The target answered an echo request.
+    EchoReply,
+    /// The target holds no socket on the port of a UDP probe.
+    PortUnreachable,
+    /// A router has no route to the network of the target.
+    NetUnreachable,
+    /// A router has no route to the target itself.
+    HostUnreachable,
+    /// A filter on the path refused the probe.
+    Prohibited,
+    /// Another unreachable message, with a code that `krt` does not name.
+    Unreachable,
+    /// The target accepted a TCP probe.
+    SynAck,
+    /// The target refused a TCP probe.
+    Reset,
+}
+
+impl Answer {
+    /// Reads the answer that one ICMP type and code name.
+    ///
+    /// `None` names a message that answers no probe, such as an echo request
+    /// that a raw socket sees on the loopback interface.
+    pub(crate) fn from_icmp(v6: bool, kind: u8, code: u8) -> Option<Self> {
+        let answer = if v6 {
+            match (kind, code) {
+                (V6_TIME_EXCEEDED, _) => Self::TimeExceeded,
+                (V6_ECHO_REPLY, _) => Self::EchoReply,
+                (V6_UNREACHABLE, V6_PORT_UNREACHABLE) => Self::PortUnreachable,
+                (V6_UNREACHABLE, V6_NO_ROUTE) => Self::NetUnreachable,
+                (V6_UNREACHABLE, V6_ADDRESS_UNREACHABLE) => Self::HostUnreachable,
+                (V6_UNREACHABLE, V6_PROHIBITED) => Self::Prohibited,
+                (V6_UNREACHABLE, _) => Self::Unreachable,
+                _ => return None,
+            }
+        } else {
+            match (kind, code) {
+                (V4_TIME_EXCEEDED, _) => Self::TimeExceeded,
+                (V4_ECHO_REPLY, _) => Self::EchoReply,
+                (V4_UNREACHABLE, V4_PORT_UNREACHABLE) => Self::PortUnreachable,
+                (V4_UNREACHABLE, V4_NET_UNREACHABLE) => Self::NetUnreachable,
+                (V4_UNREACHABLE, V4_HOST_UNREACHABLE) => Self::HostUnreachable,
+                (V4_UNREACHABLE, code) if V4_PROHIBITED.contains(&code) => Self::Prohibited,
+                (V4_UNREACHABLE, _) => Self::Unreachable,
+                _ => return None,
+            }
+        };
+        Some(answer)
+    }
+
+    /// The name that a `round` record writes for the answer.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::TimeExceeded => "time_exceeded",
+            Self::EchoReply => "echo_reply",
+            Self::PortUnreachable => "port_unreachable",
+            Self::NetUnreachable => "net_unreachable",
+            Self::HostUnreachable => "host_unreachable",
+            Self::Prohibited => "prohibited",
+            Self::Unreachable => "unreachable",
+            Self::SynAck => "tcp_syn_ack",
+            Self::Reset => "tcp_reset",
+        }
+    }
+
+    /// True when the answer came from the target and not from a router on the
+    /// path.
+    ///
+    /// A port unreachable message comes from the host that holds no socket on
+    /// the port, and a UDP probe aims at a port that no socket holds, so the
+    /// message reaches only from the target. The tracer still compares the
+    /// address, because a router can send the same message for a filter.
+    pub(crate) fn is_final(self) -> bool {
+        matches!(
+            self,
+            Self::EchoReply | Self::PortUnreachable | Self::SynAck | Self::Reset
+        )
+    }
+}
+
+/// The probe that an answer belongs to, as the answer quotes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Quote {
+    /// An echo request, by its identifier and its sequence number.
+    Echo {
+        /// The identifier of the echo request.
+        ident: u16,
+        /// The sequence number of the echo request.
+        seq: u16,
+    },
+    /// A UDP datagram, by its source port.
+    Udp {
+        /// The source port of the datagram.
+        source_port: u16,
+    },
+    /// A TCP segment, by its source port.
+    Tcp {
+        /// The source port of the segment.
+        source_port: u16,
+    },
+}
+
+/// One ICMP message that a raw socket read: what it says, and which probe it
+/// answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RawAnswer {
+    /// What the message says.
+    pub(crate) answer: Answer,
+    /// The probe that the message answers.
+    pub(crate) quote: Quote,
+    /// The mark of the probe: the checksum of a UDP datagram, or the sequence
+    /// number of a TCP segment. An echo request has none, and a quote cut
+    /// before the field holds none.
+    pub(crate) mark: Option<u32>,
+}
+
+/// Builds an echo request.
+///
+/// The checksum covers the whole message. An `ICMPv6` checksum also covers a
+/// header that names both addresses, which the kernel knows and `krt` does not,
+/// so a request of version 6 leaves the field at zero and the kernel fills it.
+/// The kernel also overwrites the identifier of a request that a datagram
+/// socket sends, with the port of that socket.
+pub(crate) fn echo_request(v6: bool, ident: u16, seq: u16) -> Vec<u8> {
+    let kind = if v6 { V6_ECHO_REQUEST } else { V4_ECHO_REQUEST };
+    let mut packet = vec![kind, 0, 0, 0];
+    packet.extend_from_slice(&ident.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(PROBE_PAYLOAD);
+    if !v6 {
+        let sum = checksum(&packet).to_be_bytes();
+        packet[2] = sum[0];
+        packet[3] = sum[1];
+    }
+    packet
+}
+
+/// Builds a UDP probe of a fixed flow, whose checksum is `mark`.
+///
+/// The datagram carries the payload of every probe and two more bytes, and
+/// those two bytes take the value that makes the checksum come out as `mark`.
+/// The datagram stays valid, so no router drops it for a bad checksum. A mark
+/// of zero would read as a datagram with no checksum, so the caller never
+/// asks for one.
+pub(crate) fn udp_probe(from: SocketAddr, to: SocketAddr, mark: u16) -> Vec<u8> {
+    let length = UDP_HEADER_LEN + PROBE_PAYLOAD.len() + MARK_ADJUST_LEN;
+    let mut datagram = Vec::with_capacity(length);
+    datagram.extend_from_slice(&from.port().to_be_bytes());
+    datagram.extend_from_slice(&to.port().to_be_bytes());
+    // The payload is a few dozen bytes, so the length fits in 16 bits.
+    datagram.extend_from_slice(&u16::try_from(length).unwrap_or(u16::MAX).to_be_bytes());
+    datagram.extend_from_slice(&mark.to_be_bytes());
+    datagram.extend_from_slice(PROBE_PAYLOAD);
+    datagram.extend_from_slice(&[0; MARK_ADJUST_LEN]);
+    // The header and the payload hold an even number of bytes, so the last two
+    // bytes form one word of the sum. That word makes the whole sum, mark
+    // included, fold to all ones, which a receiver reads as a valid checksum.
+    let mut covered = pseudo_header(from.ip(), to.ip(), PROTOCOL_UDP, length);
+    covered.extend_from_slice(&datagram);
+    let adjust = !sum(&covered);
+    let end = datagram.len();
+    datagram[end - MARK_ADJUST_LEN..].copy_from_slice(&adjust.to_be_bytes());
+    datagram
+}
+
+/// Builds a TCP probe of a fixed flow: a segment that opens a connection, whose
+/// sequence number is `mark`.
+pub(crate) fn tcp_probe(from: SocketAddr, to: SocketAddr, mark: u32) -> Vec<u8> {
+    let mut segment = vec![0_u8; TCP_HEADER_LEN];
+    segment[0..2].copy_from_slice(&from.port().to_be_bytes());
+    segment[2..4].copy_from_slice(&to.port().to_be_bytes());
+    segment[TCP_SEQUENCE..TCP_SEQUENCE + 4].copy_from_slice(&mark.to_be_bytes());
+    // The header holds five words of four bytes, and no option.
+    segment[TCP_DATA_OFFSET] = 5 << 4;
+    segment[TCP_FLAGS] = TCP_SYN;
+    segment[TCP_WINDOW..TCP_WINDOW + 2].copy_from_slice(&TCP_PROBE_WINDOW.to_be_bytes());
+    let mut covered = pseudo_header(from.ip(), to.ip(), PROTOCOL_TCP, TCP_HEADER_LEN);
+    covered.extend_from_slice(&segment);
+    segment[TCP_CHECKSUM..TCP_CHECKSUM + 2].copy_from_slice(&checksum(&covered).to_be_bytes());
+    segment
+}
+
+/// Builds the header that the checksum of UDP and of TCP covers and that no
+/// packet carries: both addresses, the protocol, and the length of the
+/// transport header and its payload.
+fn pseudo_header(from: IpAddr, to: IpAddr, protocol: u8, length: usize) -> Vec<u8> {
+    // A probe is a few dozen bytes, so its length fits in every field below.
+    let length = u32::try_from(length).unwrap_or(u32::MAX);
+    match (from, to) {
+        (IpAddr::V4(from), IpAddr::V4(to)) => {
+            let mut header = from.octets().to_vec();
+            header.extend_from_slice(&to.octets());
+            header.extend_from_slice(&[0, protocol]);
+            header.extend_from_slice(&length.to_be_bytes()[2..]);
+            header
+        }
+        (from, to) => {
+            let mut header = to_v6(from).to_vec();
+            header.extend_from_slice(&to_v6(to));
+            header.extend_from_slice(&length.to_be_bytes());
+            header.extend_from_slice(&[0, 0, 0, protocol]);
+            header
+        }
+    }
+}
+
+/// The sixteen bytes of an address of version 6. An address of version 4 maps
+/// into version 6, though a probe never mixes the two.
+fn to_v6(addr: IpAddr) -> [u8; 16] {
+    match addr {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped().octets(),
+        IpAddr::V6(v6) => v6.octets(),
+    }
+}
+
+/// Computes the Internet checksum of RFC 1071: the ones' complement of the
+/// ones' complement sum of the bytes, as 16-bit words.
+pub(crate) fn checksum(bytes: &[u8]) -> u16 {
+    !sum(bytes)
+}
+
+/// Computes the ones' complement sum of the bytes, as 16-bit words, with every
+/// carry folded back in.
+fn sum(bytes: &[u8]) -> u16 {
+    let mut sum: u32 = bytes
+        .chunks(2)
+        .map(|pair| {
+            u32::from(u16::from_be_bytes([
+                pair[0],
+                pair.get(1).copied().unwrap_or(0),
+            ]))
+        })
+        .sum();
+    while sum > u32::from(u16::MAX) {
+        sum = (sum & u32::from(u16::MAX)) + (sum >> 16);
+    }
+    // The loop folds every carry, so the sum fits in 16 bits.
+    u16::try_from(sum).unwrap_or(u16::MAX)
+}
+
+/// Reads the two bytes at `at` as one number, in network order.
+fn word(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]))
+}
+
+/// Reads a packet that a raw ICMP socket received.
+///
+/// A raw `ICMPv4` socket hands over the IPv4 header too, and a raw `ICMPv6` socket
+/// hands over the `ICMPv6` message alone. `from` is the address the packet came
+/// from, which tells the two apart.
+///
+/// `None` names a packet that answers no probe of `krt`: a message of a type
+/// that answers nothing, a packet too short to quote a probe, or a quote of a
+/// protocol that `krt` never sends.
+pub(crate) fn read_raw(from: IpAddr, packet: &[u8]) -> Option<RawAnswer> {
+    let v6 = from.is_ipv6();
+    let icmp = if v6 {
+        packet
+    } else {
+        let length = usize::from(packet.get(V4_VERSION_AND_LENGTH)? & V4_LENGTH_MASK) * V4_WORD;
+        packet.get(length.max(V4_HEADER_MIN_LEN)..)?
+    };
+    let answer = Answer::from_icmp(v6, *icmp.first()?, *icmp.get(1)?)?;
+    if answer == Answer::EchoReply {
+        let quote = Quote::Echo {
+            ident: word(icmp, 4)?,
+            seq: word(icmp, 6)?,
+        };
+        return Some(RawAnswer {
+            answer,
+            quote,
+            mark: None,
+        });
+    }
+    let quoted = icmp.get(ICMP_HEADER_LEN..)?;
+    let (quote, mark) = read_quote(v6, quoted)?;
+    Some(RawAnswer {
+        answer,
+        quote,
+        mark,
+    })
+}
+
+/// Reads a TCP segment that a raw TCP socket received, as the answer of the
+/// target to a TCP probe of a fixed flow.
+///
+/// A raw TCP socket of version 4 hands over the IPv4 header too, and one of
+/// version 6 hands over the segment alone. A segment that acknowledges a probe
+/// acknowledges its sequence number plus one, so the mark of the answer is the
+/// acknowledgment number less one.
+///
+/// `None` names a segment that neither accepts nor refuses a connection, and a
+/// segment too short to hold the fields.
+pub(crate) fn read_tcp(from: IpAddr, packet: &[u8]) -> Option<RawAnswer> {
+    let segment = if from.is_ipv6() {
+        packet
+    } else {
+        let length = usize::from(packet.get(V4_VERSION_AND_LENGTH)? & V4_LENGTH_MASK) * V4_WORD;
+        packet.get(length.max(V4_HEADER_MIN_LEN)..)?
+    };
+    let flags = *segment.get(TCP_FLAGS)?;
+    let answer = if flags & TCP_RST != 0 {
+        Answer::Reset
+    } else if flags & (TCP_SYN | TCP_ACK) == TCP_SYN | TCP_ACK {
+        Answer::SynAck
+    } else {
+        return None;
+    };
+    let acknowledged = u32::from_be_bytes(
+        segment
+            .get(TCP_ACKNOWLEDGMENT..TCP_ACKNOWLEDGMENT + 4)?
+            .try_into()
+            .ok()?,
+    );
+    Some(RawAnswer {
+        answer,
+        quote: Quote::Tcp {
+            source_port: word(segment, 2)?,
+        },
+        mark: Some(acknowledged.wrapping_sub(1)),
+    })
+}
+
+/// Reads the probe that an ICMP error quotes: the IP header of the probe, and
+/// the first eight bytes of its transport header. The mark comes back too, when
+/// the quote holds it.
+fn read_quote(v6: bool, quoted: &[u8]) -> Option<(Quote, Option<u32>)> {
+    let (protocol, transport) = if v6 {
+        (*quoted.get(V6_NEXT_HEADER)?, quoted.get(V6_HEADER_LEN..)?)
+    } else {
+        let length = usize::from(quoted.first()? & V4_LENGTH_MASK) * V4_WORD;
+        (
+            *quoted.get(V4_PROTOCOL)?,
+            quoted.get(length.max(V4_HEADER_MIN_LEN)..)?,
+        )
+    };
+    match protocol {
+        PROTOCOL_ICMP_V4 | PROTOCOL_ICMP_V6 => Some((
+            Quote::Echo {
+                ident: word(transport, 4)?,
+                seq: word(transport, 6)?,
+            },
+            None,
+        )),
+        PROTOCOL_UDP => Some((
+            Quote::Udp {
+                source_port: word(transport, 0)?,
+            },
+            word(transport, UDP_CHECKSUM).map(u32::from),
+        )),
+        PROTOCOL_TCP => Some((
+            Quote::Tcp {
+                source_port: word(transport, 0)?,
+            },
+            transport
+                .get(TCP_SEQUENCE..TCP_SEQUENCE + 4)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u32::from_be_bytes),
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checksum, echo_request, pseudo_header, read_raw, read_tcp, tcp_probe, udp_probe, Answer,
+        Quote, RawAnswer, PROBE_PAYLOAD,
+    };
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    /// The address of a router, as a raw socket reports it.
+    const ROUTER_V4: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// The address of a router of version 6.
+    const ROUTER_V6: IpAddr = IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1));
+
+    /// The address and the port that a probe of a fixed flow leaves from.
+    const FROM_V4: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), 49_200);
+
+    /// The address and the port of the target of a probe of a fixed flow.
+    const TO_V4: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 9)), 33_434);
+
+    /// The address and the port that a probe of version 6 leaves from.
+    const FROM_V6: SocketAddr = SocketAddr::new(
+        IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 2)),
+        49_201,
+    );
+
+    /// The address and the port of the target of a probe of version 6.
+    const TO_V6: SocketAddr = SocketAddr::new(
+        IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 9)),
+        80,
+    );
+
+    /// True when the checksum of a transport header and its payload holds,
+    /// with the pseudo-header that the addresses give.
+    fn verifies(from: SocketAddr, to: SocketAddr, protocol: u8, packet: &[u8]) -> bool {
+        let mut covered = pseudo_header(from.ip(), to.ip(), protocol, packet.len());
+        covered.extend_from_slice(packet);
+        checksum(&covered) == 0
+    }
+
+    /// Builds a plain IPv4 header of twenty bytes that carries `protocol`.
+    fn v4_header(protocol: u8) -> Vec<u8> {
+        let mut header = vec![0_u8; 20];
+        header[0] = 0x45;
+        header[9] = protocol;
+        header
+    }
+
+    /// Builds an IPv6 header that carries `next`.
+    fn v6_header(next: u8) -> Vec<u8> {
+        let mut header = vec![0_u8; 40];
+        header[0] = 0x60;
+        header[6] = next;
+        header
+    }
+
+    /// Builds an `ICMPv4` error, with the IPv4 header that a raw socket adds, that
+    /// quotes `quoted`.
+    fn v4_error(kind: u8, code: u8, quoted: &[u8]) -> Vec<u8> {
+        let mut packet = v4_header(1);
+        packet.extend_from_slice(&[kind, code, 0, 0, 0, 0, 0, 0]);
+        packet.extend_from_slice(quoted);
+        packet
+    }
+
+    /// The first eight bytes of a transport header that leaves from
+    /// `source_port` for `dest_port`.
+    fn ports(source_port: u16, dest_port: u16) -> Vec<u8> {
+        let mut bytes = source_port.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&dest_port.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn the_checksum_of_a_request_makes_the_request_sum_to_zero() {
+        let request = echo_request(false, 0x1234, 7);
+        assert_eq!(
+            checksum(&request),
+            0,
+            "a correct checksum folds the sum to zero"
+        );
+    }
+
+    #[test]
+    fn the_checksum_covers_an_odd_length() {
+        assert_eq!(checksum(&[0x01]), !0x0100);
+    }
+
+    #[test]
+    fn an_echo_request_carries_the_identifier_the_sequence_and_the_payload() {
+        let request = echo_request(false, 0xbeef, 0x0102);
+        assert_eq!(request[0], 8, "an ICMPv4 echo request is type 8");
+        assert_eq!(&request[4..8], &[0xbe, 0xef, 0x01, 0x02]);
+        assert_eq!(&request[8..], PROBE_PAYLOAD);
+    }
+
+    #[test]
+    fn an_echo_request_of_version_6_leaves_the_checksum_to_the_kernel() {
+        let request = echo_request(true, 1, 2);
+        assert_eq!(request[0], 128, "an ICMPv6 echo request is type 128");
+        assert_eq!(&request[2..4], &[0, 0]);
+    }
+
+    #[test]
+    fn a_time_exceeded_message_quotes_the_udp_port_of_the_probe() {
+        let mut quoted = v4_header(17);
+        quoted.extend(ports(40_000, 33_434));
+        assert_eq!(
+            read_raw(ROUTER_V4, &v4_error(11, 0, &quoted)),
+            Some(RawAnswer {
+                answer: Answer::TimeExceeded,
+                quote: Quote::Udp {
+                    source_port: 40_000
+                },
+                mark: Some(0),
+            })
+        );
+    }
+
+    #[test]
+    fn a_time_exceeded_message_quotes_the_tcp_port_of_the_probe() {
+        let mut quoted = v4_header(6);
+        quoted.extend(ports(51_000, 80));
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &quoted)).expect("a quote of TCP");
+        assert_eq!(
+            read.quote,
+            Quote::Tcp {
+                source_port: 51_000
+            }
+        );
+    }
+
+    #[test]
+    fn a_time_exceeded_message_quotes_the_echo_request_of_the_probe() {
+        let mut quoted = v4_header(1);
+        quoted.extend(&echo_request(false, 0x0a0b, 0x0c0d)[..8]);
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &quoted)).expect("a quote of ICMP");
+        assert_eq!(
+            read.quote,
+            Quote::Echo {
+                ident: 0x0a0b,
+                seq: 0x0c0d
+            }
+        );
+    }
+
+    #[test]
+    fn a_quoted_header_with_options_is_skipped_by_its_own_length() {
+        let mut quoted = v4_header(17);
+        quoted[0] = 0x46;
+        quoted.extend([0, 0, 0, 0]);
+        quoted.extend(ports(40_001, 33_435));
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &quoted)).expect("a quote with options");
+        assert_eq!(
+            read.quote,
+            Quote::Udp {
+                source_port: 40_001
+            }
+        );
+    }
+
+    #[test]
+    fn an_echo_reply_names_its_own_identifier_and_sequence() {
+        let mut packet = v4_header(1);
+        packet.extend([0, 0, 0, 0, 0x12, 0x34, 0, 9]);
+        let read = read_raw(ROUTER_V4, &packet).expect("an echo reply");
+        assert_eq!(read.answer, Answer::EchoReply);
+        assert_eq!(
+            read.quote,
+            Quote::Echo {
+                ident: 0x1234,
+                seq: 9
+            }
+        );
+    }
+
+    #[test]
+    fn a_message_of_version_6_carries_no_ip_header() {
+        let mut packet = vec![3, 0, 0, 0, 0, 0, 0, 0];
+        packet.extend(v6_header(17));
+        packet.extend(ports(40_002, 33_436));
+        let read = read_raw(ROUTER_V6, &packet).expect("an ICMPv6 time exceeded message");
+        assert_eq!(read.answer, Answer::TimeExceeded);
+        assert_eq!(
+            read.quote,
+            Quote::Udp {
+                source_port: 40_002
+            }
+        );
+    }
+
+    #[test]
+    fn an_echo_request_answers_no_probe() {
+        let mut packet = v4_header(1);
+        packet.extend(echo_request(false, 1, 1));
+        assert_eq!(
+            read_raw(ROUTER_V4, &packet),
+            None,
+            "a raw socket on the loopback interface also reads the request"
+        );
+    }
+
+    #[test]
+    fn a_cut_packet_answers_no_probe_and_never_panics() {
+        let mut quoted = v4_header(17);
+        quoted.extend(ports(40_000, 33_434));
+        let whole = v4_error(11, 0, &quoted);
+        // The source port is the first two of the eight bytes of the quoted
+        // UDP header. A cut before its end reads nothing, and a cut after it
+        // still names the probe.
+        let port_end = whole.len() - 6;
+        for length in 0..port_end {
+            assert_eq!(
+                read_raw(ROUTER_V4, &whole[..length]),
+                None,
+                "{length} bytes"
+            );
+        }
+        for length in port_end..=whole.len() {
+            assert!(
+                read_raw(ROUTER_V4, &whole[..length]).is_some(),
+                "{length} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn a_udp_probe_carries_its_mark_as_a_valid_checksum() {
+        for mark in [1, 0x1234, 0xfffe, u16::MAX] {
+            let probe = udp_probe(FROM_V4, TO_V4, mark);
+            assert_eq!(&probe[0..4], &[0xc0, 0x30, 0x82, 0x9a], "the two ports");
+            assert_eq!(&probe[6..8], &mark.to_be_bytes(), "mark {mark}");
+            assert!(verifies(FROM_V4, TO_V4, 17, &probe), "mark {mark}");
+        }
+        let probe = udp_probe(FROM_V6, TO_V6, 7);
+        assert_eq!(&probe[6..8], &[0, 7]);
+        assert!(verifies(FROM_V6, TO_V6, 17, &probe));
+    }
+
+    #[test]
+    fn a_tcp_probe_opens_a_connection_with_its_mark_as_the_sequence() {
+        for (from, to) in [(FROM_V4, TO_V4), (FROM_V6, TO_V6)] {
+            let probe = tcp_probe(from, to, 0x0102_0304);
+            assert_eq!(probe.len(), 20);
+            assert_eq!(&probe[4..8], &[1, 2, 3, 4]);
+            assert_eq!(probe[13], 0x02, "SYN alone");
+            assert!(verifies(from, to, 6, &probe));
+        }
+    }
+
+    #[test]
+    fn a_time_exceeded_message_quotes_the_mark_of_a_probe() {
+        let mut udp = v4_header(17);
+        udp.extend(&udp_probe(FROM_V4, TO_V4, 0x0abc)[..8]);
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &udp)).expect("a quote of UDP");
+        assert_eq!(read.mark, Some(0x0abc));
+
+        let mut tcp = v4_header(6);
+        tcp.extend(&tcp_probe(FROM_V4, TO_V4, 77)[..8]);
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &tcp)).expect("a quote of TCP");
+        assert_eq!(read.mark, Some(77));
+        assert_eq!(
+            read.quote,
+            Quote::Tcp {
+                source_port: 49_200
+            }
+        );
+    }
+
+    #[test]
+    fn a_quote_cut_before_the_mark_still_names_the_port() {
+        let mut quoted = v4_header(17);
+        quoted.extend(&ports(40_000, 33_434)[..4]);
+        let read = read_raw(ROUTER_V4, &v4_error(11, 0, &quoted)).expect("a cut quote of UDP");
+        assert_eq!(read.mark, None);
+    }
+
+    #[test]
+    fn a_syn_ack_from_the_target_acknowledges_the_mark_plus_one() {
+        let mut reply = v4_header(6);
+        let mut segment = tcp_probe(TO_V4, FROM_V4, 900);
+        segment[8..12].copy_from_slice(&78_u32.to_be_bytes());
+        segment[13] = 0x12;
+        reply.extend(&segment);
+        assert_eq!(
+            read_tcp(TO_V4.ip(), &reply),
+            Some(RawAnswer {
+                answer: Answer::SynAck,
+                quote: Quote::Tcp {
+                    source_port: 49_200
+                },
+                mark: Some(77),
+            })
+        );
+
+        segment[13] = 0x14;
+        let read = read_tcp(TO_V6.ip(), &segment).expect("a reset of version 6");
+        assert_eq!(read.answer, Answer::Reset);
+    }
+
+    #[test]
+    fn a_segment_that_only_opens_answers_no_probe() {
+        let segment = tcp_probe(TO_V6, FROM_V6, 1);
+        assert_eq!(read_tcp(TO_V6.ip(), &segment), None);
+        assert_eq!(read_tcp(TO_V6.ip(), &segment[..10]), None);
+    }
+
+    #[test]
+    fn the_unreachable_codes_read_as_their_names() {
+        let names: Vec<&str> = [0, 1, 3, 13, 4]
+            .into_iter()
+            .map(|code| {
+                Answer::from_icmp(false, 3, code)
+                    .expect("an unreachable message")
+                    .name()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "net_unreachable",
+                "host_unreachable",
+                "port_unreachable",
+                "prohibited",
+                "unreachable"
+            ]
+        );
+        assert_eq!(
+            Answer::from_icmp(true, 1, 4).map(Answer::name),
+            Some("port_unreachable")
+        );
+    }
+
+    #[test]
+    fn only_an_answer_from_the_target_ends_the_path() {
+        assert!(Answer::EchoReply.is_final());
+        assert!(Answer::PortUnreachable.is_final());
+        assert!(Answer::SynAck.is_final());
+        assert!(Answer::Reset.is_final());
+        assert!(!Answer::TimeExceeded.is_final());
+        assert!(!Answer::HostUnreachable.is_final());
+    }
+}
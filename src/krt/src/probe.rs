@@ -0,0 +1,871 @@
+//! The sockets of a trace, and one round of probes through them.
+//!
+//! A round sends one probe for each TTL at once, then waits for the answers.
+//! Each answer names the probe it belongs to in one of two ways, and the way
+//! decides the privilege that the run records.
+//!
+//! On Linux every probe that can leave through a socket of its own does so,
+//! and `IP_RECVERR` hands the ICMP error that a router sends back to that one
+//! socket, through its error queue. The kernel matches the answer to the probe,
+//! so `krt` reads no packet it did not send and needs no privilege. A UDP
+//! probe leaves through a UDP socket, a TCP probe through a TCP socket that
+//! connects, and an ICMP probe through a datagram socket of ICMP, which
+//! `net.ipv4.ping_group_range` opens to a group of users.
+//!
+//! Where no such socket opens, one raw ICMP socket reads every ICMP message
+//! that reaches the host, and `packet.rs` reads the probe that each message
+//! quotes. A raw socket needs privilege, so a run that opens one records
+//! `privileged`.
+//!
+//! A socket of its own for each probe gives each probe its own source port,
+//! and a UDP probe also aims at a port of its own for each TTL. A load balancer
+//! that hashes the ports sends each probe down whichever branch the hash
+//! picks, so the hops of one round can come from two paths and draw a path
+//! that no packet took. `--multipath paris` and `--multipath dublin` hold the
+//! flow themselves: one raw socket of UDP or TCP writes each probe whole, with
+//! the ports that its flow names, and the mark of `packet.rs` names the probe.
+//! Paris sends every probe of the run on one flow, so every probe follows one
+//! branch. Dublin sends every TTL on each of several flows, one source port
+//! each, and each hop names its flow, so the file holds every branch. Both
+//! need privilege, for the raw socket that writes and the one that reads.
+
+use crate::packet::{self, Answer, Quote, RawAnswer};
+use crate::record::{Hop, Privilege};
+use crate::{Multipath, Protocol};
+use nix::errno::Errno;
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+use nix::sys::socket::{recvfrom, SockaddrStorage};
+use socket2::{Domain, Protocol as Transport, SockAddr, Socket, Type};
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::os::fd::{AsFd, AsRawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// The destination port of the first UDP probe of a round. Each later TTL adds
+/// one, as traceroute has always done, so no service is likely to hold the
+/// port and the target answers with a port unreachable message.
+const UDP_BASE_PORT: u16 = 33434;
+
+/// The destination port of a TCP probe. A web server answers a connection on
+/// it, and a firewall lets it through more often than any other port.
+const TCP_PORT: u16 = 80;
+
+/// The lowest source port of a flow: the first port of the range that IANA
+/// leaves to dynamic use.
+const FLOW_FIRST_PORT: u16 = 49152;
+
+/// The number of ports that the first flow of a trace picks its source port
+/// from, by the process ID, so two traces on one host hold flows apart.
+const FLOW_PORT_SPREAD: u16 = 8192;
+
+/// The number of flows that each round of `--multipath dublin` walks.
+///
+/// A load balancer with two branches sends all eight flows down one branch one
+/// time in 128, so a single round finds both branches 99 times in 100, and
+/// every later round adds its own chance. More flows cost more probes, and a
+/// router that limits the rate of its ICMP messages drops the answers of the
+/// probes above its limit.
+const DUBLIN_FLOWS: u16 = 8;
+
+/// The port that the search for the local source address connects to. A
+/// connect of a UDP socket sends nothing, so no service on the port matters.
+const DISCARD_PORT: u16 = 9;
+
+/// What a trace of fixed flows needs, for the message of a socket that did not
+/// open.
+const FLOW_NEED: &str =
+    "the privilege of a raw socket, because `--multipath paris` and `--multipath dublin` write the headers of each probe";
+
+/// The size of the buffer that one read fills. One Ethernet frame fits.
+const READ_BUFFER: usize = 1500;
+
+/// The longest time that one wait for answers blocks, so a stop request never
+/// waits out a long round.
+const POLL_SLICE: Duration = Duration::from_millis(100);
+
+/// The number of milliseconds in one second, for a round trip time.
+const MILLIS_PER_SECOND: f64 = 1000.0;
+
+/// The precision of a round trip time, in parts of one millisecond. A
+/// microsecond is far below the noise of any network path.
+const RTT_PRECISION: f64 = 1000.0;
+
+/// The way the answers of a trace reach `krt`.
+enum Mode {
+    /// Each probe leaves through its own socket, and the kernel hands the
+    /// answer back to that socket.
+    OwnSocket,
+    /// One raw ICMP socket reads every ICMP message, and each message names
+    /// its probe by the quote it carries.
+    Listener(Socket),
+    /// One raw socket writes each probe with the ports of its flow, and the
+    /// raw ICMP socket reads the answers, which name each probe by its mark.
+    Flows(Flows),
+}
+
+/// The sockets and the ports of a trace that holds its flows itself.
+struct Flows {
+    /// The raw ICMP socket that reads every ICMP message.
+    listener: Socket,
+    /// The raw socket of UDP or TCP that writes each probe whole. A raw TCP
+    /// socket also reads the answer of the target, which comes back as TCP.
+    writer: Socket,
+    /// The address that every probe leaves from. The checksum of each probe
+    /// covers it, so the writer binds to it.
+    source: IpAddr,
+    /// The source port of the first flow. Each later flow adds one.
+    first_port: u16,
+    /// The number of flows that each round walks. Paris walks one.
+    count: u16,
+}
+
+/// A socket that one wait for answers watches.
+#[derive(Debug, Clone, Copy)]
+enum Watched {
+    /// The raw ICMP socket.
+    Listener,
+    /// The raw TCP socket of a trace of fixed flows.
+    Replies,
+    /// The socket of the probe at this index.
+    Probe(usize),
+}
+
+/// The fault of a trace that cannot open the socket its probes need.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ProbeError {
+    /// No socket for this protocol opens for this user.
+    #[error("{protocol} probes need {need}: {source}")]
+    NoSocket {
+        /// The protocol of the probes.
+        protocol: &'static str,
+        /// What the user can change so that the socket opens.
+        need: &'static str,
+        /// The reason the last socket did not open.
+        source: io::Error,
+    },
+    /// A probe did not leave, or its answer did not read.
+    #[error("a probe failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// The sockets of one trace, and the state that names each probe.
+pub(crate) struct Prober {
+    /// The address of the target.
+    target: IpAddr,
+    /// The protocol of a probe.
+    protocol: Protocol,
+    /// The way the answers reach `krt`.
+    mode: Mode,
+    /// The identifier of every echo request that the raw socket sends.
+    ident: u16,
+    /// The sequence number of the next echo request that the raw socket sends,
+    /// or the mark of the last probe of a fixed flow.
+    next_seq: u16,
+}
+
+/// What one round of probes found.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RoundResult {
+    /// The hops that answered, in the order of their TTLs.
+    pub(crate) hops: Vec<Hop>,
+    /// True when the target answered a probe.
+    pub(crate) reached: bool,
+    /// The last TTL that the round states. The TTL that reached the target
+    /// ends the path, so a TTL above it states nothing new. When the round
+    /// walks several flows, the flow that reached the target last ends it.
+    pub(crate) last_ttl: u8,
+    /// The number of flows that the round walked at each TTL, when the hops
+    /// name their flows.
+    pub(crate) flows: Option<u16>,
+}
+
+/// One probe of a round, and the answer it drew.
+struct Probe {
+    /// The TTL of the probe.
+    ttl: u8,
+    /// The moment that the probe left.
+    sent: Instant,
+    /// The socket that the probe left through, when it left through its own.
+    socket: Option<Socket>,
+    /// The probe, as an ICMP error quotes it to the raw socket.
+    quote: Option<Quote>,
+    /// The mark of a probe of a fixed flow, which its quote alone does not
+    /// tell apart from the other probes of its flow.
+    mark: Option<u32>,
+    /// The flow of the probe, when the round walks several.
+    flow: Option<u16>,
+    /// The hop that answered, the answer, and the round trip time.
+    found: Option<(IpAddr, Answer, Duration)>,
+}
+
+impl Probe {
+    /// True when a message that a raw socket read answers this probe.
+    fn claims(&self, read: &RawAnswer) -> bool {
+        self.quote == Some(read.quote) && self.mark.is_none_or(|mark| read.mark == Some(mark))
+    }
+
+    /// True when the target itself answered the probe.
+    fn reached(&self, target: IpAddr) -> bool {
+        self.found
+            .is_some_and(|(from, answer, _)| answer.is_final() && from == target)
+    }
+
+    /// Records the first answer of the probe. A later answer of the same probe
+    /// is a duplicate and changes nothing.
+    fn answer(&mut self, from: IpAddr, answer: Answer) {
+        if self.found.is_none() {
+            self.found = Some((from, answer, self.sent.elapsed()));
+        }
+    }
+}
+
+impl Prober {
+    /// Opens the sockets that a trace of `target` with `protocol` and
+    /// `multipath` needs.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason when neither a socket of its own for each probe nor a
+    /// raw socket opens, and when a trace of fixed flows finds no raw socket
+    /// or no route to the target. The message names what the user can change.
+    pub(crate) fn open(
+        target: IpAddr,
+        protocol: Protocol,
+        multipath: Multipath,
+    ) -> Result<Self, ProbeError> {
+        let ident = u16::try_from(std::process::id() & u32::from(u16::MAX)).unwrap_or_default();
+        let count = match (multipath, protocol) {
+            // The configuration refuses a multipath mode with ICMP before a
+            // trace starts, because an echo request carries no port to hold.
+            (Multipath::Classic, _) | (_, Protocol::Icmp) => None,
+            (Multipath::Paris, _) => Some(1),
+            (Multipath::Dublin, _) => Some(DUBLIN_FLOWS),
+        };
+        let mode = match count {
+            Some(count) => Mode::Flows(Flows::open(target, protocol, ident, count)?),
+            None => Self::own_or_listener(target, protocol)?,
+        };
+        Ok(Self {
+            target,
+            protocol,
+            mode,
+            ident,
+            next_seq: 0,
+        })
+    }
+
+    /// Picks a socket of its own for each probe where the system allows one,
+    /// and the raw ICMP socket where it does not.
+    fn own_or_listener(target: IpAddr, protocol: Protocol) -> Result<Mode, ProbeError> {
+        Ok(match own_socket_probe(target, protocol) {
+            Ok(_) => Mode::OwnSocket,
+            Err(own) => match listener(target) {
+                Ok(socket) => Mode::Listener(socket),
+                Err(raw) => {
+                    let (protocol, need) = needs(protocol);
+                    let source = if own.kind() == io::ErrorKind::Unsupported {
+                        raw
+                    } else {
+                        own
+                    };
+                    return Err(ProbeError::NoSocket {
+                        protocol,
+                        need,
+                        source,
+                    });
+                }
+            },
+        })
+    }
+
+    /// The privilege that the probes of this trace use.
+    pub(crate) fn privilege(&self) -> Privilege {
+        match self.mode {
+            Mode::OwnSocket => Privilege::Unprivileged,
+            Mode::Listener(_) | Mode::Flows(_) => Privilege::Privileged,
+        }
+    }
+
+    /// Sends one probe for each TTL from `first` to `last`, on each flow of
+    /// the round, and waits up to `wait` for the answers.
+    ///
+    /// The wait ends early once every TTL up to the one that reached the target
+    /// has answered, and at once when `stop` turns true. A probe that drew no
+    /// answer by then is a hop that did not answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason when a probe does not leave, or when the wait fails.
+    pub(crate) fn round(
+        &mut self,
+        first: u8,
+        last: u8,
+        wait: Duration,
+        stop: &AtomicBool,
+    ) -> Result<RoundResult, ProbeError> {
+        let start = Instant::now();
+        let count = match &self.mode {
+            Mode::Flows(flows) => flows.count,
+            Mode::OwnSocket | Mode::Listener(_) => 1,
+        };
+        let mut probes = Vec::with_capacity((usize::from(last - first) + 1) * usize::from(count));
+        for ttl in first..=last {
+            for flow in 0..count {
+                probes.push(self.send(ttl, flow)?);
+            }
+        }
+
+        let deadline = start + wait;
+        while !complete(&probes, last, self.target) && !stop.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            self.collect(&mut probes, (deadline - now).min(POLL_SLICE))?;
+        }
+        Ok(self.result(&probes, last))
+    }
+
+    /// Sends the probe of one TTL on one flow. A trace that does not hold its
+    /// flows sends one probe for each TTL, on flow zero.
+    fn send(&mut self, ttl: u8, flow: u16) -> Result<Probe, ProbeError> {
+        let v6 = self.target.is_ipv6();
+        let sent = |socket: Option<Socket>, quote: Option<Quote>| Probe {
+            ttl,
+            sent: Instant::now(),
+            socket,
+            quote,
+            mark: None,
+            flow: None,
+            found: None,
+        };
+        match (&self.mode, self.protocol) {
+            (Mode::Flows(flows), protocol) => {
+                // The count skips zero, which a UDP checksum cannot hold.
+                self.next_seq = self.next_seq.wrapping_add(1).max(1);
+                let mark = self.next_seq;
+                let from = SocketAddr::new(flows.source, flows.first_port.wrapping_add(flow));
+                let source_port = from.port();
+                // `open` builds flows for UDP and TCP alone.
+                let (bytes, quote) = match protocol {
+                    Protocol::Tcp => (
+                        packet::tcp_probe(
+                            from,
+                            SocketAddr::new(self.target, TCP_PORT),
+                            u32::from(mark),
+                        ),
+                        Quote::Tcp { source_port },
+                    ),
+                    Protocol::Udp | Protocol::Icmp => (
+                        packet::udp_probe(from, SocketAddr::new(self.target, UDP_BASE_PORT), mark),
+                        Quote::Udp { source_port },
+                    ),
+                };
+                set_ttl(&flows.writer, v6, ttl)?;
+                let mut probe = sent(None, Some(quote));
+                probe.mark = Some(u32::from(mark));
+                probe.flow = (flows.count > 1).then_some(flow);
+                flows.writer.send_to(&bytes, &self.address(0))?;
+                Ok(probe)
+            }
+            (Mode::Listener(raw), Protocol::Icmp) => {
+                let seq = self.next_seq;
+                self.next_seq = self.next_seq.wrapping_add(1);
+                set_ttl(raw, v6, ttl)?;
+                let probe = sent(
+                    None,
+                    Some(Quote::Echo {
+                        ident: self.ident,
+                        seq,
+                    }),
+                );
+                raw.send_to(&packet::echo_request(v6, self.ident, seq), &self.address(0))?;
+                Ok(probe)
+            }
+            (Mode::OwnSocket, Protocol::Icmp) => {
+                let socket = self.socket(Type::DGRAM, icmp_transport(v6), ttl)?;
+                let probe_packet = packet::echo_request(v6, 0, u16::from(ttl));
+                let mut probe = sent(None, None);
+                socket.send_to(&probe_packet, &self.address(0))?;
+                probe.socket = Some(socket);
+                Ok(probe)
+            }
+            (_, Protocol::Udp) => {
+                let socket = self.socket(Type::DGRAM, Transport::UDP, ttl)?;
+                let port = UDP_BASE_PORT.wrapping_add(u16::from(ttl - 1));
+                let mut probe = sent(None, None);
+                socket.send_to(packet::PROBE_PAYLOAD, &self.address(port))?;
+                probe.quote = Some(Quote::Udp {
+                    source_port: local_port(&socket)?,
+                });
+                probe.socket = Some(socket);
+                Ok(probe)
+            }
+            (_, Protocol::Tcp) => {
+                let socket = self.socket(Type::STREAM, Transport::TCP, ttl)?;
+                let mut probe = sent(None, None);
+                match socket.connect(&self.address(TCP_PORT)) {
+                    Ok(()) => probe.answer(self.target, Answer::SynAck),
+                    Err(error) if error.raw_os_error() == Some(Errno::EINPROGRESS as i32) => {}
+                    Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+                        probe.answer(self.target, Answer::Reset);
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+                probe.quote = Some(Quote::Tcp {
+                    source_port: local_port(&socket)?,
+                });
+                probe.socket = Some(socket);
+                Ok(probe)
+            }
+        }
+    }
+
+    /// Opens the socket of one probe, ready to send with `ttl`.
+    fn socket(&self, kind: Type, transport: Transport, ttl: u8) -> io::Result<Socket> {
+        let v6 = self.target.is_ipv6();
+        let socket = Socket::new(domain(self.target), kind, Some(transport))?;
+        socket.set_nonblocking(true)?;
+        if matches!(self.mode, Mode::OwnSocket) {
+            receive_errors(&socket, v6)?;
+        }
+        set_ttl(&socket, v6, ttl)?;
+        Ok(socket)
+    }
+
+    /// The socket address of the target, at `port`.
+    fn address(&self, port: u16) -> SockAddr {
+        SockAddr::from(SocketAddr::new(self.target, port))
+    }
+
+    /// Waits up to `timeout` for one batch of answers, and reads every answer
+    /// that arrived.
+    fn collect(&self, probes: &mut [Probe], timeout: Duration) -> Result<(), ProbeError> {
+        let ready = {
+            let mut watched: Vec<(Watched, PollFd<'_>)> = Vec::new();
+            match &self.mode {
+                Mode::OwnSocket => {}
+                Mode::Listener(raw) => {
+                    watched.push((
+                        Watched::Listener,
+                        PollFd::new(raw.as_fd(), PollFlags::POLLIN),
+                    ));
+                }
+                Mode::Flows(flows) => {
+                    watched.push((
+                        Watched::Listener,
+                        PollFd::new(flows.listener.as_fd(), PollFlags::POLLIN),
+                    ));
+                    if self.protocol == Protocol::Tcp {
+                        watched.push((
+                            Watched::Replies,
+                            PollFd::new(flows.writer.as_fd(), PollFlags::POLLIN),
+                        ));
+                    }
+                }
+            }
+            for (index, probe) in probes.iter().enumerate() {
+                if probe.found.is_some() {
+                    continue;
+                }
+                if let Some(socket) = &probe.socket {
+                    let events = match self.protocol {
+                        Protocol::Tcp => PollFlags::POLLOUT,
+                        Protocol::Icmp => PollFlags::POLLIN,
+                        // The error queue signals through `POLLERR`, which a
+                        // poll reports without asking.
+                        Protocol::Udp => PollFlags::empty(),
+                    };
+                    watched.push((Watched::Probe(index), PollFd::new(socket.as_fd(), events)));
+                }
+            }
+            let mut fds: Vec<PollFd<'_>> = watched.iter().map(|(_, fd)| *fd).collect();
+            let millis = u16::try_from(timeout.as_millis()).unwrap_or(u16::MAX);
+            match poll(&mut fds, PollTimeout::from(millis)) {
+                Ok(_) => {}
+                // A signal ends the wait early. The caller reads the stop flag.
+                Err(Errno::EINTR) => return Ok(()),
+                Err(errno) => return Err(io::Error::from(errno).into()),
+            }
+            watched
+                .iter()
+                .zip(&fds)
+                .filter_map(|((index, _), fd)| {
+                    fd.revents()
+                        .filter(|events| !events.is_empty())
+                        .map(|events| (*index, events))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for (watched, events) in ready {
+            match watched {
+                Watched::Listener => self.read_listener(probes),
+                Watched::Replies => self.read_replies(probes),
+                Watched::Probe(index) => self.read_own(&mut probes[index], events),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads every message that waits on the raw socket, and hands each one to
+    /// the probe it quotes.
+    fn read_listener(&self, probes: &mut [Probe]) {
+        let raw = match &self.mode {
+            Mode::Listener(raw) => raw,
+            Mode::Flows(flows) => &flows.listener,
+            Mode::OwnSocket => return,
+        };
+        let mut buffer = [0_u8; READ_BUFFER];
+        while let Ok((length, Some(from))) =
+            recvfrom::<SockaddrStorage>(raw.as_raw_fd(), &mut buffer)
+        {
+            let Some(from) = ip_of(&from) else {
+                continue;
+            };
+            let Some(read) = packet::read_raw(from, &buffer[..length]) else {
+                continue;
+            };
+            if let Some(probe) = probes.iter_mut().find(|probe| probe.claims(&read)) {
+                probe.answer(from, read.answer);
+            }
+        }
+    }
+
+    /// Reads every segment that waits on the raw TCP socket of a trace of
+    /// fixed flows, and hands each answer of the target to the probe it
+    /// acknowledges. The socket reads every TCP segment that reaches the host,
+    /// so a segment from any other address answers no probe.
+    fn read_replies(&self, probes: &mut [Probe]) {
+        let Mode::Flows(flows) = &self.mode else {
+            return;
+        };
+        let mut buffer = [0_u8; READ_BUFFER];
+        while let Ok((length, Some(from))) =
+            recvfrom::<SockaddrStorage>(flows.writer.as_raw_fd(), &mut buffer)
+        {
+            if ip_of(&from) != Some(self.target) {
+                continue;
+            }
+            let Some(read) = packet::read_tcp(self.target, &buffer[..length]) else {
+                continue;
+            };
+            if let Some(probe) = probes.iter_mut().find(|probe| probe.claims(&read)) {
+                probe.answer(self.target, read.answer);
+            }
+        }
+    }
+
+    /// Reads the answer that waits on the socket of one probe.
+    fn read_own(&self, probe: &mut Probe, events: PollFlags) {
+        let Some(socket) = &probe.socket else {
+            return;
+        };
+        let v6 = self.target.is_ipv6();
+        if events.contains(PollFlags::POLLERR) {
+            if let Some((from, answer)) = read_error_queue(socket) {
+                probe.answer(from, answer);
+                return;
+            }
+        }
+        match self.protocol {
+            Protocol::Tcp => match socket.take_error() {
+                Ok(None) if events.contains(PollFlags::POLLOUT) => {
+                    probe.answer(self.target, Answer::SynAck);
+                }
+                Ok(Some(error)) if error.kind() == io::ErrorKind::ConnectionRefused => {
+                    probe.answer(self.target, Answer::Reset);
+                }
+                _ => {}
+            },
+            Protocol::Icmp => {
+                let mut buffer = [0_u8; READ_BUFFER];
+                if let Ok((length, Some(from))) =
+                    recvfrom::<SockaddrStorage>(socket.as_raw_fd(), &mut buffer)
+                {
+                    let kind = buffer[..length].first().copied();
+                    let answer = kind.and_then(|kind| Answer::from_icmp(v6, kind, 0));
+                    if let (Some(from), Some(Answer::EchoReply)) = (ip_of(&from), answer) {
+                        probe.answer(from, Answer::EchoReply);
+                    }
+                }
+            }
+            Protocol::Udp => {}
+        }
+    }
+
+    /// Turns the probes of a round into the hops that the round records.
+    fn result(&self, probes: &[Probe], last: u8) -> RoundResult {
+        let last_ttl = stated_ttl(probes, last, self.target);
+        let hops = probes
+            .iter()
+            .filter(|probe| probe.ttl <= last_ttl)
+            .filter_map(|probe| {
+                probe.found.map(|(addr, answer, rtt)| Hop {
+                    ttl: probe.ttl,
+                    addr,
+                    rtt_ms: (rtt.as_secs_f64() * MILLIS_PER_SECOND * RTT_PRECISION).round()
+                        / RTT_PRECISION,
+                    icmp: answer.name().to_owned(),
+                    flow: probe.flow,
+                })
+            })
+            .collect();
+        let flows = match &self.mode {
+            Mode::Flows(flows) if flows.count > 1 => Some(flows.count),
+            Mode::OwnSocket | Mode::Listener(_) | Mode::Flows(_) => None,
+        };
+        RoundResult {
+            hops,
+            reached: probes.iter().any(|probe| probe.reached(self.target)),
+            last_ttl,
+            flows,
+        }
+    }
+}
+
+impl Flows {
+    /// Opens the two raw sockets of a trace that holds its flows, and binds
+    /// the writer to the address that the system routes to the target from.
+    fn open(
+        target: IpAddr,
+        protocol: Protocol,
+        ident: u16,
+        count: u16,
+    ) -> Result<Self, ProbeError> {
+        let (name, _) = needs(protocol);
+        let no_socket = |source| ProbeError::NoSocket {
+            protocol: name,
+            need: FLOW_NEED,
+            source,
+        };
+        let transport = if protocol == Protocol::Tcp {
+            Transport::TCP
+        } else {
+            Transport::UDP
+        };
+        let listener = listener(target).map_err(no_socket)?;
+        let writer = Socket::new(domain(target), Type::RAW, Some(transport)).map_err(no_socket)?;
+        writer.set_nonblocking(true)?;
+        let source = route_source(target)?;
+        writer.bind(&SockAddr::from(SocketAddr::new(source, 0)))?;
+        Ok(Self {
+            listener,
+            writer,
+            source,
+            first_port: FLOW_FIRST_PORT + ident % FLOW_PORT_SPREAD,
+            count,
+        })
+    }
+}
+
+/// The last TTL that the round states.
+///
+/// Each flow ends at the lowest TTL whose probe the target answered, and a
+/// flow that never reached the target states every TTL up to `last`. The round
+/// ends where its longest flow ends, because a branch of a load balancer can
+/// hold more hops than another.
+fn stated_ttl(probes: &[Probe], last: u8, target: IpAddr) -> u8 {
+    let mut ends: BTreeMap<Option<u16>, u8> = BTreeMap::new();
+    for probe in probes {
+        let end = ends.entry(probe.flow).or_insert(last);
+        if probe.reached(target) {
+            *end = (*end).min(probe.ttl);
+        }
+    }
+    ends.into_values().max().unwrap_or(last)
+}
+
+/// True when every TTL that the round states has answered, so that more
+/// waiting finds nothing new.
+fn complete(probes: &[Probe], last: u8, target: IpAddr) -> bool {
+    let last = stated_ttl(probes, last, target);
+    probes
+        .iter()
+        .filter(|probe| probe.ttl <= last)
+        .all(|probe| probe.found.is_some())
+}
+
+/// Finds the address that the system routes to `target` from, which a UDP
+/// socket learns when it connects. A connect of a UDP socket sends nothing.
+///
+/// # Errors
+///
+/// Returns the reason when the system finds no route to the target.
+pub(crate) fn route_source(target: IpAddr) -> io::Result<IpAddr> {
+    let unspecified = if target.is_ipv6() {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    } else {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+    socket.connect(SocketAddr::new(target, DISCARD_PORT))?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// The name and the need of a protocol, for the message of a socket that did
+/// not open.
+fn needs(protocol: Protocol) -> (&'static str, &'static str) {
+    match protocol {
+        Protocol::Icmp => (
+            "ICMP",
+            "a datagram socket of ICMP, which `net.ipv4.ping_group_range` allows, or the privilege of a raw socket",
+        ),
+        Protocol::Udp => ("UDP", "the privilege of a raw socket on this system"),
+        Protocol::Tcp => ("TCP", "the privilege of a raw socket on this system"),
+    }
+}
+
+/// The address family of `target`, as a socket names it.
+fn domain(target: IpAddr) -> Domain {
+    if target.is_ipv6() {
+        Domain::IPV6
+    } else {
+        Domain::IPV4
+    }
+}
+
+/// The ICMP protocol of the version of a target.
+fn icmp_transport(v6: bool) -> Transport {
+    if v6 {
+        Transport::ICMPV6
+    } else {
+        Transport::ICMPV4
+    }
+}
+
+/// Sets the TTL, or the hop limit of version 6, of every packet the socket
+/// sends next.
+fn set_ttl(socket: &Socket, v6: bool, ttl: u8) -> io::Result<()> {
+    if v6 {
+        socket.set_unicast_hops_v6(u32::from(ttl))
+    } else {
+        socket.set_ttl_v4(u32::from(ttl))
+    }
+}
+
+/// The local port of a socket that has sent or connected.
+fn local_port(socket: &Socket) -> io::Result<u16> {
+    socket
+        .local_addr()?
+        .as_socket()
+        .map(|address| address.port())
+        .ok_or_else(|| io::Error::other("the socket of a probe holds no port"))
+}
+
+/// Reads the IP address of a socket address.
+fn ip_of(address: &SockaddrStorage) -> Option<IpAddr> {
+    address
+        .as_sockaddr_in()
+        .map(|v4| IpAddr::V4(v4.ip()))
+        .or_else(|| address.as_sockaddr_in6().map(|v6| IpAddr::V6(v6.ip())))
+}
+
+/// Opens the raw ICMP socket that reads the answers of every probe.
+fn listener(target: IpAddr) -> io::Result<Socket> {
+    let socket = Socket::new(
+        domain(target),
+        Type::RAW,
+        Some(icmp_transport(target.is_ipv6())),
+    )?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Opens one socket of the kind that a probe of `protocol` leaves through when
+/// it needs no raw socket, to learn whether the system allows it.
+#[cfg(target_os = "linux")]
+fn own_socket_probe(target: IpAddr, protocol: Protocol) -> io::Result<Socket> {
+    let (kind, transport) = match protocol {
+        Protocol::Icmp => (Type::DGRAM, icmp_transport(target.is_ipv6())),
+        Protocol::Udp => (Type::DGRAM, Transport::UDP),
+        Protocol::Tcp => (Type::STREAM, Transport::TCP),
+    };
+    let socket = Socket::new(domain(target), kind, Some(transport))?;
+    receive_errors(&socket, target.is_ipv6())?;
+    Ok(socket)
+}
+
+/// Outside Linux no error queue hands an ICMP error to the socket of a probe,
+/// so every trace reads its answers through a raw socket.
+#[cfg(not(target_os = "linux"))]
+fn own_socket_probe(_target: IpAddr, _protocol: Protocol) -> io::Result<Socket> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Asks the kernel to queue each ICMP error that answers the socket.
+#[cfg(target_os = "linux")]
+fn receive_errors(socket: &Socket, v6: bool) -> io::Result<()> {
+    use nix::sys::socket::{setsockopt, sockopt};
+    let result = if v6 {
+        setsockopt(socket, sockopt::Ipv6RecvErr, &true)
+    } else {
+        setsockopt(socket, sockopt::Ipv4RecvErr, &true)
+    };
+    result.map_err(io::Error::from)
+}
+
+/// Outside Linux no socket queues its errors, and no trace asks it to.
+#[cfg(not(target_os = "linux"))]
+fn receive_errors(_socket: &Socket, _v6: bool) -> io::Result<()> {
+    Ok(())
+}
+
+/// Reads the ICMP errors that wait in the error queue of a socket, and returns
+/// the hop and the answer of the first one that an ICMP message sent.
+///
+/// An error that the local stack raised names no hop, so the read skips it.
+#[cfg(target_os = "linux")]
+fn read_error_queue(socket: &Socket) -> Option<(IpAddr, Answer)> {
+    use nix::sys::socket::{recvmsg, ControlMessageOwned, MsgFlags};
+    use std::io::IoSliceMut;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut found = None;
+    loop {
+        let mut buffer = [0_u8; READ_BUFFER];
+        let mut iov = [IoSliceMut::new(&mut buffer)];
+        let mut control = nix::cmsg_space!(libc::sock_extended_err, libc::sockaddr_in6);
+        let Ok(message) = recvmsg::<SockaddrStorage>(
+            socket.as_raw_fd(),
+            &mut iov,
+            Some(&mut control),
+            MsgFlags::MSG_ERRQUEUE,
+        ) else {
+            return found;
+        };
+        let Ok(messages) = message.cmsgs() else {
+            continue;
+        };
+        for control in messages {
+            let read = match control {
+                ControlMessageOwned::Ipv4RecvErr(error, Some(from))
+                    if error.ee_origin == libc::SO_EE_ORIGIN_ICMP =>
+                {
+                    Answer::from_icmp(false, error.ee_type, error.ee_code).map(|answer| {
+                        let from = Ipv4Addr::from(u32::from_be(from.sin_addr.s_addr));
+                        (IpAddr::V4(from), answer)
+                    })
+                }
+                ControlMessageOwned::Ipv6RecvErr(error, Some(from))
+                    if error.ee_origin == libc::SO_EE_ORIGIN_ICMP6 =>
+                {
+                    Answer::from_icmp(true, error.ee_type, error.ee_code)
+                        .map(|answer| (IpAddr::V6(Ipv6Addr::from(from.sin6_addr.s6_addr)), answer))
+                }
+                _ => None,
+            };
+            if found.is_none() {
+                found = read;
+            }
+        }
+    }
+}
+
+/// Outside Linux no socket queues its errors, so no queue holds an answer.
+#[cfg(not(target_os = "linux"))]
+fn read_error_queue(_socket: &Socket) -> Option<(IpAddr, Answer)> {
+    None
+}
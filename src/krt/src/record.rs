@@ -4,8 +4,8 @@
 //! record, and every record carries the identifier of the run it belongs to.
 //! This module builds the records, the two functions that turn a record into
 //! one line and back, the reader that loads a whole file, and the writer that
-//! appends to one. The `replay` command reads through this module. The writer
-//! waits for the tracer, which arrives in a later slice.
+//! appends to one. The `replay` command reads through this module, and the
+//! tracer writes through it.
 
 use crate::{Multipath, Protocol};
 use chrono::{DateTime, SecondsFormat, Utc};
@@ -78,10 +78,6 @@ pub(crate) struct RunId(String);
 
 impl RunId {
     /// Builds the identifier of the run that starts at this moment.
-    #[allow(
-        dead_code,
-        reason = "the tracer opens a new run and builds its identifier, and the tracer arrives in a later slice of issue #366"
-    )]
     pub(crate) fn at(start: DateTime<Utc>) -> Self {
         Self(format_millis(start))
     }
@@ -134,10 +130,6 @@ impl Record {
     /// # Errors
     ///
     /// Returns the reason when the record does not become JSON.
-    #[allow(
-        dead_code,
-        reason = "the writer is the one caller, and the tracer that runs the writer arrives in a later slice of issue #366"
-    )]
     pub(crate) fn to_line(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
     }
@@ -297,6 +289,13 @@ pub(crate) struct RoundRecord {
     /// A hop that did not answer is absent, and `ttl_range` states which TTLs
     /// the round probed.
     pub(crate) hops: Vec<Hop>,
+    /// The number of flows that the round probed at each TTL, when the run
+    /// walks the flows with `--multipath dublin`.
+    ///
+    /// A round of one flow leaves the field out, so the rounds of every other
+    /// mode write the lines they always wrote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) flows: Option<u16>,
 }
 
 /// One hop that answered a probe.
@@ -310,6 +309,16 @@ pub(crate) struct Hop {
     pub(crate) rtt_ms: f64,
     /// The name of the ICMP message that answered.
     pub(crate) icmp: String,
+    /// The flow of the probe, from zero, when the run walks the flows with
+    /// `--multipath dublin`.
+    ///
+    /// Each flow leaves from a source port of its own, and a load balancer
+    /// can send each one down a branch of its own. The hops of one TTL across
+    /// the flows of a round are the set of routers that answer at that TTL, so
+    /// a reader of the file sees every branch and not one path that mixes
+    /// them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) flow: Option<u16>,
 }
 
 /// The TTLs that one round probed, as the two numbers of a JSON array.
@@ -336,28 +345,16 @@ impl TtlRange {
     }
 
     /// The first TTL of the round.
-    #[allow(
-        dead_code,
-        reason = "the aggregate table shows a row for every TTL that the round probed, and the table arrives in a later slice of issue #366"
-    )]
     pub(crate) fn first(self) -> u8 {
         self.first
     }
 
     /// The last TTL of the round.
-    #[allow(
-        dead_code,
-        reason = "the aggregate table shows a row for every TTL that the round probed, and the table arrives in a later slice of issue #366"
-    )]
     pub(crate) fn last(self) -> u8 {
         self.last
     }
 
     /// True when the round probed this TTL.
-    #[allow(
-        dead_code,
-        reason = "the aggregate table asks whether a round probed a TTL, to part a hop that did not answer from a hop the round never probed, and the table arrives in a later slice of issue #366"
-    )]
     pub(crate) fn contains(self, ttl: u8) -> bool {
         (self.first..=self.last).contains(&ttl)
     }
@@ -629,10 +626,6 @@ impl<'a> Run<'a> {
     }
 
     /// The names that the run read.
-    #[allow(
-        dead_code,
-        reason = "the aggregate table shows the name of each hop, and the table arrives in a later slice of issue #366"
-    )]
     pub(crate) fn names(&self) -> &[&'a NameRecord] {
         &self.names
     }
@@ -643,10 +636,6 @@ impl<'a> Run<'a> {
     }
 
     /// The record that closed the run. A run that still goes holds none.
-    #[allow(
-        dead_code,
-        reason = "the aggregate table shows why the run stopped, and the table arrives in a later slice of issue #366"
-    )]
     pub(crate) fn end(&self) -> Option<&'a EndRecord> {
         self.end
     }
@@ -698,19 +687,11 @@ pub(crate) enum ReadError {
 /// process, and the operating system keeps the bytes that the process already
 /// gave it. An `fsync` guards against a power loss, which is a different fault,
 /// and not the fault that this guarantee names.
-#[allow(
-    dead_code,
-    reason = "the tracer records each round through this writer, and the tracer arrives in a later slice of issue #366"
-)]
 pub(crate) struct Writer {
     /// The open file, in append mode.
     file: BufWriter<File>,
 }
 
-#[allow(
-    dead_code,
-    reason = "the tracer records each round through this writer, and the tracer arrives in a later slice of issue #366"
-)]
 impl Writer {
     /// Opens the file for appending, and makes the file when it is absent.
     ///
@@ -880,14 +861,17 @@ mod tests {
                     addr: address(FIRST_HOP),
                     rtt_ms: 1.23,
                     icmp: "time_exceeded".to_owned(),
+                    flow: None,
                 },
                 Hop {
                     ttl: 14,
                     addr: address(TARGET_ADDRESS),
                     rtt_ms: 24.10,
                     icmp: "echo_reply".to_owned(),
+                    flow: None,
                 },
             ],
+            flows: None,
         })
     }
 
@@ -992,7 +976,9 @@ mod tests {
                 addr: address(FIRST_HOP),
                 rtt_ms: 1.23,
                 icmp: "time_exceeded".to_owned(),
+                flow: None,
             }],
+            flows: None,
         });
         let line = line_of(&record);
         assert!(
@@ -1009,6 +995,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn a_round_of_one_flow_writes_no_flow_and_a_walk_of_flows_names_each() {
+        let hop = |flow| Hop {
+            ttl: 2,
+            addr: address(FIRST_HOP),
+            rtt_ms: 1.5,
+            icmp: "time_exceeded".to_owned(),
+            flow,
+        };
+        let round = |hops, flows| {
+            Record::Round(RoundRecord {
+                run: RunId::from(RUN),
+                seq: 1,
+                ts: moment("2026-08-18T12:34:56.789Z"),
+                dur_ms: 12,
+                ttl_range: TtlRange::new(1, 2).expect("the test range must hold"),
+                reached: false,
+                hops,
+                flows,
+            })
+        };
+        let single = line_of(&round(vec![hop(None)], None));
+        assert!(!single.contains("flow"), "{single}");
+
+        let walk = round(vec![hop(Some(0)), hop(Some(3))], Some(4));
+        let line = line_of(&walk);
+        assert!(line.contains(r#""flows":4"#), "{line}");
+        assert!(line.contains(r#""flow":3"#), "{line}");
+        assert_eq!(
+            Record::from_line(&line).expect("the line reads back"),
+            Some(walk)
+        );
+    }
+
     #[test]
     fn a_range_that_runs_backward_is_a_fault() {
         let message = TtlRange::new(5, 3)
@@ -1729,6 +1749,7 @@ mod tests {
             addr: address(addr),
             rtt_ms,
             icmp: icmp.to_owned(),
+            flow: None,
         }
     }
 
@@ -1748,6 +1769,7 @@ mod tests {
                 hop_at(1, FIRST_HOP, 1.23, TIME_EXCEEDED),
                 hop_at(3, TARGET_ADDRESS, 24.10, ECHO_REPLY),
             ],
+            flows: None,
         })
     }
 
@@ -1764,6 +1786,7 @@ mod tests {
             ttl_range: TtlRange::new(1, 3).expect("the fixture range must hold"),
             reached: false,
             hops: vec![hop_at(1, FIRST_HOP, 1.41, TIME_EXCEEDED)],
+            flows: None,
         })
     }
 
@@ -1817,6 +1840,7 @@ mod tests {
                 hop_at(1, SECOND_FIRST_HOP, 0.87, TIME_EXCEEDED),
                 hop_at(2, SECOND_TARGET_ADDRESS, 12.5, ECHO_REPLY),
             ],
+            flows: None,
         })
     }
 
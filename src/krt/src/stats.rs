@@ -0,0 +1,394 @@
+//! The fold of the rounds of a run into one line of statistics for each TTL.
+//!
+//! The fold takes the rounds one at a time, in the order that the run made
+//! them, so the live table folds each round as the tracer appends it, and it
+//! never reads a round twice. The fold keeps running sums and no list of round
+//! trip times, so its size follows the length of the path and not the length
+//! of the run.
+//!
+//! A TTL counts one probe for each round whose range holds it. A round that
+//! reached the target at TTL 5 probed no TTL above 5, so a TTL above 5 counts
+//! no loss for that round. A TTL counts one answer for each round that holds a
+//! hop at it. A round that walks several flows sends one probe for each flow
+//! at each TTL, so the TTL counts one probe and at most one answer for each
+//! flow.
+//!
+//! Several addresses can answer at one TTL, when a load balancer spreads the
+//! probes over parallel links. Each TTL keeps every address that answered, and
+//! the address that answered last comes first. The table therefore reorders
+//! the addresses of a TTL when its responder changes.
+//!
+//! A replay folds the rounds of a recorded run the same way, so its report
+//! holds the numbers that the live table of the trace showed.
+
+use crate::record::{NameRecord, RoundRecord, Run};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+
+/// The number of parts in one whole, for a loss in percent.
+const PERCENT: f64 = 100.0;
+
+/// The statistics of the answers at one TTL.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TtlStats {
+    /// The number of probes that rounds sent at this TTL.
+    sent: u64,
+    /// The number of probes that drew an answer at this TTL.
+    received: u64,
+    /// The round trip time of the last answer, in milliseconds.
+    last: Option<f64>,
+    /// The shortest round trip time, in milliseconds.
+    best: Option<f64>,
+    /// The longest round trip time, in milliseconds.
+    worst: Option<f64>,
+    /// The sum of every round trip time, in milliseconds.
+    sum: f64,
+    /// The sum of the square of every round trip time.
+    sum_of_squares: f64,
+    /// The sum of the change between two answers in a row, in milliseconds.
+    jitter_sum: f64,
+    /// The addresses that answered, with the number of the round that last
+    /// heard each one.
+    responders: Vec<(IpAddr, u64)>,
+}
+
+impl TtlStats {
+    /// Counts one answer.
+    fn answer(&mut self, seq: u64, addr: IpAddr, rtt: f64) {
+        if let Some(previous) = self.last {
+            self.jitter_sum += (rtt - previous).abs();
+        }
+        self.received += 1;
+        self.last = Some(rtt);
+        self.best = Some(self.best.map_or(rtt, |best| best.min(rtt)));
+        self.worst = Some(self.worst.map_or(rtt, |worst| worst.max(rtt)));
+        self.sum += rtt;
+        self.sum_of_squares += rtt * rtt;
+        match self.responders.iter_mut().find(|(held, _)| *held == addr) {
+            Some(responder) => responder.1 = seq,
+            None => self.responders.push((addr, seq)),
+        }
+        // The sort is stable, so two addresses that one round heard keep the
+        // order of their first answer.
+        self.responders.sort_by_key(|(_, seen)| Reverse(*seen));
+    }
+
+    /// The count of answers as a float, for a mean.
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "a count of rounds stays far below 2^52, where a float stops holding every whole number"
+    )]
+    fn count(value: u64) -> f64 {
+        value as f64
+    }
+}
+
+/// One line of the table: the statistics of one TTL and the addresses that
+/// answered at it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HopRow {
+    /// The TTL of the line.
+    pub(crate) ttl: u8,
+    /// The addresses that answered, the last one first, each with its name
+    /// when reverse DNS gave one. A TTL that never answered holds none.
+    pub(crate) responders: Vec<(IpAddr, Option<String>)>,
+    /// The part of the probes that drew no answer, in percent.
+    pub(crate) loss: f64,
+    /// The number of probes that rounds sent at this TTL.
+    pub(crate) sent: u64,
+    /// The round trip time of the last answer, in milliseconds.
+    pub(crate) last: Option<f64>,
+    /// The mean round trip time, in milliseconds.
+    pub(crate) avg: Option<f64>,
+    /// The shortest round trip time, in milliseconds.
+    pub(crate) best: Option<f64>,
+    /// The longest round trip time, in milliseconds.
+    pub(crate) worst: Option<f64>,
+    /// The standard deviation of the round trip time, in milliseconds.
+    pub(crate) stddev: Option<f64>,
+    /// The mean change between two answers in a row, in milliseconds.
+    pub(crate) jitter: Option<f64>,
+}
+
+/// The fold of every round of one run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PathStats {
+    /// The statistics of each TTL that a round probed.
+    ttls: BTreeMap<u8, TtlStats>,
+    /// The name of each address that reverse DNS named.
+    names: HashMap<IpAddr, String>,
+    /// The number of rounds folded.
+    rounds: u64,
+    /// True when a round reached the target.
+    reached: bool,
+}
+
+impl PathStats {
+    /// Folds every round and every name of a recorded run.
+    pub(crate) fn of(run: &Run<'_>) -> Self {
+        let mut stats = Self::default();
+        for round in run.rounds() {
+            stats.add_round(round);
+        }
+        for name in run.names() {
+            stats.add_name(name);
+        }
+        stats
+    }
+
+    /// Folds one round.
+    pub(crate) fn add_round(&mut self, round: &RoundRecord) {
+        self.rounds += 1;
+        self.reached |= round.reached;
+        let probes = u64::from(round.flows.unwrap_or(1));
+        for ttl in round.ttl_range.first()..=round.ttl_range.last() {
+            self.ttls.entry(ttl).or_default().sent += probes;
+        }
+        for hop in &round.hops {
+            // A hop outside the range of its round breaks the format. The
+            // fold still counts it, so its probe counts too, and the loss of
+            // the TTL never drops below zero.
+            let stats = self.ttls.entry(hop.ttl).or_default();
+            if !round.ttl_range.contains(hop.ttl) {
+                stats.sent += 1;
+            }
+            stats.answer(round.seq, hop.addr, hop.rtt_ms);
+        }
+    }
+
+    /// Folds the name of one address.
+    pub(crate) fn add_name(&mut self, name: &NameRecord) {
+        self.names.insert(name.addr, name.host.clone());
+    }
+
+    /// The number of rounds folded.
+    pub(crate) fn rounds(&self) -> u64 {
+        self.rounds
+    }
+
+    /// True when a round reached the target.
+    pub(crate) fn reached(&self) -> bool {
+        self.reached
+    }
+
+    /// The lines of the table, one for each TTL up to the highest TTL that
+    /// answered. A TTL above that one holds no line, because no hop answered
+    /// there and the target lies at or below it.
+    pub(crate) fn rows(&self) -> Vec<HopRow> {
+        let Some(highest) = self
+            .ttls
+            .iter()
+            .filter(|(_, stats)| stats.received > 0)
+            .map(|(ttl, _)| *ttl)
+            .max()
+        else {
+            return Vec::new();
+        };
+        self.ttls
+            .range(..=highest)
+            .map(|(ttl, stats)| self.row(*ttl, stats))
+            .collect()
+    }
+
+    /// Builds the line of one TTL.
+    fn row(&self, ttl: u8, stats: &TtlStats) -> HopRow {
+        let received = TtlStats::count(stats.received);
+        let sent = TtlStats::count(stats.sent);
+        let answered = stats.received > 0;
+        let avg = answered.then(|| stats.sum / received);
+        let stddev = avg.map(|avg| {
+            (stats.sum_of_squares / received - avg * avg)
+                .max(0.0)
+                .sqrt()
+        });
+        let jitter =
+            (stats.received > 1).then(|| stats.jitter_sum / TtlStats::count(stats.received - 1));
+        let loss = if stats.sent == 0 {
+            0.0
+        } else {
+            (sent - received) / sent * PERCENT
+        };
+        HopRow {
+            ttl,
+            responders: stats
+                .responders
+                .iter()
+                .map(|(addr, _)| (*addr, self.names.get(addr).cloned()))
+                .collect(),
+            loss,
+            sent: stats.sent,
+            last: stats.last,
+            avg,
+            best: stats.best,
+            worst: stats.worst,
+            stddev,
+            jitter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathStats;
+    use crate::record::{Hop, NameRecord, RoundRecord, RunId, TtlRange};
+    use chrono::Utc;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// The first router of every test path.
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// A second router that takes over TTL 1 in some tests.
+    const OTHER_ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    /// The target of every test path.
+    const TARGET: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    /// Builds one round from its range and its hops.
+    fn round(seq: u64, last: u8, hops: &[(u8, IpAddr, f64)]) -> RoundRecord {
+        RoundRecord {
+            run: RunId::from("2026-08-18T12:00:00.000Z"),
+            seq,
+            ts: Utc::now(),
+            dur_ms: 10,
+            ttl_range: TtlRange::new(1, last).unwrap_or_else(|error| panic!("{error}")),
+            reached: hops.iter().any(|(_, addr, _)| *addr == TARGET),
+            hops: hops
+                .iter()
+                .map(|(ttl, addr, rtt_ms)| Hop {
+                    ttl: *ttl,
+                    addr: *addr,
+                    rtt_ms: *rtt_ms,
+                    icmp: "time_exceeded".to_owned(),
+                    flow: None,
+                })
+                .collect(),
+            flows: None,
+        }
+    }
+
+    /// Asserts that two floats agree to a millionth.
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap_or_else(|| panic!("expected {expected}, found none"));
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected}, found {actual}"
+        );
+    }
+
+    #[test]
+    fn an_empty_fold_has_no_rows() {
+        assert!(PathStats::default().rows().is_empty());
+    }
+
+    #[test]
+    fn the_round_trip_times_of_a_ttl_fold_into_every_column() {
+        let mut stats = PathStats::default();
+        for (seq, rtt) in [(1, 10.0), (2, 14.0), (3, 12.0)] {
+            stats.add_round(&round(seq, 1, &[(1, ROUTER, rtt)]));
+        }
+        let rows = stats.rows();
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.sent, 3);
+        assert_close(Some(row.loss), 0.0);
+        assert_close(row.last, 12.0);
+        assert_close(row.avg, 12.0);
+        assert_close(row.best, 10.0);
+        assert_close(row.worst, 14.0);
+        // The deviations are -2, 2, and 0, so the variance is 8/3.
+        assert_close(row.stddev, (8.0_f64 / 3.0).sqrt());
+        // The changes are 4 and 2.
+        assert_close(row.jitter, 3.0);
+    }
+
+    #[test]
+    fn a_ttl_that_missed_a_round_counts_the_loss() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, 2, &[(1, ROUTER, 1.0), (2, TARGET, 2.0)]));
+        stats.add_round(&round(2, 2, &[(2, TARGET, 2.0)]));
+        let rows = stats.rows();
+        assert_close(Some(rows[0].loss), 50.0);
+        assert_close(Some(rows[1].loss), 0.0);
+        assert_eq!(rows[0].jitter, None, "one answer has no change to measure");
+    }
+
+    // The second round reached the target at TTL 2 and probed no TTL above it,
+    // so TTL 3 lost one probe of one, not one of two.
+    #[test]
+    fn a_ttl_above_the_range_of_a_round_counts_no_probe_for_it() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, 3, &[(3, TARGET, 3.0)]));
+        stats.add_round(&round(2, 2, &[(2, TARGET, 2.0)]));
+        let rows = stats.rows();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].sent, 1);
+        assert_close(Some(rows[2].loss), 0.0);
+        assert!(rows[0].responders.is_empty(), "TTL 1 never answered");
+        assert_close(Some(rows[0].loss), 100.0);
+    }
+
+    #[test]
+    fn the_responder_that_answered_last_comes_first() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, 1, &[(1, ROUTER, 1.0)]));
+        stats.add_round(&round(2, 1, &[(1, OTHER_ROUTER, 1.0)]));
+        let order = |stats: &PathStats| -> Vec<IpAddr> {
+            stats.rows()[0]
+                .responders
+                .iter()
+                .map(|(addr, _)| *addr)
+                .collect()
+        };
+        assert_eq!(order(&stats), vec![OTHER_ROUTER, ROUTER]);
+        stats.add_round(&round(3, 1, &[(1, ROUTER, 1.0)]));
+        assert_eq!(order(&stats), vec![ROUTER, OTHER_ROUTER]);
+    }
+
+    // Four flows crossed a load balancer at TTL 1: two took each branch, and
+    // one probe drew no answer.
+    #[test]
+    fn a_walk_of_flows_counts_one_probe_per_flow_and_keeps_each_branch() {
+        let mut walk = round(
+            1,
+            1,
+            &[(1, ROUTER, 1.0), (1, OTHER_ROUTER, 2.0), (1, ROUTER, 1.0)],
+        );
+        walk.flows = Some(4);
+        for (flow, hop) in walk.hops.iter_mut().enumerate() {
+            hop.flow = u16::try_from(flow).ok();
+        }
+        let mut stats = PathStats::default();
+        stats.add_round(&walk);
+        let rows = stats.rows();
+        assert_eq!(rows[0].sent, 4);
+        assert_close(Some(rows[0].loss), 25.0);
+        assert_eq!(rows[0].responders.len(), 2, "both branches answered");
+    }
+
+    #[test]
+    fn a_name_reaches_every_row_of_its_address() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, 1, &[(1, ROUTER, 1.0)]));
+        stats.add_name(&NameRecord {
+            run: RunId::from("2026-08-18T12:00:00.000Z"),
+            ts: Utc::now(),
+            addr: ROUTER,
+            host: "gw.example.net".to_owned(),
+        });
+        assert_eq!(
+            stats.rows()[0].responders,
+            vec![(ROUTER, Some("gw.example.net".to_owned()))]
+        );
+    }
+
+    #[test]
+    fn the_fold_counts_rounds_and_whether_one_reached() {
+        let mut stats = PathStats::default();
+        stats.add_round(&round(1, 1, &[(1, ROUTER, 1.0)]));
+        assert!(!stats.reached());
+        stats.add_round(&round(2, 2, &[(2, TARGET, 1.0)]));
+        assert!(stats.reached());
+        assert_eq!(stats.rounds(), 2);
+    }
+}
@@ -0,0 +1,211 @@
+//! The text of the table of statistics: one line for each TTL, and one line
+//! for each other address that answered at it.
+//!
+//! The live table of a trace and the report of a replay read the same cells,
+//! so a recorded run prints the table that its trace showed. This module
+//! reads no terminal, so it builds on every system, and the replay of a file
+//! works where no tracer does.
+
+use crate::stats::{HopRow, PathStats};
+use std::fmt::Write as _;
+
+/// The title of each column.
+pub(crate) const HEADERS: [&str; COLUMNS] = [
+    "TTL", "Address", "Name", "Loss%", "Sent", "Last", "Avg", "Best", "Worst", "StDev", "Jitter",
+];
+
+/// The number of columns.
+pub(crate) const COLUMNS: usize = 11;
+
+/// The column of the address.
+const ADDRESS: usize = 1;
+
+/// The column of the name.
+const NAME: usize = 2;
+
+/// The address cell of a TTL that never answered, as mtr writes it.
+pub(crate) const NO_ANSWER: &str = "???";
+
+/// The text between two columns of a plain text table.
+const COLUMN_GAP: &str = "  ";
+
+/// The text of every line of the table, one array of cells for each line.
+///
+/// The first line of a TTL holds its statistics and the address that answered
+/// last. Each other address that answered at the TTL takes a line of its own
+/// below it, with the address and the name alone.
+pub(crate) fn lines(stats: &PathStats) -> Vec<[String; COLUMNS]> {
+    stats.rows().iter().flat_map(cells).collect()
+}
+
+/// The lines of one TTL.
+fn cells(row: &HopRow) -> Vec<[String; COLUMNS]> {
+    let ms = |value: Option<f64>| value.map_or_else(String::new, |value| format!("{value:.1}"));
+    let (first, rest) = match row.responders.split_first() {
+        Some((first, rest)) => (Some(first), rest),
+        None => (None, &[][..]),
+    };
+    let (address, name) = first.map_or_else(
+        || (NO_ANSWER.to_owned(), String::new()),
+        |(addr, name)| (addr.to_string(), name.clone().unwrap_or_default()),
+    );
+    let mut lines = vec![[
+        row.ttl.to_string(),
+        address,
+        name,
+        format!("{:.1}", row.loss),
+        row.sent.to_string(),
+        ms(row.last),
+        ms(row.avg),
+        ms(row.best),
+        ms(row.worst),
+        ms(row.stddev),
+        ms(row.jitter),
+    ]];
+    for (addr, name) in rest {
+        let mut line: [String; COLUMNS] = Default::default();
+        line[ADDRESS] = addr.to_string();
+        line[NAME] = name.clone().unwrap_or_default();
+        lines.push(line);
+    }
+    lines
+}
+
+/// Writes the table of statistics as plain text, for a replay.
+pub(crate) fn text(stats: &PathStats) -> String {
+    aligned(&HEADERS, &lines(stats), &[ADDRESS, NAME])
+}
+
+/// Writes a header and lines of cells as plain text columns.
+///
+/// Each column is as wide as its widest cell, and two spaces separate two
+/// columns. A column in `left` holds text and starts at its left edge. Every
+/// other column holds a number and ends at its right edge, so the decimal
+/// points of one column line up. No line ends in a space.
+pub(crate) fn aligned<const N: usize>(
+    headers: &[&str; N],
+    lines: &[[String; N]],
+    left: &[usize],
+) -> String {
+    let mut widths = headers.map(|header| header.chars().count());
+    for line in lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let write = |cells: [&str; N]| {
+        let mut out = String::new();
+        for (column, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            if column > 0 {
+                out.push_str(COLUMN_GAP);
+            }
+            if left.contains(&column) {
+                let _ = write!(out, "{cell:<width$}");
+            } else {
+                let _ = write!(out, "{cell:>width$}");
+            }
+        }
+        out.trim_end().to_owned()
+    };
+    let mut out = write(*headers);
+    out.push('\n');
+    for line in lines {
+        out.push_str(&write(line.each_ref().map(String::as_str)));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aligned, lines, text, NO_ANSWER};
+    use crate::record::{Hop, NameRecord, RoundRecord, RunId, TtlRange};
+    use crate::stats::PathStats;
+    use chrono::Utc;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// The identifier of the run of every test record.
+    const RUN: &str = "2026-08-18T12:00:00.000Z";
+
+    /// The router at TTL 2.
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// The router that answers TTL 2 in the second round.
+    const OTHER_ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    /// Folds two rounds: TTL 1 never answers, and TTL 2 changes its responder.
+    fn two_rounds() -> PathStats {
+        let mut stats = PathStats::default();
+        for (seq, addr, rtt_ms) in [(1, ROUTER, 4.0), (2, OTHER_ROUTER, 6.0)] {
+            stats.add_round(&RoundRecord {
+                run: RunId::from(RUN),
+                seq,
+                ts: Utc::now(),
+                dur_ms: 6,
+                ttl_range: TtlRange::new(1, 2).unwrap_or_else(|error| panic!("{error}")),
+                reached: false,
+                hops: vec![Hop {
+                    ttl: 2,
+                    addr,
+                    rtt_ms,
+                    icmp: "time_exceeded".to_owned(),
+                    flow: None,
+                }],
+                flows: None,
+            });
+        }
+        stats.add_name(&NameRecord {
+            run: RunId::from(RUN),
+            ts: Utc::now(),
+            addr: ROUTER,
+            host: "gw.example.net".to_owned(),
+        });
+        stats
+    }
+
+    #[test]
+    fn a_ttl_that_never_answered_shows_the_mark_and_its_loss() {
+        let lines = lines(&two_rounds());
+        assert_eq!(lines[0][0], "1");
+        assert_eq!(lines[0][1], NO_ANSWER);
+        assert_eq!(lines[0][3], "100.0");
+        assert_eq!(lines[0][5], "", "no answer has no round trip time");
+    }
+
+    #[test]
+    fn the_responder_that_answered_last_holds_the_statistics() {
+        let lines = lines(&two_rounds());
+        assert_eq!(lines.len(), 3, "TTL 1, and TTL 2 with two responders");
+        assert_eq!(
+            lines[1],
+            ["2", "10.0.0.2", "", "0.0", "2", "6.0", "5.0", "4.0", "6.0", "1.0", "2.0"]
+                .map(str::to_owned)
+        );
+        assert_eq!(lines[2][0], "", "a second responder repeats no TTL");
+        assert_eq!(lines[2][1], "10.0.0.1");
+        assert_eq!(lines[2][2], "gw.example.net");
+    }
+
+    #[test]
+    fn a_plain_table_pads_text_to_the_left_and_numbers_to_the_right() {
+        let lines = [
+            ["1".to_owned(), "a".to_owned(), "1.5".to_owned()],
+            ["10".to_owned(), "longer".to_owned(), String::new()],
+        ];
+        assert_eq!(
+            aligned(&["N", "Word", "Ms"], &lines, &[1]),
+            " N  Word     Ms\n 1  a       1.5\n10  longer\n"
+        );
+    }
+
+    #[test]
+    fn the_plain_table_of_statistics_holds_a_line_for_each_line_of_cells() {
+        let text = text(&two_rounds());
+        let mut rows = text.lines();
+        let header = rows.next().unwrap_or_default();
+        assert!(header.starts_with("TTL  Address"), "{header:?}");
+        assert!(header.ends_with("Jitter"), "{header:?}");
+        assert_eq!(rows.count(), 3);
+        assert!(text.contains("10.0.0.1  gw.example.net"), "{text}");
+    }
+}
@@ -0,0 +1,648 @@
+//! The tracer: the loop that probes the path once per round and records each
+//! round.
+//!
+//! A trace resolves the destination, opens the sockets of its probes, and
+//! opens the recorded file. It writes the `run` record, then one `round` record
+//! for each round, then the `end` record. Every record reaches the file before
+//! the next round starts, so a trace that dies loses at most one round.
+//!
+//! A round starts once per interval. A round that takes the whole interval
+//! starts the next round at once, so rounds never overlap and never queue up.
+//!
+//! A trace stops for one of four reasons, and the `end` record names it. The
+//! user stops it with Ctrl-C, the time limit passes, the round limit passes, or
+//! a fault stops it.
+//!
+//! While the trace runs, the live table of `ui.rs` shows the fold of every
+//! round so far, and it redraws after each round. A headless trace, and a trace
+//! whose standard output is no terminal, print status lines in its place. The
+//! names of the hops come from `dns.rs` between two rounds, and each name the
+//! trace learns becomes one `name` record.
+
+use crate::dns::Resolver;
+use crate::probe::{self, ProbeError, Prober};
+use crate::record::{
+    EndReason, EndRecord, Family, NameRecord, Record, RoundRecord, RunConfig, RunId, RunRecord,
+    SourceKind, SourceLabel, Target, TtlRange, TtlRangeError, Writer,
+};
+use crate::stats::PathStats;
+use crate::ui::Screen;
+use crate::{
+    counted, AddressFamily, ResolvedConfig, HOP, NEVER_REACHED, PROGRAM, REACHED, ROUND,
+    SUMMARY_SEPARATOR,
+};
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal as _};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The text that starts the name of a derived file.
+const DERIVED_PREFIX: &str = "krt";
+
+/// The text between two parts of the name of a derived file.
+const DERIVED_SEPARATOR: &str = "_";
+
+/// The extension of a recorded file.
+const DERIVED_EXTENSION: &str = "jsonl";
+
+/// The character that takes the place of every character of a derived name
+/// that a file name should not hold.
+const DERIVED_REPLACEMENT: char = '-';
+
+/// The longest time that one wait between rounds sleeps, so a stop request
+/// never waits out a long interval.
+const SLEEP_SLICE: Duration = Duration::from_millis(100);
+
+/// The period of the status line of a headless trace.
+const HEADLESS_STATUS_PERIOD: Duration = Duration::from_mins(1);
+
+/// The name of the machine, when the system does not give it.
+const HOST_UNKNOWN: &str = "unknown";
+
+/// What the status line says before the path of the recorded file.
+const RECORDING_TO: &str = "recording to";
+
+/// The fault that stops a trace.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TraceError {
+    /// The destination does not resolve.
+    #[error("`{destination}` does not resolve: {source}")]
+    Resolve {
+        /// The destination as the user typed it.
+        destination: String,
+        /// The fault that the resolver reported.
+        source: io::Error,
+    },
+    /// The destination resolves, but to no address of the asked version.
+    #[error("`{destination}` has no {family} address")]
+    NoAddress {
+        /// The destination as the user typed it.
+        destination: String,
+        /// The IP version that the user asked for.
+        family: AddressFamily,
+    },
+    /// The sockets of the probes do not open, or a probe fails.
+    #[error(transparent)]
+    Probe(#[from] ProbeError),
+    /// The recorded file does not open, or a write to it fails.
+    #[error("{}: {source}", path.display())]
+    Output {
+        /// The path of the recorded file.
+        path: PathBuf,
+        /// The fault that the operating system reported.
+        source: io::Error,
+    },
+    /// The handler of Ctrl-C does not install.
+    #[error("the handler of Ctrl-C does not install: {0}")]
+    Signal(io::Error),
+    /// A round states a range of TTLs that runs backward.
+    #[error(transparent)]
+    Range(#[from] TtlRangeError),
+}
+
+/// The limits that stop a trace, and the state that the limits read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Limits {
+    /// The number of rounds that stops the trace.
+    rounds: Option<u64>,
+    /// The time that stops the trace.
+    duration: Option<Duration>,
+}
+
+impl Limits {
+    /// Why the trace stops now, when it stops.
+    ///
+    /// A request of the user comes first, because the user asked for it. The
+    /// round limit comes next, so a trace that reaches both limits in the same
+    /// round records the limit that the user can count.
+    fn reason(self, quit: bool, rounds: u64, elapsed: Duration) -> Option<EndReason> {
+        if quit {
+            return Some(EndReason::Quit);
+        }
+        if self.rounds.is_some_and(|limit| rounds >= limit) {
+            return Some(EndReason::Rounds);
+        }
+        if self.duration.is_some_and(|limit| elapsed >= limit) {
+            return Some(EndReason::Duration);
+        }
+        None
+    }
+}
+
+/// Runs the trace that the configuration names, until one of the limits or the
+/// user stops it.
+///
+/// # Errors
+///
+/// Returns the reason when the destination does not resolve, when the sockets
+/// of the probes do not open, when the recorded file does not open, and when a
+/// round or a write fails. A fault after the `run` record still writes the
+/// `end` record, with the reason `error`.
+pub(crate) fn run(config: &ResolvedConfig) -> Result<(), TraceError> {
+    let destination = config.destination.clone().unwrap_or_default();
+    let target = resolve(&destination, config.address_family)?;
+    let mut prober = Prober::open(target, config.protocol, config.multipath)?;
+    let source = source_label(config.source, target);
+    let path = config
+        .output
+        .clone()
+        .unwrap_or_else(|| derived_path(source.addr, &destination));
+    let output = |source: io::Error| TraceError::Output {
+        path: path.clone(),
+        source,
+    };
+    let mut writer = Writer::append(&path).map_err(output)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register(signal, Arc::clone(&stop)).map_err(TraceError::Signal)?;
+    }
+
+    let run = RunId::at(Utc::now());
+    let title = format!("{PROGRAM} {destination} ({target})");
+    let start = run_record(config, &run, destination, target, source, &prober);
+    writer.write(&start).map_err(output)?;
+    print!("{config}");
+    println!("{RECORDING_TO} {}", path.display());
+
+    let mut session = Session {
+        run,
+        writer,
+        names: config.reverse_dns.then(Resolver::start),
+        stats: PathStats::default(),
+        progress: Progress::open(config.headless),
+        title,
+        rounds: 0,
+    };
+    let limits = Limits {
+        rounds: config.rounds,
+        duration: config.duration,
+    };
+    let began = Instant::now();
+    let outcome = loop {
+        let round_start = Instant::now();
+        let ts = Utc::now();
+        let result = match prober.round(config.first_ttl, config.max_ttl, config.interval, &stop) {
+            Ok(result) => result,
+            Err(error) => break Err(TraceError::from(error)),
+        };
+        let ttl_range = match TtlRange::new(config.first_ttl, result.last_ttl) {
+            Ok(ttl_range) => ttl_range,
+            Err(error) => break Err(TraceError::from(error)),
+        };
+        session.rounds += 1;
+        let round = RoundRecord {
+            run: session.run.clone(),
+            seq: session.rounds,
+            ts,
+            dur_ms: millis(round_start.elapsed()),
+            ttl_range,
+            reached: result.reached,
+            hops: result.hops,
+            flows: result.flows,
+        };
+        if let Err(error) = session.record(round) {
+            break Err(output(error));
+        }
+
+        let next = round_start + config.interval;
+        let rounds = session.rounds;
+        let reason = wait_until(
+            next,
+            |slice| {
+                if session.progress.pause(slice) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            },
+            || limits.reason(stop.load(Ordering::Relaxed), rounds, began.elapsed()),
+        );
+        if let Some(reason) = reason {
+            break Ok(reason);
+        }
+    };
+
+    let ended = session.finish(outcome.as_ref().map_or(EndReason::Error, |reason| *reason));
+    outcome?;
+    ended.map_err(output)
+}
+
+/// The state of a trace once its `run` record is written.
+struct Session {
+    /// The identifier of the run.
+    run: RunId,
+    /// The recorded file.
+    writer: Writer,
+    /// The reverse DNS of the hops, unless `--no-dns` turned it off.
+    names: Option<Resolver>,
+    /// The fold of every round so far.
+    stats: PathStats,
+    /// Where the trace shows its progress.
+    progress: Progress,
+    /// The title of the table.
+    title: String,
+    /// The number of rounds made.
+    rounds: u64,
+}
+
+impl Session {
+    /// Writes one round and every name that arrived, folds them, and shows
+    /// the progress.
+    fn record(&mut self, round: RoundRecord) -> io::Result<()> {
+        if let Some(names) = &mut self.names {
+            for hop in &round.hops {
+                names.ask(hop.addr);
+            }
+        }
+        self.stats.add_round(&round);
+        let status = status_line(&round);
+        self.writer.write(&Record::Round(round))?;
+        let arrived = self
+            .names
+            .as_ref()
+            .map(Resolver::answers)
+            .unwrap_or_default();
+        for (addr, host) in arrived {
+            let name = NameRecord {
+                run: self.run.clone(),
+                ts: Utc::now(),
+                addr,
+                host,
+            };
+            self.stats.add_name(&name);
+            self.writer.write(&Record::Name(name))?;
+        }
+        self.progress.show(&self.title, &self.stats, &status)
+    }
+
+    /// Gives the terminal back and writes the `end` record with the reason
+    /// that stopped the trace.
+    fn finish(mut self, reason: EndReason) -> io::Result<()> {
+        self.progress.close();
+        let end = Record::End(EndRecord {
+            run: self.run,
+            ts: Utc::now(),
+            rounds: self.rounds,
+            reason,
+        });
+        // The table left no trace on the terminal, so one line says what the
+        // run found.
+        if self.progress.was_table() {
+            let reached = if self.stats.reached() {
+                REACHED
+            } else {
+                NEVER_REACHED
+            };
+            println!(
+                "{}{SUMMARY_SEPARATOR}{reached}",
+                counted(
+                    usize::try_from(self.stats.rounds()).unwrap_or(usize::MAX),
+                    ROUND
+                )
+            );
+        }
+        self.writer.write(&end)
+    }
+}
+
+/// Where a trace shows its progress.
+enum Progress {
+    /// The live table, which holds the terminal until the trace stops.
+    Table(Option<Screen>),
+    /// One status line for each round, or one per minute when headless.
+    Lines {
+        /// True when the trace prints one status line per minute.
+        headless: bool,
+        /// The moment of the last status line.
+        last: Option<Instant>,
+    },
+}
+
+impl Progress {
+    /// Picks the table when the trace is not headless and standard output is
+    /// a terminal. A trace whose output goes to a pipe or a file prints lines,
+    /// because a table in a file is a wall of escape codes.
+    fn open(headless: bool) -> Self {
+        if !headless && io::stdout().is_terminal() {
+            if let Ok(screen) = Screen::open() {
+                return Self::Table(Some(screen));
+            }
+        }
+        Self::Lines {
+            headless,
+            last: None,
+        }
+    }
+
+    /// Shows the fold after one round.
+    fn show(&mut self, title: &str, stats: &PathStats, line: &str) -> io::Result<()> {
+        match self {
+            Self::Table(Some(screen)) => screen.draw(title, stats),
+            Self::Table(None) => Ok(()),
+            Self::Lines { headless, last } => {
+                let due = last.is_none_or(|at| at.elapsed() >= HEADLESS_STATUS_PERIOD);
+                if !*headless || due {
+                    println!("{line}");
+                    *last = Some(Instant::now());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Waits for one slice of the time between two rounds. True when the user
+    /// pressed a key that stops the trace.
+    fn pause(&mut self, slice: Duration) -> bool {
+        match self {
+            // A terminal that no longer reads stops the trace, because the
+            // user can no longer stop it with a key.
+            Self::Table(Some(_)) => Screen::quit_pressed(slice).unwrap_or(true),
+            Self::Table(None) | Self::Lines { .. } => {
+                std::thread::sleep(slice);
+                false
+            }
+        }
+    }
+
+    /// Gives the terminal back.
+    fn close(&mut self) {
+        if let Self::Table(screen) = self {
+            screen.take();
+        }
+    }
+
+    /// True when the trace showed the table.
+    fn was_table(&self) -> bool {
+        matches!(self, Self::Table(_))
+    }
+}
+
+/// Builds the record that opens the run.
+fn run_record(
+    config: &ResolvedConfig,
+    run: &RunId,
+    destination: String,
+    target: IpAddr,
+    source: SourceLabel,
+    prober: &Prober,
+) -> Record {
+    Record::Run(RunRecord {
+        run: run.clone(),
+        krt: buildinfo::version_string!().to_owned(),
+        source,
+        target: Target {
+            arg: destination,
+            addr: target,
+            family: if target.is_ipv6() {
+                Family::Ipv6
+            } else {
+                Family::Ipv4
+            },
+        },
+        config: RunConfig {
+            interval_ms: millis(config.interval),
+            protocol: config.protocol,
+            first_ttl: config.first_ttl,
+            max_ttl: config.max_ttl,
+            multipath: config.multipath,
+            privilege: prober.privilege(),
+            dns: config.reverse_dns,
+        },
+        host: host_name(),
+    })
+}
+
+/// Waits until the moment `next`, one slice at a time, and asks `stop` after
+/// each slice. A reason from `stop` ends the wait at once and comes back.
+fn wait_until(
+    next: Instant,
+    mut pause: impl FnMut(Duration),
+    stop: impl Fn() -> Option<EndReason>,
+) -> Option<EndReason> {
+    loop {
+        let reason = stop();
+        let now = Instant::now();
+        if reason.is_some() || now >= next {
+            return reason;
+        }
+        pause((next - now).min(SLEEP_SLICE));
+    }
+}
+
+/// Reads the first address of the destination in the asked IP version.
+///
+/// # Errors
+///
+/// Returns the reason when the destination does not resolve, and when it
+/// resolves to no address of the asked version.
+fn resolve(destination: &str, family: AddressFamily) -> Result<IpAddr, TraceError> {
+    let addresses = (destination, 0)
+        .to_socket_addrs()
+        .map_err(|source| TraceError::Resolve {
+            destination: destination.to_owned(),
+            source,
+        })?;
+    addresses
+        .map(|address| address.ip())
+        .find(|address| match family {
+            AddressFamily::Auto => true,
+            AddressFamily::Version4 => address.is_ipv4(),
+            AddressFamily::Version6 => address.is_ipv6(),
+        })
+        .ok_or_else(|| TraceError::NoAddress {
+            destination: destination.to_owned(),
+            family,
+        })
+}
+
+/// Finds the source address of the run.
+///
+/// The address the user named wins. Otherwise the address is the one that the
+/// system routes to the target from. A system that finds no route gives the
+/// unspecified address, and the run still records.
+fn source_label(named: Option<IpAddr>, target: IpAddr) -> SourceLabel {
+    if let Some(addr) = named {
+        return SourceLabel {
+            addr,
+            kind: SourceKind::Override,
+        };
+    }
+    let unspecified = if target.is_ipv6() {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    } else {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    };
+    let addr = probe::route_source(target).unwrap_or(unspecified);
+    SourceLabel {
+        addr,
+        kind: SourceKind::Local,
+    }
+}
+
+/// Builds the path of the recorded file when the user names none.
+///
+/// The name holds the source and the destination, so one source and one
+/// destination keep one file across many runs. The file lands in the current
+/// directory. A character that a file name should not hold becomes a dash, so
+/// the colons of an IPv6 address never reach the name.
+fn derived_path(source: IpAddr, destination: &str) -> PathBuf {
+    let clean = |text: &str| -> String {
+        text.chars()
+            .map(|character| {
+                if character.is_ascii_alphanumeric() || character == '.' || character == '-' {
+                    character
+                } else {
+                    DERIVED_REPLACEMENT
+                }
+            })
+            .collect()
+    };
+    let name = [
+        DERIVED_PREFIX.to_owned(),
+        clean(&source.to_string()),
+        clean(destination),
+    ]
+    .join(DERIVED_SEPARATOR);
+    PathBuf::from(format!("{name}.{DERIVED_EXTENSION}"))
+}
+
+/// Writes the status line of one round. The line counts TTLs, so the hops of
+/// several flows at one TTL count once.
+fn status_line(round: &RoundRecord) -> String {
+    let reached = if round.reached {
+        REACHED
+    } else {
+        NEVER_REACHED
+    };
+    [
+        format!("{ROUND} {}", round.seq),
+        counted(
+            round
+                .hops
+                .iter()
+                .map(|hop| hop.ttl)
+                .collect::<BTreeSet<_>>()
+                .len(),
+            HOP,
+        ),
+        reached.to_owned(),
+    ]
+    .join(SUMMARY_SEPARATOR)
+}
+
+/// Reads the name of the machine.
+fn host_name() -> String {
+    nix::unistd::gethostname().map_or_else(
+        |_| HOST_UNKNOWN.to_owned(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Counts the whole milliseconds of a duration. A duration too long for the
+/// count holds the highest count.
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derived_path, status_line, Limits};
+    use crate::record::{EndReason, Hop, RoundRecord, RunId, TtlRange};
+    use chrono::Utc;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// A trace with no limit, which only the user stops.
+    const NO_LIMITS: Limits = Limits {
+        rounds: None,
+        duration: None,
+    };
+
+    #[test]
+    fn a_derived_path_names_the_source_and_the_destination() {
+        let path = derived_path(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 7)), "example.com");
+        assert_eq!(path, PathBuf::from("krt_192.0.2.7_example.com.jsonl"));
+    }
+
+    #[test]
+    fn a_derived_path_holds_no_colon_and_no_slash() {
+        let path = derived_path(IpAddr::V6(Ipv6Addr::LOCALHOST), "a/b:c");
+        assert_eq!(path, PathBuf::from("krt_--1_a-b-c.jsonl"));
+    }
+
+    #[test]
+    fn a_trace_with_no_limit_runs_until_the_user_stops_it() {
+        let long = Duration::from_hours(24);
+        assert_eq!(NO_LIMITS.reason(false, u64::MAX, long), None);
+        assert_eq!(NO_LIMITS.reason(true, 1, long), Some(EndReason::Quit));
+    }
+
+    #[test]
+    fn the_round_limit_stops_a_trace_at_the_last_round() {
+        let limits = Limits {
+            rounds: Some(3),
+            duration: None,
+        };
+        assert_eq!(limits.reason(false, 2, Duration::ZERO), None);
+        assert_eq!(
+            limits.reason(false, 3, Duration::ZERO),
+            Some(EndReason::Rounds)
+        );
+    }
+
+    #[test]
+    fn the_time_limit_stops_a_trace_once_it_passes() {
+        let limits = Limits {
+            rounds: None,
+            duration: Some(Duration::from_secs(5)),
+        };
+        assert_eq!(limits.reason(false, 9, Duration::from_secs(4)), None);
+        assert_eq!(
+            limits.reason(false, 9, Duration::from_secs(5)),
+            Some(EndReason::Duration)
+        );
+    }
+
+    // A Ctrl-C in the last round still names the user, because the user asked
+    // for the stop before the limit gave it.
+    #[test]
+    fn a_request_of_the_user_wins_over_every_limit() {
+        let limits = Limits {
+            rounds: Some(1),
+            duration: Some(Duration::from_secs(1)),
+        };
+        assert_eq!(
+            limits.reason(true, 1, Duration::from_secs(1)),
+            Some(EndReason::Quit)
+        );
+        assert_eq!(
+            limits.reason(false, 1, Duration::from_secs(1)),
+            Some(EndReason::Rounds)
+        );
+    }
+
+    #[test]
+    fn a_status_line_counts_the_hops_of_the_round() {
+        let round = RoundRecord {
+            run: RunId::from("2026-08-18T12:00:00.000Z"),
+            seq: 4,
+            ts: Utc::now(),
+            dur_ms: 12,
+            ttl_range: TtlRange::new(1, 2).unwrap_or_else(|error| panic!("{error}")),
+            reached: true,
+            hops: vec![Hop {
+                ttl: 2,
+                addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                rtt_ms: 0.05,
+                icmp: "echo_reply".to_owned(),
+                flow: None,
+            }],
+            flows: None,
+        };
+        assert_eq!(status_line(&round), "round 4  1 hop  reached");
+    }
+}
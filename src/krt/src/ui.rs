@@ -0,0 +1,209 @@
+//! The live table of a trace: one line for each TTL, redrawn after each round.
+//!
+//! The table takes the whole terminal while the trace runs, and it gives the
+//! terminal back when the trace stops, on the panic path as well. `q`, `Esc`,
+//! and Ctrl-C stop the trace. The terminal reads keys in raw mode, so Ctrl-C
+//! reaches the table as a key and not as a signal.
+//!
+//! The text of each cell comes from `table.rs`, which reads no terminal, so
+//! the tests of that module check the text without one.
+
+use crate::stats::PathStats;
+use crate::table::{lines, COLUMNS, HEADERS};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::io::{self, Stdout};
+use std::time::Duration;
+
+/// The width of each column, in cells. The name takes what the rest leave.
+const WIDTHS: [Constraint; COLUMNS] = [
+    Constraint::Length(3),
+    Constraint::Length(39),
+    Constraint::Min(12),
+    Constraint::Length(6),
+    Constraint::Length(5),
+    Constraint::Length(7),
+    Constraint::Length(7),
+    Constraint::Length(7),
+    Constraint::Length(7),
+    Constraint::Length(7),
+    Constraint::Length(7),
+];
+
+/// What the title says about the keys that stop the trace.
+const QUIT_HINT: &str = "q to quit";
+
+/// Draws the table and its title into one frame.
+fn draw(frame: &mut Frame<'_>, title: &str, stats: &PathStats) {
+    let header = Row::new(HEADERS).style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = lines(stats).into_iter().map(Row::new);
+    let table = Table::new(rows, WIDTHS)
+        .header(header)
+        .block(Block::bordered().title(format!(" {title}  {QUIT_HINT} ")));
+    frame.render_widget(table, frame.area());
+}
+
+/// The terminal while the table holds it.
+///
+/// The screen enters raw mode and the alternate screen when it opens, and it
+/// leaves both when it drops, so a panic never leaves the terminal broken.
+pub(crate) struct Screen {
+    /// The terminal that the table draws on.
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Screen {
+    /// Takes the terminal for the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason when the terminal does not enter raw mode or the
+    /// alternate screen.
+    pub(crate) fn open() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        if let Err(error) = execute!(stdout, EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(error);
+        }
+        let terminal = match Terminal::new(CrosstermBackend::new(stdout)) {
+            Ok(terminal) => terminal,
+            Err(error) => {
+                let _ = disable_raw_mode();
+                let _ = execute!(io::stdout(), LeaveAlternateScreen);
+                return Err(error);
+            }
+        };
+        // From here on `Drop` restores the terminal, so every early return
+        // below leaves it whole.
+        let mut screen = Self { terminal };
+        screen.terminal.clear()?;
+        Ok(screen)
+    }
+
+    /// Redraws the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason when the write to the terminal fails.
+    pub(crate) fn draw(&mut self, title: &str, stats: &PathStats) -> io::Result<()> {
+        self.terminal
+            .draw(|frame| draw(frame, title, stats))
+            .map(|_| ())
+    }
+
+    /// Waits up to `timeout` for a key, and tells whether the key stops the
+    /// trace. Only an open screen reads keys in raw mode, so a caller asks
+    /// while one is open. A change of the size of the terminal redraws on the next round.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason when the read of the terminal fails.
+    pub(crate) fn quit_pressed(timeout: Duration) -> io::Result<bool> {
+        if !event::poll(timeout)? {
+            return Ok(false);
+        }
+        Ok(match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL))
+            }
+            _ => false,
+        })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draw;
+    use crate::record::{Hop, NameRecord, RoundRecord, RunId, TtlRange};
+    use crate::stats::PathStats;
+    use chrono::Utc;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// The identifier of the run of every test record.
+    const RUN: &str = "2026-08-18T12:00:00.000Z";
+
+    /// The router at TTL 2.
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    /// The router that answers TTL 2 in the second round.
+    const OTHER_ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    /// Folds two rounds: TTL 1 never answers, and TTL 2 changes its responder.
+    fn two_rounds() -> PathStats {
+        let mut stats = PathStats::default();
+        for (seq, addr, rtt_ms) in [(1, ROUTER, 4.0), (2, OTHER_ROUTER, 6.0)] {
+            stats.add_round(&RoundRecord {
+                run: RunId::from(RUN),
+                seq,
+                ts: Utc::now(),
+                dur_ms: 6,
+                ttl_range: TtlRange::new(1, 2).unwrap_or_else(|error| panic!("{error}")),
+                reached: false,
+                hops: vec![Hop {
+                    ttl: 2,
+                    addr,
+                    rtt_ms,
+                    icmp: "time_exceeded".to_owned(),
+                    flow: None,
+                }],
+                flows: None,
+            });
+        }
+        stats.add_name(&NameRecord {
+            run: RunId::from(RUN),
+            ts: Utc::now(),
+            addr: ROUTER,
+            host: "gw.example.net".to_owned(),
+        });
+        stats
+    }
+
+    #[test]
+    fn the_frame_holds_the_title_and_every_column() {
+        let mut terminal =
+            Terminal::new(TestBackend::new(160, 8)).unwrap_or_else(|error| panic!("{error}"));
+        terminal
+            .draw(|frame| draw(frame, "krt example.com", &two_rounds()))
+            .unwrap_or_else(|error| panic!("{error}"));
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        for expected in [
+            "krt example.com",
+            "q to quit",
+            "Loss%",
+            "Jitter",
+            "gw.example.net",
+        ] {
+            assert!(
+                text.contains(expected),
+                "{expected:?} missing from {text:?}"
+            );
+        }
+    }
+}
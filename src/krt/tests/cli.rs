@@ -9,7 +9,7 @@
 //! the shape of the line and not its exact text.
 //!
 //! The rest of the file covers the resolved configuration. A command line that
-//! names a destination prints the block and exits with success. A command line
+//! names a destination prints the block, traces, and exits with success. A command line
 //! that contradicts itself prints the reason on standard error and exits with a
 //! failure. The `replay` command prints one summary line in the place of the
 //! block, and `tests/replay.rs` covers that line.
@@ -20,10 +20,12 @@
 #![allow(
     clippy::unwrap_used,
     clippy::expect_used,
-    reason = "each unwrap here is an assertion about the harness, not an unhandled error: the spawn of the freshly built binary, and the decode of the output that binary wrote. A failure of either one is a broken harness, and a panic names it at once"
+    reason = "each unwrap here is an assertion about the harness, not an unhandled error: the spawn of the freshly built binary, the decode of the output that binary wrote, the clock, and the directory that one trace runs in. A failure of any one is a broken harness, and a panic names it at once"
 )]
 
+use std::fs;
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// The text before the commit hash of the build string.
 const BUILD_STRING_PREFIX: &str = "krt 0.1.0 (";
@@ -110,24 +112,35 @@ fn version_flags_report_the_build_string() {
     assert_flag_prints_the_build_string("-V");
 }
 
-/// The block that `krt example.com` prints, with every default.
-const DEFAULT_BLOCK: &str = "\
+/// The command line of the one trace that this file runs. UDP needs no
+/// privilege on Linux, and the loopback address answers at once.
+const TRACE: [&str; 5] = ["127.0.0.1", "--protocol", "udp", "--rounds", "1"];
+
+/// The block that the trace prints before it probes. Every other flag keeps
+/// its default.
+const TRACE_BLOCK: &str = "\
 resolved configuration:
-  destination:    example.com
+  destination:    127.0.0.1
   output:         derived at run time
   interval:       1s
   first ttl:      1
   max ttl:        30
-  protocol:       icmp
+  protocol:       udp
   multipath:      classic
   address family: auto
   reverse dns:    on
   source:         discovered at run time
   display:        table
   duration limit: none
-  round limit:    none
+  round limit:    1
 ";
 
+/// The name of the file that the trace derives.
+const DERIVED_FILE: &str = "krt_127.0.0.1_127.0.0.1.jsonl";
+
+/// The status line of the one round of the trace.
+const ROUND_LINE: &str = "round 1  1 hop  reached";
+
 /// What one run of the binary wrote, and whether it succeeded.
 struct Run {
     /// True when the binary exited with success.
@@ -160,19 +173,37 @@ fn failure(arguments: &[&str]) -> String {
     result.stderr
 }
 
+// The trace derives its file in the current directory, so the test runs it in
+// a directory of its own and removes that directory after.
+#[cfg(target_os = "linux")]
 #[test]
-fn a_destination_prints_the_resolved_configuration() {
-    let result = run(&["example.com"]);
+fn a_destination_prints_the_resolved_configuration_then_traces() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let directory = std::env::temp_dir().join(format!("krt-cli-{}-{nanos}", std::process::id()));
+    fs::create_dir(&directory).unwrap();
+    let output = krt().args(TRACE).current_dir(&directory).output().unwrap();
+    let derived = directory.join(DERIVED_FILE).exists();
+    let _ = fs::remove_dir_all(&directory);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(
-        result.success,
-        "`krt example.com` must exit with success; stderr: {}",
-        result.stderr
+        output.status.success(),
+        "`krt {}` must exit with success; stderr: {stderr}",
+        TRACE.join(" ")
+    );
+    assert_eq!(
+        stdout,
+        format!("{TRACE_BLOCK}recording to {DERIVED_FILE}\n{ROUND_LINE}\n")
     );
-    assert_eq!(result.stdout, DEFAULT_BLOCK);
     assert_eq!(
-        result.stderr, "",
+        stderr, "",
         "a good command line writes nothing to standard error"
     );
+    assert!(derived, "the trace must write the derived file");
 }
 
 #[test]
@@ -1,13 +1,15 @@
 //! Black-box coverage for `krt replay`, driving the real binary.
 //!
-//! A replay reads a recorded file and prints one summary line for one run. The
-//! tests read the line that the binary printed, so they cover the whole path
-//! from the command line to standard output.
+//! A replay reads a recorded file and prints a report of one run: a summary
+//! line and the table of statistics, CSV text, a Graphviz graph, or the
+//! comparison with a baseline run. The tests read the text that the binary
+//! printed, so they cover the whole path from the command line to standard
+//! output.
 //!
 //! The committed fixture holds two runs. It covers the default selection of the
-//! last run, and the selection of the other run by `--run`. Every other file is
-//! built as text in the test that needs it, and it goes away when that test
-//! ends. No test touches the network.
+//! last run, the selection of the other run by `--run`, and the comparison of
+//! the two. Every other file is built as text in the test that needs it, and it
+//! goes away when that test ends. No test touches the network.
 
 // Mirrors the crate-root attributes in src/main.rs; see "Lint Configuration" in CLAUDE.md.
 #![deny(unsafe_code)]
@@ -32,6 +34,15 @@ const REPLAY: &str = "replay";
 /// The flag that picks which run of the file to fold.
 const RUN_FLAG: &str = "--run";
 
+/// The flag that picks the form of the report.
+const FORMAT_FLAG: &str = "--format";
+
+/// The flag that names the baseline run of a comparison.
+const BASELINE_FLAG: &str = "--baseline";
+
+/// The flag that names the file of the baseline run of a comparison.
+const BASELINE_FILE_FLAG: &str = "--baseline-file";
+
 /// The exit code of a failure.
 const EXIT_FAILURE: i32 = 1;
 
@@ -51,12 +62,31 @@ const ABSENT_RUN: &str = "1999-01-01T00:00:00.000Z";
 ///
 /// The run makes two rounds. The first round answers at TTL 1 and TTL 3, and
 /// the second round answers at TTL 1, so two TTLs answered in all.
-const FIRST_RUN_SUMMARY: &str =
-    "2026-08-18T12:00:00.123Z  example.com (93.184.216.34)  2 rounds  2 hops  reached\n";
+const FIRST_RUN_SUMMARY: &str = "2026-08-18T12:00:00.123Z  example.com (93.184.216.34)  2 rounds  2 hops  reached  stopped by the user";
+
+/// The whole report of the first run of the fixture.
+///
+/// TTL 2 never answered, and TTL 3 answered in one of the two rounds. The
+/// router carries the name that the run read for it.
+const FIRST_RUN_REPORT: &str = "\
+2026-08-18T12:00:00.123Z  example.com (93.184.216.34)  2 rounds  2 hops  reached  stopped by the user
+
+TTL  Address        Name        Loss%  Sent  Last   Avg  Best  Worst  StDev  Jitter
+  1  192.168.1.1    router.lan    0.0     2   1.4   1.3   1.2    1.4    0.1     0.2
+  2  ???                        100.0     2
+  3  93.184.216.34               50.0     2  24.1  24.1  24.1   24.1    0.0
+";
+
+/// The CSV export of the first run of the fixture.
+const FIRST_RUN_CSV: &str = "\
+ttl,address,name,loss_pct,sent,last_ms,avg_ms,best_ms,worst_ms,stddev_ms,jitter_ms
+1,192.168.1.1,router.lan,0.000,2,1.410,1.320,1.230,1.410,0.090,0.180
+2,,,100.000,2,,,,,,
+3,93.184.216.34,,50.000,2,24.100,24.100,24.100,24.100,0.000,
+";
 
 /// The summary of the second run of the fixture.
-const SECOND_RUN_SUMMARY: &str =
-    "2026-08-19T09:30:00.000Z  example.org (93.184.216.35)  1 round  2 hops  reached\n";
+const SECOND_RUN_SUMMARY: &str = "2026-08-19T09:30:00.000Z  example.org (93.184.216.35)  1 round  2 hops  reached  stopped by the round limit";
 
 /// The `run` line of every file that a test builds.
 const BUILT_RUN_LINE: &str = r#"{"type":"run","run":"2026-08-20T00:00:00.000Z","krt":"0.1.0 (abc1234, clean)","source":{"addr":"1.2.3.4","kind":"public"},"target":{"arg":"example.net","addr":"198.51.100.7","family":"ipv4"},"config":{"interval_ms":1000,"protocol":"icmp","first_ttl":1,"max_ttl":30,"multipath":"classic","privilege":"unprivileged","dns":true},"host":"tims-mac"}"#;
@@ -68,11 +98,11 @@ const BUILT_ROUND_LINE: &str = r#"{"type":"round","run":"2026-08-20T00:00:00.000
 
 /// The summary of a built file that holds the `run` record.
 const BUILT_SUMMARY: &str =
-    "2026-08-20T00:00:00.000Z  example.net (198.51.100.7)  1 round  2 hops  reached\n";
+    "2026-08-20T00:00:00.000Z  example.net (198.51.100.7)  1 round  2 hops  reached  no end record";
 
 /// The summary of a built file that holds no `run` record.
 const BUILT_SUMMARY_WITHOUT_A_TARGET: &str =
-    "2026-08-20T00:00:00.000Z  unknown  1 round  2 hops  reached\n";
+    "2026-08-20T00:00:00.000Z  unknown  1 round  2 hops  reached  no end record";
 
 /// The `round` line of a run that did not reach the target.
 ///
@@ -81,7 +111,7 @@ const BUILT_MISSED_ROUND_LINE: &str = r#"{"type":"round","run":"2026-08-20T00:00
 
 /// The summary of a built file whose run did not reach the target.
 const BUILT_SUMMARY_NEVER_REACHED: &str =
-    "2026-08-20T00:00:00.000Z  example.net (198.51.100.7)  1 round  1 hop  never reached\n";
+    "2026-08-20T00:00:00.000Z  example.net (198.51.100.7)  1 round  1 hop  never reached  no end record";
 
 /// The start of a `round` line that a `kill -9` cut short.
 const CUT_CHUNK: &str = r#"{"type":"round""#;
@@ -207,6 +237,11 @@ impl Drop for TempFile {
     }
 }
 
+/// The first line of a report, which names the run.
+fn summary_of(stdout: &str) -> &str {
+    stdout.lines().next().unwrap_or_default()
+}
+
 /// Joins the lines of a file. Every line ends with a newline.
 fn file_of(lines: &[&str]) -> String {
     let mut text = String::new();
@@ -218,9 +253,9 @@ fn file_of(lines: &[&str]) -> String {
 }
 
 #[test]
-fn a_replay_prints_the_summary_of_the_last_run() {
+fn a_replay_names_the_last_run() {
     let result = success(&[REPLAY, FIXTURE]);
-    assert_eq!(result.stdout, SECOND_RUN_SUMMARY);
+    assert_eq!(summary_of(&result.stdout), SECOND_RUN_SUMMARY);
     assert_eq!(
         result.stderr, "",
         "a whole file writes nothing to standard error"
@@ -228,9 +263,9 @@ fn a_replay_prints_the_summary_of_the_last_run() {
 }
 
 #[test]
-fn a_named_run_prints_the_summary_of_that_run() {
+fn a_named_run_names_that_run() {
     let result = success(&[REPLAY, FIXTURE, RUN_FLAG, FIRST_RUN]);
-    assert_eq!(result.stdout, FIRST_RUN_SUMMARY);
+    assert_eq!(summary_of(&result.stdout), FIRST_RUN_SUMMARY);
     assert_eq!(
         result.stderr, "",
         "a whole file writes nothing to standard error"
@@ -285,13 +320,13 @@ fn a_file_that_holds_no_run_lists_no_run_for_the_run_flag() {
 }
 
 #[test]
-fn a_final_line_that_is_cut_short_warns_and_still_prints_the_summary() {
+fn a_final_line_that_is_cut_short_warns_and_still_prints_the_report() {
     let mut text = file_of(&[BUILT_RUN_LINE, BUILT_ROUND_LINE]);
     text.push_str(CUT_CHUNK);
     let file = TempFile::new("cut", &text);
     let path = file.arg();
     let result = success(&[REPLAY, path.as_str()]);
-    assert_eq!(result.stdout, BUILT_SUMMARY);
+    assert_eq!(summary_of(&result.stdout), BUILT_SUMMARY);
     assert!(
         result.stderr.contains(path.as_str()),
         "the warning names the path: {}",
@@ -310,7 +345,7 @@ fn a_file_without_a_run_record_prints_no_target() {
     let file = TempFile::new("no-run-record", &file_of(&[BUILT_ROUND_LINE]));
     let path = file.arg();
     let result = success(&[REPLAY, path.as_str()]);
-    assert_eq!(result.stdout, BUILT_SUMMARY_WITHOUT_A_TARGET);
+    assert_eq!(summary_of(&result.stdout), BUILT_SUMMARY_WITHOUT_A_TARGET);
     assert_eq!(
         result.stderr, "",
         "a whole file writes nothing to standard error"
@@ -324,7 +359,7 @@ fn a_run_that_did_not_reach_the_target_says_so() {
     let file = TempFile::new("missed", &text);
     let path = file.arg();
     let result = success(&[REPLAY, path.as_str()]);
-    assert_eq!(result.stdout, BUILT_SUMMARY_NEVER_REACHED);
+    assert_eq!(summary_of(&result.stdout), BUILT_SUMMARY_NEVER_REACHED);
     assert_eq!(
         result.stderr, "",
         "a whole file writes nothing to standard error"
@@ -370,3 +405,117 @@ fn a_cut_that_swallowed_every_record_names_the_cut_and_the_missing_run() {
         "the message names the path: {stderr}"
     );
 }
+
+#[test]
+fn a_replay_prints_the_table_of_the_run_below_its_summary() {
+    let result = success(&[REPLAY, FIXTURE, RUN_FLAG, FIRST_RUN]);
+    assert_eq!(result.stdout, FIRST_RUN_REPORT);
+}
+
+#[test]
+fn a_run_exports_as_csv() {
+    let result = success(&[REPLAY, FIXTURE, RUN_FLAG, FIRST_RUN, FORMAT_FLAG, "csv"]);
+    assert_eq!(result.stdout, FIRST_RUN_CSV);
+}
+
+#[test]
+fn a_run_exports_as_a_graph_of_its_path() {
+    let result = success(&[REPLAY, FIXTURE, FORMAT_FLAG, "dot"]);
+    assert!(
+        result.stdout.starts_with("digraph krt {\n"),
+        "{}",
+        result.stdout
+    );
+    for expected in [
+        "\"source\" [label=\"2001:db8::1\", shape=ellipse];",
+        "\"93.184.216.35\" [label=\"93.184.216.35\\nttl 2\", peripheries=2];",
+        "\"source\" -> \"10.0.0.1\" [label=\"1\"];",
+        "\"10.0.0.1\" -> \"93.184.216.35\" [label=\"1\"];",
+    ] {
+        assert!(
+            result.stdout.contains(expected),
+            "{expected} missing from {}",
+            result.stdout
+        );
+    }
+}
+
+/// The first run of the fixture is the baseline of the second. The router at
+/// TTL 1 changed, and the target moved from TTL 3 to TTL 2.
+#[test]
+fn a_baseline_run_of_the_same_file_compares_hop_by_hop() {
+    let result = success(&[REPLAY, FIXTURE, BASELINE_FLAG, FIRST_RUN]);
+    let mut lines = result.stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some(format!("baseline  {FIRST_RUN_SUMMARY}").as_str())
+    );
+    assert_eq!(
+        lines.next(),
+        Some(format!("run       {SECOND_RUN_SUMMARY}").as_str())
+    );
+    for expected in [
+        "  1  192.168.1.1    10.0.0.1            1.3   0.9         0.0    0.0  path changed",
+        "3 TTLs changed: 1 path changed, 1 no longer answers, 1 now answers, 1 less loss",
+    ] {
+        assert!(
+            result.stdout.lines().any(|line| line == expected),
+            "{expected} missing from {}",
+            result.stdout
+        );
+    }
+}
+
+#[test]
+fn a_run_compared_with_itself_changes_nothing() {
+    let result = success(&[REPLAY, FIXTURE, BASELINE_FILE_FLAG, FIXTURE]);
+    assert!(
+        result.stdout.ends_with("\nno TTL changed\n"),
+        "{}",
+        result.stdout
+    );
+}
+
+/// The baseline file is cut short, and the comparison still reads every
+/// round before the cut.
+#[test]
+fn a_baseline_file_that_is_cut_short_warns_and_still_compares() {
+    let mut text = file_of(&[BUILT_RUN_LINE, BUILT_ROUND_LINE]);
+    text.push_str(CUT_CHUNK);
+    let file = TempFile::new("cut-baseline", &text);
+    let path = file.arg();
+    let result = success(&[REPLAY, FIXTURE, BASELINE_FILE_FLAG, path.as_str()]);
+    assert_eq!(
+        result.stdout.lines().next(),
+        Some(format!("baseline  {BUILT_SUMMARY}").as_str())
+    );
+    assert!(
+        result.stderr.contains(path.as_str()) && result.stderr.contains(CUT_SHORT),
+        "the warning names the cut baseline: {}",
+        result.stderr
+    );
+}
+
+#[test]
+fn a_baseline_that_the_file_does_not_hold_fails_and_names_every_run() {
+    let stderr = failure(&[REPLAY, FIXTURE, BASELINE_FLAG, ABSENT_RUN]);
+    for id in [ABSENT_RUN, FIRST_RUN, SECOND_RUN] {
+        assert!(stderr.contains(id), "the message names `{id}`: {stderr}");
+    }
+}
+
+#[test]
+fn a_cut_file_still_exports_the_rounds_before_the_cut() {
+    let mut text = file_of(&[BUILT_RUN_LINE, BUILT_ROUND_LINE]);
+    text.push_str(CUT_CHUNK);
+    let file = TempFile::new("cut-csv", &text);
+    let path = file.arg();
+    let result = success(&[REPLAY, path.as_str(), FORMAT_FLAG, "csv"]);
+    assert_eq!(
+        result.stdout.lines().count(),
+        3,
+        "the header and two TTLs: {}",
+        result.stdout
+    );
+    assert_eq!(result.stderr.lines().count(), 1, "{}", result.stderr);
+}
@@ -0,0 +1,780 @@
+//! Black-box coverage for the tracer, driving the real binary.
+//!
+//! Each test traces one address, reads the file that the trace wrote, and
+//! checks the records. The file is what a later replay reads, so a test that
+//! reads it covers the whole path from the probe to the record.
+//!
+//! The loopback tests run on every Linux machine. The loopback address answers
+//! every probe at TTL 1, so they cover the sockets, the answers, the limits,
+//! and the records, and they need no privilege for UDP and TCP.
+//!
+//! The namespace tests build a path of two hops out of three network
+//! namespaces: a client, a router, and a destination. The router answers TTL 1
+//! with a time exceeded message, and the destination answers TTL 2. The
+//! multipath tests build a second path of five namespaces, where a load
+//! balancer splits the flows over two branches. The namespaces need root and
+//! the `ip` command, so a machine that lacks either one skips those tests and
+//! says so on standard error.
+#![cfg(target_os = "linux")]
+// Mirrors the crate-root attributes in src/main.rs; see "Lint Configuration" in CLAUDE.md.
+#![deny(unsafe_code)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    reason = "each unwrap here is an assertion about the harness, not an unhandled error: the spawn of the freshly built binary, the decode of the output that binary wrote, the read of the file the trace wrote, and the build of the namespaces. A failure of any one is a broken harness, and a panic names it at once"
+)]
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The loopback address, which answers every probe at TTL 1.
+const LOOPBACK: &str = "127.0.0.1";
+
+/// The address of the router of the namespace path.
+const ROUTER: &str = "10.66.1.1";
+
+/// The address of the destination of the namespace path.
+const DESTINATION: &str = "10.66.2.2";
+
+/// The address of the load balancer of the branched path.
+const BALANCER: &str = "10.67.1.1";
+
+/// The routers of the two branches of the branched path.
+const BRANCHES: [&str; 2] = ["10.67.2.2", "10.67.3.2"];
+
+/// The address of the destination of the branched path, which both branches
+/// reach.
+const BRANCHED_DESTINATION: &str = "10.67.9.9";
+
+/// The flags that every trace of this file carries. The short interval keeps
+/// each test fast, and `--headless` and `--no-dns` keep the output plain.
+const QUICK: [&str; 4] = ["--interval", "100ms", "--headless", "--no-dns"];
+
+/// The name of the ICMP message of a router that a TTL ran out at.
+const TIME_EXCEEDED: &str = "time_exceeded";
+
+/// What the message of an ICMP trace names when no socket for it opens.
+const PING_GROUP_RANGE: &str = "ping_group_range";
+
+/// Builds a path under the temporary directory that no other run reaches.
+///
+/// Two runs of one test can overlap, because `cargo test` runs on many threads
+/// and more than one `cargo test` can run at once. The process identifier and
+/// the nanosecond keep the two runs apart.
+fn unique(label: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("the clock must stand after the epoch")
+        .as_nanos();
+    format!("{label}-{}-{nanos}", std::process::id())
+}
+
+/// The file that one trace writes. The file goes away when the test ends, and
+/// also when the test panics.
+struct Recorded {
+    /// The path of the file.
+    path: PathBuf,
+}
+
+impl Recorded {
+    /// Picks a path that no other run reaches. The trace makes the file.
+    fn new(label: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("krt-trace-{}.jsonl", unique(label)));
+        Self { path }
+    }
+
+    /// Reads every record of the file.
+    fn records(&self) -> Vec<Value> {
+        fs::read_to_string(&self.path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// Reads the records of one type, in the order that the file holds them.
+    fn of_type(&self, kind: &str) -> Vec<Value> {
+        self.records()
+            .into_iter()
+            .filter(|record| record["type"] == kind)
+            .collect()
+    }
+}
+
+impl Drop for Recorded {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Runs `krt` with the arguments, through `prefix` when it names a command,
+/// and writes the records to `file`.
+fn trace(prefix: &[&str], file: &Recorded, arguments: &[&str]) -> Output {
+    let binary = env!("CARGO_BIN_EXE_krt");
+    let mut command = match prefix.split_first() {
+        Some((program, rest)) => {
+            let mut command = Command::new(program);
+            command.args(rest).arg(binary);
+            command
+        }
+        None => Command::new(binary),
+    };
+    command
+        .args(arguments)
+        .args(QUICK)
+        .arg("--output")
+        .arg(&file.path)
+        .output()
+        .unwrap()
+}
+
+/// Asserts that a trace exited with success.
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "the trace must exit with success; stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Reads the flow, the TTL, and the address of each hop of a round.
+fn flow_hops(round: &Value) -> Vec<(u64, u64, String)> {
+    round["hops"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hop| {
+            (
+                hop["flow"].as_u64().unwrap(),
+                hop["ttl"].as_u64().unwrap(),
+                hop["addr"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Reads the address, the TTL, and the answer of each hop of a round.
+fn hops(round: &Value) -> Vec<(u64, String, String)> {
+    round["hops"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hop| {
+            (
+                hop["ttl"].as_u64().unwrap(),
+                hop["addr"].as_str().unwrap().to_owned(),
+                hop["icmp"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Traces the loopback address for two rounds, and checks that each round
+/// reached it at TTL 1 with the answer of the protocol.
+fn assert_loopback(protocol: &str, answer: &str, privilege: &str) {
+    let file = Recorded::new(protocol);
+    let output = trace(
+        &[],
+        &file,
+        &[LOOPBACK, "--protocol", protocol, "--rounds", "2"],
+    );
+    assert_success(&output);
+
+    let runs = file.of_type("run");
+    assert_eq!(runs.len(), 1, "one trace writes one run record");
+    assert_eq!(runs[0]["config"]["protocol"], protocol);
+    assert_eq!(runs[0]["config"]["privilege"], privilege);
+    assert_eq!(runs[0]["target"]["addr"], LOOPBACK);
+
+    let rounds = file.of_type("round");
+    assert_eq!(rounds.len(), 2, "`--rounds 2` makes two rounds");
+    for (seq, round) in (1_u64..).zip(&rounds) {
+        assert_eq!(round["seq"], seq);
+        assert_eq!(round["reached"], true);
+        assert_eq!(round["ttl_range"], serde_json::json!([1, 1]));
+        assert_eq!(
+            hops(round),
+            vec![(1, LOOPBACK.to_owned(), answer.to_owned())]
+        );
+    }
+
+    let ends = file.of_type("end");
+    assert_eq!(ends.len(), 1, "one trace writes one end record");
+    assert_eq!(ends[0]["rounds"], 2);
+    assert_eq!(ends[0]["reason"], "rounds");
+}
+
+#[test]
+fn a_udp_trace_of_loopback_reaches_it_without_privilege() {
+    assert_loopback("udp", "port_unreachable", "unprivileged");
+}
+
+#[test]
+fn a_tcp_trace_of_loopback_reaches_it_without_privilege() {
+    assert_loopback("tcp", "tcp_reset", "unprivileged");
+}
+
+// An ICMP trace opens a datagram socket of ICMP when `ping_group_range` allows
+// it, and a raw socket when the user is root. A machine that allows neither
+// must say which setting to change.
+#[test]
+fn an_icmp_trace_of_loopback_reaches_it_or_names_the_setting() {
+    let probe = Recorded::new("icmp-probe");
+    let output = trace(&[], &probe, &[LOOPBACK, "--rounds", "1"]);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(PING_GROUP_RANGE),
+            "a trace that opens no socket must name `{PING_GROUP_RANGE}`; stderr: {stderr}"
+        );
+        return;
+    }
+    let privilege = probe.of_type("run")[0]["config"]["privilege"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    assert_loopback("icmp", "echo_reply", &privilege);
+}
+
+#[test]
+fn the_time_limit_stops_a_trace_and_names_itself() {
+    let file = Recorded::new("duration");
+    let output = trace(
+        &[],
+        &file,
+        &[LOOPBACK, "--protocol", "udp", "--duration", "350ms"],
+    );
+    assert_success(&output);
+
+    let rounds = file.of_type("round").len();
+    assert!(
+        (2..=5).contains(&rounds),
+        "350ms at 100ms per round makes about four rounds, but it made {rounds}"
+    );
+    let ends = file.of_type("end");
+    assert_eq!(ends[0]["reason"], "duration");
+    assert_eq!(ends[0]["rounds"], rounds);
+}
+
+// Ctrl-C ends the trace between two rounds, and the file still closes with an
+// `end` record, so a replay can tell a stop of the user from a crash.
+#[test]
+fn an_interrupt_ends_the_trace_with_the_reason_quit() {
+    let file = Recorded::new("interrupt");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_krt"))
+        .args([LOOPBACK, "--protocol", "udp"])
+        .args(QUICK)
+        .arg("--output")
+        .arg(&file.path)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(400));
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success(), "`kill -INT` must reach the trace");
+    assert!(child.wait().unwrap().success());
+
+    let ends = file.of_type("end");
+    assert_eq!(ends.len(), 1);
+    assert_eq!(ends[0]["reason"], "quit");
+    assert_eq!(ends[0]["rounds"], file.of_type("round").len());
+}
+
+// The hosts file of every unix system names the loopback address, so the
+// trace learns the name without the network. The name arrives between two
+// rounds, so the trace makes a few.
+#[test]
+fn a_trace_with_reverse_dns_records_the_name_of_a_hop() {
+    let file = Recorded::new("names");
+    let output = Command::new(env!("CARGO_BIN_EXE_krt"))
+        .args([LOOPBACK, "--protocol", "udp", "--rounds", "5"])
+        .args(["--interval", "100ms", "--headless", "--output"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert_success(&output);
+
+    assert_eq!(file.of_type("run")[0]["config"]["dns"], true);
+    let names = file.of_type("name");
+    assert_eq!(names.len(), 1, "one address is named once: {names:?}");
+    assert_eq!(names[0]["addr"], LOOPBACK);
+    assert!(!names[0]["host"].as_str().unwrap().is_empty());
+}
+
+#[test]
+fn a_second_trace_appends_a_second_run_to_the_same_file() {
+    let file = Recorded::new("append");
+    for _ in 0..2 {
+        assert_success(&trace(
+            &[],
+            &file,
+            &[LOOPBACK, "--protocol", "udp", "--rounds", "1"],
+        ));
+    }
+    assert_eq!(file.of_type("run").len(), 2);
+    assert_eq!(file.of_type("end").len(), 2);
+}
+
+#[test]
+fn the_first_ttl_starts_the_range_of_each_round() {
+    let file = Recorded::new("first-ttl");
+    let output = trace(
+        &[],
+        &file,
+        &[
+            LOOPBACK,
+            "--protocol",
+            "udp",
+            "--rounds",
+            "1",
+            "--first-ttl",
+            "3",
+        ],
+    );
+    assert_success(&output);
+    let rounds = file.of_type("round");
+    assert_eq!(rounds[0]["ttl_range"], serde_json::json!([3, 3]));
+    assert_eq!(hops(&rounds[0])[0].0, 3);
+}
+
+/// Network namespaces joined into a path, which the client traces.
+///
+/// Every name carries the process identifier and the nanosecond, so two runs
+/// never share a namespace. The namespaces go away when the test ends, and
+/// also when the test panics.
+struct Path {
+    /// The names of the namespaces. The client comes first.
+    names: Vec<String>,
+}
+
+impl Path {
+    /// Builds a path of two hops out of three namespaces.
+    ///
+    /// The client sits at 10.66.1.2 and routes through the router at
+    /// 10.66.1.1. The router forwards to the destination at 10.66.2.2.
+    fn build() -> Result<Self, String> {
+        let path = Self::create(&["c", "r", "d"])?;
+        let [client, router, destination] = [0, 1, 2].map(|index| path.names[index].as_str());
+        path.link(client, "c0", "10.66.1.2/24", router, "r0", "10.66.1.1/24")?;
+        path.link(
+            router,
+            "r1",
+            "10.66.2.1/24",
+            destination,
+            "d0",
+            "10.66.2.2/24",
+        )?;
+        ip(&["-n", client, "route", "add", "default", "via", "10.66.1.1"])?;
+        ip(&[
+            "-n",
+            destination,
+            "route",
+            "add",
+            "default",
+            "via",
+            "10.66.2.1",
+        ])?;
+        path.exec(router, &["sysctl", "-qw", "net.ipv4.ip_forward=1"])?;
+        Ok(path)
+    }
+
+    /// Builds a path that splits in two, out of five namespaces.
+    ///
+    /// The client sits at 10.67.1.2 behind the load balancer at 10.67.1.1. The
+    /// balancer holds two routes to the destination at 10.67.9.9, one through
+    /// each branch router, and it picks the route by a hash of the ports of
+    /// each packet. The destination answers through the first branch. No
+    /// router limits the rate of its ICMP messages, so every probe of every
+    /// flow draws its answer.
+    fn branches() -> Result<Self, String> {
+        let path = Self::create(&["c", "l", "a", "b", "d"])?;
+        let [client, balancer, first, second, destination] =
+            [0, 1, 2, 3, 4].map(|index| path.names[index].as_str());
+        path.link(client, "c0", "10.67.1.2/24", balancer, "l0", "10.67.1.1/24")?;
+        path.link(balancer, "l1", "10.67.2.1/24", first, "a0", "10.67.2.2/24")?;
+        path.link(balancer, "l2", "10.67.3.1/24", second, "b0", "10.67.3.2/24")?;
+        path.link(
+            first,
+            "a1",
+            "10.67.4.1/24",
+            destination,
+            "d0",
+            "10.67.4.2/24",
+        )?;
+        path.link(
+            second,
+            "b1",
+            "10.67.5.1/24",
+            destination,
+            "d1",
+            "10.67.5.2/24",
+        )?;
+        ip(&[
+            "-n",
+            destination,
+            "addr",
+            "add",
+            "10.67.9.9/32",
+            "dev",
+            "lo",
+        ])?;
+        ip(&["-n", client, "route", "add", "default", "via", "10.67.1.1"])?;
+        ip(&[
+            "-n",
+            balancer,
+            "route",
+            "add",
+            "10.67.9.9/32",
+            "nexthop",
+            "via",
+            "10.67.2.2",
+            "nexthop",
+            "via",
+            "10.67.3.2",
+        ])?;
+        for (router, next, back) in [
+            (first, "10.67.4.2", "10.67.2.1"),
+            (second, "10.67.5.2", "10.67.3.1"),
+        ] {
+            ip(&["-n", router, "route", "add", "default", "via", next])?;
+            ip(&["-n", router, "route", "add", "10.67.1.0/24", "via", back])?;
+        }
+        ip(&[
+            "-n",
+            destination,
+            "route",
+            "add",
+            "default",
+            "via",
+            "10.67.4.1",
+        ])?;
+        for name in &path.names[1..] {
+            path.exec(
+                name,
+                &[
+                    "sysctl",
+                    "-qw",
+                    "net.ipv4.ip_forward=1",
+                    "net.ipv4.icmp_ratelimit=0",
+                    "net.ipv4.conf.all.rp_filter=0",
+                    "net.ipv4.conf.default.rp_filter=0",
+                ],
+            )?;
+        }
+        path.exec(
+            balancer,
+            &["sysctl", "-qw", "net.ipv4.fib_multipath_hash_policy=1"],
+        )?;
+        Ok(path)
+    }
+
+    /// Adds one namespace for each role, with its loopback device up.
+    fn create(roles: &[&str]) -> Result<Self, String> {
+        if !is_root() {
+            return Err("the namespaces need root".to_owned());
+        }
+        let tag = unique("krt").replace('-', "");
+        let path = Self {
+            names: roles.iter().map(|role| format!("{role}{tag}")).collect(),
+        };
+        for name in &path.names {
+            ip(&["netns", "add", name])?;
+            ip(&["-n", name, "link", "set", "lo", "up"])?;
+        }
+        Ok(path)
+    }
+
+    /// Joins two namespaces with a pair of virtual devices, and gives each end
+    /// its address.
+    fn link(
+        &self,
+        near: &str,
+        near_device: &str,
+        near_address: &str,
+        far: &str,
+        far_device: &str,
+        far_address: &str,
+    ) -> Result<(), String> {
+        ip(&[
+            "link",
+            "add",
+            near_device,
+            "netns",
+            near,
+            "type",
+            "veth",
+            "peer",
+            "name",
+            far_device,
+            "netns",
+            far,
+        ])?;
+        for (name, device, address) in [
+            (near, near_device, near_address),
+            (far, far_device, far_address),
+        ] {
+            debug_assert!(self.names.iter().any(|held| held == name));
+            ip(&["-n", name, "addr", "add", address, "dev", device])?;
+            ip(&["-n", name, "link", "set", device, "up"])?;
+        }
+        Ok(())
+    }
+
+    /// The command prefix that runs a program inside the client namespace.
+    fn client(&self) -> [&str; 4] {
+        ["ip", "netns", "exec", &self.names[0]]
+    }
+
+    /// Runs a program inside the namespace of the name.
+    fn exec(&self, name: &str, program: &[&str]) -> Result<(), String> {
+        debug_assert!(self.names.iter().any(|held| held == name));
+        let mut arguments = vec!["netns", "exec", name];
+        arguments.extend_from_slice(program);
+        ip(&arguments)
+    }
+}
+
+impl Drop for Path {
+    fn drop(&mut self) {
+        for name in &self.names {
+            let _ = Command::new("ip").args(["netns", "del", name]).output();
+        }
+    }
+}
+
+/// True when the tests run as root.
+fn is_root() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+}
+
+/// Runs the `ip` command, and reads its message when it fails.
+fn ip(arguments: &[&str]) -> Result<(), String> {
+    let output = Command::new("ip")
+        .args(arguments)
+        .output()
+        .map_err(|error| format!("`ip` does not run: {error}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "`ip {}` failed: {}",
+            arguments.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Builds a namespace path, or says on standard error why this machine skips
+/// the test.
+fn path_or_skip(test: &str, build: fn() -> Result<Path, String>) -> Option<Path> {
+    match build() {
+        Ok(path) => Some(path),
+        Err(reason) => {
+            eprintln!("skipping {test}: {reason}");
+            None
+        }
+    }
+}
+
+/// Traces the destination of the namespace path for one round, and checks
+/// that the router answered TTL 1 and the destination answered TTL 2.
+fn assert_two_hops(path: &Path, protocol: &str, answer: &str) -> Recorded {
+    assert_two_hops_with(path, protocol, answer, "classic")
+}
+
+/// Traces the destination of the namespace path for one round in a multipath
+/// mode, and checks the two hops.
+fn assert_two_hops_with(path: &Path, protocol: &str, answer: &str, multipath: &str) -> Recorded {
+    let file = Recorded::new(&format!("netns-{protocol}-{multipath}"));
+    let output = trace(
+        &path.client(),
+        &file,
+        &[
+            DESTINATION,
+            "--protocol",
+            protocol,
+            "--multipath",
+            multipath,
+            "--rounds",
+            "1",
+        ],
+    );
+    assert_success(&output);
+
+    let rounds = file.of_type("round");
+    assert_eq!(rounds.len(), 1);
+    assert_eq!(rounds[0]["reached"], true);
+    assert_eq!(rounds[0]["ttl_range"], serde_json::json!([1, 2]));
+    assert_eq!(
+        hops(&rounds[0]),
+        vec![
+            (1, ROUTER.to_owned(), TIME_EXCEEDED.to_owned()),
+            (2, DESTINATION.to_owned(), answer.to_owned()),
+        ]
+    );
+    file
+}
+
+#[test]
+fn a_udp_and_a_tcp_trace_name_the_router_and_the_destination() {
+    let Some(path) = path_or_skip("the namespace trace of udp and tcp", Path::build) else {
+        return;
+    };
+    assert_two_hops(&path, "udp", "port_unreachable");
+    assert_two_hops(&path, "tcp", "tcp_reset");
+}
+
+// A new namespace starts with `ping_group_range` at "1 0", which allows no
+// group. The trace of root then falls back to a raw socket and says so. Once
+// the range admits root, the same trace opens a datagram socket of ICMP.
+#[test]
+fn an_icmp_trace_records_the_privilege_it_used() {
+    let Some(path) = path_or_skip("the namespace trace of icmp", Path::build) else {
+        return;
+    };
+    let privilege = |file: &Recorded| file.of_type("run")[0]["config"]["privilege"].clone();
+
+    let raw = assert_two_hops(&path, "icmp", "echo_reply");
+    assert_eq!(privilege(&raw), "privileged");
+
+    path.exec(
+        &path.names[0],
+        &["sysctl", "-qw", "net.ipv4.ping_group_range=0 0"],
+    )
+    .unwrap();
+    let datagram = assert_two_hops(&path, "icmp", "echo_reply");
+    assert_eq!(privilege(&datagram), "unprivileged");
+}
+
+// Paris holds one flow, so its probes name the same two hops as the classic
+// probes of a path with no load balancer, through the raw sockets that write
+// each probe.
+#[test]
+fn a_paris_trace_names_the_router_and_the_destination() {
+    let Some(path) = path_or_skip("the namespace trace of paris", Path::build) else {
+        return;
+    };
+    for (protocol, answer) in [("udp", "port_unreachable"), ("tcp", "tcp_reset")] {
+        let file = assert_two_hops_with(&path, protocol, answer, "paris");
+        let run = &file.of_type("run")[0]["config"];
+        assert_eq!(run["multipath"], "paris");
+        assert_eq!(run["privilege"], "privileged");
+        assert_eq!(file.of_type("round")[0].get("flows"), None);
+    }
+}
+
+// Every probe of a Paris trace carries the same ports, so the load balancer
+// hashes each one to the same branch, round after round.
+#[test]
+fn a_paris_trace_holds_one_branch_of_a_load_balancer() {
+    let Some(path) = path_or_skip("the namespace trace of paris branches", Path::branches) else {
+        return;
+    };
+    let file = Recorded::new("branches-paris");
+    let output = trace(
+        &path.client(),
+        &file,
+        &[
+            BRANCHED_DESTINATION,
+            "--protocol",
+            "udp",
+            "--multipath",
+            "paris",
+            "--rounds",
+            "3",
+        ],
+    );
+    assert_success(&output);
+    let second: Vec<String> = file
+        .of_type("round")
+        .iter()
+        .flat_map(hops)
+        .filter(|(ttl, _, _)| *ttl == 2)
+        .map(|(_, addr, _)| addr)
+        .collect();
+    assert_eq!(second.len(), 3, "TTL 2 answers once in each round");
+    assert!(BRANCHES.contains(&second[0].as_str()), "{second:?}");
+    assert!(
+        second.iter().all(|addr| *addr == second[0]),
+        "one flow takes one branch: {second:?}"
+    );
+}
+
+/// Traces the branched path with Dublin for two rounds, checks every hop, and
+/// returns the branch routers that the flows reached at TTL 2.
+fn dublin_branches(path: &Path, protocol: &str, answer: &str, attempt: usize) -> BTreeSet<String> {
+    let file = Recorded::new(&format!("branches-dublin-{protocol}-{attempt}"));
+    let output = trace(
+        &path.client(),
+        &file,
+        &[
+            BRANCHED_DESTINATION,
+            "--protocol",
+            protocol,
+            "--multipath",
+            "dublin",
+            "--rounds",
+            "2",
+        ],
+    );
+    assert_success(&output);
+    let rounds = file.of_type("round");
+    assert_eq!(rounds.len(), 2);
+    let mut branch_of_flow = BTreeMap::new();
+    for round in &rounds {
+        assert_eq!(round["flows"], 8, "{protocol}");
+        assert_eq!(round["reached"], true, "{protocol}");
+        assert_eq!(round["ttl_range"], serde_json::json!([1, 3]), "{protocol}");
+        let walked = flow_hops(round);
+        assert_eq!(
+            walked.len(),
+            24,
+            "every flow answers at every TTL: {walked:?}"
+        );
+        for (flow, ttl, addr) in walked {
+            match ttl {
+                1 => assert_eq!(addr, BALANCER),
+                2 => {
+                    assert!(BRANCHES.contains(&addr.as_str()), "{addr}");
+                    let held = branch_of_flow.entry(flow).or_insert_with(|| addr.clone());
+                    assert_eq!(*held, addr, "flow {flow} changed its branch");
+                }
+                _ => assert_eq!(addr, BRANCHED_DESTINATION),
+            }
+        }
+        assert_eq!(hops(round).pop().unwrap().2, answer, "{protocol}");
+    }
+    branch_of_flow.into_values().collect()
+}
+
+// Dublin sends each TTL on eight flows, and each flow keeps its branch from
+// one round to the next. The balancer hashes the ports, and the ports follow
+// the process identifier, so one trace in 128 sends all eight flows down one
+// branch. The test allows three traces to find both.
+#[test]
+fn a_dublin_trace_records_both_branches_of_a_load_balancer() {
+    let Some(path) = path_or_skip("the namespace trace of dublin branches", Path::branches) else {
+        return;
+    };
+    for (protocol, answer) in [("udp", "port_unreachable"), ("tcp", "tcp_reset")] {
+        let mut seen = BTreeSet::new();
+        for attempt in 0..3 {
+            seen.extend(dublin_branches(&path, protocol, answer, attempt));
+            if seen.len() == BRANCHES.len() {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), BRANCHES.len(), "{protocol}: {seen:?}");
+    }
+}
@@ -0,0 +1,310 @@
+//! The sidecar journal behind `--resume`.
+//!
+//! A resumable copy keeps its partial destination file when it fails, and a
+//! small journal beside it records the source's size and modification time
+//! plus the Blake3 hash of every fixed-size chunk already written. The next
+//! run checks the kept prefix against those hashes, chunk by chunk, and
+//! carries on after the last chunk that still matches.
+//!
+//! The journal is plain text so it can grow a line at a time:
+//!
+//! ```text
+//! prcp-journal 1
+//! size 5368709120
+//! modified 1760659200123456789
+//! chunk 67108864
+//! <Blake3 hex of chunk 0>
+//! <Blake3 hex of chunk 1>
+//! ```
+//!
+//! A chunk's line is appended only after its data has been written, so the
+//! journal never claims more than the file holds, and a line cut short by a
+//! kill does not parse and is ignored.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Bytes covered by each journal entry. Only whole chunks are recorded, so
+/// at most this much is copied again after an interruption. Tests use small
+/// chunks so they can cover several without writing large files.
+pub const CHUNK_SIZE: u64 = if cfg!(test) {
+    64 * 1024
+} else {
+    64 * 1024 * 1024
+};
+
+const MAGIC: &str = "prcp-journal 1";
+
+/// The journal for `destination`: a hidden file beside it.
+pub fn path_for(destination: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(destination.file_name().unwrap_or_default());
+    name.push(".prcp-journal");
+    destination.with_file_name(name)
+}
+
+/// Delete the journal for `destination`, if there is one.
+pub fn discard(destination: &Path) {
+    let _ = fs::remove_file(path_for(destination));
+}
+
+/// What must stay the same about a source for its partial copy to be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceStamp {
+    pub size: u64,
+    /// Modification time in nanoseconds since the Unix epoch, or 0 when unknown.
+    pub modified: u128,
+}
+
+impl SourceStamp {
+    pub fn of(metadata: &fs::Metadata) -> Self {
+        Self {
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_nanos()),
+        }
+    }
+}
+
+/// The chunk hashes recorded for `destination`, if it has a journal that was
+/// written for a source with this stamp.
+pub fn load(destination: &Path, stamp: SourceStamp) -> Option<Vec<blake3::Hash>> {
+    let text = fs::read_to_string(path_for(destination)).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != MAGIC {
+        return None;
+    }
+    let recorded = SourceStamp {
+        size: field(lines.next()?, "size")?,
+        modified: field(lines.next()?, "modified")?,
+    };
+    let chunk_size: u64 = field(lines.next()?, "chunk")?;
+    if recorded != stamp || chunk_size != CHUNK_SIZE {
+        return None;
+    }
+    Some(
+        lines
+            .map_while(|line| blake3::Hash::from_hex(line).ok())
+            .collect(),
+    )
+}
+
+fn field<T: std::str::FromStr>(line: &str, name: &str) -> Option<T> {
+    line.strip_prefix(name)?.strip_prefix(' ')?.parse().ok()
+}
+
+/// Hash the next chunk of `file`, feeding the same bytes to `running` too.
+///
+/// Returns `None` when the file ends before a whole chunk, since only whole
+/// chunks are ever recorded.
+///
+/// # Errors
+///
+/// Returns an error if reading fails.
+pub fn hash_chunk(
+    file: &mut File,
+    buffer: &mut [u8],
+    mut running: Option<&mut blake3::Hasher>,
+) -> io::Result<Option<blake3::Hash>> {
+    let mut chunk = blake3::Hasher::new();
+    let mut remaining = CHUNK_SIZE;
+    while remaining > 0 {
+        let wanted = usize::try_from(remaining).map_or(buffer.len(), |r| r.min(buffer.len()));
+        let bytes_read = file.read(&mut buffer[..wanted])?;
+        if bytes_read == 0 {
+            return Ok(None);
+        }
+        chunk.update(&buffer[..bytes_read]);
+        if let Some(running) = running.as_deref_mut() {
+            running.update(&buffer[..bytes_read]);
+        }
+        remaining -= bytes_read as u64;
+    }
+    Ok(Some(chunk.finalize()))
+}
+
+/// An open journal that records chunks as the copy writes them.
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+    chunk: blake3::Hasher,
+    pending: u64,
+}
+
+impl Journal {
+    /// Start the journal for `destination`, whose first `verified` chunks are
+    /// already in place. Any older journal is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written.
+    pub fn create(
+        destination: &Path,
+        stamp: SourceStamp,
+        verified: &[blake3::Hash],
+    ) -> io::Result<Self> {
+        let path = path_for(destination);
+        let mut text = format!(
+            "{MAGIC}\nsize {}\nmodified {}\nchunk {CHUNK_SIZE}\n",
+            stamp.size, stamp.modified
+        );
+        for hash in verified {
+            text.push_str(hash.to_hex().as_str());
+            text.push('\n');
+        }
+        let mut file = File::create(&path)?;
+        file.write_all(text.as_bytes())?;
+        Ok(Self {
+            path,
+            file,
+            chunk: blake3::Hasher::new(),
+            pending: 0,
+        })
+    }
+
+    /// Account for `data`, which has just been written to the destination,
+    /// appending a line for every chunk it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be appended.
+    pub fn record(&mut self, mut data: &[u8]) -> io::Result<()> {
+        while !data.is_empty() {
+            let room = usize::try_from(CHUNK_SIZE - self.pending).unwrap_or(usize::MAX);
+            let (head, rest) = data.split_at(room.min(data.len()));
+            self.chunk.update(head);
+            self.pending += head.len() as u64;
+            if self.pending == CHUNK_SIZE {
+                writeln!(self.file, "{}", self.chunk.finalize().to_hex())?;
+                self.chunk.reset();
+                self.pending = 0;
+            }
+            data = rest;
+        }
+        Ok(())
+    }
+
+    /// Push the journal to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if syncing fails.
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Delete the journal once the copy is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be removed.
+    pub fn finish(self) -> io::Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.path)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    reason = "tests use unwrap for brevity and clear failure messages"
+)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const STAMP: SourceStamp = SourceStamp {
+        size: 3 * CHUNK_SIZE,
+        modified: 1_760_659_200_123_456_789,
+    };
+
+    #[test]
+    fn journal_sits_beside_destination() {
+        assert_eq!(
+            path_for(Path::new("backup/movie.mkv")),
+            PathBuf::from("backup/.movie.mkv.prcp-journal")
+        );
+    }
+
+    #[test]
+    fn recorded_chunks_are_loaded_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path().join("dest.bin");
+        let first = blake3::hash(b"first");
+
+        let mut journal = Journal::create(&destination, STAMP, &[first]).unwrap();
+        let chunk = vec![7_u8; usize::try_from(CHUNK_SIZE).unwrap()];
+        // Split across calls the way reads arrive, with a partial chunk left over
+        journal.record(&chunk[..1000]).unwrap();
+        journal.record(&chunk[1000..]).unwrap();
+        journal.record(b"partial").unwrap();
+
+        assert_eq!(
+            load(&destination, STAMP).unwrap(),
+            vec![first, blake3::hash(&chunk)]
+        );
+    }
+
+    #[test]
+    fn changed_source_discards_journal() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path().join("dest.bin");
+        Journal::create(&destination, STAMP, &[blake3::hash(b"first")]).unwrap();
+
+        let touched = SourceStamp {
+            modified: STAMP.modified + 1,
+            ..STAMP
+        };
+        assert_eq!(load(&destination, touched), None);
+    }
+
+    #[test]
+    fn torn_last_line_is_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path().join("dest.bin");
+        let first = blake3::hash(b"first");
+        Journal::create(&destination, STAMP, &[first]).unwrap();
+
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(path_for(&destination))
+            .unwrap();
+        let torn = blake3::hash(b"second").to_hex();
+        file.write_all(&torn.as_bytes()[..20]).unwrap();
+
+        assert_eq!(load(&destination, STAMP).unwrap(), vec![first]);
+    }
+
+    #[test]
+    fn finish_removes_journal() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path().join("dest.bin");
+        let journal = Journal::create(&destination, STAMP, &[]).unwrap();
+
+        journal.finish().unwrap();
+        assert!(!path_for(&destination).exists());
+    }
+
+    #[test]
+    fn short_file_has_no_whole_chunk() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("short.bin");
+        fs::write(&path, b"not a whole chunk").unwrap();
+
+        let mut running = blake3::Hasher::new();
+        let mut buffer = vec![0; 4096];
+        let hash = hash_chunk(
+            &mut File::open(&path).unwrap(),
+            &mut buffer,
+            Some(&mut running),
+        )
+        .unwrap();
+        assert_eq!(hash, None);
+    }
+}
@@ -17,7 +17,7 @@ use shellsetup::ShellIntegration;
 use termbar::{ProgressStyleBuilder, TerminalWidthWatcher};
 // Blake3 imported via blake3 crate (no Digest trait needed)
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -25,6 +25,10 @@ use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task;
 
+mod journal;
+mod metadata;
+mod tree;
+
 /// Create a buffer without zeroing memory.
 ///
 /// This is an optimization to avoid the cost of zeroing large buffers
@@ -106,10 +110,12 @@ impl Drop for RawModeGuard {
 /// Ensures the destination file is properly closed and removed if the copy fails.
 /// This handles all error paths consistently, including Ctrl+C cancellation,
 /// I/O errors, and any other failures during the copy operation.
+/// With `--resume` the file is closed but kept, so a later run can continue it.
 struct PartialFileGuard<'a> {
     destination: &'a Path,
     file: Option<File>,
     defused: bool,
+    keep: bool,
 }
 
 impl<'a> PartialFileGuard<'a> {
@@ -119,6 +125,18 @@ impl<'a> PartialFileGuard<'a> {
             destination,
             file: Some(file),
             defused: false,
+            keep: false,
+        }
+    }
+
+    /// Create a guard that only closes the destination file on drop, keeping
+    /// the partial contents for `--resume`.
+    fn resumable(destination: &'a Path, file: File) -> Self {
+        Self {
+            destination,
+            file: Some(file),
+            defused: false,
+            keep: true,
         }
     }
 
@@ -160,7 +178,9 @@ impl Drop for PartialFileGuard<'_> {
             // Close the file handle first (important for Windows compatibility)
             drop(self.file.take());
             // Now remove the partial file - ignore errors since we're already in cleanup
-            let _ = fs::remove_file(self.destination);
+            if !self.keep {
+                let _ = fs::remove_file(self.destination);
+            }
         }
     }
 }
@@ -178,10 +198,29 @@ struct Args {
     #[arg(long, verbatim_doc_comment)]
     shell_setup: bool,
 
-    /// Remove source files after successful copy (verified by Blake3 hash)
+    /// Remove source files after successful copy (verified by Blake3 hash).
+    /// With -R, source directories left empty are removed too
     #[arg(long, short = 'r')]
     rm: bool,
 
+    /// Copy directories recursively, recreating symlinks instead of following them
+    /// (-r is taken by --rm)
+    #[arg(long, short = 'R')]
+    recursive: bool,
+
+    /// Keep access and modification times as well as permissions (always on with -R)
+    #[arg(long, short = 'p')]
+    preserve: bool,
+
+    /// Also copy extended attributes (Linux and macOS); implies --preserve
+    #[arg(long)]
+    xattrs: bool,
+
+    /// Keep partial files plus a journal beside each one, and continue an
+    /// interrupted copy from its last verified chunk when run again with --resume
+    #[arg(long)]
+    resume: bool,
+
     /// Skip Blake3 verification after copy (not allowed with --rm)
     #[arg(long)]
     no_verify: bool,
@@ -219,6 +258,15 @@ struct Args {
     quiet: bool,
 }
 
+impl Args {
+    /// Whether each copy takes its source's timestamps (and extended
+    /// attributes with `--xattrs`) once verified. Permissions are copied
+    /// either way.
+    fn preserve_metadata(&self) -> bool {
+        self.recursive || self.preserve || self.xattrs
+    }
+}
+
 /// The shell code to add to shell config files.
 const SHELL_CODE: &str = r#"
 function prmv() {
@@ -317,14 +365,16 @@ fn format_buffer_size(size: usize) -> String {
     }
 }
 
-/// Resolve source patterns into a list of files.
+/// Resolve source patterns into a list of files, and directories when
+/// `allow_directories` is set (for `--recursive`).
 ///
 /// # Behavior
 ///
 /// For each pattern:
-/// 1. If the path exists as a literal file, use it directly (no glob expansion)
+/// 1. If the path exists as a literal file (or allowed directory), use it directly
+///    (no glob expansion)
 /// 2. If `literal` is false and the path contains glob characters (*, ?, []),
-///    expand the glob and collect matching files
+///    expand the glob and collect matching files (and allowed directories)
 /// 3. Otherwise, return an error (path doesn't exist or is not a file)
 ///
 /// This "literal-first" approach (like `mv` and `cp`) allows filenames containing
@@ -334,6 +384,7 @@ fn format_buffer_size(size: usize) -> String {
 ///
 /// * `patterns` - Paths that may be literal files or glob patterns
 /// * `literal` - If true, disable glob expansion entirely (all paths treated as literals)
+/// * `allow_directories` - If true, directories are resolved like files
 ///
 /// # Errors
 ///
@@ -341,7 +392,11 @@ fn format_buffer_size(size: usize) -> String {
 /// - A glob pattern matches no files
 /// - A literal path doesn't exist or is not a file
 /// - Glob iteration encounters errors (collected and reported)
-fn resolve_sources(patterns: &[PathBuf], literal: bool) -> Result<Vec<PathBuf>> {
+fn resolve_sources(
+    patterns: &[PathBuf],
+    literal: bool,
+    allow_directories: bool,
+) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     let mut glob_errors: Vec<String> = Vec::new();
 
@@ -349,7 +404,7 @@ fn resolve_sources(patterns: &[PathBuf], literal: bool) -> Result<Vec<PathBuf>>
         // Check if literal path exists first (like `mv` does) - this allows paths
         // with glob characters (e.g., [brackets]) to work when they're literal filenames.
         // This single is_file() check avoids redundant syscalls in the common case.
-        if pattern.is_file() {
+        if pattern.is_file() || (allow_directories && pattern.is_dir()) {
             files.push(pattern.clone());
             continue;
         }
@@ -367,7 +422,7 @@ fn resolve_sources(patterns: &[PathBuf], literal: bool) -> Result<Vec<PathBuf>>
             for entry in glob_iter {
                 match entry {
                     Ok(path) => {
-                        if path.is_file() {
+                        if path.is_file() || (allow_directories && path.is_dir()) {
                             matches.push(path);
                         }
                     }
@@ -396,6 +451,12 @@ fn resolve_sources(patterns: &[PathBuf], literal: bool) -> Result<Vec<PathBuf>>
                 anyhow::bail!("Source '{}' does not exist", pattern.display());
             }
             // Path exists but is not a file (e.g., directory)
+            if pattern.is_dir() {
+                anyhow::bail!(
+                    "Source '{}' is not a file (use -R to copy directories)",
+                    pattern.display()
+                );
+            }
             anyhow::bail!("Source '{}' is not a file", pattern.display());
         }
     }
@@ -434,6 +495,10 @@ async fn main() -> Result<()> {
         );
     }
 
+    if args.xattrs && !metadata::XATTRS_SUPPORTED {
+        anyhow::bail!("--xattrs is only supported on Linux and macOS.");
+    }
+
     // Parse paths: all but last are sources, last is destination
     if args.paths.len() < 2 {
         anyhow::bail!("Usage: prcp <source>... <destination>\n\nAt least one source and a destination are required.");
@@ -443,13 +508,12 @@ async fn main() -> Result<()> {
     let destination = destination[0].clone();
     let source_paths: Vec<PathBuf> = source_paths.to_vec();
 
-    // Resolve all source files (handles glob patterns)
-    let sources = resolve_sources(&source_paths, args.literal)?;
-    let total_files = sources.len();
+    // Resolve all sources (handles glob patterns; directories only with --recursive)
+    let sources = resolve_sources(&source_paths, args.literal, args.recursive)?;
 
-    // Validate destination for multi-file operations
-    if total_files > 1 {
-        // For multiple files, destination must be a directory
+    // Validate destination for multi-source operations
+    if sources.len() > 1 {
+        // For multiple sources, destination must be a directory
         // Check if exists and is NOT a directory (error case)
         if destination.exists() && !destination.is_dir() {
             anyhow::bail!(
@@ -461,6 +525,16 @@ async fn main() -> Result<()> {
         // This avoids creating empty directories if all operations fail
     }
 
+    // Walk every source tree now, so the batch total covers every file before copying starts
+    let plan = tree::plan(&sources, &destination)?;
+    for path in &plan.special {
+        eprintln!(
+            "Warning: Skipping '{}' (not a regular file, directory or symlink)",
+            path.display()
+        );
+    }
+    let total_files = plan.files.len();
+
     // Warn about potentially dangerous combination
     if args.rm && args.continue_on_error && total_files > 1 && !args.yes {
         eprintln!("Warning: Using --rm with --continue-on-error may result in partial moves.");
@@ -476,6 +550,27 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Track failures for --continue-on-error mode
+    let mut failures: Vec<(PathBuf, String)> = Vec::new();
+
+    // Recreate the directory skeleton first, so empty directories come across too
+    for directory in &plan.directories {
+        if let Err(e) = fs::create_dir_all(&directory.destination) {
+            if args.continue_on_error {
+                failures.push((
+                    directory.source.clone(),
+                    format!("Failed to create directory: {}", e),
+                ));
+            } else {
+                anyhow::bail!(
+                    "Failed to create destination directory '{}': {}",
+                    directory.destination.display(),
+                    e
+                );
+            }
+        }
+    }
+
     // Set up shutdown flag (can be reset if user declines cancellation)
     let shutdown = Arc::new(AtomicBool::new(false));
 
@@ -602,7 +697,7 @@ async fn main() -> Result<()> {
     let verify_enabled = !args.no_verify;
     let total_batch_bytes = if total_files > 1 {
         // Calculate total batch work; if it fails, we'll continue without the batch progress bar
-        calculate_total_batch_bytes(&sources, verify_enabled).ok()
+        calculate_total_batch_bytes(&plan.files, verify_enabled).ok()
     } else {
         None
     };
@@ -631,12 +726,11 @@ async fn main() -> Result<()> {
         None
     };
 
-    // Track failures for --continue-on-error mode
-    let mut failures: Vec<(PathBuf, String)> = Vec::new();
     // Track files skipped due to --skip-existing (not counted as failures)
     let mut skipped_existing: Vec<PathBuf> = Vec::new();
     let mut successful_copies = 0_u64;
     let mut total_bytes_copied = 0_u64;
+    let mut total_resumed_bytes = 0_u64;
     let mut total_copy_duration = Duration::ZERO;
     let mut total_verify_duration = Duration::ZERO;
 
@@ -647,23 +741,16 @@ async fn main() -> Result<()> {
     let mut completed_files = 0_usize;
 
     // Copy each file
-    for source in &sources {
+    for entry in &plan.files {
+        let source = &entry.source;
+        let dest_path = &entry.destination;
+
         // Check for shutdown
         if shutdown.load(Ordering::SeqCst) {
             eprintln!("\nCopy cancelled by user");
             break;
         }
 
-        // Resolve destination path
-        let dest_path = if destination.is_dir() {
-            let filename = source
-                .file_name()
-                .ok_or_else(|| anyhow::anyhow!("Source '{}' has no filename", source.display()))?;
-            destination.join(filename)
-        } else {
-            destination.clone()
-        };
-
         // Get file metadata
         let metadata = match fs::metadata(source) {
             Ok(m) => m,
@@ -689,8 +776,12 @@ async fn main() -> Result<()> {
             file_size
         };
 
-        // Check if destination exists
-        if dest_path.exists() {
+        // Check if destination exists. A partial file is continued instead, but only
+        // when its journal was written for this very source: a stale journal must not
+        // let a finished or unrelated file be overwritten without asking
+        let resuming =
+            args.resume && journal::load(dest_path, journal::SourceStamp::of(&metadata)).is_some();
+        if dest_path.exists() && !resuming {
             let should_overwrite = if args.yes {
                 true
             } else if args.skip_existing {
@@ -789,7 +880,7 @@ async fn main() -> Result<()> {
         // Perform the copy
         let result = copy_with_progress(
             source,
-            &dest_path,
+            dest_path,
             &file_pb,
             &filename,
             paused.clone(),
@@ -800,6 +891,7 @@ async fn main() -> Result<()> {
             args.buffer_size,
             args.sequential,
             !args.no_buffer_pool,
+            args.resume,
         )
         .await;
 
@@ -810,6 +902,7 @@ async fn main() -> Result<()> {
             Ok(copy_result) => {
                 successful_copies += 1;
                 total_bytes_copied += copy_result.bytes_copied;
+                total_resumed_bytes += copy_result.resumed_bytes;
                 total_copy_duration += copy_result.copy_duration;
 
                 // Update batch progress for completed copy
@@ -820,14 +913,27 @@ async fn main() -> Result<()> {
                 }
 
                 // Build stats for this file
-                let copy_speed = format_speed(copy_result.bytes_copied, copy_result.copy_duration);
+                // A resumed copy only wrote what came after the verified prefix
+                let copy_speed = format_speed(
+                    copy_result.bytes_copied - copy_result.resumed_bytes,
+                    copy_result.copy_duration,
+                );
                 let copy_time = format_duration(copy_result.copy_duration);
+                let size = if copy_result.resumed_bytes > 0 {
+                    format!(
+                        "{}, resumed after {}",
+                        HumanBytes(copy_result.bytes_copied),
+                        HumanBytes(copy_result.resumed_bytes)
+                    )
+                } else {
+                    HumanBytes(copy_result.bytes_copied).to_string()
+                };
 
                 // Verify by default (unless --no-verify)
                 // Cancellation prompt and resume happen inside verify_destination now
                 let verify_outcome = if !args.no_verify {
                     match verify_destination(
-                        &dest_path,
+                        dest_path,
                         &copy_result.source_hash,
                         &multi,
                         &shutdown,
@@ -857,7 +963,7 @@ async fn main() -> Result<()> {
                         }
                         Err(VerifyError::Cancelled) => {
                             // User already confirmed cancellation via prompt inside verify_destination
-                            if let Err(e) = fs::remove_file(&dest_path) {
+                            if let Err(e) = fs::remove_file(dest_path) {
                                 eprintln!("Warning: Failed to remove destination file: {}", e);
                             } else {
                                 eprintln!("Destination file deleted.");
@@ -905,6 +1011,28 @@ async fn main() -> Result<()> {
                     break;
                 }
 
+                // Carry timestamps (and extended attributes) over once verification is done
+                // reading the copy, so its access time is the source's too
+                let preserved = if matches!(verify_outcome, VerifyOutcome::Failed) {
+                    false
+                } else if !args.preserve_metadata() {
+                    true
+                } else {
+                    match metadata::preserve(source, dest_path, args.xattrs) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            let error_msg = format!(
+                                "Failed to preserve metadata on '{}': {:#}",
+                                dest_path.display(),
+                                e
+                            );
+                            eprintln!("\n{}", error_msg);
+                            failures.push((source.clone(), error_msg));
+                            false
+                        }
+                    }
+                };
+
                 // Update completed file count and batch progress message
                 if matches!(
                     verify_outcome,
@@ -917,10 +1045,12 @@ async fn main() -> Result<()> {
                 }
 
                 // Remove source if --rm and verification passed (or was skipped, which is blocked by flag validation)
-                let should_allow_removal = matches!(
-                    verify_outcome,
-                    VerifyOutcome::Passed { .. } | VerifyOutcome::Skipped
-                );
+                // and its metadata made it onto the copy
+                let should_allow_removal = preserved
+                    && matches!(
+                        verify_outcome,
+                        VerifyOutcome::Passed { .. } | VerifyOutcome::Skipped
+                    );
                 let removed = if args.rm && should_allow_removal {
                     match fs::remove_file(source) {
                         Ok(()) => true,
@@ -941,14 +1071,15 @@ async fn main() -> Result<()> {
                 };
 
                 // Print per-file stats (unless quiet mode, but always show problems)
-                let is_problem =
-                    matches!(verify_outcome, VerifyOutcome::Failed) || (args.rm && !removed);
+                let is_problem = matches!(verify_outcome, VerifyOutcome::Failed)
+                    || !preserved
+                    || (args.rm && !removed);
 
                 if !args.quiet || is_problem {
                     let status = match &verify_outcome {
                         VerifyOutcome::Failed => "fail".red(),
                         VerifyOutcome::Passed { .. } | VerifyOutcome::Skipped
-                            if args.rm && !removed =>
+                            if !preserved || (args.rm && !removed) =>
                         {
                             "partial".yellow()
                         }
@@ -962,7 +1093,7 @@ async fn main() -> Result<()> {
                                 status,
                                 filename,
                                 dest_path.display(),
-                                HumanBytes(copy_result.bytes_copied),
+                                size,
                                 copy_time,
                                 copy_speed,
                                 time,
@@ -976,7 +1107,7 @@ async fn main() -> Result<()> {
                                 status,
                                 filename,
                                 dest_path.display(),
-                                HumanBytes(copy_result.bytes_copied),
+                                size,
                                 copy_time,
                                 copy_speed
                             )
@@ -1012,6 +1143,9 @@ async fn main() -> Result<()> {
         }
     }
 
+    // A confirmed Ctrl+C leaves the flag set and stops the rest of the plan too
+    let cancelled = shutdown.load(Ordering::SeqCst);
+
     // Signal key listener and signal handler to exit (operation complete)
     key_listener_done.store(true, Ordering::SeqCst);
     drop(resize_shutdown_tx.take()); // Signal resize task to exit
@@ -1034,10 +1168,121 @@ async fn main() -> Result<()> {
         anyhow::bail!("{}", error_msg);
     }
 
+    // Symlinks and directory metadata come last: links need no progress display,
+    // and a directory's times only settle once everything inside it exists
+    let mut symlinks_created = 0_usize;
+    let mut kept_directories = 0_usize;
+    if !cancelled {
+        for link in &plan.symlinks {
+            if fs::symlink_metadata(&link.destination).is_ok() {
+                let should_replace = if args.yes {
+                    true
+                } else if args.skip_existing {
+                    false
+                } else {
+                    eprint!(
+                        "\nDestination '{}' already exists. Overwrite? (y/N): ",
+                        link.destination.display()
+                    );
+                    io::stderr().flush()?;
+                    let mut input = String::new();
+                    io::stdin().read_line(&mut input)?;
+                    input.trim().eq_ignore_ascii_case("y")
+                };
+
+                if !should_replace {
+                    if args.skip_existing {
+                        skipped_existing.push(link.source.clone());
+                    } else {
+                        failures.push((
+                            link.source.clone(),
+                            "Skipped (destination exists)".to_string(),
+                        ));
+                    }
+                    continue;
+                }
+                if let Err(e) = fs::remove_file(&link.destination) {
+                    failures.push((
+                        link.source.clone(),
+                        format!("Failed to replace destination: {}", e),
+                    ));
+                    continue;
+                }
+            }
+
+            let result = tree::recreate_symlink(&link.source, &link.destination)
+                .and_then(|()| metadata::preserve(&link.source, &link.destination, false));
+            if let Err(e) = result {
+                failures.push((link.source.clone(), format!("{:#}", e)));
+                continue;
+            }
+            symlinks_created += 1;
+
+            if args.rm {
+                if let Err(e) = fs::remove_file(&link.source) {
+                    failures.push((
+                        link.source.clone(),
+                        format!("Failed to remove source '{}': {}", link.source.display(), e),
+                    ));
+                    continue;
+                }
+            }
+            if !args.quiet {
+                println!(
+                    "{} {} -> '{}' (symlink)",
+                    "ok".green(),
+                    link.source.display(),
+                    link.destination.display()
+                );
+            }
+        }
+
+        // Deepest first, so setting a child's times cannot disturb its parent's
+        for directory in plan.directories.iter().rev() {
+            if let Err(e) =
+                metadata::preserve(&directory.source, &directory.destination, args.xattrs)
+            {
+                failures.push((
+                    directory.source.clone(),
+                    format!(
+                        "Failed to preserve metadata on '{}': {:#}",
+                        directory.destination.display(),
+                        e
+                    ),
+                ));
+            }
+        }
+
+        // prmv moves a tree file by file, so a source directory is removed only once
+        // everything in it has been verified and removed
+        if args.rm {
+            for directory in plan.directories.iter().rev() {
+                // `.` and `..` name a directory the caller is still using
+                if directory.source.file_name().is_none() {
+                    continue;
+                }
+                match fs::remove_dir(&directory.source) {
+                    Ok(()) => {}
+                    // Something in it failed, was skipped, or appeared after planning
+                    Err(e) if e.kind() == io::ErrorKind::DirectoryNotEmpty => {
+                        kept_directories += 1;
+                    }
+                    Err(e) => failures.push((
+                        directory.source.clone(),
+                        format!("Failed to remove source directory: {}", e),
+                    )),
+                }
+            }
+        }
+    }
+
     // Print summary for multiple files
     if total_files > 1 {
         if successful_copies > 0 {
-            let copy_speed = format_speed(total_bytes_copied, total_copy_duration);
+            let copy_speed = format_speed(
+                total_bytes_copied - total_resumed_bytes,
+                total_copy_duration,
+            );
             let copy_time = format_duration(total_copy_duration);
 
             // Show verify stats only if verification was enabled AND at least one
@@ -1079,6 +1324,16 @@ async fn main() -> Result<()> {
         }
     }
 
+    if symlinks_created > 0 {
+        println!("Recreated {} symlink(s)", symlinks_created);
+    }
+    if kept_directories > 0 {
+        println!(
+            "Left {} source directory(ies) in place because they still contain files",
+            kept_directories
+        );
+    }
+
     // Report skipped files (when using --skip-existing)
     if !skipped_existing.is_empty() {
         println!(
@@ -1130,10 +1385,20 @@ impl From<blake3::Hash> for Blake3Hash {
 /// Result of a copy operation, including bytes copied, source hash, and timing
 struct CopyResult {
     bytes_copied: u64,
+    /// Bytes a `--resume` run found already copied and verified
+    resumed_bytes: u64,
     source_hash: Blake3Hash,
     copy_duration: Duration,
 }
 
+/// Where a `--resume` copy starts: the length of the verified prefix, the Blake3
+/// state of the source over that prefix, and the journal to keep extending.
+struct Resume {
+    offset: u64,
+    hasher: blake3::Hasher,
+    journal: journal::Journal,
+}
+
 /// Result of a verification operation, including timing information
 struct VerifyResult {
     verify_duration: Duration,
@@ -1289,11 +1554,11 @@ fn calculate_file_hash(
 /// is doubled since each byte needs to be read twice (once for copy, once for verify).
 ///
 /// Returns an error if any source file's metadata cannot be read.
-fn calculate_total_batch_bytes(sources: &[PathBuf], verify_enabled: bool) -> Result<u64> {
+fn calculate_total_batch_bytes(files: &[tree::Entry], verify_enabled: bool) -> Result<u64> {
     let mut total_bytes = 0_u64;
-    for source in sources {
-        let metadata = fs::metadata(source)
-            .with_context(|| format!("Failed to read metadata for '{}'", source.display()))?;
+    for file in files {
+        let metadata = fs::metadata(&file.source)
+            .with_context(|| format!("Failed to read metadata for '{}'", file.source.display()))?;
         total_bytes = total_bytes.saturating_add(metadata.len());
     }
     // If verification is enabled, we read each byte twice
@@ -1425,15 +1690,16 @@ fn verify_destination(
 /// Returns true if user confirms cancellation, false to continue.
 /// Uses crossterm event reading to capture Ctrl+C as a key event (not SIGINT).
 /// Pressing Ctrl+C at this prompt is treated as confirmation to cancel.
-fn prompt_cancel_copy(destination: &Path, input_active: &Arc<AtomicBool>) -> bool {
+fn prompt_cancel_copy(destination: &Path, input_active: &Arc<AtomicBool>, keep: bool) -> bool {
     // Pause key listener while we handle input ourselves
     input_active.store(true, Ordering::SeqCst);
 
     // Disable raw mode temporarily to print prompt with proper line handling
     let _ = crossterm::terminal::disable_raw_mode();
     eprint!(
-        "\nCancel copy? Partial file '{}' will be deleted. (y/N): ",
-        destination.display()
+        "\nCancel copy? Partial file '{}' will be {}. (y/N): ",
+        destination.display(),
+        if keep { "kept for --resume" } else { "deleted" }
     );
     let _ = io::stderr().flush();
 
@@ -1449,6 +1715,17 @@ fn prompt_cancel_copy(destination: &Path, input_active: &Arc<AtomicBool>) -> boo
     confirmed
 }
 
+/// The error for a copy the user cancelled, saying what became of the partial file.
+fn copy_cancelled(kept: bool) -> anyhow::Error {
+    if kept {
+        anyhow::anyhow!(
+            "Copy cancelled by user (partial destination file kept; run again with --resume to continue)"
+        )
+    } else {
+        anyhow::anyhow!("Copy cancelled by user (partial destination file deleted)")
+    }
+}
+
 /// Prompt user to confirm verification cancellation.
 ///
 /// Returns true if user confirms cancellation, false to continue/resume.
@@ -1536,6 +1813,10 @@ fn read_yes_no_with_ctrlc() -> bool {
 /// and potentially improving write performance. Errors are silently ignored
 /// since preallocation is an optimization, not a requirement.
 #[cfg(target_os = "linux")]
+#[allow(
+    clippy::cast_possible_wrap,
+    reason = "no real file is larger than i64::MAX bytes"
+)]
 fn try_preallocate(file: &File, size: u64) {
     use std::os::unix::io::AsRawFd;
     // posix_fallocate returns 0 on success, error code on failure
@@ -1543,7 +1824,6 @@ fn try_preallocate(file: &File, size: u64) {
     // SAFETY: posix_fallocate is a standard POSIX function. We pass a valid file descriptor
     // obtained from AsRawFd, offset 0, and the file size. The cast to off_t is safe for
     // typical file sizes (up to i64::MAX bytes). Errors are ignored as this is an optimization.
-    #[allow(clippy::cast_possible_wrap)]
     unsafe {
         libc::posix_fallocate(file.as_raw_fd(), 0, size as libc::off_t);
     }
@@ -1687,7 +1967,28 @@ async fn copy_with_progress(
     buffer_size: usize,
     force_sequential: bool,
     use_buffer_pool: bool,
+    resumable: bool,
 ) -> Result<CopyResult> {
+    let resume = if resumable {
+        Some(
+            prepare_resume(
+                source,
+                destination,
+                pb,
+                &paused,
+                &shutdown,
+                &input_active,
+                rx,
+                buffer_size,
+            )
+            .await?,
+        )
+    } else {
+        // A journal from an earlier --resume run no longer describes this destination
+        journal::discard(destination);
+        None
+    };
+
     if force_sequential || same_device(source, destination) {
         copy_sequential(
             source,
@@ -1700,6 +2001,7 @@ async fn copy_with_progress(
             rx,
             term_width_rx,
             buffer_size,
+            resume,
         )
         .await
     } else {
@@ -1715,11 +2017,123 @@ async fn copy_with_progress(
             term_width_rx,
             buffer_size,
             use_buffer_pool,
+            resume,
         )
         .await
     }
 }
 
+/// Check what an interrupted `--resume` copy left at `destination` and work out
+/// where to carry on.
+///
+/// Each chunk in the journal is read back from both the source and the partial
+/// file, and both must hash to what the journal recorded. The first chunk that
+/// does not match is copied again, along with everything after it. A journal
+/// written for a source of a different size or modification time is ignored.
+/// Space pauses the check and Ctrl+C cancels it, as during the copy itself.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "all parameters serve distinct purposes for progress/cancellation"
+)]
+async fn prepare_resume(
+    source: &Path,
+    destination: &Path,
+    pb: &ProgressBar,
+    paused: &AtomicBool,
+    shutdown: &AtomicBool,
+    input_active: &Arc<AtomicBool>,
+    rx: &mut mpsc::UnboundedReceiver<()>,
+    buffer_size: usize,
+) -> Result<Resume> {
+    let metadata = fs::metadata(source).context("Failed to get source file metadata")?;
+    let stamp = journal::SourceStamp::of(&metadata);
+    let recorded = journal::load(destination, stamp).unwrap_or_default();
+
+    let mut hasher = blake3::Hasher::new();
+    let mut verified = Vec::new();
+    let files = if recorded.is_empty() {
+        None
+    } else {
+        File::open(source).ok().zip(File::open(destination).ok())
+    };
+    if let Some((mut src_file, mut dst_file)) = files {
+        pb.set_message("Checking partial copy");
+        let mut buffer = create_uninit_buffer(buffer_size);
+        let mut offset = 0_u64;
+
+        for expected in &recorded {
+            if rx.try_recv().is_ok() && !paused.fetch_xor(true, Ordering::SeqCst) {
+                pb.set_message("PAUSED - Press space to resume");
+            }
+            while paused.load(Ordering::SeqCst) && !shutdown.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                if rx.try_recv().is_ok() {
+                    paused.store(false, Ordering::SeqCst);
+                    pb.set_message("Checking partial copy");
+                }
+            }
+            if shutdown.load(Ordering::SeqCst) {
+                if prompt_cancel_copy(destination, input_active, true) {
+                    return Err(copy_cancelled(true));
+                }
+                shutdown.store(false, Ordering::SeqCst);
+            }
+
+            // The running hash only takes a chunk once both copies of it check out
+            let mut candidate = hasher.clone();
+            let source_chunk =
+                journal::hash_chunk(&mut src_file, &mut buffer, Some(&mut candidate))
+                    .context("Failed to read source file")?;
+            let destination_chunk = journal::hash_chunk(&mut dst_file, &mut buffer, None)
+                .context("Failed to read partial destination file")?;
+            if source_chunk != Some(*expected) || destination_chunk != Some(*expected) {
+                break;
+            }
+            hasher = candidate;
+            verified.push(*expected);
+            offset += journal::CHUNK_SIZE;
+            pb.set_position(offset);
+        }
+        pb.set_message("");
+    }
+
+    let journal = journal::Journal::create(destination, stamp, &verified)
+        .context("Failed to write resume journal")?;
+    Ok(Resume {
+        offset: verified.len() as u64 * journal::CHUNK_SIZE,
+        hasher,
+        journal,
+    })
+}
+
+/// Open the destination for a copy. A fresh copy truncates it; a resumed one
+/// cuts it back to the verified prefix and moves both files past that prefix.
+fn open_destination(
+    src_file: &mut File,
+    destination: &Path,
+    resume_offset: Option<u64>,
+) -> Result<File> {
+    let Some(offset) = resume_offset else {
+        return File::create(destination).context("Failed to create destination file");
+    };
+    let mut dst_file = File::options()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(destination)
+        .context("Failed to open destination file")?;
+    dst_file
+        .set_len(offset)
+        .context("Failed to truncate destination file")?;
+    dst_file
+        .seek(SeekFrom::Start(offset))
+        .context("Failed to seek destination file")?;
+    src_file
+        .seek(SeekFrom::Start(offset))
+        .context("Failed to seek source file")?;
+    Ok(dst_file)
+}
+
 /// Sequential copy implementation for same-device scenarios.
 ///
 /// Performs read→hash→write in sequence. This is optimal for spinning HDDs
@@ -1739,6 +2153,7 @@ async fn copy_sequential(
     rx: &mut mpsc::UnboundedReceiver<()>,
     term_width_rx: &watch::Receiver<u16>,
     buffer_size: usize,
+    resume: Option<Resume>,
 ) -> Result<CopyResult> {
     let start_time = Instant::now();
     let mut src_file = File::open(source).context("Failed to open source file")?;
@@ -1752,18 +2167,31 @@ async fn copy_sequential(
     // Hint sequential read pattern for source file
     hint_sequential_io(&src_file);
 
-    let dst_file = File::create(destination).context("Failed to create destination file")?;
+    let resumed_bytes = resume.as_ref().map_or(0, |resume| resume.offset);
+    let dst_file = open_destination(
+        &mut src_file,
+        destination,
+        resume.as_ref().map(|_| resumed_bytes),
+    )?;
 
     // Preallocate space to reduce fragmentation and improve write performance
     try_preallocate(&dst_file, file_size);
 
+    let (mut hasher, mut journal) = match resume {
+        Some(resume) => (resume.hasher, Some(resume.journal)),
+        None => (blake3::Hasher::new(), None),
+    };
+
     // Use RAII guard to ensure partial file cleanup on any error path
     // (Ctrl+C, I/O errors, etc.). The guard is defused on successful completion.
-    let mut guard = PartialFileGuard::new(destination, dst_file);
+    let mut guard = if journal.is_some() {
+        PartialFileGuard::resumable(destination, dst_file)
+    } else {
+        PartialFileGuard::new(destination, dst_file)
+    };
 
     let mut buffer = create_uninit_buffer(buffer_size);
-    let mut total_bytes = 0_u64;
-    let mut hasher = blake3::Hasher::new();
+    let mut total_bytes = resumed_bytes;
 
     // Track last terminal width for resize detection
     let mut last_width = *term_width_rx.borrow();
@@ -1780,10 +2208,8 @@ async fn copy_sequential(
     loop {
         // Check for shutdown - prompt user for confirmation (keep responsive)
         if shutdown.load(Ordering::SeqCst) {
-            if prompt_cancel_copy(destination, &input_active) {
-                return Err(anyhow::anyhow!(
-                    "Copy cancelled by user (partial destination file deleted)"
-                ));
+            if prompt_cancel_copy(destination, &input_active, journal.is_some()) {
+                return Err(copy_cancelled(journal.is_some()));
             }
             // User declined cancellation - reset flag and continue
             shutdown.store(false, Ordering::SeqCst);
@@ -1794,6 +2220,12 @@ async fn copy_sequential(
             let was_paused = paused.fetch_xor(true, Ordering::SeqCst);
             if !was_paused {
                 pb.set_message("PAUSED - Press space to resume");
+                // A pause is often followed by a kill, so get everything journaled onto
+                // disk now and a later --resume has nothing to redo
+                if let Some(journal) = journal.as_ref() {
+                    let _ = guard.file_mut().sync_data();
+                    let _ = journal.sync();
+                }
             } else {
                 pb.set_message("");
             }
@@ -1812,10 +2244,8 @@ async fn copy_sequential(
 
             // Check for shutdown while paused - prompt user for confirmation
             if shutdown.load(Ordering::SeqCst) {
-                if prompt_cancel_copy(destination, &input_active) {
-                    return Err(anyhow::anyhow!(
-                        "Copy cancelled by user (partial destination file deleted)"
-                    ));
+                if prompt_cancel_copy(destination, &input_active, journal.is_some()) {
+                    return Err(copy_cancelled(journal.is_some()));
                 }
                 // User declined cancellation - reset flag and continue
                 shutdown.store(false, Ordering::SeqCst);
@@ -1845,6 +2275,11 @@ async fn copy_sequential(
             .file_mut()
             .write_all(&buffer[..bytes_read])
             .context("Failed to write to destination file")?;
+        if let Some(journal) = journal.as_mut() {
+            journal
+                .record(&buffer[..bytes_read])
+                .context("Failed to update resume journal")?;
+        }
 
         total_bytes += bytes_read as u64;
         iteration_count = iteration_count.wrapping_add(1);
@@ -1888,6 +2323,12 @@ async fn copy_sequential(
     let metadata = fs::metadata(source)?;
     fs::set_permissions(destination, metadata.permissions())?;
 
+    // The copy is whole, so the journal has nothing left to resume. One left behind
+    // would be harmless: it is checked against the file before it is trusted.
+    if let Some(journal) = journal {
+        let _ = journal.finish();
+    }
+
     // Explicitly close the destination file before verification can occur
     drop(dst_file);
 
@@ -1897,6 +2338,7 @@ async fn copy_sequential(
 
     Ok(CopyResult {
         bytes_copied: total_bytes,
+        resumed_bytes,
         source_hash,
         copy_duration,
     })
@@ -1937,11 +2379,12 @@ async fn copy_parallel(
     term_width_rx: &watch::Receiver<u16>,
     buffer_size: usize,
     use_buffer_pool: bool,
+    resume: Option<Resume>,
 ) -> Result<CopyResult> {
     let start_time = Instant::now();
 
     // Open source file
-    let src_file = File::open(source).context("Failed to open source file")?;
+    let mut src_file = File::open(source).context("Failed to open source file")?;
 
     // Get source file size for preallocation
     let file_size = src_file
@@ -1952,14 +2395,28 @@ async fn copy_parallel(
     // Hint sequential read pattern for source file
     hint_sequential_io(&src_file);
 
-    // Create destination file
-    let dst_file = File::create(destination).context("Failed to create destination file")?;
+    // Create destination file (or reopen it past the verified prefix when resuming)
+    let resumed_bytes = resume.as_ref().map_or(0, |resume| resume.offset);
+    let dst_file = open_destination(
+        &mut src_file,
+        destination,
+        resume.as_ref().map(|_| resumed_bytes),
+    )?;
 
     // Preallocate space to reduce fragmentation and improve write performance
     try_preallocate(&dst_file, file_size);
 
+    let (mut hasher, mut journal) = match resume {
+        Some(resume) => (resume.hasher, Some(resume.journal)),
+        None => (blake3::Hasher::new(), None),
+    };
+
     // Use RAII guard to ensure partial file cleanup on any error path
-    let mut guard = PartialFileGuard::new(destination, dst_file);
+    let mut guard = if journal.is_some() {
+        PartialFileGuard::resumable(destination, dst_file)
+    } else {
+        PartialFileGuard::new(destination, dst_file)
+    };
 
     // Bounded channel for reader → writer communication
     let (data_tx, data_rx) = std::sync::mpsc::sync_channel::<CopyMessage>(PARALLEL_CHANNEL_DEPTH);
@@ -2025,8 +2482,7 @@ async fn copy_parallel(
     });
 
     // Writer runs in the main async task
-    let mut hasher = blake3::Hasher::new();
-    let mut total_bytes = 0_u64;
+    let mut total_bytes = resumed_bytes;
 
     // Track last terminal width for resize detection
     let mut last_width = *term_width_rx.borrow();
@@ -2043,6 +2499,12 @@ async fn copy_parallel(
             let was_paused = paused.fetch_xor(true, Ordering::SeqCst);
             if !was_paused {
                 pb.set_message("PAUSED - Press space to resume");
+                // A pause is often followed by a kill, so get everything journaled onto
+                // disk now and a later --resume has nothing to redo
+                if let Some(journal) = journal.as_ref() {
+                    let _ = guard.file_mut().sync_data();
+                    let _ = journal.sync();
+                }
             } else {
                 pb.set_message("");
             }
@@ -2050,15 +2512,13 @@ async fn copy_parallel(
 
         // Check for shutdown - prompt user for confirmation
         if shutdown.load(Ordering::SeqCst) {
-            if prompt_cancel_copy(destination, &input_active) {
+            if prompt_cancel_copy(destination, &input_active, journal.is_some()) {
                 // User confirmed cancellation
                 // Per issue #86: unpause reader so it can exit, then drop receiver
                 paused.store(false, Ordering::SeqCst);
                 drop(data_rx);
                 // Don't block waiting for reader - it will exit on its own
-                return Err(anyhow::anyhow!(
-                    "Copy cancelled by user (partial destination file deleted)"
-                ));
+                return Err(copy_cancelled(journal.is_some()));
             }
             // User declined cancellation - reset flag and continue
             shutdown.store(false, Ordering::SeqCst);
@@ -2077,12 +2537,10 @@ async fn copy_parallel(
 
             // Check for shutdown while paused
             if shutdown.load(Ordering::SeqCst) {
-                if prompt_cancel_copy(destination, &input_active) {
+                if prompt_cancel_copy(destination, &input_active, journal.is_some()) {
                     paused.store(false, Ordering::SeqCst);
                     drop(data_rx);
-                    return Err(anyhow::anyhow!(
-                        "Copy cancelled by user (partial destination file deleted)"
-                    ));
+                    return Err(copy_cancelled(journal.is_some()));
                 }
                 shutdown.store(false, Ordering::SeqCst);
             }
@@ -2107,6 +2565,11 @@ async fn copy_parallel(
                     .file_mut()
                     .write_all(&buffer[..bytes_read])
                     .context("Failed to write to destination file")?;
+                if let Some(journal) = journal.as_mut() {
+                    journal
+                        .record(&buffer[..bytes_read])
+                        .context("Failed to update resume journal")?;
+                }
 
                 total_bytes += bytes_read as u64;
                 iteration_count = iteration_count.wrapping_add(1);
@@ -2182,6 +2645,12 @@ async fn copy_parallel(
     let metadata = fs::metadata(source)?;
     fs::set_permissions(destination, metadata.permissions())?;
 
+    // The copy is whole, so the journal has nothing left to resume. One left behind
+    // would be harmless: it is checked against the file before it is trusted.
+    if let Some(journal) = journal {
+        let _ = journal.finish();
+    }
+
     // Explicitly close the destination file
     drop(dst_file);
 
@@ -2191,6 +2660,7 @@ async fn copy_parallel(
 
     Ok(CopyResult {
         bytes_copied: total_bytes,
+        resumed_bytes,
         source_hash,
         copy_duration,
     })
@@ -2453,7 +2923,7 @@ mod tests {
             let file = temp_dir.path().join("test.txt");
             fs::write(&file, "content").unwrap();
 
-            let result = resolve_sources(std::slice::from_ref(&file), false).unwrap();
+            let result = resolve_sources(std::slice::from_ref(&file), false, false).unwrap();
             assert_eq!(result, vec![file]);
         }
 
@@ -2465,7 +2935,7 @@ mod tests {
             fs::write(&file, "content").unwrap();
 
             // Without --literal flag, but file exists literally
-            let result = resolve_sources(std::slice::from_ref(&file), false).unwrap();
+            let result = resolve_sources(std::slice::from_ref(&file), false, false).unwrap();
             assert_eq!(result, vec![file]);
         }
 
@@ -2478,7 +2948,7 @@ mod tests {
             fs::write(&file2, "content2").unwrap();
 
             let pattern = temp_dir.path().join("*.txt");
-            let result = resolve_sources(&[pattern], false).unwrap();
+            let result = resolve_sources(&[pattern], false, false).unwrap();
 
             assert_eq!(result.len(), 2);
             assert!(result.contains(&file1));
@@ -2493,7 +2963,7 @@ mod tests {
 
             // Pattern that would match, but --literal is set
             let pattern = temp_dir.path().join("*.txt");
-            let result = resolve_sources(&[pattern], true);
+            let result = resolve_sources(&[pattern], true, false);
 
             // Should fail because "*.txt" doesn't exist as a literal file
             assert!(result.is_err());
@@ -2506,7 +2976,7 @@ mod tests {
             let temp_dir = TempDir::new().unwrap();
             let nonexistent = temp_dir.path().join("does_not_exist.txt");
 
-            let result = resolve_sources(&[nonexistent], false);
+            let result = resolve_sources(&[nonexistent], false, false);
             assert!(result.is_err());
             let err = result.unwrap_err().to_string();
             assert!(err.contains("does not exist"));
@@ -2518,12 +2988,26 @@ mod tests {
             let dir = temp_dir.path().join("subdir");
             fs::create_dir(&dir).unwrap();
 
-            let result = resolve_sources(&[dir], false);
+            let result = resolve_sources(&[dir], false, false);
             assert!(result.is_err());
             let err = result.unwrap_err().to_string();
             assert!(err.contains("not a file"));
         }
 
+        #[test]
+        fn directory_path_is_resolved_when_recursive() {
+            let temp_dir = TempDir::new().unwrap();
+            let dir = temp_dir.path().join("subdir");
+            fs::create_dir(&dir).unwrap();
+            let file = temp_dir.path().join("file.txt");
+            fs::write(&file, "content").unwrap();
+
+            let pattern = temp_dir.path().join("*");
+            let mut result = resolve_sources(&[pattern], false, true).unwrap();
+            result.sort();
+            assert_eq!(result, vec![file, dir]);
+        }
+
         #[test]
         fn glob_pattern_with_no_matches_returns_error() {
             let temp_dir = TempDir::new().unwrap();
@@ -2532,7 +3016,7 @@ mod tests {
             fs::write(&file, "content").unwrap();
 
             let pattern = temp_dir.path().join("*.xyz");
-            let result = resolve_sources(&[pattern], false);
+            let result = resolve_sources(&[pattern], false, false);
 
             assert!(result.is_err());
             let err = result.unwrap_err().to_string();
@@ -2547,7 +3031,7 @@ mod tests {
             fs::write(&file1, "content1").unwrap();
             fs::write(&file2, "content2").unwrap();
 
-            let result = resolve_sources(&[file1.clone(), file2.clone()], false).unwrap();
+            let result = resolve_sources(&[file1.clone(), file2.clone()], false, false).unwrap();
             assert_eq!(result, vec![file1, file2]);
         }
 
@@ -2565,8 +3049,185 @@ mod tests {
             fs::write(&bracket_file, "brackets").unwrap();
 
             // When [abc].txt exists, it should be used literally, NOT expanded to a.txt, b.txt
-            let result = resolve_sources(std::slice::from_ref(&bracket_file), false).unwrap();
+            let result =
+                resolve_sources(std::slice::from_ref(&bracket_file), false, false).unwrap();
             assert_eq!(result, vec![bracket_file]);
         }
     }
+
+    mod preserve_metadata_tests {
+        use super::*;
+
+        fn preserves(flags: &[&str]) -> bool {
+            let mut argv = vec!["prcp"];
+            argv.extend_from_slice(flags);
+            argv.extend(["src", "dst"]);
+            Args::parse_from(argv).preserve_metadata()
+        }
+
+        #[test]
+        fn plain_copy_keeps_only_permissions() {
+            assert!(!preserves(&[]));
+            assert!(!preserves(&["--rm"]));
+        }
+
+        #[test]
+        fn recursive_preserve_and_xattrs_keep_times() {
+            assert!(preserves(&["-R"]));
+            assert!(preserves(&["--preserve"]));
+            assert!(preserves(&["-p"]));
+            assert!(preserves(&["--xattrs"]));
+        }
+    }
+
+    mod resume_tests {
+        use super::*;
+        use tempfile::TempDir;
+
+        fn chunk() -> usize {
+            usize::try_from(journal::CHUNK_SIZE).unwrap()
+        }
+
+        /// Three whole chunks and a bit, each chunk different from the others.
+        fn source_data() -> Vec<u8> {
+            (0..3 * chunk() + 100)
+                .map(|i| u8::try_from((i / chunk() * 7 + i) % 251).unwrap())
+                .collect()
+        }
+
+        /// Leave what a copy killed after `chunks` whole chunks leaves: a
+        /// partial destination a little past them, and a journal of their hashes.
+        fn interrupt(source: &Path, destination: &Path, data: &[u8], chunks: usize) {
+            fs::write(destination, &data[..chunks * chunk() + 10]).unwrap();
+            let hashes: Vec<_> = data
+                .chunks(chunk())
+                .take(chunks)
+                .map(blake3::hash)
+                .collect();
+            let stamp = journal::SourceStamp::of(&fs::metadata(source).unwrap());
+            journal::Journal::create(destination, stamp, &hashes).unwrap();
+        }
+
+        async fn copy(source: &Path, destination: &Path, resumable: bool) -> CopyResult {
+            let flag = || Arc::new(AtomicBool::new(false));
+            let (_key_tx, mut key_rx) = mpsc::unbounded_channel();
+            let (_width_tx, width_rx) = watch::channel(80);
+            copy_with_progress(
+                source,
+                destination,
+                &ProgressBar::hidden(),
+                "test",
+                flag(),
+                flag(),
+                flag(),
+                &mut key_rx,
+                &width_rx,
+                16 * 1024,
+                true,
+                true,
+                resumable,
+            )
+            .await
+            .unwrap()
+        }
+
+        fn assert_complete(destination: &Path, data: &[u8], result: &CopyResult) {
+            assert_eq!(fs::read(destination).unwrap(), data);
+            assert_eq!(result.bytes_copied, data.len() as u64);
+            assert_eq!(
+                result.source_hash,
+                Blake3Hash::from(blake3::hash(data)),
+                "the running hash must cover the resumed prefix too"
+            );
+            assert!(!journal::path_for(destination).exists());
+        }
+
+        #[tokio::test]
+        async fn interrupted_copy_continues_after_matching_chunks() {
+            let temp_dir = TempDir::new().unwrap();
+            let source = temp_dir.path().join("source.bin");
+            let destination = temp_dir.path().join("destination.bin");
+            let data = source_data();
+            fs::write(&source, &data).unwrap();
+            interrupt(&source, &destination, &data, 2);
+
+            let result = copy(&source, &destination, true).await;
+            assert_eq!(result.resumed_bytes, 2 * journal::CHUNK_SIZE);
+            assert_complete(&destination, &data, &result);
+        }
+
+        #[tokio::test]
+        async fn mismatched_chunk_is_copied_again_with_everything_after_it() {
+            let temp_dir = TempDir::new().unwrap();
+            let source = temp_dir.path().join("source.bin");
+            let destination = temp_dir.path().join("destination.bin");
+            let data = source_data();
+            fs::write(&source, &data).unwrap();
+            interrupt(&source, &destination, &data, 2);
+
+            // Damage the second chunk of the partial copy
+            let mut partial = fs::read(&destination).unwrap();
+            partial[chunk() + 5] ^= 0xff;
+            fs::write(&destination, &partial).unwrap();
+
+            let result = copy(&source, &destination, true).await;
+            assert_eq!(result.resumed_bytes, journal::CHUNK_SIZE);
+            assert_complete(&destination, &data, &result);
+        }
+
+        #[tokio::test]
+        async fn changed_source_starts_over() {
+            let temp_dir = TempDir::new().unwrap();
+            let source = temp_dir.path().join("source.bin");
+            let destination = temp_dir.path().join("destination.bin");
+            let data = source_data();
+            fs::write(&source, &data).unwrap();
+
+            // Touched since the journal was written
+            interrupt(&source, &destination, &data, 2);
+            File::options()
+                .write(true)
+                .open(&source)
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() - Duration::from_secs(3600))
+                .unwrap();
+            let result = copy(&source, &destination, true).await;
+            assert_eq!(result.resumed_bytes, 0);
+            assert_complete(&destination, &data, &result);
+
+            // Grown since the journal was written
+            interrupt(&source, &destination, &data, 2);
+            let mut grown = data.clone();
+            grown.extend_from_slice(b"appended");
+            fs::OpenOptions::new()
+                .append(true)
+                .open(&source)
+                .unwrap()
+                .write_all(b"appended")
+                .unwrap();
+            let result = copy(&source, &destination, true).await;
+            assert_eq!(result.resumed_bytes, 0);
+            assert_complete(&destination, &grown, &result);
+        }
+
+        #[tokio::test]
+        async fn journal_is_removed_once_the_copy_completes() {
+            let temp_dir = TempDir::new().unwrap();
+            let source = temp_dir.path().join("source.bin");
+            let destination = temp_dir.path().join("destination.bin");
+            let data = source_data();
+            fs::write(&source, &data).unwrap();
+
+            // A fresh resumable copy writes a journal as it goes and drops it at the end
+            let result = copy(&source, &destination, true).await;
+            assert_eq!(result.resumed_bytes, 0);
+            assert_complete(&destination, &data, &result);
+
+            // A copy without --resume discards one left by an earlier run
+            interrupt(&source, &destination, &data, 1);
+            let result = copy(&source, &destination, false).await;
+            assert_eq!(result.resumed_bytes, 0);
+            assert_complete(&destination, &data, &result);
+        }
+    }
 }
@@ -0,0 +1,324 @@
+//! Carrying metadata from a source onto its copy: permission bits,
+//! timestamps and, on request, extended attributes.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Whether extended attributes can be copied on this platform.
+pub const XATTRS_SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+/// Copy the permission bits and access/modification times of `source` onto
+/// `destination`, plus its extended attributes when `xattrs` is set.
+///
+/// Symlinks are handled as links: their own timestamps are copied and their
+/// targets are left alone. Attributes are set before the mode, which may make
+/// the destination read-only and refuse them.
+///
+/// # Errors
+///
+/// Returns an error if the source metadata cannot be read or any part of it
+/// cannot be applied to the destination.
+pub fn preserve(source: &Path, destination: &Path, xattrs: bool) -> Result<()> {
+    let metadata = fs::symlink_metadata(source)
+        .with_context(|| format!("Failed to read metadata for '{}'", source.display()))?;
+    let is_symlink = metadata.file_type().is_symlink();
+
+    if xattrs && !is_symlink {
+        copy_xattrs(source, destination).context("Failed to copy extended attributes")?;
+    }
+    if !is_symlink {
+        fs::set_permissions(destination, metadata.permissions())
+            .context("Failed to set permissions")?;
+    }
+    set_times(destination, &metadata).context("Failed to set timestamps")?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn c_path(path: &Path) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a NUL byte"))
+}
+
+#[cfg(unix)]
+fn set_times(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = c_path(path)?;
+    let times = [
+        timespec(metadata.atime(), metadata.atime_nsec()),
+        timespec(metadata.mtime(), metadata.mtime_nsec()),
+    ];
+    // SAFETY: `path` is a NUL-terminated string that outlives the call, and
+    // `times` is the two-element array utimensat reads. AT_SYMLINK_NOFOLLOW makes
+    // a symlink get its own times instead of its target's.
+    let result = unsafe {
+        libc::utimensat(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            times.as_ptr(),
+            libc::AT_SYMLINK_NOFOLLOW,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(unix)]
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "time_t and the nanosecond field are narrower than i64 only on 32-bit targets"
+)]
+fn timespec(seconds: i64, nanoseconds: i64) -> libc::timespec {
+    // SAFETY: timespec is plain integers, so all zeroes is a valid value. Starting
+    // from zero also fills the padding fields some targets add.
+    let mut time: libc::timespec = unsafe { std::mem::zeroed() };
+    time.tv_sec = seconds as libc::time_t;
+    time.tv_nsec = nanoseconds as _;
+    time
+}
+
+#[cfg(not(unix))]
+fn set_times(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    let times = fs::FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    fs::File::options().write(true).open(path)?.set_times(times)
+}
+
+/// Copy every extended attribute of `source` onto `destination`.
+///
+/// A source on a filesystem without extended attributes has none to copy.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn copy_xattrs(source: &Path, destination: &Path) -> Result<()> {
+    let source = c_path(source)?;
+    let destination = c_path(destination)?;
+
+    let names = match read_sized(|buffer| xattr::list(&source, buffer)) {
+        Ok(names) => names,
+        Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(()),
+        Err(e) => return Err(e).context("Failed to list extended attributes"),
+    };
+
+    for name in names
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+    {
+        let display = String::from_utf8_lossy(name).into_owned();
+        let name = std::ffi::CString::new(name)?;
+        let value = read_sized(|buffer| xattr::get(&source, &name, buffer))
+            .with_context(|| format!("Failed to read extended attribute {display}"))?;
+        xattr::set(&destination, &name, &value)
+            .with_context(|| format!("Failed to set extended attribute {display}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn copy_xattrs(_source: &Path, _destination: &Path) -> Result<()> {
+    anyhow::bail!("Extended attributes are only copied on Linux and macOS")
+}
+
+/// Run a list/get style call twice: once with an empty buffer to learn the
+/// size, then with a buffer that large. An attribute that grows in between
+/// makes the second call fail with ERANGE, so that case starts over.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn read_sized(mut call: impl FnMut(&mut [u8]) -> isize) -> io::Result<Vec<u8>> {
+    loop {
+        let size = usize::try_from(call(&mut [])).map_err(|_| io::Error::last_os_error())?;
+        let mut buffer = vec![0; size];
+        match usize::try_from(call(&mut buffer)) {
+            Ok(length) => {
+                buffer.truncate(length);
+                return Ok(buffer);
+            }
+            Err(_) => {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::ERANGE) {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// Thin wrappers over the platform xattr calls. None of them follow symlinks.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod xattr {
+    use std::ffi::CStr;
+    use std::io;
+
+    /// A null pointer for an empty buffer, which asks the call for a size only.
+    fn buffer_ptr(buffer: &mut [u8]) -> *mut libc::c_char {
+        if buffer.is_empty() {
+            std::ptr::null_mut()
+        } else {
+            buffer.as_mut_ptr().cast()
+        }
+    }
+
+    pub fn list(path: &CStr, buffer: &mut [u8]) -> isize {
+        let size = buffer.len();
+        let pointer = buffer_ptr(buffer);
+        // SAFETY: `path` is NUL-terminated, and `pointer` is either null with a
+        // size of zero or points at `size` writable bytes.
+        unsafe {
+            #[cfg(target_os = "linux")]
+            let length = libc::llistxattr(path.as_ptr(), pointer, size);
+            #[cfg(target_os = "macos")]
+            let length = libc::listxattr(path.as_ptr(), pointer, size, libc::XATTR_NOFOLLOW);
+            length
+        }
+    }
+
+    pub fn get(path: &CStr, name: &CStr, buffer: &mut [u8]) -> isize {
+        let size = buffer.len();
+        let pointer = buffer_ptr(buffer).cast();
+        // SAFETY: `path` and `name` are NUL-terminated, and `pointer` is either
+        // null with a size of zero or points at `size` writable bytes.
+        unsafe {
+            #[cfg(target_os = "linux")]
+            let length = libc::lgetxattr(path.as_ptr(), name.as_ptr(), pointer, size);
+            #[cfg(target_os = "macos")]
+            let length = libc::getxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                pointer,
+                size,
+                0,
+                libc::XATTR_NOFOLLOW,
+            );
+            length
+        }
+    }
+
+    pub fn set(path: &CStr, name: &CStr, value: &[u8]) -> io::Result<()> {
+        // SAFETY: `path` and `name` are NUL-terminated, and `value` is read for
+        // exactly its own length.
+        let result = unsafe {
+            #[cfg(target_os = "linux")]
+            let result = libc::lsetxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            );
+            #[cfg(target_os = "macos")]
+            let result = libc::setxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+                libc::XATTR_NOFOLLOW,
+            );
+            result
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    reason = "tests use unwrap for brevity and clear failure messages"
+)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn an_hour_ago() -> SystemTime {
+        SystemTime::now() - Duration::from_secs(3600)
+    }
+
+    #[test]
+    fn file_times_and_mode_are_copied() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let destination = temp_dir.path().join("destination.txt");
+        fs::write(&source, "content").unwrap();
+        fs::write(&destination, "content").unwrap();
+
+        let modified = an_hour_ago();
+        fs::File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let mut permissions = fs::metadata(&source).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&source, permissions).unwrap();
+
+        preserve(&source, &destination, false).unwrap();
+
+        let copied = fs::metadata(&destination).unwrap();
+        assert_eq!(copied.modified().unwrap(), modified);
+        assert!(copied.permissions().readonly());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_target_is_left_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target.txt");
+        fs::write(&target, "content").unwrap();
+        let modified = an_hour_ago();
+        fs::File::options()
+            .write(true)
+            .open(&target)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let source = temp_dir.path().join("source-link");
+        let destination = temp_dir.path().join("destination-link");
+        std::os::unix::fs::symlink(&target, &source).unwrap();
+        std::os::unix::fs::symlink(&target, &destination).unwrap();
+
+        preserve(&source, &destination, false).unwrap();
+
+        // The link took the source link's own time, and the target kept its own
+        let source_time = fs::symlink_metadata(&source).unwrap().modified().unwrap();
+        let copied = fs::symlink_metadata(&destination).unwrap();
+        assert_eq!(copied.modified().unwrap(), source_time);
+        assert_eq!(fs::metadata(&target).unwrap().modified().unwrap(), modified);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn extended_attributes_are_copied() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let destination = temp_dir.path().join("destination.txt");
+        fs::write(&source, "content").unwrap();
+        fs::write(&destination, "content").unwrap();
+
+        let name = c"user.prcp.test";
+        if xattr::set(&c_path(&source).unwrap(), name, b"value").is_err() {
+            // The temporary directory's filesystem has no user attributes
+            return;
+        }
+
+        preserve(&source, &destination, true).unwrap();
+
+        let copied =
+            read_sized(|buffer| xattr::get(&c_path(&destination).unwrap(), name, buffer)).unwrap();
+        assert_eq!(copied, b"value");
+    }
+}
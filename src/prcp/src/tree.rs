@@ -0,0 +1,338 @@
+//! Planning a copy before any data moves.
+//!
+//! Every source is expanded into the files, directories and symlinks it will
+//! produce, each paired with its destination. Walking whole trees up front is
+//! what lets the batch progress bar show the real size of a recursive copy
+//! from the first byte.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One thing to copy and where it lands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+/// Everything a copy will create, in the order it is created.
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// Regular files, copied and verified one at a time.
+    pub files: Vec<Entry>,
+    /// Directories, each listed before anything inside it.
+    pub directories: Vec<Entry>,
+    /// Symbolic links, recreated as links rather than followed.
+    pub symlinks: Vec<Entry>,
+    /// Sockets, FIFOs and device nodes found while walking, which are not copied.
+    pub special: Vec<PathBuf>,
+}
+
+/// Plan copying `sources` to `destination`.
+///
+/// Sources are used as resolved on the command line, so a symlink named there
+/// is followed. With more than one source, or when `destination` is an existing
+/// directory, each source lands inside it under its own name; otherwise the
+/// single source becomes `destination`, like `cp -R src new-name`.
+///
+/// Directories are walked depth-first with entries sorted by name. Inside a
+/// tree, symlinks are never followed.
+///
+/// # Errors
+///
+/// Returns an error if a source cannot be read, if a directory would be copied
+/// into itself, or if a directory would replace an existing non-directory.
+pub fn plan(sources: &[PathBuf], destination: &Path) -> Result<Plan> {
+    let into_directory = sources.len() > 1 || destination.is_dir();
+    let mut plan = Plan::default();
+
+    for source in sources {
+        let target = match source.file_name() {
+            Some(name) if into_directory => destination.join(name),
+            // `.` and `..` have no name, so their contents merge into the destination
+            _ => destination.to_path_buf(),
+        };
+
+        let metadata = fs::metadata(source)
+            .with_context(|| format!("Failed to read metadata for '{}'", source.display()))?;
+        if metadata.is_dir() {
+            if target.exists() && !target.is_dir() {
+                anyhow::bail!(
+                    "Cannot overwrite non-directory '{}' with directory '{}'",
+                    target.display(),
+                    source.display()
+                );
+            }
+            if absolute(&target).starts_with(absolute(source)) {
+                anyhow::bail!(
+                    "Cannot copy directory '{}' into itself ('{}')",
+                    source.display(),
+                    target.display()
+                );
+            }
+            walk(source, &target, &mut plan)?;
+        } else {
+            plan.files.push(Entry {
+                source: source.clone(),
+                destination: target,
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+fn walk(directory: &Path, target: &Path, plan: &mut Plan) -> Result<()> {
+    plan.directories.push(Entry {
+        source: directory.to_path_buf(),
+        destination: target.to_path_buf(),
+    });
+
+    let mut entries = fs::read_dir(directory)
+        .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+        .with_context(|| format!("Failed to read directory '{}'", directory.display()))?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+
+    for entry in entries {
+        let source = entry.path();
+        let destination = target.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .with_context(|| format!("Failed to read metadata for '{}'", source.display()))?;
+
+        if file_type.is_dir() {
+            walk(&source, &destination, plan)?;
+        } else if file_type.is_file() {
+            plan.files.push(Entry {
+                source,
+                destination,
+            });
+        } else if file_type.is_symlink() {
+            plan.symlinks.push(Entry {
+                source,
+                destination,
+            });
+        } else {
+            plan.special.push(source);
+        }
+    }
+
+    Ok(())
+}
+
+/// `path` made absolute with symlinks resolved, for as much of it as exists.
+///
+/// The destination of a copy usually does not exist yet, so the deepest
+/// existing ancestor is canonicalized and the rest appended unchanged.
+fn absolute(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |joined, part| joined.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Create `destination` as a symlink with the same target as `source`.
+///
+/// The target is copied as written, so relative links still point within the
+/// copied tree.
+///
+/// # Errors
+///
+/// Returns an error if `source` is not a symlink or `destination` cannot be created.
+#[cfg(unix)]
+pub fn recreate_symlink(source: &Path, destination: &Path) -> Result<()> {
+    let target = fs::read_link(source)
+        .with_context(|| format!("Failed to read symlink '{}'", source.display()))?;
+    std::os::unix::fs::symlink(&target, destination)
+        .with_context(|| format!("Failed to create symlink '{}'", destination.display()))
+}
+
+/// Symlinks are only recreated on Unix.
+///
+/// # Errors
+///
+/// Always returns an error.
+#[cfg(not(unix))]
+pub fn recreate_symlink(source: &Path, _destination: &Path) -> Result<()> {
+    anyhow::bail!(
+        "Cannot recreate symlink '{}': symlinks are only copied on Unix",
+        source.display()
+    )
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    reason = "tests use unwrap for brevity and clear failure messages"
+)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(source: &Path, destination: &Path) -> Entry {
+        Entry {
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn single_file_to_new_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let destination = temp_dir.path().join("b.txt");
+
+        let plan = plan(std::slice::from_ref(&file), &destination).unwrap();
+        assert_eq!(plan.files, vec![entry(&file, &destination)]);
+        assert!(plan.directories.is_empty());
+    }
+
+    #[test]
+    fn several_files_land_inside_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        // The destination does not exist yet, but two sources need a directory
+        let destination = temp_dir.path().join("out");
+
+        let plan = plan(&[a.clone(), b.clone()], &destination).unwrap();
+        assert_eq!(
+            plan.files,
+            vec![
+                entry(&a, &destination.join("a.txt")),
+                entry(&b, &destination.join("b.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn tree_is_walked_in_name_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        fs::create_dir_all(source.join("sub/empty")).unwrap();
+        fs::write(source.join("z.txt"), "z").unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        fs::write(source.join("sub/inner.txt"), "inner").unwrap();
+        let destination = temp_dir.path().join("dst");
+
+        let plan = plan(std::slice::from_ref(&source), &destination).unwrap();
+        assert_eq!(
+            plan.files,
+            vec![
+                entry(&source.join("a.txt"), &destination.join("a.txt")),
+                entry(
+                    &source.join("sub/inner.txt"),
+                    &destination.join("sub/inner.txt")
+                ),
+                entry(&source.join("z.txt"), &destination.join("z.txt")),
+            ]
+        );
+        assert_eq!(
+            plan.directories,
+            vec![
+                entry(&source, &destination),
+                entry(&source.join("sub"), &destination.join("sub")),
+                entry(&source.join("sub/empty"), &destination.join("sub/empty")),
+            ]
+        );
+    }
+
+    #[test]
+    fn tree_goes_inside_existing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        fs::create_dir(&source).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        let destination = temp_dir.path().join("dst");
+        fs::create_dir(&destination).unwrap();
+
+        let plan = plan(std::slice::from_ref(&source), &destination).unwrap();
+        assert_eq!(
+            plan.files,
+            vec![entry(&source.join("a.txt"), &destination.join("src/a.txt"))]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_recorded_not_followed() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir(&source).unwrap();
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("big.bin"), "data").unwrap();
+        std::os::unix::fs::symlink(&outside, source.join("link")).unwrap();
+        let destination = temp_dir.path().join("dst");
+
+        let plan = plan(std::slice::from_ref(&source), &destination).unwrap();
+        assert!(plan.files.is_empty());
+        assert_eq!(
+            plan.symlinks,
+            vec![entry(&source.join("link"), &destination.join("link"))]
+        );
+    }
+
+    #[test]
+    fn copying_into_itself_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        fs::create_dir(&source).unwrap();
+
+        let err = plan(std::slice::from_ref(&source), &source.join("nested/copy"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("into itself"), "{err}");
+    }
+
+    #[test]
+    fn directory_over_file_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        fs::create_dir(&source).unwrap();
+        let destination = temp_dir.path().join("file.txt");
+        fs::write(&destination, "x").unwrap();
+
+        let err = plan(std::slice::from_ref(&source), &destination)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("non-directory"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recreated_symlink_keeps_relative_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink("../elsewhere/file", &link).unwrap();
+        let copy = temp_dir.path().join("copy");
+
+        recreate_symlink(&link, &copy).unwrap();
+        assert_eq!(
+            fs::read_link(&copy).unwrap(),
+            PathBuf::from("../elsewhere/file")
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Access control: basic-auth credentials and per-run tokens, and the check
+//! every request passes before it is served.
+
+use std::path::Path;
+
+/// Who may use the server: every request is checked before it is served.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Auth {
+    /// Anyone who can reach the port (the default).
+    #[default]
+    Open,
+    /// HTTP basic authentication with one user name and password (`--auth`).
+    Basic {
+        /// The user name.
+        user: String,
+        /// The password.
+        password: String,
+    },
+    /// A secret token (`--token`), printed in the banner's URLs. A request
+    /// carries it as a `token` query parameter, an `Authorization: Bearer`
+    /// header, or the cookie set when a browser first opens a token URL.
+    Token(String),
+}
+
+/// Parses a `--auth` value, `USER:PASSWORD`, into [`Auth::Basic`].
+///
+/// The user name ends at the first `:`, so a password may itself contain
+/// colons; neither part may be empty.
+///
+/// # Errors
+///
+/// Returns a message for clap to show when the value has no `:` or either
+/// side of it is empty.
+pub fn parse_credentials(credentials: &str) -> Result<Auth, String> {
+    match credentials.split_once(':') {
+        Some((user, password)) if !user.is_empty() && !password.is_empty() => Ok(Auth::Basic {
+            user: user.to_string(),
+            password: password.to_string(),
+        }),
+        _ => Err("expected USER:PASSWORD, with neither part empty".to_string()),
+    }
+}
+
+/// Reads an `--auth-file`: [`parse_credentials`] on its first line, so the
+/// password stays out of the process list and shell history.
+///
+/// # Errors
+///
+/// Returns a message naming the file when it cannot be read or its first
+/// line is not valid credentials.
+pub fn read_credentials(path: &Path) -> Result<Auth, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    parse_credentials(text.lines().next().unwrap_or_default())
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// A fresh random access token for [`Auth::Token`]: 32 letters and digits
+/// (about 190 bits) from the thread-local CSPRNG.
+#[must_use]
+pub fn random_token() -> String {
+    use rand::distr::{Alphanumeric, SampleString};
+
+    Alphanumeric.sample_string(&mut rand::rng(), 32)
+}
+
+/// The outcome of checking one request against an [`Auth`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// Serve the request.
+    Allowed,
+    /// The request carried a valid token in its URL and looks like a browser
+    /// navigation: redirect to this URL (the same one, minus the token) and
+    /// set the token cookie, so the secret leaves the address bar and
+    /// history and the page's own links and assets are let in by the cookie.
+    Redirect(String),
+    /// Refuse the request with `401`.
+    Denied,
+}
+
+/// The query parameter that carries an access token.
+pub(crate) const TOKEN_PARAM: &str = "token";
+
+/// Checks one request's credentials against `auth`.
+///
+/// `url` is the raw request target, `authorization` and `cookie` the headers
+/// of those names, and `navigation` whether the request is a browser `GET` for
+/// a page (see [`Admission::Redirect`]). `cookie_name` is the token cookie's
+/// name. Secrets are compared in constant time.
+#[must_use]
+pub fn admit(
+    auth: &Auth,
+    url: &str,
+    authorization: Option<&str>,
+    cookie: Option<&str>,
+    cookie_name: &str,
+    navigation: bool,
+) -> Admission {
+    let allowed = |ok: bool| {
+        if ok {
+            Admission::Allowed
+        } else {
+            Admission::Denied
+        }
+    };
+    match auth {
+        Auth::Open => Admission::Allowed,
+        Auth::Basic { user, password } => {
+            let expected = format!("{user}:{password}");
+            let given = authorization
+                .and_then(|value| scheme_value(value, "Basic"))
+                .and_then(|encoded| {
+                    use base64::Engine as _;
+
+                    base64::engine::general_purpose::STANDARD
+                        .decode(encoded)
+                        .ok()
+                });
+            allowed(given.is_some_and(|given| same_secret(&given, expected.as_bytes())))
+        }
+        Auth::Token(token) => {
+            let matches = |given: &str| same_secret(given.as_bytes(), token.as_bytes());
+            let by_header = authorization
+                .and_then(|value| scheme_value(value, "Bearer"))
+                .is_some_and(matches);
+            let by_cookie = cookie.is_some_and(|cookie| {
+                cookie
+                    .split(';')
+                    .filter_map(|pair| pair.trim().split_once('='))
+                    .any(|(name, value)| name == cookie_name && matches(value))
+            });
+            if by_header || by_cookie {
+                return Admission::Allowed;
+            }
+            let (path, query) = url.split_once('?').unwrap_or((url, ""));
+            let mut by_query = false;
+            let rest: Vec<&str> = query
+                .split('&')
+                .filter(|pair| match pair.split_once('=') {
+                    Some((TOKEN_PARAM, value)) => {
+                        by_query |= matches(value);
+                        false
+                    }
+                    _ => !pair.is_empty(),
+                })
+                .collect();
+            match (by_query, navigation) {
+                (false, _) => Admission::Denied,
+                (true, false) => Admission::Allowed,
+                (true, true) if rest.is_empty() => Admission::Redirect(path.to_string()),
+                (true, true) => Admission::Redirect(format!("{path}?{}", rest.join("&"))),
+            }
+        }
+    }
+}
+
+/// The credentials of an `Authorization` header value using `scheme`
+/// (matched case-insensitively, as RFC 9110 asks).
+fn scheme_value<'a>(value: &'a str, scheme: &str) -> Option<&'a str> {
+    let (given, credentials) = value.trim().split_once(' ')?;
+    given
+        .eq_ignore_ascii_case(scheme)
+        .then_some(credentials.trim())
+}
+
+/// Compares two secrets without stopping at the first differing byte, so the
+/// time taken does not reveal how much of a guess was right.
+fn same_secret(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod auth_tests {
+    use super::{admit, parse_credentials, random_token, read_credentials, Admission, Auth};
+
+    const COOKIE: &str = "sirn-token-8080";
+
+    fn token() -> Auth {
+        Auth::Token("s3cret".to_string())
+    }
+
+    fn basic() -> Auth {
+        parse_credentials("me:pa:ss").expect("valid credentials")
+    }
+
+    #[test]
+    fn credentials_split_at_the_first_colon() {
+        assert_eq!(
+            basic(),
+            Auth::Basic {
+                user: "me".to_string(),
+                password: "pa:ss".to_string()
+            }
+        );
+        assert!(parse_credentials("nopassword").is_err());
+        assert!(parse_credentials(":pass").is_err());
+        assert!(parse_credentials("user:").is_err());
+    }
+
+    #[test]
+    fn credentials_file_is_read_from_its_first_line() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("auth");
+        std::fs::write(&path, "me:pa:ss\r\nignored\n").expect("write auth file");
+        assert_eq!(read_credentials(&path), Ok(basic()));
+
+        std::fs::write(&path, "\n").expect("write auth file");
+        let err = read_credentials(&path).expect_err("empty first line");
+        assert!(err.contains("expected USER:PASSWORD"), "got {err}");
+        assert!(read_credentials(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn random_tokens_are_long_and_distinct() {
+        let (a, b) = (random_token(), random_token());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_alphanumeric()), "got {a}");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn open_admits_everything() {
+        assert_eq!(
+            admit(&Auth::Open, "/x", None, None, COOKIE, true),
+            Admission::Allowed
+        );
+    }
+
+    #[test]
+    fn basic_checks_the_decoded_credentials() {
+        // "me:pa:ss" and "me:wrong" in base64.
+        let good = "Basic bWU6cGE6c3M=";
+        let bad = "Basic bWU6d3Jvbmc=";
+        assert_eq!(
+            admit(&basic(), "/", Some(good), None, COOKIE, false),
+            Admission::Allowed
+        );
+        assert_eq!(
+            admit(
+                &basic(),
+                "/",
+                Some("basic bWU6cGE6c3M="),
+                None,
+                COOKIE,
+                false
+            ),
+            Admission::Allowed
+        );
+        assert_eq!(
+            admit(&basic(), "/", Some(bad), None, COOKIE, false),
+            Admission::Denied
+        );
+        assert_eq!(
+            admit(&basic(), "/", None, None, COOKIE, false),
+            Admission::Denied
+        );
+    }
+
+    #[test]
+    fn token_is_accepted_from_a_header_or_this_servers_cookie() {
+        assert_eq!(
+            admit(&token(), "/", Some("Bearer s3cret"), None, COOKIE, true),
+            Admission::Allowed
+        );
+        assert_eq!(
+            admit(
+                &token(),
+                "/",
+                None,
+                Some("theme=dark; sirn-token-8080=s3cret"),
+                COOKIE,
+                true
+            ),
+            Admission::Allowed
+        );
+        // Another sirn's cookie on the same host does not count.
+        assert_eq!(
+            admit(
+                &token(),
+                "/",
+                None,
+                Some("sirn-token-9090=s3cret"),
+                COOKIE,
+                true
+            ),
+            Admission::Denied
+        );
+        assert_eq!(
+            admit(&token(), "/", Some("Bearer guess"), None, COOKIE, true),
+            Admission::Denied
+        );
+    }
+
+    #[test]
+    fn token_in_the_query_admits_clients_and_redirects_browsers() {
+        assert_eq!(
+            admit(&token(), "/a.txt?token=s3cret", None, None, COOKIE, false),
+            Admission::Allowed
+        );
+        assert_eq!(
+            admit(&token(), "/docs/?token=s3cret", None, None, COOKIE, true),
+            Admission::Redirect("/docs/".to_string())
+        );
+        assert_eq!(
+            admit(
+                &token(),
+                "/?sort=size&token=s3cret&order=desc",
+                None,
+                None,
+                COOKIE,
+                true
+            ),
+            Admission::Redirect("/?sort=size&order=desc".to_string())
+        );
+        assert_eq!(
+            admit(&token(), "/?token=s3cretx", None, None, COOKIE, true),
+            Admission::Denied
+        );
+    }
+}
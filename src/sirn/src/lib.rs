@@ -3,17 +3,42 @@
 //! This library crate holds the reusable pieces of the `sirn` binary so they can
 //! be exercised directly by unit tests. It covers content-type lookup by file
 //! extension, route building from a list of files with basename-collision
-//! detection, serve-mode selection and request serving, directory listing
-//! rendering with path confinement under a served root, and the background
-//! availability monitor that reports when served paths appear or disappear.
-
+//! detection, serve-mode selection and request serving (byte ranges,
+//! conditional requests, and on-the-fly compression), sortable directory
+//! listing rendering with path confinement under a served root, index-page and
+//! single-page-app fallback serving, the opt-in writable directory mode
+//! (uploads, directory creation, and deletes), live reload over Server-Sent
+//! Events, HTTPS identities (loaded or self-signed), basic-auth and token
+//! access control, and the background availability monitor that reports when
+//! served paths appear or disappear.
+
+use httpdate::HttpDate;
 use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::sync::Arc;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod auth;
+mod live_reload;
+mod range;
+mod tls;
+mod upload;
+
+use auth::TOKEN_PARAM;
+pub use auth::{admit, parse_credentials, random_token, read_credentials, Admission, Auth};
+use live_reload::respond_events;
+pub use live_reload::{inject_live_reload, spawn_watcher, LiveReload, LIVE_RELOAD_PATH};
+use range::ByteRanges;
+pub use range::{parse_range, RangeRequest};
+pub use tls::{TlsError, TlsIdentity};
+use upload::{is_write_method, respond_write, UPLOAD_PREFIX};
+pub use upload::{
+    parse_size, resolve_write_target, WriteConfig, WriteResolution, DEFAULT_MAX_UPLOAD,
+};
 
 /// Returns the HTTP `Content-Type` for a file, based on its extension.
 ///
@@ -129,15 +154,158 @@ fn encode_path(path: &str) -> String {
         .join("/")
 }
 
+/// One entry of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListingEntry {
+    /// The entry's file name.
+    pub name: String,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+    /// The file's size in bytes; `0` for a directory.
+    pub size: u64,
+    /// The last modification time, when the platform reports one.
+    pub modified: Option<SystemTime>,
+}
+
+/// The column a directory listing is sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// By entry name (the default).
+    #[default]
+    Name,
+    /// By file size.
+    Size,
+    /// By modification time.
+    Modified,
+}
+
+impl SortKey {
+    /// The key's name in a listing URL's `?sort=` query.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Size => "size",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// How a directory listing is ordered: a column and a direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListingSort {
+    /// The column sorted by.
+    pub key: SortKey,
+    /// Whether the order is reversed (largest, newest, or Z first).
+    pub descending: bool,
+}
+
+impl ListingSort {
+    /// Reads the order from a listing URL's query string, e.g.
+    /// `sort=size&order=desc`. Missing or unknown values keep the default:
+    /// ascending by name.
+    #[must_use]
+    pub fn from_query(query: &str) -> Self {
+        let mut sort = Self::default();
+        for pair in query.split('&') {
+            match pair.split_once('=').unwrap_or((pair, "")) {
+                ("sort", "name") => sort.key = SortKey::Name,
+                ("sort", "size") => sort.key = SortKey::Size,
+                ("sort", "modified") => sort.key = SortKey::Modified,
+                ("order", "desc") => sort.descending = true,
+                ("order", "asc") => sort.descending = false,
+                _ => {}
+            }
+        }
+        sort
+    }
+}
+
+/// Orders `entries` for display: directories first, then by `sort`, with
+/// ties broken by name so the order is always stable.
+pub fn sort_entries(entries: &mut [ListingEntry], sort: ListingSort) {
+    entries.sort_by(|a, b| {
+        let by_key = match sort.key {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+        };
+        let by_key = if sort.descending {
+            by_key.reverse()
+        } else {
+            by_key
+        };
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then(by_key)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Formats a byte count for a listing: exact below 1 KiB, otherwise one
+/// decimal place in the largest binary unit that keeps it at least 1.
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    // `u64` to `f64` can round past 2^53 bytes, far below what a listing shows
+    // to one decimal place.
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 /// Renders a directory-listing HTML page for `url_path` (the request path, e.g.
-/// `/` or `/sub/`) given `entries` as `(name, is_dir)` pairs (already sorted by
-/// the caller). Each entry name is HTML-escaped; directories are marked with a
-/// trailing `/` in both the displayed text and the link target. Entry hrefs are
-/// absolute (built from `url_path`) so they resolve correctly regardless of
-/// whether `url_path` ends in a slash. A `..` parent link is included unless
-/// `url_path` is the root (`/` or empty).
+/// `/` or `/sub/`) given `entries` (already ordered by the caller, see
+/// [`sort_entries`]). Each entry name is HTML-escaped; directories are marked
+/// with a trailing `/` in both the displayed text and the link target. Entry
+/// hrefs are absolute (built from `url_path`) so they resolve correctly
+/// regardless of whether `url_path` ends in a slash. A `..` parent link is
+/// included unless `url_path` is the root (`/` or empty).
+///
+/// Each row also shows the entry's size (see [`format_size`]) and
+/// modification time. The column headings link to the same listing sorted by
+/// that column, reversing the order when it is already the one in `sort`.
 #[must_use]
-pub fn render_directory_listing(url_path: &str, entries: &[(String, bool)]) -> String {
+pub fn render_directory_listing(
+    url_path: &str,
+    entries: &[ListingEntry],
+    sort: ListingSort,
+) -> String {
+    render_listing(url_path, entries, sort, false)
+}
+
+/// Renders the listing page of a writable directory mode (`--writable`).
+///
+/// The page is [`render_directory_listing`]'s plus, above the list, a form to
+/// upload files into this directory (with an opt-in checkbox to overwrite
+/// existing ones) and a form to create a subdirectory, and a delete button
+/// beside each entry. Every form posts back to this directory's own URL, which
+/// redirects to the refreshed listing when the change is made.
+#[must_use]
+pub fn render_writable_listing(
+    url_path: &str,
+    entries: &[ListingEntry],
+    sort: ListingSort,
+) -> String {
+    render_listing(url_path, entries, sort, true)
+}
+
+/// The listing page behind [`render_directory_listing`] and
+/// [`render_writable_listing`]; `writable` adds the write controls.
+fn render_listing(
+    url_path: &str,
+    entries: &[ListingEntry],
+    sort: ListingSort,
+    writable: bool,
+) -> String {
     use std::fmt::Write as _;
 
     // A normalized base always ends in `/`, so hrefs are `{base}{name}` and
@@ -155,31 +323,110 @@ pub fn render_directory_listing(url_path: &str, entries: &[(String, bool)]) -> S
     let _ = writeln!(page, "<head>");
     let _ = writeln!(page, "<meta charset=\"utf-8\">");
     let _ = writeln!(page, "<title>{escaped_path}</title>");
+    let _ = writeln!(
+        page,
+        "<style>td{{padding:0 1em 0 0}}td.size{{text-align:right}}</style>"
+    );
     let _ = writeln!(page, "</head>");
     let _ = writeln!(page, "<body>");
     let _ = writeln!(page, "<h1>{escaped_path}</h1>");
-    let _ = writeln!(page, "<ul>");
+    // Every form posts to the directory itself, through the same encoded href
+    // form the entry links use.
+    let action = html_escape(&encode_path(&base));
+    if writable {
+        // The overwrite checkbox precedes the file input so browsers submit it
+        // first, and the server knows the choice before any file arrives.
+        let _ = writeln!(
+            page,
+            "<form method=\"post\" action=\"{action}\" enctype=\"multipart/form-data\">\
+             <label><input type=\"checkbox\" name=\"overwrite\" value=\"1\"> overwrite existing files</label> \
+             <input type=\"file\" name=\"file\" multiple required> \
+             <button type=\"submit\">Upload</button></form>"
+        );
+        let _ = writeln!(
+            page,
+            "<form method=\"post\" action=\"{action}\">\
+             <input name=\"mkdir\" placeholder=\"folder name\" required> \
+             <button type=\"submit\">Create folder</button></form>"
+        );
+    }
+    let _ = writeln!(page, "<table>");
+
+    // Column headings link to this listing re-sorted by their column; the
+    // current column's link flips the direction and carries an arrow.
+    let _ = write!(page, "<tr>");
+    for (key, label) in [
+        (SortKey::Name, "Name"),
+        (SortKey::Size, "Size"),
+        (SortKey::Modified, "Modified"),
+    ] {
+        let current = sort.key == key;
+        let order = if current && !sort.descending {
+            "desc"
+        } else {
+            "asc"
+        };
+        let arrow = match (current, sort.descending) {
+            (false, _) => "",
+            (true, false) => " ▲",
+            (true, true) => " ▼",
+        };
+        let _ = write!(
+            page,
+            "<th><a href=\"?sort={}&amp;order={order}\">{label}</a>{arrow}</th>",
+            key.as_str()
+        );
+    }
+    let _ = writeln!(page, "</tr>");
 
     // A `..` parent link precedes the entries unless this is the root listing.
     // The href is percent-encoded so a parent path with reserved/non-ASCII bytes
     // still forms a valid URL.
     if url_path != "/" && !url_path.is_empty() {
         let parent_href = html_escape(&encode_path(&parent_of(&base)));
-        let _ = writeln!(page, "<li><a href=\"{parent_href}\">../</a></li>");
+        let _ = writeln!(
+            page,
+            "<tr><td><a href=\"{parent_href}\">../</a></td><td></td><td></td></tr>"
+        );
     }
 
-    for (name, is_dir) in entries {
-        let suffix = if *is_dir { "/" } else { "" };
+    for entry in entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
         // The visible link text stays the decoded, human-readable name; only the
         // href is percent-encoded so a name with a space or non-ASCII byte (e.g.
         // `a b.txt`, `café.txt`) yields a valid, clickable URL.
-        let text = format!("{}{suffix}", html_escape(name));
-        let href_raw = format!("{}{}{suffix}", encode_path(&base), encode_segment(name));
+        let text = format!("{}{suffix}", html_escape(&entry.name));
+        let href_raw = format!(
+            "{}{}{suffix}",
+            encode_path(&base),
+            encode_segment(&entry.name)
+        );
         let href = html_escape(&href_raw);
-        let _ = writeln!(page, "<li><a href=\"{href}\">{text}</a></li>");
+        let size = if entry.is_dir {
+            "-".to_string()
+        } else {
+            format_size(entry.size)
+        };
+        let modified = entry
+            .modified
+            .map(httpdate::fmt_http_date)
+            .unwrap_or_default();
+        let _ = write!(
+            page,
+            "<tr><td><a href=\"{href}\">{text}</a></td><td class=\"size\">{size}</td><td>{modified}</td>"
+        );
+        if writable {
+            let value = html_escape(&entry.name);
+            let _ = write!(
+                page,
+                "<td><form method=\"post\" action=\"{action}\" style=\"display:inline\">\
+                 <button type=\"submit\" name=\"delete\" value=\"{value}\">delete</button></form></td>"
+            );
+        }
+        let _ = writeln!(page, "</tr>");
     }
 
-    let _ = writeln!(page, "</ul>");
+    let _ = writeln!(page, "</table>");
     let _ = writeln!(page, "</body>");
     let _ = writeln!(page, "</html>");
     page
@@ -354,12 +601,80 @@ pub fn decide_mode(
     }
 }
 
+/// How the listener is protected, for the startup banners.
+#[derive(Debug, Clone, Copy)]
+pub struct Security<'a> {
+    /// Whether the server speaks HTTPS.
+    pub https: bool,
+    /// Who may use the server.
+    pub auth: &'a Auth,
+    /// The certificate's SHA-256 fingerprint (see
+    /// [`TlsIdentity::fingerprint`]), so a self-signed certificate can be
+    /// checked before a browser is told to trust it.
+    pub fingerprint: Option<&'a str>,
+}
+
+impl Default for Security<'_> {
+    /// Plain HTTP, open to anyone.
+    fn default() -> Self {
+        static OPEN: Auth = Auth::Open;
+        Self {
+            https: false,
+            auth: &OPEN,
+            fingerprint: None,
+        }
+    }
+}
+
+impl Security<'_> {
+    /// The URL scheme: `https` or `http`.
+    fn scheme(&self) -> &'static str {
+        if self.https {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    /// The query a banner URL ends with: the token for [`Auth::Token`], so the
+    /// printed URL opens as is, and nothing otherwise.
+    fn url_query(&self) -> String {
+        match self.auth {
+            Auth::Token(token) => format!("?{TOKEN_PARAM}={token}"),
+            _ => String::new(),
+        }
+    }
+
+    /// Appends the banner lines describing the access control and certificate.
+    fn describe(&self, banner: &mut String) {
+        use std::fmt::Write as _;
+
+        match self.auth {
+            Auth::Open => {}
+            Auth::Basic { user, .. } => {
+                let _ = writeln!(banner, "Basic auth required: user '{user}'");
+            }
+            Auth::Token(token) => {
+                let _ = writeln!(
+                    banner,
+                    "Access token: {token} (in the URLs above; curl can send `-H 'Authorization: Bearer {token}'`)"
+                );
+            }
+        }
+        if let Some(fingerprint) = self.fingerprint {
+            let _ = writeln!(banner, "Certificate SHA-256: {fingerprint}");
+        }
+    }
+}
+
 /// Builds the multi-line startup banner for files mode.
 ///
 /// `version` is the buildinfo version string, `bind` the bind address, `port`
 /// the resolved port, `source` the optional port-derivation description (included
 /// only under `--verbose`), and `routes` the sorted route map. Every served
-/// file's full `http://<bind>:<port>/<basename>` URL appears on its own line.
+/// file's full `<scheme>://<bind>:<port>/<basename>` URL appears on its own
+/// line, carrying the access token when `security` requires one, followed by
+/// the access-control and certificate lines.
 #[must_use]
 pub fn files_banner(
     version: &str,
@@ -367,25 +682,35 @@ pub fn files_banner(
     port: u16,
     source: Option<&str>,
     routes: &BTreeMap<String, PathBuf>,
+    security: &Security<'_>,
 ) -> String {
     use std::fmt::Write as _;
 
+    let scheme = security.scheme();
+    let query = security.url_query();
     let mut banner = format!("sirn {version}\n");
     if let Some(source) = source {
         let _ = writeln!(banner, "{source}");
     }
-    let _ = writeln!(banner, "Serving on http://{bind}:{port}");
+    let _ = writeln!(banner, "Serving on {scheme}://{bind}:{port}");
     for url_path in routes.keys() {
         // Percent-encode the route path so a key with a space or non-ASCII byte
         // (e.g. `/with space.txt`) prints as a valid, clickable URL line.
-        let _ = writeln!(banner, "  http://{bind}:{port}{}", encode_path(url_path));
+        let _ = writeln!(
+            banner,
+            "  {scheme}://{bind}:{port}{}{query}",
+            encode_path(url_path)
+        );
     }
+    security.describe(&mut banner);
     banner
 }
 
 /// Builds the directory-mode startup banner: the version line, the optional
-/// port-derivation `source` line (under `--verbose`), and a line naming the
-/// served root plus its root URL `http://<bind>:<port>/`.
+/// port-derivation `source` line (under `--verbose`), a line naming the
+/// served root plus its root URL `<scheme>://<bind>:<port>/` (carrying the
+/// access token when `security` requires one), and the access-control and
+/// certificate lines.
 #[must_use]
 pub fn directory_banner(
     version: &str,
@@ -393,6 +718,7 @@ pub fn directory_banner(
     port: u16,
     source: Option<&str>,
     root: &Path,
+    security: &Security<'_>,
 ) -> String {
     use std::fmt::Write as _;
 
@@ -402,9 +728,12 @@ pub fn directory_banner(
     }
     let _ = writeln!(
         banner,
-        "Serving {} on http://{bind}:{port}/",
-        root.display()
+        "Serving {} on {}://{bind}:{port}/{}",
+        root.display(),
+        security.scheme(),
+        security.url_query()
     );
+    security.describe(&mut banner);
     banner
 }
 
@@ -417,7 +746,7 @@ pub fn directory_banner(
 #[must_use]
 pub fn port_basis(mode: &ServeMode) -> Option<&Path> {
     match mode {
-        ServeMode::Directory(root) => Some(root.as_ref()),
+        ServeMode::Directory(config) => Some(&config.root),
         ServeMode::Files(_) => None,
     }
 }
@@ -428,11 +757,50 @@ pub enum ServeMode {
     /// Files mode: a fixed `/<basename>` -> file route map.
     Files(Arc<BTreeMap<String, PathBuf>>),
     /// Directory mode: serve a canonicalized root directory as a browsable tree.
-    Directory(Arc<PathBuf>),
+    Directory(Arc<DirectoryConfig>),
+}
+
+/// How directory mode serves its root.
+#[derive(Debug, Clone)]
+pub struct DirectoryConfig {
+    /// The served root. MUST already be canonicalized (see
+    /// [`resolve_under_root`]).
+    pub root: PathBuf,
+    /// Whether clients may upload, create directories, and delete under the
+    /// root, and within what limits. `None` (the default) serves a read-only
+    /// tree and answers every write method with `405`.
+    pub writes: Option<WriteConfig>,
+    /// Whether a directory holding an `index.html` serves that page in place
+    /// of its listing (`--index`).
+    pub index: bool,
+    /// Whether a request for a missing path whose last segment has no
+    /// extension gets the root `index.html` instead of `404` (`--spa`), so a
+    /// client-side router can handle deep links.
+    pub spa: bool,
+    /// The hub that tells open pages to reload, when live reload is on
+    /// (`--live-reload`). HTML pages and listings then carry a script that
+    /// listens on [`LIVE_RELOAD_PATH`].
+    pub live_reload: Option<Arc<LiveReload>>,
+}
+
+impl DirectoryConfig {
+    /// A read-only configuration serving the canonicalized `root`, with
+    /// listings for every directory and no live reload.
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            writes: None,
+            index: false,
+            spa: false,
+            live_reload: None,
+        }
+    }
 }
 
 /// Serves requests on `server` in the given `mode` using a fixed pool of
-/// `workers` threads.
+/// `workers` threads, admitting only requests that satisfy `auth` (see
+/// [`admit`]).
 ///
 /// Each worker loops on `server.recv()`; the pool shuts down when the server is
 /// unblocked (`server.unblock()` once per worker), at which point `recv()` errors
@@ -442,25 +810,82 @@ pub enum ServeMode {
 pub fn serve(
     server: Arc<tiny_http::Server>,
     mode: ServeMode,
+    auth: Auth,
     workers: usize,
 ) -> Vec<JoinHandle<()>> {
+    // Cookies are shared by every port on a host, so the token cookie is
+    // named for this one; two servers with different tokens then coexist.
+    let port = server.server_addr().to_ip().map_or(0, |addr| addr.port());
+    let cookie_name = format!("sirn-token-{port}");
+    let auth = Arc::new(auth);
     (0..workers.max(1))
         .map(|_| {
             let server = Arc::clone(&server);
             let mode = mode.clone();
+            let auth = Arc::clone(&auth);
+            let cookie_name = cookie_name.clone();
             std::thread::spawn(move || {
                 // `recv()` errors when the server is unblocked, ending the loop.
                 while let Ok(request) = server.recv() {
                     // A request or mid-response IO error (e.g. a client
                     // disconnecting) is swallowed so a single bad request can
                     // never panic a worker and poison the pool.
-                    let _ = respond(&mode, request);
+                    let _ = respond_admitted(&mode, &auth, &cookie_name, request);
                 }
             })
         })
         .collect()
 }
 
+/// Checks `request` against `auth` and serves it if admitted.
+///
+/// A refused request gets `401`, with a basic-auth challenge under
+/// [`Auth::Basic`] so a browser prompts for the password. A browser opening
+/// a token URL is redirected (`303`) to the same URL without the token, with
+/// the token set as an `HttpOnly` cookie (`Secure` over HTTPS).
+fn respond_admitted(
+    mode: &ServeMode,
+    auth: &Auth,
+    cookie_name: &str,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let navigation = *request.method() == tiny_http::Method::Get
+        && request_header(&request, "Accept").is_some_and(|accept| accept.contains("text/html"));
+    let admission = admit(
+        auth,
+        request.url(),
+        request_header(&request, "Authorization"),
+        request_header(&request, "Cookie"),
+        cookie_name,
+        navigation,
+    );
+    match admission {
+        Admission::Allowed => respond(mode, request),
+        Admission::Redirect(location) => {
+            let Auth::Token(token) = auth else {
+                return respond(mode, request);
+            };
+            let secure = if request.secure() { "; Secure" } else { "" };
+            let cookie = format!("{cookie_name}={token}; Path=/; HttpOnly; SameSite=Lax{secure}");
+            let response = tiny_http::Response::empty(303)
+                .with_header(header("Location", &location))
+                .with_header(header("Set-Cookie", &cookie))
+                .with_header(header("Cache-Control", "no-store"));
+            request.respond(response)
+        }
+        Admission::Denied => {
+            let response = plain(401, "authentication required\n");
+            match auth {
+                Auth::Basic { .. } => request.respond(response.with_header(header(
+                    "WWW-Authenticate",
+                    "Basic realm=\"sirn\", charset=\"UTF-8\"",
+                ))),
+                _ => request.respond(response),
+            }
+        }
+    }
+}
+
 /// How often the availability monitor restats the served files.
 const POLL_INTERVAL: Duration = Duration::from_secs(1);
 
@@ -493,45 +918,354 @@ pub fn spawn_monitor(
     })
 }
 
+/// A content coding `sirn` can apply to a response on the fly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// `br`: Brotli.
+    Brotli,
+    /// `gzip`.
+    Gzip,
+}
+
+impl Encoding {
+    /// The coding's token, as used in `Accept-Encoding` and `Content-Encoding`.
+    #[must_use]
+    pub fn token(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+}
+
+/// Returns true if a response of `content_type` is worth compressing.
+///
+/// Text, JSON, and SVG shrink severalfold. Images, audio, video, fonts, and
+/// archives are already compressed, so recompressing them costs CPU and saves
+/// nothing.
+#[must_use]
+pub fn is_compressible(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || content_type == "application/json"
+        || content_type == "image/svg+xml"
+}
+
+/// Picks the coding to apply from an `Accept-Encoding` request header, or
+/// `None` to send the body as is.
+///
+/// Each coding's weight is its own `q` value, else the `*` wildcard's, else
+/// zero; a coding weighted zero is refused. The highest-weighted of `br` and
+/// `gzip` wins, with `br` preferred on a tie because it compresses text
+/// tighter. `x-gzip` is accepted as an alias for `gzip`, and a missing or
+/// unparsable `q` counts as `1`.
+///
+/// This function never panics.
+#[must_use]
+pub fn negotiate_encoding(accept_encoding: &str) -> Option<Encoding> {
+    let (mut brotli, mut gzip, mut wildcard) = (None, None, None);
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or("").trim().to_ascii_lowercase();
+        let weight = params
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .map_or(1.0, |(_, value)| value.trim().parse::<f32>().unwrap_or(1.0));
+        match coding.as_str() {
+            "br" => brotli = Some(weight),
+            "gzip" | "x-gzip" => gzip = Some(weight),
+            "*" => wildcard = Some(weight),
+            _ => {}
+        }
+    }
+
+    let brotli = brotli.or(wildcard).unwrap_or(0.0);
+    let gzip = gzip.or(wildcard).unwrap_or(0.0);
+    if brotli > 0.0 && brotli >= gzip {
+        Some(Encoding::Brotli)
+    } else if gzip > 0.0 {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Builds the strong `ETag` for a file of `len` bytes last modified at
+/// `modified`, sent with `encoding` (or as is, for `None`).
+///
+/// The tag changes whenever the file's size or modification time does, which
+/// is what an edit or a replacement changes, without reading the file. Each
+/// coding gets its own tag because a compressed body is a different sequence
+/// of bytes, and a cache must never answer a `gzip` request with a stored
+/// identity body (or a range request with compressed bytes).
+#[must_use]
+pub fn entity_tag(len: u64, modified: SystemTime, encoding: Option<Encoding>) -> String {
+    // A modification time before the epoch is vanishingly rare; it still gets a
+    // stable tag from its size alone.
+    let since_epoch = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let nanos = since_epoch.subsec_nanos();
+    match encoding {
+        Some(encoding) => format!("\"{secs:x}.{nanos:x}-{len:x}-{}\"", encoding.token()),
+        None => format!("\"{secs:x}.{nanos:x}-{len:x}\""),
+    }
+}
+
+/// Returns true if a conditional `GET` can be answered `304 Not Modified`.
+///
+/// `If-None-Match` takes precedence: when present, the answer is whether any
+/// listed tag matches `etag` under the weak comparison RFC 9110 requires here
+/// (a `W/` prefix is ignored), or whether it is `*`. Only without it is
+/// `If-Modified-Since` consulted, matching when `modified` — truncated to the
+/// whole second an HTTP date can carry — is no later than the given date. An
+/// unparsable date is ignored, as the RFC asks.
+///
+/// This function never panics.
+#[must_use]
+pub fn is_not_modified(
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+    etag: &str,
+    modified: SystemTime,
+) -> bool {
+    if let Some(tags) = if_none_match {
+        let etag = etag.trim_start_matches("W/");
+        return tags
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag);
+    }
+    if_modified_since
+        .and_then(|date| httpdate::parse_http_date(date.trim()).ok())
+        .is_some_and(|since| HttpDate::from(modified) <= HttpDate::from(since))
+}
+
+/// Returns true if an `If-Range` request header still matches the file, so its
+/// `Range` header applies.
+///
+/// The header carries either an entity tag or an HTTP date. A tag must match
+/// `etag` under the strong comparison, so a weak (`W/`) tag never matches; a
+/// date must equal the file's `Last-Modified` exactly. On a mismatch the client
+/// holds a stale copy, and the whole file must be sent instead of a range that
+/// would be spliced onto the wrong bytes.
+///
+/// This function never panics.
+#[must_use]
+pub fn if_range_matches(if_range: &str, etag: &str, modified: SystemTime) -> bool {
+    let if_range = if_range.trim();
+    if if_range.starts_with('"') {
+        return if_range == etag;
+    }
+    if if_range.starts_with("W/") {
+        return false;
+    }
+    httpdate::parse_http_date(if_range)
+        .is_ok_and(|date| HttpDate::from(date) == HttpDate::from(modified))
+}
+
 /// Opens `path` only if it is a regular file, returning `None` otherwise.
 ///
 /// A directory, a missing path, or any other non-regular entry yields `None`.
 /// This guards the streaming path: advertising a directory's metadata length
 /// and then failing to read its bytes would hang the client waiting for a body
-/// that never arrives.
-fn open_regular_file(path: &Path) -> Option<std::fs::File> {
+/// that never arrives. The metadata comes back with the file so the caller
+/// sizes and dates the response from the very handle it streams.
+fn open_regular_file(path: &Path) -> Option<(std::fs::File, std::fs::Metadata)> {
     let file = std::fs::File::open(path).ok()?;
     // A directory opens successfully on Unix, so confirm the entry is a regular
     // file before letting it onto the streaming path.
-    file.metadata().ok()?.is_file().then_some(file)
+    let metadata = file.metadata().ok()?;
+    metadata.is_file().then_some((file, metadata))
+}
+
+/// Builds a response header from a name and a value `sirn` generated itself.
+///
+/// Every value passed here is ASCII built from a static string, a number, a
+/// hex digest, or an HTTP date, so the `expect` can never fire.
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("sirn-generated header is always valid")
+}
+
+/// Returns the value of the request header `name`, if the client sent one.
+fn request_header<'a>(request: &'a tiny_http::Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+/// A short `text/plain` response, for errors a `curl` user needs to read.
+fn plain(status: u16, message: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(format!("{message}\n")).with_status_code(status)
 }
 
 /// Streams `path` to `request`, or responds `404` if it is not a regular file.
 ///
 /// `path` is opened through [`open_regular_file`], so a missing path, a
 /// directory, or any other non-regular entry yields `404` (never a hung stream).
-/// A regular file (even empty) streams as a `200` with a `Content-Length` (set
-/// by `tiny_http` from the file size) and a `Content-Type` from its extension.
+/// A regular file (even empty) is sent with a `Content-Type` from its
+/// extension, `Accept-Ranges: bytes`, and — when the file has a modification
+/// time — an `ETag` and `Last-Modified`. On a `GET` or `HEAD`:
+///
+/// - a matching `If-None-Match` or `If-Modified-Since` yields `304` (see
+///   [`is_not_modified`]);
+/// - a `GET` with a `Range` header (and a matching `If-Range`, if any) yields
+///   `206` with one range, `206` `multipart/byteranges` with several, or `416`
+///   when no range overlaps the file (see [`parse_range`]);
+/// - otherwise a compressible type (see [`is_compressible`]) is compressed on
+///   the fly with the coding the client prefers (see [`negotiate_encoding`])
+///   and carries `Vary: Accept-Encoding`.
+///
+/// Ranges are always served from the uncompressed file, so byte offsets mean
+/// the same thing no matter what a client accepts. Anything else streams as a
+/// plain `200` with a `Content-Length`.
 fn serve_file(path: &Path, request: tiny_http::Request) -> std::io::Result<()> {
-    let Some(file) = open_regular_file(path) else {
+    let Some((mut file, metadata)) = open_regular_file(path) else {
         return request.respond(tiny_http::Response::empty(404));
     };
+    let len = metadata.len();
+    let modified = metadata.modified().ok();
+    let content_type = content_type_for(path);
+    let compressible = is_compressible(content_type);
+    let is_get = *request.method() == tiny_http::Method::Get;
+    let is_conditional = is_get || *request.method() == tiny_http::Method::Head;
+
+    // RFC 9110 has a server ignore `Range` on anything but a `GET`, and an
+    // `If-Range` that no longer matches asks for the whole (changed) file.
+    let range = match request_header(&request, "Range") {
+        Some(range) if is_get => {
+            let current = request_header(&request, "If-Range").is_none_or(|if_range| {
+                modified.is_some_and(|modified| {
+                    if_range_matches(if_range, &entity_tag(len, modified, None), modified)
+                })
+            });
+            if current {
+                parse_range(range, len)
+            } else {
+                RangeRequest::Full
+            }
+        }
+        _ => RangeRequest::Full,
+    };
+    let encoding = match range {
+        RangeRequest::Full if compressible => {
+            request_header(&request, "Accept-Encoding").and_then(negotiate_encoding)
+        }
+        _ => None,
+    };
 
-    // The header name and value are compile-time-known-valid, so the only
-    // `expect` on the request path can never fire.
-    let content_type =
-        tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type_for(path).as_bytes())
-            .expect("static Content-Type header is always valid");
+    let mut headers = vec![header("Accept-Ranges", "bytes")];
+    if compressible {
+        headers.push(header("Vary", "Accept-Encoding"));
+    }
+    if let Some(modified) = modified {
+        let etag = entity_tag(len, modified, encoding);
+        headers.push(header("ETag", &etag));
+        headers.push(header("Last-Modified", &httpdate::fmt_http_date(modified)));
+        if is_conditional
+            && is_not_modified(
+                request_header(&request, "If-None-Match"),
+                request_header(&request, "If-Modified-Since"),
+                &etag,
+                modified,
+            )
+        {
+            return request.respond(respond_with(tiny_http::Response::empty(304), headers));
+        }
+    }
 
-    let response = tiny_http::Response::from_file(file).with_header(content_type);
-    request.respond(response)
+    match range {
+        RangeRequest::Full => {
+            headers.push(header("Content-Type", content_type));
+            match encoding {
+                None => {
+                    let response = tiny_http::Response::from_file(file);
+                    request.respond(respond_with(response, headers))
+                }
+                Some(encoding) => {
+                    headers.push(header("Content-Encoding", encoding.token()));
+                    // The compressed length is unknown until the whole file has
+                    // been read, so the body goes out chunked (or, to an HTTP/1.0
+                    // client, buffered by `tiny_http` and then sent).
+                    let response = tiny_http::Response::empty(200);
+                    match encoding {
+                        Encoding::Brotli => request.respond(respond_with(
+                            response.with_data(
+                                brotli::CompressorReader::new(
+                                    file,
+                                    COMPRESS_BUFFER,
+                                    BROTLI_QUALITY,
+                                    BROTLI_WINDOW,
+                                ),
+                                None,
+                            ),
+                            headers,
+                        )),
+                        Encoding::Gzip => request.respond(respond_with(
+                            response.with_data(
+                                flate2::read::GzEncoder::new(file, flate2::Compression::default()),
+                                None,
+                            ),
+                            headers,
+                        )),
+                    }
+                }
+            }
+        }
+        RangeRequest::Unsatisfiable => {
+            headers.push(header("Content-Range", &format!("bytes */{len}")));
+            request.respond(respond_with(tiny_http::Response::empty(416), headers))
+        }
+        RangeRequest::Partial(ranges) => {
+            if let [(first, last)] = ranges[..] {
+                file.seek(SeekFrom::Start(first))?;
+                headers.push(header("Content-Type", content_type));
+                headers.push(header(
+                    "Content-Range",
+                    &format!("bytes {first}-{last}/{len}"),
+                ));
+                let span = last - first + 1;
+                let response = tiny_http::Response::empty(206)
+                    .with_data(file.take(span), usize::try_from(span).ok());
+                return request.respond(respond_with(response, headers));
+            }
+            let body = ByteRanges::new(file, &ranges, len, content_type);
+            headers.push(header(
+                "Content-Type",
+                &format!("multipart/byteranges; boundary={}", body.boundary),
+            ));
+            let body_len = usize::try_from(body.len()).ok();
+            let response = tiny_http::Response::empty(206).with_data(body, body_len);
+            request.respond(respond_with(response, headers))
+        }
+    }
+}
+
+/// Internal buffer size for on-the-fly Brotli compression.
+const COMPRESS_BUFFER: usize = 16 * 1024;
+/// Brotli quality for on-the-fly compression: well short of the maximum (11),
+/// which is far too slow to run per request, but still ahead of gzip on text.
+const BROTLI_QUALITY: u32 = 5;
+/// Brotli window size, as a power of two (the encoder's usual default).
+const BROTLI_WINDOW: u32 = 22;
+
+/// Adds every header in `headers` to `response`.
+fn respond_with<R: Read>(
+    mut response: tiny_http::Response<R>,
+    headers: Vec<tiny_http::Header>,
+) -> tiny_http::Response<R> {
+    for header in headers {
+        response.add_header(header);
+    }
+    response
 }
 
 /// Dispatches one request to the handler for the active [`ServeMode`].
 fn respond(mode: &ServeMode, request: tiny_http::Request) -> std::io::Result<()> {
     match mode {
         ServeMode::Files(routes) => respond_files(routes, request),
-        ServeMode::Directory(root) => respond_directory(root, request),
+        ServeMode::Directory(config) => respond_directory(config, request),
     }
 }
 
@@ -569,8 +1303,8 @@ fn respond_files(
     serve_file(file_path, request)
 }
 
-/// Handles one directory-mode request: resolves the URL path under `root`, then
-/// either renders a listing (for a directory) or streams the file.
+/// Handles one directory-mode request: resolves the URL path under the root,
+/// then either renders a listing (for a directory) or streams the file.
 ///
 /// The request URL has any `?query` stripped and is then percent-decoded (see
 /// [`decode_path`]) before resolution, so an encoded name like `/my%20file.txt`
@@ -580,49 +1314,172 @@ fn respond_files(
 /// traversal or a symlink pointing outside the root) yields `403`; an in-root
 /// path that does not exist yields `404`. An in-root directory renders an HTML
 /// listing; an in-root regular file streams as a `200` (see [`serve_file`]).
-fn respond_directory(root: &Path, request: tiny_http::Request) -> std::io::Result<()> {
-    // Own the decoded path so `request` can be moved into the handler below while
-    // it is still needed (the listing renderer needs the request path).
-    let url_path = decode_path(request.url().split('?').next().unwrap_or(""));
-    match resolve_under_root(root, &url_path) {
+///
+/// The config's options adjust this: with `index`, a directory holding an
+/// `index.html` serves that page (after a `301` to the slash-terminated URL,
+/// so the page's relative links resolve); with `spa`, a missing path with no
+/// extension in its last segment serves the root `index.html`; with live
+/// reload, [`LIVE_RELOAD_PATH`] streams reload events and every HTML response
+/// carries the reload script (see [`serve_page`]).
+///
+/// The write methods (`PUT`, `POST`, `DELETE`, `MKCOL`) go to
+/// [`respond_write`] when the config allows writes, and get `405` otherwise.
+fn respond_directory(config: &DirectoryConfig, request: tiny_http::Request) -> std::io::Result<()> {
+    if is_write_method(request.method()) {
+        return match config.writes {
+            Some(writes) => respond_write(&config.root, writes, request),
+            None => request
+                .respond(tiny_http::Response::empty(405).with_header(header("Allow", "GET, HEAD"))),
+        };
+    }
+
+    // Own the decoded path and query so `request` can be moved into the handler
+    // below while they are still needed (the listing renderer needs both).
+    let (raw_path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let url_path = decode_path(raw_path);
+    let query = query.to_string();
+    if let Some(hub) = &config.live_reload {
+        if url_path == LIVE_RELOAD_PATH {
+            return respond_events(hub, &query, request);
+        }
+    }
+
+    match resolve_under_root(&config.root, &url_path) {
         PathResolution::Forbidden => request.respond(tiny_http::Response::empty(403)),
-        PathResolution::Missing => request.respond(tiny_http::Response::empty(404)),
+        PathResolution::Missing => {
+            let last = url_path.rsplit('/').next().unwrap_or_default();
+            if config.spa && !last.contains('.') {
+                if let PathResolution::Allowed(index) =
+                    resolve_under_root(&config.root, "/index.html")
+                {
+                    return serve_page(&index, config, request);
+                }
+            }
+            request.respond(tiny_http::Response::empty(404))
+        }
         PathResolution::Allowed(path) => {
-            if path.is_dir() {
-                respond_listing(&path, &url_path, request)
-            } else {
-                serve_file(&path, request)
+            if !path.is_dir() {
+                return serve_page(&path, config, request);
             }
+            if config.index {
+                let index = format!("{}/index.html", url_path.trim_end_matches('/'));
+                if let PathResolution::Allowed(index) = resolve_under_root(&config.root, &index) {
+                    if !index.is_dir() {
+                        if !url_path.ends_with('/') {
+                            return redirect_to_slash(&url_path, &query, request);
+                        }
+                        return serve_page(&index, config, request);
+                    }
+                }
+            }
+            respond_listing(config, &path, &url_path, &query, request)
         }
     }
 }
 
+/// Redirects a directory URL missing its trailing slash to the slash-terminated
+/// one, keeping any `query`, so relative links on its index page resolve
+/// inside the directory rather than beside it.
+fn redirect_to_slash(
+    url_path: &str,
+    query: &str,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let mut location = format!("{}/", encode_path(url_path));
+    if !query.is_empty() {
+        location.push('?');
+        location.push_str(query);
+    }
+    request.respond(tiny_http::Response::empty(301).with_header(header("Location", &location)))
+}
+
+/// Serves a file in directory mode.
+///
+/// With live reload on, an HTML page is read whole and sent with the reload
+/// script injected (see [`inject_live_reload`]) and `Cache-Control: no-cache`,
+/// so a reload always fetches the current page. The injected page differs from
+/// the file on disk, so it is sent without validators, ranges, or
+/// compression. Every other file goes to [`serve_file`].
+fn serve_page(
+    path: &Path,
+    config: &DirectoryConfig,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let content_type = content_type_for(path);
+    let Some(hub) = config
+        .live_reload
+        .as_ref()
+        .filter(|_| content_type.starts_with("text/html"))
+    else {
+        return serve_file(path, request);
+    };
+    let Ok(page) = std::fs::read(path) else {
+        return request.respond(tiny_http::Response::empty(404));
+    };
+    let body = match String::from_utf8(page) {
+        Ok(page) => inject_live_reload(&page, hub.generation()).into_bytes(),
+        // A page that is not UTF-8 cannot be edited as text; serve it as is.
+        Err(err) => err.into_bytes(),
+    };
+    let response = tiny_http::Response::from_data(body)
+        .with_header(header("Content-Type", content_type))
+        .with_header(header("Cache-Control", "no-cache"));
+    request.respond(response)
+}
+
 /// Renders an HTML directory listing for `dir` (already confirmed in-root) and
 /// responds with it.
 ///
-/// Entries are collected as `(name, is_dir)` via [`std::fs::DirEntry::file_name`]
-/// (UTF-8 safe, no byte slicing) and sorted directories-first then by name
-/// ascending. A `read_dir` error responds `500`. The body is served as
-/// `text/html; charset=utf-8`.
-fn respond_listing(dir: &Path, url_path: &str, request: tiny_http::Request) -> std::io::Result<()> {
+/// Entries are collected via [`std::fs::DirEntry::file_name`] (UTF-8 safe, no
+/// byte slicing) with their size and modification time (following symlinks,
+/// falling back to the link itself when it dangles), and ordered by
+/// [`sort_entries`] with the sort read from `query` (see
+/// [`ListingSort::from_query`]). Uploads still being received (see
+/// [`UPLOAD_PREFIX`]) are left out. A `read_dir` error responds `500`. The body
+/// is served as `text/html; charset=utf-8`, with the write controls when the
+/// config allows writes and the reload script when live reload is on.
+fn respond_listing(
+    config: &DirectoryConfig,
+    dir: &Path,
+    url_path: &str,
+    query: &str,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
     let Ok(read_dir) = std::fs::read_dir(dir) else {
         return request.respond(tiny_http::Response::empty(500));
     };
 
-    let mut entries: Vec<(String, bool)> = Vec::new();
+    let mut entries: Vec<ListingEntry> = Vec::new();
     for entry in read_dir.flatten() {
         // `to_string_lossy` keeps multi-byte UTF-8 names intact with no slicing.
         let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(UPLOAD_PREFIX) {
+            continue;
+        }
         let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
-        entries.push((name, is_dir));
+        let metadata = std::fs::metadata(entry.path()).or_else(|_| entry.metadata());
+        let (size, modified) = metadata.map_or((0, None), |metadata| {
+            let size = if is_dir { 0 } else { metadata.len() };
+            (size, metadata.modified().ok())
+        });
+        entries.push(ListingEntry {
+            name,
+            is_dir,
+            size,
+            modified,
+        });
+    }
+    let sort = ListingSort::from_query(query);
+    sort_entries(&mut entries, sort);
+
+    let mut body = if config.writes.is_some() {
+        render_writable_listing(url_path, &entries, sort)
+    } else {
+        render_directory_listing(url_path, &entries, sort)
+    };
+    if let Some(hub) = &config.live_reload {
+        body = inject_live_reload(&body, hub.generation());
     }
-    // Directories first, then by name ascending — matching the listing renderer's
-    // caller contract (it expects pre-sorted entries).
-    entries.sort_by(|(a_name, a_dir), (b_name, b_dir)| {
-        b_dir.cmp(a_dir).then_with(|| a_name.cmp(b_name))
-    });
-
-    let body = render_directory_listing(url_path, &entries);
 
     // The header name and value are compile-time-known-valid, so this `expect`
     // can never fire.
@@ -917,7 +1774,7 @@ mod route_tests {
 
 #[cfg(test)]
 mod banner_tests {
-    use super::files_banner;
+    use super::{files_banner, Auth, Security};
     use std::collections::BTreeMap;
     use std::path::PathBuf;
 
@@ -932,7 +1789,14 @@ mod banner_tests {
     #[test]
     fn banner_includes_version_and_bind_port() {
         let routes = sample_routes();
-        let banner = files_banner("0.1.0 (abc1234, clean)", "127.0.0.1", 8080, None, &routes);
+        let banner = files_banner(
+            "0.1.0 (abc1234, clean)",
+            "127.0.0.1",
+            8080,
+            None,
+            &routes,
+            &Security::default(),
+        );
 
         assert!(
             banner.contains("0.1.0 (abc1234, clean)"),
@@ -947,7 +1811,14 @@ mod banner_tests {
     #[test]
     fn banner_includes_every_route_url() {
         let routes = sample_routes();
-        let banner = files_banner("0.1.0", "127.0.0.1", 8080, None, &routes);
+        let banner = files_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            &routes,
+            &Security::default(),
+        );
 
         assert!(
             banner.contains("http://127.0.0.1:8080/a.txt"),
@@ -963,7 +1834,14 @@ mod banner_tests {
     fn banner_includes_source_when_some() {
         let routes = sample_routes();
         let source = "Port 8080 for repo 'sirn' on branch 'main'";
-        let banner = files_banner("0.1.0", "127.0.0.1", 8080, Some(source), &routes);
+        let banner = files_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            Some(source),
+            &routes,
+            &Security::default(),
+        );
 
         assert!(
             banner.contains(source),
@@ -975,7 +1853,14 @@ mod banner_tests {
     fn banner_omits_source_when_none() {
         let routes = sample_routes();
         let source = "Port 8080 for repo 'sirn' on branch 'main'";
-        let banner = files_banner("0.1.0", "127.0.0.1", 8080, None, &routes);
+        let banner = files_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            &routes,
+            &Security::default(),
+        );
 
         assert!(
             !banner.contains(source),
@@ -992,7 +1877,14 @@ mod banner_tests {
             "/with space.txt".to_string(),
             PathBuf::from("dir/with space.txt"),
         );
-        let banner = files_banner("0.1.0", "127.0.0.1", 8080, None, &routes);
+        let banner = files_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            &routes,
+            &Security::default(),
+        );
 
         assert!(
             banner.contains("http://127.0.0.1:8080/with%20space.txt"),
@@ -1003,11 +1895,30 @@ mod banner_tests {
             "banner must not contain a raw-space URL, got:\n{banner}"
         );
     }
+
+    #[test]
+    fn token_banner_puts_the_token_on_every_route_url() {
+        let routes = sample_routes();
+        let auth = Auth::Token("abc123".to_string());
+        let security = Security {
+            auth: &auth,
+            ..Security::default()
+        };
+        let banner = files_banner("0.1.0", "127.0.0.1", 8080, None, &routes, &security);
+        assert!(
+            banner.contains("http://127.0.0.1:8080/a.txt?token=abc123"),
+            "got:\n{banner}"
+        );
+        assert!(
+            banner.contains("http://127.0.0.1:8080/b.css?token=abc123"),
+            "got:\n{banner}"
+        );
+    }
 }
 
 #[cfg(test)]
 mod directory_banner_tests {
-    use super::directory_banner;
+    use super::{directory_banner, Auth, Security};
     use std::path::Path;
 
     #[test]
@@ -1018,6 +1929,7 @@ mod directory_banner_tests {
             8080,
             None,
             Path::new("/srv/www"),
+            &Security::default(),
         );
         assert!(
             banner.contains("0.1.0 (abc1234, clean)"),
@@ -1028,7 +1940,7 @@ mod directory_banner_tests {
     #[test]
     fn banner_includes_root_display() {
         let root = Path::new("/srv/www");
-        let banner = directory_banner("0.1.0", "127.0.0.1", 8080, None, root);
+        let banner = directory_banner("0.1.0", "127.0.0.1", 8080, None, root, &Security::default());
         assert!(
             banner.contains(&root.display().to_string()),
             "banner should include the served root path, got:\n{banner}"
@@ -1037,7 +1949,14 @@ mod directory_banner_tests {
 
     #[test]
     fn banner_includes_root_url() {
-        let banner = directory_banner("0.1.0", "127.0.0.1", 8080, None, Path::new("/srv/www"));
+        let banner = directory_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            Path::new("/srv/www"),
+            &Security::default(),
+        );
         assert!(
             banner.contains("http://127.0.0.1:8080/"),
             "banner should include the root URL, got:\n{banner}"
@@ -1053,6 +1972,7 @@ mod directory_banner_tests {
             8080,
             Some(source),
             Path::new("/srv/www"),
+            &Security::default(),
         );
         assert!(
             banner.contains(source),
@@ -1063,12 +1983,65 @@ mod directory_banner_tests {
     #[test]
     fn banner_omits_source_when_none() {
         let source = "Port 8080 for repo 'sirn' on branch 'main'";
-        let banner = directory_banner("0.1.0", "127.0.0.1", 8080, None, Path::new("/srv/www"));
+        let banner = directory_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            Path::new("/srv/www"),
+            &Security::default(),
+        );
         assert!(
             !banner.contains(source),
             "banner should not include any derivation source when None, got:\n{banner}"
         );
     }
+
+    #[test]
+    fn secured_banner_shows_https_token_url_and_fingerprint() {
+        let auth = Auth::Token("abc123".to_string());
+        let security = Security {
+            https: true,
+            auth: &auth,
+            fingerprint: Some("AA:BB"),
+        };
+        let banner = directory_banner("0.1.0", "0.0.0.0", 8443, None, Path::new("/srv"), &security);
+        assert!(
+            banner.contains("on https://0.0.0.0:8443/?token=abc123"),
+            "got:\n{banner}"
+        );
+        assert!(banner.contains("Access token: abc123"), "got:\n{banner}");
+        assert!(
+            banner.contains("Certificate SHA-256: AA:BB"),
+            "got:\n{banner}"
+        );
+    }
+
+    #[test]
+    fn basic_auth_banner_names_the_user_but_not_the_password() {
+        let auth = Auth::Basic {
+            user: "me".to_string(),
+            password: "hunter2".to_string(),
+        };
+        let security = Security {
+            auth: &auth,
+            ..Security::default()
+        };
+        let banner = directory_banner(
+            "0.1.0",
+            "127.0.0.1",
+            8080,
+            None,
+            Path::new("/srv"),
+            &security,
+        );
+        assert!(
+            banner.contains("http://127.0.0.1:8080/\n"),
+            "got:\n{banner}"
+        );
+        assert!(banner.contains("user 'me'"), "got:\n{banner}");
+        assert!(!banner.contains("hunter2"), "got:\n{banner}");
+    }
 }
 
 #[cfg(test)]
@@ -1292,7 +2265,7 @@ mod mode_tests {
 
 #[cfg(test)]
 mod port_basis_tests {
-    use super::{port_basis, ServeMode};
+    use super::{port_basis, DirectoryConfig, ServeMode};
     use std::collections::BTreeMap;
     use std::path::{Path, PathBuf};
     use std::sync::Arc;
@@ -1301,7 +2274,7 @@ mod port_basis_tests {
     fn directory_mode_derives_from_its_root() {
         // Directory mode must derive its port from the served root so that
         // `sirn <dir>` picks the same port as `cd <dir> && sirn`.
-        let mode = ServeMode::Directory(Arc::new(PathBuf::from("/some/dir")));
+        let mode = ServeMode::Directory(Arc::new(DirectoryConfig::new(PathBuf::from("/some/dir"))));
         assert_eq!(port_basis(&mode), Some(Path::new("/some/dir")));
     }
 
@@ -1316,13 +2289,18 @@ mod port_basis_tests {
 
 #[cfg(test)]
 mod listing_tests {
-    use super::{html_escape, render_directory_listing};
+    use super::{html_escape, render_directory_listing, ListingEntry, ListingSort};
 
-    /// Builds an owned `(name, is_dir)` entry list from string slices.
-    fn entries(items: &[(&str, bool)]) -> Vec<(String, bool)> {
+    /// Builds an entry list from `(name, is_dir)` pairs, with no size or time.
+    fn entries(items: &[(&str, bool)]) -> Vec<ListingEntry> {
         items
             .iter()
-            .map(|(name, is_dir)| ((*name).to_string(), *is_dir))
+            .map(|(name, is_dir)| ListingEntry {
+                name: (*name).to_string(),
+                is_dir: *is_dir,
+                size: 0,
+                modified: None,
+            })
             .collect()
     }
 
@@ -1351,14 +2329,22 @@ mod listing_tests {
 
     #[test]
     fn listing_lists_entry_names() {
-        let html = render_directory_listing("/", &entries(&[("a.txt", false), ("docs", true)]));
+        let html = render_directory_listing(
+            "/",
+            &entries(&[("a.txt", false), ("docs", true)]),
+            ListingSort::default(),
+        );
         assert!(html.contains("a.txt"), "should list a.txt, got:\n{html}");
         assert!(html.contains("docs"), "should list docs, got:\n{html}");
     }
 
     #[test]
     fn listing_marks_directories() {
-        let html = render_directory_listing("/", &entries(&[("a.txt", false), ("docs", true)]));
+        let html = render_directory_listing(
+            "/",
+            &entries(&[("a.txt", false), ("docs", true)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("docs/"),
             "directory should be marked with a trailing slash, got:\n{html}"
@@ -1371,7 +2357,11 @@ mod listing_tests {
 
     #[test]
     fn listing_html_escapes_names() {
-        let html = render_directory_listing("/", &entries(&[("a<b>.txt", false)]));
+        let html = render_directory_listing(
+            "/",
+            &entries(&[("a<b>.txt", false)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("a&lt;b&gt;.txt"),
             "name should be HTML-escaped, got:\n{html}"
@@ -1384,7 +2374,8 @@ mod listing_tests {
 
     #[test]
     fn root_listing_has_no_parent_link() {
-        let html = render_directory_listing("/", &entries(&[("a.txt", false)]));
+        let html =
+            render_directory_listing("/", &entries(&[("a.txt", false)]), ListingSort::default());
         assert!(
             !html.contains(">../<"),
             "root listing must not include a parent link, got:\n{html}"
@@ -1393,7 +2384,11 @@ mod listing_tests {
 
     #[test]
     fn subdir_listing_has_parent_link() {
-        let html = render_directory_listing("/sub/", &entries(&[("a.txt", false)]));
+        let html = render_directory_listing(
+            "/sub/",
+            &entries(&[("a.txt", false)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains(">../<"),
             "subdir listing should include a `../` parent link, got:\n{html}"
@@ -1406,7 +2401,11 @@ mod listing_tests {
 
     #[test]
     fn nested_listing_parent_points_one_level_up() {
-        let html = render_directory_listing("/a/b/", &entries(&[("c.txt", false)]));
+        let html = render_directory_listing(
+            "/a/b/",
+            &entries(&[("c.txt", false)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("href=\"/a/\""),
             "nested parent link should point to /a/, got:\n{html}"
@@ -1415,7 +2414,11 @@ mod listing_tests {
 
     #[test]
     fn entry_hrefs_are_absolute() {
-        let html = render_directory_listing("/sub/", &entries(&[("c.txt", false)]));
+        let html = render_directory_listing(
+            "/sub/",
+            &entries(&[("c.txt", false)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("href=\"/sub/c.txt\""),
             "entry href should be absolute, got:\n{html}"
@@ -1428,6 +2431,7 @@ mod listing_tests {
         let html = render_directory_listing(
             "/",
             &entries(&[("日本語.txt", false), ("café", true), ("🎉", true)]),
+            ListingSort::default(),
         );
         assert!(html.contains("café/"), "café/ should appear, got:\n{html}");
         assert!(html.contains("🎉/"), "🎉/ should appear, got:\n{html}");
@@ -1437,7 +2441,8 @@ mod listing_tests {
     fn space_in_name_is_percent_encoded_in_href_only() {
         // A name with a space must be percent-encoded in the href (a raw space in
         // an href is malformed) while the visible link text stays human-readable.
-        let html = render_directory_listing("/", &entries(&[("a b.txt", false)]));
+        let html =
+            render_directory_listing("/", &entries(&[("a b.txt", false)]), ListingSort::default());
         assert!(
             html.contains("href=\"/a%20b.txt\""),
             "href should percent-encode the space, got:\n{html}"
@@ -1456,7 +2461,11 @@ mod listing_tests {
     fn non_ascii_name_is_percent_encoded_in_href() {
         // A non-ASCII name must be percent-encoded in the href (UTF-8 bytes), so
         // `café.txt` -> `caf%C3%A9.txt`, while the text stays readable.
-        let html = render_directory_listing("/", &entries(&[("café.txt", false)]));
+        let html = render_directory_listing(
+            "/",
+            &entries(&[("café.txt", false)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("href=\"/caf%C3%A9.txt\""),
             "href should percent-encode the non-ASCII bytes, got:\n{html}"
@@ -1471,7 +2480,8 @@ mod listing_tests {
     fn directory_with_space_encodes_name_and_keeps_trailing_slash() {
         // A directory name with a space must be encoded with its trailing `/`
         // preserved (the `/` separator stays literal, the space is encoded).
-        let html = render_directory_listing("/", &entries(&[("my dir", true)]));
+        let html =
+            render_directory_listing("/", &entries(&[("my dir", true)]), ListingSort::default());
         assert!(
             html.contains("href=\"/my%20dir/\""),
             "directory href should encode the space and keep the trailing slash, got:\n{html}"
@@ -1482,7 +2492,11 @@ mod listing_tests {
     fn plain_ascii_hrefs_round_trip_unchanged() {
         // Plain ASCII names must be byte-for-byte unchanged so existing links keep
         // working: `/sub/c.txt` stays `/sub/c.txt`, `/docs/` stays `/docs/`.
-        let html = render_directory_listing("/sub/", &entries(&[("c.txt", false), ("docs", true)]));
+        let html = render_directory_listing(
+            "/sub/",
+            &entries(&[("c.txt", false), ("docs", true)]),
+            ListingSort::default(),
+        );
         assert!(
             html.contains("href=\"/sub/c.txt\""),
             "plain ASCII file href should be unchanged, got:\n{html}"
@@ -1627,3 +2641,356 @@ mod confinement_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::{is_compressible, negotiate_encoding, Encoding};
+
+    #[test]
+    fn brotli_is_preferred_on_a_tie() {
+        assert_eq!(
+            negotiate_encoding("gzip, deflate, br"),
+            Some(Encoding::Brotli)
+        );
+    }
+
+    #[test]
+    fn gzip_alone_is_used() {
+        assert_eq!(negotiate_encoding("gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate_encoding("x-gzip"), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn higher_q_wins() {
+        assert_eq!(
+            negotiate_encoding("br;q=0.5, gzip;q=0.9"),
+            Some(Encoding::Gzip)
+        );
+    }
+
+    #[test]
+    fn zero_q_refuses_a_coding() {
+        assert_eq!(negotiate_encoding("br;q=0, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate_encoding("br;q=0, gzip;q=0"), None);
+    }
+
+    #[test]
+    fn wildcard_covers_unlisted_codings() {
+        assert_eq!(negotiate_encoding("*"), Some(Encoding::Brotli));
+        assert_eq!(negotiate_encoding("br;q=0, *"), Some(Encoding::Gzip));
+        assert_eq!(negotiate_encoding("*;q=0"), None);
+    }
+
+    #[test]
+    fn unknown_or_empty_header_is_identity() {
+        assert_eq!(negotiate_encoding(""), None);
+        assert_eq!(negotiate_encoding("identity"), None);
+        assert_eq!(negotiate_encoding("deflate, zstd"), None);
+    }
+
+    #[test]
+    fn textual_types_are_compressible() {
+        assert!(is_compressible("text/html; charset=utf-8"));
+        assert!(is_compressible("text/plain; charset=utf-8"));
+        assert!(is_compressible("application/json"));
+        assert!(is_compressible("image/svg+xml"));
+    }
+
+    #[test]
+    fn compressed_and_binary_types_are_not() {
+        assert!(!is_compressible("image/png"));
+        assert!(!is_compressible("video/mp4"));
+        assert!(!is_compressible("application/gzip"));
+        assert!(!is_compressible("application/octet-stream"));
+    }
+}
+
+#[cfg(test)]
+mod conditional_tests {
+    use super::{entity_tag, if_range_matches, is_not_modified, Encoding};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// 2026-01-01T00:00:00.5Z: a sub-second part that an HTTP date drops.
+    fn modified() -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(1_767_225_600_500)
+    }
+
+    const MODIFIED_DATE: &str = "Thu, 01 Jan 2026 00:00:00 GMT";
+
+    #[test]
+    fn etag_changes_with_size_time_and_encoding() {
+        let base = entity_tag(10, modified(), None);
+        assert!(base.starts_with('"') && base.ends_with('"'));
+        assert_ne!(base, entity_tag(11, modified(), None));
+        assert_ne!(
+            base,
+            entity_tag(10, modified() + Duration::from_nanos(1), None)
+        );
+        assert_ne!(base, entity_tag(10, modified(), Some(Encoding::Gzip)));
+        assert_ne!(
+            entity_tag(10, modified(), Some(Encoding::Gzip)),
+            entity_tag(10, modified(), Some(Encoding::Brotli))
+        );
+    }
+
+    #[test]
+    fn matching_if_none_match_is_not_modified() {
+        let etag = entity_tag(10, modified(), None);
+        assert!(is_not_modified(Some(&etag), None, &etag, modified()));
+        assert!(is_not_modified(
+            Some(&format!("\"other\", W/{etag}")),
+            None,
+            &etag,
+            modified()
+        ));
+        assert!(is_not_modified(Some("*"), None, &etag, modified()));
+    }
+
+    #[test]
+    fn stale_if_none_match_wins_over_a_matching_date() {
+        let etag = entity_tag(10, modified(), None);
+        assert!(!is_not_modified(
+            Some("\"stale\""),
+            Some(MODIFIED_DATE),
+            &etag,
+            modified()
+        ));
+    }
+
+    #[test]
+    fn if_modified_since_compares_whole_seconds() {
+        let etag = entity_tag(10, modified(), None);
+        assert!(is_not_modified(
+            None,
+            Some(MODIFIED_DATE),
+            &etag,
+            modified()
+        ));
+        assert!(is_not_modified(
+            None,
+            Some("Fri, 02 Jan 2026 00:00:00 GMT"),
+            &etag,
+            modified()
+        ));
+        assert!(!is_not_modified(
+            None,
+            Some("Wed, 31 Dec 2025 23:59:59 GMT"),
+            &etag,
+            modified()
+        ));
+    }
+
+    #[test]
+    fn unparsable_date_or_no_conditions_is_modified() {
+        let etag = entity_tag(10, modified(), None);
+        assert!(!is_not_modified(None, Some("yesterday"), &etag, modified()));
+        assert!(!is_not_modified(None, None, &etag, modified()));
+    }
+
+    #[test]
+    fn if_range_needs_a_strong_tag_or_the_exact_date() {
+        let etag = entity_tag(10, modified(), None);
+        assert!(if_range_matches(&etag, &etag, modified()));
+        assert!(!if_range_matches(&format!("W/{etag}"), &etag, modified()));
+        assert!(!if_range_matches("\"stale\"", &etag, modified()));
+        assert!(if_range_matches(MODIFIED_DATE, &etag, modified()));
+        assert!(!if_range_matches(
+            "Fri, 02 Jan 2026 00:00:00 GMT",
+            &etag,
+            modified()
+        ));
+        assert!(!if_range_matches("garbage", &etag, modified()));
+    }
+}
+
+#[cfg(test)]
+mod writable_listing_tests {
+    use super::{render_directory_listing, render_writable_listing, ListingEntry, ListingSort};
+
+    fn entry(name: &str, is_dir: bool) -> ListingEntry {
+        ListingEntry {
+            name: name.to_string(),
+            is_dir,
+            size: 0,
+            modified: None,
+        }
+    }
+
+    fn entries() -> Vec<ListingEntry> {
+        vec![entry("logs", true), entry("a b.txt", false)]
+    }
+
+    #[test]
+    fn read_only_listing_has_no_forms() {
+        let html = render_directory_listing("/sub/", &entries(), ListingSort::default());
+        assert!(!html.contains("<form"), "got:\n{html}");
+    }
+
+    #[test]
+    fn writable_listing_has_upload_and_mkdir_forms_posting_to_itself() {
+        let html = render_writable_listing("/my dir", &entries(), ListingSort::default());
+        assert!(
+            html.contains("action=\"/my%20dir/\" enctype=\"multipart/form-data\""),
+            "got:\n{html}"
+        );
+        assert!(html.contains("name=\"mkdir\""), "got:\n{html}");
+        // The checkbox must precede the file input so it is submitted first.
+        let overwrite = html.find("name=\"overwrite\"").expect("overwrite box");
+        let file = html.find("type=\"file\"").expect("file input");
+        assert!(overwrite < file, "got:\n{html}");
+    }
+
+    #[test]
+    fn writable_listing_has_an_escaped_delete_button_per_entry() {
+        let html = render_writable_listing("/", &[entry("<b>.txt", false)], ListingSort::default());
+        assert!(
+            html.contains("name=\"delete\" value=\"&lt;b&gt;.txt\""),
+            "got:\n{html}"
+        );
+        assert_eq!(html.matches("name=\"delete\"").count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod listing_sort_tests {
+    use super::{
+        format_size, render_directory_listing, sort_entries, ListingEntry, ListingSort, SortKey,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn entry(name: &str, is_dir: bool, size: u64, secs: u64) -> ListingEntry {
+        ListingEntry {
+            name: name.to_string(),
+            is_dir,
+            size,
+            modified: Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+    }
+
+    fn names(entries: &[ListingEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn query_selects_key_and_order() {
+        assert_eq!(ListingSort::from_query(""), ListingSort::default());
+        assert_eq!(
+            ListingSort::from_query("sort=size&order=desc"),
+            ListingSort {
+                key: SortKey::Size,
+                descending: true
+            }
+        );
+        assert_eq!(
+            ListingSort::from_query("order=asc&sort=modified"),
+            ListingSort {
+                key: SortKey::Modified,
+                descending: false
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_query_values_keep_the_default() {
+        assert_eq!(
+            ListingSort::from_query("sort=colour&order=up&x=1"),
+            ListingSort::default()
+        );
+    }
+
+    #[test]
+    fn directories_come_first_in_every_order() {
+        let mut entries = vec![
+            entry("big.bin", false, 900, 1),
+            entry("zdir", true, 0, 5),
+            entry("a.txt", false, 10, 9),
+        ];
+        sort_entries(
+            &mut entries,
+            ListingSort {
+                key: SortKey::Size,
+                descending: true,
+            },
+        );
+        assert_eq!(names(&entries), ["zdir", "big.bin", "a.txt"]);
+    }
+
+    #[test]
+    fn sorts_by_modified_with_ties_broken_by_name() {
+        let mut entries = vec![
+            entry("c", false, 1, 7),
+            entry("b", false, 1, 3),
+            entry("a", false, 1, 7),
+        ];
+        sort_entries(
+            &mut entries,
+            ListingSort {
+                key: SortKey::Modified,
+                descending: false,
+            },
+        );
+        assert_eq!(names(&entries), ["b", "a", "c"]);
+        sort_entries(
+            &mut entries,
+            ListingSort {
+                key: SortKey::Modified,
+                descending: true,
+            },
+        );
+        assert_eq!(names(&entries), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn sizes_are_human_readable() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn listing_shows_size_and_modified_time() {
+        let html = render_directory_listing(
+            "/",
+            &[entry("docs", true, 0, 0), entry("a.txt", false, 2048, 0)],
+            ListingSort::default(),
+        );
+        assert!(html.contains(">2.0 KiB<"), "got:\n{html}");
+        assert!(
+            html.contains(">Thu, 01 Jan 1970 00:00:00 GMT<"),
+            "got:\n{html}"
+        );
+        // A directory's size is not meaningful, so it shows a dash.
+        assert!(
+            html.contains("docs/</a></td><td class=\"size\">-<"),
+            "got:\n{html}"
+        );
+    }
+
+    #[test]
+    fn current_column_link_flips_the_order() {
+        let html = render_directory_listing("/", &[], ListingSort::default());
+        assert!(
+            html.contains("href=\"?sort=name&amp;order=desc\">Name</a> ▲"),
+            "got:\n{html}"
+        );
+        assert!(
+            html.contains("href=\"?sort=size&amp;order=asc\">Size</a><"),
+            "got:\n{html}"
+        );
+
+        let html = render_directory_listing(
+            "/",
+            &[],
+            ListingSort {
+                key: SortKey::Size,
+                descending: true,
+            },
+        );
+        assert!(
+            html.contains("href=\"?sort=size&amp;order=asc\">Size</a> ▼"),
+            "got:\n{html}"
+        );
+    }
+}
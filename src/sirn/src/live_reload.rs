@@ -0,0 +1,427 @@
+//! Live reload: a watcher over the served tree and the Server-Sent Events
+//! stream that tells open pages to reload when it changes.
+
+use crate::plain;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// The URL path of the live-reload event stream.
+///
+/// Pages served with live reload on open a Server-Sent Events connection here
+/// (see [`inject_live_reload`]). The path is answered before the served tree is
+/// consulted, so a file at the same path is shadowed while live reload is on.
+pub const LIVE_RELOAD_PATH: &str = "/__sirn/live-reload";
+
+/// How long the live-reload watcher gathers changes before reloading pages,
+/// so a burst of writes (a build, a `git checkout`) reloads them once.
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+/// The longest the polling fallback waits between rescans of a large tree.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The most live-reload event streams open at once. Each holds a thread for
+/// as long as its page is open, so past this a page gets `503` and simply does
+/// not reload itself.
+const MAX_LIVE_RELOAD_STREAMS: usize = 64;
+
+/// How long a live-reload stream may sit idle before a keep-alive comment is
+/// sent. A write is also how a stream notices its page has gone away.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The change counter shared by the live-reload watcher and every open event
+/// stream.
+///
+/// The watcher [`bump`](Self::bump)s the generation whenever the served tree
+/// changes; each stream waits for it to pass the generation its page was
+/// rendered at. Counting rather than signalling means a change that lands
+/// between rendering a page and the page connecting its stream is not missed.
+#[derive(Debug, Default)]
+pub struct LiveReload {
+    /// The number of changes seen since startup.
+    generation: Mutex<u64>,
+    /// Notified on every bump.
+    changed: Condvar,
+    /// The number of event streams currently open.
+    streams: AtomicUsize,
+}
+
+/// One of the [`MAX_LIVE_RELOAD_STREAMS`] stream slots, given back on drop.
+struct StreamSlot(Arc<LiveReload>);
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        self.0.streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LiveReload {
+    /// A hub at generation `0`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current generation.
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        *self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records a change and wakes every waiting stream.
+    pub fn bump(&self) {
+        *self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) += 1;
+        self.changed.notify_all();
+    }
+
+    /// Waits up to `timeout` for the generation to pass `seen`, then returns
+    /// the current generation (which is still `seen` on a timeout).
+    #[must_use]
+    pub fn wait_past(&self, seen: u64, timeout: Duration) -> u64 {
+        let guard = self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = self
+            .changed
+            .wait_timeout_while(guard, timeout, |generation| *generation <= seen)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard
+    }
+
+    /// Claims a stream slot, or `None` when [`MAX_LIVE_RELOAD_STREAMS`] are
+    /// already open.
+    fn open_stream(self: &Arc<Self>) -> Option<StreamSlot> {
+        self.streams
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |open| {
+                (open < MAX_LIVE_RELOAD_STREAMS).then_some(open + 1)
+            })
+            .ok()
+            .map(|_| StreamSlot(Arc::clone(self)))
+    }
+}
+
+/// Inserts the live-reload client into an HTML page.
+///
+/// The script opens an `EventSource` on [`LIVE_RELOAD_PATH`], passing the
+/// `generation` the page was rendered at, and reloads the page on the first
+/// message. It goes just before the last `</body>` (matched
+/// case-insensitively), or at the end of a page that has none.
+#[must_use]
+pub fn inject_live_reload(html: &str, generation: u64) -> String {
+    let script = format!(
+        "<script>new EventSource(\"{LIVE_RELOAD_PATH}?since={generation}\").onmessage=()=>location.reload();</script>"
+    );
+    // ASCII lowercasing keeps every byte offset, so the match indexes `html`.
+    let body_end = html
+        .to_ascii_lowercase()
+        .rfind("</body")
+        .and_then(|at| html.split_at_checked(at));
+    match body_end {
+        Some((before, after)) => format!("{before}{script}\n{after}"),
+        None => format!("{html}{script}\n"),
+    }
+}
+
+/// What the live-reload watcher compares between scans: every file and
+/// directory under the root, with a file's length and modification time.
+type TreeSnapshot = BTreeMap<PathBuf, Option<(u64, Option<SystemTime>)>>;
+
+/// Records every entry under `dir` into `snapshot`.
+///
+/// Directories are recorded without metadata, so a created or removed one
+/// counts as a change but a directory's own timestamp (which moves whenever an
+/// upload is staged inside it) does not. Hidden entries, such as `.git`, are
+/// skipped, as are symlinked directories' contents, so a link cannot make the
+/// walk loop. Unreadable entries are skipped.
+fn snapshot_tree(dir: &Path, snapshot: &mut TreeSnapshot) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|ft| ft.is_dir()) {
+            snapshot_tree(&path, snapshot);
+            snapshot.insert(path, None);
+        } else if let Ok(metadata) = std::fs::metadata(&path).or_else(|_| entry.metadata()) {
+            let stamp = (!metadata.is_dir()).then(|| (metadata.len(), metadata.modified().ok()));
+            snapshot.insert(path, stamp);
+        }
+    }
+}
+
+/// Spawns the live-reload watcher on its own thread.
+///
+/// Changes under `root` are reported by the platform's file notifications
+/// (see [`watch_tree`]), which are set up before this returns, so a change made
+/// right after is seen. Notifications are gathered for [`WATCH_INTERVAL`] and
+/// then bump `hub` once. Where notifications cannot be set up, such as past
+/// the system's limit on watches, the thread falls back to rescanning the tree
+/// (see [`poll_tree`]). Like [`crate::spawn_monitor`], the thread exits
+/// promptly when `shutdown` is signalled or its sender is dropped. Returns the
+/// join handle so the caller can wait for a clean shutdown.
+#[must_use]
+pub fn spawn_watcher(
+    root: PathBuf,
+    hub: Arc<LiveReload>,
+    shutdown: Receiver<()>,
+) -> JoinHandle<()> {
+    let (changed_tx, changed) = std::sync::mpsc::channel();
+    let watcher = watch_tree(&root, changed_tx);
+    std::thread::spawn(move || {
+        let Ok(_watcher) = watcher else {
+            poll_tree(&root, &hub, &shutdown);
+            return;
+        };
+        loop {
+            match shutdown.recv_timeout(WATCH_INTERVAL) {
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            if changed.try_iter().count() > 0 {
+                hub.bump();
+            }
+        }
+    })
+}
+
+/// Watches everything under `root`, sending on `changed` for every
+/// notification about an entry that is not hidden.
+///
+/// Hidden entries, such as `.git` or an upload being staged, are skipped like
+/// in [`snapshot_tree`], and so is a file merely being opened, or serving a
+/// page would reload it.
+fn watch_tree(
+    root: &Path,
+    changed: std::sync::mpsc::Sender<()>,
+) -> notify::Result<notify::RecommendedWatcher> {
+    use notify::Watcher as _;
+
+    let prefix = root.to_path_buf();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+        if matches!(event.kind, notify::EventKind::Access(_)) {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|path| !is_hidden_under(&prefix, path))
+        {
+            let _ = changed.send(());
+        }
+    })?;
+    watcher.watch(root, notify::RecursiveMode::Recursive)?;
+    Ok(watcher)
+}
+
+/// Whether `path`, or any directory between `root` and it, is hidden.
+fn is_hidden_under(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|part| part.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Rescans everything under `root` (see [`snapshot_tree`]) and bumps `hub`
+/// when anything was added, removed, or modified since the previous scan,
+/// until `shutdown` is signalled or its sender is dropped.
+///
+/// Scans are [`WATCH_INTERVAL`] apart for every thousand entries, up to
+/// [`MAX_POLL_INTERVAL`], so a large tree is not rescanned flat out.
+fn poll_tree(root: &Path, hub: &LiveReload, shutdown: &Receiver<()>) {
+    let mut previous = TreeSnapshot::new();
+    snapshot_tree(root, &mut previous);
+    loop {
+        let thousands = u32::try_from(previous.len() / 1000 + 1).unwrap_or(u32::MAX);
+        let interval = WATCH_INTERVAL
+            .saturating_mul(thousands)
+            .min(MAX_POLL_INTERVAL);
+        match shutdown.recv_timeout(interval) {
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        let mut current = TreeSnapshot::new();
+        snapshot_tree(root, &mut current);
+        if current != previous {
+            hub.bump();
+            previous = current;
+        }
+    }
+}
+
+/// Answers a request on [`LIVE_RELOAD_PATH`] with an event stream.
+///
+/// The stream is written on its own thread so an open page never ties up a
+/// worker. It sends `data: reload` as soon as the hub's generation passes the
+/// `since` query parameter (the current generation when absent), and a comment
+/// every [`KEEPALIVE_INTERVAL`] otherwise; the thread ends when a write fails
+/// because the page has gone away. With [`MAX_LIVE_RELOAD_STREAMS`] already
+/// open, the request gets `503` instead.
+pub(crate) fn respond_events(
+    hub: &Arc<LiveReload>,
+    query: &str,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let Some(slot) = hub.open_stream() else {
+        return request.respond(plain(503, "too many live-reload pages are open"));
+    };
+    let since = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("since="))
+        .and_then(|since| since.parse().ok())
+        .unwrap_or_else(|| hub.generation());
+    std::thread::spawn(move || {
+        // `tiny_http` buffers a body of unknown length, which would hold every
+        // event back, so the stream bypasses its response writer.
+        let mut writer = request.into_writer();
+        let _ = stream_events(&slot.0, since, &mut writer);
+    });
+    Ok(())
+}
+
+/// Writes the event stream behind [`respond_events`] until a write fails.
+fn stream_events(hub: &LiveReload, since: u64, writer: &mut dyn Write) -> std::io::Result<()> {
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+    writer.flush()?;
+    let mut seen = since;
+    loop {
+        let generation = hub.wait_past(seen, KEEPALIVE_INTERVAL);
+        if generation > seen {
+            seen = generation;
+            writer.write_all(b"data: reload\n\n")?;
+        } else {
+            writer.write_all(b": keep-alive\n\n")?;
+        }
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod live_reload_tests {
+    use super::{
+        inject_live_reload, is_hidden_under, snapshot_tree, LiveReload, TreeSnapshot,
+        MAX_LIVE_RELOAD_STREAMS,
+    };
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn script_goes_before_the_last_closing_body_tag() {
+        let html = inject_live_reload("<html><BODY>hi</Body></html>", 3);
+        let script = html.find("<script>").expect("script injected");
+        assert!(
+            script < html.find("</Body>").expect("body kept"),
+            "got: {html}"
+        );
+        assert!(html.contains("/__sirn/live-reload?since=3"), "got: {html}");
+    }
+
+    #[test]
+    fn page_without_body_gets_the_script_appended() {
+        let html = inject_live_reload("<p>fragment</p>", 0);
+        assert!(html.starts_with("<p>fragment</p><script>"), "got: {html}");
+    }
+
+    #[test]
+    fn multibyte_text_before_the_body_tag_is_kept_whole() {
+        let html = inject_live_reload("<body>日本語 🎉</body>", 1);
+        assert!(html.starts_with("<body>日本語 🎉<script>"), "got: {html}");
+    }
+
+    #[test]
+    fn wait_returns_at_once_when_already_past() {
+        let hub = LiveReload::new();
+        hub.bump();
+        assert_eq!(hub.wait_past(0, Duration::from_secs(60)), 1);
+    }
+
+    #[test]
+    fn wait_times_out_with_the_same_generation() {
+        let hub = LiveReload::new();
+        assert_eq!(hub.wait_past(0, Duration::from_millis(10)), 0);
+    }
+
+    #[test]
+    fn bump_wakes_a_waiting_stream() {
+        let hub = Arc::new(LiveReload::new());
+        let waiter = {
+            let hub = Arc::clone(&hub);
+            std::thread::spawn(move || hub.wait_past(0, Duration::from_secs(60)))
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        hub.bump();
+        assert_eq!(waiter.join().expect("waiter joins"), 1);
+    }
+
+    #[test]
+    fn streams_past_the_cap_are_refused_until_one_closes() {
+        let hub = Arc::new(LiveReload::new());
+        let mut slots: Vec<_> = (0..MAX_LIVE_RELOAD_STREAMS)
+            .map(|_| hub.open_stream().expect("slot under the cap"))
+            .collect();
+        assert!(hub.open_stream().is_none());
+
+        slots.pop();
+        assert!(hub.open_stream().is_some());
+    }
+
+    #[test]
+    fn changes_inside_hidden_directories_are_ignored() {
+        let root = Path::new("/srv/site");
+        assert!(!is_hidden_under(root, Path::new("/srv/site/css/site.css")));
+        assert!(!is_hidden_under(root, root));
+        assert!(is_hidden_under(root, Path::new("/srv/site/.git/index")));
+        assert!(is_hidden_under(
+            root,
+            Path::new("/srv/site/.sirn-upload-1-0")
+        ));
+        // Only the part under the root counts
+        assert!(!is_hidden_under(
+            Path::new("/home/me/.www"),
+            Path::new("/home/me/.www/index.html")
+        ));
+    }
+
+    fn snapshot(dir: &TempDir) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::new();
+        snapshot_tree(dir.path(), &mut snapshot);
+        snapshot
+    }
+
+    #[test]
+    fn snapshot_sees_nested_changes_but_not_hidden_entries() {
+        let dir = TempDir::new().expect("temp dir");
+        std::fs::create_dir(dir.path().join("css")).expect("mkdir");
+        std::fs::write(dir.path().join("css/site.css"), "a{}").expect("write");
+        let before = snapshot(&dir);
+        assert_eq!(before.len(), 2);
+
+        std::fs::create_dir(dir.path().join(".git")).expect("mkdir");
+        std::fs::write(dir.path().join(".git/HEAD"), "ref").expect("write");
+        assert_eq!(snapshot(&dir), before);
+
+        std::fs::write(dir.path().join("css/site.css"), "a{color:red}").expect("write");
+        assert_ne!(snapshot(&dir), before);
+    }
+}
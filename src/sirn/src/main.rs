@@ -29,6 +29,50 @@ struct Cli {
     /// Print the port-derivation source at startup.
     #[arg(short, long)]
     verbose: bool,
+
+    /// Directory mode only: accept uploads (PUT or the listing's form), MKCOL, and DELETE under the served root. Existing files are only replaced with ?overwrite=1.
+    #[arg(long)]
+    writable: bool,
+
+    /// Largest upload accepted with --writable, e.g. 100M or 2G.
+    #[arg(long, value_name = "SIZE", value_parser = sirn::parse_size, requires = "writable")]
+    max_upload: Option<u64>,
+
+    /// Directory mode only: serve a directory's index.html instead of its listing.
+    #[arg(long)]
+    index: bool,
+
+    /// Directory mode only: answer a missing path with no file extension with the root index.html, for client-side routers.
+    #[arg(long)]
+    spa: bool,
+
+    /// Directory mode only: reload open pages whenever a file under the served root changes.
+    #[arg(long)]
+    live_reload: bool,
+
+    /// Serve HTTPS with this PEM certificate chain (needs --key).
+    #[arg(long, value_name = "PEM", requires = "key")]
+    cert: Option<PathBuf>,
+
+    /// The unencrypted PEM private key (PKCS#8 or RSA) for --cert.
+    #[arg(long, value_name = "PEM", requires = "cert")]
+    key: Option<PathBuf>,
+
+    /// Serve HTTPS with a self-signed certificate, generated on first use and cached per port.
+    #[arg(long, conflicts_with = "cert")]
+    tls: bool,
+
+    /// Require HTTP basic auth on every request. On the command line the password is visible to other local users (in ps) and kept in shell history; prefer --auth-file or SIRN_AUTH.
+    #[arg(long, value_name = "USER:PASSWORD", value_parser = sirn::parse_credentials, env = "SIRN_AUTH", hide_env_values = true)]
+    auth: Option<sirn::Auth>,
+
+    /// Require HTTP basic auth with the USER:PASSWORD on the first line of this file.
+    #[arg(long, value_name = "FILE", conflicts_with = "auth")]
+    auth_file: Option<PathBuf>,
+
+    /// Require a random access token, printed in the banner URLs, on every request.
+    #[arg(long, conflicts_with_all = ["auth", "auth_file"])]
+    token: bool,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -44,6 +88,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // bind.
     let mode = match decision {
         sirn::ModeDecision::Files => {
+            let directory_only = [
+                ("--writable", cli.writable),
+                ("--index", cli.index),
+                ("--spa", cli.spa),
+                ("--live-reload", cli.live_reload),
+            ];
+            if let Some((flag, _)) = directory_only.iter().find(|(_, set)| *set) {
+                return Err(format!("{flag} needs directory mode: pass a single directory (or nothing, for the current directory) instead of files").into());
+            }
             let routes = sirn::build_routes(&cli.files).map_err(|e| e.to_string())?;
             sirn::ServeMode::Files(Arc::new(routes))
         }
@@ -54,7 +107,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             };
             // Canonicalize so directory-mode path confinement has a stable root.
             let root = root.canonicalize()?;
-            sirn::ServeMode::Directory(Arc::new(root))
+            let writes = cli.writable.then(|| sirn::WriteConfig {
+                max_upload: cli.max_upload.unwrap_or(sirn::DEFAULT_MAX_UPLOAD),
+            });
+            sirn::ServeMode::Directory(Arc::new(sirn::DirectoryConfig {
+                root,
+                writes,
+                index: cli.index,
+                spa: cli.spa,
+                live_reload: cli.live_reload.then(|| Arc::new(sirn::LiveReload::new())),
+            }))
         }
     };
 
@@ -80,15 +142,49 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let bind = cli.bind.unwrap_or_else(|| "127.0.0.1".to_string());
+
+    // Render TLS errors via Display, like the mode errors above. The
+    // self-signed certificate is cached per port, so a project keeps the
+    // same certificate (and the browser exception for it) from run to run.
+    let identity = match (&cli.cert, &cli.key) {
+        (Some(cert), Some(key)) => {
+            Some(sirn::TlsIdentity::load(cert, key).map_err(|e| e.to_string())?)
+        }
+        _ if cli.tls => {
+            let cache = dirs::cache_dir()
+                .ok_or("no cache directory for the self-signed certificate; pass --cert and --key instead")?
+                .join("sirn")
+                .join("tls");
+            Some(sirn::TlsIdentity::self_signed(&cache, port, &bind).map_err(|e| e.to_string())?)
+        }
+        _ => None,
+    };
+    let https = identity.is_some();
+    let fingerprint = identity.as_ref().and_then(sirn::TlsIdentity::fingerprint);
+    let auth = match (cli.auth, &cli.auth_file) {
+        (Some(auth), _) => auth,
+        (None, Some(path)) => sirn::read_credentials(path)?,
+        (None, None) if cli.token => sirn::Auth::Token(sirn::random_token()),
+        (None, None) => sirn::Auth::Open,
+    };
+    let security = sirn::Security {
+        https,
+        auth: &auth,
+        fingerprint: fingerprint.as_deref(),
+    };
+
     // `Server::http` errors as `Box<dyn Error + Send + Sync>`, which does not
     // coerce into our `Box<dyn Error>` via `?`; render it to a String instead.
-    let server = Arc::new(
-        tiny_http::Server::http(format!("{bind}:{port}"))
-            .map_err(|e| format!("failed to bind {bind}:{port}: {e}"))?,
-    );
+    let addr = format!("{bind}:{port}");
+    let server = match identity {
+        Some(identity) => tiny_http::Server::https(addr, identity.into_ssl_config()),
+        None => tiny_http::Server::http(addr),
+    };
+    let server = Arc::new(server.map_err(|e| format!("failed to bind {bind}:{port}: {e}"))?);
 
-    // Print the mode-appropriate banner and, in files mode only, spawn the
-    // background availability monitor (it stats the served files).
+    // Print the mode-appropriate banner and spawn the background poller: the
+    // availability monitor in files mode (it stats the served files), or the
+    // live-reload watcher in directory mode when asked for.
     let monitor = match &mode {
         sirn::ServeMode::Files(routes) => {
             println!(
@@ -98,7 +194,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     &bind,
                     port,
                     source_desc.as_deref(),
-                    routes
+                    routes,
+                    &security
                 )
             );
             let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel();
@@ -107,7 +204,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 sirn::spawn_monitor(Arc::clone(routes), shutdown_rx),
             ))
         }
-        sirn::ServeMode::Directory(root) => {
+        sirn::ServeMode::Directory(config) => {
             println!(
                 "{}",
                 sirn::directory_banner(
@@ -115,20 +212,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     &bind,
                     port,
                     source_desc.as_deref(),
-                    root
+                    &config.root,
+                    &security
                 )
             );
-            None
+            if let Some(writes) = config.writes {
+                println!(
+                    "Writable: uploads up to {} bytes, directory creation, and deletes are allowed",
+                    writes.max_upload
+                );
+            }
+            config.live_reload.as_ref().map(|hub| {
+                println!("Live reload: open pages reload when files under the root change");
+                let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel();
+                (
+                    shutdown_tx,
+                    sirn::spawn_watcher(config.root.clone(), Arc::clone(hub), shutdown_rx),
+                )
+            })
         }
     };
 
-    let handles = sirn::serve(server, mode, WORKER_THREADS);
+    let handles = sirn::serve(server, mode, auth.clone(), WORKER_THREADS);
     for h in handles {
         let _ = h.join();
     }
 
     // Workers exit only when the server is unblocked; on a clean exit, stop the
-    // monitor too (files mode) and join it so teardown is orderly.
+    // monitor or watcher too and join it so teardown is orderly.
     if let Some((shutdown_tx, monitor)) = monitor {
         drop(shutdown_tx);
         let _ = monitor.join();
@@ -0,0 +1,308 @@
+//! Byte ranges: parsing a `Range` header against a file's length, and the
+//! streamed `multipart/byteranges` body for a request of several ranges.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The most byte ranges a single `Range` header may ask for.
+///
+/// A request for more is served as a plain `200` instead. Without a cap, a
+/// header of thousands of tiny ranges turns one request into thousands of
+/// multipart parts, each with its own headers and seek.
+const MAX_RANGES: usize = 32;
+
+/// The byte ranges a `Range` header asks for, resolved against a file length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable `Range` header: serve the whole file as a `200`.
+    Full,
+    /// One or more satisfiable ranges as inclusive `(first, last)` byte offsets,
+    /// in the order they were requested: serve a `206`.
+    Partial(Vec<(u64, u64)>),
+    /// A well-formed header none of whose ranges overlaps the file: serve a
+    /// `416`.
+    Unsatisfiable,
+}
+
+/// Resolves a `Range` request header against a file of `len` bytes.
+///
+/// Understands the `bytes` unit with all three range forms: `first-last`,
+/// `first-` (to the end), and `-suffix` (the final `suffix` bytes). A `last`
+/// past the end of the file is clamped to it. Ranges that start at or past the
+/// end are dropped; if that leaves none, the result is
+/// [`RangeRequest::Unsatisfiable`].
+///
+/// Anything this does not understand — another unit, a malformed range, a
+/// `last` before its `first`, or more than [`MAX_RANGES`] ranges — yields
+/// [`RangeRequest::Full`]. RFC 9110 lets a server ignore a `Range` header it
+/// cannot use, and the whole file is always a correct answer.
+///
+/// This function never panics.
+#[must_use]
+pub fn parse_range(header: &str, len: u64) -> RangeRequest {
+    let Some((unit, specs)) = header.split_once('=') else {
+        return RangeRequest::Full;
+    };
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return RangeRequest::Full;
+    }
+
+    let mut ranges = Vec::new();
+    let mut requested = 0;
+    // Empty list elements (`bytes=0-1,,5-6`) are allowed by the list syntax.
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        requested += 1;
+        if requested > MAX_RANGES {
+            return RangeRequest::Full;
+        }
+        let Some((first, last)) = spec.split_once('-') else {
+            return RangeRequest::Full;
+        };
+        let (first, last) = (first.trim(), last.trim());
+
+        if first.is_empty() {
+            // `-suffix`: the final `suffix` bytes. A zero suffix, or any suffix
+            // of an empty file, selects nothing.
+            let Ok(suffix) = last.parse::<u64>() else {
+                return RangeRequest::Full;
+            };
+            if suffix > 0 && len > 0 {
+                ranges.push((len.saturating_sub(suffix), len - 1));
+            }
+            continue;
+        }
+
+        let Ok(first) = first.parse::<u64>() else {
+            return RangeRequest::Full;
+        };
+        let last = if last.is_empty() {
+            u64::MAX
+        } else {
+            match last.parse::<u64>() {
+                Ok(last) if last >= first => last,
+                _ => return RangeRequest::Full,
+            }
+        };
+        if first < len {
+            ranges.push((first, last.min(len - 1)));
+        }
+    }
+
+    match (requested, ranges.is_empty()) {
+        (0, _) => RangeRequest::Full,
+        (_, true) => RangeRequest::Unsatisfiable,
+        (_, false) => RangeRequest::Partial(ranges),
+    }
+}
+
+/// A `multipart/byteranges` body streamed straight from the file.
+///
+/// The body is a sequence of pieces — each part's boundary and headers, then
+/// its span of the file — read one after another, so even a request for many
+/// large ranges of a big video holds only one read buffer in memory. Every
+/// piece's length is known up front, so the response carries an exact
+/// `Content-Length`.
+pub(crate) struct ByteRanges {
+    file: std::fs::File,
+    pub(crate) boundary: String,
+    pieces: std::collections::VecDeque<Piece>,
+}
+
+/// One piece of a [`ByteRanges`] body.
+enum Piece {
+    /// Literal bytes: a boundary line and part headers, or the closing boundary.
+    Text(std::io::Cursor<Vec<u8>>),
+    /// `remaining` bytes of the file starting at `start`. The file is seeked to
+    /// `start` when the span is first read, then `start` is cleared.
+    Span { start: Option<u64>, remaining: u64 },
+}
+
+impl ByteRanges {
+    /// Lays out the parts for `ranges` of a file of `len` bytes.
+    pub(crate) fn new(
+        file: std::fs::File,
+        ranges: &[(u64, u64)],
+        len: u64,
+        content_type: &str,
+    ) -> Self {
+        let boundary = multipart_boundary();
+        let mut pieces = std::collections::VecDeque::new();
+        for (index, &(first, last)) in ranges.iter().enumerate() {
+            // The CRLF before a boundary belongs to the delimiter, so every part
+            // but the first starts by ending the previous part's data.
+            let separator = if index == 0 { "" } else { "\r\n" };
+            let part_headers = format!(
+                "{separator}--{boundary}\r\nContent-Type: {content_type}\r\nContent-Range: bytes {first}-{last}/{len}\r\n\r\n"
+            );
+            pieces.push_back(Piece::Text(std::io::Cursor::new(part_headers.into_bytes())));
+            pieces.push_back(Piece::Span {
+                start: Some(first),
+                remaining: last - first + 1,
+            });
+        }
+        let closing = format!("\r\n--{boundary}--\r\n");
+        pieces.push_back(Piece::Text(std::io::Cursor::new(closing.into_bytes())));
+        Self {
+            file,
+            boundary,
+            pieces,
+        }
+    }
+
+    /// The total length of the body in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.get_ref().len() as u64,
+                Piece::Span { remaining, .. } => *remaining,
+            })
+            .sum()
+    }
+}
+
+impl Read for ByteRanges {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while let Some(piece) = self.pieces.front_mut() {
+            let n = match piece {
+                Piece::Text(text) => text.read(buf)?,
+                Piece::Span { start, remaining } => {
+                    if let Some(start) = start.take() {
+                        self.file.seek(SeekFrom::Start(start))?;
+                    }
+                    let want = usize::try_from(*remaining).map_or(buf.len(), |r| r.min(buf.len()));
+                    let n = self.file.read(&mut buf[..want])?;
+                    if n == 0 && want > 0 {
+                        // The file shrank after its length was advertised; ending
+                        // early would leave the client waiting for bytes that
+                        // never come, so fail the response instead.
+                        return Err(std::io::ErrorKind::UnexpectedEof.into());
+                    }
+                    *remaining -= n as u64;
+                    n
+                }
+            };
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.pieces.pop_front();
+        }
+        Ok(0)
+    }
+}
+
+/// A multipart boundary that is all but certain not to occur in the file.
+///
+/// RFC 2046 only asks that the boundary not appear in the body; the current
+/// time in nanoseconds plus a per-process counter makes a collision with file
+/// contents practically impossible without pulling in a random number crate.
+fn multipart_boundary() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("sirn-{nanos:x}-{count:x}")
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::{parse_range, RangeRequest};
+
+    #[test]
+    fn bounded_range_is_inclusive() {
+        assert_eq!(
+            parse_range("bytes=0-9", 100),
+            RangeRequest::Partial(vec![(0, 9)])
+        );
+    }
+
+    #[test]
+    fn open_ended_range_runs_to_the_end() {
+        assert_eq!(
+            parse_range("bytes=90-", 100),
+            RangeRequest::Partial(vec![(90, 99)])
+        );
+    }
+
+    #[test]
+    fn suffix_range_selects_the_final_bytes() {
+        assert_eq!(
+            parse_range("bytes=-10", 100),
+            RangeRequest::Partial(vec![(90, 99)])
+        );
+        // A suffix longer than the file selects the whole file.
+        assert_eq!(
+            parse_range("bytes=-500", 100),
+            RangeRequest::Partial(vec![(0, 99)])
+        );
+    }
+
+    #[test]
+    fn last_past_the_end_is_clamped() {
+        assert_eq!(
+            parse_range("bytes=50-1000", 100),
+            RangeRequest::Partial(vec![(50, 99)])
+        );
+    }
+
+    #[test]
+    fn multiple_ranges_keep_request_order() {
+        assert_eq!(
+            parse_range("bytes=50-59, 0-9,,-5", 100),
+            RangeRequest::Partial(vec![(50, 59), (0, 9), (95, 99)])
+        );
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_dropped() {
+        assert_eq!(
+            parse_range("bytes=0-9,200-300", 100),
+            RangeRequest::Partial(vec![(0, 9)])
+        );
+    }
+
+    #[test]
+    fn only_out_of_bounds_ranges_are_unsatisfiable() {
+        assert_eq!(parse_range("bytes=100-", 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), RangeRequest::Unsatisfiable);
+    }
+
+    #[test]
+    fn malformed_or_foreign_headers_serve_the_whole_file() {
+        for header in [
+            "items=0-9",
+            "bytes",
+            "bytes=",
+            "bytes=abc",
+            "bytes=9-0",
+            "bytes=0-9,x-y",
+            "bytes=--5",
+        ] {
+            assert_eq!(parse_range(header, 100), RangeRequest::Full, "{header}");
+        }
+    }
+
+    #[test]
+    fn unit_is_case_insensitive() {
+        assert_eq!(
+            parse_range("Bytes=0-0", 100),
+            RangeRequest::Partial(vec![(0, 0)])
+        );
+    }
+
+    #[test]
+    fn too_many_ranges_serve_the_whole_file() {
+        let many = (0..=super::MAX_RANGES)
+            .map(|i| format!("{i}-{i}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            parse_range(&format!("bytes={many}"), 100),
+            RangeRequest::Full
+        );
+    }
+}
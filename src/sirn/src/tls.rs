@@ -0,0 +1,276 @@
+//! HTTPS identities: a certificate and key loaded from disk, or a
+//! self-signed pair generated and cached per port.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A PEM certificate chain and private key for serving HTTPS.
+#[derive(Clone)]
+pub struct TlsIdentity {
+    /// The PEM certificate chain, leaf first.
+    pub certificate: Vec<u8>,
+    /// The PEM private key (PKCS#8 or PKCS#1 RSA, unencrypted).
+    pub private_key: Vec<u8>,
+}
+
+/// Why a certificate and key could not be loaded or generated.
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    /// A certificate or key file could not be read.
+    #[error("cannot read '{}': {source}", path.display())]
+    Read {
+        /// The file that could not be read.
+        path: PathBuf,
+        /// The underlying IO error.
+        source: std::io::Error,
+    },
+    /// A generated certificate could not be cached.
+    #[error("cannot write '{}': {source}", path.display())]
+    Write {
+        /// The file or directory that could not be written.
+        path: PathBuf,
+        /// The underlying IO error.
+        source: std::io::Error,
+    },
+    /// The certificate file holds no PEM `CERTIFICATE` block.
+    #[error("'{}' holds no PEM certificate", .0.display())]
+    NoCertificate(PathBuf),
+    /// The key file holds no unencrypted PKCS#8 or RSA PEM private key.
+    #[error("'{}' holds no unencrypted PKCS#8 (`BEGIN PRIVATE KEY`) or RSA (`BEGIN RSA PRIVATE KEY`) private key", .0.display())]
+    NoPrivateKey(PathBuf),
+    /// The self-signed certificate could not be generated.
+    #[error("cannot generate a self-signed certificate: {0}")]
+    Generate(#[from] rcgen::Error),
+}
+
+impl TlsIdentity {
+    /// Reads a PEM certificate chain from `cert` and its private key from `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Read`] if either file cannot be read, and
+    /// [`TlsError::NoCertificate`] or [`TlsError::NoPrivateKey`] if it does not
+    /// hold what the HTTPS listener accepts. Checking here turns a bad file
+    /// into a startup error rather than a panic inside the listener.
+    pub fn load(cert: &Path, key: &Path) -> Result<Self, TlsError> {
+        let read = |path: &Path| {
+            std::fs::read(path).map_err(|source| TlsError::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        };
+        let identity = Self {
+            certificate: read(cert)?,
+            private_key: read(key)?,
+        };
+        if pem_block(&identity.certificate, "CERTIFICATE").is_none() {
+            return Err(TlsError::NoCertificate(cert.to_path_buf()));
+        }
+        if pem_block(&identity.private_key, "PRIVATE KEY").is_none()
+            && pem_block(&identity.private_key, "RSA PRIVATE KEY").is_none()
+        {
+            return Err(TlsError::NoPrivateKey(key.to_path_buf()));
+        }
+        Ok(identity)
+    }
+
+    /// The self-signed identity for serving on `port`, cached in `cache_dir`.
+    ///
+    /// The certificate and key live in `cache_dir/<port>/`, so a project,
+    /// whose `portplz`-derived port is stable, keeps the same certificate from
+    /// run to run and a browser's exception for it keeps working. The pair is
+    /// generated for `localhost`, `127.0.0.1`, `::1`, and `bind` when it names
+    /// a specific host, and the key is written readable only by its owner.
+    /// The names are kept beside the pair in `names.txt`, and a cached pair
+    /// made for other names, as for another `bind`, is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Generate`] if generation fails, [`TlsError::Write`]
+    /// if the pair cannot be cached, and the errors of [`Self::load`] if a
+    /// cached pair cannot be read back.
+    pub fn self_signed(cache_dir: &Path, port: u16, bind: &str) -> Result<Self, TlsError> {
+        let dir = cache_dir.join(port.to_string());
+        let (cert, key) = (dir.join("cert.pem"), dir.join("key.pem"));
+        let names_file = dir.join("names.txt");
+        let mut names = vec![
+            "localhost".to_string(),
+            "127.0.0.1".to_string(),
+            "::1".to_string(),
+        ];
+        let specific = bind
+            .parse::<std::net::IpAddr>()
+            .map_or(true, |ip| !ip.is_unspecified());
+        if specific && !names.iter().any(|name| name == bind) {
+            names.push(bind.to_string());
+        }
+        let names_text = names.join("\n") + "\n";
+        let cached_names = std::fs::read_to_string(&names_file).ok();
+        if cert.is_file() && key.is_file() && cached_names.as_deref() == Some(&*names_text) {
+            return Self::load(&cert, &key);
+        }
+
+        let rcgen::CertifiedKey {
+            cert: generated,
+            key_pair,
+        } = rcgen::generate_simple_self_signed(names)?;
+        let identity = Self {
+            certificate: generated.pem().into_bytes(),
+            private_key: key_pair.serialize_pem().into_bytes(),
+        };
+
+        let write_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| TlsError::Write { path, source }
+        };
+        std::fs::create_dir_all(&dir).map_err(write_error(&dir))?;
+        write_private(&key, &identity.private_key).map_err(write_error(&key))?;
+        std::fs::write(&cert, &identity.certificate).map_err(write_error(&cert))?;
+        // Written last, so a pair cut short is made again next time
+        std::fs::write(&names_file, names_text).map_err(write_error(&names_file))?;
+        Ok(identity)
+    }
+
+    /// The SHA-256 fingerprint of the leaf certificate, as colon-separated
+    /// uppercase hex (the form browsers show), or `None` if it does not decode.
+    #[must_use]
+    pub fn fingerprint(&self) -> Option<String> {
+        use sha2::{Digest, Sha256};
+
+        let der = pem_block(&self.certificate, "CERTIFICATE")?;
+        let digest = Sha256::digest(der);
+        Some(
+            digest
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(":"),
+        )
+    }
+
+    /// The listener configuration for `tiny_http::Server::https`.
+    #[must_use]
+    pub fn into_ssl_config(self) -> tiny_http::SslConfig {
+        tiny_http::SslConfig {
+            certificate: self.certificate,
+            private_key: self.private_key,
+        }
+    }
+}
+
+/// Writes `contents` to a new file at `path` that only its owner can read.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+/// Decodes the first PEM block labelled `label` in `pem`, or `None` if there
+/// is none or its base64 does not decode.
+fn pem_block(pem: &[u8], label: &str) -> Option<Vec<u8>> {
+    use base64::Engine as _;
+
+    let pem = String::from_utf8_lossy(pem);
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+    let (_, rest) = pem.split_once(&begin)?;
+    let (body, _) = rest.split_once(&end)?;
+    let body: String = body.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD.decode(body).ok()
+}
+
+#[cfg(test)]
+mod tls_tests {
+    use super::{TlsError, TlsIdentity};
+    use tempfile::TempDir;
+
+    #[test]
+    fn self_signed_identity_is_cached_per_port() {
+        let cache = TempDir::new().expect("temp dir");
+        let first = TlsIdentity::self_signed(cache.path(), 8443, "0.0.0.0").expect("generate");
+        assert!(cache.path().join("8443/cert.pem").is_file());
+        assert!(cache.path().join("8443/key.pem").is_file());
+
+        let again = TlsIdentity::self_signed(cache.path(), 8443, "0.0.0.0").expect("reload");
+        assert_eq!(again.certificate, first.certificate);
+        assert_eq!(again.private_key, first.private_key);
+
+        let other = TlsIdentity::self_signed(cache.path(), 8444, "0.0.0.0").expect("generate");
+        assert_ne!(other.certificate, first.certificate);
+    }
+
+    #[test]
+    fn self_signed_identity_is_remade_for_another_bind() {
+        let cache = TempDir::new().expect("temp dir");
+        let local = TlsIdentity::self_signed(cache.path(), 8443, "0.0.0.0").expect("generate");
+        // 127.0.0.1 is already covered, so the certificate is kept
+        let same = TlsIdentity::self_signed(cache.path(), 8443, "127.0.0.1").expect("reload");
+        assert_eq!(same.certificate, local.certificate);
+
+        let named = TlsIdentity::self_signed(cache.path(), 8443, "devbox.lan").expect("generate");
+        assert_ne!(named.certificate, local.certificate);
+        let names = std::fs::read_to_string(cache.path().join("8443/names.txt")).expect("names");
+        assert!(names.lines().any(|name| name == "devbox.lan"), "{names}");
+
+        let again = TlsIdentity::self_signed(cache.path(), 8443, "devbox.lan").expect("reload");
+        assert_eq!(again.certificate, named.certificate);
+        let back = TlsIdentity::self_signed(cache.path(), 8443, "0.0.0.0").expect("generate");
+        assert_ne!(back.certificate, named.certificate);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cached_key_is_private_to_its_owner() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let cache = TempDir::new().expect("temp dir");
+        TlsIdentity::self_signed(cache.path(), 8443, "127.0.0.1").expect("generate");
+        let mode = std::fs::metadata(cache.path().join("8443/key.pem"))
+            .expect("key metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn fingerprint_is_colon_separated_sha256() {
+        let cache = TempDir::new().expect("temp dir");
+        let identity = TlsIdentity::self_signed(cache.path(), 8443, "127.0.0.1").expect("generate");
+        let fingerprint = identity.fingerprint().expect("fingerprint");
+        assert_eq!(fingerprint.len(), 32 * 3 - 1, "got {fingerprint}");
+        assert!(fingerprint
+            .split(':')
+            .all(|byte| byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit())));
+    }
+
+    #[test]
+    fn load_rejects_files_that_are_not_pem() {
+        let dir = TempDir::new().expect("temp dir");
+        let junk = dir.path().join("junk.pem");
+        std::fs::write(&junk, "not a certificate").expect("write junk");
+        assert!(matches!(
+            TlsIdentity::load(&junk, &junk),
+            Err(TlsError::NoCertificate(_))
+        ));
+
+        let identity = TlsIdentity::self_signed(dir.path(), 1, "127.0.0.1").expect("generate");
+        let cert = dir.path().join("1/cert.pem");
+        assert!(matches!(
+            TlsIdentity::load(&cert, &cert),
+            Err(TlsError::NoPrivateKey(_))
+        ));
+        let loaded = TlsIdentity::load(&cert, &dir.path().join("1/key.pem")).expect("load");
+        assert_eq!(loaded.certificate, identity.certificate);
+
+        assert!(matches!(
+            TlsIdentity::load(&dir.path().join("missing.pem"), &junk),
+            Err(TlsError::Read { .. })
+        ));
+    }
+}
@@ -0,0 +1,1068 @@
+//! The writable directory mode: resolving where a write lands, and handling
+//! `PUT` uploads, `MKCOL`, `DELETE`, and the listing's upload, create-folder,
+//! and delete forms.
+
+use crate::{
+    decode_path, encode_path, header, plain, request_header, resolve_under_root, PathResolution,
+};
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The default cap on one upload request's body, in bytes (512 MiB).
+pub const DEFAULT_MAX_UPLOAD: u64 = 512 * 1024 * 1024;
+
+/// Limits on writes in a writable directory mode (`--writable`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteConfig {
+    /// The largest request body accepted for an upload, in bytes. A bigger
+    /// upload is refused with `413` and nothing it sent is kept.
+    pub max_upload: u64,
+}
+
+/// The result of resolving a request URL path as the target of a write.
+#[derive(Debug, PartialEq, Eq)]
+pub enum WriteResolution {
+    /// The write lands on entry `name` directly inside `dir`, an existing,
+    /// in-root, canonical directory. `name` is a single path component.
+    Allowed {
+        /// The canonical directory that holds (or will hold) the entry.
+        dir: PathBuf,
+        /// The entry's name within `dir`.
+        name: String,
+    },
+    /// The path escapes the root, names the root itself, or ends in a
+    /// component that cannot be an entry name (`..`, `.`) -> `403`.
+    Forbidden,
+    /// The entry's name is one kept for the server's own staging files
+    /// -> `403`.
+    Reserved,
+    /// The parent directory does not exist (or is not a directory) -> `409`.
+    MissingParent,
+}
+
+/// Resolves the target of an upload, directory creation, or delete, confining
+/// it to `root` exactly as [`resolve_under_root`] confines reads.
+///
+/// The target need not exist yet, so only its parent is resolved — through
+/// [`resolve_under_root`], which rejects `..` traversal and symlinks that lead
+/// out of the root — and must be an existing directory. The final component
+/// must be a plain entry name. A trailing `/` is ignored, so `/new/` names the
+/// entry `new` in `/`.
+///
+/// The write then acts on the entry itself, never on whatever it points to:
+/// `dir` is canonical and `name` has no separators, so `dir.join(name)` is in
+/// the root even when it is a symlink, and replacing or deleting it touches
+/// the link, not its target.
+#[must_use]
+pub fn resolve_write_target(root: &Path, url_path: &str) -> WriteResolution {
+    let trimmed = url_path.trim_end_matches('/');
+    let (parent, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+    if !is_entry_name(name) {
+        return WriteResolution::Forbidden;
+    }
+    if is_reserved_name(name) {
+        return WriteResolution::Reserved;
+    }
+    match resolve_under_root(root, parent) {
+        PathResolution::Allowed(dir) if dir.is_dir() => WriteResolution::Allowed {
+            dir,
+            name: name.to_string(),
+        },
+        PathResolution::Allowed(_) | PathResolution::Missing => WriteResolution::MissingParent,
+        PathResolution::Forbidden => WriteResolution::Forbidden,
+    }
+}
+
+/// Returns true if `name` can name one entry inside a directory: non-empty,
+/// not `.` or `..`, and free of path separators and NUL.
+fn is_entry_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', '\0'])
+}
+
+/// Returns true if `name` could be one of the staging files uploads are
+/// written to, which clients may not create, replace, or delete.
+fn is_reserved_name(name: &str) -> bool {
+    name.starts_with(UPLOAD_PREFIX)
+}
+
+/// The error for a write to a reserved entry name.
+fn reserved_name_error() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        format!("names starting with {UPLOAD_PREFIX} are kept for uploads in progress"),
+    )
+}
+
+/// Parses a byte size like `1048576`, `64K`, `512M`, or `2G` (also `512MB` or
+/// `512MiB`). Suffixes are binary multiples and case-insensitive.
+///
+/// # Errors
+/// Returns a message naming the input when it is not a whole number with an
+/// optional `K`, `M`, `G`, or `T` suffix, or when it overflows a `u64`.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let trimmed = size.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let unit = upper
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+        .trim_start_matches(|c: char| c.is_ascii_digit());
+    let shift = match unit {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(format!("invalid size '{size}': expected e.g. 100M or 2G")),
+    };
+    let digits = upper.trim_end_matches(|c: char| !c.is_ascii_digit());
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1_u64 << shift))
+        .ok_or_else(|| format!("invalid size '{size}': expected e.g. 100M or 2G"))
+}
+
+/// The name prefix of an upload still being received.
+///
+/// An upload is written to a hidden file beside its destination and only
+/// renamed into place once the whole body has arrived, so a half-received
+/// upload never appears under its real name. Listings skip these files.
+pub(crate) const UPLOAD_PREFIX: &str = ".sirn-upload-";
+
+/// The most bytes read from a URL-encoded form (the create-folder and delete
+/// buttons), which only ever carries one short name.
+const MAX_FORM: u64 = 64 * 1024;
+
+/// The most bytes of headers a single multipart part may carry.
+const MAX_PART_HEADERS: usize = 16 * 1024;
+
+/// Returns true for the methods a writable directory mode handles.
+pub(crate) fn is_write_method(method: &tiny_http::Method) -> bool {
+    use tiny_http::Method;
+
+    matches!(method, Method::Put | Method::Post | Method::Delete)
+        || method.as_str().eq_ignore_ascii_case("MKCOL")
+}
+
+/// An empty `204 No Content`.
+fn no_content() -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_data(Vec::new()).with_status_code(204)
+}
+
+/// Returns true if the `?query` of a request URL sets `flag` to a true value
+/// (`1`, `true`, `yes`, or just `?flag`).
+fn query_flag(query: &str, flag: &str) -> bool {
+    query.split('&').any(|pair| {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, "1"));
+        name == flag && matches!(value, "1" | "true" | "yes" | "on")
+    })
+}
+
+/// Handles one write request in a writable directory mode.
+///
+/// - `PUT /dir/name` stores the request body as `name` (`201`, or `204` when
+///   it replaced a file);
+/// - `POST /dir/` takes the listing page's forms: a `multipart/form-data`
+///   upload of one or more files, or a URL-encoded `mkdir=name` or
+///   `delete=name`, answered with a `303` back to the listing;
+/// - `MKCOL /dir/name` creates a directory (`201`);
+/// - `DELETE /dir/name` removes a file or an empty directory (`204`).
+///
+/// Every target resolves through [`resolve_write_target`] (or, for the form's
+/// own directory, [`resolve_under_root`]), so writes are confined exactly like
+/// reads. An existing file is only replaced when the request asks, with
+/// `?overwrite=1` or the form's checkbox; otherwise it is `409`. A body over
+/// [`WriteConfig::max_upload`] is `413`, and nothing from it is kept. A form
+/// posted from another site is `403` (see [`is_cross_site`]).
+pub(crate) fn respond_write(
+    root: &Path,
+    writes: WriteConfig,
+    request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let url_path = decode_path(path);
+    let overwrite = query_flag(query, "overwrite");
+
+    match request.method() {
+        tiny_http::Method::Put => put_file(root, &url_path, overwrite, writes, request),
+        tiny_http::Method::Post => post_form(root, &url_path, overwrite, writes, request),
+        tiny_http::Method::Delete => {
+            let response = match resolve_write_target(root, &url_path) {
+                WriteResolution::Allowed { dir, name } => match remove_entry(&dir.join(&name)) {
+                    Ok(()) => no_content(),
+                    Err(err) => write_error(&err, &name),
+                },
+                WriteResolution::Forbidden => {
+                    plain(403, "refusing to delete outside the served root")
+                }
+                WriteResolution::Reserved => plain(403, &reserved_name_error().to_string()),
+                WriteResolution::MissingParent => plain(404, "no such file or directory"),
+            };
+            request.respond(response)
+        }
+        _ => {
+            let response = match resolve_write_target(root, &url_path) {
+                WriteResolution::Allowed { dir, name } => {
+                    match std::fs::create_dir(dir.join(&name)) {
+                        Ok(()) => plain(201, "created"),
+                        Err(err) => write_error(&err, &name),
+                    }
+                }
+                WriteResolution::Forbidden => {
+                    plain(403, "refusing to write outside the served root")
+                }
+                WriteResolution::Reserved => plain(403, &reserved_name_error().to_string()),
+                WriteResolution::MissingParent => plain(409, "the parent directory does not exist"),
+            };
+            request.respond(response)
+        }
+    }
+}
+
+/// The response for a failed write of entry `name`.
+fn write_error(err: &std::io::Error, name: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    use std::io::ErrorKind;
+
+    match err.kind() {
+        ErrorKind::AlreadyExists => plain(
+            409,
+            &format!("'{name}' already exists; add ?overwrite=1 to replace a file"),
+        ),
+        ErrorKind::NotFound => plain(404, &format!("'{name}' does not exist")),
+        ErrorKind::DirectoryNotEmpty => plain(409, &format!("'{name}' is not empty")),
+        ErrorKind::InvalidData => plain(400, &format!("malformed upload: {err}")),
+        ErrorKind::PermissionDenied => plain(403, &format!("could not write '{name}': {err}")),
+        _ => plain(500, &format!("could not write '{name}': {err}")),
+    }
+}
+
+/// Removes a file, a symlink, or an empty directory. A symlink is removed
+/// itself; its target is never touched.
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Stores a `PUT` body at `url_path`.
+fn put_file(
+    root: &Path,
+    url_path: &str,
+    overwrite: bool,
+    writes: WriteConfig,
+    mut request: tiny_http::Request,
+) -> std::io::Result<()> {
+    let (dir, name) = match resolve_write_target(root, url_path) {
+        _ if url_path.ends_with('/') => {
+            return request.respond(plain(400, "PUT needs a file path, not a directory"));
+        }
+        WriteResolution::Allowed { dir, name } => (dir, name),
+        WriteResolution::Forbidden => {
+            return request.respond(plain(403, "refusing to write outside the served root"));
+        }
+        WriteResolution::Reserved => {
+            return request.respond(plain(403, &reserved_name_error().to_string()));
+        }
+        WriteResolution::MissingParent => {
+            return request.respond(plain(409, "the parent directory does not exist"));
+        }
+    };
+    // Refuse early, before `as_reader` tells a client waiting on
+    // `Expect: 100-continue` to send the body after all.
+    if request
+        .body_length()
+        .is_some_and(|len| len as u64 > writes.max_upload)
+    {
+        return request.respond(too_large(writes));
+    }
+    if !overwrite && std::fs::symlink_metadata(dir.join(&name)).is_ok() {
+        return request.respond(write_error(
+            &std::io::ErrorKind::AlreadyExists.into(),
+            &name,
+        ));
+    }
+
+    let mut body = Capped::new(request.as_reader(), writes.max_upload);
+    let result = stage_upload(&dir, &mut body)
+        .and_then(|staged| commit_upload(&staged, &dir.join(&name), overwrite));
+    let exceeded = body.exceeded;
+    let response = match result {
+        Ok(false) => plain(201, "created"),
+        Ok(true) => no_content(),
+        Err(_) if exceeded => too_large(writes),
+        Err(err) => write_error(&err, &name),
+    };
+    request.respond(response)
+}
+
+/// The `413` for an upload over the limit.
+fn too_large(writes: WriteConfig) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    plain(
+        413,
+        &format!("upload exceeds the {}-byte limit", writes.max_upload),
+    )
+}
+
+/// Handles a form posted to the directory listing at `url_path`.
+fn post_form(
+    root: &Path,
+    url_path: &str,
+    overwrite: bool,
+    writes: WriteConfig,
+    mut request: tiny_http::Request,
+) -> std::io::Result<()> {
+    if is_cross_site(&request) {
+        return request.respond(plain(403, "refusing a form posted from another site"));
+    }
+    let dir = match resolve_under_root(root, url_path) {
+        PathResolution::Allowed(dir) if dir.is_dir() => dir,
+        PathResolution::Allowed(_) => {
+            return request.respond(plain(405, "forms post to a directory"));
+        }
+        PathResolution::Forbidden => {
+            return request.respond(plain(403, "refusing to write outside the served root"));
+        }
+        PathResolution::Missing => return request.respond(plain(404, "no such directory")),
+    };
+    if request
+        .body_length()
+        .is_some_and(|len| len as u64 > writes.max_upload)
+    {
+        return request.respond(too_large(writes));
+    }
+
+    let content_type = request_header(&request, "Content-Type")
+        .unwrap_or("")
+        .to_string();
+    let result = if let Some(boundary) = multipart_boundary_of(&content_type) {
+        let mut body = Capped::new(request.as_reader(), writes.max_upload);
+        let result = save_multipart(&dir, &mut body, &boundary, overwrite);
+        if body.exceeded {
+            return request.respond(too_large(writes));
+        }
+        result
+    } else if content_type
+        .to_ascii_lowercase()
+        .starts_with("application/x-www-form-urlencoded")
+    {
+        let mut form = String::new();
+        let mut body = Capped::new(request.as_reader(), MAX_FORM);
+        let read = body.read_to_string(&mut form);
+        if body.exceeded {
+            return request.respond(plain(
+                413,
+                &format!("form exceeds the {MAX_FORM}-byte limit"),
+            ));
+        }
+        if read.is_err() {
+            return request.respond(plain(400, "could not read the form"));
+        }
+        form_action(&dir, &form)
+    } else {
+        return request.respond(plain(
+            415,
+            "expected multipart/form-data or a URL-encoded form",
+        ));
+    };
+
+    match result {
+        Ok(()) => {
+            // Back to the listing, which now shows the change.
+            let base = if url_path.ends_with('/') {
+                url_path.to_string()
+            } else {
+                format!("{url_path}/")
+            };
+            let location = header("Location", &encode_path(&base));
+            request.respond(tiny_http::Response::empty(303).with_header(location))
+        }
+        Err((err, name)) => request.respond(write_error(&err, &name)),
+    }
+}
+
+/// Whether `request` was sent by a page from another site: its browser says
+/// so in `Sec-Fetch-Site`, or its `Origin` names a different host than the
+/// one it was sent to.
+///
+/// Any page can post a form to this server, and the browser attaches the
+/// visitor's credentials, so without this check a link could upload or delete
+/// files. `PUT`, `MKCOL`, and `DELETE` need no check: a page cannot send them
+/// to another origin without a CORS preflight, which this server never grants.
+/// A request with neither header, as from `curl`, is not from a browser page.
+fn is_cross_site(request: &tiny_http::Request) -> bool {
+    if request_header(request, "Sec-Fetch-Site")
+        .is_some_and(|site| site.trim().eq_ignore_ascii_case("cross-site"))
+    {
+        return true;
+    }
+    let Some(origin) = request_header(request, "Origin") else {
+        return false;
+    };
+    // `null` is an opaque origin: a sandboxed frame, a `data:` URL, or a file.
+    let origin_host = origin
+        .trim()
+        .split_once("://")
+        .map(|(_, host)| host.trim_end_matches('/'));
+    match (origin_host, request_header(request, "Host")) {
+        (Some(origin_host), Some(host)) => !origin_host.eq_ignore_ascii_case(host.trim()),
+        _ => true,
+    }
+}
+
+/// Performs a URL-encoded `mkdir=name` or `delete=name` in `dir`. The error
+/// carries the entry name for the response.
+fn form_action(dir: &Path, form: &str) -> Result<(), (std::io::Error, String)> {
+    let invalid = |name: &str| {
+        (
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "not a file or folder name"),
+            name.to_string(),
+        )
+    };
+    for pair in form.split('&') {
+        let (field, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let name = percent_decode_str(&value.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned();
+        if field != "mkdir" && field != "delete" {
+            continue;
+        }
+        if !is_entry_name(&name) {
+            return Err(invalid(&name));
+        }
+        if is_reserved_name(&name) {
+            return Err((reserved_name_error(), name));
+        }
+        let path = dir.join(&name);
+        let result = if field == "mkdir" {
+            std::fs::create_dir(&path)
+        } else {
+            remove_entry(&path)
+        };
+        return result.map_err(|err| (err, name));
+    }
+    Err(invalid(""))
+}
+
+/// Extracts the boundary from a `multipart/form-data` `Content-Type`.
+fn multipart_boundary_of(content_type: &str) -> Option<String> {
+    let (media_type, _) = content_type.split_once(';').unwrap_or((content_type, ""));
+    if !media_type
+        .trim()
+        .eq_ignore_ascii_case("multipart/form-data")
+    {
+        return None;
+    }
+    header_param(content_type, "boundary").filter(|b| !b.is_empty())
+}
+
+/// Returns the value of parameter `name` in a header value such as
+/// `form-data; name="file"; filename="a;b.txt"`, unquoting it. Semicolons
+/// inside quotes do not split parameters.
+///
+/// A backslash is kept as is rather than read as a quoted-string escape:
+/// browsers percent-encode a `"` in a filename instead of escaping it, and
+/// some send a full Windows path whose backslashes must survive.
+fn header_param(value: &str, name: &str) -> Option<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => params.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    params.push(current);
+    params.iter().find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// Saves every file part of a multipart body into `dir`, all or nothing.
+///
+/// Parts are staged as hidden files while the body streams in, and only
+/// renamed into place once the whole body has been read; any failure — a
+/// malformed body, two files with the same name, an existing file without
+/// `overwrite`, or the size cap — removes everything staged. A field named `overwrite` set to `1` before the
+/// files (as the listing form sends it) turns `overwrite` on. The error
+/// carries the entry name for the response.
+fn save_multipart(
+    dir: &Path,
+    body: &mut impl Read,
+    boundary: &str,
+    mut overwrite: bool,
+) -> Result<(), (std::io::Error, String)> {
+    let mut staged: Vec<(PathBuf, String)> = Vec::new();
+    let result = stage_parts(dir, body, boundary, &mut overwrite, &mut staged);
+    let result = result.and_then(|()| {
+        // Check every destination before renaming any, so a clash leaves the
+        // directory untouched. Two parts with one name would otherwise both
+        // be committed, the second replacing the first.
+        let mut names = HashSet::new();
+        if let Some((_, name)) = staged.iter().find(|(_, name)| !names.insert(name)) {
+            return Err((
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "the same file name appears twice",
+                ),
+                name.clone(),
+            ));
+        }
+        if !overwrite {
+            if let Some((_, name)) = staged
+                .iter()
+                .find(|(_, name)| std::fs::symlink_metadata(dir.join(name)).is_ok())
+            {
+                return Err((std::io::ErrorKind::AlreadyExists.into(), name.clone()));
+            }
+        }
+        for (temp, name) in &staged {
+            commit_upload(temp, &dir.join(name), overwrite).map_err(|err| (err, name.clone()))?;
+        }
+        Ok(())
+    });
+    for (temp, _) in &staged {
+        // Already renamed away on success; on failure, the leftovers go.
+        let _ = std::fs::remove_file(temp);
+    }
+    result
+}
+
+/// Streams each part of a multipart body: file parts into staged files
+/// (pushed onto `staged` as they are created, so the caller can clean up), and
+/// the `overwrite` field into `overwrite`. Other fields are skipped.
+fn stage_parts(
+    dir: &Path,
+    body: &mut impl Read,
+    boundary: &str,
+    overwrite: &mut bool,
+    staged: &mut Vec<(PathBuf, String)>,
+) -> Result<(), (std::io::Error, String)> {
+    let mut parts = Multipart::new(body, boundary);
+    let unnamed = |err| (err, String::new());
+    // Anything before the first boundary is a preamble to be ignored.
+    let mut more = parts.copy_part(&mut std::io::sink()).map_err(unnamed)?;
+    while more {
+        let headers = parts.headers().map_err(unnamed)?;
+        let disposition = headers
+            .iter()
+            .find(|(name, _)| name == "content-disposition")
+            .map_or("", |(_, value)| value.as_str());
+        let field = header_param(disposition, "name").unwrap_or_default();
+        match header_param(disposition, "filename") {
+            // A file input left empty still sends a part, with no filename.
+            Some(filename) if !filename.is_empty() => {
+                // Some browsers send the full client-side path; keep its last
+                // component.
+                let name = filename
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                if !is_entry_name(&name) {
+                    return Err((
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, "bad file name"),
+                        name,
+                    ));
+                }
+                if is_reserved_name(&name) {
+                    return Err((reserved_name_error(), name));
+                }
+                let (temp, mut file) =
+                    create_staging_file(dir).map_err(|err| (err, name.clone()))?;
+                staged.push((temp, name.clone()));
+                more = parts
+                    .copy_part(&mut file)
+                    .and_then(|more| file.sync_all().map(|()| more))
+                    .map_err(|err| (err, name))?;
+            }
+            _ if field == "overwrite" => {
+                let mut value = Vec::new();
+                more = parts.copy_part(&mut value).map_err(unnamed)?;
+                *overwrite |= matches!(&value[..], b"1" | b"true" | b"on" | b"yes");
+            }
+            _ => more = parts.copy_part(&mut std::io::sink()).map_err(unnamed)?,
+        }
+    }
+    Ok(())
+}
+
+/// Streams `body` into a new staged file in `dir` and returns its path. The
+/// staged file is removed again if the body cannot be read in full.
+fn stage_upload(dir: &Path, body: &mut impl Read) -> std::io::Result<PathBuf> {
+    let (temp, mut file) = create_staging_file(dir)?;
+    let result = std::io::copy(body, &mut file).and_then(|_| file.sync_all());
+    match result {
+        Ok(()) => Ok(temp),
+        Err(err) => {
+            let _ = std::fs::remove_file(&temp);
+            Err(err)
+        }
+    }
+}
+
+/// Creates a new, uniquely named staging file in `dir`.
+fn create_staging_file(dir: &Path) -> std::io::Result<(PathBuf, std::fs::File)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp = dir.join(format!("{UPLOAD_PREFIX}{}-{count}", std::process::id()));
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)?;
+    Ok((temp, file))
+}
+
+/// Moves a staged upload to `dest`, returning whether it replaced a file.
+///
+/// Without `overwrite` the staged file is hard-linked into place, which fails
+/// with `AlreadyExists` if `dest` appeared in the meantime — no window in which
+/// a concurrent upload could be clobbered. With it, the staged file is renamed
+/// over `dest`, which readers see switch from the old contents to the new in
+/// one step. A directory is never replaced. The staged file is always removed.
+fn commit_upload(temp: &Path, dest: &Path, overwrite: bool) -> std::io::Result<bool> {
+    let existing = std::fs::symlink_metadata(dest).ok();
+    let result = match &existing {
+        Some(meta) if meta.is_dir() || !overwrite => Err(std::io::ErrorKind::AlreadyExists.into()),
+        _ if overwrite => std::fs::rename(temp, dest),
+        _ => std::fs::hard_link(temp, dest),
+    };
+    let _ = std::fs::remove_file(temp);
+    result.map(|()| existing.is_some())
+}
+
+/// A reader that fails once its inner reader yields more than `limit` bytes,
+/// and remembers that it did.
+struct Capped<R> {
+    inner: R,
+    remaining: u64,
+    /// Set once the inner reader has gone past the limit.
+    exceeded: bool,
+}
+
+impl<R: Read> Capped<R> {
+    fn new(inner: R, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+            exceeded: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Capped<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            // At the limit: a body that ends here is fine, one more byte is not.
+            let mut probe = [0_u8; 1];
+            if self.inner.read(&mut probe)? == 0 {
+                return Ok(0);
+            }
+            self.exceeded = true;
+            return Err(std::io::Error::other("upload exceeds the size limit"));
+        }
+        let want = usize::try_from(self.remaining).map_or(buf.len(), |r| r.min(buf.len()));
+        let n = self.inner.read(&mut buf[..want])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// A streaming reader over the parts of a `multipart/form-data` body.
+///
+/// Only a small window of the body is buffered at a time, so an upload of any
+/// size streams straight through to disk.
+struct Multipart<R> {
+    inner: R,
+    /// `CRLF--boundary`, which ends each part's data.
+    delimiter: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Multipart<R> {
+    fn new(inner: R, boundary: &str) -> Self {
+        Self {
+            inner,
+            delimiter: format!("\r\n--{boundary}").into_bytes(),
+            // The first boundary opens the body with no CRLF before it; starting
+            // the buffer with one lets the same delimiter match it too.
+            buf: b"\r\n".to_vec(),
+        }
+    }
+
+    /// Reads more of the body into the buffer, returning false at its end.
+    fn fill(&mut self) -> std::io::Result<bool> {
+        let mut chunk = [0_u8; 8192];
+        let n = self.inner.read(&mut chunk)?;
+        self.buf.extend_from_slice(&chunk[..n]);
+        Ok(n > 0)
+    }
+
+    /// Copies the current part's data into `out` and consumes the boundary
+    /// after it. Returns true if another part follows, false after the closing
+    /// boundary.
+    fn copy_part(&mut self, out: &mut impl std::io::Write) -> std::io::Result<bool> {
+        loop {
+            if let Some(at) = find_bytes(&self.buf, &self.delimiter) {
+                out.write_all(&self.buf[..at])?;
+                self.buf.drain(..at + self.delimiter.len());
+                return self.after_delimiter();
+            }
+            // All but a tail that could be the start of the delimiter is data.
+            let safe = self.buf.len().saturating_sub(self.delimiter.len() - 1);
+            out.write_all(&self.buf[..safe])?;
+            self.buf.drain(..safe);
+            if !self.fill()? {
+                return Err(malformed("the body ended inside a part"));
+            }
+        }
+    }
+
+    /// Reads what follows a delimiter: `--` closes the body, and otherwise
+    /// optional whitespace and a CRLF lead into the next part.
+    fn after_delimiter(&mut self) -> std::io::Result<bool> {
+        while self.buf.len() < 2 {
+            if !self.fill()? {
+                return Err(malformed("the body ended after a boundary"));
+            }
+        }
+        if self.buf.starts_with(b"--") {
+            return Ok(false);
+        }
+        loop {
+            if let Some(at) = find_bytes(&self.buf, b"\r\n") {
+                if !self.buf[..at].iter().all(|b| *b == b' ' || *b == b'\t') {
+                    return Err(malformed("unexpected bytes after a boundary"));
+                }
+                self.buf.drain(..at + 2);
+                return Ok(true);
+            }
+            if self.buf.len() > MAX_PART_HEADERS || !self.fill()? {
+                return Err(malformed("a boundary line never ended"));
+            }
+        }
+    }
+
+    /// Reads the current part's headers, up to the blank line that ends them,
+    /// as `(lowercased name, value)` pairs.
+    fn headers(&mut self) -> std::io::Result<Vec<(String, String)>> {
+        loop {
+            // A part with no headers starts straight away with the blank line.
+            let end = if self.buf.starts_with(b"\r\n") {
+                Some(0)
+            } else {
+                find_bytes(&self.buf, b"\r\n\r\n").map(|at| at + 2)
+            };
+            if let Some(end) = end {
+                let text = String::from_utf8_lossy(&self.buf[..end]).into_owned();
+                self.buf.drain(..end + 2);
+                return Ok(text
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| {
+                        (name.trim().to_ascii_lowercase(), value.trim().to_string())
+                    })
+                    .collect());
+            }
+            if self.buf.len() > MAX_PART_HEADERS || !self.fill()? {
+                return Err(malformed("a part's headers never ended"));
+            }
+        }
+    }
+}
+
+/// The first position of `needle` in `haystack`.
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// An `InvalidData` error for a malformed multipart body.
+fn malformed(why: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, why.to_string())
+}
+
+#[cfg(test)]
+mod write_target_tests {
+    use super::{resolve_write_target, WriteResolution};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn canonical_root() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().expect("temp dir");
+        let root = dir.path().canonicalize().expect("canonicalize root");
+        (dir, root)
+    }
+
+    fn allowed(dir: &std::path::Path, name: &str) -> WriteResolution {
+        WriteResolution::Allowed {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn new_file_in_root_is_allowed() {
+        let (_dir, root) = canonical_root();
+        assert_eq!(
+            resolve_write_target(&root, "/upload.tar.gz"),
+            allowed(&root, "upload.tar.gz")
+        );
+    }
+
+    #[test]
+    fn new_entry_in_a_subdirectory_is_allowed() {
+        let (_dir, root) = canonical_root();
+        std::fs::create_dir(root.join("logs")).expect("create logs");
+        assert_eq!(
+            resolve_write_target(&root, "/logs/bundle.zip"),
+            allowed(&root.join("logs"), "bundle.zip")
+        );
+    }
+
+    #[test]
+    fn trailing_slash_names_the_entry_before_it() {
+        let (_dir, root) = canonical_root();
+        assert_eq!(resolve_write_target(&root, "/new/"), allowed(&root, "new"));
+    }
+
+    #[test]
+    fn the_root_itself_is_forbidden() {
+        let (_dir, root) = canonical_root();
+        assert_eq!(resolve_write_target(&root, "/"), WriteResolution::Forbidden);
+        assert_eq!(resolve_write_target(&root, ""), WriteResolution::Forbidden);
+    }
+
+    #[test]
+    fn traversal_is_forbidden() {
+        let (_dir, root) = canonical_root();
+        std::fs::create_dir(root.join("sub")).expect("create sub");
+        assert_eq!(
+            resolve_write_target(&root, "/../escape.txt"),
+            WriteResolution::Forbidden
+        );
+        assert_eq!(
+            resolve_write_target(&root, "/sub/.."),
+            WriteResolution::Forbidden
+        );
+        assert_eq!(
+            resolve_write_target(&root, "/sub/."),
+            WriteResolution::Forbidden
+        );
+    }
+
+    #[test]
+    fn staging_file_names_are_reserved() {
+        let (_dir, root) = canonical_root();
+        assert_eq!(
+            resolve_write_target(&root, "/.sirn-upload-abc"),
+            WriteResolution::Reserved
+        );
+        assert_eq!(
+            resolve_write_target(&root, "/.sirn-upload"),
+            allowed(&root, ".sirn-upload")
+        );
+    }
+
+    #[test]
+    fn missing_or_file_parent_is_reported() {
+        let (_dir, root) = canonical_root();
+        std::fs::write(root.join("a.txt"), b"a").expect("write a.txt");
+        assert_eq!(
+            resolve_write_target(&root, "/nope/x.txt"),
+            WriteResolution::MissingParent
+        );
+        assert_eq!(
+            resolve_write_target(&root, "/a.txt/x.txt"),
+            WriteResolution::MissingParent
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_parent_leading_out_is_forbidden() {
+        let (_dir, root) = canonical_root();
+        let outside = TempDir::new().expect("outside temp dir");
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).expect("create symlink");
+        assert_eq!(
+            resolve_write_target(&root, "/link/dropped.txt"),
+            WriteResolution::Forbidden
+        );
+    }
+
+    #[test]
+    fn utf8_name_is_kept_whole() {
+        let (_dir, root) = canonical_root();
+        assert_eq!(
+            resolve_write_target(&root, "/日本語.txt"),
+            allowed(&root, "日本語.txt")
+        );
+    }
+}
+
+#[cfg(test)]
+mod size_tests {
+    use super::parse_size;
+
+    #[test]
+    fn plain_bytes() {
+        assert_eq!(parse_size("1048576"), Ok(1_048_576));
+    }
+
+    #[test]
+    fn binary_suffixes_in_any_case() {
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("512m"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+    }
+
+    #[test]
+    fn byte_unit_spellings() {
+        assert_eq!(parse_size("100MB"), Ok(100 << 20));
+        assert_eq!(parse_size("100MiB"), Ok(100 << 20));
+        assert_eq!(parse_size("10B"), Ok(10));
+    }
+
+    #[test]
+    fn garbage_and_overflow_are_errors() {
+        for size in ["", "M", "1.5G", "12X", "-1", "99999999999T"] {
+            assert!(parse_size(size).is_err(), "{size}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod multipart_tests {
+    use super::{header_param, multipart_boundary_of, Capped, Multipart};
+    use std::io::Read;
+
+    /// A reader that hands out one byte per `read`, so every boundary and
+    /// header straddles a buffer refill.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(slot)) => {
+                    *slot = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    const BODY: &[u8] = b"preamble\r\n--XyZ\r\n\
+        Content-Disposition: form-data; name=\"overwrite\"\r\n\r\n\
+        1\r\n--XyZ\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+        Content-Type: text/plain\r\n\r\n\
+        line one\r\n--Xy almost\r\n--XyZ--\r\nepilogue";
+
+    /// One parsed part: its headers and its data.
+    type Part = (Vec<(String, String)>, Vec<u8>);
+
+    fn parts(reader: impl Read) -> Vec<Part> {
+        let mut multipart = Multipart::new(reader, "XyZ");
+        let mut parts = Vec::new();
+        let mut more = multipart.copy_part(&mut std::io::sink()).expect("preamble");
+        while more {
+            let headers = multipart.headers().expect("headers");
+            let mut data = Vec::new();
+            more = multipart.copy_part(&mut data).expect("part data");
+            parts.push((headers, data));
+        }
+        parts
+    }
+
+    #[test]
+    fn parts_split_at_boundaries_only() {
+        let parts = parts(BODY);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].1, b"1");
+        assert_eq!(parts[1].1, b"line one\r\n--Xy almost");
+        assert_eq!(
+            parts[1].0,
+            vec![
+                (
+                    "content-disposition".to_string(),
+                    "form-data; name=\"file\"; filename=\"a.txt\"".to_string()
+                ),
+                ("content-type".to_string(), "text/plain".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_at_a_time_body_parses_the_same() {
+        assert_eq!(parts(Trickle(BODY)), parts(BODY));
+    }
+
+    #[test]
+    fn truncated_body_is_an_error() {
+        let mut multipart = Multipart::new(&b"--XyZ\r\n\r\npartial data"[..], "XyZ");
+        assert!(multipart.copy_part(&mut std::io::sink()).expect("preamble"));
+        multipart.headers().expect("headers");
+        let err = multipart
+            .copy_part(&mut std::io::sink())
+            .expect_err("no closing boundary");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn boundary_comes_from_a_form_data_content_type() {
+        assert_eq!(
+            multipart_boundary_of("multipart/form-data; boundary=----abc"),
+            Some("----abc".to_string())
+        );
+        assert_eq!(
+            multipart_boundary_of("Multipart/Form-Data; boundary=\"a b\""),
+            Some("a b".to_string())
+        );
+        assert_eq!(multipart_boundary_of("text/plain; boundary=x"), None);
+        assert_eq!(multipart_boundary_of("multipart/form-data"), None);
+    }
+
+    #[test]
+    fn quoted_params_keep_semicolons_and_backslashes() {
+        let disposition = r#"form-data; name="file"; filename="C:\dir\a;b.txt""#;
+        assert_eq!(
+            header_param(disposition, "filename"),
+            Some(r"C:\dir\a;b.txt".to_string())
+        );
+        assert_eq!(header_param(disposition, "name"), Some("file".to_string()));
+        assert_eq!(header_param(disposition, "missing"), None);
+    }
+
+    #[test]
+    fn capped_reader_allows_exactly_the_limit() {
+        let mut out = Vec::new();
+        let mut capped = Capped::new(&b"12345"[..], 5);
+        capped.read_to_end(&mut out).expect("within the limit");
+        assert_eq!(out, b"12345");
+        assert!(!capped.exceeded);
+    }
+
+    #[test]
+    fn capped_reader_fails_past_the_limit() {
+        let mut out = Vec::new();
+        let mut capped = Capped::new(&b"123456"[..], 5);
+        assert!(capped.read_to_end(&mut out).is_err());
+        assert!(capped.exceeded);
+    }
+}
@@ -0,0 +1,113 @@
+//! End-to-end tests for access control: `--auth` basic authentication and the
+//! `--token` access token, enforced on every request before it is served.
+//!
+//! Each test starts its own server on `127.0.0.1:0` rooted at a unique
+//! `tempfile::TempDir`, so the suite is parallel-safe.
+
+mod common;
+use common::{http_request, start_mode, stop};
+use std::sync::Arc;
+
+/// A directory-mode server over a temp root holding `a.txt`, guarded by `auth`.
+fn start_guarded(
+    dir: &tempfile::TempDir,
+    auth: sirn::Auth,
+) -> (
+    std::net::SocketAddr,
+    Arc<tiny_http::Server>,
+    Vec<std::thread::JoinHandle<()>>,
+) {
+    std::fs::write(dir.path().join("a.txt"), b"alpha").expect("write a.txt");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+    start_mode(
+        sirn::ServeMode::Directory(Arc::new(sirn::DirectoryConfig::new(root))),
+        auth,
+    )
+}
+
+#[test]
+fn basic_auth_challenges_then_admits_the_right_password() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let auth = sirn::parse_credentials("me:s3cret").expect("credentials");
+    let (addr, server, handles) = start_guarded(&dir, auth);
+
+    let (status, headers, _) = http_request(addr, "GET", "/a.txt", &[]);
+    assert_eq!(status, 401);
+    assert_eq!(
+        headers.get("www-authenticate").map(String::as_str),
+        Some("Basic realm=\"sirn\", charset=\"UTF-8\"")
+    );
+
+    // "me:wrong" and "me:s3cret" in base64.
+    let (status, _, _) = http_request(
+        addr,
+        "GET",
+        "/a.txt",
+        &[("Authorization", "Basic bWU6d3Jvbmc=")],
+    );
+    assert_eq!(status, 401);
+    let (status, _, body) = http_request(
+        addr,
+        "GET",
+        "/a.txt",
+        &[("Authorization", "Basic bWU6czNjcmV0")],
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body, b"alpha");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn token_is_required_on_every_request() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let (addr, server, handles) = start_guarded(&dir, sirn::Auth::Token("t0ken".to_string()));
+
+    let (status, headers, _) = http_request(addr, "GET", "/a.txt", &[]);
+    assert_eq!(status, 401);
+    assert!(!headers.contains_key("www-authenticate"));
+
+    let (status, _, body) = http_request(addr, "GET", "/a.txt?token=t0ken", &[]);
+    assert_eq!(status, 200);
+    assert_eq!(body, b"alpha");
+
+    let (status, _, _) = http_request(addr, "GET", "/a.txt", &[("Authorization", "Bearer t0ken")]);
+    assert_eq!(status, 200);
+
+    let (status, _, _) = http_request(addr, "GET", "/a.txt?token=wrong", &[]);
+    assert_eq!(status, 401);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn browser_opening_a_token_url_gets_a_cookie_and_a_clean_url() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let (addr, server, handles) = start_guarded(&dir, sirn::Auth::Token("t0ken".to_string()));
+
+    let (status, headers, _) = http_request(
+        addr,
+        "GET",
+        "/?sort=size&token=t0ken",
+        &[("Accept", "text/html,application/xhtml+xml")],
+    );
+    assert_eq!(status, 303);
+    assert_eq!(
+        headers.get("location").map(String::as_str),
+        Some("/?sort=size")
+    );
+    let cookie = headers.get("set-cookie").expect("token cookie");
+    let expected = format!(
+        "sirn-token-{}=t0ken; Path=/; HttpOnly; SameSite=Lax",
+        addr.port()
+    );
+    assert_eq!(cookie, &expected);
+
+    // The cookie alone now admits the page and everything it links to.
+    let sent = format!("sirn-token-{}=t0ken", addr.port());
+    let (status, _, body) = http_request(addr, "GET", "/a.txt", &[("Cookie", &sent)]);
+    assert_eq!(status, 200);
+    assert_eq!(body, b"alpha");
+
+    stop(&server, handles);
+}
@@ -149,3 +149,146 @@ fn malformed_uid_reports_helpful_message() {
         "stderr should surface the helpful Display message, not the Debug form, got: {stderr}"
     );
 }
+
+/// `--writable` only makes sense for a served tree, so combining it with files
+/// aborts startup with an explanation instead of silently serving read-only.
+#[test]
+fn writable_with_files_aborts_startup() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .args(["--writable", "/some/dir/file.txt"])
+        .output()
+        .expect("spawning sirn --writable with a file should succeed");
+
+    assert!(
+        !output.status.success(),
+        "--writable with files should make sirn exit non-zero, got {:?}",
+        output.status
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--writable needs directory mode"),
+        "stderr should explain --writable needs directory mode, got: {stderr}"
+    );
+}
+
+/// A malformed `--max-upload` size is rejected by argument parsing, before any
+/// bind.
+#[test]
+fn malformed_max_upload_is_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .args(["--writable", "--max-upload", "lots"])
+        .output()
+        .expect("spawning sirn with a malformed --max-upload should succeed");
+
+    assert!(
+        !output.status.success(),
+        "a malformed --max-upload should make sirn exit non-zero, got {:?}",
+        output.status
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("invalid size 'lots'"),
+        "stderr should name the bad size, got: {stderr}"
+    );
+}
+
+/// The directory-only options are refused alongside files just like
+/// `--writable`, naming the option that was given.
+#[test]
+fn live_reload_with_files_aborts_startup() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .args(["--live-reload", "/some/dir/file.txt"])
+        .output()
+        .expect("spawning sirn --live-reload with a file should succeed");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--live-reload needs directory mode"),
+        "stderr should explain --live-reload needs directory mode, got: {stderr}"
+    );
+}
+
+/// A malformed `--auth` value is rejected by argument parsing with a hint at
+/// the expected form.
+#[test]
+fn auth_without_a_password_is_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .args(["--auth", "justauser"])
+        .output()
+        .expect("spawning sirn --auth should succeed");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("expected USER:PASSWORD"),
+        "stderr should show the expected form, got: {stderr}"
+    );
+}
+
+/// `SIRN_AUTH` stands in for `--auth`, keeping the password out of the
+/// process list, and is checked the same way.
+#[test]
+fn auth_from_the_environment_is_checked_like_the_flag() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .env("SIRN_AUTH", "justauser")
+        .output()
+        .expect("spawning sirn with SIRN_AUTH should succeed");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("expected USER:PASSWORD"),
+        "stderr should show the expected form, got: {stderr}"
+    );
+}
+
+/// An `--auth-file` without valid credentials aborts startup, naming the file.
+#[test]
+fn auth_file_without_credentials_aborts_startup() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let auth = dir.path().join("auth.txt");
+    std::fs::write(&auth, "justauser\n").expect("write auth file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .arg("--auth-file")
+        .arg(&auth)
+        .args(["--port", "1"])
+        .arg(dir.path())
+        .output()
+        .expect("spawning sirn --auth-file should succeed");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("auth.txt") && stderr.contains("expected USER:PASSWORD"),
+        "stderr should name the file and the expected form, got: {stderr}"
+    );
+}
+
+/// A `--cert` file holding no certificate aborts startup with a readable message.
+#[test]
+fn cert_that_is_not_pem_aborts_startup() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let junk = dir.path().join("junk.pem");
+    std::fs::write(&junk, "junk").expect("write junk");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sirn"))
+        .arg("--cert")
+        .arg(&junk)
+        .arg("--key")
+        .arg(&junk)
+        .args(["--port", "1"])
+        .arg(dir.path())
+        .output()
+        .expect("spawning sirn --cert should succeed");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("holds no PEM certificate"),
+        "stderr should explain the certificate is missing, got: {stderr}"
+    );
+}
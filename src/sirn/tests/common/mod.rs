@@ -28,27 +28,47 @@ use std::thread::JoinHandle;
 /// it for shutdown), and the worker-thread join handles.
 pub fn start(
     routes: BTreeMap<String, PathBuf>,
+) -> (SocketAddr, Arc<tiny_http::Server>, Vec<JoinHandle<()>>) {
+    start_mode(sirn::ServeMode::Files(Arc::new(routes)), sirn::Auth::Open)
+}
+
+/// Starts a server on an ephemeral loopback port serving `mode` to requests
+/// that satisfy `auth`.
+pub fn start_mode(
+    mode: sirn::ServeMode,
+    auth: sirn::Auth,
 ) -> (SocketAddr, Arc<tiny_http::Server>, Vec<JoinHandle<()>>) {
     let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").expect("bind ephemeral port"));
     let addr = server.server_addr().to_ip().expect("ip addr");
-    let handles = sirn::serve(
-        Arc::clone(&server),
-        sirn::ServeMode::Files(Arc::new(routes)),
-        2,
-    );
+    let handles = sirn::serve(Arc::clone(&server), mode, auth, 2);
     (addr, server, handles)
 }
 
 /// Starts a directory-mode server rooted at `root` (caller canonicalizes it).
 pub fn start_dir(root: PathBuf) -> (SocketAddr, Arc<tiny_http::Server>, Vec<JoinHandle<()>>) {
-    let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").expect("bind ephemeral port"));
-    let addr = server.server_addr().to_ip().expect("ip addr");
-    let handles = sirn::serve(
-        Arc::clone(&server),
-        sirn::ServeMode::Directory(Arc::new(root)),
-        2,
-    );
-    (addr, server, handles)
+    start_config(sirn::DirectoryConfig::new(root))
+}
+
+/// Starts a writable directory-mode server rooted at `root` (caller
+/// canonicalizes it) that accepts uploads of up to `max_upload` bytes.
+pub fn start_writable(
+    root: PathBuf,
+    max_upload: u64,
+) -> (SocketAddr, Arc<tiny_http::Server>, Vec<JoinHandle<()>>) {
+    start_config(sirn::DirectoryConfig {
+        writes: Some(sirn::WriteConfig { max_upload }),
+        ..sirn::DirectoryConfig::new(root)
+    })
+}
+
+/// Starts a directory-mode server with an arbitrary `config`.
+pub fn start_config(
+    config: sirn::DirectoryConfig,
+) -> (SocketAddr, Arc<tiny_http::Server>, Vec<JoinHandle<()>>) {
+    start_mode(
+        sirn::ServeMode::Directory(Arc::new(config)),
+        sirn::Auth::Open,
+    )
 }
 
 /// Unblocks `server` and joins every worker so threads don't linger after a test.
@@ -71,9 +91,40 @@ pub fn stop(server: &Arc<tiny_http::Server>, handles: Vec<JoinHandle<()>>) {
 /// response, so EOF delimits the body. Header keys are lowercased and values
 /// trimmed for case-insensitive lookup.
 pub fn http_get(addr: SocketAddr, path: &str) -> (u16, HashMap<String, String>, Vec<u8>) {
+    http_request(addr, "GET", path, &[])
+}
+
+/// Like [`http_get`] but with any `method` and extra request `headers`, for
+/// exercising conditional, range, and content-coding negotiation.
+pub fn http_request(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> (u16, HashMap<String, String>, Vec<u8>) {
+    http_send(addr, method, path, headers, b"")
+}
+
+/// Like [`http_request`] but also sends `body`, with its `Content-Length`.
+pub fn http_send(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> (u16, HashMap<String, String>, Vec<u8>) {
     let mut stream = TcpStream::connect(addr).expect("connect to server");
-    let request = format!("GET {path} HTTP/1.0\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    let mut request =
+        format!("{method} {path} HTTP/1.0\r\nHost: localhost\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if !body.is_empty() {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
     stream.write_all(request.as_bytes()).expect("write request");
+    stream.write_all(body).expect("write body");
     stream.flush().expect("flush request");
 
     let mut raw = Vec::new();
@@ -8,7 +8,7 @@
 //! and directory-mode path confinement compares against the canonical root.
 
 mod common;
-use common::{http_get, start_dir, stop};
+use common::{http_get, start_config, start_dir, stop};
 
 #[test]
 fn root_request_returns_listing_with_entries() {
@@ -174,3 +174,107 @@ fn missing_path_returns_404() {
 
     stop(&server, handles);
 }
+
+#[test]
+fn listing_sorts_by_the_query() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::write(dir.path().join("big.txt"), vec![b'x'; 4096]).expect("write big.txt");
+    std::fs::write(dir.path().join("small.txt"), b"x").expect("write small.txt");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+
+    let (addr, server, handles) = start_dir(root);
+
+    let (_, _, body) = http_get(addr, "/");
+    let html = String::from_utf8_lossy(&body);
+    assert!(
+        html.find(">big.txt<") < html.find(">small.txt<"),
+        "got:\n{html}"
+    );
+    assert!(html.contains(">4.0 KiB<"), "got:\n{html}");
+
+    let (_, _, body) = http_get(addr, "/?sort=size&order=asc");
+    let html = String::from_utf8_lossy(&body);
+    assert!(
+        html.find(">small.txt<") < html.find(">big.txt<"),
+        "got:\n{html}"
+    );
+
+    stop(&server, handles);
+}
+
+#[test]
+fn index_option_serves_index_html_for_directories() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::create_dir(dir.path().join("docs")).expect("create docs");
+    std::fs::write(dir.path().join("docs/index.html"), b"<h1>docs</h1>").expect("write index");
+    std::fs::create_dir(dir.path().join("bare")).expect("create bare");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+
+    let (addr, server, handles) = start_config(sirn::DirectoryConfig {
+        index: true,
+        ..sirn::DirectoryConfig::new(root)
+    });
+
+    let (status, headers, body) = http_get(addr, "/docs/");
+    assert_eq!(status, 200);
+    assert_eq!(body, b"<h1>docs</h1>");
+    assert_eq!(
+        headers.get("content-type").map(String::as_str),
+        Some("text/html; charset=utf-8")
+    );
+
+    // Without the slash the page's relative links would resolve one level up.
+    let (status, headers, _) = http_get(addr, "/docs?x=1");
+    assert_eq!(status, 301);
+    assert_eq!(
+        headers.get("location").map(String::as_str),
+        Some("/docs/?x=1")
+    );
+
+    // A directory with no index.html still lists.
+    let (status, _, body) = http_get(addr, "/bare/");
+    assert_eq!(status, 200);
+    assert!(String::from_utf8_lossy(&body).contains("<table>"));
+
+    stop(&server, handles);
+}
+
+#[test]
+fn index_html_is_listed_without_the_index_option() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::write(dir.path().join("index.html"), b"<h1>home</h1>").expect("write index");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+
+    let (addr, server, handles) = start_dir(root);
+
+    let (_, _, body) = http_get(addr, "/");
+    assert!(String::from_utf8_lossy(&body).contains(">index.html<"));
+
+    stop(&server, handles);
+}
+
+#[test]
+fn spa_option_falls_back_to_root_index_for_routes_only() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::write(dir.path().join("index.html"), b"<div id=app></div>").expect("write index");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+
+    let (addr, server, handles) = start_config(sirn::DirectoryConfig {
+        spa: true,
+        ..sirn::DirectoryConfig::new(root)
+    });
+
+    let (status, _, body) = http_get(addr, "/users/42/settings");
+    assert_eq!(status, 200);
+    assert_eq!(body, b"<div id=app></div>");
+
+    // A missing asset is a real 404, not the app shell.
+    let (status, _, _) = http_get(addr, "/assets/missing.js");
+    assert_eq!(status, 404);
+
+    // Escapes are still refused.
+    let (status, _, _) = http_get(addr, "/../etc/passwd");
+    assert_eq!(status, 403);
+
+    stop(&server, handles);
+}
@@ -0,0 +1,144 @@
+//! End-to-end tests for `--live-reload`: the injected client script and the
+//! Server-Sent Events stream it listens on.
+//!
+//! Each test starts its own directory-mode server on `127.0.0.1:0` rooted at a
+//! unique `tempfile::TempDir`, and keeps its own handle on the reload hub so it
+//! can signal a change directly or through a real watcher thread.
+
+mod common;
+use common::{http_get, start_config, stop};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A live-reload config for `dir`, plus the hub it shares with the server.
+fn live_config(dir: &tempfile::TempDir) -> (sirn::DirectoryConfig, Arc<sirn::LiveReload>) {
+    let root = dir.path().canonicalize().expect("canonicalize root");
+    let hub = Arc::new(sirn::LiveReload::new());
+    let config = sirn::DirectoryConfig {
+        live_reload: Some(Arc::clone(&hub)),
+        ..sirn::DirectoryConfig::new(root)
+    };
+    (config, hub)
+}
+
+/// Opens the event stream at `since` and returns the socket once the response
+/// head has arrived, with whatever followed it.
+fn open_stream(addr: SocketAddr, since: u64) -> (TcpStream, String) {
+    let mut stream = TcpStream::connect(addr).expect("connect to server");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .expect("set read timeout");
+    write!(
+        stream,
+        "GET /__sirn/live-reload?since={since} HTTP/1.1\r\nHost: localhost\r\n\r\n"
+    )
+    .expect("send request");
+    let received = read_until(&mut stream, String::new(), "\r\n\r\n");
+    (stream, received)
+}
+
+/// Reads from `stream` onto `received` until it contains `needle`.
+fn read_until(stream: &mut TcpStream, mut received: String, needle: &str) -> String {
+    let mut buf = [0; 1024];
+    while !received.contains(needle) {
+        let n = stream.read(&mut buf).expect("stream stays open");
+        assert_ne!(n, 0, "stream closed early, got: {received}");
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    received
+}
+
+#[test]
+fn pages_and_listings_carry_the_reload_script() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::write(dir.path().join("page.html"), "<body>hello</body>").expect("write page");
+    std::fs::write(dir.path().join("notes.txt"), "<body>plain</body>").expect("write text");
+    let (config, hub) = live_config(&dir);
+    hub.bump();
+    let (addr, server, handles) = start_config(config);
+
+    let (status, headers, body) = http_get(addr, "/page.html");
+    assert_eq!(status, 200);
+    assert_eq!(
+        headers.get("cache-control").map(String::as_str),
+        Some("no-cache")
+    );
+    let page = String::from_utf8_lossy(&body);
+    assert!(page.starts_with("<body>hello<script>"), "got: {page}");
+    assert!(page.contains("since=1"), "got: {page}");
+
+    let (_, _, body) = http_get(addr, "/");
+    assert!(String::from_utf8_lossy(&body).contains("/__sirn/live-reload"));
+
+    let (_, _, body) = http_get(addr, "/notes.txt");
+    assert_eq!(body, b"<body>plain</body>");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn stream_sends_reload_when_the_hub_moves_on() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let (config, hub) = live_config(&dir);
+    let (addr, server, handles) = start_config(config);
+
+    let (mut stream, head) = open_stream(addr, 0);
+    assert!(head.starts_with("HTTP/1.1 200"), "got: {head}");
+    assert!(
+        head.contains("Content-Type: text/event-stream"),
+        "got: {head}"
+    );
+
+    hub.bump();
+    let events = read_until(&mut stream, head, "data: reload\n\n");
+    assert!(events.ends_with("data: reload\n\n"), "got: {events}");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn stream_behind_the_hub_reloads_at_once() {
+    // A change between rendering a page and its stream connecting must still
+    // reload the page.
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let (config, hub) = live_config(&dir);
+    hub.bump();
+    let (addr, server, handles) = start_config(config);
+
+    let (mut stream, head) = open_stream(addr, 0);
+    read_until(&mut stream, head, "data: reload\n\n");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn watcher_reloads_pages_when_a_file_changes() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    std::fs::write(dir.path().join("index.html"), "v1").expect("write page");
+    let (config, hub) = live_config(&dir);
+    let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel();
+    let watcher = sirn::spawn_watcher(config.root.clone(), Arc::clone(&hub), shutdown_rx);
+    let (addr, server, handles) = start_config(config);
+
+    let (mut stream, head) = open_stream(addr, 0);
+    std::fs::write(dir.path().join("style.css"), "body{}").expect("write stylesheet");
+    read_until(&mut stream, head, "data: reload\n\n");
+
+    drop(shutdown_tx);
+    watcher.join().expect("watcher joins cleanly");
+    stop(&server, handles);
+}
+
+#[test]
+fn stream_path_is_an_ordinary_path_without_live_reload() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+    let (addr, server, handles) = start_config(sirn::DirectoryConfig::new(root));
+
+    let (status, _, _) = http_get(addr, "/__sirn/live-reload");
+    assert_eq!(status, 404);
+
+    stop(&server, handles);
+}
@@ -24,6 +24,7 @@ fn file_created_then_deleted_is_reflected_in_fetches() {
     let handles = sirn::serve(
         Arc::clone(&server),
         sirn::ServeMode::Files(Arc::clone(&routes)),
+        sirn::Auth::Open,
         2,
     );
 
@@ -7,7 +7,8 @@
 //! [`common`] module so they can also back the `monitor` integration test.
 
 mod common;
-use common::{http_get, http_get_with_timeout, start, stop};
+use common::{http_get, http_get_with_timeout, http_request, start, stop};
+use std::io::Read;
 use std::time::Duration;
 
 #[test]
@@ -174,3 +175,318 @@ fn unregistered_route_returns_404() {
 
     stop(&server, handles);
 }
+
+/// Serves a ~64 KiB deterministic binary payload and returns it with its route
+/// and server, for the range tests.
+fn start_binary() -> (
+    tempfile::TempDir,
+    Vec<u8>,
+    std::net::SocketAddr,
+    std::sync::Arc<tiny_http::Server>,
+    Vec<std::thread::JoinHandle<()>>,
+) {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let path = dir.path().join("clip.mp4");
+    let data: Vec<u8> = (0_u8..=255).cycle().take(64 * 1024).collect();
+    std::fs::write(&path, &data).expect("write clip.mp4");
+    let routes = sirn::build_routes(std::slice::from_ref(&path)).expect("routes build");
+    let (addr, server, handles) = start(routes);
+    (dir, data, addr, server, handles)
+}
+
+#[test]
+fn full_response_advertises_ranges_and_validators() {
+    let (_dir, _data, addr, server, handles) = start_binary();
+
+    let (status, headers, _body) = http_get(addr, "/clip.mp4");
+    assert_eq!(status, 200);
+    assert_eq!(
+        headers.get("accept-ranges").map(String::as_str),
+        Some("bytes")
+    );
+    assert!(headers.contains_key("etag"), "headers: {headers:?}");
+    assert!(
+        headers.contains_key("last-modified"),
+        "headers: {headers:?}"
+    );
+    // Video is already compressed, so the response never varies by coding.
+    assert!(!headers.contains_key("vary"), "headers: {headers:?}");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn single_range_returns_206_with_content_range() {
+    let (_dir, data, addr, server, handles) = start_binary();
+
+    let (status, headers, body) =
+        http_request(addr, "GET", "/clip.mp4", &[("Range", "bytes=1000-1999")]);
+    assert_eq!(status, 206);
+    assert_eq!(body, &data[1000..2000]);
+    assert_eq!(
+        headers.get("content-range").map(String::as_str),
+        Some("bytes 1000-1999/65536")
+    );
+    assert_eq!(
+        headers.get("content-length").map(String::as_str),
+        Some("1000")
+    );
+    assert_eq!(
+        headers.get("content-type").map(String::as_str),
+        Some("video/mp4")
+    );
+
+    let (status, _headers, body) =
+        http_request(addr, "GET", "/clip.mp4", &[("Range", "bytes=-16")]);
+    assert_eq!(status, 206);
+    assert_eq!(body, &data[data.len() - 16..]);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn multiple_ranges_return_multipart_byteranges() {
+    let (_dir, data, addr, server, handles) = start_binary();
+
+    let (status, headers, body) =
+        http_request(addr, "GET", "/clip.mp4", &[("Range", "bytes=0-9,100-149")]);
+    assert_eq!(status, 206);
+    assert_eq!(
+        headers.get("content-length").map(String::as_str),
+        Some(body.len().to_string().as_str())
+    );
+    let content_type = headers.get("content-type").expect("content-type present");
+    let boundary = content_type
+        .strip_prefix("multipart/byteranges; boundary=")
+        .expect("multipart content type");
+
+    let mut expected = Vec::new();
+    for (index, (first, last)) in [(0_usize, 9_usize), (100, 149)].into_iter().enumerate() {
+        if index > 0 {
+            expected.extend_from_slice(b"\r\n");
+        }
+        expected.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Type: video/mp4\r\nContent-Range: bytes {first}-{last}/65536\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        expected.extend_from_slice(&data[first..=last]);
+    }
+    expected.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    assert_eq!(body, expected);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn unsatisfiable_range_returns_416() {
+    let (_dir, _data, addr, server, handles) = start_binary();
+
+    let (status, headers, body) =
+        http_request(addr, "GET", "/clip.mp4", &[("Range", "bytes=70000-")]);
+    assert_eq!(status, 416);
+    assert_eq!(
+        headers.get("content-range").map(String::as_str),
+        Some("bytes */65536")
+    );
+    assert!(body.is_empty(), "416 should have an empty body");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn stale_if_range_serves_the_whole_file() {
+    let (_dir, data, addr, server, handles) = start_binary();
+
+    let (status, _headers, body) = http_request(
+        addr,
+        "GET",
+        "/clip.mp4",
+        &[("Range", "bytes=0-9"), ("If-Range", "\"stale\"")],
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body, data);
+
+    let (_status, headers, _body) = http_get(addr, "/clip.mp4");
+    let etag = headers.get("etag").expect("etag present");
+    let (status, _headers, body) = http_request(
+        addr,
+        "GET",
+        "/clip.mp4",
+        &[("Range", "bytes=0-9"), ("If-Range", etag)],
+    );
+    assert_eq!(status, 206);
+    assert_eq!(body, &data[..10]);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn matching_validators_return_304() {
+    let (_dir, _data, addr, server, handles) = start_binary();
+
+    let (_status, headers, _body) = http_get(addr, "/clip.mp4");
+    let etag = headers.get("etag").expect("etag present");
+    let last_modified = headers.get("last-modified").expect("last-modified present");
+
+    let (status, headers, body) =
+        http_request(addr, "GET", "/clip.mp4", &[("If-None-Match", etag)]);
+    assert_eq!(status, 304);
+    assert!(body.is_empty(), "304 must not have a body");
+    assert_eq!(headers.get("etag"), Some(etag));
+
+    let (status, _headers, body) = http_request(
+        addr,
+        "GET",
+        "/clip.mp4",
+        &[("If-Modified-Since", last_modified)],
+    );
+    assert_eq!(status, 304);
+    assert!(body.is_empty(), "304 must not have a body");
+
+    let (status, _headers, _body) =
+        http_request(addr, "GET", "/clip.mp4", &[("If-None-Match", "\"stale\"")]);
+    assert_eq!(status, 200);
+
+    stop(&server, handles);
+}
+
+/// Serves a repetitive text file, which compresses well, and returns its
+/// contents with its server.
+fn start_text() -> (
+    tempfile::TempDir,
+    Vec<u8>,
+    std::net::SocketAddr,
+    std::sync::Arc<tiny_http::Server>,
+    Vec<std::thread::JoinHandle<()>>,
+) {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let path = dir.path().join("notes.txt");
+    let data = "the quick brown fox jumps over the lazy dog\n"
+        .repeat(500)
+        .into_bytes();
+    std::fs::write(&path, &data).expect("write notes.txt");
+    let routes = sirn::build_routes(std::slice::from_ref(&path)).expect("routes build");
+    let (addr, server, handles) = start(routes);
+    (dir, data, addr, server, handles)
+}
+
+#[test]
+fn text_is_gzipped_when_accepted() {
+    let (_dir, data, addr, server, handles) = start_text();
+
+    let (status, headers, body) =
+        http_request(addr, "GET", "/notes.txt", &[("Accept-Encoding", "gzip")]);
+    assert_eq!(status, 200);
+    assert_eq!(
+        headers.get("content-encoding").map(String::as_str),
+        Some("gzip")
+    );
+    assert_eq!(
+        headers.get("vary").map(String::as_str),
+        Some("Accept-Encoding")
+    );
+    assert!(
+        body.len() < data.len(),
+        "gzip should shrink repetitive text"
+    );
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(&body[..])
+        .read_to_end(&mut decoded)
+        .expect("valid gzip");
+    assert_eq!(decoded, data);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn text_is_brotli_compressed_when_preferred() {
+    let (_dir, data, addr, server, handles) = start_text();
+
+    let (status, headers, body) = http_request(
+        addr,
+        "GET",
+        "/notes.txt",
+        &[("Accept-Encoding", "gzip, br")],
+    );
+    assert_eq!(status, 200);
+    assert_eq!(
+        headers.get("content-encoding").map(String::as_str),
+        Some("br")
+    );
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(&body[..], 4096)
+        .read_to_end(&mut decoded)
+        .expect("valid brotli");
+    assert_eq!(decoded, data);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn compressed_and_identity_bodies_have_distinct_etags() {
+    let (_dir, data, addr, server, handles) = start_text();
+
+    let (_status, identity, body) = http_get(addr, "/notes.txt");
+    assert_eq!(body, data);
+    assert!(!identity.contains_key("content-encoding"));
+    assert_eq!(
+        identity.get("vary").map(String::as_str),
+        Some("Accept-Encoding")
+    );
+    let (_status, gzipped, _body) =
+        http_request(addr, "GET", "/notes.txt", &[("Accept-Encoding", "gzip")]);
+    assert_ne!(identity.get("etag"), gzipped.get("etag"));
+
+    // The identity tag must not validate a cached gzip body, or the reverse.
+    let identity_etag = identity.get("etag").expect("etag present");
+    let (status, _headers, _body) = http_request(
+        addr,
+        "GET",
+        "/notes.txt",
+        &[
+            ("Accept-Encoding", "gzip"),
+            ("If-None-Match", identity_etag),
+        ],
+    );
+    assert_eq!(status, 200);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn range_requests_are_never_compressed() {
+    let (_dir, data, addr, server, handles) = start_text();
+
+    let (status, headers, body) = http_request(
+        addr,
+        "GET",
+        "/notes.txt",
+        &[("Accept-Encoding", "gzip, br"), ("Range", "bytes=4-8")],
+    );
+    assert_eq!(status, 206);
+    assert!(
+        !headers.contains_key("content-encoding"),
+        "headers: {headers:?}"
+    );
+    assert_eq!(body, &data[4..=8]);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn head_ignores_range_and_sends_no_body() {
+    let (_dir, data, addr, server, handles) = start_binary();
+
+    let (status, headers, body) =
+        http_request(addr, "HEAD", "/clip.mp4", &[("Range", "bytes=0-9")]);
+    assert_eq!(status, 200);
+    assert!(body.is_empty(), "HEAD must not have a body");
+    assert_eq!(
+        headers.get("content-length").map(String::as_str),
+        Some(data.len().to_string().as_str())
+    );
+
+    stop(&server, handles);
+}
@@ -0,0 +1,423 @@
+//! End-to-end integration tests for `sirn`'s writable directory mode.
+//!
+//! Each test starts its own writable directory-mode server on `127.0.0.1:0`
+//! rooted at a unique, canonicalized `tempfile::TempDir`, so the suite is
+//! parallel-safe. Bodies stay small so `tiny_http` reads them in full before a
+//! handler runs, even when the handler refuses the upload without reading it.
+
+mod common;
+use common::{http_get, http_request, http_send, start_dir, start_writable, stop};
+
+const LIMIT: u64 = 1024;
+
+fn canonical_root() -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let root = dir.path().canonicalize().expect("canonicalize root");
+    (dir, root)
+}
+
+/// Builds a `multipart/form-data` body from `(field, filename, data)` parts.
+fn multipart(boundary: &str, parts: &[(&str, Option<&str>, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (field, filename, data) in parts {
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        let disposition = match filename {
+            Some(filename) => {
+                format!("form-data; name=\"{field}\"; filename=\"{filename}\"")
+            }
+            None => format!("form-data; name=\"{field}\""),
+        };
+        body.extend_from_slice(format!("Content-Disposition: {disposition}\r\n\r\n").as_bytes());
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    body
+}
+
+#[test]
+fn put_creates_a_file_and_refuses_to_overwrite_it() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    let (status, _headers, _body) = http_send(addr, "PUT", "/bundle.log", &[], b"first");
+    assert_eq!(status, 201);
+    assert_eq!(
+        std::fs::read(root.join("bundle.log")).expect("read"),
+        b"first"
+    );
+
+    let (status, _headers, body) = http_send(addr, "PUT", "/bundle.log", &[], b"second");
+    assert_eq!(status, 409);
+    assert!(String::from_utf8_lossy(&body).contains("overwrite=1"));
+    assert_eq!(
+        std::fs::read(root.join("bundle.log")).expect("read"),
+        b"first"
+    );
+
+    let (status, _headers, _body) =
+        http_send(addr, "PUT", "/bundle.log?overwrite=1", &[], b"second");
+    assert_eq!(status, 204);
+    assert_eq!(
+        std::fs::read(root.join("bundle.log")).expect("read"),
+        b"second"
+    );
+
+    stop(&server, handles);
+}
+
+#[test]
+fn put_into_a_subdirectory_via_percent_encoded_path() {
+    let (_dir, root) = canonical_root();
+    std::fs::create_dir(root.join("my logs")).expect("create dir");
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    let (status, _headers, _body) = http_send(addr, "PUT", "/my%20logs/caf%C3%A9.txt", &[], b"x");
+    assert_eq!(status, 201);
+    assert_eq!(
+        std::fs::read(root.join("my logs/café.txt")).expect("read"),
+        b"x"
+    );
+
+    let (status, _headers, body) = http_get(addr, "/my%20logs/caf%C3%A9.txt");
+    assert_eq!(status, 200);
+    assert_eq!(body, b"x");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn writes_cannot_escape_the_root() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    for path in ["/../escape.txt", "/%2e%2e/escape.txt", "/"] {
+        let (status, _headers, _body) = http_send(addr, "PUT", path, &[], b"x");
+        assert!(
+            status == 403 || status == 400,
+            "PUT {path} should be refused, got {status}"
+        );
+    }
+    let (status, _headers, _body) = http_request(addr, "DELETE", "/", &[]);
+    assert_eq!(status, 403);
+    assert!(root.exists(), "the root must never be deleted");
+
+    stop(&server, handles);
+}
+
+#[cfg(unix)]
+#[test]
+fn writes_cannot_follow_a_symlink_out_of_the_root() {
+    let (_dir, root) = canonical_root();
+    let outside = tempfile::TempDir::new().expect("outside temp dir");
+    std::os::unix::fs::symlink(outside.path(), root.join("link")).expect("create symlink");
+    let (addr, server, handles) = start_writable(root, LIMIT);
+
+    let (status, _headers, _body) = http_send(addr, "PUT", "/link/dropped.txt", &[], b"x");
+    assert_eq!(status, 403);
+    assert!(!outside.path().join("dropped.txt").exists());
+
+    stop(&server, handles);
+}
+
+#[test]
+fn oversized_upload_is_refused_and_leaves_nothing_behind() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), 8);
+
+    let (status, _headers, _body) = http_send(addr, "PUT", "/big.bin", &[], b"nine bytes");
+    assert_eq!(status, 413);
+    let leftovers: Vec<_> = std::fs::read_dir(&root).expect("read root").collect();
+    assert!(leftovers.is_empty(), "nothing may be kept: {leftovers:?}");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn put_to_a_missing_directory_is_a_conflict() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root, LIMIT);
+
+    let (status, _headers, _body) = http_send(addr, "PUT", "/nope/file.txt", &[], b"x");
+    assert_eq!(status, 409);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn mkcol_and_delete_manage_entries() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    let (status, _headers, _body) = http_request(addr, "MKCOL", "/logs/", &[]);
+    assert_eq!(status, 201);
+    assert!(root.join("logs").is_dir());
+    let (status, _headers, _body) = http_request(addr, "MKCOL", "/logs", &[]);
+    assert_eq!(status, 409);
+
+    std::fs::write(root.join("logs/a.txt"), b"a").expect("write a.txt");
+    let (status, _headers, _body) = http_request(addr, "DELETE", "/logs", &[]);
+    assert_eq!(status, 409, "a non-empty directory is not deleted");
+
+    let (status, _headers, _body) = http_request(addr, "DELETE", "/logs/a.txt", &[]);
+    assert_eq!(status, 204);
+    let (status, _headers, _body) = http_request(addr, "DELETE", "/logs", &[]);
+    assert_eq!(status, 204);
+    assert!(!root.join("logs").exists());
+
+    let (status, _headers, _body) = http_request(addr, "DELETE", "/logs", &[]);
+    assert_eq!(status, 404);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn multipart_post_uploads_files_and_redirects_to_the_listing() {
+    let (_dir, root) = canonical_root();
+    std::fs::create_dir(root.join("sub dir")).expect("create dir");
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    let body = multipart(
+        "BoUnDaRy",
+        &[
+            ("file", Some("one.txt"), b"first\r\nfile"),
+            ("file", Some("C:\\Users\\me\\two.txt"), b"second"),
+            // An empty file input still sends a part; it is skipped.
+            ("file", Some(""), b""),
+        ],
+    );
+    let (status, headers, _body) = http_send(
+        addr,
+        "POST",
+        "/sub%20dir",
+        &[("Content-Type", "multipart/form-data; boundary=BoUnDaRy")],
+        &body,
+    );
+    assert_eq!(status, 303);
+    assert_eq!(
+        headers.get("location").map(String::as_str),
+        Some("/sub%20dir/")
+    );
+    assert_eq!(
+        std::fs::read(root.join("sub dir/one.txt")).expect("read"),
+        b"first\r\nfile"
+    );
+    assert_eq!(
+        std::fs::read(root.join("sub dir/two.txt")).expect("read"),
+        b"second"
+    );
+
+    stop(&server, handles);
+}
+
+#[test]
+fn multipart_clash_saves_nothing_unless_overwrite_is_checked() {
+    let (_dir, root) = canonical_root();
+    std::fs::write(root.join("two.txt"), b"old").expect("write two.txt");
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+    let content_type = [("Content-Type", "multipart/form-data; boundary=b")];
+
+    let files: [(&str, Option<&str>, &[u8]); 2] = [
+        ("file", Some("one.txt"), b"new one"),
+        ("file", Some("two.txt"), b"new two"),
+    ];
+    let (status, _headers, _body) =
+        http_send(addr, "POST", "/", &content_type, &multipart("b", &files));
+    assert_eq!(status, 409);
+    assert!(!root.join("one.txt").exists(), "a clash saves nothing");
+    assert_eq!(std::fs::read(root.join("two.txt")).expect("read"), b"old");
+
+    let with_overwrite = [("overwrite", None, &b"1"[..]), files[0], files[1]];
+    let (status, _headers, _body) = http_send(
+        addr,
+        "POST",
+        "/",
+        &content_type,
+        &multipart("b", &with_overwrite),
+    );
+    assert_eq!(status, 303);
+    assert_eq!(
+        std::fs::read(root.join("one.txt")).expect("read"),
+        b"new one"
+    );
+    assert_eq!(
+        std::fs::read(root.join("two.txt")).expect("read"),
+        b"new two"
+    );
+    let leftovers = std::fs::read_dir(&root)
+        .expect("read root")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with('.'))
+        .count();
+    assert_eq!(leftovers, 0, "no staging files may remain");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn staging_file_names_cannot_be_written() {
+    let (_dir, root) = canonical_root();
+    std::fs::write(root.join(".sirn-upload-keep"), b"staged").expect("write staging file");
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+
+    let (status, _headers, _body) =
+        http_send(addr, "PUT", "/.sirn-upload-keep?overwrite=1", &[], b"x");
+    assert_eq!(status, 403);
+    let (status, _headers, _body) = http_send(addr, "DELETE", "/.sirn-upload-keep", &[], b"");
+    assert_eq!(status, 403);
+    let (status, _headers, _body) = http_send(addr, "MKCOL", "/.sirn-upload-dir", &[], b"");
+    assert_eq!(status, 403);
+
+    let form = [("Content-Type", "application/x-www-form-urlencoded")];
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &form, b"mkdir=.sirn-upload-dir");
+    assert_eq!(status, 403);
+    let (status, _headers, _body) =
+        http_send(addr, "POST", "/", &form, b"delete=.sirn-upload-keep");
+    assert_eq!(status, 403);
+
+    let content_type = [("Content-Type", "multipart/form-data; boundary=b")];
+    let files: [(&str, Option<&str>, &[u8]); 1] = [("file", Some(".sirn-upload-keep"), b"x")];
+    let (status, _headers, _body) =
+        http_send(addr, "POST", "/", &content_type, &multipart("b", &files));
+    assert_eq!(status, 403);
+
+    assert_eq!(
+        std::fs::read(root.join(".sirn-upload-keep")).expect("read"),
+        b"staged"
+    );
+    assert!(!root.join(".sirn-upload-dir").exists());
+
+    stop(&server, handles);
+}
+
+#[test]
+fn multipart_with_a_repeated_file_name_saves_nothing() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+    let content_type = [("Content-Type", "multipart/form-data; boundary=b")];
+
+    let files: [(&str, Option<&str>, &[u8]); 3] = [
+        ("file", Some("one.txt"), b"first"),
+        ("file", Some("other.txt"), b"other"),
+        ("file", Some("one.txt"), b"second"),
+    ];
+    let (status, _headers, body) =
+        http_send(addr, "POST", "/", &content_type, &multipart("b", &files));
+    assert_eq!(status, 400);
+    assert!(String::from_utf8_lossy(&body).contains("twice"));
+    let entries = std::fs::read_dir(&root).expect("read root").count();
+    assert_eq!(entries, 0, "nothing may be saved or left staged");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn form_buttons_create_and_delete() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+    let form = [("Content-Type", "application/x-www-form-urlencoded")];
+
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &form, b"mkdir=new+folder");
+    assert_eq!(status, 303);
+    assert!(root.join("new folder").is_dir());
+
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &form, b"delete=new%20folder");
+    assert_eq!(status, 303);
+    assert!(!root.join("new folder").exists());
+
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &form, b"mkdir=..");
+    assert_eq!(status, 400);
+
+    stop(&server, handles);
+}
+
+#[test]
+fn oversized_form_is_refused_with_a_response() {
+    let (_dir, root) = canonical_root();
+    // An upload limit above the form limit, so only the latter applies
+    let (addr, server, handles) = start_writable(root.clone(), 1024 * 1024);
+    let form = [("Content-Type", "application/x-www-form-urlencoded")];
+
+    // One byte over the 64 KiB a form may carry
+    let mut body = b"mkdir=".to_vec();
+    body.resize(64 * 1024 + 1, b'a');
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &form, &body);
+    assert_eq!(status, 413);
+    let leftovers: Vec<_> = std::fs::read_dir(&root).expect("read root").collect();
+    assert!(
+        leftovers.is_empty(),
+        "nothing may be created: {leftovers:?}"
+    );
+
+    stop(&server, handles);
+}
+
+#[test]
+fn forms_posted_from_another_site_are_refused() {
+    let (_dir, root) = canonical_root();
+    std::fs::create_dir(root.join("keep")).expect("create keep");
+    let (addr, server, handles) = start_writable(root.clone(), LIMIT);
+    let form = ("Content-Type", "application/x-www-form-urlencoded");
+
+    for headers in [
+        [form, ("Origin", "https://evil.example")],
+        [form, ("Sec-Fetch-Site", "cross-site")],
+        [form, ("Origin", "null")],
+    ] {
+        let (status, _headers, _body) = http_send(addr, "POST", "/", &headers, b"delete=keep");
+        assert_eq!(status, 403, "{headers:?}");
+        let (status, _headers, _body) = http_send(addr, "POST", "/", &headers, b"mkdir=planted");
+        assert_eq!(status, 403, "{headers:?}");
+    }
+    assert!(
+        root.join("keep").is_dir(),
+        "a cross-site form deletes nothing"
+    );
+    assert!(
+        !root.join("planted").exists(),
+        "a cross-site form creates nothing"
+    );
+
+    // The listing's own form, sent from the same origin, still works.
+    let same_origin = [
+        form,
+        ("Origin", "http://localhost"),
+        ("Sec-Fetch-Site", "same-origin"),
+    ];
+    let (status, _headers, _body) = http_send(addr, "POST", "/", &same_origin, b"delete=keep");
+    assert_eq!(status, 303);
+    assert!(!root.join("keep").exists());
+
+    stop(&server, handles);
+}
+
+#[test]
+fn writable_listing_shows_the_upload_form() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_writable(root, LIMIT);
+
+    let (status, _headers, body) = http_get(addr, "/");
+    assert_eq!(status, 200);
+    let html = String::from_utf8_lossy(&body);
+    assert!(html.contains("multipart/form-data"), "got:\n{html}");
+
+    stop(&server, handles);
+}
+
+#[test]
+fn read_only_directory_mode_refuses_writes() {
+    let (_dir, root) = canonical_root();
+    let (addr, server, handles) = start_dir(root.clone());
+
+    for method in ["PUT", "POST", "DELETE", "MKCOL"] {
+        let (status, headers, _body) = http_send(addr, method, "/x.txt", &[], b"x");
+        assert_eq!(status, 405, "{method} should be refused");
+        assert_eq!(headers.get("allow").map(String::as_str), Some("GET, HEAD"));
+    }
+    assert!(!root.join("x.txt").exists());
+
+    let (_status, _headers, body) = http_get(addr, "/");
+    assert!(!String::from_utf8_lossy(&body).contains("<form"));
+
+    stop(&server, handles);
+}
@@ -34,6 +34,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::git::{git_must, validate_worktree_name, WorktreeName, WORKTREE_NAME_RULE};
 use crate::green_check::{is_green, shell_quote};
+use crate::status::record_green;
 use crate::teardown::{hold_unverified_worktree, remove_unverified_worktree};
 
 /// The git query that names the root of the worktree `swt` was invoked in.
@@ -43,8 +44,11 @@ const TOPLEVEL_ARGS: [&str; 2] = ["rev-parse", "--show-toplevel"];
 /// a repository is recognizable as `swt`'s at a glance.
 const WORKTREE_SUFFIX: &str = ".swt";
 
-/// Namespace every branch `swt` creates lives under.
-const BRANCH_PREFIX: &str = "swt";
+/// Namespace every branch `swt` creates lives under. It is also how
+/// [`crate::status`] tells a subagent's worktree from the user's own: the
+/// directory can be renamed, but a branch outside this namespace was never
+/// `swt`'s to begin with.
+pub(crate) const BRANCH_PREFIX: &str = "swt";
 
 /// Radix the uniqueness token is spelled in — the Rust spelling of the
 /// original's `Date.now().toString(36)`. Base 36 is the largest radix `char`
@@ -138,6 +142,23 @@ impl UniqueToken {
         // 2^96 ms is some 10^18 times the age of the universe.
         Self(base36((millis << PID_BITS) | u128::from(pid)))
     }
+
+    /// Reads a spelled token back into the process id and millisecond timestamp
+    /// it was minted from — the inverse of [`UniqueToken::from_parts`], which
+    /// the packing's injectivity is what makes possible.
+    ///
+    /// `spelled` is a token as written into a lock file. Returns `None` for
+    /// anything that is not base 36, including the empty token an older `swt`
+    /// wrote: it names no process, and guessing one would point the user at a
+    /// stranger.
+    pub(crate) fn parts(spelled: &str) -> Option<(u32, u128)> {
+        if spelled.is_empty() {
+            return None;
+        }
+        let packed = u128::from_str_radix(spelled, TOKEN_RADIX).ok()?;
+        let pid = u32::try_from(packed & u128::from(u32::MAX)).ok()?;
+        Some((pid, packed >> PID_BITS))
+    }
 }
 
 impl fmt::Display for UniqueToken {
@@ -214,7 +235,10 @@ impl WorktreeNaming {
 /// caller can capture it cleanly. On a red check the worktree and its branch are
 /// torn down again, what that teardown actually did is reported on stderr, and
 /// the command fails.
-pub fn create(raw_name: &str) -> ExitCode {
+///
+/// `recheck` runs the green check even when HEAD's tree already passed it — see
+/// [`is_green`].
+pub fn create(raw_name: &str, recheck: bool) -> ExitCode {
     let Some(name): Option<WorktreeName> = validate_worktree_name(raw_name) else {
         eprintln!("Invalid worktree name {raw_name:?} — {WORKTREE_NAME_RULE}.");
         return ExitCode::FAILURE;
@@ -252,7 +276,10 @@ pub fn create(raw_name: &str) -> ExitCode {
     // while the tree worth verifying is the fresh one. Swapping these two
     // directories is the difference between verifying HEAD and verifying
     // whatever the user happens to have half-written.
-    let green = is_green(path, Some(&root));
+    let green = is_green(path, Some(&root), recheck);
+    // Recorded for `swt status` whichever way it went; a red worktree is torn
+    // down below, and the record goes with it.
+    record_green(path, &green);
     if !green.ok {
         // The verdict before the cleanup: why the worktree is going away matters
         // more than the fact that it did. The check's output already ends in a
@@ -455,6 +482,22 @@ mod tests {
         );
     }
 
+    // A lock holder is reported by the pid its token spells, so reading a token
+    // back has to give exactly the readings it was minted from.
+    #[test]
+    fn a_token_reads_back_as_the_pid_and_millisecond_it_was_minted_from() {
+        for (pid, millis) in [(1, 0), (4242, 1_706_651_234_567), (u32::MAX, 1)] {
+            let token = UniqueToken::from_parts(pid, millis).to_string();
+            assert_eq!(UniqueToken::parts(&token), Some((pid, millis)), "{token:?}");
+        }
+        assert_eq!(
+            UniqueToken::parts(""),
+            None,
+            "an old-format lock names nobody"
+        );
+        assert_eq!(UniqueToken::parts("not a token"), None);
+    }
+
     // The token is spliced into a branch name and a path component without any
     // further escaping, so every character it can produce has to be legal in
     // both — which is the whole reason it is spelled in base 36.
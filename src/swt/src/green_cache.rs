@@ -0,0 +1,196 @@
+//! green_cache — remembering which trees have already been verified green.
+//!
+//! A subagent that has made no commits yet has exactly the tree its parent has,
+//! and `swt merge` checks both; several subagents created from one HEAD all check
+//! the same tree at `create`. Running a multi-minute check again on a tree that
+//! passed it seconds ago proves nothing new, so [`crate::green_check::is_green`]
+//! asks here first.
+//!
+//! **What a pass is keyed by.** The tree object id of the worktree's `HEAD` and
+//! the plan text — plus, when the plan is the `.swt-check` override, the script's
+//! contents, because the plan names the script by path and a path says nothing
+//! about what the file at it does today. A worktree with *any* dirt, untracked
+//! files included, has no key at all: its `HEAD` tree is not what the check would
+//! run against, and the honest answer is to run it. Gitignored files are outside
+//! the key by design; they are build output and installed dependencies, which a
+//! check is expected to tolerate being warm.
+//!
+//! **Only passes are kept.** A cache hit can therefore only ever *skip* a check,
+//! never fail one — a flaky red stays a one-off rather than becoming the answer
+//! for that tree until someone thinks to pass `--recheck`.
+//!
+//! **Where, and why concurrency is not a problem.** One file per key under
+//! `<git-common-dir>/swt-green-cache/`, shared by every worktree of the
+//! repository like the merge lock is. An entry is written to a temporary name
+//! and renamed into place, so a reader sees either nothing or a whole entry, and
+//! two writers racing on one key write the same bytes. That matters because the
+//! re-check after a rebase runs *inside* `swt merge`'s locked region, where
+//! taking the lock again would deadlock and exiting would leak it: nothing here
+//! locks, and nothing here exits. Every failure to read or write is a cache miss.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::git::{git, worktree_dirt};
+
+/// The git query naming the tree `HEAD` points at.
+const HEAD_TREE_ARGS: [&str; 2] = ["rev-parse", "HEAD^{tree}"];
+
+/// The git query naming the git directory every worktree of a repository shares.
+const GIT_COMMON_DIR_ARGS: [&str; 2] = ["rev-parse", "--git-common-dir"];
+
+/// Directory inside the shared git directory that holds one file per pass.
+const CACHE_DIR: &str = "swt-green-cache";
+
+/// What one green check was asked to verify: where its pass would be recorded,
+/// and everything the pass depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheKey {
+    /// The file this key's pass lives in.
+    entry: PathBuf,
+    /// The inputs, spelled out in full. Stored as the entry's contents and
+    /// compared on lookup, so two inputs that hash alike can never share a pass.
+    material: String,
+}
+
+impl CacheKey {
+    /// Builds the key for running `plan` in `target`, or `None` when the check's
+    /// inputs cannot be pinned down — a dirty worktree, or a git that will not
+    /// answer — and so must not be cached.
+    ///
+    /// `override_script` is the contents of the `.swt-check` override when the
+    /// plan is that override, `None` otherwise.
+    pub(crate) fn for_check(
+        target: &Path,
+        plan: &[String],
+        override_script: Option<&[u8]>,
+    ) -> Option<Self> {
+        if !worktree_dirt(target, true).ok()?.is_empty() {
+            return None;
+        }
+        let tree = git(HEAD_TREE_ARGS, Some(target));
+        let common_dir = git(GIT_COMMON_DIR_ARGS, Some(target));
+        if !tree.ok || !common_dir.ok {
+            return None;
+        }
+        let dir = target.join(common_dir.out.trim()).join(CACHE_DIR);
+        Some(Self::new(&dir, tree.out.trim(), plan, override_script))
+    }
+
+    /// Assembles a key from inputs already gathered. `dir` is the cache
+    /// directory and `tree` the tree object id being verified.
+    fn new(dir: &Path, tree: &str, plan: &[String], override_script: Option<&[u8]>) -> Self {
+        let mut material = format!("tree {tree}\n");
+        for cmd in plan {
+            material.push_str(&format!("run {cmd}\n"));
+        }
+        if let Some(script) = override_script {
+            material.push_str("override\n");
+            material.push_str(&String::from_utf8_lossy(script));
+        }
+        // `DefaultHasher` is not stable across Rust releases. That costs nothing
+        // here: a `swt` built by a different compiler names different files, and
+        // every lookup it makes is a miss rather than a wrong answer.
+        let mut hasher = DefaultHasher::new();
+        material.hash(&mut hasher);
+        Self {
+            entry: dir.join(format!("{:016x}", hasher.finish())),
+            material,
+        }
+    }
+
+    /// Whether this exact check has passed before.
+    pub(crate) fn passed(&self) -> bool {
+        fs::read_to_string(&self.entry).is_ok_and(|stored| stored == self.material)
+    }
+
+    /// Records that this check passed. Best effort: a pass that cannot be
+    /// remembered is still a pass, and the next run simply checks again.
+    pub(crate) fn record_pass(&self) {
+        let Some(dir) = self.entry.parent() else {
+            return;
+        };
+        if fs::create_dir_all(dir).is_err() {
+            return;
+        }
+        // Named for this process, so two writers never share a temporary file,
+        // and renamed over the entry, so no reader ever sees half of one.
+        let staging = self.entry.with_extension(format!("{}.tmp", process::id()));
+        if fs::write(&staging, &self.material).is_err()
+            || fs::rename(&staging, &self.entry).is_err()
+        {
+            let _ = fs::remove_file(&staging);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CacheKey;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn plan(cmds: &[&str]) -> Vec<String> {
+        cmds.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn a_recorded_pass_is_found_again_under_the_same_inputs() {
+        let dir = TempDir::new().expect("cache temp dir");
+        let key = CacheKey::new(dir.path(), "abc", &plan(&["cargo test"]), None);
+        assert!(!key.passed(), "nothing has been recorded yet");
+        key.record_pass();
+        assert!(key.passed());
+        assert!(CacheKey::new(dir.path(), "abc", &plan(&["cargo test"]), None).passed());
+    }
+
+    // Each input on its own has to be able to turn a hit into a miss, or a
+    // changed tree, plan, or override script would be waved through on an old
+    // pass.
+    #[test]
+    fn changing_any_input_misses() {
+        let dir = TempDir::new().expect("cache temp dir");
+        let check = plan(&["./check"]);
+        CacheKey::new(dir.path(), "abc", &check, Some(b"exit 0")).record_pass();
+
+        for (tree, cmds, script) in [
+            ("abd", check.clone(), Some(&b"exit 0"[..])),
+            (
+                "abc",
+                plan(&["./check", "cargo test"]),
+                Some(&b"exit 0"[..]),
+            ),
+            ("abc", check.clone(), Some(&b"exit 1"[..])),
+            ("abc", check, None),
+        ] {
+            let key = CacheKey::new(dir.path(), tree, &cmds, script);
+            assert!(!key.passed(), "{key:?}");
+        }
+    }
+
+    #[test]
+    fn an_entry_that_does_not_spell_the_inputs_is_a_miss() {
+        let dir = TempDir::new().expect("cache temp dir");
+        let key = CacheKey::new(dir.path(), "abc", &plan(&["true"]), None);
+        fs::write(&key.entry, "tree other\n").expect("colliding entry");
+        assert!(
+            !key.passed(),
+            "a colliding hash must not borrow another pass"
+        );
+    }
+
+    #[test]
+    fn recording_leaves_no_temporary_file_behind() {
+        let dir = TempDir::new().expect("cache temp dir");
+        let key = CacheKey::new(&dir.path().join("nested"), "abc", &plan(&["true"]), None);
+        key.record_pass();
+        let names: Vec<_> = fs::read_dir(dir.path().join("nested"))
+            .expect("cache dir created on first pass")
+            .map(|entry| entry.expect("dir entry").file_name())
+            .collect();
+        assert_eq!(names.len(), 1, "{names:?}");
+    }
+}
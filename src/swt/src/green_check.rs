@@ -1,38 +1,58 @@
 //! green_check — what "green" means for a repo, and how to verify it.
 //!
 //! This module owns the whole definition of the green check: detecting which
-//! toolchains a worktree uses (pnpm / cargo / Tauri), assembling the command
-//! plan, and running it. Callers see only [`is_green`]; the detection stays
-//! hidden behind it — they never ask "is this a cargo repo?" themselves, they
-//! ask whether the worktree is green. ([`build_check_plan`] and [`pkg_scripts`]
-//! are exported for inspection and tests, [`run_plan`] so an already-built plan
-//! can be run — and asserted about — on its own, and [`shell_quote`] because
-//! `swt` prints shell command lines for humans to paste too.)
+//! toolchains a worktree uses (pnpm / go / python / cargo / Tauri), assembling
+//! the command plan, and running it. Callers see only [`is_green`]; the
+//! detection stays hidden behind it — they never ask "is this a cargo repo?"
+//! themselves, they ask whether the worktree is green. ([`build_check_plan`],
+//! [`pkg_scripts`] and [`committed_checks`] are exported for inspection and
+//! tests, [`run_plan`] so an already-built plan can be run — and asserted
+//! about — on its own, and [`shell_quote`] because `swt` prints shell command
+//! lines for humans to paste too.)
 //!
-//! The plan always runs inside the worktree being checked, never the parent:
+//! The plan always runs inside the worktree being checked, never the parent.
+//! The first of these that applies is the whole plan:
 //!
-//! - `.swt-check` at the config root — which defaults to the target, but is the
-//!   *parent* repo root when `swt` checks a fresh worktree: the escape hatch is a
-//!   gitignored, per-developer file, so it is absent from a checkout of HEAD.
-//!   Used alone if present, as a shell-quoted absolute path, still run in the
-//!   target.
-//! - Otherwise, detected from the target and run there, whichever apply,
-//!   additively (Tauri repos have both):
-//!   - `package.json` declaring at least one of typecheck/tsc/lint/test:
-//!     `pnpm install --frozen-lockfile` (only when `pnpm-lock.yaml` exists *and*
-//!     `node_modules` does not), then those checks. A `package.json` with none of
-//!     those scripts contributes nothing — the install alone verifies nothing and
-//!     must never stand in for a check.
-//!   - `Cargo.toml` at the root and/or `src-tauri/Cargo.toml`: cargo check + test
-//!     + clippy per manifest.
+//! 1. `.swt-check` at the config root — which defaults to the target, but is the
+//!    *parent* repo root when `swt` checks a fresh worktree: the escape hatch is a
+//!    gitignored, per-developer file, so it is absent from a checkout of HEAD.
+//!    Used alone if present, as a shell-quoted absolute path, still run in the
+//!    target.
+//! 2. A `[check]` table in `.swt.toml` at the target root — the committed,
+//!    fleet-wide way to say what green means for a repo detection gets wrong or
+//!    cannot see (a Makefile, say). Read from the target, not the config root:
+//!    it is committed, so the checkout being verified carries the definition it
+//!    is verified against. Its `commands` run in order, and nothing is detected
+//!    alongside them.
+//! 3. Otherwise, detected from the target and run there, whichever apply,
+//!    additively (Tauri repos have both pnpm and cargo), in this order:
+//!    - `package.json` declaring at least one of typecheck/tsc/lint/test:
+//!      `pnpm install --frozen-lockfile` (only when `pnpm-lock.yaml` exists *and*
+//!      `node_modules` does not), then those checks. A `package.json` with none of
+//!      those scripts contributes nothing — the install alone verifies nothing and
+//!      must never stand in for a check.
+//!    - `go.mod`: `go vet ./...` then `go test ./...`.
+//!    - `pyproject.toml` configuring ruff and/or pytest (`[tool.ruff]`,
+//!      `[tool.pytest]`): `ruff check .` and/or `pytest`, each under `uv run`
+//!      when `uv.lock` exists. A `pyproject.toml` configuring neither contributes
+//!      nothing, for the same reason as a checkless `package.json`.
+//!    - `Cargo.toml` at the root and/or `src-tauri/Cargo.toml`: cargo check + test
+//!      + clippy per manifest.
 //! - If nothing applies there is no plan, and the caller is expected to say so
-//!   rather than report a vacuous green.
+//!   rather than report a vacuous green. A `.swt.toml` that is present but
+//!   malformed is not "nothing applies": it is a failure naming the file, never a
+//!   silent fall-back to detection that might check something else entirely.
+//!
+//! A plan that already passed on the same clean tree is not run again unless the
+//! caller asks for a recheck; [`crate::green_cache`] owns what "the same" means.
 
 use std::collections::BTreeSet;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
 
+use crate::green_cache::CacheKey;
+
 /// The per-developer green-check override script, looked up at the config root.
 const OVERRIDE_FILE: &str = ".swt-check";
 
@@ -54,6 +74,30 @@ const PNPM_LOCKFILE: &str = "pnpm-lock.yaml";
 /// therefore that an install would be a mutation, not a setup step.
 const NODE_MODULES: &str = "node_modules";
 
+/// The committed green-check definition, looked up at the target root.
+const CONFIG_FILE: &str = ".swt.toml";
+
+/// The table in [`CONFIG_FILE`] that defines the green check.
+const CHECK_TABLE: &str = "check";
+
+/// The key in [`CHECK_TABLE`] listing the commands to run, in order.
+const CHECK_COMMANDS_KEY: &str = "commands";
+
+/// The module file whose presence marks a directory as a Go module.
+const GO_MOD: &str = "go.mod";
+
+/// The manifest whose presence marks a directory as a Python project.
+const PYPROJECT: &str = "pyproject.toml";
+
+/// The uv lockfile; its presence means the project's tools run under `uv run`.
+const UV_LOCKFILE: &str = "uv.lock";
+
+/// The `[tool.*]` table that says a Python project lints with ruff.
+const RUFF_TOOL: &str = "ruff";
+
+/// The `[tool.*]` table that says a Python project tests with pytest.
+const PYTEST_TOOL: &str = "pytest";
+
 /// The cargo manifest at the worktree root, which cargo finds on its own.
 const ROOT_CARGO_MANIFEST: &str = "Cargo.toml";
 
@@ -180,6 +224,203 @@ pub fn pkg_scripts(dir: &Path) -> BTreeSet<String> {
         .unwrap_or_default()
 }
 
+/// Reads the names of the `[tool.*]` tables a directory's `pyproject.toml`
+/// configures.
+///
+/// `dir` is a directory that may contain a `pyproject.toml`. Returns the set of
+/// tool names — empty when the file is missing, unreadable, or unparseable, for
+/// the reason [`pkg_scripts`] gives: a broken manifest configures nothing the
+/// plan builder can use.
+#[must_use]
+pub fn pyproject_tools(dir: &Path) -> BTreeSet<String> {
+    let Ok(text) = fs::read_to_string(dir.join(PYPROJECT)) else {
+        return BTreeSet::new();
+    };
+    let Ok(manifest) = text.parse::<toml::Table>() else {
+        return BTreeSet::new();
+    };
+    manifest
+        .get("tool")
+        .and_then(toml::Value::as_table)
+        .map(|tools| tools.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Reads the `[check]` table of a directory's committed `.swt.toml`.
+///
+/// `dir` is the directory to look in. Returns `Ok(None)` when there is no
+/// `.swt.toml`, or one without a `[check]` table, and `Ok(Some(commands))` for a
+/// table whose `commands` is a non-empty list of non-empty strings:
+///
+/// ```toml
+/// [check]
+/// commands = ["make lint", "make test"]
+/// ```
+///
+/// # Errors
+///
+/// Unlike a broken `package.json`, a broken `.swt.toml` is an error, returned as
+/// the message to report. The file exists for no reason other than to define
+/// the check, so one that cannot be read — invalid TOML, a misspelled key, an
+/// empty list — is a definition the author got wrong, and falling back to
+/// detection would verify something they never asked for.
+pub fn committed_checks(dir: &Path) -> Result<Option<Vec<String>>, String> {
+    let path = dir.join(CONFIG_FILE);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("cannot read {}: {err}\n", path.display())),
+    };
+    let invalid = |why: &str| format!("invalid {}: {why}\n", path.display());
+    let config = text
+        .parse::<toml::Table>()
+        .map_err(|err| invalid(err.message()))?;
+    let Some(check) = config.get(CHECK_TABLE) else {
+        return Ok(None);
+    };
+    let Some(check) = check.as_table() else {
+        return Err(invalid("[check] must be a table"));
+    };
+    if let Some(unknown) = check.keys().find(|key| *key != CHECK_COMMANDS_KEY) {
+        return Err(invalid(&format!(
+            "unknown key `{unknown}` in [check]; the only key is `{CHECK_COMMANDS_KEY}`"
+        )));
+    }
+    let commands: Option<Vec<String>> = check
+        .get(CHECK_COMMANDS_KEY)
+        .and_then(toml::Value::as_array)
+        .and_then(|commands| {
+            commands
+                .iter()
+                .map(|command| {
+                    command
+                        .as_str()
+                        .filter(|command| !command.trim().is_empty())
+                        .map(str::to_string)
+                })
+                .collect()
+        });
+    match commands {
+        // An empty list would run nothing and report green — the vacuous pass
+        // the whole module exists to refuse.
+        Some(commands) if !commands.is_empty() => Ok(Some(commands)),
+        _ => Err(invalid(
+            "[check] commands must be a non-empty list of non-empty command strings",
+        )),
+    }
+}
+
+/// The pnpm checks a directory's `package.json` declares, with the install they
+/// need in a fresh tree.
+fn pnpm_checks(target: &Path) -> Vec<String> {
+    if !target.join(PACKAGE_JSON).exists() {
+        return Vec::new();
+    }
+    let scripts = pkg_scripts(target);
+    let mut js_checks: Vec<String> = Vec::new();
+    // `tsc` is the fallback spelling, not a second check: a repo declaring
+    // both would otherwise type-check itself twice.
+    if scripts.contains(TYPECHECK_SCRIPT) {
+        js_checks.push("pnpm typecheck".to_string());
+    } else if scripts.contains(TSC_SCRIPT) {
+        js_checks.push("pnpm exec tsc --noEmit".to_string());
+    }
+    if scripts.contains(LINT_SCRIPT) {
+        js_checks.push("pnpm lint".to_string());
+    }
+    if scripts.contains(TEST_SCRIPT) {
+        js_checks.push("pnpm test --run".to_string());
+    }
+
+    // The install verifies nothing on its own — it exists only so the js
+    // checks can run in a fresh worktree, which has no node_modules. A plan
+    // of just an install would report green having checked nothing, so it
+    // rides along with the js checks or not at all.
+    //
+    // And it only rides along into a tree that is actually fresh. The green
+    // check also runs against the parent worktree the user is living in,
+    // where an install is not a read-only step: `--frozen-lockfile` prunes
+    // extraneous packages and undoes local `pnpm link`s. An existing
+    // node_modules is the tell that the dependencies are already there —
+    // nothing to set up, and something to lose — so verification inspects
+    // that tree without touching it.
+    let mut cmds: Vec<String> = Vec::new();
+    if !js_checks.is_empty() {
+        let needs_install =
+            target.join(PNPM_LOCKFILE).exists() && !target.join(NODE_MODULES).exists();
+        if needs_install {
+            cmds.push("pnpm install --frozen-lockfile".to_string());
+        }
+        cmds.append(&mut js_checks);
+    }
+    cmds
+}
+
+/// The Go checks for a module rooted at `target`. Vet runs first: it is the
+/// cheaper of the two and fails on code that would only confuse the tests.
+fn go_checks(target: &Path) -> Vec<String> {
+    if !target.join(GO_MOD).exists() {
+        return Vec::new();
+    }
+    vec!["go vet ./...".to_string(), "go test ./...".to_string()]
+}
+
+/// The Python checks a directory's `pyproject.toml` configures.
+///
+/// Only tools the project configures are run, the way only declared scripts are
+/// for `package.json`: a `pyproject.toml` is as often packaging metadata alone,
+/// and running a linter the project never chose would fail it for rules nobody
+/// agreed to. `uv run` is prefixed when a `uv.lock` says the tools live in uv's
+/// environment — and, unlike `pnpm install`, it syncs into `.venv` rather than
+/// pruning anything, so it is safe in the parent worktree too.
+fn python_checks(target: &Path) -> Vec<String> {
+    if !target.join(PYPROJECT).exists() {
+        return Vec::new();
+    }
+    let tools = pyproject_tools(target);
+    let runner = if target.join(UV_LOCKFILE).exists() {
+        "uv run "
+    } else {
+        ""
+    };
+    let mut cmds: Vec<String> = Vec::new();
+    if tools.contains(RUFF_TOOL) {
+        cmds.push(format!("{runner}ruff check ."));
+    }
+    if tools.contains(PYTEST_TOOL) {
+        cmds.push(format!("{runner}pytest"));
+    }
+    cmds
+}
+
+/// The cargo checks for every manifest `target` carries — the root one and the
+/// Tauri one, root first.
+fn cargo_checks(target: &Path) -> Vec<String> {
+    // `None` is the root manifest, which cargo finds without being told.
+    let mut manifests: Vec<Option<&str>> = Vec::new();
+    if target.join(ROOT_CARGO_MANIFEST).exists() {
+        manifests.push(None);
+    }
+    if target.join(TAURI_CARGO_MANIFEST).exists() {
+        manifests.push(Some(TAURI_CARGO_MANIFEST));
+    }
+    let mut cmds: Vec<String> = Vec::new();
+    for manifest in manifests {
+        let flag = manifest.map_or_else(String::new, |path| format!(" --manifest-path {path}"));
+        cmds.push(format!("cargo check{flag}"));
+        cmds.push(format!("cargo test{flag}"));
+        cmds.push(format!("cargo clippy{flag} -- -D warnings"));
+    }
+    cmds
+}
+
+/// Every toolchain detector, in the order their checks run. Each looks only at
+/// the target root and returns the commands it contributes — nothing when its
+/// toolchain is absent, or present but declaring no check. Supporting another
+/// toolchain is one more function here; the plan builder never changes.
+const DETECTORS: [fn(&Path) -> Vec<String>; 4] =
+    [pnpm_checks, go_checks, python_checks, cargo_checks];
+
 /// Determines the ordered list of shell commands that constitute the green check
 /// for a worktree, based on the files present at its root.
 ///
@@ -194,8 +435,15 @@ pub fn pkg_scripts(dir: &Path) -> BTreeSet<String> {
 /// somewhere else.
 ///
 /// Returns the commands to run in order, or `None` when no check applies.
-#[must_use]
-pub fn build_check_plan(target: &Path, config_root: Option<&Path>) -> Option<Vec<String>> {
+///
+/// # Errors
+///
+/// The message to report when the target's `.swt.toml` is present but cannot be
+/// used — see [`committed_checks`].
+pub fn build_check_plan(
+    target: &Path,
+    config_root: Option<&Path>,
+) -> Result<Option<Vec<String>>, String> {
     // Resolved against the config root, run in the target. The escape hatch is
     // documented as a file you *drop* at the repo root — uncommitted, and so
     // absent from the fresh checkout of HEAD that `create` checks. Looking it up
@@ -203,67 +451,15 @@ pub fn build_check_plan(target: &Path, config_root: Option<&Path>) -> Option<Vec
     // target keeps the check honest about what it is verifying.
     let override_path = config_root.unwrap_or(target).join(OVERRIDE_FILE);
     if override_path.exists() {
-        return Some(vec![shell_quote(&override_path.to_string_lossy())]);
+        return Ok(Some(vec![shell_quote(&override_path.to_string_lossy())]));
     }
 
-    let mut cmds: Vec<String> = Vec::new();
-
-    if target.join(PACKAGE_JSON).exists() {
-        let scripts = pkg_scripts(target);
-        let mut js_checks: Vec<String> = Vec::new();
-        // `tsc` is the fallback spelling, not a second check: a repo declaring
-        // both would otherwise type-check itself twice.
-        if scripts.contains(TYPECHECK_SCRIPT) {
-            js_checks.push("pnpm typecheck".to_string());
-        } else if scripts.contains(TSC_SCRIPT) {
-            js_checks.push("pnpm exec tsc --noEmit".to_string());
-        }
-        if scripts.contains(LINT_SCRIPT) {
-            js_checks.push("pnpm lint".to_string());
-        }
-        if scripts.contains(TEST_SCRIPT) {
-            js_checks.push("pnpm test --run".to_string());
-        }
-
-        // The install verifies nothing on its own — it exists only so the js
-        // checks can run in a fresh worktree, which has no node_modules. A plan
-        // of just an install would report green having checked nothing, so it
-        // rides along with the js checks or not at all.
-        //
-        // And it only rides along into a tree that is actually fresh. The green
-        // check also runs against the parent worktree the user is living in,
-        // where an install is not a read-only step: `--frozen-lockfile` prunes
-        // extraneous packages and undoes local `pnpm link`s. An existing
-        // node_modules is the tell that the dependencies are already there —
-        // nothing to set up, and something to lose — so verification inspects
-        // that tree without touching it.
-        if !js_checks.is_empty() {
-            let needs_install =
-                target.join(PNPM_LOCKFILE).exists() && !target.join(NODE_MODULES).exists();
-            if needs_install {
-                cmds.push("pnpm install --frozen-lockfile".to_string());
-            }
-            cmds.append(&mut js_checks);
-        }
-    }
-
-    // Rust checks run alongside the package.json ones — Tauri repos have both.
-    // `None` is the root manifest, which cargo finds without being told.
-    let mut manifests: Vec<Option<&str>> = Vec::new();
-    if target.join(ROOT_CARGO_MANIFEST).exists() {
-        manifests.push(None);
-    }
-    if target.join(TAURI_CARGO_MANIFEST).exists() {
-        manifests.push(Some(TAURI_CARGO_MANIFEST));
-    }
-    for manifest in manifests {
-        let flag = manifest.map_or_else(String::new, |path| format!(" --manifest-path {path}"));
-        cmds.push(format!("cargo check{flag}"));
-        cmds.push(format!("cargo test{flag}"));
-        cmds.push(format!("cargo clippy{flag} -- -D warnings"));
+    if let Some(cmds) = committed_checks(target)? {
+        return Ok(Some(cmds));
     }
 
-    (!cmds.is_empty()).then_some(cmds)
+    let cmds: Vec<String> = DETECTORS.iter().flat_map(|detect| detect(target)).collect();
+    Ok((!cmds.is_empty()).then_some(cmds))
 }
 
 /// Runs a single check command, streaming its output live so the user sees
@@ -307,13 +503,21 @@ pub fn run_plan(plan: &[String], target: &Path) -> Outcome {
 /// `target` is the worktree root to check; every command runs in this directory.
 /// `config_root` is forwarded to [`build_check_plan`] as the directory the
 /// `.swt-check` override is looked up in, `None` meaning `target` itself.
+/// `recheck` runs the plan even when this exact check already passed on this
+/// exact tree — see [`crate::green_cache`] for what "exact" covers — and is
+/// what to reach for when something outside the tree, a toolchain say, changed.
 ///
 /// Returns an ok outcome with no output when every command passed, otherwise the
 /// first failure — or, when nothing applies, a failure saying so rather than a
-/// vacuous green.
+/// vacuous green, and when the target's `.swt.toml` is broken, a failure naming
+/// it before anything runs.
 #[must_use]
-pub fn is_green(target: &Path, config_root: Option<&Path>) -> Outcome {
-    let Some(plan) = build_check_plan(target, config_root) else {
+pub fn is_green(target: &Path, config_root: Option<&Path>, recheck: bool) -> Outcome {
+    let plan = match build_check_plan(target, config_root) {
+        Ok(plan) => plan,
+        Err(why) => return Outcome::failed(why),
+    };
+    let Some(plan) = plan else {
         // Named after the config root, not the target: that is where the
         // override is looked up, so that is where dropping one would help.
         return Outcome::failed(format!(
@@ -321,13 +525,45 @@ pub fn is_green(target: &Path, config_root: Option<&Path>) -> Outcome {
             config_root.unwrap_or(target).display()
         ));
     };
+
+    let key = cache_key(target, config_root, &plan);
+    if !recheck && key.as_ref().is_some_and(CacheKey::passed) {
+        eprintln!(
+            "Green check already passed on this tree in {}; skipping (--recheck to run it).",
+            target.display()
+        );
+        return Outcome::ok();
+    }
     eprint!("Running green check in {}…", target.display());
-    run_plan(&plan, target)
+    let outcome = run_plan(&plan, target);
+    if outcome.ok {
+        if let Some(key) = key {
+            key.record_pass();
+        }
+    }
+    outcome
+}
+
+/// The cache key for running `plan` in `target`, or `None` when the check must
+/// not be cached. The plan names the override by path only, so when the override
+/// is the plan its contents join the key — and an override that exists but
+/// cannot be read leaves no key at all rather than one that ignores it.
+fn cache_key(target: &Path, config_root: Option<&Path>, plan: &[String]) -> Option<CacheKey> {
+    let override_path = config_root.unwrap_or(target).join(OVERRIDE_FILE);
+    if override_path.exists() {
+        let script = fs::read(&override_path).ok()?;
+        CacheKey::for_check(target, plan, Some(&script))
+    } else {
+        CacheKey::for_check(target, plan, None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{build_check_plan, is_green, pkg_scripts, run_plan, shell_quote, OVERRIDE_FILE};
+    use super::{
+        build_check_plan, committed_checks, is_green, pkg_scripts, pyproject_tools, run_plan,
+        shell_quote, CONFIG_FILE, OVERRIDE_FILE,
+    };
     use std::collections::BTreeSet;
     use std::fs;
     use std::path::Path;
@@ -371,9 +607,28 @@ mod tests {
         "src-tauri/Cargo.toml",
         "[package]\nname = \"fixture-tauri\"\n",
     );
+    /// A Go module.
+    const GO_MOD: (&str, &str) = ("go.mod", "module example.com/fixture\n\ngo 1.22\n");
+    /// A `pyproject.toml` configuring both ruff and pytest.
+    const PYPROJECT_RUFF_PYTEST: (&str, &str) = (
+        "pyproject.toml",
+        "[project]\nname = \"fixture\"\n\n[tool.ruff]\nline-length = 100\n\n[tool.pytest.ini_options]\ntestpaths = [\"tests\"]\n",
+    );
+    /// A `pyproject.toml` that is packaging metadata only.
+    const PYPROJECT_METADATA_ONLY: (&str, &str) =
+        ("pyproject.toml", "[project]\nname = \"fixture\"\n");
+    /// A uv lockfile; only its existence matters to the plan.
+    const UV_LOCK: (&str, &str) = ("uv.lock", "version = 1\n");
+    /// A committed check definition for a Makefile-driven repo.
+    const SWT_TOML_MAKE: (&str, &str) = (
+        CONFIG_FILE,
+        "[check]\ncommands = [\"make lint\", \"make test\"]\n",
+    );
     /// A trivial always-green override script.
     const SWT_CHECK: (&str, &str) = (OVERRIDE_FILE, "#!/bin/sh\nexit 0\n");
 
+    /// The plan a `go.mod` produces.
+    const GO_PLAN: &[&str] = &["go vet ./...", "go test ./..."];
     /// The js checks a fully-scripted `package.json` produces, without any install.
     const JS_CHECKS: &[&str] = &["pnpm typecheck", "pnpm lint", "pnpm test --run"];
     /// The install that rides along with js checks in a fresh worktree.
@@ -419,6 +674,13 @@ mod tests {
         dir
     }
 
+    /// The plan for `target`, for a fixture whose `.swt.toml` — if it has one —
+    /// is well-formed; the malformed ones are pinned through [`committed_checks`]
+    /// and [`is_green`] instead.
+    fn plan_for(target: &Path, config_root: Option<&Path>) -> Option<Vec<String>> {
+        build_check_plan(target, config_root).expect("a usable .swt.toml, or none")
+    }
+
     /// Borrows a plan as string slices so it can be compared against a literal list.
     fn as_strs(plan: &Option<Vec<String>>) -> Option<Vec<&str>> {
         plan.as_ref()
@@ -454,7 +716,7 @@ mod tests {
 
     /// Asserts a target directory's self-configured plan equals `expected`.
     fn assert_plan(target: &Path, expected: Option<&[&str]>, why: &str) {
-        let plan = build_check_plan(target, None);
+        let plan = plan_for(target, None);
         assert_eq!(
             as_strs(&plan).as_deref(),
             expected,
@@ -552,6 +814,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_go_module_is_vetted_then_tested() {
+        assert_plan(
+            fixture(&[GO_MOD]).path(),
+            Some(GO_PLAN),
+            "vet is the cheaper check, so it runs first",
+        );
+    }
+
+    #[test]
+    fn a_pyproject_runs_only_the_tools_it_configures() {
+        assert_plan(
+            fixture(&[PYPROJECT_RUFF_PYTEST]).path(),
+            Some(&["ruff check .", "pytest"]),
+            "ruff lints before pytest tests",
+        );
+        assert_plan(
+            fixture(&[(
+                "pyproject.toml",
+                "[project]\nname = \"fixture\"\n\n[tool.pytest.ini_options]\n",
+            )])
+            .path(),
+            Some(&["pytest"]),
+            "a project that never chose ruff is not linted with it",
+        );
+        assert_plan(
+            fixture(&[PYPROJECT_METADATA_ONLY]).path(),
+            None,
+            "packaging metadata alone declares no check",
+        );
+        assert_plan(
+            fixture(&[("pyproject.toml", "[tool.ruff\n")]).path(),
+            None,
+            "an unparseable pyproject configures nothing, like a broken package.json",
+        );
+    }
+
+    #[test]
+    fn a_uv_lockfile_runs_the_python_tools_under_uv() {
+        assert_plan(
+            fixture(&[PYPROJECT_RUFF_PYTEST, UV_LOCK]).path(),
+            Some(&["uv run ruff check .", "uv run pytest"]),
+            "the tools live in uv's environment, not on the PATH",
+        );
+    }
+
+    #[test]
+    fn detected_toolchains_are_additive_in_a_fixed_order() {
+        assert_plan(
+            fixture(&[CARGO, PYPROJECT_RUFF_PYTEST, GO_MOD, FULL_PKG]).path(),
+            Some(joined(&[JS_CHECKS, GO_PLAN, &["ruff check .", "pytest"], CARGO_PLAN]).as_slice()),
+            "pnpm, go, python, then cargo",
+        );
+    }
+
+    #[test]
+    fn pyproject_tools_lists_the_tool_tables() {
+        let dir = fixture(&[PYPROJECT_RUFF_PYTEST]);
+        let expected: BTreeSet<String> = ["pytest", "ruff"]
+            .iter()
+            .map(|s| (*s).to_string())
+            .collect();
+        assert_eq!(pyproject_tools(dir.path()), expected);
+        assert_eq!(pyproject_tools(fixture(&[]).path()), BTreeSet::new());
+    }
+
+    #[test]
+    fn a_committed_check_table_replaces_detection() {
+        assert_plan(
+            fixture(&[SWT_TOML_MAKE, CARGO, GO_MOD]).path(),
+            Some(&["make lint", "make test"]),
+            "the repo said what green means; nothing is detected alongside it",
+        );
+    }
+
+    #[test]
+    fn a_swt_toml_without_a_check_table_leaves_detection_alone() {
+        assert_plan(
+            fixture(&[(CONFIG_FILE, "# nothing here yet\n"), GO_MOD]).path(),
+            Some(GO_PLAN),
+            "a file that defines no check does not stand in for one",
+        );
+    }
+
+    #[test]
+    fn the_override_beats_the_committed_check_table() {
+        let dir = fixture(&[SWT_CHECK, SWT_TOML_MAKE]);
+        let expected = quoted(&dir.path().join(OVERRIDE_FILE));
+        assert_plan(
+            dir.path(),
+            Some(&[expected.as_str()]),
+            "the per-developer escape hatch stays an escape hatch",
+        );
+    }
+
+    // Committed, so it is read from the checkout being verified: a parent whose
+    // `.swt.toml` the target does not carry must not decide the target's check.
+    #[test]
+    fn the_committed_check_table_is_read_from_the_target() {
+        let config_root = fixture(&[SWT_TOML_MAKE]);
+        let target = fixture(&[CARGO]);
+        assert_eq!(
+            as_strs(&plan_for(target.path(), Some(config_root.path()))).as_deref(),
+            Some(CARGO_PLAN),
+        );
+        let target = fixture(&[SWT_TOML_MAKE]);
+        assert_eq!(
+            as_strs(&plan_for(target.path(), Some(fixture(&[]).path()))).as_deref(),
+            Some(["make lint", "make test"].as_slice()),
+        );
+    }
+
+    #[test]
+    fn a_malformed_check_table_is_an_error_not_a_fallback() {
+        for body in [
+            "[check\n",
+            "check = \"make test\"\n",
+            "[check]\n",
+            "[check]\ncommands = []\n",
+            "[check]\ncommands = [\"make test\", \"  \"]\n",
+            "[check]\ncommands = [\"make test\", 3]\n",
+            "[check]\ncommand = [\"make test\"]\n",
+        ] {
+            let dir = fixture(&[(CONFIG_FILE, body), CARGO]);
+            let err = committed_checks(dir.path()).expect_err(body);
+            assert!(err.contains(CONFIG_FILE), "{body:?}: {err}");
+            assert!(
+                build_check_plan(dir.path(), None).is_err(),
+                "{body:?} must not fall back to the cargo checks"
+            );
+        }
+    }
+
+    #[test]
+    fn a_malformed_check_table_is_not_green_and_says_why() {
+        let dir = fixture(&[(CONFIG_FILE, "[check]\ncommands = []\n")]);
+        let outcome = is_green(dir.path(), None, false);
+        assert!(!outcome.ok, "a broken definition must never report green");
+        assert!(outcome.out.contains("non-empty"), "{}", outcome.out);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_committed_check_table_runs_in_the_target() {
+        let config_root = fixture(&[]);
+        let target = fixture(&[(
+            CONFIG_FILE,
+            &format!("[check]\ncommands = [\"{PASSES}\", \"{TOUCH_MARKER}\"]\n"),
+        )]);
+        let outcome = is_green(target.path(), Some(config_root.path()), false);
+        assert!(outcome.ok, "every committed command passed: {outcome:?}");
+        assert!(target.path().join(MARKER).exists());
+    }
+
     #[test]
     fn a_fully_scripted_package_json_maps_each_script_to_its_check() {
         assert_plan(
@@ -632,7 +1048,7 @@ mod tests {
     #[test]
     fn the_install_never_appears_without_a_js_check_to_run() {
         let dir = fixture(&[("package.json", PKG_ONLY_IRRELEVANT), LOCKFILE, CARGO]);
-        let plan = build_check_plan(dir.path(), None);
+        let plan = plan_for(dir.path(), None);
         assert_eq!(
             installs_in(&plan),
             Vec::<&str>::new(),
@@ -663,7 +1079,7 @@ mod tests {
                 files.push(NODE_MODULES);
             }
             let dir = fixture(&files);
-            let plan = build_check_plan(dir.path(), None);
+            let plan = plan_for(dir.path(), None);
             let expected: Vec<&str> = if expect_install {
                 joined(&[&[INSTALL], JS_CHECKS])
             } else {
@@ -684,12 +1100,12 @@ mod tests {
     fn node_modules_removes_the_install_and_nothing_else() {
         let files = [("package.json", PKG_TSC), LOCKFILE, CARGO];
         let fresh_dir = fixture(&files);
-        let fresh = build_check_plan(fresh_dir.path(), None);
+        let fresh = plan_for(fresh_dir.path(), None);
 
         let mut populated_files = files.to_vec();
         populated_files.push(NODE_MODULES);
         let populated_dir = fixture(&populated_files);
-        let populated = build_check_plan(populated_dir.path(), None);
+        let populated = plan_for(populated_dir.path(), None);
 
         assert_eq!(
             installs_in(&fresh),
@@ -719,8 +1135,8 @@ mod tests {
         let populated = fixture(&populated_files);
 
         assert_eq!(
-            as_strs(&build_check_plan(populated.path(), None)),
-            as_strs(&build_check_plan(bare.path(), None)),
+            as_strs(&plan_for(populated.path(), None)),
+            as_strs(&plan_for(bare.path(), None)),
             "node_modules is irrelevant without js checks"
         );
         assert_plan(
@@ -756,7 +1172,7 @@ mod tests {
     fn the_override_is_looked_up_in_the_config_root_not_the_target() {
         let config_root = fixture(&[SWT_CHECK]);
         let target = fixture(&[FULL_PKG, LOCKFILE, CARGO]);
-        let plan = build_check_plan(target.path(), Some(config_root.path()));
+        let plan = plan_for(target.path(), Some(config_root.path()));
         let expected = quoted(&config_root.path().join(OVERRIDE_FILE));
         assert_eq!(
             as_strs(&plan).as_deref(),
@@ -769,7 +1185,7 @@ mod tests {
     fn an_override_in_the_target_is_ignored_when_a_config_root_is_given() {
         let config_root = fixture(&[]);
         let target = fixture(&[SWT_CHECK, CARGO]);
-        let plan = build_check_plan(target.path(), Some(config_root.path()));
+        let plan = plan_for(target.path(), Some(config_root.path()));
         assert_eq!(
             as_strs(&plan).as_deref(),
             Some(CARGO_PLAN),
@@ -782,7 +1198,7 @@ mod tests {
         let config_root = fixture(&[]);
         let target = fixture(&[]);
         assert_eq!(
-            build_check_plan(target.path(), Some(config_root.path())),
+            plan_for(target.path(), Some(config_root.path())),
             None,
             "a missing override does not invent a check"
         );
@@ -805,8 +1221,7 @@ mod tests {
         fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("make executable");
 
         let target = fixture(&[CARGO]);
-        let plan =
-            build_check_plan(target.path(), Some(&config_root)).expect("override yields a plan");
+        let plan = plan_for(target.path(), Some(&config_root)).expect("override yields a plan");
         assert_eq!(plan, vec![quoted(&script)]);
 
         let status = Command::new("sh")
@@ -866,7 +1281,7 @@ mod tests {
     fn no_plan_is_not_green_and_points_at_the_config_root() {
         let config_root = fixture(&[]);
         let target = fixture(&[]);
-        let outcome = is_green(target.path(), Some(config_root.path()));
+        let outcome = is_green(target.path(), Some(config_root.path()), false);
         assert!(!outcome.ok, "an undefined check must never report green");
         assert_eq!(
             outcome.out,
@@ -885,7 +1300,7 @@ mod tests {
     #[test]
     fn no_plan_and_no_config_root_points_at_the_target() {
         let target = fixture(&[]);
-        let outcome = is_green(target.path(), None);
+        let outcome = is_green(target.path(), None, false);
         assert!(!outcome.ok);
         assert_eq!(
             outcome.out,
@@ -902,7 +1317,7 @@ mod tests {
     fn an_override_that_exits_zero_is_green_with_no_output() {
         let dir = fixture(&[]);
         executable_script(&dir.path().join(OVERRIDE_FILE), "#!/bin/sh\nexit 0\n");
-        let outcome = is_green(dir.path(), None);
+        let outcome = is_green(dir.path(), None, false);
         assert!(outcome.ok, "a passing check is green: {outcome:?}");
         assert_eq!(outcome.out, "", "a green verdict has nothing to report");
     }
@@ -913,7 +1328,7 @@ mod tests {
         let dir = fixture(&[]);
         let script = dir.path().join(OVERRIDE_FILE);
         executable_script(&script, "#!/bin/sh\nexit 3\n");
-        let outcome = is_green(dir.path(), None);
+        let outcome = is_green(dir.path(), None, false);
         assert!(!outcome.ok, "a non-zero exit is not green");
         assert_eq!(
             outcome.out,
@@ -932,7 +1347,7 @@ mod tests {
         let target = fixture(&[]);
         executable_script(&config_root.path().join(OVERRIDE_FILE), &marking_script());
 
-        let outcome = is_green(target.path(), Some(config_root.path()));
+        let outcome = is_green(target.path(), Some(config_root.path()), false);
         assert!(outcome.ok, "the override should have passed: {outcome:?}");
         assert!(
             target.path().join(MARKER).exists(),
@@ -986,7 +1401,7 @@ mod tests {
         fs::create_dir_all(&config_root).expect("weird config root");
         executable_script(&config_root.join(OVERRIDE_FILE), &marking_script());
 
-        let outcome = is_green(&target, Some(&config_root));
+        let outcome = is_green(&target, Some(&config_root), false);
         assert!(
             outcome.ok,
             "a quoted override path must survive sh -c: {outcome:?}"
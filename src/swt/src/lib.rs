@@ -9,7 +9,9 @@
 
 pub mod create;
 pub mod git;
+pub mod green_cache;
 pub mod green_check;
 pub mod lock;
 pub mod merge;
+pub mod status;
 pub mod teardown;
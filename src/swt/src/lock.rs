@@ -342,6 +342,42 @@ fn parent_lock_path(repo_root: &Path) -> PathBuf {
     repo_root.join(common_dir).join(LOCK_FILE)
 }
 
+/// Who holds a repository's merge lock right now, as far as the lock file says.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LockHolder {
+    /// The owner token written into the file, empty for a lock an older `swt`
+    /// created.
+    pub token: String,
+    /// The process id the token spells, or `None` when it spells none.
+    pub pid: Option<u32>,
+    /// How long the file has existed, judged by its mtime the same way the
+    /// staleness reap judges it; `None` when the clock cannot say.
+    pub held_for: Option<Duration>,
+}
+
+/// Reads the holder of the merge lock for the repository `repo_root` belongs to,
+/// or `None` when nobody holds it.
+///
+/// Only ever reads: a report of who is merging must not be the thing that
+/// decides a lock is stale. A lock that vanishes between the read and the stat
+/// was released in the meantime, and is reported as the free lock it now is.
+pub(crate) fn lock_holder(repo_root: &Path) -> Option<LockHolder> {
+    let lock_path = parent_lock_path(repo_root);
+    let token = String::from_utf8_lossy(&lock_owner(&lock_path)?)
+        .trim()
+        .to_string();
+    let held_for = fs::metadata(&lock_path)
+        .ok()?
+        .modified()
+        .ok()
+        .and_then(|mtime| SystemTime::now().duration_since(mtime).ok());
+    Some(LockHolder {
+        pid: UniqueToken::parts(&token).map(|(pid, _)| pid),
+        token,
+        held_for,
+    })
+}
+
 /// Runs `f` while holding a lock file, retrying until it can be created.
 ///
 /// The internal entrance every timing decision goes through, so a test can drive
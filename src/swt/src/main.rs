@@ -3,6 +3,9 @@
 //! ```text
 //! swt create <name>          → verify HEAD green, create worktree on a new branch, print path
 //! swt merge <worktree-path>  → verify subagent green, ff-merge (rebase if parent advanced), cleanup
+//!   (either with --recheck)  → run the green check even on a tree that already passed it
+//! swt list [--json]          → print the path of every subagent worktree
+//! swt status [--json]        → report each subagent's dirt, last green check, and lead; and the lock
 //! ```
 //!
 //! The guarantees the two commands enforce — worktrees are only ever created
@@ -20,6 +23,7 @@ use buildinfo::version_string;
 use clap::{Parser, Subcommand};
 use swt::create::create;
 use swt::merge::merge;
+use swt::status::{list, status};
 
 /// Command line surface of `swt`.
 ///
@@ -38,13 +42,14 @@ struct Cli {
     command: Command,
 }
 
-/// The commands `swt` accepts. Each takes exactly one argument, and neither is
-/// optional: there is nothing sensible to create without a name, and nothing to
-/// merge without a worktree.
+/// The commands `swt` accepts. `create` and `merge` each take exactly one
+/// argument, and neither is optional: there is nothing sensible to create without
+/// a name, and nothing to merge without a worktree. `list` and `status` only read,
+/// and take nothing but `--json`.
 ///
-/// Both arguments take hyphen-leading values. Neither command has options of its
-/// own beyond `--help`, so an argument that looks like a flag is a name or a
-/// path that starts with `-`, and the command that owns it has a better answer
+/// The two arguments take hyphen-leading values. Neither command has options of
+/// its own beyond `--recheck` and `--help`, so any other argument that looks like
+/// a flag is a name or a path that starts with `-`, and the command that owns it has a better answer
 /// than "unexpected argument": `create` quotes the naming rule the input broke,
 /// and `merge` reports that no such worktree exists.
 #[derive(Debug, Subcommand)]
@@ -54,18 +59,42 @@ enum Command {
         /// Name for the new worktree and its branch.
         #[arg(allow_hyphen_values = true)]
         name: String,
+        /// Run the green check even if this tree already passed it.
+        #[arg(long)]
+        recheck: bool,
     },
     /// Verify the subagent is green, merge it into the parent, and clean up.
     Merge {
         /// Path to the subagent worktree to merge back.
         #[arg(allow_hyphen_values = true)]
         worktree_path: PathBuf,
+        /// Run the green checks even on trees that already passed them.
+        #[arg(long)]
+        recheck: bool,
+    },
+    /// Print the path of every subagent worktree, one per line.
+    List {
+        /// Print a JSON array of `{path, branch, head}` objects instead.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report each subagent worktree's dirt, last green check, and commits ahead
+    /// of the parent, and who holds the merge lock.
+    Status {
+        /// Print one JSON object instead of the table.
+        #[arg(long)]
+        json: bool,
     },
 }
 
 fn main() -> ExitCode {
     match Cli::parse().command {
-        Command::Create { name } => create(&name),
-        Command::Merge { worktree_path } => merge(&worktree_path),
+        Command::Create { name, recheck } => create(&name, recheck),
+        Command::Merge {
+            worktree_path,
+            recheck,
+        } => merge(&worktree_path, recheck),
+        Command::List { json } => list(json),
+        Command::Status { json } => status(json),
     }
 }
@@ -32,6 +32,7 @@ use std::process::ExitCode;
 use crate::git::{git, git_must, worktree_dirt};
 use crate::green_check::{is_green, Outcome};
 use crate::lock::with_parent_lock;
+use crate::status::record_green;
 
 /// The git query that names the root of the worktree `swt` was invoked in.
 const TOPLEVEL_ARGS: [&str; 2] = ["rev-parse", "--show-toplevel"];
@@ -165,8 +166,15 @@ fn is_clean(cwd: &Path, side: Side) -> bool {
 ///
 /// `root` is the parent worktree, `wt` the subagent worktree, `branch` the branch
 /// checked out in `wt`, and `parent_branch` the one checked out in `root`.
+/// `recheck` is passed through to the re-check after a rebase.
 /// Returns success with the line to print, or the failure to report.
-fn merge_under_lock(root: &Path, wt: &Path, branch: &str, parent_branch: &str) -> Outcome {
+fn merge_under_lock(
+    root: &Path,
+    wt: &Path,
+    branch: &str,
+    parent_branch: &str,
+    recheck: bool,
+) -> Outcome {
     let ff = git(["merge", "--ff-only", branch], Some(root));
     if !ff.ok {
         // Not an error: the parent moving on during a subagent's work is the
@@ -186,7 +194,8 @@ fn merge_under_lock(root: &Path, wt: &Path, branch: &str, parent_branch: &str) -
                 wt.display()
             ));
         }
-        let re_green = is_green(wt, Some(root));
+        let re_green = is_green(wt, Some(root), recheck);
+        record_green(wt, &re_green);
         if !re_green.ok {
             return Outcome::failed(format!("Not green after rebase: {}", re_green.out));
         }
@@ -227,8 +236,10 @@ fn merge_under_lock(root: &Path, wt: &Path, branch: &str, parent_branch: &str) -
 /// untouched.
 ///
 /// `worktree_path` is the subagent worktree as typed on the command line,
-/// absolute or relative. Returns the status `swt` should exit with.
-pub fn merge(worktree_path: &Path) -> ExitCode {
+/// absolute or relative. `recheck` runs every green check even on a tree that
+/// already passed it — see [`is_green`]. Returns the status `swt` should exit
+/// with.
+pub fn merge(worktree_path: &Path, recheck: bool) -> ExitCode {
     let wt = absolute(worktree_path);
     let root = PathBuf::from(git_must(TOPLEVEL_ARGS, None));
 
@@ -254,7 +265,7 @@ pub fn merge(worktree_path: &Path) -> ExitCode {
     // The parent must be green: a merge must never advance it past an in-progress
     // red, which would bury the failure the user is currently looking at. This
     // mirrors the create-time invariant.
-    let parent_green = is_green(&root, None);
+    let parent_green = is_green(&root, None, recheck);
     if !parent_green.ok {
         // The check's output already ends in a newline of its own.
         eprint!("Parent worktree not green: {}", parent_green.out);
@@ -265,7 +276,8 @@ pub fn merge(worktree_path: &Path) -> ExitCode {
     // Checked in the subagent worktree, configured from the parent — the same
     // asymmetry `create` relies on, and for the same reason: the `.swt-check`
     // override is an uncommitted per-developer file that only exists in `root`.
-    let green = is_green(&wt, Some(&root));
+    let green = is_green(&wt, Some(&root), recheck);
+    record_green(&wt, &green);
     if !green.ok {
         eprint!("Subagent worktree not green: {}", green.out);
         return ExitCode::FAILURE;
@@ -277,7 +289,7 @@ pub fn merge(worktree_path: &Path) -> ExitCode {
     let parent_branch = git_must(CURRENT_BRANCH_ARGS, Some(&root));
 
     let outcome = with_parent_lock(&root, || {
-        merge_under_lock(&root, &wt, &branch, &parent_branch)
+        merge_under_lock(&root, &wt, &branch, &parent_branch, recheck)
     });
 
     // Out here, holding nothing: the lock is gone whichever way the region ended.
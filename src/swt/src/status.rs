@@ -0,0 +1,553 @@
+//! status — `swt list` and `swt status`: which subagent worktrees exist, and
+//! what state each one is in.
+//!
+//! Everything here only reads. Several subagents run at once, and the point of
+//! asking is to decide what to do next, so the answer must not change what it
+//! describes: no lock is taken, no stale lock is reaped, and no green check is
+//! run. The green column is the *last recorded* verdict rather than a fresh one
+//! for the same reason — a check can take minutes, and running one per worktree
+//! to draw a table would be the slowest possible way to find out nothing new.
+//!
+//! **Which worktrees are `swt`'s.** Those with a branch under `swt/`, the
+//! namespace `swt create` puts every branch in. The directory name is not
+//! consulted: it can be moved with `git worktree move`, while a branch outside
+//! the namespace was never `swt`'s to report on.
+//!
+//! **Where a verdict is recorded.** `swt create` and `swt merge` write the
+//! outcome of every check they run on a subagent worktree into that worktree's
+//! own git directory (`.git/worktrees/<name>/swt-green`), together with the
+//! commit it was run against. Inside the git directory it can never show up as
+//! dirt, it goes away with the worktree, and the commit is what lets a report
+//! say when a verdict describes a HEAD the subagent has since moved past.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::create::BRANCH_PREFIX;
+use crate::git::{git, git_must, worktree_dirt};
+use crate::green_check::Outcome;
+use crate::lock::{lock_holder, LockHolder};
+
+/// The git query that names the root of the worktree `swt` was invoked in.
+const TOPLEVEL_ARGS: [&str; 2] = ["rev-parse", "--show-toplevel"];
+
+/// The git query listing every worktree of the repository, machine-readably.
+const WORKTREE_LIST_ARGS: [&str; 3] = ["worktree", "list", "--porcelain"];
+
+/// The git query naming a worktree's own git directory, absolutely.
+const GIT_DIR_ARGS: [&str; 2] = ["rev-parse", "--absolute-git-dir"];
+
+/// The git query naming the commit a worktree has checked out.
+const HEAD_ARGS: [&str; 2] = ["rev-parse", "HEAD"];
+
+/// Basename of the recorded verdict, inside a worktree's git directory.
+const GREEN_RECORD_FILE: &str = "swt-green";
+
+/// The ref prefix `git worktree list --porcelain` spells a branch with.
+const HEADS_PREFIX: &str = "refs/heads/";
+
+/// One worktree as `git worktree list --porcelain` describes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeEntry {
+    /// The worktree's root directory.
+    pub path: PathBuf,
+    /// The commit checked out, or `None` for a bare entry.
+    pub head: Option<String>,
+    /// The branch checked out, without `refs/heads/`; `None` when detached.
+    pub branch: Option<String>,
+}
+
+impl WorktreeEntry {
+    /// Whether this is a subagent worktree — one on a branch under `swt/`.
+    #[must_use]
+    pub fn is_swt_owned(&self) -> bool {
+        self.branch
+            .as_deref()
+            .and_then(|branch| branch.strip_prefix(BRANCH_PREFIX))
+            .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// Parses `git worktree list --porcelain` into its entries, in git's order.
+///
+/// `text` is git's output: one attribute per line, entries separated by a blank
+/// line, each starting with `worktree <path>`. Attributes `swt` has no use for
+/// (`locked`, `prunable`, `bare`, `detached`) are skipped rather than refused, so
+/// a newer git that adds one does not blank the report.
+#[must_use]
+pub fn parse_worktree_list(text: &str) -> Vec<WorktreeEntry> {
+    let mut entries: Vec<WorktreeEntry> = Vec::new();
+    for line in text.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            entries.push(WorktreeEntry {
+                path: PathBuf::from(path),
+                head: None,
+                branch: None,
+            });
+        } else if let Some(entry) = entries.last_mut() {
+            if let Some(head) = line.strip_prefix("HEAD ") {
+                entry.head = Some(head.to_string());
+            } else if let Some(branch) = line.strip_prefix("branch ") {
+                entry.branch = Some(
+                    branch
+                        .strip_prefix(HEADS_PREFIX)
+                        .unwrap_or(branch)
+                        .to_string(),
+                );
+            }
+        }
+    }
+    entries
+}
+
+/// The last green check `swt` ran on a worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenRecord {
+    /// Whether the check passed.
+    pub ok: bool,
+    /// The commit the worktree had checked out when the check ran.
+    pub commit: String,
+    /// When the check finished, in seconds since the UNIX epoch.
+    pub at: u64,
+}
+
+impl GreenRecord {
+    /// The record as it is stored and as `--json` reports it.
+    fn to_json(&self) -> Value {
+        json!({ "ok": self.ok, "commit": self.commit, "at": self.at })
+    }
+
+    /// Reads a stored record back; `None` for anything that is not one.
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            ok: value.get("ok")?.as_bool()?,
+            commit: value.get("commit")?.as_str()?.to_string(),
+            at: value.get("at")?.as_u64()?,
+        })
+    }
+}
+
+/// Where a worktree's recorded verdict lives, or `None` when git cannot name
+/// the worktree's git directory.
+fn green_record_path(worktree: &Path) -> Option<PathBuf> {
+    let answer = git(GIT_DIR_ARGS, Some(worktree));
+    answer
+        .ok
+        .then(|| PathBuf::from(answer.out.trim()).join(GREEN_RECORD_FILE))
+}
+
+/// Records the outcome of a green check `swt` just ran on a subagent worktree,
+/// for [`status`] to report later.
+///
+/// Best effort, and silent about it: the record is a convenience for a report,
+/// and a check that passed must not turn into a failed `create` or `merge`
+/// because a note about it could not be written. It also never exits, which is
+/// what lets `merge` call it from inside its locked region.
+///
+/// `worktree` is the worktree the check ran in and `outcome` its verdict.
+pub fn record_green(worktree: &Path, outcome: &Outcome) {
+    let head = git(HEAD_ARGS, Some(worktree));
+    let Some(path) = green_record_path(worktree).filter(|_| head.ok) else {
+        return;
+    };
+    let record = GreenRecord {
+        ok: outcome.ok,
+        commit: head.out.trim().to_string(),
+        at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs()),
+    };
+    let _ = fs::write(path, record.to_json().to_string());
+}
+
+/// Reads the last verdict recorded for a worktree, or `None` when `swt` never
+/// recorded one there — or recorded something it can no longer read, which
+/// tells the user the same thing: no verdict worth trusting.
+#[must_use]
+pub fn last_green(worktree: &Path) -> Option<GreenRecord> {
+    let text = fs::read_to_string(green_record_path(worktree)?).ok()?;
+    GreenRecord::from_json(&serde_json::from_str(&text).ok()?)
+}
+
+/// Everything `swt status` reports about one subagent worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeStatus {
+    /// The worktree itself.
+    pub entry: WorktreeEntry,
+    /// Its uncommitted and untracked files as porcelain lines — the same scope
+    /// `swt merge` would refuse it for — or git's explanation when git could not
+    /// say. An empty list is clean.
+    pub dirt: Result<Vec<String>, String>,
+    /// The last recorded green check.
+    pub green: Option<GreenRecord>,
+    /// Commits on its branch that the parent's `HEAD` does not have, or `None`
+    /// when git could not count them.
+    pub ahead: Option<u64>,
+}
+
+impl WorktreeStatus {
+    /// Reads one worktree's state. `root` is the parent worktree, whose `HEAD`
+    /// the ahead count is taken against — the branch `swt merge` run from there
+    /// would merge into.
+    fn read(root: &Path, entry: WorktreeEntry) -> Self {
+        let dirt = worktree_dirt(&entry.path, true)
+            .map(|listing| listing.lines().map(ToString::to_string).collect())
+            .map_err(|failure| failure.output().trim().to_string());
+        let ahead = entry.branch.as_deref().and_then(|branch| {
+            let range = format!("HEAD..{HEADS_PREFIX}{branch}");
+            let count = git(["rev-list", "--count", range.as_str()], Some(root));
+            count.ok.then(|| count.out.trim().parse().ok()).flatten()
+        });
+        Self {
+            green: last_green(&entry.path),
+            entry,
+            dirt,
+            ahead,
+        }
+    }
+
+    /// Whether the recorded verdict was reached on the commit checked out now.
+    /// A verdict about an older commit says nothing about this one.
+    #[must_use]
+    pub fn green_is_current(&self) -> bool {
+        self.green
+            .as_ref()
+            .is_some_and(|green| self.entry.head.as_deref() == Some(green.commit.as_str()))
+    }
+
+    /// The worktree as `swt status --json` reports it.
+    fn to_json(&self) -> Value {
+        let (dirty, dirt, error) = match &self.dirt {
+            Ok(lines) => (Value::Bool(!lines.is_empty()), json!(lines), Value::Null),
+            Err(why) => (Value::Null, Value::Null, json!(why)),
+        };
+        json!({
+            "path": self.entry.path,
+            "branch": self.entry.branch,
+            "head": self.entry.head,
+            "dirty": dirty,
+            "dirt": dirt,
+            "dirt_error": error,
+            "green": self.green.as_ref().map(GreenRecord::to_json),
+            "green_is_current": self.green_is_current(),
+            "ahead": self.ahead,
+        })
+    }
+
+    /// The worktree as one row of the human-readable table.
+    fn to_row(&self) -> [String; 5] {
+        let branch = self.entry.branch.clone().unwrap_or_default();
+        let dirt = match &self.dirt {
+            Ok(lines) if lines.is_empty() => "clean".to_string(),
+            Ok(lines) => format!("{} changed", lines.len()),
+            Err(_) => "unknown".to_string(),
+        };
+        let green = match &self.green {
+            None => "unchecked".to_string(),
+            Some(green) => {
+                let verdict = if green.ok { "green" } else { "red" };
+                if self.green_is_current() {
+                    verdict.to_string()
+                } else {
+                    format!("{verdict} (stale)")
+                }
+            }
+        };
+        let ahead = self
+            .ahead
+            .map_or_else(|| "?".to_string(), |ahead| format!("+{ahead}"));
+        [
+            branch,
+            dirt,
+            green,
+            ahead,
+            self.entry.path.display().to_string(),
+        ]
+    }
+}
+
+/// The subagent worktrees of the repository `root` belongs to, in git's order.
+fn swt_worktrees(root: &Path) -> Vec<WorktreeEntry> {
+    parse_worktree_list(&git_must(WORKTREE_LIST_ARGS, Some(root)))
+        .into_iter()
+        .filter(WorktreeEntry::is_swt_owned)
+        .collect()
+}
+
+/// The lock holder as `swt status --json` reports it.
+fn lock_json(holder: Option<&LockHolder>) -> Value {
+    holder.map_or(Value::Null, |holder| {
+        json!({
+            "pid": holder.pid,
+            "token": holder.token,
+            "held_secs": holder.held_for.map(|held| held.as_secs()),
+        })
+    })
+}
+
+/// The lock holder as the first line of `swt status`.
+fn lock_line(holder: Option<&LockHolder>) -> String {
+    let Some(holder) = holder else {
+        return "merge lock: free".to_string();
+    };
+    let who = holder.pid.map_or_else(
+        || "an unknown process".to_string(),
+        |pid| format!("pid {pid}"),
+    );
+    match holder.held_for {
+        Some(held) => format!("merge lock: held by {who} for {}s", held.as_secs()),
+        None => format!("merge lock: held by {who}"),
+    }
+}
+
+/// Lays rows out as left-aligned columns two spaces apart, the last one
+/// unpadded so a long path does not drag trailing blanks behind it.
+fn table(rows: &[[String; 5]]) -> String {
+    let mut widths = [0_usize; 5];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i + 1 == row.len() {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{cell:<width$}  ", width = widths[i]));
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Lists the subagent worktrees of the repository `swt` was run in.
+///
+/// One path per line on stdout, nothing else — the same contract as `swt
+/// create`'s output, so a script can loop over it. `json` prints an array of
+/// `{"path", "branch", "head"}` objects instead. An empty list is not a failure.
+pub fn list(json: bool) -> ExitCode {
+    let root = PathBuf::from(git_must(TOPLEVEL_ARGS, None));
+    let worktrees = swt_worktrees(&root);
+    if json {
+        let entries: Vec<Value> = worktrees
+            .iter()
+            .map(|entry| json!({ "path": entry.path, "branch": entry.branch, "head": entry.head }))
+            .collect();
+        println!("{}", Value::Array(entries));
+    } else {
+        for entry in &worktrees {
+            println!("{}", entry.path.display());
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Reports every subagent worktree of the repository `swt` was run in — its
+/// dirt, its last recorded green check, how far it is ahead of the parent — and
+/// who holds the repository's merge lock.
+///
+/// `json` prints one object, `{"parent", "lock", "worktrees"}`, for an
+/// orchestrator to read instead of the table. Reading state is all this does, so
+/// it succeeds whatever that state is.
+pub fn status(json: bool) -> ExitCode {
+    let root = PathBuf::from(git_must(TOPLEVEL_ARGS, None));
+    let holder = lock_holder(&root);
+    let statuses: Vec<WorktreeStatus> = swt_worktrees(&root)
+        .into_iter()
+        .map(|entry| WorktreeStatus::read(&root, entry))
+        .collect();
+
+    if json {
+        let report = json!({
+            "parent": root,
+            "lock": lock_json(holder.as_ref()),
+            "worktrees": statuses.iter().map(WorktreeStatus::to_json).collect::<Vec<_>>(),
+        });
+        println!("{report}");
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{}", lock_line(holder.as_ref()));
+    if statuses.is_empty() {
+        println!("no subagent worktrees");
+        return ExitCode::SUCCESS;
+    }
+    let rows: Vec<[String; 5]> = statuses.iter().map(WorktreeStatus::to_row).collect();
+    print!("{}", table(&rows));
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        lock_line, parse_worktree_list, table, GreenRecord, WorktreeEntry, WorktreeStatus,
+    };
+    use crate::lock::LockHolder;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// What `git worktree list --porcelain` prints for a main worktree, a
+    /// subagent, a detached worktree, and a branch that only looks like `swt`'s.
+    const PORCELAIN: &str = "\
+worktree /repos/tools
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /repos/fix-parser-abc.swt
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/swt/fix-parser-abc
+locked
+
+worktree /repos/detached
+HEAD 3333333333333333333333333333333333333333
+detached
+
+worktree /repos/swtish
+HEAD 4444444444444444444444444444444444444444
+branch refs/heads/swtish
+";
+
+    fn entry(path: &str, head: &str, branch: Option<&str>) -> WorktreeEntry {
+        WorktreeEntry {
+            path: PathBuf::from(path),
+            head: Some(head.to_string()),
+            branch: branch.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn the_porcelain_listing_parses_into_one_entry_per_worktree() {
+        let entries = parse_worktree_list(PORCELAIN);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(
+            entries[1],
+            entry(
+                "/repos/fix-parser-abc.swt",
+                "2222222222222222222222222222222222222222",
+                Some("swt/fix-parser-abc"),
+            ),
+            "an attribute swt has no use for (`locked`) is skipped, not fatal"
+        );
+        assert_eq!(entries[2].branch, None, "a detached worktree has no branch");
+    }
+
+    #[test]
+    fn only_branches_in_the_swt_namespace_are_swt_owned() {
+        let owned: Vec<bool> = parse_worktree_list(PORCELAIN)
+            .iter()
+            .map(WorktreeEntry::is_swt_owned)
+            .collect();
+        assert_eq!(
+            owned,
+            [false, true, false, false],
+            "`swtish` shares the prefix but not the namespace"
+        );
+    }
+
+    fn status_with(head: &str, green: Option<(bool, &str)>) -> WorktreeStatus {
+        WorktreeStatus {
+            entry: entry("/repos/a.swt", head, Some("swt/a")),
+            dirt: Ok(Vec::new()),
+            green: green.map(|(ok, commit)| GreenRecord {
+                ok,
+                commit: commit.to_string(),
+                at: 0,
+            }),
+            ahead: Some(2),
+        }
+    }
+
+    // A verdict belongs to a commit. Reporting a pass on a commit the subagent
+    // has since moved past as if it described the current one is how a red
+    // worktree gets waved through.
+    #[test]
+    fn a_verdict_about_an_older_commit_is_reported_stale() {
+        assert_eq!(status_with("aaa", Some((true, "aaa"))).to_row()[2], "green");
+        assert_eq!(
+            status_with("bbb", Some((true, "aaa"))).to_row()[2],
+            "green (stale)"
+        );
+        assert_eq!(status_with("aaa", Some((false, "aaa"))).to_row()[2], "red");
+        assert_eq!(status_with("aaa", None).to_row()[2], "unchecked");
+    }
+
+    #[test]
+    fn the_json_form_carries_every_column() {
+        let mut status = status_with("aaa", Some((true, "aaa")));
+        status.dirt = Ok(vec!["?? notes.txt".to_string()]);
+        let value = status.to_json();
+        assert_eq!(value["dirty"], true);
+        assert_eq!(value["dirt"][0], "?? notes.txt");
+        assert_eq!(value["green"]["ok"], true);
+        assert_eq!(value["green_is_current"], true);
+        assert_eq!(value["ahead"], 2);
+        assert_eq!(value["branch"], "swt/a");
+
+        status.dirt = Err("fatal: not a git repository".to_string());
+        let value = status.to_json();
+        assert!(value["dirty"].is_null(), "unknown is not clean: {value}");
+        assert_eq!(value["dirt_error"], "fatal: not a git repository");
+    }
+
+    #[test]
+    fn a_green_record_round_trips_through_its_stored_form() {
+        let record = GreenRecord {
+            ok: false,
+            commit: "abc".to_string(),
+            at: 1_706_651_234,
+        };
+        assert_eq!(GreenRecord::from_json(&record.to_json()), Some(record));
+        assert_eq!(
+            GreenRecord::from_json(&serde_json::json!({"ok": true})),
+            None
+        );
+    }
+
+    #[test]
+    fn the_lock_line_names_the_holder_when_it_can() {
+        assert_eq!(lock_line(None), "merge lock: free");
+        let holder = LockHolder {
+            token: "x".to_string(),
+            pid: Some(4242),
+            held_for: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(
+            lock_line(Some(&holder)),
+            "merge lock: held by pid 4242 for 7s"
+        );
+        let old_format = LockHolder {
+            token: String::new(),
+            pid: None,
+            held_for: None,
+        };
+        assert_eq!(
+            lock_line(Some(&old_format)),
+            "merge lock: held by an unknown process"
+        );
+    }
+
+    #[test]
+    fn the_table_aligns_every_column_but_the_last() {
+        let rows = [
+            status_with("aaa", Some((true, "aaa"))).to_row(),
+            status_with("bbb", None).to_row(),
+        ];
+        let out = table(&rows);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0].find("/repos/a.swt"),
+            lines[1].find("/repos/a.swt"),
+            "{out}"
+        );
+        assert!(!out.lines().any(|line| line.ends_with(' ')), "{out:?}");
+    }
+}
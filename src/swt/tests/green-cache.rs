@@ -0,0 +1,115 @@
+//! The green-check cache end to end: a tree that already passed is not checked
+//! again, anything that changes what the check would see runs it again, and
+//! `--recheck` always runs it.
+//!
+//! Each case counts runs with a `.swt-check` that appends a line to a file
+//! beside the repository — outside every worktree, so the counting itself is
+//! never dirt and never part of a tree.
+//!
+//! Unix only: the fixtures are `sh` scripts dropped as executable `.swt-check`
+//! overrides, which is precisely how the escape hatch is documented.
+#![cfg(unix)]
+
+mod support;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use support::{run_swt, write_swt_check, TestRepo};
+
+/// Installs a `.swt-check` at the repository root that passes and appends one
+/// line to a tally beside the repository each time it runs. Returns the tally.
+fn counting_check(repo: &TestRepo) -> PathBuf {
+    let tally = repo.sibling("tally");
+    write_swt_check(
+        repo.path(),
+        &format!("#!/bin/sh\necho ran >> '{}'\n", tally.display()),
+    );
+    tally
+}
+
+/// How many times the counting check has run.
+fn runs(tally: &Path) -> usize {
+    fs::read_to_string(tally).map_or(0, |text| text.lines().count())
+}
+
+/// Runs `swt` with `args` in the repository, asserting success, and returns
+/// its stderr.
+fn create(repo: &TestRepo, args: &[&str]) -> String {
+    let out = run_swt(repo.path(), args);
+    assert!(
+        out.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8_lossy(&out.stderr).into_owned()
+}
+
+#[test]
+fn a_second_create_from_the_same_head_skips_the_check() {
+    let repo = TestRepo::new();
+    let tally = counting_check(&repo);
+
+    create(&repo, &["create", "first"]);
+    assert_eq!(runs(&tally), 1);
+    let stderr = create(&repo, &["create", "second"]);
+    assert_eq!(runs(&tally), 1, "the same tree already passed: {stderr}");
+    assert!(
+        stderr.contains("--recheck"),
+        "a skip says how to undo it: {stderr}"
+    );
+}
+
+#[test]
+fn recheck_runs_the_check_on_a_tree_that_already_passed() {
+    let repo = TestRepo::new();
+    let tally = counting_check(&repo);
+
+    create(&repo, &["create", "first"]);
+    create(&repo, &["create", "--recheck", "second"]);
+    assert_eq!(runs(&tally), 2);
+}
+
+#[test]
+fn a_new_commit_or_a_new_override_runs_the_check_again() {
+    let repo = TestRepo::new();
+    let tally = counting_check(&repo);
+    create(&repo, &["create", "first"]);
+
+    repo.commit_file("more.txt", "more\n");
+    create(&repo, &["create", "after-commit"]);
+    assert_eq!(runs(&tally), 2, "a different tree is a different check");
+
+    // Same path, same plan text, different script.
+    write_swt_check(
+        repo.path(),
+        &format!("#!/bin/sh\n# edited\necho ran >> '{}'\n", tally.display()),
+    );
+    create(&repo, &["create", "after-edit"]);
+    assert_eq!(
+        runs(&tally),
+        3,
+        "the override's contents are part of the key"
+    );
+}
+
+// A subagent merged before it committed anything still has the tree `create`
+// verified. The parent's check runs regardless: it carries the untracked
+// `.swt-check`, and a dirty worktree is never vouched for by a tree.
+#[test]
+fn merge_reuses_the_pass_create_recorded_for_an_unchanged_subagent() {
+    let repo = TestRepo::new();
+    let tally = counting_check(&repo);
+    let out = run_swt(repo.path(), &["create", "idle"]);
+    assert!(out.status.success());
+    let created = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    assert_eq!(runs(&tally), 1);
+
+    let out = run_swt(repo.path(), &["merge", &created]);
+    assert!(
+        out.status.success(),
+        "merge failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(runs(&tally), 2, "only the parent's check ran");
+}
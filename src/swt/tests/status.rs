@@ -0,0 +1,183 @@
+//! `swt list` and `swt status` end to end: which worktrees count as a
+//! subagent's, and whether what is reported about each matches what git says.
+//!
+//! Every case drives the real binary against a fixture repository, because the
+//! report is only worth anything if it agrees with the world it describes — a
+//! file the subagent left lying around, a commit it made, a check `swt` ran on
+//! it, a lock another merge is holding.
+//!
+//! Unix only: the green-check cases use an `sh` script dropped as the
+//! `.swt-check` override, the same way the other command suites do.
+#![cfg(unix)]
+
+mod support;
+
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+use serde_json::Value;
+use support::{exiting_check, git, run_swt, write_swt_check, TestRepo, TRACKED_FILE};
+
+/// Runs `swt status --json` in `cwd`, asserting success, and parses the report.
+fn status_json(cwd: &Path) -> Value {
+    let out = run_swt(cwd, &["status", "--json"]);
+    assert_success(&out);
+    serde_json::from_slice(&out.stdout).expect("status --json prints one JSON object")
+}
+
+fn assert_success(out: &Output) {
+    assert!(
+        out.status.success(),
+        "swt failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+/// The report's entry for the worktree at `path`.
+fn worktree<'a>(report: &'a Value, path: &Path) -> &'a Value {
+    report["worktrees"]
+        .as_array()
+        .expect("worktrees is an array")
+        .iter()
+        .find(|wt| wt["path"] == path.to_str().expect("utf-8 fixture path"))
+        .unwrap_or_else(|| panic!("{} missing from {report}", path.display()))
+}
+
+// A worktree the user made by hand, on their own branch, is not a subagent's:
+// listing it would invite an orchestrator to merge it.
+#[test]
+fn only_worktrees_on_swt_branches_are_listed() {
+    let repo = TestRepo::new();
+    let subagent = repo.add_worktree("listed");
+    let own = repo.sibling("own");
+    repo.git(&[
+        "worktree",
+        "add",
+        "--quiet",
+        "-b",
+        "feature",
+        own.to_str().expect("utf-8 fixture path"),
+        "HEAD",
+    ]);
+
+    let out = run_swt(repo.path(), &["list"]);
+    assert_success(&out);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        format!("{}\n", subagent.path.display()),
+        "one path per line, and only the subagent's"
+    );
+
+    let out = run_swt(repo.path(), &["list", "--json"]);
+    assert_success(&out);
+    let listed: Value = serde_json::from_slice(&out.stdout).expect("list --json is JSON");
+    assert_eq!(listed.as_array().map(Vec::len), Some(1), "{listed}");
+    assert_eq!(listed[0]["branch"], subagent.branch.as_str());
+}
+
+#[test]
+fn a_repository_without_subagents_reports_an_empty_list() {
+    let repo = TestRepo::new();
+
+    let out = run_swt(repo.path(), &["list"]);
+    assert_success(&out);
+    assert!(
+        out.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+
+    let report = status_json(repo.path());
+    assert_eq!(report["worktrees"], Value::Array(Vec::new()));
+    assert_eq!(
+        report["parent"],
+        repo.path().to_str().expect("utf-8 fixture path")
+    );
+    assert!(report["lock"].is_null(), "nobody is merging: {report}");
+}
+
+// The dirt scope is the one `swt merge` refuses a subagent for — untracked files
+// included — so a clean report means a merge will not bounce off dirt.
+#[test]
+fn status_reports_dirt_and_commits_ahead_of_the_parent() {
+    let repo = TestRepo::new();
+    let subagent = repo.add_worktree("busy");
+    fs::write(subagent.path.join("notes.txt"), "scratch\n").expect("untracked file");
+    fs::write(subagent.path.join("committed.txt"), "work\n").expect("new file");
+    git(&subagent.path, &["add", "--", "committed.txt"]);
+    git(&subagent.path, &["commit", "--quiet", "-m", "work"]);
+    fs::write(subagent.path.join(TRACKED_FILE), "edited\n").expect("tracked edit");
+
+    let report = status_json(repo.path());
+    let entry = worktree(&report, &subagent.path);
+    assert_eq!(entry["dirty"], true, "{entry}");
+    assert_eq!(entry["dirt"].as_array().map(Vec::len), Some(2), "{entry}");
+    assert_eq!(entry["ahead"], 1, "{entry}");
+    assert!(entry["green"].is_null(), "no check has run there: {entry}");
+
+    let out = run_swt(repo.path(), &["status"]);
+    assert_success(&out);
+    let table = String::from_utf8_lossy(&out.stdout);
+    let row = table
+        .lines()
+        .find(|line| line.starts_with(&subagent.branch))
+        .unwrap_or_else(|| panic!("no row for {}: {table}", subagent.branch));
+    for column in ["2 changed", "unchecked", "+1"] {
+        assert!(row.contains(column), "{column:?} missing from {row:?}");
+    }
+}
+
+// `create` records the check it ran; a commit made after it turns that record
+// into a statement about a commit that is no longer checked out.
+#[test]
+fn the_green_check_create_ran_is_reported_until_head_moves_on() {
+    let repo = TestRepo::new();
+    write_swt_check(repo.path(), &exiting_check(0));
+    let out = run_swt(repo.path(), &["create", "checked"]);
+    assert_success(&out);
+    let created = repo.sole_created_worktree("checked");
+
+    let report = status_json(repo.path());
+    let entry = worktree(&report, &created);
+    assert_eq!(entry["green"]["ok"], true, "{entry}");
+    assert_eq!(entry["green_is_current"], true, "{entry}");
+    assert_eq!(
+        entry["dirty"], false,
+        "the record must not count as dirt: {entry}"
+    );
+
+    fs::write(created.join("later.txt"), "later\n").expect("new file");
+    git(&created, &["add", "--", "later.txt"]);
+    git(&created, &["commit", "--quiet", "-m", "later"]);
+
+    let report = status_json(repo.path());
+    let entry = worktree(&report, &created);
+    assert_eq!(entry["green"]["ok"], true, "{entry}");
+    assert_eq!(entry["green_is_current"], false, "{entry}");
+}
+
+#[test]
+fn a_held_merge_lock_is_reported_with_the_pid_its_token_spells() {
+    let repo = TestRepo::new();
+    let common_dir = repo.git(&["rev-parse", "--git-common-dir"]);
+    // `1z` packs pid 71 into the low 32 bits and millisecond 0 above them.
+    let lock = repo.path().join(common_dir).join("swt.lock");
+    fs::write(&lock, "1z").expect("lock file");
+
+    let report = status_json(repo.path());
+    assert_eq!(report["lock"]["pid"], 71, "{report}");
+    assert_eq!(report["lock"]["token"], "1z", "{report}");
+    assert!(
+        lock.exists(),
+        "a report must never reap the lock it reports"
+    );
+
+    let out = run_swt(repo.path(), &["status"]);
+    assert_success(&out);
+    assert!(
+        String::from_utf8_lossy(&out.stdout).starts_with("merge lock: held by pid 71"),
+        "{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+}